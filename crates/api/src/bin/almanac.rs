@@ -3,25 +3,54 @@
 // This binary provides the primary command-line interface for running
 // the Almanac indexer with various blockchain clients and storage backends
 
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 use std::sync::Arc;
+use std::time::{Duration, UNIX_EPOCH};
 use tracing::{info, error};
 use tracing_subscriber::fmt;
 
 // Import core types
 use indexer_core::{Error, Result};
-use indexer_storage::{create_postgres_storage, postgres::migrations::PostgresMigrationManager};
+use indexer_core::service::{BoxedEventService, EventServiceWrapper};
+use indexer_core::reorg::{BlockHashReorgDetector, ReorgConfig, ReorgDetector};
+use indexer_core::types::ApiConfig;
+use indexer_storage::{create_postgres_storage, ingest::EventIngestor, postgres::migrations::PostgresMigrationManager};
+use indexer_storage::service::StorageEventService;
+
+// Import the API server and its storage-backed schema registry
+use indexer_api::{ApiServer, StorageSchemaRegistry};
+
+// Import causality storage and graph export
+use indexer_causality::{
+    CausalityEventProcessor, CausalityIndexer, CausalityIndexerConfig, CausalityStorageBackend,
+    ExportDirection, ExportFormat, ExportOptions, PostgresCausalityStorage, PostgresSmtBackend,
+    RocksCausalityStorage, DEFAULT_EXPORT_DEPTH
+};
 
 // Import blockchain clients with correct names
 use indexer_ethereum::EthereumClient;
+use indexer_ethereum::proxy::{Address, ProxyResolver};
 
 // Import service management from tools
 use indexer_tools::service::ServiceManager;
 use indexer_tools::config::{ConfigManager, Environment};
 
 // Import codegen modules
-use indexer_cosmos::codegen::{CosmosCodegenConfig, generate_contract_code as generate_cosmos_contract};
-use indexer_ethereum::codegen::{EthereumCodegenConfig, generate_contract_code as generate_ethereum_contract};
+use indexer_core::codegen::{fail_on_drift, report_drift};
+use indexer_cosmos::codegen::{
+    CosmosCodegenConfig, check_contract_code as check_cosmos_contract,
+    dump_contract_templates as dump_cosmos_templates,
+    generate_contract_code as generate_cosmos_contract,
+};
+use indexer_ethereum::codegen::{
+    EthereumCodegenConfig, check_contract_code as check_ethereum_contract,
+    dump_contract_templates as dump_ethereum_templates,
+    generate_contract_code as generate_ethereum_contract,
+    generate_proxy_contract_code as generate_ethereum_proxy_contract,
+};
+
+/// Interval at which chains are polled for new block headers
+const BLOCK_POLL_INTERVAL: Duration = Duration::from_secs(2);
 
 #[derive(Parser)]
 #[command(name = "almanac")]
@@ -32,9 +61,30 @@ struct Cli {
     command: Commands,
 }
 
+/// GraphQL limits overriding those of the configuration file
+#[derive(Args)]
+struct GraphQLArgs {
+    /// Maximum nesting depth of GraphQL queries
+    #[arg(long)]
+    graphql_max_depth: Option<usize>,
+
+    /// Maximum complexity of a GraphQL query
+    #[arg(long)]
+    graphql_max_complexity: Option<usize>,
+
+    /// Directory of persisted GraphQL queries, one `.graphql` file per query
+    #[arg(long)]
+    graphql_persisted_queries: Option<String>,
+
+    /// Only execute persisted GraphQL queries
+    #[arg(long)]
+    graphql_persisted_queries_only: bool,
+}
+
 #[derive(Subcommand)]
 enum Commands {
-    /// Start the indexer
+    /// Start the indexer, ingesting the logs of every configured chain as
+    /// an EVM chain
     Start {
         /// Configuration file path
         #[arg(short, long, default_value = "config.toml")]
@@ -43,6 +93,9 @@ enum Commands {
         /// Environment to run in
         #[arg(short, long, default_value = "development")]
         env: String,
+
+        #[command(flatten)]
+        graphql: GraphQLArgs,
     },
     /// Stop the indexer
     Stop,
@@ -70,6 +123,11 @@ enum Commands {
         #[command(subcommand)]
         command: EthereumCommands,
     },
+    /// Causality graph commands
+    Causality {
+        #[command(subcommand)]
+        command: CausalityCommands,
+    },
 }
 
 #[derive(Subcommand)]
@@ -91,11 +149,24 @@ enum CosmosCommands {
         #[arg(long)]
         namespace: Option<String>,
         /// Comma-separated list of features to generate
-        #[arg(long, default_value = "client,storage,api,migrations")]
+        #[arg(long, default_value = "client,storage,api,migrations,handlers")]
         features: String,
+        /// Directory of Handlebars templates overriding or adding generated files
+        #[arg(long)]
+        templates_dir: Option<String>,
+        /// Version prefix of the generated migration, the current time by default
+        #[arg(long)]
+        migration_version: Option<String>,
         /// Preview generated code without writing files
         #[arg(long)]
         dry_run: bool,
+        /// Write the generated files as reference Handlebars templates to
+        /// this directory instead
+        #[arg(long)]
+        dump_templates: Option<String>,
+        /// Regenerate in memory and fail if the files in the output directory differ
+        #[arg(long)]
+        check: bool,
         /// Enable verbose output
         #[arg(long, short)]
         verbose: bool,
@@ -106,11 +177,16 @@ enum CosmosCommands {
 enum EthereumCommands {
     /// Generate code for interacting with an Ethereum contract
     GenerateContract {
-        /// Path to the contract ABI JSON file
+        /// Path to the contract ABI JSON file, a Foundry or Hardhat artifact,
+        /// or a directory of artifacts
         abi_file: String,
-        /// Contract address on the chain
+        /// Contract address on the chain, optional for a directory of artifacts
         #[arg(long)]
-        address: String,
+        address: Option<String>,
+        /// ABI or artifact of an implementation behind the proxy, repeated
+        /// oldest first
+        #[arg(long)]
+        implementation_abi: Vec<String>,
         /// Chain ID where the contract is deployed
         #[arg(long)]
         chain: String,
@@ -121,17 +197,63 @@ enum EthereumCommands {
         #[arg(long)]
         namespace: Option<String>,
         /// Comma-separated list of features to generate
-        #[arg(long, default_value = "client,storage,api,migrations")]
+        #[arg(long, default_value = "client,storage,api,migrations,handlers")]
         features: String,
+        /// Directory of Handlebars templates overriding or adding generated files
+        #[arg(long)]
+        templates_dir: Option<String>,
+        /// Version prefix of the generated migration, the current time by default
+        #[arg(long)]
+        migration_version: Option<String>,
         /// Preview generated code without writing files
         #[arg(long)]
         dry_run: bool,
+        /// Write the generated files as reference Handlebars templates to
+        /// this directory instead
+        #[arg(long)]
+        dump_templates: Option<String>,
+        /// Regenerate in memory and fail if the files in the output directory differ
+        #[arg(long)]
+        check: bool,
         /// Enable verbose output
         #[arg(long, short)]
         verbose: bool,
     },
 }
 
+#[derive(Subcommand)]
+enum CausalityCommands {
+    /// Export the causality subgraph around an event for visualization
+    Export {
+        /// ID of the event to root the export at
+        event: String,
+        /// Path of a RocksDB causality store
+        #[arg(long, conflicts_with = "database_url")]
+        rocks_path: Option<String>,
+        /// PostgreSQL connection string of a causality store
+        #[arg(long)]
+        database_url: Option<String>,
+        /// Output format (dot, graphml or json)
+        #[arg(long, default_value = "dot")]
+        format: String,
+        /// Maximum number of hops from the root event
+        #[arg(long, default_value_t = DEFAULT_EXPORT_DEPTH)]
+        depth: usize,
+        /// Side of the root to follow (forward, backward or both)
+        #[arg(long, default_value = "both")]
+        direction: String,
+        /// Leave out events before this Unix timestamp
+        #[arg(long)]
+        since: Option<u64>,
+        /// Leave out events after this Unix timestamp
+        #[arg(long)]
+        until: Option<u64>,
+        /// Write the export to this file instead of stdout
+        #[arg(long, short)]
+        output: Option<String>,
+    },
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     // Initialize tracing
@@ -141,8 +263,8 @@ async fn main() -> Result<()> {
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::Start { config, env } => {
-            start_indexer(config, env).await?;
+        Commands::Start { config, env, graphql } => {
+            start_indexer(config, env, graphql).await?;
         }
         Commands::Stop => {
             stop_indexer().await?;
@@ -162,12 +284,15 @@ async fn main() -> Result<()> {
         Commands::Ethereum { command } => {
             handle_ethereum_command(command).await?;
         }
+        Commands::Causality { command } => {
+            handle_causality_command(command).await?;
+        }
     }
 
     Ok(())
 }
 
-async fn start_indexer(config_path: String, env_str: String) -> Result<()> {
+async fn start_indexer(config_path: String, env_str: String, graphql: GraphQLArgs) -> Result<()> {
     info!("Starting Almanac indexer with config: {}", config_path);
     
     // Parse environment
@@ -195,15 +320,26 @@ async fn start_indexer(config_path: String, env_str: String) -> Result<()> {
     let config = config_manager.config();
     
     // Initialize storage
-    let _storage = if let Some(postgres_url) = &config.database.postgres_url {
-        create_postgres_storage(postgres_url).await?
-    } else {
-        return Err(Error::generic("PostgreSQL URL not configured"));
-    };
+    let postgres_url = config.database.postgres_url.as_ref()
+        .ok_or_else(|| Error::generic("PostgreSQL URL not configured"))?;
+    let storage = create_postgres_storage(postgres_url).await?;
     info!("Storage initialized");
+
+    // Index the causality of ingested events in the same database, resuming
+    // from the events indexed before a restart
+    let causality_storage = PostgresCausalityStorage::connect(postgres_url, config.database.max_connections.unwrap_or(5)).await
+        .map_err(|e| Error::generic(format!("Failed to connect to causality store: {}", e)))?;
+    let smt_backend = PostgresSmtBackend::new(causality_storage.pool().clone());
+    let causality_indexer = CausalityIndexer::new(CausalityIndexerConfig::default(), smt_backend, Box::new(causality_storage))
+        .map_err(|e| Error::generic(format!("Failed to create causality indexer: {}", e)))?;
+    causality_indexer.initialize().await
+        .map_err(|e| Error::generic(format!("Failed to initialize causality indexer: {}", e)))?;
+    let causality_indexer = Arc::new(causality_indexer);
+    info!("Causality indexer initialized");
     
     // Initialize blockchain clients based on configuration
     let mut ethereum_clients = Vec::new();
+    let mut proxy_resolvers = Vec::new();
     
     // Initialize Ethereum clients
     for (chain_name, chain_config) in &config.chains {
@@ -212,7 +348,19 @@ async fn start_indexer(config_path: String, env_str: String) -> Result<()> {
         match EthereumClient::new(chain_config.chain_id.clone(), chain_config.rpc_url.clone()).await {
             Ok(client) => {
                 info!("Initialized Ethereum client for chain: {} ({})", chain_name, chain_config.chain_id);
-                ethereum_clients.push(Arc::new(client));
+                let client = Arc::new(client);
+                if !chain_config.proxy_addresses.is_empty() {
+                    // Proxies read their implementation slots through the chain's client
+                    let resolver = ProxyResolver::new(client.clone());
+                    for proxy in &chain_config.proxy_addresses {
+                        let proxy: Address = proxy.parse().map_err(|e| {
+                            Error::generic(format!("Invalid proxy address {} for chain {}: {}", proxy, chain_name, e))
+                        })?;
+                        resolver.add_proxy(proxy);
+                    }
+                    proxy_resolvers.push((chain_config.chain_id.clone(), Arc::new(resolver)));
+                }
+                ethereum_clients.push(client);
             }
             Err(e) => {
                 error!("Failed to initialize Ethereum client for chain {}: {}", chain_name, e);
@@ -222,6 +370,87 @@ async fn start_indexer(config_path: String, env_str: String) -> Result<()> {
     }
     
     info!("All blockchain clients initialized successfully");
+    
+    // Detect reorgs from the blocks of ingested events and the polled block
+    // headers, so stored events are rolled back, subscriptions rewound and
+    // WebSocket clients notified
+    let reorg_detectors: Vec<Arc<BlockHashReorgDetector>> = ethereum_clients.iter()
+        .map(|client| Arc::new(BlockHashReorgDetector::new(client.chain_id().clone(), ReorgConfig::default())))
+        .collect();
+    
+    // Serve the indexed events of every chain, keeping registered contract
+    // schemas in storage. The first chain by ID is reported as the server's
+    // own where a request names no chain.
+    let mut chain_ids: Vec<&String> = config.chains.values().map(|chain| &chain.chain_id).collect();
+    chain_ids.sort();
+    let chain_id = chain_ids.first().map(|chain_id| chain_id.to_string()).unwrap_or_default();
+    let mut storage_service = StorageEventService::new(chain_id, storage.clone());
+    for chain_id in chain_ids {
+        storage_service = storage_service.with_chain(chain_id.as_str());
+    }
+    for detector in &reorg_detectors {
+        storage_service = storage_service.with_reorg_detector(detector.clone());
+    }
+    let event_service: BoxedEventService = Arc::new(EventServiceWrapper::new(Arc::new(storage_service)));
+    let api_config = ApiConfig {
+        host: config.api.host.clone(),
+        port: config.api.port,
+        enable_graphql: true,
+        enable_rest: true,
+        enable_websocket: true,
+        rate_limit: config.api.rate_limit,
+        graphql_max_depth: graphql.graphql_max_depth.or(config.api.graphql_max_depth),
+        graphql_max_complexity: graphql.graphql_max_complexity.or(config.api.graphql_max_complexity),
+        graphql_persisted_queries: graphql.graphql_persisted_queries.or_else(|| config.api.graphql_persisted_queries.clone()),
+        graphql_persisted_queries_only: graphql.graphql_persisted_queries_only || config.api.graphql_persisted_queries_only,
+        params: Default::default(),
+    };
+    let mut api_server = ApiServer::from_config(&api_config, event_service, Arc::new(StorageSchemaRegistry::new(storage.clone())))?
+        .with_storage(storage.clone())
+        .with_cross_chain_causality(causality_indexer.cross_chain())
+        .with_violation_detector(causality_indexer.violation_detector());
+    for detector in &reorg_detectors {
+        api_server = api_server.with_reorg_detector(detector.clone());
+    }
+    api_server.start().await?;
+    info!("API server listening on {}:{}", config.api.host, config.api.port);
+    
+    // Ingest the events of every chain into storage, decoding the events of
+    // contracts with a registered schema and the logs of configured proxies
+    // with the schema of their implementation, and index their causality
+    let mut decoder = api_server.schema_decoder();
+    for (chain_id, resolver) in proxy_resolvers {
+        decoder = decoder.with_proxy_resolver(chain_id, resolver);
+    }
+    let mut ingestor = EventIngestor::new(storage)
+        .with_decoder(Arc::new(decoder))
+        .with_event_handler(Arc::new(CausalityEventProcessor::new(causality_indexer)));
+    for detector in &reorg_detectors {
+        ingestor = ingestor.with_reorg_detector(detector.clone());
+    }
+    let ingestor = Arc::new(ingestor);
+    for (client, detector) in ethereum_clients.iter().zip(&reorg_detectors) {
+        // Logs of the blocks replacing rolled back ones are fetched again
+        let mut subscription = client.subscribe_logs().await?.with_reorgs(detector.subscribe().await?);
+        let ingestor = ingestor.clone();
+        let chain_id = client.chain_id().0.clone();
+        tokio::spawn(async move {
+            match ingestor.run(&mut subscription).await {
+                Ok(handled) => info!("Event subscription for chain {} ended, {} events handled", chain_id, handled),
+                Err(e) => error!("Event ingestion for chain {} failed: {}", chain_id, e),
+            }
+        });
+    }
+    for client in ethereum_clients {
+        let ingestor = ingestor.clone();
+        tokio::spawn(async move {
+            let chain_id = client.chain_id().0.clone();
+            if let Err(e) = ingestor.track_blocks(&chain_id, client.as_ref(), BLOCK_POLL_INTERVAL).await {
+                error!("Block tracking for chain {} failed: {}", chain_id, e);
+            }
+        });
+    }
+    
     info!("Indexer started successfully");
     
     // Keep the process running
@@ -345,7 +574,11 @@ async fn handle_cosmos_command(command: CosmosCommands) -> Result<()> {
             output_dir,
             namespace,
             features,
+            templates_dir,
+            migration_version,
             dry_run,
+            dump_templates,
+            check,
             verbose,
         } => {
             // Validate contract address format
@@ -369,6 +602,8 @@ async fn handle_cosmos_command(command: CosmosCommands) -> Result<()> {
                 namespace,
                 features,
                 dry_run,
+                templates_dir,
+                migration_version,
             };
 
             if verbose {
@@ -382,10 +617,30 @@ async fn handle_cosmos_command(command: CosmosCommands) -> Result<()> {
                     println!("Namespace: {}", ns);
                 }
                 println!("Features: {}", config.features.join(", "));
+                if let Some(ref dir) = config.templates_dir {
+                    println!("Templates directory: {}", dir);
+                }
+                if let Some(ref version) = config.migration_version {
+                    println!("Migration version: {}", version);
+                }
                 println!("Dry run: {}", dry_run);
                 println!();
             }
 
+            if let Some(dir) = dump_templates {
+                println!("📝 Writing reference templates to {}", dir);
+                let templates = dump_cosmos_templates(&msg_file, config, &dir).await?;
+                println!("✅ Wrote {} reference templates", templates.len());
+                return Ok(());
+            }
+
+            if check {
+                println!("🔍 Checking generated code in {}", output_dir);
+                let drift = check_cosmos_contract(&msg_file, config).await?;
+                println!("{}", report_drift(&drift));
+                return fail_on_drift(&drift);
+            }
+
             if dry_run {
                 println!("🔍 Performing dry run - no files will be written");
             } else {
@@ -408,21 +663,40 @@ async fn handle_ethereum_command(command: EthereumCommands) -> Result<()> {
         EthereumCommands::GenerateContract {
             abi_file,
             address,
+            implementation_abi,
             chain,
             output_dir,
             namespace,
             features,
+            templates_dir,
+            migration_version,
             dry_run,
+            dump_templates,
+            check,
             verbose,
         } => {
-            // Validate contract address format
-            validate_ethereum_contract_address(&address)?;
+            let is_artifacts_dir = std::path::Path::new(&abi_file).is_dir();
+            let address = match address {
+                Some(address) => address,
+                None if is_artifacts_dir => String::new(),
+                None => return Err(Error::generic("Contract address is required")),
+            };
+
+            // Validate contract address format, artifacts may be generated without one
+            if !is_artifacts_dir || !address.is_empty() {
+                validate_ethereum_contract_address(&address)?;
+            }
 
             // Validate chain ID
             validate_ethereum_chain_id(&chain)?;
 
             // Validate ABI file exists
-            validate_ethereum_abi_file(&abi_file).await?;
+            if !is_artifacts_dir {
+                validate_ethereum_abi_file(&abi_file).await?;
+            }
+            for path in &implementation_abi {
+                validate_ethereum_abi_file(path).await?;
+            }
 
             let features = features
                 .split(',')
@@ -436,6 +710,8 @@ async fn handle_ethereum_command(command: EthereumCommands) -> Result<()> {
                 namespace,
                 features,
                 dry_run,
+                templates_dir,
+                migration_version,
             };
 
             if verbose {
@@ -449,17 +725,41 @@ async fn handle_ethereum_command(command: EthereumCommands) -> Result<()> {
                     println!("Namespace: {}", ns);
                 }
                 println!("Features: {}", config.features.join(", "));
+                if let Some(ref dir) = config.templates_dir {
+                    println!("Templates directory: {}", dir);
+                }
+                if let Some(ref version) = config.migration_version {
+                    println!("Migration version: {}", version);
+                }
                 println!("Dry run: {}", dry_run);
                 println!();
             }
 
+            if let Some(dir) = dump_templates {
+                println!("📝 Writing reference templates to {}", dir);
+                let templates = dump_ethereum_templates(&abi_file, &implementation_abi, config, &dir).await?;
+                println!("✅ Wrote {} reference templates", templates.len());
+                return Ok(());
+            }
+
+            if check {
+                println!("🔍 Checking generated code in {}", output_dir);
+                let drift = check_ethereum_contract(&abi_file, &implementation_abi, config).await?;
+                println!("{}", report_drift(&drift));
+                return fail_on_drift(&drift);
+            }
+
             if dry_run {
                 println!("🔍 Performing dry run - no files will be written");
             } else {
                 println!("🚀 Generating ethereum contract code...");
             }
 
-            generate_ethereum_contract(&abi_file, config).await?;
+            if implementation_abi.is_empty() {
+                generate_ethereum_contract(&abi_file, config).await?;
+            } else {
+                generate_ethereum_proxy_contract(&abi_file, &implementation_abi, config).await?;
+            }
 
             if !dry_run {
                 println!("✅ Code generation completed successfully!");
@@ -470,6 +770,67 @@ async fn handle_ethereum_command(command: EthereumCommands) -> Result<()> {
     Ok(())
 }
 
+async fn handle_causality_command(command: CausalityCommands) -> Result<()> {
+    match command {
+        CausalityCommands::Export {
+            event,
+            rocks_path,
+            database_url,
+            format,
+            depth,
+            direction,
+            since,
+            until,
+            output,
+        } => {
+            let format = format.parse::<ExportFormat>()
+                .map_err(|e| Error::generic(e.to_string()))?;
+            let options = ExportOptions {
+                max_depth: depth,
+                direction: direction.parse::<ExportDirection>()
+                    .map_err(|e| Error::generic(e.to_string()))?,
+                since: since.map(|secs| UNIX_EPOCH + Duration::from_secs(secs)),
+                until: until.map(|secs| UNIX_EPOCH + Duration::from_secs(secs)),
+            };
+
+            // Rebuild the tracker with the stored cross-chain references, as the
+            // HTTP export does, so links between chains are part of the subgraph
+            let cross_chain = match (rocks_path, database_url) {
+                (Some(path), _) => {
+                    RocksCausalityStorage::open(&path)
+                        .map_err(|e| Error::generic(format!("Failed to open causality store: {}", e)))?
+                        .load_cross_chain().await
+                }
+                (None, Some(url)) => {
+                    info!("Loading causality graph from {}", mask_password(&url));
+                    PostgresCausalityStorage::connect(&url, 1).await
+                        .map_err(|e| Error::generic(format!("Failed to connect to causality store: {}", e)))?
+                        .load_cross_chain().await
+                }
+                (None, None) => {
+                    return Err(Error::generic("Either --rocks-path or --database-url is required"));
+                }
+            }
+            .map_err(|e| Error::generic(format!("Failed to load causality graph: {}", e)))?;
+
+            let subgraph = cross_chain.export_subgraph(&event, &options)
+                .ok_or_else(|| Error::generic(format!("Event not found: {}", event)))?;
+            let rendered = subgraph.render(format)
+                .map_err(|e| Error::generic(format!("Failed to render causality graph: {}", e)))?;
+
+            match output {
+                Some(path) => {
+                    tokio::fs::write(&path, rendered).await
+                        .map_err(|e| Error::generic(format!("Failed to write {}: {}", path, e)))?;
+                    info!("Exported {} events and {} links to {}", subgraph.nodes.len(), subgraph.edges.len(), path);
+                }
+                None => print!("{}", rendered),
+            }
+        }
+    }
+    Ok(())
+}
+
 /// Validate cosmos contract address format
 fn validate_cosmos_contract_address(address: &str) -> Result<()> {
     if address.is_empty() {
@@ -0,0 +1,533 @@
+/// Decoding of events with the contract schemas in the registry
+///
+/// Contracts registered with `registerContractSchema` can be indexed with
+/// readable fields without generated code. EVM logs are decoded with an ABI
+/// built from the schema's events and CosmWasm events by converting their
+/// attributes to the registered field types. The fields are attached to the
+/// event on ingestion and returned as its attributes by the APIs.
+///
+/// Logs of upgradeable proxies are decoded with the schema of the
+/// implementation active when they were emitted when a [`ProxyResolver`] is
+/// set for their chain.
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::{json, Map, Value};
+
+use indexer_core::event::{EventAttribute, EventData, UnifiedEvent};
+use indexer_core::pipeline::{event_contract_address, EventDecoder};
+use indexer_core::{Error, Result};
+use indexer_ethereum::codegen::parser::{AbiParser, EthereumAbi};
+use indexer_ethereum::decode::decode_event;
+use indexer_ethereum::proxy::ProxyResolver;
+
+use crate::{ContractSchema, ContractSchemaRegistry};
+
+/// CosmWasm field types serialized as JSON strings
+const STRING_TYPES: &[&str] = &[
+    "string", "String", "Addr", "Binary", "Decimal", "Decimal256", "Timestamp",
+    "Uint64", "Uint128", "Uint256", "Uint512", "Int64", "Int128", "Int256", "Int512",
+];
+
+/// CosmWasm field types serialized as JSON numbers
+const NUMBER_TYPES: &[&str] = &[
+    "integer", "number", "u8", "u16", "u32", "u64", "i8", "i16", "i32", "i64", "f32", "f64",
+];
+
+/// Decodes events with the latest schema registered for their contract
+#[derive(Clone)]
+pub struct SchemaDecoder {
+    registry: Arc<dyn ContractSchemaRegistry + Send + Sync>,
+    /// Proxy resolvers by chain
+    proxies: HashMap<String, Arc<ProxyResolver>>,
+}
+
+impl SchemaDecoder {
+    /// Create a decoder looking up schemas in `registry`
+    pub fn new(registry: Arc<dyn ContractSchemaRegistry + Send + Sync>) -> Self {
+        Self {
+            registry,
+            proxies: HashMap::new(),
+        }
+    }
+
+    /// Resolve the implementations of the proxies tracked by `resolver` for
+    /// the logs of `chain_id`
+    ///
+    /// Events must then be decoded in chain order, so that upgrades are seen
+    /// before the logs following them.
+    pub fn with_proxy_resolver(mut self, chain_id: impl Into<String>, resolver: Arc<ProxyResolver>) -> Self {
+        self.proxies.insert(chain_id.into(), resolver);
+        self
+    }
+
+    /// Decode the fields of an event, keyed by name
+    ///
+    /// Returns `None` when no schema is registered for the emitting contract
+    /// or the schema has no matching event. EVM events are matched by their
+    /// signature hash, CosmWasm events by name. Logs of a tracked proxy are
+    /// decoded with the schema of its implementation at the log, falling back
+    /// to the proxy's own schema.
+    pub async fn decode(&self, event: &UnifiedEvent) -> Result<Option<Map<String, Value>>> {
+        let Some(address) = event_contract_address(event) else {
+            return Ok(None);
+        };
+        let implementation = match (&event.event_data, self.proxies.get(&event.chain)) {
+            (EventData::Evm { .. }, Some(resolver)) => resolver.resolve_event(event).await?,
+            _ => None,
+        };
+
+        let addresses = implementation.map(|implementation| implementation.to_string()).into_iter().chain([address]);
+        for address in addresses {
+            let Some(schema) = self.registry.get_latest_schema(&event.chain, &address).await? else {
+                continue;
+            };
+            let decoded = match &event.event_data {
+                EventData::Evm { .. } => decode_event(&schema_abi(&schema)?, event)?.map(|(_, fields)| fields),
+                EventData::Cosmos { attributes, .. } => decode_wasm_event(&schema, event, attributes)?,
+                EventData::Generic { .. } => None,
+            };
+            if decoded.is_some() {
+                return Ok(decoded);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Decode an event before it is stored, attaching its fields, and
+    /// return whether it was decoded
+    pub async fn decode_event(&self, event: &mut UnifiedEvent) -> Result<bool> {
+        event.decoded = self.decode(event).await?;
+        Ok(event.decoded.is_some())
+    }
+}
+
+/// Lets an `EventIngestor` decode events with the registered schemas before
+/// storing them
+#[async_trait]
+impl EventDecoder for SchemaDecoder {
+    async fn decode(&self, event: &UnifiedEvent) -> Result<Option<Map<String, Value>>> {
+        SchemaDecoder::decode(self, event).await
+    }
+
+    /// Drop the proxy upgrades tracked in the reorganized blocks
+    async fn rollback(&self, chain: &str, from_block: u64) -> Result<()> {
+        if let Some(resolver) = self.proxies.get(chain) {
+            resolver.rollback(from_block);
+        }
+        Ok(())
+    }
+}
+
+/// ABI of the events of a contract schema
+///
+/// Tuple fields are typed by their component types, as in
+/// `(address,uint256)[]`, and decode to objects keyed `arg{index}`.
+fn schema_abi(schema: &ContractSchema) -> Result<EthereumAbi> {
+    let mut events = Vec::with_capacity(schema.events.len());
+    for event in &schema.events {
+        let mut inputs = Vec::with_capacity(event.fields.len());
+        for field in &event.fields {
+            let mut input = abi_type(&field.type_name)?;
+            input.insert("name".to_string(), json!(field.name));
+            input.insert("indexed".to_string(), json!(field.indexed));
+            inputs.push(Value::Object(input));
+        }
+        events.push(json!({
+            "type": "event",
+            "name": event.name,
+            "inputs": inputs,
+            "anonymous": false,
+        }));
+    }
+    AbiParser::new().parse_content(&Value::Array(events).to_string())
+}
+
+/// ABI JSON of a canonical type, with tuples expanded to unnamed components
+fn abi_type(type_name: &str) -> Result<Map<String, Value>> {
+    let type_name = type_name.trim();
+    let mut abi = Map::new();
+    let Some(tuple) = type_name.strip_prefix('(') else {
+        abi.insert("type".to_string(), json!(type_name));
+        return Ok(abi);
+    };
+
+    let end = tuple.rfind(')')
+        .ok_or_else(|| Error::invalid_data(format!("Unbalanced tuple type {}", type_name)))?;
+    let components = split_components(&tuple[..end])
+        .into_iter()
+        .map(|component| abi_type(component).map(Value::Object))
+        .collect::<Result<Vec<_>>>()?;
+    abi.insert("type".to_string(), json!(format!("tuple{}", &tuple[end + 1..])));
+    abi.insert("components".to_string(), Value::Array(components));
+    Ok(abi)
+}
+
+/// Component types of a tuple, split at the commas outside nested tuples
+fn split_components(components: &str) -> Vec<&str> {
+    if components.trim().is_empty() {
+        return Vec::new();
+    }
+    let mut depth = 0;
+    let mut start = 0;
+    let mut split = Vec::new();
+    for (i, c) in components.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                split.push(&components[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    split.push(&components[start..]);
+    split
+}
+
+/// Decode the attributes of a CosmWasm event with the schema event of the
+/// same name
+///
+/// Custom events are emitted as `wasm-{name}`, the default one as `wasm`
+/// with an `action` attribute. Schema event names may keep the `wasm-`
+/// prefix. Attributes missing from the event are left out.
+fn decode_wasm_event(
+    schema: &ContractSchema,
+    event: &UnifiedEvent,
+    attributes: &[EventAttribute],
+) -> Result<Option<Map<String, Value>>> {
+    let name = match event.event_type.strip_prefix("wasm-") {
+        Some(name) => name,
+        None if event.event_type == "wasm" => match attributes.iter().find(|attribute| attribute.key == "action") {
+            Some(action) => action.value.as_str(),
+            None => return Ok(None),
+        },
+        None => return Ok(None),
+    };
+    let Some(event_schema) = schema.events.iter().find(|event| event.name.trim_start_matches("wasm-") == name) else {
+        return Ok(None);
+    };
+
+    let mut fields = Map::new();
+    for field in &event_schema.fields {
+        let Some(attribute) = attributes.iter().find(|attribute| attribute.key == field.name) else {
+            continue;
+        };
+        let value = attribute_value(&field.type_name, &attribute.value).ok_or_else(|| {
+            Error::invalid_data(format!(
+                "Invalid {} value {:?} for attribute {} of event {}",
+                field.type_name, attribute.value, field.name, event.id
+            ))
+        })?;
+        fields.insert(field.name.clone(), value);
+    }
+    Ok(Some(fields))
+}
+
+/// Value of an attribute as its type deserializes from JSON
+///
+/// cosmwasm-std integers, decimals and addresses stay strings, numbers and
+/// booleans are parsed, and other types are parsed as JSON or else kept as
+/// strings. Returns `None` for numbers and booleans that don't parse.
+fn attribute_value(type_name: &str, value: &str) -> Option<Value> {
+    match type_name {
+        "bool" | "boolean" => value.parse().ok().map(Value::Bool),
+        type_name if NUMBER_TYPES.contains(&type_name) => value.parse().ok().map(Value::Number),
+        type_name if STRING_TYPES.contains(&type_name) => Some(Value::String(value.to_string())),
+        _ => Some(serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::SystemTime;
+    use crate::{ContractSchemaVersion, EventSchema, FieldSchema, InMemorySchemaRegistry};
+    use indexer_ethereum::proxy::{Address, ContractStateReader, B256, IMPLEMENTATION_SLOT};
+
+    fn field(name: &str, type_name: &str, indexed: bool) -> FieldSchema {
+        FieldSchema {
+            name: name.to_string(),
+            type_name: type_name.to_string(),
+            indexed,
+        }
+    }
+
+    async fn decoder(chain: &str, address: &str, events: Vec<EventSchema>) -> SchemaDecoder {
+        let registry = InMemorySchemaRegistry::new();
+        registry.register_schema(ContractSchemaVersion {
+            version: "1.0.0".to_string(),
+            schema: ContractSchema {
+                chain: chain.to_string(),
+                address: address.to_string(),
+                name: "Test".to_string(),
+                events,
+                functions: Vec::new(),
+            },
+        }).await.unwrap();
+        SchemaDecoder::new(Arc::new(registry))
+    }
+
+    fn event(chain: &str, event_type: &str, event_data: EventData) -> UnifiedEvent {
+        UnifiedEvent {
+            id: "0xabc:0".to_string(),
+            chain: chain.to_string(),
+            block_number: 1,
+            block_hash: "0x01".to_string(),
+            tx_hash: "0xabc".to_string(),
+            timestamp: SystemTime::UNIX_EPOCH,
+            event_type: event_type.to_string(),
+            event_data,
+            raw_data: Vec::new(),
+            decoded: None,
+        }
+    }
+
+    fn word(value: &str) -> String {
+        format!("{:0>64}", value)
+    }
+
+    #[tokio::test]
+    async fn test_decode_evm_log() {
+        let transfer_schema = EventSchema {
+            name: "Transfer".to_string(),
+            fields: vec![
+                field("from", "address", true),
+                field("to", "address", true),
+                field("value", "uint256", false),
+                field("route", "(uint32,bool)", false),
+            ],
+        };
+        let topic0 = schema_abi(&ContractSchema {
+            chain: "1".to_string(),
+            address: String::new(),
+            name: "Test".to_string(),
+            events: vec![transfer_schema.clone()],
+            functions: Vec::new(),
+        }).unwrap().events[0].signature.clone().unwrap();
+        let decoder = decoder("1", "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48", vec![transfer_schema]).await;
+
+        let mut transfer = event("1", "log", EventData::Evm {
+            topics: vec![
+                topic0,
+                format!("0x{}", word("dac17f958d2ee523a2206206994597c13d831ec7")),
+                format!("0x{}", word("a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48")),
+            ],
+            data: format!("0x{}{}{}", word("f4240"), word("7"), word("1")),
+            address: "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48".to_string(),
+            log_index: 0,
+        });
+        assert!(decoder.decode_event(&mut transfer).await.unwrap());
+        let decoded = transfer.decoded.unwrap();
+        assert_eq!(decoded["from"], "0xdAC17F958D2ee523a2206206994597C13D831ec7");
+        assert_eq!(decoded["to"], "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48");
+        assert_eq!(decoded["value"], "1000000");
+        assert_eq!(decoded["route"], json!({"arg0": "7", "arg1": true}));
+
+        let mut unknown = event("1", "log", EventData::Evm {
+            topics: vec![format!("0x{}", word("1"))],
+            data: "0x".to_string(),
+            address: "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48".to_string(),
+            log_index: 1,
+        });
+        assert!(!decoder.decode_event(&mut unknown).await.unwrap());
+        assert_eq!(unknown.decoded, None);
+    }
+
+    /// Subscription delivering a fixed list of events
+    struct ListSubscription(std::collections::VecDeque<Box<dyn indexer_core::event::Event>>);
+
+    #[async_trait]
+    impl indexer_core::service::EventSubscription for ListSubscription {
+        async fn next(&mut self) -> Option<Box<dyn indexer_core::event::Event>> {
+            self.0.pop_front()
+        }
+
+        async fn close(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ingested_events_are_served_decoded() {
+        use indexer_core::service::{EventServiceWrapper, BoxedEventService};
+        use indexer_storage::ingest::EventIngestor;
+        use indexer_storage::rocks::{RocksConfig, RocksStorage};
+        use indexer_storage::service::StorageEventService;
+        use indexer_storage::BoxedStorage;
+        use crate::graphql::{create_schema, CausalityState, GraphQLLimits};
+        use crate::StorageSchemaRegistry;
+
+        let path = std::env::temp_dir().join("decoder_ingest_test");
+        if path.exists() {
+            std::fs::remove_dir_all(&path).unwrap();
+        }
+        let storage: BoxedStorage = Arc::new(RocksStorage::new(RocksConfig {
+            path: path.to_str().unwrap().to_string(),
+            create_if_missing: true,
+            cache_size_mb: 0,
+        }).unwrap());
+        let service: BoxedEventService = Arc::new(EventServiceWrapper::new(Arc::new(
+            StorageEventService::new("1", storage.clone()),
+        )));
+        let schema = create_schema(
+            service.clone(),
+            Arc::new(StorageSchemaRegistry::new(storage.clone())),
+            Some(storage.clone()),
+            CausalityState::default(),
+            &GraphQLLimits::default(),
+            Arc::new(indexer_core::security::RateLimiter::new(1000, std::time::Duration::from_secs(60))),
+        );
+
+        // Register the schema through the API, as a client would
+        let response = schema.execute(format!(r#"mutation {{
+            registerContractSchema(input: {{
+                chainId: "1", contractAddress: "{}", version: "1.0.0", name: "Vault",
+                events: [{{ name: "Deposit", fields: [{{ name: "amount", typeName: "uint256", indexed: false }}] }}],
+                functions: []
+            }}) {{ success error }}
+        }}"#, PROXY)).await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        assert_eq!(response.data.into_json().unwrap()["registerContractSchema"]["success"], true);
+
+        // The indexer sees the schema through its own registry over the same storage
+        let topic0 = schema_abi(&ContractSchema {
+            chain: "1".to_string(),
+            address: String::new(),
+            name: "Vault".to_string(),
+            events: vec![EventSchema {
+                name: "Deposit".to_string(),
+                fields: vec![field("amount", "uint256", false)],
+            }],
+            functions: Vec::new(),
+        }).unwrap().events[0].signature.clone().unwrap();
+        let deposit = event("1", "log", EventData::Evm {
+            topics: vec![topic0],
+            data: format!("0x{}", word("7")),
+            address: PROXY.to_string(),
+            log_index: 0,
+        });
+        let decoder = SchemaDecoder::new(Arc::new(StorageSchemaRegistry::new(storage.clone())));
+        let ingestor = EventIngestor::new(storage.clone()).with_decoder(Arc::new(decoder));
+        let mut subscription = ListSubscription(vec![Box::new(deposit) as Box<dyn indexer_core::event::Event>].into());
+        ingestor.run(&mut subscription).await.unwrap();
+
+        let events = service.get_events(vec![indexer_core::types::EventFilter::default()]).await.unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].decoded().unwrap()["amount"], "7");
+
+        let response = schema.execute("{ eventsConnection(first: 10) { edges { node { id attributes } } } }").await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        assert_eq!(
+            response.data.into_json().unwrap()["eventsConnection"]["edges"],
+            json!([{"node": {"id": "0xabc:0", "attributes": {"amount": "7"}}}]),
+        );
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    /// Proxy whose EIP-1967 slot holds `IMPLEMENTATION`
+    struct ProxyState;
+
+    const PROXY: &str = "0x1111111111111111111111111111111111111111";
+    const IMPLEMENTATION: &str = "0xAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA";
+
+    #[async_trait::async_trait]
+    impl ContractStateReader for ProxyState {
+        async fn storage_at(&self, _address: Address, slot: B256, _block: u64) -> Result<B256> {
+            Ok(if slot == IMPLEMENTATION_SLOT {
+                IMPLEMENTATION.parse::<Address>().unwrap().into_word()
+            } else {
+                B256::ZERO
+            })
+        }
+
+        async fn call(&self, to: Address, _data: &[u8], _block: u64) -> Result<Vec<u8>> {
+            Err(Error::invalid_data(format!("{} is not a beacon", to)))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_decode_proxy_log() {
+        let deposit_schema = EventSchema {
+            name: "Deposit".to_string(),
+            fields: vec![field("amount", "uint256", false)],
+        };
+        let topic0 = schema_abi(&ContractSchema {
+            chain: "1".to_string(),
+            address: String::new(),
+            name: "Test".to_string(),
+            events: vec![deposit_schema.clone()],
+            functions: Vec::new(),
+        }).unwrap().events[0].signature.clone().unwrap();
+        let deposit = || event("1", "log", EventData::Evm {
+            topics: vec![topic0.clone()],
+            data: format!("0x{}", word("7")),
+            address: PROXY.to_string(),
+            log_index: 0,
+        });
+
+        // Only the implementation has a schema
+        let decoder = decoder("1", IMPLEMENTATION, vec![deposit_schema]).await;
+        assert_eq!(decoder.decode(&deposit()).await.unwrap(), None);
+
+        let resolver = Arc::new(ProxyResolver::new(Arc::new(ProxyState)));
+        resolver.add_proxy(PROXY.parse().unwrap());
+        let decoder = decoder.with_proxy_resolver("1", resolver);
+        let decoded = decoder.decode(&deposit()).await.unwrap().unwrap();
+        assert_eq!(decoded["amount"], "7");
+    }
+
+    #[tokio::test]
+    async fn test_decode_wasm_event() {
+        let decoder = decoder("neutron-1", "neutron1contract", vec![EventSchema {
+            name: "wasm-transfer".to_string(),
+            fields: vec![
+                field("amount", "Uint128", false),
+                field("count", "integer", false),
+                field("paused", "boolean", false),
+                field("route", "Route", false),
+                field("memo", "String", false),
+            ],
+        }]).await;
+        let attribute = |key: &str, value: &str| EventAttribute {
+            key: key.to_string(),
+            value: value.to_string(),
+            index: false,
+        };
+        let cosmos = |event_type: &str, attributes: Vec<EventAttribute>| {
+            let mut attributes = attributes;
+            attributes.push(attribute("_contract_address", "neutron1contract"));
+            event("neutron-1", event_type, EventData::Cosmos { attributes, module: "wasm".to_string() })
+        };
+
+        let transfer = cosmos("wasm-transfer", vec![
+            attribute("amount", "100"),
+            attribute("count", "3"),
+            attribute("paused", "false"),
+            attribute("route", r#"{"hops":2}"#),
+        ]);
+        let decoded = decoder.decode(&transfer).await.unwrap().unwrap();
+        assert_eq!(Value::Object(decoded), json!({"amount": "100", "count": 3, "paused": false, "route": {"hops": 2}}));
+
+        let action = cosmos("wasm", vec![attribute("action", "transfer"), attribute("memo", "hi")]);
+        assert_eq!(decoder.decode(&action).await.unwrap().unwrap()["memo"], "hi");
+
+        assert!(decoder.decode(&cosmos("wasm-mint", Vec::new())).await.unwrap().is_none());
+        assert!(decoder.decode(&cosmos("wasm-transfer", vec![attribute("count", "many")])).await.is_err());
+    }
+
+    #[test]
+    fn test_abi_type() {
+        assert_eq!(Value::Object(abi_type("uint256").unwrap()), json!({"type": "uint256"}));
+        assert_eq!(
+            Value::Object(abi_type("(address,(uint8,bytes)[])[2]").unwrap()),
+            json!({"type": "tuple[2]", "components": [
+                {"type": "address"},
+                {"type": "tuple[]", "components": [{"type": "uint8"}, {"type": "bytes"}]},
+            ]})
+        );
+        assert!(abi_type("(address").is_err());
+    }
+}
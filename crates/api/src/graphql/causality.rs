@@ -0,0 +1,237 @@
+/// GraphQL types for cross-chain causal path and safety violation queries
+use std::sync::Arc;
+
+use async_graphql::{Context, Enum, SimpleObject};
+use base64::prelude::*;
+use tokio::sync::RwLock;
+
+use indexer_causality::types::hash_to_hex;
+use indexer_causality::{
+    CrossChainCausality, CrossChainPath, CrossChainReference, SafetyViolation, ViolationDetector,
+    ViolationKind, ViolationType,
+};
+
+use super::AppState;
+
+/// Cross-chain causality tracker shared with the causality indexer
+pub type SharedCrossChainCausality = Arc<RwLock<CrossChainCausality>>;
+
+/// Safety violation detector shared with the causality indexer
+pub type SharedViolationDetector = Arc<RwLock<ViolationDetector>>;
+
+/// Causality indexer state served by the API
+#[derive(Clone, Default)]
+pub struct CausalityState {
+    /// Cross-chain causality tracker for causal path queries
+    pub cross_chain: Option<SharedCrossChainCausality>,
+    /// Violation detector for safety violation queries
+    pub violations: Option<SharedViolationDetector>,
+}
+
+/// Hops allowed when a query does not set `maxDepth`
+pub(super) const DEFAULT_PATH_DEPTH: usize = 6;
+
+/// Upper bound on `maxDepth`, keeping path enumeration cheap
+pub(super) const MAX_PATH_DEPTH: usize = indexer_causality::causality::DEFAULT_MAX_PATH_DEPTH;
+
+/// Get the cross-chain causality tracker backing path queries
+pub(super) fn cross_chain_causality(ctx: &Context<'_>) -> async_graphql::Result<SharedCrossChainCausality> {
+    let state = ctx.data::<AppState>()?;
+    state.causality.cross_chain.clone()
+        .ok_or_else(|| async_graphql::Error::new("Cross-chain causality tracking is not configured"))
+}
+
+/// Get the violation detector backing safety violation queries
+pub(super) fn violation_detector(ctx: &Context<'_>) -> async_graphql::Result<SharedViolationDetector> {
+    let state = ctx.data::<AppState>()?;
+    state.causality.violations.clone()
+        .ok_or_else(|| async_graphql::Error::new("Causality tracking is not configured"))
+}
+
+/// Clamp an optional GraphQL depth argument to the allowed range
+pub(super) fn path_depth(max_depth: Option<i32>) -> usize {
+    max_depth.map_or(DEFAULT_PATH_DEPTH, |d| (d.max(1) as usize).min(MAX_PATH_DEPTH))
+}
+
+/// Reference linking an event on one chain to the event it caused on another
+#[derive(SimpleObject, Clone)]
+pub struct CausalReference {
+    /// Chain of the causing event
+    source_chain: String,
+    /// Chain of the caused event
+    target_chain: String,
+    /// Causing event ID
+    source_event: String,
+    /// Caused event ID
+    target_event: String,
+    /// Reference type (e.g. "bridge", "message")
+    ref_type: String,
+    /// Reference data (base64 encoded)
+    data: String,
+}
+
+impl From<&CrossChainReference> for CausalReference {
+    fn from(reference: &CrossChainReference) -> Self {
+        Self {
+            source_chain: reference.source_chain.0.clone(),
+            target_chain: reference.target_chain.0.clone(),
+            source_event: reference.source_event.clone(),
+            target_event: reference.target_event.clone(),
+            ref_type: reference.ref_type.clone(),
+            data: BASE64_STANDARD.encode(&reference.ref_data),
+        }
+    }
+}
+
+/// Causal path between events, possibly spanning several chains
+#[derive(SimpleObject, Clone)]
+pub struct CausalPath {
+    /// Chains the path passes through, in order
+    chains: Vec<String>,
+    /// Event IDs along the path, from cause to effect
+    events: Vec<String>,
+    /// Cross-chain references the path uses
+    references: Vec<CausalReference>,
+    /// Number of hops in the path
+    length: i32,
+}
+
+impl From<&CrossChainPath> for CausalPath {
+    fn from(path: &CrossChainPath) -> Self {
+        Self {
+            chains: path.chains.iter().map(|chain| chain.0.clone()).collect(),
+            events: path.events.clone(),
+            references: path.references.iter().map(CausalReference::from).collect(),
+            length: path.length as i32,
+        }
+    }
+}
+
+/// Kind of safety violation
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum SafetyViolationType {
+    /// A nullifier was consumed more than once
+    DoubleSpend,
+    /// Resource amounts flowing into an event differ from those flowing out
+    UnbalancedFlow,
+}
+
+impl From<SafetyViolationType> for ViolationType {
+    fn from(violation_type: SafetyViolationType) -> Self {
+        match violation_type {
+            SafetyViolationType::DoubleSpend => ViolationType::DoubleSpend,
+            SafetyViolationType::UnbalancedFlow => ViolationType::UnbalancedFlow,
+        }
+    }
+}
+
+/// Double spend or conservation violation found while indexing an event
+#[derive(SimpleObject, Clone)]
+pub struct CausalViolation {
+    /// Event that broke the property
+    event_id: String,
+    /// Chain of the offending event
+    chain: String,
+    /// Block of the offending event
+    block_number: i64,
+    /// Kind of violation
+    violation_type: SafetyViolationType,
+    /// Reused nullifier hash (hex), for double spends
+    nullifier_hash: Option<String>,
+    /// Resource the nullifier consumes (hex), for double spends
+    resource_id: Option<String>,
+    /// Event that consumed the nullifier first, for double spends
+    first_event: Option<String>,
+    /// Chain of the first consumption, for double spends
+    first_chain: Option<String>,
+    /// Resource type whose amounts differ, for unbalanced flows
+    resource_type: Option<String>,
+    /// Total amount flowing in, for unbalanced flows
+    inputs: Option<String>,
+    /// Total amount flowing out, for unbalanced flows
+    outputs: Option<String>,
+    /// When the violation was detected
+    detected_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<&SafetyViolation> for CausalViolation {
+    fn from(violation: &SafetyViolation) -> Self {
+        let mut result = Self {
+            event_id: violation.event_id.clone(),
+            chain: violation.chain_id.0.clone(),
+            block_number: violation.block_number as i64,
+            violation_type: SafetyViolationType::DoubleSpend,
+            nullifier_hash: None,
+            resource_id: None,
+            first_event: None,
+            first_chain: None,
+            resource_type: None,
+            inputs: None,
+            outputs: None,
+            detected_at: chrono::DateTime::<chrono::Utc>::from(violation.detected_at),
+        };
+
+        match &violation.kind {
+            ViolationKind::DoubleSpend { nullifier_hash, resource_id, first_event, first_chain } => {
+                result.nullifier_hash = Some(hash_to_hex(nullifier_hash));
+                result.resource_id = Some(hash_to_hex(&resource_id.inner()));
+                result.first_event = Some(first_event.clone());
+                result.first_chain = Some(first_chain.0.clone());
+            }
+            ViolationKind::UnbalancedFlow { resource_type, inputs, outputs } => {
+                result.violation_type = SafetyViolationType::UnbalancedFlow;
+                result.resource_type = Some(resource_type.clone());
+                result.inputs = Some(inputs.to_string());
+                result.outputs = Some(outputs.to_string());
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indexer_causality::types::EntityId;
+    use indexer_core::types::ChainId;
+
+    #[test]
+    fn test_violation_fields() {
+        let violation = |kind| SafetyViolation {
+            event_id: "ntrn-spend".to_string(),
+            chain_id: ChainId("neutron-1".to_string()),
+            block_number: 4,
+            kind,
+            detected_at: std::time::SystemTime::UNIX_EPOCH,
+        };
+
+        let double_spend = CausalViolation::from(&violation(ViolationKind::DoubleSpend {
+            nullifier_hash: [7; 32],
+            resource_id: EntityId::new([8; 32]),
+            first_event: "eth-spend".to_string(),
+            first_chain: ChainId("ethereum".to_string()),
+        }));
+        assert!(double_spend.violation_type == SafetyViolationType::DoubleSpend);
+        assert_eq!(double_spend.nullifier_hash, Some(hash_to_hex(&[7; 32])));
+        assert_eq!(double_spend.first_chain.as_deref(), Some("ethereum"));
+        assert!(double_spend.inputs.is_none());
+
+        let unbalanced = CausalViolation::from(&violation(ViolationKind::UnbalancedFlow {
+            resource_type: "token:usdc".to_string(),
+            inputs: u128::MAX,
+            outputs: 150,
+        }));
+        assert!(unbalanced.violation_type == SafetyViolationType::UnbalancedFlow);
+        assert_eq!(unbalanced.inputs, Some(u128::MAX.to_string()));
+        assert!(unbalanced.first_event.is_none());
+    }
+
+    #[test]
+    fn test_path_depth_is_clamped() {
+        assert_eq!(path_depth(None), DEFAULT_PATH_DEPTH);
+        assert_eq!(path_depth(Some(3)), 3);
+        assert_eq!(path_depth(Some(0)), 1);
+        assert_eq!(path_depth(Some(10_000)), MAX_PATH_DEPTH);
+    }
+}
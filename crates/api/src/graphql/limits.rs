@@ -0,0 +1,313 @@
+/// Query depth/complexity limits and persisted queries for the GraphQL endpoint
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_graphql::extensions::{
+    Extension, ExtensionContext, ExtensionFactory, NextPrepareRequest, NextValidation,
+};
+use async_graphql::{Request, ServerError, ServerResult, ValidationResult, Value as GraphQLValue};
+use sha2::{Digest, Sha256};
+
+use indexer_core::security::RateLimiter;
+
+/// Cost multiplier for list fields without a page size argument
+pub(super) const UNBOUNDED_LIST_COST: usize = 10;
+
+/// Limits applied to GraphQL queries
+#[derive(Debug, Clone)]
+pub struct GraphQLLimits {
+    /// Maximum nesting depth of a query
+    pub max_depth: usize,
+    /// Maximum complexity of a single query
+    pub max_complexity: usize,
+    /// Complexity charged as one request against the caller's rate limit
+    pub complexity_per_request: usize,
+    /// Only execute queries from the persisted query allow-list
+    pub persisted_queries_only: bool,
+    /// Persisted queries keyed by the hex SHA-256 hash of their text
+    persisted_queries: HashMap<String, String>,
+}
+
+impl Default for GraphQLLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: 10,
+            max_complexity: 5_000,
+            complexity_per_request: 50,
+            persisted_queries_only: false,
+            persisted_queries: HashMap::new(),
+        }
+    }
+}
+
+impl GraphQLLimits {
+    /// Set the maximum query depth
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Set the maximum query complexity
+    pub fn with_max_complexity(mut self, max_complexity: usize) -> Self {
+        self.max_complexity = max_complexity;
+        self
+    }
+
+    /// Set the complexity charged as one request against the caller's rate limit
+    pub fn with_complexity_per_request(mut self, complexity_per_request: usize) -> Self {
+        self.complexity_per_request = complexity_per_request;
+        self
+    }
+
+    /// Add a query to the persisted query allow-list
+    pub fn with_persisted_query(mut self, query: impl Into<String>) -> Self {
+        let query = query.into();
+        self.persisted_queries.insert(query_hash(&query), query);
+        self
+    }
+
+    /// Only accept persisted queries, as in production
+    pub fn with_persisted_queries_only(mut self, enabled: bool) -> Self {
+        self.persisted_queries_only = enabled;
+        self
+    }
+
+    /// Resolve the query text of a request against the persisted query allow-list
+    ///
+    /// Requests identify persisted queries with the Apollo
+    /// `extensions.persistedQuery.sha256Hash` field. In persisted-only mode
+    /// any request that is not on the allow-list is rejected.
+    pub fn resolve_persisted_query(&self, mut request: Request) -> std::result::Result<Request, ServerError> {
+        let hash = request.extensions.get("persistedQuery").and_then(|persisted| match persisted {
+            GraphQLValue::Object(fields) => match fields.get("sha256Hash") {
+                Some(GraphQLValue::String(hash)) => Some(hash.to_lowercase()),
+                _ => None,
+            },
+            _ => None,
+        });
+
+        match hash {
+            Some(hash) => {
+                let query = self.persisted_queries.get(&hash)
+                    .ok_or_else(|| ServerError::new("PersistedQueryNotFound", None))?;
+                if !request.query.is_empty() && request.query != *query {
+                    return Err(ServerError::new("Query does not match its persisted query hash", None));
+                }
+                request.query = query.clone();
+                Ok(request)
+            }
+            None if self.persisted_queries_only => {
+                let known = self.persisted_queries.contains_key(&query_hash(&request.query));
+                if known {
+                    Ok(request)
+                } else {
+                    Err(ServerError::new("Only persisted queries are accepted", None))
+                }
+            }
+            None => Ok(request),
+        }
+    }
+}
+
+/// Hex SHA-256 hash identifying a persisted query
+pub fn query_hash(query: &str) -> String {
+    hex::encode(Sha256::digest(query.as_bytes()))
+}
+
+/// Cost of a list field fetching `page` items of `child_complexity` each
+pub(super) fn list_cost(page: Option<i32>, default_page: usize, child_complexity: usize) -> usize {
+    let page = page.map_or(default_page, |p| p.max(0) as usize);
+    page.max(1).saturating_mul(child_complexity)
+}
+
+/// Address of the client issuing a GraphQL request
+///
+/// Set on each HTTP request and on the session of each websocket connection.
+#[derive(Debug, Clone)]
+pub(super) struct ClientAddr(pub String);
+
+/// Extension resolving requests against the persisted query allow-list
+///
+/// Runs before parsing for queries and subscriptions alike, so no transport
+/// can execute a query the allow-list does not contain.
+pub(super) struct PersistedQueries {
+    limits: Arc<GraphQLLimits>,
+}
+
+impl PersistedQueries {
+    /// Create the extension for the allow-list of `limits`
+    pub(super) fn new(limits: Arc<GraphQLLimits>) -> Self {
+        Self { limits }
+    }
+}
+
+impl ExtensionFactory for PersistedQueries {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(PersistedQueriesExtension {
+            limits: self.limits.clone(),
+        })
+    }
+}
+
+struct PersistedQueriesExtension {
+    limits: Arc<GraphQLLimits>,
+}
+
+#[async_trait::async_trait]
+impl Extension for PersistedQueriesExtension {
+    async fn prepare_request(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        request: Request,
+        next: NextPrepareRequest<'_>,
+    ) -> ServerResult<Request> {
+        let request = self.limits.resolve_persisted_query(request)?;
+        next.run(ctx, request).await
+    }
+}
+
+/// Extension charging query complexity against the caller's rate limit
+///
+/// The limiter is the one the REST API charges requests against, so a client
+/// shares a single budget across both endpoints.
+pub(super) struct ComplexityRateLimit {
+    limiter: Arc<RateLimiter>,
+    complexity_per_request: usize,
+}
+
+impl ComplexityRateLimit {
+    /// Create the extension, charging complexity against `limiter` as one
+    /// request per `complexity_per_request`
+    pub(super) fn new(limiter: Arc<RateLimiter>, complexity_per_request: usize) -> Self {
+        Self { limiter, complexity_per_request }
+    }
+}
+
+impl ExtensionFactory for ComplexityRateLimit {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(ComplexityRateLimitExtension {
+            limiter: self.limiter.clone(),
+            complexity_per_request: self.complexity_per_request,
+        })
+    }
+}
+
+struct ComplexityRateLimitExtension {
+    limiter: Arc<RateLimiter>,
+    complexity_per_request: usize,
+}
+
+#[async_trait::async_trait]
+impl Extension for ComplexityRateLimitExtension {
+    async fn validation(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        next: NextValidation<'_>,
+    ) -> std::result::Result<ValidationResult, Vec<ServerError>> {
+        let result = next.run(ctx).await?;
+
+        // Only requests built outside the HTTP and websocket handlers have no client
+        if let Some(ClientAddr(client)) = ctx.data_opt::<ClientAddr>() {
+            let cost = result.complexity.div_ceil(self.complexity_per_request.max(1)).max(1);
+            if !self.limiter.is_allowed_with_cost(client, cost).await {
+                return Err(vec![ServerError::new(
+                    format!("Rate limit exceeded: query complexity {} exceeds the remaining budget", result.complexity),
+                    None,
+                )]);
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUERY: &str = "{ health { healthy } }";
+
+    fn persisted_request(hash: &str) -> Request {
+        let mut request = Request::new("");
+        let extension = serde_json::json!({ "sha256Hash": hash });
+        request.extensions.insert(
+            "persistedQuery".to_string(),
+            GraphQLValue::from_json(extension).unwrap(),
+        );
+        request
+    }
+
+    #[test]
+    fn test_resolve_persisted_query_by_hash() {
+        let limits = GraphQLLimits::default().with_persisted_query(QUERY);
+
+        let request = limits.resolve_persisted_query(persisted_request(&query_hash(QUERY))).unwrap();
+        assert_eq!(request.query, QUERY);
+
+        assert!(limits.resolve_persisted_query(persisted_request(&query_hash("{ other }"))).is_err());
+    }
+
+    #[test]
+    fn test_persisted_queries_only() {
+        let limits = GraphQLLimits::default().with_persisted_query(QUERY);
+        assert!(limits.resolve_persisted_query(Request::new("{ other }")).is_ok());
+
+        let production = limits.with_persisted_queries_only(true);
+        assert!(production.resolve_persisted_query(Request::new("{ other }")).is_err());
+        assert!(production.resolve_persisted_query(Request::new(QUERY)).is_ok());
+        assert!(production.resolve_persisted_query(persisted_request(&query_hash(QUERY))).is_ok());
+    }
+
+    struct TestQuery;
+
+    #[async_graphql::Object]
+    impl TestQuery {
+        async fn value(&self) -> i32 {
+            1
+        }
+    }
+
+    struct TestSubscription;
+
+    #[async_graphql::Subscription]
+    impl TestSubscription {
+        async fn values(&self) -> impl futures::Stream<Item = i32> {
+            futures::stream::iter(vec![1, 2])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_subscriptions_are_limited() {
+        use futures::StreamExt;
+
+        const SUBSCRIPTION: &str = "subscription { values }";
+        let limits = GraphQLLimits::default()
+            .with_persisted_query(SUBSCRIPTION)
+            .with_persisted_queries_only(true);
+        let limiter = Arc::new(RateLimiter::new(2, std::time::Duration::from_secs(60)));
+        let schema = async_graphql::Schema::build(TestQuery, async_graphql::EmptyMutation, TestSubscription)
+            .extension(PersistedQueries::new(Arc::new(limits)))
+            .extension(ComplexityRateLimit::new(limiter.clone(), 50))
+            .finish();
+        let subscribe = |query: &str| {
+            schema.execute_stream(Request::new(query).data(ClientAddr("10.0.0.1".to_string())))
+        };
+
+        let unlisted = subscribe("subscription { other: values }").next().await.unwrap();
+        assert!(unlisted.is_err());
+
+        assert!(subscribe(SUBSCRIPTION).next().await.unwrap().is_ok());
+
+        // A REST request of the same client spent the rest of its budget
+        assert!(limiter.is_allowed("10.0.0.1").await);
+        assert!(subscribe(SUBSCRIPTION).next().await.unwrap().is_err());
+    }
+
+    #[test]
+    fn test_list_cost() {
+        assert_eq!(list_cost(Some(20), 100, 3), 60);
+        assert_eq!(list_cost(None, 100, 3), 300);
+        assert_eq!(list_cost(Some(0), 100, 3), 3);
+        assert_eq!(list_cost(Some(-5), 100, usize::MAX), usize::MAX);
+    }
+}
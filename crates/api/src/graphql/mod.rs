@@ -2,27 +2,54 @@
 use std::sync::Arc;
 
 use async_graphql::{
-    Context, EmptySubscription, InputObject, Object, Schema, SimpleObject,
+    Context, InputObject, Object, Schema, SimpleObject,
     ID, Enum, Scalar, ScalarType, 
-    http::GraphiQLSource,
+    http::{GraphiQLSource, ALL_WEBSOCKET_PROTOCOLS},
     InputValueError, InputValueResult, Value as GraphQLValue,
 };
-use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
+use async_graphql_axum::{GraphQLProtocol, GraphQLRequest, GraphQLResponse, GraphQLWebSocket};
 use axum::{
-    response::{IntoResponse, Html},
+    response::{IntoResponse, Html, Response},
     routing::get,
-    Router, extract::State,
+    Router, extract::{ws::WebSocketUpgrade, ConnectInfo, State},
 };
+use base64::prelude::*;
 use serde_json::Value as JsonValue;
 use tracing::info;
 
 use indexer_core::{Error, Result};
+use indexer_core::event::Event;
+use indexer_core::security::RateLimiter;
 use indexer_core::service::BoxedEventService;
+use indexer_core::types::EventFilter;
+use indexer_storage::BoxedStorage;
 use crate::{
     ContractSchemaVersion, ContractSchema, EventSchema, FunctionSchema, FieldSchema,
     ContractSchemaRegistry,
 };
 
+mod causality;
+mod limits;
+mod pagination;
+mod subscription;
+pub mod valence;
+
+pub use causality::{
+    CausalPath, CausalReference, CausalViolation, CausalityState, SafetyViolationType,
+    SharedCrossChainCausality, SharedViolationDetector,
+};
+pub use limits::{query_hash, GraphQLLimits};
+pub use pagination::{EventConnection, EventEdge, PageInfo};
+pub use subscription::SubscriptionRoot;
+
+use causality::{cross_chain_causality, path_depth, violation_detector};
+use limits::{list_cost, ClientAddr, ComplexityRateLimit, PersistedQueries};
+use pagination::{PageArgs, DEFAULT_PAGE_SIZE};
+use valence::{
+    storage_error, valence_storage, ProcessorMessage, ValenceAccount, ValenceAuthorization,
+    ValenceLibrary, ValenceProcessor,
+};
+
 /// JSON scalar for GraphQL
 #[derive(Clone)]
 struct Json(JsonValue);
@@ -69,7 +96,7 @@ impl ScalarType for BlockHash {
 }
 
 /// GraphQL schema with query, mutation, and subscription
-pub type GraphQLSchema = Schema<QueryRoot, MutationRoot, EmptySubscription>;
+pub type GraphQLSchema = Schema<QueryRoot, MutationRoot, SubscriptionRoot>;
 
 /// Query root
 pub struct QueryRoot;
@@ -118,6 +145,40 @@ impl QueryRoot {
         ])
     }
 
+    /// Page through events matching the filter using Relay cursors
+    ///
+    /// Events are ordered by chain, block and position within the block.
+    /// `limit` and `offset` in the filter are ignored in favour of
+    /// `first`/`after` and `last`/`before`. `totalCount` is only computed
+    /// when selected and is null when the backend cannot count cheaply.
+    #[graphql(complexity = "list_cost(first.or(last), DEFAULT_PAGE_SIZE, child_complexity)")]
+    async fn events_connection(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<EventFilterInput>,
+        first: Option<i32>,
+        after: Option<String>,
+        last: Option<i32>,
+        before: Option<String>,
+    ) -> async_graphql::Result<EventConnection> {
+        let state = ctx.data::<AppState>()?;
+        let filter: EventFilter = filter.as_ref().map(Into::into).unwrap_or_default();
+        let args = PageArgs { first, after, last, before };
+
+        let events = state.event_service.get_events_page(filter.clone(), args.event_page()?).await
+            .map_err(|e| async_graphql::Error::new(format!("Failed to query events: {}", e)))?;
+
+        // Only count when the client asked for it, counting may be expensive
+        let total_count = if ctx.look_ahead().field("totalCount").exists() {
+            state.event_service.count_events(filter).await
+                .map_err(|e| async_graphql::Error::new(format!("Failed to count events: {}", e)))?
+        } else {
+            None
+        };
+
+        EventConnection::from_events(events, &args, total_count)
+    }
+
     /// Get latest block
     async fn latest_block(&self, ctx: &Context<'_>, chain: Option<String>) -> async_graphql::Result<ChainBlock> {
         let _state = ctx.data::<AppState>()?;
@@ -177,14 +238,14 @@ impl QueryRoot {
         // If version is provided, get that specific version
         // Otherwise, get the latest
         if let Some(version) = version {
-            match state.schema_registry.get_schema(&chain, &address, &version) {
+            match state.schema_registry.get_schema(&chain, &address, &version).await {
                 Ok(Some(schema)) => Ok(Some(schema)),
                 Ok(None) => Ok(None),
                 Err(e) => Err(async_graphql::Error::new(format!("Failed to get schema: {}", e))),
             }
         } else {
             // Get the latest schema
-            match state.schema_registry.get_latest_schema(&chain, &address) {
+            match state.schema_registry.get_latest_schema(&chain, &address).await {
                 Ok(Some(schema)) => Ok(Some(schema)),
                 Ok(None) => Ok(None),
                 Err(e) => Err(async_graphql::Error::new(format!("Failed to get latest schema: {}", e))),
@@ -206,6 +267,141 @@ impl QueryRoot {
         // In a real implementation, we would filter schemas by chain and paginate results
         Ok(vec![])
     }
+
+    /// Get a Valence account, optionally as it was at a given block
+    async fn valence_account(
+        &self,
+        ctx: &Context<'_>,
+        id: ID,
+        at_block: Option<i64>,
+    ) -> async_graphql::Result<Option<ValenceAccount>> {
+        let storage = valence_storage(ctx)?;
+        let at_block = at_block.map(|block| block.max(0) as u64);
+        let state = match at_block {
+            Some(block) => storage.get_historical_valence_account_state(id.as_str(), block).await,
+            None => storage.get_valence_account_state(id.as_str()).await,
+        }.map_err(storage_error)?;
+
+        Ok(state.as_ref().map(|state| ValenceAccount::from(state).as_of(at_block)))
+    }
+
+    /// Get a Valence processor, optionally as it was at a given block
+    async fn valence_processor(
+        &self,
+        ctx: &Context<'_>,
+        id: ID,
+        at_block: Option<i64>,
+    ) -> async_graphql::Result<Option<ValenceProcessor>> {
+        let storage = valence_storage(ctx)?;
+        let state = match at_block {
+            Some(block) => storage.get_historical_valence_processor_state(id.as_str(), block.max(0) as u64).await,
+            None => storage.get_valence_processor_state(id.as_str()).await,
+        }.map_err(storage_error)?;
+
+        Ok(state.as_ref().map(ValenceProcessor::from))
+    }
+
+    /// Get a Valence processor message
+    async fn valence_processor_message(
+        &self,
+        ctx: &Context<'_>,
+        id: ID,
+    ) -> async_graphql::Result<Option<ProcessorMessage>> {
+        let storage = valence_storage(ctx)?;
+        let message = storage.get_valence_processor_message(id.as_str()).await
+            .map_err(storage_error)?;
+
+        Ok(message.as_ref().map(ProcessorMessage::from))
+    }
+
+    /// Get a Valence authorization contract
+    async fn valence_authorization(
+        &self,
+        ctx: &Context<'_>,
+        id: ID,
+    ) -> async_graphql::Result<Option<ValenceAuthorization>> {
+        let storage = valence_storage(ctx)?;
+        let state = storage.get_valence_authorization_state(id.as_str()).await
+            .map_err(storage_error)?;
+
+        Ok(state.as_ref().map(ValenceAuthorization::from))
+    }
+
+    /// Get a Valence library
+    async fn valence_library(
+        &self,
+        ctx: &Context<'_>,
+        id: ID,
+    ) -> async_graphql::Result<Option<ValenceLibrary>> {
+        let storage = valence_storage(ctx)?;
+        let state = storage.get_valence_library_state(id.as_str()).await
+            .map_err(storage_error)?;
+
+        Ok(state.as_ref().map(ValenceLibrary::from))
+    }
+
+    /// Shortest causal path from one event to another, following cross-chain references
+    async fn causal_path(
+        &self,
+        ctx: &Context<'_>,
+        from_event: ID,
+        to_event: ID,
+        max_depth: Option<i32>,
+    ) -> async_graphql::Result<Option<CausalPath>> {
+        let causality = cross_chain_causality(ctx)?;
+        let causality = causality.read().await;
+        let path = causality.find_shortest_path(from_event.as_str(), to_event.as_str(), path_depth(max_depth));
+
+        Ok(path.as_ref().map(CausalPath::from))
+    }
+
+    /// All causal paths from one event to another with at most `maxDepth` hops, shortest first
+    #[graphql(complexity = "list_cost(first, 20, child_complexity)")]
+    async fn causal_paths(
+        &self,
+        ctx: &Context<'_>,
+        from_event: ID,
+        to_event: ID,
+        max_depth: Option<i32>,
+        first: Option<i32>,
+    ) -> async_graphql::Result<Vec<CausalPath>> {
+        let causality = cross_chain_causality(ctx)?;
+        let causality = causality.read().await;
+        let limit = first.map_or(20, |f| f.max(0) as usize);
+        let paths = causality.find_paths(from_event.as_str(), to_event.as_str(), path_depth(max_depth), limit);
+
+        Ok(paths.iter().map(CausalPath::from).collect())
+    }
+
+    /// Double spends and conservation violations detected while indexing, oldest first
+    #[graphql(complexity = "list_cost(first, 100, child_complexity)")]
+    async fn safety_violations(
+        &self,
+        ctx: &Context<'_>,
+        chain: Option<String>,
+        violation_type: Option<SafetyViolationType>,
+        nullifier_hash: Option<String>,
+        from_block: Option<i64>,
+        first: Option<i32>,
+    ) -> async_graphql::Result<Vec<CausalViolation>> {
+        let filter = indexer_causality::ViolationFilter {
+            chain_id: chain.map(indexer_core::types::ChainId),
+            violation_type: violation_type.map(Into::into),
+            nullifier_hash: nullifier_hash
+                .map(|hash| indexer_causality::types::hash_from_hex(&hash))
+                .transpose()
+                .map_err(|e| async_graphql::Error::new(format!("Invalid nullifier hash: {}", e)))?,
+            from_block: from_block.map(|block| block.max(0) as u64),
+        };
+
+        let detector = violation_detector(ctx)?;
+        let violations = detector.read().await.violations(&filter);
+
+        Ok(violations.iter()
+            .take(first.map_or(100, |f| f.max(0) as usize))
+            .map(CausalViolation::from)
+            .collect())
+    }
 }
 
 /// GraphQL event type
@@ -231,6 +427,22 @@ pub struct GraphQLEvent {
     attributes: Option<JsonValue>,
 }
 
+impl From<&dyn Event> for GraphQLEvent {
+    fn from(event: &dyn Event) -> Self {
+        Self {
+            id: ID(event.id().to_string()),
+            chain: event.chain().to_string(),
+            block_number: event.block_number() as i64,
+            block_hash: event.block_hash().to_string(),
+            tx_hash: event.tx_hash().to_string(),
+            timestamp: chrono::DateTime::<chrono::Utc>::from(event.timestamp()),
+            event_type: event.event_type().to_string(),
+            data: BASE64_STANDARD.encode(event.raw_data()),
+            attributes: event.decoded().map(|fields| JsonValue::Object(fields.clone())),
+        }
+    }
+}
+
 /// Event filter input
 #[derive(InputObject)]
 struct EventFilterInput {
@@ -305,7 +517,8 @@ pub struct MutationRoot;
 
 #[Object]
 impl MutationRoot {
-    /// Register a new contract schema
+    /// Register a new contract schema, used to decode the contract's events
+    /// on ingestion
     async fn register_contract_schema(
         &self, 
         ctx: &Context<'_>, 
@@ -345,7 +558,7 @@ impl MutationRoot {
         };
         
         // Attempt to register the schema
-        match state.schema_registry.register_schema(schema_version.clone()) {
+        match state.schema_registry.register_schema(schema_version.clone()).await {
             Ok(_) => Ok(RegisterSchemaResult {
                 success: true,
                 error: None,
@@ -471,37 +684,64 @@ pub struct AppState {
     
     /// Schema registry
     pub schema_registry: Arc<dyn ContractSchemaRegistry + Send + Sync>,
+    
+    /// Storage for Valence contract state
+    pub storage: Option<BoxedStorage>,
+
+    /// Causality indexer state for causal path and violation queries
+    pub causality: CausalityState,
 }
 
-/// Create GraphQL schema
+/// Create GraphQL schema, charging query complexity against `rate_limiter`
 pub fn create_schema(
     event_service: BoxedEventService,
     schema_registry: Arc<dyn ContractSchemaRegistry + Send + Sync>,
+    storage: Option<BoxedStorage>,
+    causality: CausalityState,
+    limits: &GraphQLLimits,
+    rate_limiter: Arc<RateLimiter>,
 ) -> GraphQLSchema {
-    Schema::build(QueryRoot, MutationRoot, EmptySubscription)
+    Schema::build(QueryRoot, MutationRoot, SubscriptionRoot)
         .data(AppState { 
             event_service,
             schema_registry,
+            storage,
+            causality,
         })
+        .limit_depth(limits.max_depth)
+        .limit_complexity(limits.max_complexity)
+        .extension(PersistedQueries::new(Arc::new(limits.clone())))
+        .extension(ComplexityRateLimit::new(rate_limiter, limits.complexity_per_request))
         .finish()
 }
 
+/// State shared by the GraphQL HTTP handlers
+#[derive(Clone)]
+struct GraphQLServerState {
+    schema: GraphQLSchema,
+}
+
 /// Start GraphQL server
 pub async fn start_graphql_server(
     addr: std::net::SocketAddr,
     event_service: BoxedEventService,
     schema_registry: Arc<dyn ContractSchemaRegistry + Send + Sync>,
+    storage: Option<BoxedStorage>,
+    causality: CausalityState,
+    limits: GraphQLLimits,
+    rate_limiter: Arc<RateLimiter>,
     enable_playground: bool,
 ) -> Result<()> {
     info!("Starting GraphQL server on {}", addr);
 
     // Create schema
-    let schema = create_schema(event_service, schema_registry);
+    let schema = create_schema(event_service, schema_registry, storage, causality, &limits, rate_limiter);
 
-    // Create router
+    // Create router with subscriptions served over websocket
     let mut app = Router::new()
         .route("/", get(graphql_handler).post(graphql_handler))
-        .with_state(schema);
+        .route("/ws", get(graphql_ws_handler))
+        .with_state(GraphQLServerState { schema });
         
     // Add GraphiQL if enabled
     if enable_playground {
@@ -518,7 +758,7 @@ pub async fn start_graphql_server(
         
     // Start server
     server
-        .serve(app.into_make_service())
+        .serve(app.into_make_service_with_connect_info::<std::net::SocketAddr>())
         .await
         .map_err(|e| Error::api(format!("Server error: {}", e)))?;
     
@@ -527,13 +767,37 @@ pub async fn start_graphql_server(
 
 /// GraphiQL handler
 async fn graphiql() -> impl IntoResponse {
-    Html(GraphiQLSource::build().endpoint("/").finish())
+    Html(GraphiQLSource::build().endpoint("/").subscription_endpoint("/ws").finish())
 }
 
 /// GraphQL handler
 async fn graphql_handler(
-    State(schema): State<GraphQLSchema>,
+    State(state): State<GraphQLServerState>,
+    ConnectInfo(addr): ConnectInfo<std::net::SocketAddr>,
     req: GraphQLRequest,
 ) -> GraphQLResponse {
-    schema.execute(req.into_inner()).await.into()
+    let request = req.into_inner().data(ClientAddr(addr.ip().to_string()));
+    state.schema.execute(request).await.into()
+}
+
+/// GraphQL websocket handler
+///
+/// Operations on the connection go through the same persisted query and
+/// complexity checks as HTTP requests, charged to the connecting client.
+async fn graphql_ws_handler(
+    State(state): State<GraphQLServerState>,
+    ConnectInfo(addr): ConnectInfo<std::net::SocketAddr>,
+    protocol: GraphQLProtocol,
+    websocket: WebSocketUpgrade,
+) -> Response {
+    websocket
+        .protocols(ALL_WEBSOCKET_PROTOCOLS)
+        .on_upgrade(move |stream| {
+            let mut data = async_graphql::Data::default();
+            data.insert(ClientAddr(addr.ip().to_string()));
+
+            GraphQLWebSocket::new(stream, state.schema, protocol)
+                .with_data(data)
+                .serve()
+        })
 } 
\ No newline at end of file
@@ -0,0 +1,323 @@
+/// Relay-style cursor pagination for GraphQL event queries
+use async_graphql::SimpleObject;
+use base64::prelude::*;
+use serde_json::Value as JsonValue;
+
+use indexer_core::event::Event;
+use indexer_core::types::{EventFilter, EventPage, EventPosition};
+
+use super::{EventFilterInput, GraphQLEvent};
+
+/// Page size used when neither `first` nor `last` is given
+pub(super) const DEFAULT_PAGE_SIZE: usize = 100;
+
+/// Largest page a single query may request
+pub(super) const MAX_PAGE_SIZE: usize = 1000;
+
+/// Encode the position of an event as an opaque cursor
+pub(super) fn encode_cursor(position: &EventPosition) -> String {
+    let json = serde_json::to_vec(position).expect("event positions are always serializable");
+    BASE64_URL_SAFE_NO_PAD.encode(json)
+}
+
+/// Decode a cursor previously returned by `encode_cursor`
+pub(super) fn decode_cursor(value: &str) -> async_graphql::Result<EventPosition> {
+    let invalid = || async_graphql::Error::new(format!("Invalid cursor: {}", value));
+    let json = BASE64_URL_SAFE_NO_PAD.decode(value).map_err(|_| invalid())?;
+    serde_json::from_slice(&json).map_err(|_| invalid())
+}
+
+/// Page of events
+#[derive(SimpleObject)]
+pub struct EventConnection {
+    /// Events in this page with their cursors
+    edges: Vec<EventEdge>,
+    /// Pagination details
+    page_info: PageInfo,
+    /// Number of events matching the filter across all pages, when the
+    /// backend can count them cheaply
+    total_count: Option<i64>,
+}
+
+/// Event with its cursor
+#[derive(SimpleObject)]
+pub struct EventEdge {
+    /// Opaque cursor of this event
+    cursor: String,
+    /// The event
+    node: GraphQLEvent,
+}
+
+/// Relay page information
+#[derive(SimpleObject)]
+pub struct PageInfo {
+    /// Whether events exist after this page
+    has_next_page: bool,
+    /// Whether events exist before this page
+    has_previous_page: bool,
+    /// Cursor of the first event in this page
+    start_cursor: Option<String>,
+    /// Cursor of the last event in this page
+    end_cursor: Option<String>,
+}
+
+/// Relay pagination arguments
+#[derive(Debug, Default)]
+pub(super) struct PageArgs {
+    pub first: Option<i32>,
+    pub after: Option<String>,
+    pub last: Option<i32>,
+    pub before: Option<String>,
+}
+
+impl PageArgs {
+    /// Keyset page to read from the event service
+    ///
+    /// One event more than requested is read so the connection can tell
+    /// whether another page follows without counting.
+    pub(super) fn event_page(&self) -> async_graphql::Result<EventPage> {
+        let first = page_size(self.first, "first")?;
+        let last = page_size(self.last, "last")?;
+        let (limit, from_end) = match (first, last) {
+            (Some(first), _) => (first, false),
+            (None, Some(last)) => (last, true),
+            (None, None) => (DEFAULT_PAGE_SIZE, false),
+        };
+
+        Ok(EventPage {
+            after: self.after.as_deref().map(decode_cursor).transpose()?,
+            before: self.before.as_deref().map(decode_cursor).transpose()?,
+            limit: limit + 1,
+            from_end,
+        })
+    }
+}
+
+impl EventConnection {
+    /// Build a connection from the events read for `EventPage` of `args`
+    ///
+    /// Events are in (chain, block, log index) order, with at most one
+    /// event beyond the requested page on the side the page was read from.
+    pub(super) fn from_events(
+        events: Vec<Box<dyn Event>>,
+        args: &PageArgs,
+        total_count: Option<u64>,
+    ) -> async_graphql::Result<Self> {
+        let first = page_size(args.first, "first")?;
+        let last = page_size(args.last, "last")?;
+
+        let mut edges: Vec<EventEdge> = events.into_iter()
+            .map(|event| EventEdge {
+                cursor: encode_cursor(&EventPosition::of(event.as_ref())),
+                node: GraphQLEvent::from(event.as_ref()),
+            })
+            .collect();
+
+        let (has_next_page, has_previous_page) = match (first, last) {
+            (Some(first), last) => {
+                let has_next_page = edges.len() > first;
+                edges.truncate(first);
+                // `last` applies to the events `first` selected
+                let skipped = last.map_or(0, |last| edges.len().saturating_sub(last));
+                edges.drain(..skipped);
+                (has_next_page, args.after.is_some() || skipped > 0)
+            }
+            (None, Some(last)) => {
+                let skipped = edges.len().saturating_sub(last);
+                edges.drain(..skipped);
+                (args.before.is_some(), skipped > 0)
+            }
+            (None, None) => {
+                let has_next_page = edges.len() > DEFAULT_PAGE_SIZE;
+                edges.truncate(DEFAULT_PAGE_SIZE);
+                (has_next_page, args.after.is_some())
+            }
+        };
+
+        Ok(Self {
+            page_info: PageInfo {
+                has_next_page: has_next_page && !edges.is_empty(),
+                has_previous_page,
+                start_cursor: edges.first().map(|edge| edge.cursor.clone()),
+                end_cursor: edges.last().map(|edge| edge.cursor.clone()),
+            },
+            total_count: total_count.map(|count| count as i64),
+            edges,
+        })
+    }
+}
+
+/// Validate a `first`/`last` argument
+fn page_size(size: Option<i32>, name: &str) -> async_graphql::Result<Option<usize>> {
+    match size {
+        Some(size) if size < 0 => Err(async_graphql::Error::new(format!("`{}` must not be negative", name))),
+        Some(size) if size as usize > MAX_PAGE_SIZE => Err(async_graphql::Error::new(
+            format!("`{}` must not exceed {}", name, MAX_PAGE_SIZE),
+        )),
+        Some(size) => Ok(Some(size as usize)),
+        None => Ok(None),
+    }
+}
+
+impl From<&EventFilterInput> for EventFilter {
+    fn from(input: &EventFilterInput) -> Self {
+        let mut filter = EventFilter::new();
+
+        filter.chain = input.chain.clone();
+        filter.event_types = input.event_types.clone();
+
+        if let Some(ref range) = input.block_range {
+            let from = range.first().map_or(0, |&from| from.max(0) as u64);
+            let to = range.get(1).map_or(u64::MAX, |&to| to.max(0) as u64);
+            filter.block_range = Some((from, to));
+        }
+
+        if let Some(ref range) = input.time_range {
+            let parse = |value: Option<&String>| {
+                value.and_then(|v| chrono::DateTime::parse_from_rfc3339(v).ok())
+                    .map(|t| t.timestamp().max(0) as u64)
+            };
+            filter.time_range = Some((parse(range.first()).unwrap_or(0), parse(range.get(1)).unwrap_or(u64::MAX)));
+        }
+
+        if let Some(JsonValue::Object(ref attributes)) = input.attributes {
+            for (key, value) in attributes {
+                if let Some(value) = value.as_str() {
+                    filter.custom_filters.insert(key.clone(), value.to_string());
+                }
+            }
+        }
+
+        filter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[derive(Debug)]
+    struct TestEvent {
+        id: String,
+        chain: String,
+        block_number: u64,
+        log_index: u32,
+    }
+
+    impl Event for TestEvent {
+        fn id(&self) -> &str { &self.id }
+        fn chain(&self) -> &str { &self.chain }
+        fn block_number(&self) -> u64 { self.block_number }
+        fn block_hash(&self) -> &str { "0xblock" }
+        fn tx_hash(&self) -> &str { "0xtx" }
+        fn timestamp(&self) -> SystemTime { UNIX_EPOCH }
+        fn event_type(&self) -> &str { "Transfer" }
+        fn raw_data(&self) -> &[u8] { &[] }
+        fn log_index(&self) -> Option<u32> { Some(self.log_index) }
+        fn as_any(&self) -> &dyn std::any::Any { self }
+    }
+
+    fn events(positions: &[(u64, u32)]) -> Vec<Box<dyn Event>> {
+        positions.iter().enumerate()
+            .map(|(i, &(block_number, log_index))| Box::new(TestEvent {
+                id: format!("event-{}", i),
+                chain: "ethereum".to_string(),
+                block_number,
+                log_index,
+            }) as Box<dyn Event>)
+            .collect()
+    }
+
+    /// Page through events the way an event service without storage does
+    fn query(events: Vec<Box<dyn Event>>, args: &PageArgs) -> EventConnection {
+        let page = args.event_page().unwrap();
+        EventConnection::from_events(page.apply(events), args, None).unwrap()
+    }
+
+    fn ids(connection: &EventConnection) -> Vec<String> {
+        connection.edges.iter().map(|edge| edge.node.id.to_string()).collect()
+    }
+
+    #[test]
+    fn test_cursor_roundtrip() {
+        let position = EventPosition {
+            chain: "cosmos:hub-4".to_string(),
+            block_number: 42,
+            log_index: None,
+            tx_hash: "ABCD".to_string(),
+            id: "ABCD-0".to_string(),
+        };
+        assert_eq!(decode_cursor(&encode_cursor(&position)).unwrap(), position);
+        let position = EventPosition { log_index: Some(3), ..position };
+        assert_eq!(decode_cursor(&encode_cursor(&position)).unwrap(), position);
+        assert!(decode_cursor("not a cursor").is_err());
+    }
+
+    #[test]
+    fn test_page_reads_one_extra_event() {
+        let args = PageArgs { first: Some(2), ..Default::default() };
+        assert_eq!(args.event_page().unwrap().limit, 3);
+
+        let args = PageArgs { last: Some(5), ..Default::default() };
+        let page = args.event_page().unwrap();
+        assert_eq!(page.limit, 6);
+        assert!(page.from_end);
+    }
+
+    #[test]
+    fn test_forward_pagination_is_stable() {
+        // Service returns events out of order; the log index orders events within a block
+        let args = PageArgs { first: Some(2), ..Default::default() };
+        let first_page = query(events(&[(11, 0), (10, 1), (10, 0), (12, 0)]), &args);
+        assert_eq!(ids(&first_page), vec!["event-2", "event-1"]);
+        assert!(first_page.page_info.has_next_page);
+        assert!(!first_page.page_info.has_previous_page);
+
+        // A new event in a later block does not shift the next page
+        let args = PageArgs {
+            first: Some(2),
+            after: first_page.page_info.end_cursor.clone(),
+            ..Default::default()
+        };
+        let second_page = query(events(&[(11, 0), (10, 1), (10, 0), (12, 0), (13, 0)]), &args);
+        assert_eq!(ids(&second_page), vec!["event-0", "event-3"]);
+        assert!(second_page.page_info.has_next_page);
+        assert!(second_page.page_info.has_previous_page);
+    }
+
+    #[test]
+    fn test_backward_pagination() {
+        let all = query(events(&[(1, 0), (2, 0), (3, 0), (4, 0)]), &PageArgs::default());
+        assert!(!all.page_info.has_next_page);
+
+        let args = PageArgs {
+            last: Some(2),
+            before: all.page_info.end_cursor.clone(),
+            ..Default::default()
+        };
+        let page = query(events(&[(1, 0), (2, 0), (3, 0), (4, 0)]), &args);
+        assert_eq!(ids(&page), vec!["event-1", "event-2"]);
+        assert!(page.page_info.has_previous_page);
+        assert!(page.page_info.has_next_page);
+    }
+
+    #[test]
+    fn test_total_count_is_passed_through() {
+        let args = PageArgs { first: Some(1), ..Default::default() };
+        let connection = EventConnection::from_events(events(&[(1, 0)]), &args, Some(7)).unwrap();
+        assert_eq!(connection.total_count, Some(7));
+
+        let connection = EventConnection::from_events(events(&[(1, 0)]), &args, None).unwrap();
+        assert_eq!(connection.total_count, None);
+    }
+
+    #[test]
+    fn test_page_size_limits() {
+        assert!(PageArgs { first: Some(-1), ..Default::default() }.event_page().is_err());
+        assert!(PageArgs {
+            first: Some(MAX_PAGE_SIZE as i32 + 1),
+            ..Default::default()
+        }.event_page().is_err());
+    }
+}
@@ -0,0 +1,424 @@
+/// GraphQL subscriptions over the websocket transport
+use std::time::Duration;
+
+use async_graphql::{Context, Subscription, ID};
+use futures::stream::{self, Stream, StreamExt};
+use serde_json::Value as JsonValue;
+use tracing::{debug, warn};
+
+use indexer_core::event::Event;
+use indexer_core::pipeline::event_contract_address;
+use indexer_core::service::{BoxedEventServiceTrait, EventSubscription};
+use indexer_core::types::EventFilter;
+use indexer_core::BlockStatus;
+
+use super::valence::{valence_storage, ProcessorMessage, ValenceAccount};
+use super::{AppState, ChainBlock, EventFilterInput, GraphQLEvent, GraphQLFinalityStatus};
+
+/// Interval at which Valence state is polled for changes
+const VALENCE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Subscription root
+pub struct SubscriptionRoot;
+
+#[Subscription]
+impl SubscriptionRoot {
+    /// Stream new events matching the filter
+    async fn events(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<EventFilterInput>,
+    ) -> async_graphql::Result<impl Stream<Item = GraphQLEvent>> {
+        let state = ctx.data::<AppState>()?;
+        let subscription = state.event_service.subscribe().await
+            .map_err(|e| async_graphql::Error::new(format!("Failed to subscribe to events: {}", e)))?;
+
+        let limit = filter.as_ref()
+            .and_then(|f| f.limit)
+            .map(|l| l.max(0) as usize)
+            .unwrap_or(usize::MAX);
+
+        let events = event_stream(subscription)
+            .filter_map(move |event| {
+                let matched = filter.as_ref()
+                    .map_or(true, |f| event_matches_input(event.as_ref(), f))
+                    .then(|| GraphQLEvent::from(event.as_ref()));
+                async move { matched }
+            })
+            .take(limit);
+
+        Ok(events)
+    }
+
+    /// Stream blocks as they are first seen in indexed events
+    async fn new_blocks(
+        &self,
+        ctx: &Context<'_>,
+        chain: Option<String>,
+    ) -> async_graphql::Result<impl Stream<Item = ChainBlock>> {
+        let state = ctx.data::<AppState>()?;
+        let chain = chain.unwrap_or_else(|| state.event_service.chain_id().0.clone());
+        let subscription = state.event_service.subscribe().await
+            .map_err(|e| async_graphql::Error::new(format!("Failed to subscribe to events: {}", e)))?;
+
+        let event_service = state.event_service.clone();
+        let mut last_block = None;
+        let blocks = event_stream(subscription)
+            .filter_map(move |event| {
+                let is_new = event.chain() == chain
+                    && last_block.map_or(true, |last| event.block_number() > last);
+                if is_new {
+                    last_block = Some(event.block_number());
+                }
+                let event_service = event_service.clone();
+                let chain = chain.clone();
+                async move {
+                    if !is_new {
+                        return None;
+                    }
+                    let finality_status = block_finality(event_service.as_ref(), &chain, event.block_number()).await;
+                    Some(ChainBlock {
+                        chain,
+                        number: event.block_number() as i64,
+                        hash: event.block_hash().to_string(),
+                        timestamp: chrono::DateTime::<chrono::Utc>::from(event.timestamp()),
+                        finality_status,
+                    })
+                }
+            });
+
+        Ok(blocks)
+    }
+
+    /// Stream the state of a Valence account every time it changes
+    async fn valence_account_changed(
+        &self,
+        ctx: &Context<'_>,
+        id: ID,
+    ) -> async_graphql::Result<impl Stream<Item = ValenceAccount>> {
+        let storage = valence_storage(ctx)?;
+        let account_id = id.to_string();
+
+        let states = poll_changes(VALENCE_POLL_INTERVAL, move || {
+            let storage = storage.clone();
+            let account_id = account_id.clone();
+            async move { storage.get_valence_account_state(&account_id).await }
+        });
+
+        Ok(states.map(|state| ValenceAccount::from(&state)))
+    }
+
+    /// Stream a processor message every time its status or processing details change
+    async fn processor_message_status(
+        &self,
+        ctx: &Context<'_>,
+        id: ID,
+    ) -> async_graphql::Result<impl Stream<Item = ProcessorMessage>> {
+        let storage = valence_storage(ctx)?;
+        let message_id = id.to_string();
+
+        let messages = poll_changes(VALENCE_POLL_INTERVAL, move || {
+            let storage = storage.clone();
+            let message_id = message_id.clone();
+            async move { storage.get_valence_processor_message(&message_id).await }
+        });
+
+        Ok(messages.map(|message| ProcessorMessage::from(&message)))
+    }
+}
+
+/// Turn an event subscription into a stream of events
+fn event_stream(subscription: Box<dyn EventSubscription>) -> impl Stream<Item = Box<dyn Event>> {
+    stream::unfold(subscription, |mut subscription| async move {
+        subscription.next().await.map(|event| (event, subscription))
+    })
+}
+
+/// Poll a value and yield it every time it differs from the previously yielded one
+fn poll_changes<T, F, Fut>(period: Duration, fetch: F) -> impl Stream<Item = T>
+where
+    T: PartialEq + Clone + Send + 'static,
+    F: FnMut() -> Fut + Send + 'static,
+    Fut: std::future::Future<Output = indexer_core::Result<Option<T>>> + Send,
+{
+    let interval = tokio::time::interval(period);
+
+    stream::unfold((fetch, interval, None::<T>), |(mut fetch, mut interval, mut last)| async move {
+        loop {
+            interval.tick().await;
+
+            match fetch().await {
+                Ok(Some(value)) if last.as_ref() != Some(&value) => {
+                    last = Some(value.clone());
+                    return Some((value, (fetch, interval, last)));
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Failed to poll Valence state: {}", e),
+            }
+        }
+    })
+}
+
+/// Finality status of a block, from the heights the event service reports
+/// for each status
+///
+/// Services that do not track finality report their head for every status,
+/// so only heights below the head are taken into account. Blocks past every
+/// tracked height, or whose heights cannot be read, are reported confirmed.
+async fn block_finality(service: &dyn BoxedEventServiceTrait, chain: &str, block: u64) -> GraphQLFinalityStatus {
+    let latest = match service.get_latest_block_with_status(chain, BlockStatus::Latest).await {
+        Ok(latest) => latest,
+        Err(e) => {
+            debug!("Failed to get latest block for {}: {}", chain, e);
+            return GraphQLFinalityStatus::Confirmed;
+        }
+    };
+
+    let statuses = [
+        (BlockStatus::Finalized, GraphQLFinalityStatus::Finalized),
+        (BlockStatus::Justified, GraphQLFinalityStatus::Justified),
+        (BlockStatus::Safe, GraphQLFinalityStatus::Safe),
+    ];
+    for (status, finality_status) in statuses {
+        match service.get_latest_block_with_status(chain, status).await {
+            Ok(height) if height < latest && block <= height => return finality_status,
+            Ok(_) => {}
+            Err(e) => debug!("Failed to get {} block for {}: {}", status.as_str(), chain, e),
+        }
+    }
+    GraphQLFinalityStatus::Confirmed
+}
+
+/// Check whether an event matches a GraphQL event filter
+///
+/// The filter is converted as it is for queries, so subscriptions apply the
+/// same chain, block range, time range, event type and attribute predicates.
+pub(super) fn event_matches_input(event: &dyn Event, filter: &EventFilterInput) -> bool {
+    let filter = EventFilter::from(filter);
+    filter.matches_event(event)
+        && filter.custom_filters.iter().all(|(key, value)| attribute_matches(event, key, value))
+}
+
+/// Check an attribute filter the way storage does: `address` against the
+/// emitting contract, ignoring case, and any other key against the fields
+/// decoded on ingestion
+fn attribute_matches(event: &dyn Event, key: &str, value: &str) -> bool {
+    if key == "address" {
+        return event_contract_address(event).is_some_and(|address| address.eq_ignore_ascii_case(value));
+    }
+
+    match event.decoded().and_then(|fields| fields.get(key)) {
+        Some(JsonValue::String(field)) => field == value,
+        Some(JsonValue::Null) | None => false,
+        Some(field) => field.to_string() == value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use indexer_core::types::ChainId;
+
+    #[derive(Debug)]
+    struct TestEvent {
+        chain: String,
+        block_number: u64,
+        event_type: String,
+        timestamp: SystemTime,
+        decoded: Option<serde_json::Map<String, JsonValue>>,
+    }
+
+    impl Event for TestEvent {
+        fn id(&self) -> &str { "test-event" }
+        fn chain(&self) -> &str { &self.chain }
+        fn block_number(&self) -> u64 { self.block_number }
+        fn block_hash(&self) -> &str { "0xblock" }
+        fn tx_hash(&self) -> &str { "0xtx" }
+        fn timestamp(&self) -> SystemTime { self.timestamp }
+        fn event_type(&self) -> &str { &self.event_type }
+        fn raw_data(&self) -> &[u8] { &[] }
+        fn decoded(&self) -> Option<&serde_json::Map<String, JsonValue>> { self.decoded.as_ref() }
+        fn as_any(&self) -> &dyn std::any::Any { self }
+    }
+
+    fn transfer() -> TestEvent {
+        TestEvent {
+            chain: "ethereum".to_string(),
+            block_number: 150,
+            event_type: "Transfer".to_string(),
+            // 2023-11-14T22:13:20Z
+            timestamp: UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+            decoded: serde_json::from_value(serde_json::json!({"from": "0x01", "value": 2500})).ok(),
+        }
+    }
+
+    fn filter(chain: Option<&str>, block_range: Option<Vec<i64>>, event_types: Option<Vec<&str>>) -> EventFilterInput {
+        EventFilterInput {
+            chain: chain.map(|c| c.to_string()),
+            block_range,
+            time_range: None,
+            event_types: event_types.map(|types| types.into_iter().map(|t| t.to_string()).collect()),
+            limit: None,
+            offset: None,
+            attributes: None,
+        }
+    }
+
+    #[test]
+    fn test_event_matches_input() {
+        let event = transfer();
+
+        assert!(event_matches_input(&event, &filter(None, None, None)));
+        assert!(event_matches_input(&event, &filter(Some("ethereum"), Some(vec![100, 200]), Some(vec!["Transfer"]))));
+        assert!(event_matches_input(&event, &filter(None, Some(vec![150]), None)));
+
+        assert!(!event_matches_input(&event, &filter(Some("polygon"), None, None)));
+        assert!(!event_matches_input(&event, &filter(None, Some(vec![151, 200]), None)));
+        assert!(!event_matches_input(&event, &filter(None, Some(vec![100, 149]), None)));
+        assert!(!event_matches_input(&event, &filter(None, None, Some(vec!["Approval"]))));
+    }
+
+    #[test]
+    fn test_event_matches_time_range() {
+        let event = transfer();
+        let time_range = |range: &[&str]| EventFilterInput {
+            time_range: Some(range.iter().map(|t| t.to_string()).collect()),
+            ..filter(None, None, None)
+        };
+
+        assert!(event_matches_input(&event, &time_range(&["2023-11-14T00:00:00Z", "2023-11-15T00:00:00Z"])));
+        assert!(event_matches_input(&event, &time_range(&["2023-11-14T22:13:20Z"])));
+        assert!(!event_matches_input(&event, &time_range(&["2023-11-15T00:00:00Z"])));
+        assert!(!event_matches_input(&event, &time_range(&["2023-11-01T00:00:00Z", "2023-11-14T22:13:19Z"])));
+    }
+
+    #[test]
+    fn test_event_matches_attributes() {
+        let event = transfer();
+        let attributes = |attributes: JsonValue| EventFilterInput {
+            attributes: Some(attributes),
+            ..filter(None, None, None)
+        };
+
+        assert!(event_matches_input(&event, &attributes(serde_json::json!({"from": "0x01"}))));
+        assert!(event_matches_input(&event, &attributes(serde_json::json!({"from": "0x01", "value": "2500"}))));
+        assert!(!event_matches_input(&event, &attributes(serde_json::json!({"from": "0x02"}))));
+        assert!(!event_matches_input(&event, &attributes(serde_json::json!({"to": "0x01"}))));
+
+        let undecoded = TestEvent { decoded: None, ..transfer() };
+        assert!(!event_matches_input(&undecoded, &attributes(serde_json::json!({"from": "0x01"}))));
+    }
+
+    #[test]
+    fn test_event_matches_contract_address() {
+        let event = indexer_core::event::UnifiedEvent {
+            id: "0xtx:0".to_string(),
+            chain: "ethereum".to_string(),
+            block_number: 150,
+            block_hash: "0xblock".to_string(),
+            tx_hash: "0xtx".to_string(),
+            timestamp: UNIX_EPOCH,
+            event_type: "Transfer".to_string(),
+            event_data: indexer_core::event::EventData::Evm {
+                topics: Vec::new(),
+                data: "0x".to_string(),
+                address: "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48".to_string(),
+                log_index: 0,
+            },
+            raw_data: Vec::new(),
+            decoded: None,
+        };
+        let address = |address: &str| EventFilterInput {
+            attributes: Some(serde_json::json!({ "address": address })),
+            ..filter(None, None, None)
+        };
+
+        assert!(event_matches_input(&event, &address("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48")));
+        assert!(!event_matches_input(&event, &address("0xdAC17F958D2ee523a2206206994597C13D831ec7")));
+    }
+
+    /// Event service reporting fixed heights for each block status
+    struct FinalityService {
+        chain_id: ChainId,
+        heights: HashMap<&'static str, u64>,
+    }
+
+    #[async_trait::async_trait]
+    impl BoxedEventServiceTrait for FinalityService {
+        fn chain_id(&self) -> &ChainId {
+            &self.chain_id
+        }
+
+        async fn get_latest_block_with_status(&self, _chain: &str, status: BlockStatus) -> indexer_core::Result<u64> {
+            self.heights.get(status.as_str()).copied()
+                .ok_or_else(|| indexer_core::Error::generic("status not tracked"))
+        }
+
+        async fn get_events(&self, _filters: Vec<EventFilter>) -> indexer_core::Result<Vec<Box<dyn Event>>> {
+            Ok(Vec::new())
+        }
+
+        async fn subscribe(&self) -> indexer_core::Result<Box<dyn EventSubscription>> {
+            Err(indexer_core::Error::generic("no live events"))
+        }
+
+        async fn get_latest_block(&self) -> indexer_core::Result<u64> {
+            Ok(self.heights["latest"])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_block_finality() {
+        let service = FinalityService {
+            chain_id: ChainId("ethereum".to_string()),
+            heights: HashMap::from([("latest", 200), ("safe", 180), ("justified", 160), ("finalized", 150)]),
+        };
+        let status = |block| block_finality(&service, "ethereum", block);
+
+        assert!(status(100).await == GraphQLFinalityStatus::Finalized);
+        assert!(status(150).await == GraphQLFinalityStatus::Finalized);
+        assert!(status(151).await == GraphQLFinalityStatus::Justified);
+        assert!(status(170).await == GraphQLFinalityStatus::Safe);
+        assert!(status(181).await == GraphQLFinalityStatus::Confirmed);
+
+        // Without finality tracking every status reports the head
+        let untracked = FinalityService {
+            chain_id: ChainId("ethereum".to_string()),
+            heights: HashMap::from([("latest", 200), ("safe", 200), ("justified", 200), ("finalized", 200)]),
+        };
+        assert!(block_finality(&untracked, "ethereum", 100).await == GraphQLFinalityStatus::Confirmed);
+
+        // Statuses the service cannot report are skipped
+        let finalized_only = FinalityService {
+            chain_id: ChainId("ethereum".to_string()),
+            heights: HashMap::from([("latest", 200), ("finalized", 150)]),
+        };
+        assert!(block_finality(&finalized_only, "ethereum", 120).await == GraphQLFinalityStatus::Finalized);
+        assert!(block_finality(&finalized_only, "ethereum", 170).await == GraphQLFinalityStatus::Confirmed);
+    }
+
+    #[tokio::test]
+    async fn test_poll_changes_yields_only_changes() {
+        let values = std::sync::Arc::new(std::sync::Mutex::new(vec![
+            Ok(None),
+            Ok(Some(1)),
+            Ok(Some(1)),
+            Err(indexer_core::Error::generic("transient")),
+            Ok(Some(2)),
+        ]));
+
+        let source = values.clone();
+        let changes = poll_changes(Duration::from_millis(1), move || {
+            let mut pending = source.lock().unwrap();
+            let next = if pending.is_empty() { Ok(Some(2)) } else { pending.remove(0) };
+            async move { next }
+        });
+
+        let collected: Vec<i32> = changes.take(2).collect().await;
+        assert_eq!(collected, vec![1, 2]);
+        assert!(values.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,774 @@
+/// GraphQL types for Valence contract state
+use async_graphql::{ComplexObject, Context, Enum, SimpleObject, ID};
+use serde_json::Value as JsonValue;
+
+use indexer_storage::{
+    BoxedStorage, ValenceAccountState, ValenceAuthorizationDecision, ValenceAuthorizationGrant,
+    ValenceAuthorizationRequest, ValenceAuthorizationState, ValenceLibraryApproval, ValenceLibraryState,
+    ValenceLibraryUsage, ValenceLibraryVersion, ValenceMessageStatus, ValenceProcessorConfig,
+    ValenceProcessorMessage, ValenceProcessorState,
+};
+
+use super::limits::{list_cost, UNBOUNDED_LIST_COST};
+use super::AppState;
+
+/// Get the storage backing Valence queries and subscriptions
+pub(super) fn valence_storage(ctx: &Context<'_>) -> async_graphql::Result<BoxedStorage> {
+    let state = ctx.data::<AppState>()?;
+    state.storage.clone()
+        .ok_or_else(|| async_graphql::Error::new("Valence state storage is not configured"))
+}
+
+/// Map a storage error into a GraphQL error
+pub(super) fn storage_error(e: indexer_core::Error) -> async_graphql::Error {
+    async_graphql::Error::new(format!("Failed to query Valence state: {}", e))
+}
+
+/// Convert optional GraphQL pagination arguments into storage arguments
+fn page(limit: Option<i32>, offset: Option<i32>) -> (Option<usize>, Option<usize>) {
+    (limit.map(|l| l.max(0) as usize), offset.map(|o| o.max(0) as usize))
+}
+
+/// Valence account state
+#[derive(SimpleObject, Clone)]
+#[graphql(complex)]
+pub struct ValenceAccount {
+    /// Account ID (e.g. "<chain_id>:<contract_address>")
+    id: ID,
+    /// Chain ID
+    chain_id: String,
+    /// Contract address
+    address: String,
+    /// Current owner
+    current_owner: Option<String>,
+    /// Pending owner
+    pending_owner: Option<String>,
+    /// Expiry of the pending ownership transfer
+    pending_owner_expiry: Option<i64>,
+    /// Approved library addresses
+    libraries: Vec<String>,
+    /// Block of the last update
+    last_update_block: i64,
+    /// Transaction of the last update
+    last_update_tx: String,
+    /// Block the account was read at, if not its current state
+    #[graphql(skip)]
+    at_block: Option<u64>,
+}
+
+impl ValenceAccount {
+    /// The account as read at `block`, so that its library approvals are
+    /// also those in effect at that block
+    pub(super) fn as_of(mut self, block: Option<u64>) -> Self {
+        self.at_block = block;
+        self
+    }
+}
+
+impl From<&ValenceAccountState> for ValenceAccount {
+    fn from(state: &ValenceAccountState) -> Self {
+        Self {
+            id: ID(state.account_id.clone()),
+            chain_id: state.chain_id.clone(),
+            address: state.address.clone(),
+            current_owner: state.current_owner.clone(),
+            pending_owner: state.pending_owner.clone(),
+            pending_owner_expiry: state.pending_owner_expiry.map(|v| v as i64),
+            libraries: state.libraries.clone(),
+            last_update_block: state.last_update_block as i64,
+            last_update_tx: state.last_update_tx.clone(),
+            at_block: None,
+        }
+    }
+}
+
+/// Processor message status
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum GraphQLMessageStatus {
+    /// Waiting to be processed
+    Pending,
+    /// Being processed
+    Processing,
+    /// Processed successfully
+    Completed,
+    /// Processing failed
+    Failed,
+    /// Timed out before processing
+    TimedOut,
+}
+
+#[ComplexObject]
+impl ValenceAccount {
+    /// Library approvals granted by this account, as they were at the block
+    /// the account was read at
+    #[graphql(complexity = "UNBOUNDED_LIST_COST * child_complexity")]
+    async fn approved_libraries(
+        &self,
+        ctx: &Context<'_>,
+        active_only: Option<bool>,
+    ) -> async_graphql::Result<Vec<LibraryApproval>> {
+        let storage = valence_storage(ctx)?;
+        let approvals = storage.get_valence_libraries_for_account(self.id.as_str()).await
+            .map_err(storage_error)?;
+
+        Ok(approvals_at(&approvals, self.at_block, active_only.unwrap_or(true)))
+    }
+}
+
+/// Approvals as they were at `block`, or as they are now without one
+///
+/// Approvals made after the block are left out, and those revoked after it
+/// are reported as still active.
+fn approvals_at(approvals: &[ValenceLibraryApproval], block: Option<u64>, active_only: bool) -> Vec<LibraryApproval> {
+    approvals.iter()
+        .filter(|approval| block.map_or(true, |block| approval.approved_at_block <= block))
+        .map(|approval| {
+            let mut approval = LibraryApproval::from(approval);
+            if let (Some(block), Some(revoked)) = (block, approval.revoked_at_block) {
+                if revoked > block as i64 {
+                    approval.is_active = true;
+                    approval.revoked_at_block = None;
+                    approval.revoked_at_tx = None;
+                }
+            }
+            approval
+        })
+        .filter(|approval| approval.is_active || !active_only)
+        .collect()
+}
+
+impl From<&ValenceMessageStatus> for GraphQLMessageStatus {
+    fn from(status: &ValenceMessageStatus) -> Self {
+        match status {
+            ValenceMessageStatus::Pending => GraphQLMessageStatus::Pending,
+            ValenceMessageStatus::Processing => GraphQLMessageStatus::Processing,
+            ValenceMessageStatus::Completed => GraphQLMessageStatus::Completed,
+            ValenceMessageStatus::Failed => GraphQLMessageStatus::Failed,
+            ValenceMessageStatus::TimedOut => GraphQLMessageStatus::TimedOut,
+        }
+    }
+}
+
+impl From<GraphQLMessageStatus> for ValenceMessageStatus {
+    fn from(status: GraphQLMessageStatus) -> Self {
+        match status {
+            GraphQLMessageStatus::Pending => ValenceMessageStatus::Pending,
+            GraphQLMessageStatus::Processing => ValenceMessageStatus::Processing,
+            GraphQLMessageStatus::Completed => ValenceMessageStatus::Completed,
+            GraphQLMessageStatus::Failed => ValenceMessageStatus::Failed,
+            GraphQLMessageStatus::TimedOut => ValenceMessageStatus::TimedOut,
+        }
+    }
+}
+
+/// Cross-chain message submitted to a Valence processor
+#[derive(SimpleObject, Clone)]
+pub struct ProcessorMessage {
+    /// Message ID
+    id: ID,
+    /// Processor ID
+    processor_id: String,
+    /// Chain where the message originated
+    source_chain_id: String,
+    /// Chain where the message is processed
+    target_chain_id: String,
+    /// Address that submitted the message
+    sender_address: String,
+    /// Encoded message payload
+    payload: String,
+    /// Current status
+    status: GraphQLMessageStatus,
+    /// Block the message was created in
+    created_at_block: i64,
+    /// Transaction the message was created in
+    created_at_tx: String,
+    /// Block of the last update
+    last_updated_block: i64,
+    /// Block the message was processed in
+    processed_at_block: Option<i64>,
+    /// Transaction the message was processed in
+    processed_at_tx: Option<String>,
+    /// Number of retries so far
+    retry_count: i32,
+    /// Block of the next retry
+    next_retry_block: Option<i64>,
+    /// Gas used for processing
+    gas_used: Option<i64>,
+    /// Error if processing failed
+    error: Option<String>,
+}
+
+impl From<&ValenceProcessorMessage> for ProcessorMessage {
+    fn from(message: &ValenceProcessorMessage) -> Self {
+        Self {
+            id: ID(message.id.clone()),
+            processor_id: message.processor_id.clone(),
+            source_chain_id: message.source_chain_id.clone(),
+            target_chain_id: message.target_chain_id.clone(),
+            sender_address: message.sender_address.clone(),
+            payload: message.payload.clone(),
+            status: GraphQLMessageStatus::from(&message.status),
+            created_at_block: message.created_at_block as i64,
+            created_at_tx: message.created_at_tx.clone(),
+            last_updated_block: message.last_updated_block as i64,
+            processed_at_block: message.processed_at_block.map(|v| v as i64),
+            processed_at_tx: message.processed_at_tx.clone(),
+            retry_count: message.retry_count as i32,
+            next_retry_block: message.next_retry_block.map(|v| v as i64),
+            gas_used: message.gas_used.map(|v| v as i64),
+            error: message.error.clone(),
+        }
+    }
+}
+
+/// Valence processor configuration
+#[derive(SimpleObject, Clone)]
+pub struct ProcessorConfig {
+    /// Maximum gas per message
+    max_gas_per_message: Option<i64>,
+    /// Blocks after which a message times out
+    message_timeout_blocks: Option<i64>,
+    /// Blocks between retries
+    retry_interval_blocks: Option<i64>,
+    /// Maximum number of retries
+    max_retry_count: Option<i32>,
+    /// Whether the processor is paused
+    paused: bool,
+}
+
+impl From<&ValenceProcessorConfig> for ProcessorConfig {
+    fn from(config: &ValenceProcessorConfig) -> Self {
+        Self {
+            max_gas_per_message: config.max_gas_per_message.map(|v| v as i64),
+            message_timeout_blocks: config.message_timeout_blocks.map(|v| v as i64),
+            retry_interval_blocks: config.retry_interval_blocks.map(|v| v as i64),
+            max_retry_count: config.max_retry_count.map(|v| v as i32),
+            paused: config.paused,
+        }
+    }
+}
+
+/// Valence processor state
+#[derive(SimpleObject, Clone)]
+#[graphql(complex)]
+pub struct ValenceProcessor {
+    /// Processor ID
+    id: ID,
+    /// Chain ID
+    chain_id: String,
+    /// Contract address
+    address: String,
+    /// Current owner
+    owner: Option<String>,
+    /// Processor configuration
+    config: Option<ProcessorConfig>,
+    /// Number of pending messages
+    pending_message_count: i64,
+    /// Number of completed messages
+    completed_message_count: i64,
+    /// Number of failed messages
+    failed_message_count: i64,
+    /// Block of the last update
+    last_update_block: i64,
+    /// Transaction of the last update
+    last_update_tx: String,
+}
+
+impl From<&ValenceProcessorState> for ValenceProcessor {
+    fn from(state: &ValenceProcessorState) -> Self {
+        Self {
+            id: ID(state.processor_id.clone()),
+            chain_id: state.chain_id.clone(),
+            address: state.address.clone(),
+            owner: state.owner.clone(),
+            config: state.config.as_ref().map(ProcessorConfig::from),
+            pending_message_count: state.pending_message_count as i64,
+            completed_message_count: state.completed_message_count as i64,
+            failed_message_count: state.failed_message_count as i64,
+            last_update_block: state.last_update_block as i64,
+            last_update_tx: state.last_update_tx.clone(),
+        }
+    }
+}
+
+#[ComplexObject]
+impl ValenceProcessor {
+    /// Messages submitted to this processor, newest first
+    #[graphql(complexity = "list_cost(limit, 100, child_complexity)")]
+    async fn messages(
+        &self,
+        ctx: &Context<'_>,
+        status: Option<GraphQLMessageStatus>,
+        limit: Option<i32>,
+        offset: Option<i32>,
+    ) -> async_graphql::Result<Vec<ProcessorMessage>> {
+        let storage = valence_storage(ctx)?;
+        let (limit, offset) = page(limit, offset);
+        let messages = storage
+            .get_valence_processor_messages(self.id.as_str(), status.map(ValenceMessageStatus::from), limit, offset)
+            .await
+            .map_err(storage_error)?;
+
+        Ok(messages.iter().map(ProcessorMessage::from).collect())
+    }
+}
+
+/// Authorization request decision
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum GraphQLAuthorizationDecision {
+    /// Not decided yet
+    Pending,
+    /// Request approved
+    Approved,
+    /// Request denied
+    Denied,
+    /// Request could not be evaluated
+    Error,
+}
+
+impl From<&ValenceAuthorizationDecision> for GraphQLAuthorizationDecision {
+    fn from(decision: &ValenceAuthorizationDecision) -> Self {
+        match decision {
+            ValenceAuthorizationDecision::Pending => GraphQLAuthorizationDecision::Pending,
+            ValenceAuthorizationDecision::Approved => GraphQLAuthorizationDecision::Approved,
+            ValenceAuthorizationDecision::Denied => GraphQLAuthorizationDecision::Denied,
+            ValenceAuthorizationDecision::Error => GraphQLAuthorizationDecision::Error,
+        }
+    }
+}
+
+impl From<GraphQLAuthorizationDecision> for ValenceAuthorizationDecision {
+    fn from(decision: GraphQLAuthorizationDecision) -> Self {
+        match decision {
+            GraphQLAuthorizationDecision::Pending => ValenceAuthorizationDecision::Pending,
+            GraphQLAuthorizationDecision::Approved => ValenceAuthorizationDecision::Approved,
+            GraphQLAuthorizationDecision::Denied => ValenceAuthorizationDecision::Denied,
+            GraphQLAuthorizationDecision::Error => ValenceAuthorizationDecision::Error,
+        }
+    }
+}
+
+/// Permission grant issued by an authorization contract
+#[derive(SimpleObject, Clone)]
+pub struct AuthorizationGrant {
+    /// Grant ID
+    id: ID,
+    /// Authorization contract ID
+    auth_id: String,
+    /// Address of the grantee
+    grantee: String,
+    /// Granted permissions
+    permissions: Vec<String>,
+    /// Resources the permissions apply to
+    resources: Vec<String>,
+    /// Block the grant was issued in
+    granted_at_block: i64,
+    /// Transaction the grant was issued in
+    granted_at_tx: String,
+    /// Expiry of the grant
+    expiry: Option<i64>,
+    /// Whether the grant is still active
+    is_active: bool,
+    /// Block the grant was revoked in
+    revoked_at_block: Option<i64>,
+    /// Transaction the grant was revoked in
+    revoked_at_tx: Option<String>,
+}
+
+impl From<&ValenceAuthorizationGrant> for AuthorizationGrant {
+    fn from(grant: &ValenceAuthorizationGrant) -> Self {
+        Self {
+            id: ID(grant.id.clone()),
+            auth_id: grant.auth_id.clone(),
+            grantee: grant.grantee.clone(),
+            permissions: grant.permissions.clone(),
+            resources: grant.resources.clone(),
+            granted_at_block: grant.granted_at_block as i64,
+            granted_at_tx: grant.granted_at_tx.clone(),
+            expiry: grant.expiry.map(|v| v as i64),
+            is_active: grant.is_active,
+            revoked_at_block: grant.revoked_at_block.map(|v| v as i64),
+            revoked_at_tx: grant.revoked_at_tx.clone(),
+        }
+    }
+}
+
+/// Request evaluated by an authorization contract
+#[derive(SimpleObject, Clone)]
+pub struct AuthorizationRequest {
+    /// Request ID
+    id: ID,
+    /// Authorization contract ID
+    auth_id: String,
+    /// Address requesting authorization
+    requester: String,
+    /// Requested action
+    action: String,
+    /// Resource to act upon
+    resource: String,
+    /// Additional request data
+    request_data: Option<String>,
+    /// Decision on the request
+    decision: GraphQLAuthorizationDecision,
+    /// Block the request was made in
+    requested_at_block: i64,
+    /// Transaction the request was made in
+    requested_at_tx: String,
+    /// Block the request was processed in
+    processed_at_block: Option<i64>,
+    /// Transaction the request was processed in
+    processed_at_tx: Option<String>,
+    /// Reason for the decision
+    reason: Option<String>,
+}
+
+impl From<&ValenceAuthorizationRequest> for AuthorizationRequest {
+    fn from(request: &ValenceAuthorizationRequest) -> Self {
+        Self {
+            id: ID(request.id.clone()),
+            auth_id: request.auth_id.clone(),
+            requester: request.requester.clone(),
+            action: request.action.clone(),
+            resource: request.resource.clone(),
+            request_data: request.request_data.clone(),
+            decision: GraphQLAuthorizationDecision::from(&request.decision),
+            requested_at_block: request.requested_at_block as i64,
+            requested_at_tx: request.requested_at_tx.clone(),
+            processed_at_block: request.processed_at_block.map(|v| v as i64),
+            processed_at_tx: request.processed_at_tx.clone(),
+            reason: request.reason.clone(),
+        }
+    }
+}
+
+/// Valence authorization contract state
+#[derive(SimpleObject, Clone)]
+#[graphql(complex)]
+pub struct ValenceAuthorization {
+    /// Authorization contract ID
+    id: ID,
+    /// Chain ID
+    chain_id: String,
+    /// Contract address
+    address: String,
+    /// Current owner
+    current_owner: Option<String>,
+    /// Currently active policy ID
+    active_policy_id: Option<String>,
+    /// Block of the last update
+    last_update_block: i64,
+    /// Transaction of the last update
+    last_update_tx: String,
+    #[graphql(skip)]
+    active_grants: Vec<AuthorizationGrant>,
+}
+
+impl From<&ValenceAuthorizationState> for ValenceAuthorization {
+    fn from(state: &ValenceAuthorizationState) -> Self {
+        Self {
+            id: ID(state.auth_id.clone()),
+            chain_id: state.chain_id.clone(),
+            address: state.address.clone(),
+            current_owner: state.current_owner.clone(),
+            active_policy_id: state.active_policy_id.clone(),
+            last_update_block: state.last_update_block as i64,
+            last_update_tx: state.last_update_tx.clone(),
+            active_grants: state.active_grants.iter().map(AuthorizationGrant::from).collect(),
+        }
+    }
+}
+
+#[ComplexObject]
+impl ValenceAuthorization {
+    /// Grants issued by this contract, only active ones by default
+    #[graphql(complexity = "UNBOUNDED_LIST_COST * child_complexity")]
+    async fn grants(
+        &self,
+        ctx: &Context<'_>,
+        active_only: Option<bool>,
+    ) -> async_graphql::Result<Vec<AuthorizationGrant>> {
+        if active_only.unwrap_or(true) {
+            return Ok(self.active_grants.clone());
+        }
+
+        let storage = valence_storage(ctx)?;
+        let grants = storage.get_valence_authorization_grants(self.id.as_str(), false).await
+            .map_err(storage_error)?;
+
+        Ok(grants.iter().map(AuthorizationGrant::from).collect())
+    }
+
+    /// Requests evaluated by this contract, newest first
+    #[graphql(complexity = "list_cost(limit, 100, child_complexity)")]
+    async fn requests(
+        &self,
+        ctx: &Context<'_>,
+        decision: Option<GraphQLAuthorizationDecision>,
+        limit: Option<i32>,
+        offset: Option<i32>,
+    ) -> async_graphql::Result<Vec<AuthorizationRequest>> {
+        let storage = valence_storage(ctx)?;
+        let (limit, offset) = page(limit, offset);
+        let requests = storage
+            .get_valence_authorization_requests(self.id.as_str(), decision.map(ValenceAuthorizationDecision::from), limit, offset)
+            .await
+            .map_err(storage_error)?;
+
+        Ok(requests.iter().map(AuthorizationRequest::from).collect())
+    }
+}
+
+/// Version of a Valence library
+#[derive(SimpleObject, Clone)]
+pub struct LibraryVersion {
+    /// Version ID
+    id: ID,
+    /// Library ID
+    library_id: String,
+    /// Version number
+    version: i32,
+    /// Hash of the version code
+    code_hash: String,
+    /// Block the version was published in
+    created_at_block: i64,
+    /// Transaction the version was published in
+    created_at_tx: String,
+    /// Whether this is the current version
+    is_active: bool,
+    /// Features supported by this version
+    features: Vec<String>,
+    /// Additional version metadata
+    metadata: Option<JsonValue>,
+}
+
+impl From<&ValenceLibraryVersion> for LibraryVersion {
+    fn from(version: &ValenceLibraryVersion) -> Self {
+        Self {
+            id: ID(version.id.clone()),
+            library_id: version.library_id.clone(),
+            version: version.version as i32,
+            code_hash: version.code_hash.clone(),
+            created_at_block: version.created_at_block as i64,
+            created_at_tx: version.created_at_tx.clone(),
+            is_active: version.is_active,
+            features: version.features.clone(),
+            metadata: version.metadata.clone(),
+        }
+    }
+}
+
+/// Call into a Valence library
+#[derive(SimpleObject, Clone)]
+pub struct LibraryUsage {
+    /// Usage ID
+    id: ID,
+    /// Library ID
+    library_id: String,
+    /// Address calling the library
+    user_address: String,
+    /// Valence account making the call, if any
+    account_id: Option<String>,
+    /// Function called, if known
+    function_name: Option<String>,
+    /// Block of the call
+    usage_at_block: i64,
+    /// Transaction of the call
+    usage_at_tx: String,
+    /// Gas used by the call
+    gas_used: Option<i64>,
+    /// Whether the call succeeded
+    success: bool,
+    /// Error if the call failed
+    error: Option<String>,
+}
+
+impl From<&ValenceLibraryUsage> for LibraryUsage {
+    fn from(usage: &ValenceLibraryUsage) -> Self {
+        Self {
+            id: ID(usage.id.clone()),
+            library_id: usage.library_id.clone(),
+            user_address: usage.user_address.clone(),
+            account_id: usage.account_id.clone(),
+            function_name: usage.function_name.clone(),
+            usage_at_block: usage.usage_at_block as i64,
+            usage_at_tx: usage.usage_at_tx.clone(),
+            gas_used: usage.gas_used.map(|v| v as i64),
+            success: usage.success,
+            error: usage.error.clone(),
+        }
+    }
+}
+
+/// Approval of a library by a Valence account
+#[derive(SimpleObject, Clone)]
+#[graphql(complex)]
+pub struct LibraryApproval {
+    /// Approval ID
+    id: ID,
+    /// Approved library ID
+    library_id: String,
+    /// Approving account ID
+    account_id: String,
+    /// Block the approval was made in
+    approved_at_block: i64,
+    /// Transaction the approval was made in
+    approved_at_tx: String,
+    /// Whether the approval is still active
+    is_active: bool,
+    /// Block the approval was revoked in
+    revoked_at_block: Option<i64>,
+    /// Transaction the approval was revoked in
+    revoked_at_tx: Option<String>,
+}
+
+impl From<&ValenceLibraryApproval> for LibraryApproval {
+    fn from(approval: &ValenceLibraryApproval) -> Self {
+        Self {
+            id: ID(approval.id.clone()),
+            library_id: approval.library_id.clone(),
+            account_id: approval.account_id.clone(),
+            approved_at_block: approval.approved_at_block as i64,
+            approved_at_tx: approval.approved_at_tx.clone(),
+            is_active: approval.is_active,
+            revoked_at_block: approval.revoked_at_block.map(|v| v as i64),
+            revoked_at_tx: approval.revoked_at_tx.clone(),
+        }
+    }
+}
+
+#[ComplexObject]
+impl LibraryApproval {
+    /// The approved library
+    async fn library(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<ValenceLibrary>> {
+        let storage = valence_storage(ctx)?;
+        let state = storage.get_valence_library_state(&self.library_id).await
+            .map_err(storage_error)?;
+
+        Ok(state.as_ref().map(ValenceLibrary::from))
+    }
+}
+
+/// Valence library state
+#[derive(SimpleObject, Clone)]
+#[graphql(complex)]
+pub struct ValenceLibrary {
+    /// Library ID
+    id: ID,
+    /// Chain ID
+    chain_id: String,
+    /// Contract address
+    address: String,
+    /// Type of library (e.g. "swap", "bridge")
+    library_type: String,
+    /// Current owner
+    current_owner: Option<String>,
+    /// Current version number
+    current_version: Option<i32>,
+    /// Block of the last update
+    last_update_block: i64,
+    /// Transaction of the last update
+    last_update_tx: String,
+}
+
+impl From<&ValenceLibraryState> for ValenceLibrary {
+    fn from(state: &ValenceLibraryState) -> Self {
+        Self {
+            id: ID(state.library_id.clone()),
+            chain_id: state.chain_id.clone(),
+            address: state.address.clone(),
+            library_type: state.library_type.clone(),
+            current_owner: state.current_owner.clone(),
+            current_version: state.current_version.map(|v| v as i32),
+            last_update_block: state.last_update_block as i64,
+            last_update_tx: state.last_update_tx.clone(),
+        }
+    }
+}
+
+#[ComplexObject]
+impl ValenceLibrary {
+    /// All published versions of this library
+    #[graphql(complexity = "UNBOUNDED_LIST_COST * child_complexity")]
+    async fn versions(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<LibraryVersion>> {
+        let storage = valence_storage(ctx)?;
+        let versions = storage.get_valence_library_versions(self.id.as_str()).await
+            .map_err(storage_error)?;
+
+        Ok(versions.iter().map(LibraryVersion::from).collect())
+    }
+
+    /// Accounts that approved this library
+    #[graphql(complexity = "UNBOUNDED_LIST_COST * child_complexity")]
+    async fn approvals(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<LibraryApproval>> {
+        let storage = valence_storage(ctx)?;
+        let approvals = storage.get_valence_library_approvals(self.id.as_str()).await
+            .map_err(storage_error)?;
+
+        Ok(approvals.iter().map(LibraryApproval::from).collect())
+    }
+
+    /// Usage history of this library, newest first
+    #[graphql(complexity = "list_cost(limit, 100, child_complexity)")]
+    async fn usage(
+        &self,
+        ctx: &Context<'_>,
+        limit: Option<i32>,
+        offset: Option<i32>,
+    ) -> async_graphql::Result<Vec<LibraryUsage>> {
+        let storage = valence_storage(ctx)?;
+        let (limit, offset) = page(limit, offset);
+        let usage = storage.get_valence_library_usage_history(self.id.as_str(), limit, offset).await
+            .map_err(storage_error)?;
+
+        Ok(usage.iter().map(LibraryUsage::from).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approval(library_id: &str, approved_at_block: u64, revoked_at_block: Option<u64>) -> ValenceLibraryApproval {
+        ValenceLibraryApproval {
+            id: format!("account:{}", library_id),
+            library_id: library_id.to_string(),
+            account_id: "account".to_string(),
+            approved_at_block,
+            approved_at_tx: format!("0xapprove{}", library_id),
+            is_active: revoked_at_block.is_none(),
+            revoked_at_block,
+            revoked_at_tx: revoked_at_block.map(|_| format!("0xrevoke{}", library_id)),
+        }
+    }
+
+    fn library_ids(approvals: &[LibraryApproval]) -> Vec<&str> {
+        approvals.iter().map(|approval| approval.library_id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_approvals_at_block() {
+        let approvals = vec![
+            approval("kept", 10, None),
+            approval("revoked-later", 10, Some(30)),
+            approval("revoked-before", 5, Some(15)),
+            approval("approved-later", 25, None),
+        ];
+
+        let current = approvals_at(&approvals, None, true);
+        assert_eq!(library_ids(&current), vec!["kept", "approved-later"]);
+
+        // At block 20 the library revoked at 30 was still approved
+        let at_block = approvals_at(&approvals, Some(20), true);
+        assert_eq!(library_ids(&at_block), vec!["kept", "revoked-later"]);
+        assert!(at_block[1].is_active);
+        assert_eq!(at_block[1].revoked_at_block, None);
+        assert_eq!(at_block[1].revoked_at_tx, None);
+
+        let all = approvals_at(&approvals, Some(20), false);
+        assert_eq!(library_ids(&all), vec!["kept", "revoked-later", "revoked-before"]);
+        assert!(!all[2].is_active);
+
+        // Revoked in the block itself
+        assert_eq!(library_ids(&approvals_at(&approvals, Some(30), true)), vec!["kept", "approved-later"]);
+    }
+}
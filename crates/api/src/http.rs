@@ -20,13 +20,16 @@ use tracing::{debug, info};
 
 use indexer_core::{Error, Result, BlockStatus};
 use indexer_core::event::Event;
+use indexer_core::reorg::BoxedReorgDetector;
 use indexer_core::service::BoxedEventService;
 use indexer_core::types::{
     ChainId, EventFilter as CoreEventFilter, TextSearchConfig, TextSearchMode,
     AggregationConfig, AggregationResult, AggregationFunction, TimePeriod
 };
 use indexer_core::security::RateLimiter;
-use crate::{ContractSchemaRegistry, auth::AuthState};
+use indexer_causality::{ExportDirection, ExportFormat, ExportOptions, DEFAULT_EXPORT_DEPTH};
+use crate::{ContractSchemaRegistry, auth::AuthState, websocket::ConnectionManager};
+use crate::graphql::SharedCrossChainCausality;
 
 /// HTTP server state
 #[derive(Clone)]
@@ -41,6 +44,10 @@ pub struct HttpState {
     pub rate_limiter: Arc<RateLimiter>,
     /// Server start time for uptime calculation
     pub start_time: SystemTime,
+    /// WebSocket connection manager shared by every connection on this server
+    pub connections: ConnectionManager,
+    /// Cross-chain causality tracker backing the graph export endpoint
+    pub cross_chain: Option<SharedCrossChainCausality>,
 }
 
 impl AsRef<HttpState> for HttpState {
@@ -139,6 +146,21 @@ pub struct EventFilterRequest {
     pub max_search_results: Option<usize>,
 }
 
+/// Query parameters for causality graph exports
+#[derive(Debug, Deserialize)]
+pub struct CausalityExportQuery {
+    /// Output format: dot, graphml or json (default dot)
+    pub format: Option<String>,
+    /// Maximum number of hops from the root event
+    pub depth: Option<usize>,
+    /// Side of the root to follow: forward, backward or both
+    pub direction: Option<String>,
+    /// Leave out events before this Unix timestamp
+    pub since: Option<u64>,
+    /// Leave out events after this Unix timestamp
+    pub until: Option<u64>,
+}
+
 /// Query parameters for GET endpoints
 #[derive(Debug, Deserialize)]
 pub struct EventsQuery {
@@ -216,12 +238,13 @@ impl From<Error> for ApiError {
 }
 
 /// Convert core event to API response
+///
+/// Attributes are the fields decoded on ingestion with the contract's
+/// registered schema, and empty for events that weren't decoded.
 fn event_to_response(event: &dyn Event) -> EventResponse {
-    let attributes = HashMap::new();
-    
-    // Extract basic attributes from the event
-    // This is a simplified implementation - in practice, you'd want to 
-    // properly parse event-specific attributes
+    let attributes = event.decoded()
+        .map(|fields| fields.clone().into_iter().collect())
+        .unwrap_or_default();
     
     EventResponse {
         chain_id: event.chain().to_string(),
@@ -320,7 +343,7 @@ async fn rate_limit_middleware<B>(
         })).into_response();
         
         let headers = response.headers_mut();
-        headers.insert("X-RateLimit-Limit", "1000".parse().unwrap());
+        headers.insert("X-RateLimit-Limit", state.rate_limiter.max_requests().into());
         headers.insert("X-RateLimit-Remaining", "0".parse().unwrap());
         
         *response.status_mut() = StatusCode::TOO_MANY_REQUESTS;
@@ -345,16 +368,40 @@ pub async fn start_http_server(
     event_service: BoxedEventService,
     schema_registry: Arc<dyn ContractSchemaRegistry + Send + Sync>,
     auth_state: AuthState,
+    reorg_detectors: Vec<BoxedReorgDetector>,
+    cross_chain: Option<SharedCrossChainCausality>,
+    rate_limiter: Arc<RateLimiter>,
 ) -> Result<()> {
+    let connections = ConnectionManager::new(event_service.clone(), auth_state.clone());
+    for reorg_detector in &reorg_detectors {
+        connections.attach_reorg_detector(reorg_detector).await;
+    }
+
     let state = HttpState {
         event_service,
         schema_registry,
         auth_state,
-        rate_limiter: Arc::new(RateLimiter::new(1000, std::time::Duration::from_secs(60))), // 1000 requests per minute
+        rate_limiter,
         start_time: SystemTime::now(),
+        connections,
+        cross_chain,
     };
 
-    let app = Router::new()
+    info!("Starting HTTP REST API server on {}", addr);
+    
+    axum::Server::bind(&addr)
+        .serve(router(state).into_make_service_with_connect_info::<SocketAddr>())
+        .await
+        .map_err(|e| Error::generic(format!("HTTP server error: {}", e)))?;
+
+    Ok(())
+}
+
+/// Routes of the HTTP REST API
+///
+/// Requests must carry the client's `ConnectInfo<SocketAddr>` for rate limiting.
+pub fn router(state: HttpState) -> Router {
+    Router::new()
         // Chain endpoints
         .route("/api/v1/chains/:chain_id/status", get(get_chain_status))
         
@@ -369,6 +416,9 @@ pub async fn start_http_server(
         .route("/api/v1/blocks/:chain_id/latest/:status", get(get_latest_block_with_status))
         .route("/api/v1/blocks/:chain_id/:block_number", get(get_block))
         
+        // Causality endpoints
+        .route("/api/v1/causality/export/:event_id", get(export_causality_subgraph))
+        
         // Authentication endpoints
         .route("/api/v1/auth/keys", post(crate::auth::endpoints::create_api_key))
         .route("/api/v1/auth/users", post(crate::auth::endpoints::create_user))
@@ -384,16 +434,7 @@ pub async fn start_http_server(
         .route("/api/v1/version", get(get_version))
         
         .layer(middleware::from_fn_with_state(state.clone(), rate_limit_middleware))
-        .with_state(state);
-
-    info!("Starting HTTP REST API server on {}", addr);
-    
-    axum::Server::bind(&addr)
-        .serve(app.into_make_service_with_connect_info::<SocketAddr>())
-        .await
-        .map_err(|e| Error::generic(format!("HTTP server error: {}", e)))?;
-
-    Ok(())
+        .with_state(state)
 }
 
 /// GET /api/v1/chains/{chain_id}/status
@@ -404,7 +445,8 @@ async fn get_chain_status(
     debug!("Getting status for chain: {}", chain_id);
     
     // Get latest block from the event service
-    let latest_height = state.event_service.get_latest_block().await
+    let latest_height = state.event_service
+        .get_latest_block_with_status(&chain_id, BlockStatus::Latest).await
         .map_err(|e| ApiError::InternalError(format!("Failed to get latest block: {}", e)))?;
     
     // Get finalized block (if supported)
@@ -638,7 +680,8 @@ async fn get_latest_block(
 ) -> std::result::Result<Json<BlockResponse>, ApiError> {
     debug!("Getting latest block for chain: {}", chain_id);
     
-    let block_number = state.event_service.get_latest_block().await
+    let block_number = state.event_service
+        .get_latest_block_with_status(&chain_id, BlockStatus::Latest).await
         .map_err(|e| ApiError::InternalError(format!("Failed to get latest block: {}", e)))?;
     
     let response = BlockResponse {
@@ -698,6 +741,40 @@ async fn get_block(
     Err(ApiError::NotFound)
 }
 
+/// GET /api/v1/causality/export/{event_id}
+async fn export_causality_subgraph(
+    State(state): State<HttpState>,
+    Path(event_id): Path<String>,
+    Query(params): Query<CausalityExportQuery>,
+) -> std::result::Result<Response, ApiError> {
+    debug!("Exporting causality subgraph around event: {}", event_id);
+
+    let cross_chain = state.cross_chain.as_ref()
+        .ok_or_else(|| ApiError::BadRequest("Causality tracking is not configured".to_string()))?;
+
+    let format = params.format.as_deref().unwrap_or("dot").parse::<ExportFormat>()
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    let direction = match params.direction.as_deref() {
+        Some(direction) => direction.parse::<ExportDirection>()
+            .map_err(|e| ApiError::BadRequest(e.to_string()))?,
+        None => ExportDirection::default(),
+    };
+    let options = ExportOptions {
+        max_depth: params.depth.unwrap_or(DEFAULT_EXPORT_DEPTH),
+        direction,
+        since: params.since.map(|secs| UNIX_EPOCH + std::time::Duration::from_secs(secs)),
+        until: params.until.map(|secs| UNIX_EPOCH + std::time::Duration::from_secs(secs)),
+    };
+
+    let subgraph = cross_chain.read().await
+        .export_subgraph(&event_id, &options)
+        .ok_or(ApiError::NotFound)?;
+    let body = subgraph.render(format)
+        .map_err(|e| ApiError::InternalError(format!("Failed to render causality graph: {}", e)))?;
+
+    Ok(([(axum::http::header::CONTENT_TYPE, format.content_type())], body).into_response())
+}
+
 /// GET /api/v1/health
 async fn health_check(
     State(state): State<HttpState>,
@@ -710,17 +787,18 @@ async fn health_check(
     // Get latest block info for health check
     let mut latest_blocks = HashMap::new();
     
-    if let Ok(latest_height) = state.event_service.get_latest_block().await {
-        let chain_id = state.event_service.chain_id().0.clone();
-        latest_blocks.insert(chain_id, BlockInfo {
-            block_number: latest_height,
-            block_hash: format!("0x{:x}", latest_height),
-            timestamp: SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs(),
-            finality_status: "confirmed".to_string(),
-        });
+    for chain_id in state.event_service.chain_ids() {
+        if let Ok(latest_height) = state.event_service.get_latest_block_with_status(&chain_id.0, BlockStatus::Latest).await {
+            latest_blocks.insert(chain_id.0, BlockInfo {
+                block_number: latest_height,
+                block_hash: format!("0x{:x}", latest_height),
+                timestamp: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs(),
+                finality_status: "confirmed".to_string(),
+            });
+        }
     }
     
     let response = HealthResponse {
@@ -2,34 +2,46 @@
 use std::net::SocketAddr;
 use std::sync::Arc;
 use std::collections::HashMap;
+use std::time::Duration;
 
 use indexer_core::{Error, Result};
+use indexer_core::reorg::BoxedReorgDetector;
+use indexer_core::security::RateLimiter;
 use indexer_core::service::BoxedEventService;
 use indexer_core::types::ApiConfig;
+use indexer_storage::BoxedStorage;
 use tracing::{info, error};
 use tokio::sync::Mutex;
 use async_graphql::SimpleObject;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 
 pub mod http;
 pub mod graphql;
+pub mod decoder;
 pub mod subscription;
 pub mod auth;
 pub mod websocket;
 
+/// Requests each client may make per minute when the configuration sets no
+/// rate limit
+pub const DEFAULT_RATE_LIMIT: usize = 1000;
+
 /// Registry for contract schemas
+#[async_trait]
 pub trait ContractSchemaRegistry: Send + Sync {
     /// Get a contract schema by chain and address
-    fn get_schema(&self, chain: &str, address: &str, version: &str) -> Result<Option<ContractSchema>>;
+    async fn get_schema(&self, chain: &str, address: &str, version: &str) -> Result<Option<ContractSchema>>;
     
     /// Get the latest schema version for a contract
-    fn get_latest_schema(&self, chain: &str, address: &str) -> Result<Option<ContractSchema>>;
+    async fn get_latest_schema(&self, chain: &str, address: &str) -> Result<Option<ContractSchema>>;
     
     /// Register a new schema
-    fn register_schema(&self, schema: ContractSchemaVersion) -> Result<()>;
+    async fn register_schema(&self, schema: ContractSchemaVersion) -> Result<()>;
 }
 
 /// Contract schema version
-#[derive(Clone, Debug, SimpleObject)]
+#[derive(Clone, Debug, SimpleObject, Serialize, Deserialize)]
 pub struct ContractSchemaVersion {
     /// Version string (e.g. "1.0.0")
     pub version: String,
@@ -38,7 +50,7 @@ pub struct ContractSchemaVersion {
 }
 
 /// Contract schema
-#[derive(Clone, Debug, SimpleObject)]
+#[derive(Clone, Debug, SimpleObject, Serialize, Deserialize)]
 pub struct ContractSchema {
     /// Chain ID (e.g. "ethereum", "polygon")
     pub chain: String,
@@ -53,7 +65,7 @@ pub struct ContractSchema {
 }
 
 /// Event schema
-#[derive(Clone, Debug, SimpleObject)]
+#[derive(Clone, Debug, SimpleObject, Serialize, Deserialize)]
 pub struct EventSchema {
     /// Event name
     pub name: String,
@@ -62,7 +74,7 @@ pub struct EventSchema {
 }
 
 /// Function schema
-#[derive(Clone, Debug, SimpleObject)]
+#[derive(Clone, Debug, SimpleObject, Serialize, Deserialize)]
 pub struct FunctionSchema {
     /// Function name
     pub name: String,
@@ -73,7 +85,7 @@ pub struct FunctionSchema {
 }
 
 /// Field schema
-#[derive(Clone, Debug, SimpleObject)]
+#[derive(Clone, Debug, SimpleObject, Serialize, Deserialize)]
 pub struct FieldSchema {
     /// Field name
     pub name: String,
@@ -102,15 +114,17 @@ impl InMemorySchemaRegistry {
         }
     }
     
+    /// Key of a contract's schemas, comparing addresses case-insensitively
+    /// so checksummed EVM addresses match their lowercase form
     fn create_key(chain: &str, address: &str) -> String {
-        format!("{}:{}", chain, address)
+        format!("{}:{}", chain, address.to_lowercase())
     }
 }
 
+#[async_trait]
 impl ContractSchemaRegistry for InMemorySchemaRegistry {
-    fn get_schema(&self, chain: &str, address: &str, version: &str) -> Result<Option<ContractSchema>> {
-        let schemas_lock = self.schemas.try_lock()
-            .map_err(|_| Error::generic("Failed to acquire lock on schema registry"))?;
+    async fn get_schema(&self, chain: &str, address: &str, version: &str) -> Result<Option<ContractSchema>> {
+        let schemas_lock = self.schemas.lock().await;
         
         let key = Self::create_key(chain, address);
         
@@ -125,9 +139,8 @@ impl ContractSchemaRegistry for InMemorySchemaRegistry {
         Ok(None)
     }
     
-    fn get_latest_schema(&self, chain: &str, address: &str) -> Result<Option<ContractSchema>> {
-        let schemas_lock = self.schemas.try_lock()
-            .map_err(|_| Error::generic("Failed to acquire lock on schema registry"))?;
+    async fn get_latest_schema(&self, chain: &str, address: &str) -> Result<Option<ContractSchema>> {
+        let schemas_lock = self.schemas.lock().await;
         
         let key = Self::create_key(chain, address);
         
@@ -140,9 +153,8 @@ impl ContractSchemaRegistry for InMemorySchemaRegistry {
         Ok(None)
     }
     
-    fn register_schema(&self, schema: ContractSchemaVersion) -> Result<()> {
-        let mut schemas_lock = self.schemas.try_lock()
-            .map_err(|_| Error::generic("Failed to acquire lock on schema registry"))?;
+    async fn register_schema(&self, schema: ContractSchemaVersion) -> Result<()> {
+        let mut schemas_lock = self.schemas.lock().await;
         
         let key = Self::create_key(&schema.schema.chain, &schema.schema.address);
         
@@ -153,6 +165,60 @@ impl ContractSchemaRegistry for InMemorySchemaRegistry {
     }
 }
 
+/// Contract schema registry kept in storage
+///
+/// Schemas registered through the API are seen by every process sharing
+/// the storage, such as the indexer decoding events on ingestion. The
+/// versions of a contract are stored together as JSON, keyed by chain and
+/// lowercase address.
+pub struct StorageSchemaRegistry {
+    /// Storage holding the schemas
+    storage: BoxedStorage,
+    /// Serializes the read-modify-write of registrations
+    registrations: Mutex<()>,
+}
+
+impl StorageSchemaRegistry {
+    /// Create a registry over the contract schemas in `storage`
+    pub fn new(storage: BoxedStorage) -> Self {
+        Self {
+            storage,
+            registrations: Mutex::new(()),
+        }
+    }
+
+    /// Registered versions of a contract's schema, oldest first
+    async fn versions(&self, chain: &str, address: &str) -> Result<Vec<ContractSchemaVersion>> {
+        match self.storage.get_contract_schema(chain, &address.to_lowercase()).await? {
+            Some(data) => serde_json::from_slice(&data)
+                .map_err(|e| Error::invalid_data(format!("Invalid stored schema of {} on {}: {}", address, chain, e))),
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl ContractSchemaRegistry for StorageSchemaRegistry {
+    async fn get_schema(&self, chain: &str, address: &str, version: &str) -> Result<Option<ContractSchema>> {
+        let versions = self.versions(chain, address).await?;
+        Ok(versions.into_iter().find(|ver| ver.version == version).map(|ver| ver.schema))
+    }
+    
+    async fn get_latest_schema(&self, chain: &str, address: &str) -> Result<Option<ContractSchema>> {
+        Ok(self.versions(chain, address).await?.pop().map(|ver| ver.schema))
+    }
+    
+    async fn register_schema(&self, schema: ContractSchemaVersion) -> Result<()> {
+        let _registration = self.registrations.lock().await;
+        let chain = schema.schema.chain.clone();
+        let address = schema.schema.address.to_lowercase();
+        
+        let mut versions = self.versions(&chain, &address).await?;
+        versions.push(schema);
+        self.storage.store_contract_schema(&chain, &address, &serde_json::to_vec(&versions)?).await
+    }
+}
+
 /// API server configuration
 pub struct ApiServerConfig {
     /// HTTP server address
@@ -175,6 +241,16 @@ pub struct ApiServer {
     auth_state: auth::AuthState,
     /// API server configuration
     config: ApiServerConfig,
+    /// Reorg detectors, one per chain, used for WebSocket reorg notifications
+    reorg_detectors: Vec<BoxedReorgDetector>,
+    /// Storage for Valence contract state
+    storage: Option<BoxedStorage>,
+    /// Causality indexer state for causal path and violation queries
+    causality: graphql::CausalityState,
+    /// Depth, complexity and persisted query limits for GraphQL
+    graphql_limits: graphql::GraphQLLimits,
+    /// Per-client rate limit charged by REST requests and GraphQL query cost
+    rate_limiter: Arc<RateLimiter>,
     /// Running state
     running: Arc<Mutex<bool>>,
 }
@@ -195,10 +271,60 @@ impl ApiServer {
             schema_registry,
             auth_state,
             config,
+            reorg_detectors: Vec::new(),
+            storage: None,
+            causality: graphql::CausalityState::default(),
+            graphql_limits: graphql::GraphQLLimits::default(),
+            rate_limiter: Arc::new(RateLimiter::new(DEFAULT_RATE_LIMIT, Duration::from_secs(60))),
             running: Arc::new(Mutex::new(false)),
         }
     }
 
+    /// Attach storage so Valence contract state can be queried and subscribed to
+    pub fn with_storage(mut self, storage: BoxedStorage) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Attach the causality indexer's cross-chain tracker so causal paths can be queried
+    pub fn with_cross_chain_causality(mut self, cross_chain: graphql::SharedCrossChainCausality) -> Self {
+        self.causality.cross_chain = Some(cross_chain);
+        self
+    }
+
+    /// Attach the causality indexer's violation detector so safety violations can be queried
+    pub fn with_violation_detector(mut self, violations: graphql::SharedViolationDetector) -> Self {
+        self.causality.violations = Some(violations);
+        self
+    }
+
+    /// Set the depth, complexity and persisted query limits of the GraphQL endpoint
+    pub fn with_graphql_limits(mut self, limits: graphql::GraphQLLimits) -> Self {
+        self.graphql_limits = limits;
+        self
+    }
+
+    /// Set the requests each client may make per minute, shared by REST
+    /// requests and the cost of GraphQL queries
+    pub fn with_rate_limit(mut self, requests_per_minute: usize) -> Self {
+        self.rate_limiter = Arc::new(RateLimiter::new(requests_per_minute, Duration::from_secs(60)));
+        self
+    }
+
+    /// Attach a reorg detector so WebSocket clients can receive reorg notifications
+    ///
+    /// Detectors watch a single chain; attach one for every indexed chain.
+    pub fn with_reorg_detector(mut self, reorg_detector: BoxedReorgDetector) -> Self {
+        self.reorg_detectors.push(reorg_detector);
+        self
+    }
+
+    /// Decoder for events of the contracts registered in the schema
+    /// registry, to decode events on ingestion
+    pub fn schema_decoder(&self) -> decoder::SchemaDecoder {
+        decoder::SchemaDecoder::new(self.schema_registry.clone())
+    }
+
     /// Create a new API server from an API config
     pub fn from_config(
         config: &ApiConfig,
@@ -240,7 +366,12 @@ impl ApiServer {
         };
         
         // Create API server
-        Ok(Self::new(event_service, schema_registry, server_config))
+        let mut server = Self::new(event_service, schema_registry, server_config)
+            .with_graphql_limits(graphql_limits_from_config(config)?);
+        if let Some(rate_limit) = config.rate_limit {
+            server = server.with_rate_limit(rate_limit as usize);
+        }
+        Ok(server)
     }
 
     /// Start the API server
@@ -257,11 +388,14 @@ impl ApiServer {
             let event_service = self.event_service.clone();
             let schema_registry = self.schema_registry.clone();
             let auth_state = self.auth_state.clone();
+            let reorg_detectors = self.reorg_detectors.clone();
+            let cross_chain = self.causality.cross_chain.clone();
+            let rate_limiter = self.rate_limiter.clone();
             let addr = self.config.http_addr;
             
             // Spawn HTTP server task
             tokio::spawn(async move {
-                if let Err(e) = http::start_http_server(addr, event_service, schema_registry, auth_state).await {
+                if let Err(e) = http::start_http_server(addr, event_service, schema_registry, auth_state, reorg_detectors, cross_chain, rate_limiter).await {
                     error!("HTTP REST API server error: {}", e);
                 }
             });
@@ -271,12 +405,16 @@ impl ApiServer {
         if self.config.graphql_addr.port() != 0 {
             let event_service = self.event_service.clone();
             let schema_registry = self.schema_registry.clone();
+            let storage = self.storage.clone();
+            let causality = self.causality.clone();
+            let limits = self.graphql_limits.clone();
+            let rate_limiter = self.rate_limiter.clone();
             let addr = self.config.graphql_addr;
             let enable_playground = self.config.enable_playground;
             
             // Spawn GraphQL server task
             tokio::spawn(async move {
-                if let Err(e) = graphql::start_graphql_server(addr, event_service, schema_registry, enable_playground).await {
+                if let Err(e) = graphql::start_graphql_server(addr, event_service, schema_registry, storage, causality, limits, rate_limiter, enable_playground).await {
                     error!("GraphQL server error: {}", e);
                 }
             });
@@ -285,10 +423,11 @@ impl ApiServer {
         // Start WebSocket server if enabled
         if let Some(ws_addr) = self.config.ws_addr {
             let event_service = self.event_service.clone();
+            let reorg_detectors = self.reorg_detectors.clone();
             
             // Spawn WebSocket server task
             tokio::spawn(async move {
-                if let Err(e) = subscription::start_websocket_server(ws_addr, event_service).await {
+                if let Err(e) = subscription::start_websocket_server(ws_addr, event_service, reorg_detectors).await {
                     error!("WebSocket server error: {}", e);
                 }
             });
@@ -310,6 +449,34 @@ impl ApiServer {
     }
 }
 
+/// GraphQL limits set by an API config, the defaults for the others
+///
+/// Persisted queries are read from the `.graphql` files of the configured
+/// directory.
+pub fn graphql_limits_from_config(config: &ApiConfig) -> Result<graphql::GraphQLLimits> {
+    let mut limits = graphql::GraphQLLimits::default()
+        .with_persisted_queries_only(config.graphql_persisted_queries_only);
+    if let Some(max_depth) = config.graphql_max_depth {
+        limits = limits.with_max_depth(max_depth);
+    }
+    if let Some(max_complexity) = config.graphql_max_complexity {
+        limits = limits.with_max_complexity(max_complexity);
+    }
+    if let Some(dir) = &config.graphql_persisted_queries {
+        let entries = std::fs::read_dir(dir)
+            .map_err(|e| Error::Config(format!("Failed to read persisted queries from {}: {}", dir, e)))?;
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_some_and(|extension| extension == "graphql") {
+                let query = std::fs::read_to_string(&path)
+                    .map_err(|e| Error::Config(format!("Failed to read persisted query {}: {}", path.display(), e)))?;
+                limits = limits.with_persisted_query(query);
+            }
+        }
+    }
+    Ok(limits)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -339,23 +506,57 @@ mod tests {
         };
         
         // Test registration
-        assert!(registry.register_schema(schema_version).is_ok());
+        assert!(registry.register_schema(schema_version).await.is_ok());
         
         // Test retrieval by version
-        let retrieved = registry.get_schema("ethereum", "0x123", "1.0.0").unwrap();
+        let retrieved = registry.get_schema("ethereum", "0x123", "1.0.0").await.unwrap();
         assert!(retrieved.is_some());
         assert_eq!(retrieved.unwrap().name, "TestContract");
         
         // Test latest schema retrieval
-        let latest = registry.get_latest_schema("ethereum", "0x123").unwrap();
+        let latest = registry.get_latest_schema("ethereum", "0x123").await.unwrap();
         assert!(latest.is_some());
         assert_eq!(latest.unwrap().name, "TestContract");
         
         // Test non-existent schema
-        let non_existent = registry.get_schema("ethereum", "0x456", "1.0.0").unwrap();
+        let non_existent = registry.get_schema("ethereum", "0x456", "1.0.0").await.unwrap();
         assert!(non_existent.is_none());
     }
     
+    #[test]
+    fn test_graphql_limits_from_config() {
+        let dir = std::env::temp_dir().join("graphql_persisted_queries_test");
+        if dir.exists() {
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("health.graphql"), "{ health { healthy } }").unwrap();
+        std::fs::write(dir.join("README.md"), "{ other }").unwrap();
+
+        let config = ApiConfig {
+            host: "127.0.0.1".to_string(),
+            port: 8080,
+            enable_graphql: true,
+            enable_rest: true,
+            enable_websocket: false,
+            rate_limit: Some(10),
+            graphql_max_depth: Some(4),
+            graphql_max_complexity: None,
+            graphql_persisted_queries: Some(dir.to_str().unwrap().to_string()),
+            graphql_persisted_queries_only: true,
+            params: HashMap::new(),
+        };
+        let limits = graphql_limits_from_config(&config).unwrap();
+        assert_eq!(limits.max_depth, 4);
+        assert_eq!(limits.max_complexity, graphql::GraphQLLimits::default().max_complexity);
+        assert!(limits.resolve_persisted_query(async_graphql::Request::new("{ health { healthy } }")).is_ok());
+        assert!(limits.resolve_persisted_query(async_graphql::Request::new("{ other }")).is_err());
+
+        let missing = ApiConfig { graphql_persisted_queries: Some(dir.join("missing").to_str().unwrap().to_string()), ..config };
+        assert!(graphql_limits_from_config(&missing).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[test]
     fn test_api_server_config() {
         let config = ApiServerConfig {
@@ -10,6 +10,7 @@ use axum::{
 use tokio::net::TcpListener;
 use tracing::info;
 
+use indexer_core::reorg::BoxedReorgDetector;
 use indexer_core::service::BoxedEventService;
 use indexer_core::{Result, Error};
 use crate::{
@@ -22,6 +23,7 @@ use crate::{
 pub async fn start_websocket_server(
     addr: SocketAddr,
     event_service: BoxedEventService,
+    reorg_detectors: Vec<BoxedReorgDetector>,
 ) -> Result<()> {
     info!("Starting WebSocket server on {}", addr);
     
@@ -29,11 +31,11 @@ pub async fn start_websocket_server(
     let jwt_secret = b"your-256-bit-secret-key-here-please-change-this-in-production";
     let auth_state = AuthState::new(jwt_secret);
     
-    // Create connection manager for WebSocket connections
-    let _connection_manager = Arc::new(ConnectionManager::new(
-        event_service.clone(),
-        auth_state.clone(),
-    ));
+    // Create the connection manager shared by all WebSocket connections
+    let connections = ConnectionManager::new(event_service.clone(), auth_state.clone());
+    for reorg_detector in &reorg_detectors {
+        connections.attach_reorg_detector(reorg_detector).await;
+    }
     
     // Create HTTP state for the WebSocket server
     let state = HttpState {
@@ -45,6 +47,8 @@ pub async fn start_websocket_server(
             std::time::Duration::from_secs(60)
         )),
         start_time: std::time::SystemTime::now(),
+        connections,
+        cross_chain: None,
     };
     
     // Create router with WebSocket endpoints
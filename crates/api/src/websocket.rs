@@ -1,7 +1,7 @@
 /// WebSocket API implementation for real-time event streaming
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use axum::{
     extract::{
@@ -25,15 +25,25 @@ use uuid::Uuid;
 
 use indexer_core::{
     event::Event,
+    reorg::{BoxedReorgDetector, ReorgEvent, ReorgSubscription},
     service::{BoxedEventService},
     types::{ChainId, EventFilter as CoreEventFilter},
-    Error, Result,
+    BlockStatus, Error, Result,
 };
 use crate::{
     auth::{AuthState, UserRole},
     http::HttpState,
 };
 
+/// Number of blocks of delivered events retained per chain for reorg notifications
+const REORG_WINDOW_BLOCKS: u64 = 256;
+
+/// Interval at which the finalized block height is polled from the event service
+const FINALITY_POLL_INTERVAL: Duration = Duration::from_secs(12);
+
+/// Delivered events per chain, keyed by block number
+type DeliveredEvents = HashMap<String, BTreeMap<u64, Vec<EventData>>>;
+
 /// Subscription persistence storage
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PersistedSubscription {
@@ -140,6 +150,19 @@ pub enum WsMessage {
         subscription_id: String,
         event: EventData,
     },
+    /// Server notifies that previously delivered events were rolled back by a reorg
+    Reorg {
+        subscription_id: String,
+        chain_id: String,
+        from_block: u64,
+        removed_event_ids: Vec<String>,
+    },
+    /// Server notifies that a block, and every event up to it, is finalized
+    Finalized {
+        subscription_id: String,
+        chain_id: String,
+        block: u64,
+    },
     /// Server confirms subscription
     Subscribed {
         id: String,
@@ -190,6 +213,12 @@ pub struct EventFilters {
     pub attributes: Option<HashMap<String, Value>>,
     /// Maximum number of events to send
     pub limit: Option<usize>,
+    /// Receive `reorg` notifications for delivered events that get rolled back
+    #[serde(default)]
+    pub include_reorgs: bool,
+    /// Receive `finalized` notifications for the subscribed chain
+    #[serde(default)]
+    pub include_finality: bool,
 }
 
 /// Event data for WebSocket messages
@@ -222,7 +251,9 @@ impl From<&dyn Event> for EventData {
                 .unwrap_or_default()
                 .as_secs(),
             raw_data: BASE64_STANDARD.encode(event.raw_data()),
-            attributes: HashMap::new(), // TODO: Extract from event data
+            attributes: event.decoded()
+                .map(|fields| fields.clone().into_iter().collect())
+                .unwrap_or_default(),
         }
     }
 }
@@ -362,6 +393,11 @@ pub struct ConnectionManager {
     event_broadcast: broadcast::Sender<(String, EventData)>,
     /// Subscription storage
     subscription_storage: Arc<InMemorySubscriptionStorage>,
+    /// Recently delivered events per chain, keyed by block number, used to
+    /// tell reorg-aware subscribers which events were rolled back
+    delivered_events: Arc<RwLock<DeliveredEvents>>,
+    /// Highest finalized block announced per chain
+    finalized_blocks: Arc<RwLock<HashMap<String, u64>>>,
 }
 
 impl ConnectionManager {
@@ -374,11 +410,16 @@ impl ConnectionManager {
             auth_state,
             event_broadcast,
             subscription_storage: Arc::new(InMemorySubscriptionStorage::new()),
+            delivered_events: Arc::new(RwLock::new(HashMap::new())),
+            finalized_blocks: Arc::new(RwLock::new(HashMap::new())),
         };
         
         // Start the event streaming background task
         manager.start_event_streaming();
         
+        // Start the finality tracking task
+        manager.start_finality_tracking();
+        
         // Start the subscription cleanup task
         manager.start_cleanup_task();
         
@@ -531,48 +572,223 @@ impl ConnectionManager {
         })
     }
 
+    /// Deliver an event to every matching subscription and remember it for
+    /// later reorg notifications
+    pub async fn dispatch_event(&self, event_data: EventData) {
+        let connections = self.connections.read().await;
+        for (connection_id, connection) in connections.iter() {
+            for (sub_id, subscription) in connection.subscriptions.iter() {
+                if Self::event_matches_filter(&event_data, &subscription.filters) {
+                    if let Some(sender) = &connection.sender {
+                        let ws_message = WsMessage::Event {
+                            subscription_id: sub_id.clone(),
+                            event: event_data.clone(),
+                        };
+                        
+                        if let Err(e) = sender.send(ws_message) {
+                            debug!("Failed to send event to connection {}: {}", connection_id, e);
+                            // Connection is likely closed, but we'll let the cleanup handle it
+                        } else {
+                            // Update event count in storage
+                            let new_count = subscription.event_count + 1;
+                            if let Err(e) = self.subscription_storage.update_subscription_count(sub_id, new_count).await {
+                                warn!("Failed to update subscription count: {}", e);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        drop(connections);
+        
+        self.record_delivered_event(event_data).await;
+    }
+
+    /// Remember a delivered event until its block is finalized or leaves the reorg window
+    async fn record_delivered_event(&self, event_data: EventData) {
+        let finalized = self.finalized_blocks.read().await
+            .get(&event_data.chain_id)
+            .copied();
+        if finalized.is_some_and(|block| event_data.block_number <= block) {
+            return;
+        }
+        
+        let mut delivered = self.delivered_events.write().await;
+        let blocks = delivered.entry(event_data.chain_id.clone()).or_default();
+        blocks.entry(event_data.block_number).or_default().push(event_data);
+        
+        // Drop blocks that fell out of the reorg window
+        if let Some(&tip) = blocks.keys().next_back() {
+            let cutoff = tip.saturating_sub(REORG_WINDOW_BLOCKS);
+            *blocks = blocks.split_off(&cutoff);
+        }
+    }
+
+    /// Notify reorg-aware subscribers about delivered events that were rolled back
+    ///
+    /// Returns the number of notifications sent.
+    pub async fn notify_reorg(&self, reorg: &ReorgEvent) -> usize {
+        let from_block = match reorg.reorganized_blocks.iter().map(|b| b.old_block.number).min() {
+            Some(block) => block,
+            None => return 0,
+        };
+        let chain_id = reorg.chain_id.0.clone();
+        
+        // Forget everything at or above the fork point; it is no longer canonical
+        let removed: Vec<EventData> = {
+            let mut delivered = self.delivered_events.write().await;
+            match delivered.get_mut(&chain_id) {
+                Some(blocks) => blocks.split_off(&from_block).into_values().flatten().collect(),
+                None => Vec::new(),
+            }
+        };
+        
+        if removed.is_empty() {
+            debug!("Reorg on {} from block {} removed no delivered events", chain_id, from_block);
+            return 0;
+        }
+        
+        let mut sent = 0;
+        let connections = self.connections.read().await;
+        for (connection_id, connection) in connections.iter() {
+            let sender = match &connection.sender {
+                Some(sender) => sender,
+                None => continue,
+            };
+            
+            for (sub_id, subscription) in connection.subscriptions.iter() {
+                if !subscription.filters.include_reorgs {
+                    continue;
+                }
+                
+                let removed_event_ids: Vec<String> = removed
+                    .iter()
+                    .filter(|event| Self::event_matches_filter(event, &subscription.filters))
+                    .map(|event| event.id.clone())
+                    .collect();
+                
+                if removed_event_ids.is_empty() {
+                    continue;
+                }
+                
+                let ws_message = WsMessage::Reorg {
+                    subscription_id: sub_id.clone(),
+                    chain_id: chain_id.clone(),
+                    from_block,
+                    removed_event_ids,
+                };
+                
+                if let Err(e) = sender.send(ws_message) {
+                    debug!("Failed to send reorg notification to connection {}: {}", connection_id, e);
+                } else {
+                    sent += 1;
+                }
+            }
+        }
+        
+        info!("Reorg on {} from block {} rolled back {} delivered events", chain_id, from_block, removed.len());
+        sent
+    }
+
+    /// Apply a block status update, announcing newly finalized blocks to
+    /// finality-aware subscribers
+    ///
+    /// Returns the number of notifications sent.
+    pub async fn notify_block_status(&self, chain_id: &str, block: u64, status: BlockStatus) -> usize {
+        if status != BlockStatus::Finalized {
+            return 0;
+        }
+        
+        {
+            let mut finalized = self.finalized_blocks.write().await;
+            let current = finalized.entry(chain_id.to_string()).or_insert(0);
+            if block <= *current {
+                return 0;
+            }
+            *current = block;
+        }
+        
+        // Finalized events can no longer be rolled back
+        {
+            let mut delivered = self.delivered_events.write().await;
+            if let Some(blocks) = delivered.get_mut(chain_id) {
+                *blocks = blocks.split_off(&(block + 1));
+            }
+        }
+        
+        let mut sent = 0;
+        let connections = self.connections.read().await;
+        for (connection_id, connection) in connections.iter() {
+            let sender = match &connection.sender {
+                Some(sender) => sender,
+                None => continue,
+            };
+            
+            for (sub_id, subscription) in connection.subscriptions.iter() {
+                if !subscription.filters.include_finality {
+                    continue;
+                }
+                
+                if let Some(ref chain_filter) = subscription.filters.chain_id {
+                    if chain_filter != chain_id {
+                        continue;
+                    }
+                }
+                
+                let ws_message = WsMessage::Finalized {
+                    subscription_id: sub_id.clone(),
+                    chain_id: chain_id.to_string(),
+                    block,
+                };
+                
+                if let Err(e) = sender.send(ws_message) {
+                    debug!("Failed to send finality notification to connection {}: {}", connection_id, e);
+                } else {
+                    sent += 1;
+                }
+            }
+        }
+        
+        sent
+    }
+
+    /// Subscribe to a reorg detector and feed its events into connected clients
+    pub async fn attach_reorg_detector(&self, reorg_detector: &BoxedReorgDetector) {
+        match reorg_detector.subscribe().await {
+            Ok(subscription) => self.start_reorg_streaming(subscription),
+            Err(e) => warn!("Failed to subscribe to reorg events: {}", e),
+        }
+    }
+
+    /// Feed reorg events from a reorg subscription into connected clients
+    pub fn start_reorg_streaming(&self, mut subscription: Box<dyn ReorgSubscription>) {
+        let manager = self.clone();
+        
+        tokio::spawn(async move {
+            info!("Started WebSocket reorg streaming task");
+            
+            while let Some(reorg) = subscription.next().await {
+                manager.notify_reorg(&reorg).await;
+            }
+            
+            debug!("Reorg subscription ended");
+        });
+    }
+
     /// Start the background task for streaming events to subscribers
     fn start_event_streaming(&self) {
-        let connections = self.connections.clone();
-        let event_service = self.event_service.clone();
+        let manager = self.clone();
         let _event_broadcast = self.event_broadcast.clone();
-        let subscription_storage = self.subscription_storage.clone();
 
         tokio::spawn(async move {
             // Subscribe to events from the event service
-            match event_service.subscribe().await {
+            match manager.event_service.subscribe().await {
                 Ok(mut subscription) => {
                     info!("Started WebSocket event streaming task");
                     
                     // Process events in a loop
                     while let Some(event) = subscription.next().await {
-                        let event_data = EventData::from(event.as_ref());
-                        
-                        // Broadcast to all connections with matching subscriptions
-                        let connections_read = connections.read().await;
-                        for (connection_id, connection) in connections_read.iter() {
-                            for (sub_id, subscription) in connection.subscriptions.iter() {
-                                if Self::event_matches_filter(&event_data, &subscription.filters) {
-                                    if let Some(sender) = &connection.sender {
-                                        let ws_message = WsMessage::Event {
-                                            subscription_id: sub_id.clone(),
-                                            event: event_data.clone(),
-                                        };
-                                        
-                                        if let Err(e) = sender.send(ws_message) {
-                                            debug!("Failed to send event to connection {}: {}", connection_id, e);
-                                            // Connection is likely closed, but we'll let the cleanup handle it
-                                        } else {
-                                            // Update event count in storage
-                                            let new_count = subscription.event_count + 1;
-                                            if let Err(e) = subscription_storage.update_subscription_count(sub_id, new_count).await {
-                                                warn!("Failed to update subscription count: {}", e);
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
+                        manager.dispatch_event(EventData::from(event.as_ref())).await;
                     }
                 }
                 Err(e) => {
@@ -582,6 +798,59 @@ impl ConnectionManager {
         });
     }
 
+    /// Start the background task that polls the finalized block heights
+    fn start_finality_tracking(&self) {
+        let manager = self.clone();
+        
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(FINALITY_POLL_INTERVAL);
+            
+            loop {
+                interval.tick().await;
+                manager.poll_finality().await;
+            }
+        });
+    }
+
+    /// Poll the finalized block height of the service's chains and of every
+    /// chain events were delivered for
+    ///
+    /// Services that do not track finality report their head for every
+    /// status. Only a finalized height below the head is applied, so such a
+    /// service never marks delivered events final and reorgs can still be
+    /// reported for them.
+    ///
+    /// Returns the number of notifications sent.
+    pub async fn poll_finality(&self) -> usize {
+        let mut chains: BTreeSet<String> = self.delivered_events.read().await.keys().cloned().collect();
+        chains.extend(self.event_service.chain_ids().into_iter().map(|chain_id| chain_id.0));
+        
+        let mut sent = 0;
+        for chain_id in chains {
+            let finalized = match self.event_service.get_latest_block_with_status(&chain_id, BlockStatus::Finalized).await {
+                Ok(block) => block,
+                Err(e) => {
+                    debug!("Failed to get finalized block for {}: {}", chain_id, e);
+                    continue;
+                }
+            };
+            let latest = match self.event_service.get_latest_block_with_status(&chain_id, BlockStatus::Latest).await {
+                Ok(block) => block,
+                Err(e) => {
+                    debug!("Failed to get latest block for {}: {}", chain_id, e);
+                    continue;
+                }
+            };
+            
+            if finalized >= latest {
+                debug!("Finalized block {} of {} is not below its head {}, ignoring", finalized, chain_id, latest);
+                continue;
+            }
+            sent += self.notify_block_status(&chain_id, finalized, BlockStatus::Finalized).await;
+        }
+        sent
+    }
+
     /// Start the background cleanup task
     fn start_cleanup_task(&self) {
         let subscription_storage = self.subscription_storage.clone();
@@ -663,8 +932,8 @@ pub async fn websocket_handler(
 async fn handle_websocket(socket: WebSocket, addr: SocketAddr, state: HttpState) {
     let connection_id = Uuid::new_v4().to_string();
     
-    // Create connection manager
-    let manager = ConnectionManager::new(state.event_service.clone(), state.auth_state.clone());
+    // Connections share the server's manager and its background tasks
+    let manager = state.connections.clone();
     
     // Register connection
     manager.register_connection(connection_id.clone(), addr).await;
@@ -887,7 +1156,6 @@ async fn handle_websocket_message(
 
 /// WebSocket statistics endpoint
 pub async fn websocket_stats(State(state): State<HttpState>) -> axum::response::Json<Value> {
-    let manager = ConnectionManager::new(state.event_service, state.auth_state);
-    let stats = manager.get_stats().await;
+    let stats = state.connections.get_stats().await;
     axum::response::Json(stats)
 } 
\ No newline at end of file
@@ -5,11 +5,22 @@ use std::time::{SystemTime, UNIX_EPOCH};
 use serde_json::json;
 use tokio::time::Duration;
 
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+
 use indexer_api::{
-    websocket::{WsMessage, EventFilters, EventData, InMemorySubscriptionStorage, PersistedSubscription},
+    auth::AuthState,
+    websocket::{WsMessage, EventFilters, EventData, InMemorySubscriptionStorage, PersistedSubscription, ConnectionManager},
 };
 use indexer_core::{
     event::Event,
+    reorg::{CanonicalBlock, ReorgEvent, ReorganizedBlock},
+    service::{BoxedEventService, BoxedEventServiceTrait, EventSubscription},
+    types::{ChainId, EventFilter},
+    BlockStatus, Error, Result,
 };
 
 // Mock event for testing
@@ -76,6 +87,109 @@ fn create_test_event(id: &str, chain: &str, block_number: u64, event_type: &str)
     }
 }
 
+// Event service without live events, used to drive a ConnectionManager directly
+struct IdleEventService {
+    chain_id: ChainId,
+}
+
+#[async_trait]
+impl BoxedEventServiceTrait for IdleEventService {
+    fn chain_id(&self) -> &ChainId {
+        &self.chain_id
+    }
+
+    async fn get_events(&self, _filters: Vec<EventFilter>) -> Result<Vec<Box<dyn Event>>> {
+        Ok(Vec::new())
+    }
+
+    async fn subscribe(&self) -> Result<Box<dyn EventSubscription>> {
+        Err(Error::generic("Idle event service has no live events"))
+    }
+
+    async fn get_latest_block(&self) -> Result<u64> {
+        Ok(0)
+    }
+}
+
+// Event service reporting a head for every chain and, when it tracks
+// finality, a finalized height below it
+struct FinalityEventService {
+    chain_id: ChainId,
+    latest_block: u64,
+    finalized_block: Option<u64>,
+}
+
+#[async_trait]
+impl BoxedEventServiceTrait for FinalityEventService {
+    fn chain_id(&self) -> &ChainId {
+        &self.chain_id
+    }
+
+    async fn get_latest_block_with_status(&self, _chain: &str, status: BlockStatus) -> Result<u64> {
+        match status {
+            BlockStatus::Finalized => Ok(self.finalized_block.unwrap_or(self.latest_block)),
+            _ => Ok(self.latest_block),
+        }
+    }
+
+    async fn get_events(&self, _filters: Vec<EventFilter>) -> Result<Vec<Box<dyn Event>>> {
+        Ok(Vec::new())
+    }
+
+    async fn subscribe(&self) -> Result<Box<dyn EventSubscription>> {
+        Err(Error::generic("Finality event service has no live events"))
+    }
+
+    async fn get_latest_block(&self) -> Result<u64> {
+        Ok(self.latest_block)
+    }
+}
+
+fn create_test_event_data(id: &str, chain: &str, block_number: u64, event_type: &str) -> EventData {
+    let event = create_test_event(id, chain, block_number, event_type);
+    EventData::from(&event as &dyn Event)
+}
+
+fn create_test_block(number: u64) -> CanonicalBlock {
+    CanonicalBlock {
+        number,
+        hash: format!("0x{:064x}", number),
+        parent_hash: format!("0x{:064x}", number.saturating_sub(1)),
+        timestamp: 1640000000 + number * 60,
+    }
+}
+
+fn notification_filters(event_type: Option<&str>, include_reorgs: bool, include_finality: bool) -> EventFilters {
+    EventFilters {
+        chain_id: Some("ethereum".to_string()),
+        address: None,
+        event_type: event_type.map(|t| t.to_string()),
+        block_range: None,
+        attributes: None,
+        limit: None,
+        include_reorgs,
+        include_finality,
+    }
+}
+
+async fn create_test_manager() -> (ConnectionManager, mpsc::UnboundedReceiver<WsMessage>) {
+    create_test_manager_with(Arc::new(IdleEventService {
+        chain_id: ChainId::from("ethereum"),
+    })).await
+}
+
+async fn create_test_manager_with(event_service: BoxedEventService) -> (ConnectionManager, mpsc::UnboundedReceiver<WsMessage>) {
+    let auth_state = AuthState::new(b"test-secret-key-for-testing-only-32-bytes");
+    let manager = ConnectionManager::new(event_service, auth_state);
+    
+    let addr: SocketAddr = "127.0.0.1:9000".parse().unwrap();
+    let (tx, rx) = mpsc::unbounded_channel();
+    manager.register_connection("conn-1".to_string(), addr).await;
+    manager.set_connection_sender("conn-1", tx).await;
+    
+    (manager, rx)
+}
+
 #[test]
 fn test_ws_message_serialization() {
     // Test Subscribe message
@@ -88,6 +202,8 @@ fn test_ws_message_serialization() {
             block_range: Some((100, 200)),
             attributes: None,
             limit: Some(10),
+            include_reorgs: false,
+            include_finality: false,
         },
     };
     
@@ -123,6 +239,8 @@ fn test_event_filters_validation() {
         block_range: Some((100, 200)),
         attributes: Some(HashMap::new()),
         limit: Some(10),
+        include_reorgs: false,
+        include_finality: false,
     };
     
     assert_eq!(valid_filter.chain_id, Some("ethereum".to_string()));
@@ -139,6 +257,8 @@ fn test_event_filters_validation() {
         block_range: None,
         attributes: None,
         limit: None,
+        include_reorgs: false,
+        include_finality: false,
     };
     
     assert!(empty_filter.chain_id.is_none());
@@ -177,6 +297,8 @@ async fn test_subscription_storage() {
             block_range: None,
             attributes: None,
             limit: Some(100),
+            include_reorgs: false,
+            include_finality: false,
         },
         created_at: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
         event_count: 0,
@@ -220,6 +342,8 @@ fn test_ws_message_types() {
             block_range: None,
             attributes: None,
             limit: None,
+            include_reorgs: false,
+            include_finality: false,
         },
     };
     assert!(serde_json::to_string(&subscribe).is_ok());
@@ -296,6 +420,8 @@ fn test_event_filters_json() {
             attrs
         }),
         limit: Some(50),
+        include_reorgs: false,
+        include_finality: false,
     };
     
     // Test serialization
@@ -394,6 +520,8 @@ async fn test_subscription_cleanup() {
             block_range: None,
             attributes: None,
             limit: None,
+            include_reorgs: false,
+            include_finality: false,
         },
         created_at: old_time,
         event_count: 0,
@@ -411,6 +539,8 @@ async fn test_subscription_cleanup() {
             block_range: None,
             attributes: None,
             limit: None,
+            include_reorgs: false,
+            include_finality: false,
         },
         created_at: new_time,
         event_count: 0,
@@ -432,4 +562,183 @@ async fn test_subscription_cleanup() {
     let remaining_subs = storage.load_all_subscriptions().await.unwrap();
     assert_eq!(remaining_subs.len(), 1);
     assert_eq!(remaining_subs[0].id, "new-sub");
-} 
\ No newline at end of file
+} 
+
+#[test]
+fn test_notification_messages_json() {
+    let reorg = WsMessage::Reorg {
+        subscription_id: "sub-1".to_string(),
+        chain_id: "ethereum".to_string(),
+        from_block: 100,
+        removed_event_ids: vec!["event-1".to_string(), "event-2".to_string()],
+    };
+    
+    let json = serde_json::to_value(&reorg).unwrap();
+    assert_eq!(json["type"], "reorg");
+    assert_eq!(json["from_block"], 100);
+    assert_eq!(json["removed_event_ids"][1], "event-2");
+    
+    let finalized = WsMessage::Finalized {
+        subscription_id: "sub-1".to_string(),
+        chain_id: "ethereum".to_string(),
+        block: 90,
+    };
+    
+    let json = serde_json::to_value(&finalized).unwrap();
+    assert_eq!(json["type"], "finalized");
+    assert_eq!(json["block"], 90);
+    
+    // Notifications are opt-in and default to off for existing clients
+    let filters: EventFilters = serde_json::from_value(json!({ "chain_id": "ethereum" })).unwrap();
+    assert!(!filters.include_reorgs);
+    assert!(!filters.include_finality);
+}
+
+#[tokio::test]
+async fn test_reorg_notifies_opted_in_subscriptions() {
+    let (manager, mut rx) = create_test_manager().await;
+    
+    manager.add_subscription("conn-1", "transfers".to_string(), notification_filters(Some("Transfer"), true, false)).await.unwrap();
+    manager.add_subscription("conn-1", "silent".to_string(), notification_filters(None, false, false)).await.unwrap();
+    
+    manager.dispatch_event(create_test_event_data("event-1", "ethereum", 100, "Transfer")).await;
+    manager.dispatch_event(create_test_event_data("event-2", "ethereum", 101, "Transfer")).await;
+    manager.dispatch_event(create_test_event_data("event-3", "ethereum", 102, "Approval")).await;
+    
+    // Drain the delivered events
+    while let Ok(message) = rx.try_recv() {
+        assert!(matches!(message, WsMessage::Event { .. }));
+    }
+    
+    let reorg = ReorgEvent {
+        chain_id: ChainId::from("ethereum"),
+        reorganized_blocks: vec![
+            ReorganizedBlock {
+                old_block: create_test_block(102),
+                new_block: create_test_block(102),
+                depth: 1,
+                detected_at: 0,
+            },
+            ReorganizedBlock {
+                old_block: create_test_block(101),
+                new_block: create_test_block(102),
+                depth: 2,
+                detected_at: 0,
+            },
+        ],
+        canonical_tip: create_test_block(102),
+    };
+    
+    assert_eq!(manager.notify_reorg(&reorg).await, 1);
+    
+    match rx.try_recv().unwrap() {
+        WsMessage::Reorg { subscription_id, chain_id, from_block, removed_event_ids } => {
+            assert_eq!(subscription_id, "transfers");
+            assert_eq!(chain_id, "ethereum");
+            assert_eq!(from_block, 101);
+            assert_eq!(removed_event_ids, vec!["event-2".to_string()]);
+        }
+        other => panic!("Expected Reorg message, got {:?}", other),
+    }
+    assert!(rx.try_recv().is_err());
+    
+    // The rolled back events are forgotten, so a repeated reorg is a no-op
+    assert_eq!(manager.notify_reorg(&reorg).await, 0);
+}
+
+#[tokio::test]
+async fn test_finality_notifications() {
+    let (manager, mut rx) = create_test_manager().await;
+    
+    manager.add_subscription("conn-1", "final".to_string(), notification_filters(None, true, true)).await.unwrap();
+    manager.dispatch_event(create_test_event_data("event-1", "ethereum", 100, "Transfer")).await;
+    while rx.try_recv().is_ok() {}
+    
+    // Non-final statuses are ignored
+    assert_eq!(manager.notify_block_status("ethereum", 100, BlockStatus::Safe).await, 0);
+    
+    assert_eq!(manager.notify_block_status("ethereum", 100, BlockStatus::Finalized).await, 1);
+    match rx.try_recv().unwrap() {
+        WsMessage::Finalized { subscription_id, chain_id, block } => {
+            assert_eq!(subscription_id, "final");
+            assert_eq!(chain_id, "ethereum");
+            assert_eq!(block, 100);
+        }
+        other => panic!("Expected Finalized message, got {:?}", other),
+    }
+    
+    // Already announced heights are not repeated
+    assert_eq!(manager.notify_block_status("ethereum", 100, BlockStatus::Finalized).await, 0);
+    
+    // Finalized events can no longer be reported as rolled back
+    let reorg = ReorgEvent {
+        chain_id: ChainId::from("ethereum"),
+        reorganized_blocks: vec![ReorganizedBlock {
+            old_block: create_test_block(100),
+            new_block: create_test_block(100),
+            depth: 1,
+            detected_at: 0,
+        }],
+        canonical_tip: create_test_block(100),
+    };
+    assert_eq!(manager.notify_reorg(&reorg).await, 0);
+}
+
+fn reorg_from(chain: &str, block: u64) -> ReorgEvent {
+    ReorgEvent {
+        chain_id: ChainId::from(chain),
+        reorganized_blocks: vec![ReorganizedBlock {
+            old_block: create_test_block(block),
+            new_block: create_test_block(block),
+            depth: 1,
+            detected_at: 0,
+        }],
+        canonical_tip: create_test_block(block),
+    }
+}
+
+#[tokio::test]
+async fn test_reorg_after_finality_poll_still_notifies() {
+    // Like the chain clients, the service reports its head as finalized
+    let (manager, mut rx) = create_test_manager_with(Arc::new(FinalityEventService {
+        chain_id: ChainId::from("ethereum"),
+        latest_block: 100,
+        finalized_block: None,
+    })).await;
+    
+    manager.add_subscription("conn-1", "transfers".to_string(), notification_filters(None, true, true)).await.unwrap();
+    manager.dispatch_event(create_test_event_data("event-1", "ethereum", 100, "Transfer")).await;
+    while rx.try_recv().is_ok() {}
+    
+    assert_eq!(manager.poll_finality().await, 0);
+    assert_eq!(manager.notify_reorg(&reorg_from("ethereum", 100)).await, 1);
+    match rx.try_recv().unwrap() {
+        WsMessage::Reorg { removed_event_ids, .. } => assert_eq!(removed_event_ids, vec!["event-1".to_string()]),
+        other => panic!("Expected Reorg message, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_finality_polled_for_every_delivered_chain() {
+    let (manager, mut rx) = create_test_manager_with(Arc::new(FinalityEventService {
+        chain_id: ChainId::from("ethereum"),
+        latest_block: 200,
+        finalized_block: Some(150),
+    })).await;
+    
+    let mut filters = notification_filters(None, true, true);
+    filters.chain_id = Some("neutron-1".to_string());
+    manager.add_subscription("conn-1", "neutron".to_string(), filters).await.unwrap();
+    manager.dispatch_event(create_test_event_data("event-1", "neutron-1", 100, "Transfer")).await;
+    manager.poll_finality().await;
+    
+    // The finalized height of a chain other than the service's own is announced
+    let finalized: Vec<(String, u64)> = std::iter::from_fn(|| rx.try_recv().ok())
+        .filter_map(|message| match message {
+            WsMessage::Finalized { chain_id, block, .. } => Some((chain_id, block)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(finalized, vec![("neutron-1".to_string(), 150)]);
+    assert_eq!(manager.notify_reorg(&reorg_from("neutron-1", 100)).await, 0);
+}
@@ -21,6 +21,7 @@ pub mod postgres_pool;
 pub mod profile;
 pub mod report;
 pub mod rocksdb_opt;
+pub mod smt_batch;
 pub mod suite;
 
 /// A measurement of a single benchmark operation
@@ -0,0 +1,211 @@
+// smt_batch.rs - Sparse Merkle Tree insertion benchmarks
+//
+// Purpose: Compares one-by-one SMT insertion with per-block batched insertion
+// as used by the causality indexer, in time and in backend round trips
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use indexer_causality::smt::{SmtBackend, SmtBatchEntry};
+use indexer_causality::types::Hash;
+use indexer_causality::{MemorySmtBackend, RocksSmtBackend, SparseMerkleTree};
+use indexer_core::Error;
+use super::{Measurement, BenchmarkReport};
+
+/// SMT backend wrapper counting the calls made to the wrapped backend
+#[derive(Debug, Clone)]
+pub struct CountingBackend<B: SmtBackend> {
+    inner: B,
+    reads: Arc<AtomicU64>,
+    writes: Arc<AtomicU64>,
+    batches: Arc<AtomicU64>,
+}
+
+impl<B: SmtBackend> CountingBackend<B> {
+    /// Wrap a backend with zeroed counters
+    pub fn new(inner: B) -> Self {
+        Self {
+            inner,
+            reads: Arc::new(AtomicU64::new(0)),
+            writes: Arc::new(AtomicU64::new(0)),
+            batches: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Number of `get` and `has` calls
+    pub fn reads(&self) -> u64 {
+        self.reads.load(Ordering::Relaxed)
+    }
+
+    /// Number of entries written, individually or in batches
+    pub fn writes(&self) -> u64 {
+        self.writes.load(Ordering::Relaxed)
+    }
+
+    /// Number of `set_batch` calls
+    pub fn batches(&self) -> u64 {
+        self.batches.load(Ordering::Relaxed)
+    }
+}
+
+#[async_trait]
+impl<B: SmtBackend> SmtBackend for CountingBackend<B> {
+    async fn get(&self, prefix: &[u8], key: &Hash) -> indexer_causality::Result<Option<Vec<u8>>> {
+        self.reads.fetch_add(1, Ordering::Relaxed);
+        self.inner.get(prefix, key).await
+    }
+
+    async fn set(&self, prefix: &[u8], key: &Hash, data: &[u8]) -> indexer_causality::Result<Option<Vec<u8>>> {
+        self.writes.fetch_add(1, Ordering::Relaxed);
+        self.inner.set(prefix, key, data).await
+    }
+
+    async fn remove(&self, prefix: &[u8], key: &Hash) -> indexer_causality::Result<Option<Vec<u8>>> {
+        self.inner.remove(prefix, key).await
+    }
+
+    async fn has(&self, prefix: &[u8], key: &Hash) -> indexer_causality::Result<bool> {
+        self.reads.fetch_add(1, Ordering::Relaxed);
+        self.inner.has(prefix, key).await
+    }
+
+    async fn set_batch(&self, entries: Vec<SmtBatchEntry>) -> indexer_causality::Result<()> {
+        self.batches.fetch_add(1, Ordering::Relaxed);
+        self.writes.fetch_add(entries.len() as u64, Ordering::Relaxed);
+        self.inner.set_batch(entries).await
+    }
+}
+
+/// Generate `num_blocks` blocks of `events_per_block` key/value pairs
+fn generate_blocks(num_blocks: usize, events_per_block: usize, value_size: usize) -> Vec<Vec<(Hash, Vec<u8>)>> {
+    let smt = SparseMerkleTree::with_sha256(MemorySmtBackend::new());
+    (0..num_blocks)
+        .map(|block| {
+            (0..events_per_block)
+                .map(|i| {
+                    let key = smt.hasher().key("smt-batch-benchmark", format!("{}-{}", block, i).as_bytes());
+                    (key, vec![(i % 251) as u8; value_size])
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Insert the blocks one entry at a time and then one batch per block into
+/// fresh backends, returning both measurements
+async fn compare_insertion<B, F>(
+    name: &str,
+    new_backend: F,
+    blocks: &[Vec<(Hash, Vec<u8>)>],
+    value_size: usize,
+) -> Result<Vec<Measurement>, Error>
+where
+    B: SmtBackend,
+    F: Fn() -> Result<B, Error>,
+{
+    let operations: u64 = blocks.iter().map(|block| block.len() as u64).sum();
+    let data_size = operations * value_size as u64;
+    let smt_error = |e: indexer_causality::CausalityError| Error::generic(format!("SMT error: {}", e));
+
+    let backend = CountingBackend::new(new_backend()?);
+    let smt = SparseMerkleTree::with_sha256(backend.clone());
+    let start = Instant::now();
+    let mut sequential_root = SparseMerkleTree::<CountingBackend<B>>::empty_root();
+    for block in blocks {
+        for (key, value) in block {
+            sequential_root = smt.insert(sequential_root, key, value).await.map_err(smt_error)?;
+        }
+    }
+    let sequential_duration = start.elapsed();
+    let sequential = Measurement::new(&format!("{}-sequential-insert", name), sequential_duration, operations, data_size)
+        .with_metric("backend_reads", backend.reads() as f64)
+        .with_metric("backend_writes", backend.writes() as f64)
+        .with_metric("backend_batches", backend.batches() as f64);
+
+    let backend = CountingBackend::new(new_backend()?);
+    let smt = SparseMerkleTree::with_sha256(backend.clone());
+    let start = Instant::now();
+    let mut batch_root = SparseMerkleTree::<CountingBackend<B>>::empty_root();
+    for block in blocks {
+        batch_root = smt.insert_batch(batch_root, block.clone()).await.map_err(smt_error)?;
+    }
+    let batch_duration = start.elapsed();
+
+    if batch_root != sequential_root {
+        return Err(Error::generic("Batched insertion produced a different SMT root"));
+    }
+
+    let speedup = if batch_duration.as_secs_f64() > 0.0 {
+        sequential_duration.as_secs_f64() / batch_duration.as_secs_f64()
+    } else {
+        0.0
+    };
+    let batch = Measurement::new(&format!("{}-batch-insert", name), batch_duration, operations, data_size)
+        .with_metric("backend_reads", backend.reads() as f64)
+        .with_metric("backend_writes", backend.writes() as f64)
+        .with_metric("backend_batches", backend.batches() as f64)
+        .with_metric("speedup", speedup);
+
+    Ok(vec![sequential, batch])
+}
+
+/// Compare one-by-one and per-block batched SMT insertion on the in-memory backend
+pub async fn benchmark_smt_batch_insert(
+    num_blocks: usize,
+    events_per_block: usize,
+    value_size: usize,
+) -> Result<BenchmarkReport, Error> {
+    let blocks = generate_blocks(num_blocks, events_per_block, value_size);
+    let measurements = compare_insertion("memory", || Ok(MemorySmtBackend::new()), &blocks, value_size).await?;
+
+    Ok(BenchmarkReport::new("smt_batch_insert_benchmark", measurements))
+}
+
+/// Compare one-by-one and per-block batched SMT insertion on RocksDB
+pub async fn benchmark_smt_batch_insert_rocks(
+    db_path: &str,
+    num_blocks: usize,
+    events_per_block: usize,
+    value_size: usize,
+) -> Result<BenchmarkReport, Error> {
+    let blocks = generate_blocks(num_blocks, events_per_block, value_size);
+    let run = std::sync::atomic::AtomicUsize::new(0);
+
+    let measurements = compare_insertion(
+        "rocksdb",
+        || {
+            let path = format!("{}-{}", db_path, run.fetch_add(1, Ordering::Relaxed));
+            let _ = std::fs::remove_dir_all(&path); // Clean up any existing DB
+            RocksSmtBackend::open(&path)
+                .map_err(|e| Error::generic(format!("Failed to open RocksDB SMT backend: {}", e)))
+        },
+        &blocks,
+        value_size,
+    ).await;
+
+    for i in 0..run.load(Ordering::Relaxed) {
+        let _ = std::fs::remove_dir_all(format!("{}-{}", db_path, i));
+    }
+
+    Ok(BenchmarkReport::new("smt_batch_insert_rocksdb_benchmark", measurements?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_benchmark_smt_batch_insert() {
+        let report = benchmark_smt_batch_insert(4, 32, 64).await.unwrap();
+
+        assert_eq!(report.measurements.len(), 2);
+        let sequential = &report.measurements[0];
+        let batch = &report.measurements[1];
+        assert_eq!(sequential.operations, 128);
+        assert_eq!(batch.metrics["backend_batches"], 4.0);
+        assert!(batch.metrics["backend_reads"] < sequential.metrics["backend_reads"]);
+        assert!(batch.metrics["backend_writes"] < sequential.metrics["backend_writes"]);
+    }
+}
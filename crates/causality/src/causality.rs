@@ -1,14 +1,15 @@
 /// Causality tracking and relationship management
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
 use std::time::SystemTime;
 
 use indexer_core::types::ChainId;
 
 use crate::error::{CausalityError, Result};
+use crate::export::{extract_subgraph, CausalitySubgraph, ExportOptions};
 use crate::types::{
     CausalityEvent, CausalityResource, CausalityEffect, CausalityTransaction,
     CausalityIntent, CausalityHandler, CausalityDomain, CausalityNullifier,
-    CrossChainReference, SmtHasher, Hash, EntityId, DomainId
+    CausalityEventData, CrossChainReference, ResourceFlow, SmtHasher, Hash, EntityId, DomainId
 };
 
 /// Represents a causal relationship between Resource Model entities
@@ -74,6 +75,12 @@ pub struct CausalityGraph {
     domain_entities: HashMap<DomainId, HashSet<EntityId>>,
     /// Chain-specific event indices (for cross-chain events)
     chain_events: HashMap<ChainId, HashSet<String>>,
+    /// Event IDs by the entity hash relations use for them
+    event_hashes: HashMap<Hash, String>,
+    /// Events spending from each resource flow endpoint, by block
+    spenders: HashMap<String, BTreeSet<(u64, String)>>,
+    /// Events sending to each resource flow endpoint, by block
+    senders: HashMap<String, BTreeSet<(u64, String)>>,
 }
 
 impl CausalityGraph {
@@ -92,15 +99,35 @@ impl CausalityGraph {
             reverse_relations: HashMap::new(),
             domain_entities: HashMap::new(),
             chain_events: HashMap::new(),
+            event_hashes: HashMap::new(),
+            spenders: HashMap::new(),
+            senders: HashMap::new(),
         }
     }
 
-    /// Add an event to the graph
+    /// Add an event and the entity it carries to the graph
     pub fn add_event(&mut self, event: CausalityEvent) -> Result<()> {
         let event_id = event.id.clone();
         let chain_id = event.chain_id.clone();
 
-        // Add to events
+        match &event.data {
+            CausalityEventData::Resource(resource) => self.add_resource(resource.clone())?,
+            CausalityEventData::Effect(effect) => self.add_effect(effect.clone())?,
+            CausalityEventData::Transaction(transaction) => self.add_transaction(transaction.clone())?,
+            CausalityEventData::Intent(intent) => self.add_intent(intent.clone())?,
+            CausalityEventData::Handler(handler) => self.add_handler(handler.clone())?,
+            CausalityEventData::Domain(domain) => self.add_domain(domain.clone())?,
+            CausalityEventData::Nullifier(nullifier) => self.add_nullifier(nullifier.clone())?,
+            CausalityEventData::CrossDomainMessage { .. } | CausalityEventData::TegStateChange { .. } => {}
+        }
+
+        self.event_hashes.insert(event_hash(&event_id), event_id.clone());
+
+        // Add to events, replacing the flows of an event with the same ID
+        if let Some(replaced) = self.events.remove(&event_id) {
+            self.unindex_flows(&replaced);
+        }
+        self.index_flows(&event);
         self.events.insert(event_id.clone(), event);
 
         // Add to chain index
@@ -112,6 +139,115 @@ impl CausalityGraph {
         Ok(())
     }
 
+    /// Remove an event, the entity it carries and its relations from the graph
+    pub fn remove_event(&mut self, event_id: &str) -> Option<CausalityEvent> {
+        let event = self.events.remove(event_id)?;
+        self.event_hashes.remove(&event_hash(event_id));
+        self.unindex_flows(&event);
+        self.remove_entity(&event.data);
+
+        if let Some(chain_events) = self.chain_events.get_mut(&event.chain_id) {
+            chain_events.remove(event_id);
+            if chain_events.is_empty() {
+                self.chain_events.remove(&event.chain_id);
+            }
+        }
+
+        if let Ok(event_hash) = self.string_to_hash(event_id) {
+            if let Some(relations) = self.relations.remove(&event_hash) {
+                for relation in relations {
+                    if let Some(sources) = self.reverse_relations.get_mut(&relation.to_entity) {
+                        sources.retain(|source| *source != event_hash);
+                    }
+                }
+            }
+            if let Some(sources) = self.reverse_relations.remove(&event_hash) {
+                for source in sources {
+                    if let Some(relations) = self.relations.get_mut(&source) {
+                        relations.retain(|relation| relation.to_entity != event_hash);
+                    }
+                }
+            }
+        }
+
+        Some(event)
+    }
+
+    /// Add the resource flows of `event` to the spender and sender indices
+    fn index_flows(&mut self, event: &CausalityEvent) {
+        let key = (event.block_number, event.id.clone());
+        let (inputs, outputs) = event_flows(event);
+        for input in inputs {
+            self.spenders.entry(input.from_event.clone()).or_default().insert(key.clone());
+        }
+        for output in outputs {
+            self.senders.entry(output.to_event.clone()).or_default().insert(key.clone());
+        }
+    }
+
+    /// Remove the resource flows of `event` from the spender and sender indices
+    fn unindex_flows(&mut self, event: &CausalityEvent) {
+        let key = (event.block_number, event.id.clone());
+        let (inputs, outputs) = event_flows(event);
+        for input in inputs {
+            remove_flow(&mut self.spenders, &input.from_event, &key);
+        }
+        for output in outputs {
+            remove_flow(&mut self.senders, &output.to_event, &key);
+        }
+    }
+
+    /// Remove the entity carried by event data from the graph
+    fn remove_entity(&mut self, data: &CausalityEventData) {
+        let (id, domain_id) = match data {
+            CausalityEventData::Resource(resource) => {
+                self.resources.remove(&resource.id);
+                (resource.id, resource.domain_id)
+            }
+            CausalityEventData::Effect(effect) => {
+                self.effects.remove(&effect.id);
+                (effect.id, effect.domain_id)
+            }
+            CausalityEventData::Transaction(transaction) => {
+                self.transactions.remove(&transaction.id);
+                (transaction.id, transaction.domain_id)
+            }
+            CausalityEventData::Intent(intent) => {
+                self.intents.remove(&intent.id);
+                (intent.id, intent.domain_id)
+            }
+            CausalityEventData::Handler(handler) => {
+                self.handlers.remove(&handler.id);
+                (handler.id, handler.domain_id)
+            }
+            CausalityEventData::Nullifier(nullifier) => {
+                // Fall back to the earliest remaining event consuming the same nullifier
+                let remaining = self.events.values()
+                    .filter_map(|event| match &event.data {
+                        CausalityEventData::Nullifier(other) if other.nullifier_hash == nullifier.nullifier_hash => {
+                            Some((event.block_number, &event.id, other))
+                        }
+                        _ => None,
+                    })
+                    .min_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)))
+                    .map(|(_, _, other)| other.clone());
+                match remaining {
+                    Some(other) => self.nullifiers.insert(nullifier.nullifier_hash, other),
+                    None => self.nullifiers.remove(&nullifier.nullifier_hash),
+                };
+                return;
+            }
+            // Domains stay registered, other entities may still live in them
+            CausalityEventData::Domain(_)
+            | CausalityEventData::CrossDomainMessage { .. }
+            | CausalityEventData::TegStateChange { .. } => return,
+        };
+
+        if let Some(entities) = self.domain_entities.get_mut(&domain_id) {
+            entities.remove(&id);
+        }
+    }
+
     /// Add a resource to the graph
     pub fn add_resource(&mut self, resource: CausalityResource) -> Result<()> {
         let resource_id = resource.id;
@@ -214,15 +350,23 @@ impl CausalityGraph {
     }
 
     /// Add a nullifier to the graph
+    ///
+    /// The first consumption of a nullifier is kept; reuse is reported by the
+    /// indexer's `ViolationDetector` rather than overwriting it here.
     pub fn add_nullifier(&mut self, nullifier: CausalityNullifier) -> Result<()> {
         let nullifier_id = nullifier.nullifier_hash;
         
         // Add to nullifiers (using nullifier_hash as the key)
-        self.nullifiers.insert(nullifier_id, nullifier);
+        self.nullifiers.entry(nullifier_id).or_insert(nullifier);
 
         Ok(())
     }
 
+    /// Get the first consumption of a nullifier
+    pub fn get_nullifier(&self, nullifier_hash: &Hash) -> Option<&CausalityNullifier> {
+        self.nullifiers.get(nullifier_hash)
+    }
+
     /// Get all entities for a specific domain
     pub fn get_domain_entities(&self, domain_id: &DomainId) -> Vec<EntityId> {
         self.domain_entities
@@ -272,18 +416,13 @@ impl CausalityGraph {
         self.intents.contains_key(&entity_id_typed) ||
         self.handlers.contains_key(&entity_id_typed) ||
         self.domains.contains_key(&entity_id_typed) ||
-        self.nullifiers.contains_key(entity_id)
+        self.nullifiers.contains_key(entity_id) ||
+        self.event_hashes.contains_key(entity_id)
     }
 
     /// Convert string to hash (simple implementation for now)
     fn string_to_hash(&self, s: &str) -> Result<Hash> {
-        use sha2::{Sha256, Digest};
-        let mut hasher = Sha256::new();
-        hasher.update(s.as_bytes());
-        let result = hasher.finalize();
-        let mut hash = [0u8; 32];
-        hash.copy_from_slice(&result);
-        Ok(hash)
+        Ok(event_hash(s))
     }
 
     /// Convert hash to string representation
@@ -311,7 +450,9 @@ impl CausalityGraph {
         if let Ok(event_hash) = self.string_to_hash(event_id) {
             return self.reverse_relations
                 .get(&event_hash)
-                .map(|hashes| hashes.iter().map(|h| self.hash_to_string(h)).collect())
+                .map(|hashes| hashes.iter()
+                    .map(|h| self.event_hashes.get(h).cloned().unwrap_or_else(|| self.hash_to_string(h)))
+                    .collect())
                 .unwrap_or_default();
         }
         Vec::new()
@@ -330,6 +471,98 @@ impl CausalityGraph {
             .unwrap_or_default()
     }
 
+    /// Get an event in the graph
+    pub fn get_event(&self, event_id: &str) -> Option<&CausalityEvent> {
+        self.events.get(event_id)
+    }
+
+    /// Iterate over all events in the graph
+    pub fn events(&self) -> impl Iterator<Item = &CausalityEvent> {
+        self.events.values()
+    }
+
+    /// Iterate over all relations in the graph
+    pub fn relations(&self) -> impl Iterator<Item = &CausalityRelation> {
+        self.relations.values().flatten()
+    }
+
+    /// Direct causal successors of `event_id`, sorted by event ID
+    ///
+    /// An event leads to another when a relation links them, when one of its
+    /// output resource flows targets the other event, or when it sends a
+    /// resource to an account the other event later spends from.
+    pub fn successors(&self, event_id: &str) -> Vec<String> {
+        let Some(event) = self.events.get(event_id) else {
+            return Vec::new();
+        };
+        let mut next: BTreeSet<&str> = self.related_events(self.relations.get(&event_hash(event_id))
+            .into_iter()
+            .flatten()
+            .map(|relation| &relation.to_entity));
+
+        for output in event_flows(event).1 {
+            if output.to_event != event.id && self.events.contains_key(&output.to_event) {
+                next.insert(&output.to_event);
+            }
+            // Spenders of the account from the event's block on
+            let spenders = self.spenders.get(&output.to_event).into_iter()
+                .flat_map(|spenders| spenders.range((event.block_number, String::new())..));
+            next.extend(spenders.map(|(_, id)| id.as_str()).filter(|id| *id != event.id));
+        }
+
+        next.into_iter().map(String::from).collect()
+    }
+
+    /// Direct causal predecessors of `event_id`, sorted by event ID
+    ///
+    /// The events `event_id` is a successor of.
+    pub fn predecessors(&self, event_id: &str) -> Vec<String> {
+        let Some(event) = self.events.get(event_id) else {
+            return Vec::new();
+        };
+        let mut previous: BTreeSet<&str> = self.related_events(self.reverse_relations.get(&event_hash(event_id))
+            .into_iter()
+            .flatten());
+
+        let senders = self.senders.get(event_id).into_iter().flatten();
+        previous.extend(senders.map(|(_, id)| id.as_str()).filter(|id| *id != event.id));
+        for input in event_flows(event).0 {
+            // Senders to the account up to the event's block
+            let senders = self.senders.get(&input.from_event).into_iter()
+                .flat_map(|senders| senders.iter().take_while(|(block, _)| *block <= event.block_number));
+            previous.extend(senders.map(|(_, id)| id.as_str()).filter(|id| *id != event.id));
+        }
+
+        previous.into_iter().map(String::from).collect()
+    }
+
+    /// IDs of the events among entity `hashes`
+    fn related_events<'a>(&'a self, hashes: impl Iterator<Item = &'a Hash>) -> BTreeSet<&'a str> {
+        hashes.filter_map(|hash| self.event_hashes.get(hash)).map(String::as_str).collect()
+    }
+
+    /// Direct causal successors of each event with any, as given by
+    /// [`successors`](Self::successors)
+    pub fn event_successors(&self) -> HashMap<String, Vec<String>> {
+        self.events.keys()
+            .map(|id| (id.clone(), self.successors(id)))
+            .filter(|(_, next)| !next.is_empty())
+            .collect()
+    }
+
+    /// Export the events around `root` within the bounds of `options`
+    ///
+    /// Returns `None` if the root event is not in the graph.
+    pub fn export_subgraph(&self, root: &str, options: &ExportOptions) -> Option<CausalitySubgraph> {
+        extract_subgraph(
+            root,
+            options,
+            |id| self.events.get(id),
+            |id| self.successors(id).into_iter().map(|to| (to, None)).collect(),
+            |id| self.predecessors(id),
+        )
+    }
+
     /// Find causal paths between two events
     pub fn find_causal_path(&self, from: &str, to: &str) -> Vec<Vec<String>> {
         let mut paths = Vec::new();
@@ -456,27 +689,31 @@ impl CausalityTracker {
     }
 
     /// Add an event and automatically detect causal relationships
-    pub fn add_event(&mut self, event: CausalityEvent) -> Result<()> {
+    ///
+    /// Returns the relations detected for the event.
+    pub fn add_event(&mut self, event: CausalityEvent) -> Result<Vec<CausalityRelation>> {
+        let mut relations = Vec::new();
+
         // Extract relationships based on event data type
         match &event.data {
             crate::types::CausalityEventData::Effect(effect) => {
                 // Add relationships based on effect inputs/outputs
                 for input in &effect.inputs {
-                    if self.graph.events.contains_key(&input.from_event) {
+                    let endpoints_known = self.graph.events.contains_key(&input.from_event)
+                        && (input.to_event == event.id || self.graph.events.contains_key(&input.to_event));
+                    if endpoints_known {
                         // Create a causal relationship for resource flow
                         let from_hash = self.graph.string_to_hash(&input.from_event)?;
                         let to_hash = self.graph.string_to_hash(&input.to_event)?;
                         
-                        let relation = CausalityRelation {
+                        relations.push(CausalityRelation {
                             from_entity: from_hash,
                             to_entity: to_hash,
                             relation_type: CausalityRelationType::ResourceFlow,
                             strength: 1.0,
                             established_at: std::time::SystemTime::now(),
                             metadata: std::collections::HashMap::new(),
-                        };
-                        
-                        self.graph.add_relation(relation)?;
+                        });
                     }
                 }
             },
@@ -492,10 +729,13 @@ impl CausalityTracker {
             }
         }
 
-        // Add the event to the graph
+        // Add the event to the graph before relating it
         self.graph.add_event(event)?;
+        for relation in &relations {
+            self.graph.add_relation(relation.clone())?;
+        }
 
-        Ok(())
+        Ok(relations)
     }
 
     /// Get the causality graph
@@ -565,6 +805,8 @@ pub struct CrossChainCausality {
     chain_trackers: HashMap<ChainId, CausalityTracker>,
     /// Cross-chain relationships
     cross_chain_relations: Vec<CrossChainReference>,
+    /// Indices of the cross-chain references from or to each event
+    reference_index: HashMap<String, Vec<usize>>,
     /// SMT hasher
     #[allow(dead_code)]
     hasher: Box<dyn SmtHasher>,
@@ -576,6 +818,7 @@ impl CrossChainCausality {
         Self {
             chain_trackers: HashMap::new(),
             cross_chain_relations: Vec::new(),
+            reference_index: HashMap::new(),
             hasher,
         }
     }
@@ -588,15 +831,337 @@ impl CrossChainCausality {
     }
 
     /// Add a cross-chain reference
+    ///
+    /// A reference of the same type between the same events is only added once.
     pub fn add_cross_chain_reference(&mut self, reference: CrossChainReference) {
-        self.cross_chain_relations.push(reference);
+        let known = self.references_of(&reference.source_event).any(|(_, existing)| {
+            existing.source_event == reference.source_event
+                && existing.target_event == reference.target_event
+                && existing.ref_type == reference.ref_type
+        });
+        if !known {
+            index_reference(&mut self.reference_index, self.cross_chain_relations.len(), &reference);
+            self.cross_chain_relations.push(reference);
+        }
+    }
+
+    /// Cross-chain references from or to `event_id`, with their indices
+    fn references_of<'a>(&'a self, event_id: &str) -> impl Iterator<Item = (usize, &'a CrossChainReference)> + 'a {
+        self.reference_index.get(event_id).into_iter()
+            .flatten()
+            .map(|&index| (index, &self.cross_chain_relations[index]))
+    }
+
+    /// Add an event to the tracker of its chain
+    pub fn add_event(&mut self, event: CausalityEvent) -> Result<()> {
+        let chain_id = event.chain_id.clone();
+        self.get_chain_tracker(&chain_id).add_event(event)?;
+        Ok(())
+    }
+
+    /// Remove an event and the cross-chain references touching it
+    pub fn remove_event(&mut self, event_id: &str) -> Option<CausalityEvent> {
+        if self.reference_index.contains_key(event_id) {
+            // Removing references shifts the indices of the later ones
+            self.cross_chain_relations
+                .retain(|reference| reference.source_event != event_id && reference.target_event != event_id);
+            self.reference_index.clear();
+            for (index, reference) in self.cross_chain_relations.iter().enumerate() {
+                index_reference(&mut self.reference_index, index, reference);
+            }
+        }
+        self.chain_trackers.values_mut()
+            .find_map(|tracker| tracker.graph_mut().remove_event(event_id))
+    }
+
+    /// Get the cross-chain references
+    pub fn references(&self) -> &[CrossChainReference] {
+        &self.cross_chain_relations
+    }
+
+    /// Get an event from the tracker of any chain
+    pub fn get_event(&self, event_id: &str) -> Option<&CausalityEvent> {
+        self.chain_trackers.values().find_map(|tracker| tracker.graph().get_event(event_id))
+    }
+
+    /// Export the events around `root` across all chains
+    ///
+    /// Cross-chain references become `CrossChain` edges labelled with
+    /// their reference type. Returns `None` if the root event is unknown.
+    pub fn export_subgraph(&self, root: &str, options: &ExportOptions) -> Option<CausalitySubgraph> {
+        let graph = self.path_graph();
+        extract_subgraph(
+            root,
+            options,
+            |id| self.get_event(id),
+            |id| {
+                graph.successors(id).into_iter()
+                    .map(|(to, reference)| (to, reference.map(|index| &self.cross_chain_relations[index])))
+                    .collect()
+            },
+            |id| graph.predecessors(id),
+        )
+    }
+
+    /// Find the shortest causal path from one event to another
+    ///
+    /// Paths follow causal links within each chain and cross-chain references
+    /// between chains, using at most `max_depth` hops.
+    pub fn find_shortest_path(&self, from_event: &str, to_event: &str, max_depth: usize) -> Option<CrossChainPath> {
+        let graph = self.path_graph();
+        let parents = graph.bfs(&[from_event.to_string()], max_depth);
+        parents.contains_key(to_event).then(|| graph.path_to(to_event, &parents))
+    }
+
+    /// Find up to `limit` causal paths from one event to another with at most `max_depth` hops
+    ///
+    /// Paths are returned shortest first, then ordered by their events. The
+    /// search deepens one hop at a time and stops once `limit` paths are found.
+    pub fn find_paths(&self, from_event: &str, to_event: &str, max_depth: usize, limit: usize) -> Vec<CrossChainPath> {
+        let graph = self.path_graph();
+        let mut found = Vec::new();
+        let mut current = vec![(from_event.to_string(), None)];
+        let mut on_path = HashSet::from([from_event.to_string()]);
+
+        for length in 0..=max_depth {
+            if found.len() >= limit {
+                break;
+            }
+            graph.collect_paths(to_event, length, &mut current, &mut on_path, &mut found, limit);
+        }
+
+        found.iter().map(|steps| graph.build_path(steps)).collect()
     }
 
     /// Find cross-chain causal paths
-    pub fn find_cross_chain_paths(&self, _from_chain: &ChainId, _to_chain: &ChainId) -> Vec<CrossChainPath> {
-        // This would implement sophisticated cross-chain path finding
-        // For now, return empty vector as placeholder
-        Vec::new()
+    ///
+    /// Returns the shortest path into each event on `to_chain` that can be
+    /// reached from an event on `from_chain` through at least one cross-chain
+    /// reference, using at most `DEFAULT_MAX_PATH_DEPTH` hops.
+    pub fn find_cross_chain_paths(&self, from_chain: &ChainId, to_chain: &ChainId) -> Vec<CrossChainPath> {
+        let graph = self.path_graph();
+        let mut sources: BTreeSet<String> = self.chain_trackers.get(from_chain).into_iter()
+            .flat_map(|tracker| tracker.graph().events.keys().cloned())
+            .collect();
+        // Events only known from the references to or from them
+        for reference in &self.cross_chain_relations {
+            for event_id in [&reference.source_event, &reference.target_event] {
+                if graph.chain(event_id) == Some(from_chain) {
+                    sources.insert(event_id.clone());
+                }
+            }
+        }
+        let sources: Vec<String> = sources.into_iter().collect();
+
+        let parents = graph.bfs(&sources, DEFAULT_MAX_PATH_DEPTH);
+
+        let mut targets: Vec<&String> = parents.keys()
+            .filter(|id| graph.chain(id) == Some(to_chain))
+            .collect();
+        targets.sort();
+
+        targets.into_iter()
+            .map(|target| graph.path_to(target, &parents))
+            .filter(|path| !path.references.is_empty())
+            .collect()
+    }
+
+    /// Combined graph of all chains and the cross-chain references between them
+    fn path_graph(&self) -> PathGraph<'_> {
+        PathGraph { causality: self }
+    }
+}
+
+/// Add the reference at `index` to the references of its events
+fn index_reference(reference_index: &mut HashMap<String, Vec<usize>>, index: usize, reference: &CrossChainReference) {
+    reference_index.entry(reference.source_event.clone()).or_default().push(index);
+    if reference.target_event != reference.source_event {
+        reference_index.entry(reference.target_event.clone()).or_default().push(index);
+    }
+}
+
+/// Default maximum number of hops in a cross-chain path search
+pub const DEFAULT_MAX_PATH_DEPTH: usize = 16;
+
+/// Entity hash that relations use to refer to an event
+pub fn event_hash(event_id: &str) -> Hash {
+    use sha2::{Sha256, Digest};
+    let mut hasher = Sha256::new();
+    hasher.update(event_id.as_bytes());
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&hasher.finalize());
+    hash
+}
+
+/// Remove the event `key` from the events of `endpoint` in a flow index
+fn remove_flow(index: &mut HashMap<String, BTreeSet<(u64, String)>>, endpoint: &str, key: &(u64, String)) {
+    if let Some(events) = index.get_mut(endpoint) {
+        events.remove(key);
+        if events.is_empty() {
+            index.remove(endpoint);
+        }
+    }
+}
+
+/// Input and output resource flows of an event
+pub(crate) fn event_flows(event: &CausalityEvent) -> (&[ResourceFlow], &[ResourceFlow]) {
+    match &event.data {
+        CausalityEventData::Effect(effect) => (effect.inputs.as_slice(), effect.outputs.as_slice()),
+        CausalityEventData::Transaction(transaction) => (transaction.inputs.as_slice(), transaction.outputs.as_slice()),
+        _ => (&[], &[]),
+    }
+}
+
+/// Event graph spanning all chains, used for path searches
+///
+/// Links are looked up from the indices of the chain graphs and the
+/// cross-chain references as the search reaches each event.
+struct PathGraph<'a> {
+    causality: &'a CrossChainCausality,
+}
+
+/// Event on a search path and the index of the cross-chain reference used to reach it
+type PathStep = (String, Option<usize>);
+
+/// Predecessor of an event on a search path and the reference used to reach it
+type Parent = Option<PathStep>;
+
+impl PathGraph<'_> {
+    /// Chain of an event, from its tracker or else from the first
+    /// cross-chain reference to or from it
+    fn chain(&self, event_id: &str) -> Option<&ChainId> {
+        let tracked = self.causality.chain_trackers.iter()
+            .find(|(_, tracker)| tracker.graph().get_event(event_id).is_some())
+            .map(|(chain, _)| chain);
+        tracked.or_else(|| {
+            self.causality.references_of(event_id).next().map(|(_, reference)| {
+                if reference.source_event == event_id { &reference.source_chain } else { &reference.target_chain }
+            })
+        })
+    }
+
+    /// Successors of an event, with the cross-chain reference used if any,
+    /// sorted so that path enumeration is deterministic
+    fn successors(&self, event_id: &str) -> Vec<PathStep> {
+        let mut next: Vec<PathStep> = self.causality.chain_trackers.values()
+            .flat_map(|tracker| tracker.graph().successors(event_id))
+            .map(|to| (to, None))
+            .collect();
+        next.extend(self.causality.references_of(event_id)
+            .filter(|(_, reference)| reference.source_event == event_id)
+            .map(|(index, reference)| (reference.target_event.clone(), Some(index))));
+        next.sort();
+        next
+    }
+
+    /// Predecessors of an event, within its chain or through cross-chain references
+    fn predecessors(&self, event_id: &str) -> Vec<String> {
+        let mut previous: Vec<String> = self.causality.chain_trackers.values()
+            .flat_map(|tracker| tracker.graph().predecessors(event_id))
+            .collect();
+        previous.extend(self.causality.references_of(event_id)
+            .filter(|(_, reference)| reference.target_event == event_id)
+            .map(|(_, reference)| reference.source_event.clone()));
+        previous
+    }
+
+    /// Breadth-first search from `sources`, returning the parent of each reached event
+    fn bfs(&self, sources: &[String], max_depth: usize) -> HashMap<String, Parent> {
+        let mut parents: HashMap<String, Parent> = HashMap::new();
+        let mut queue = VecDeque::new();
+
+        for source in sources {
+            if parents.insert(source.clone(), None).is_none() {
+                queue.push_back((source.clone(), 0));
+            }
+        }
+
+        while let Some((current, depth)) = queue.pop_front() {
+            if depth >= max_depth {
+                continue;
+            }
+            for (next, reference) in self.successors(&current) {
+                if !parents.contains_key(&next) {
+                    parents.insert(next.clone(), Some((current.clone(), reference)));
+                    queue.push_back((next, depth + 1));
+                }
+            }
+        }
+
+        parents
+    }
+
+    /// Rebuild the path to `target` from the parents found by `bfs`
+    fn path_to(&self, target: &str, parents: &HashMap<String, Parent>) -> CrossChainPath {
+        let mut steps = Vec::new();
+        let mut current = target.to_string();
+        while let Some(Some((parent, reference))) = parents.get(&current) {
+            steps.push((current, *reference));
+            current = parent.clone();
+        }
+        steps.push((current, None));
+        steps.reverse();
+
+        self.build_path(&steps)
+    }
+
+    /// Depth-first enumeration of simple paths of exactly `length` hops ending at `target`
+    ///
+    /// Paths are appended to `found` in order of their events until it holds
+    /// `limit` paths.
+    fn collect_paths(
+        &self,
+        target: &str,
+        length: usize,
+        current: &mut Vec<PathStep>,
+        on_path: &mut HashSet<String>,
+        found: &mut Vec<Vec<PathStep>>,
+        limit: usize,
+    ) {
+        let (last, _) = current.last().expect("path has a step").clone();
+        let hops = current.len() - 1;
+        if last == target {
+            if hops == length {
+                found.push(current.clone());
+            }
+            return;
+        }
+        if hops >= length {
+            return;
+        }
+
+        for (next, reference) in self.successors(&last) {
+            if found.len() >= limit {
+                return;
+            }
+            if on_path.insert(next.clone()) {
+                current.push((next.clone(), reference));
+                self.collect_paths(target, length, current, on_path, found, limit);
+                current.pop();
+                on_path.remove(&next);
+            }
+        }
+    }
+
+    /// Build a path from steps, each holding the reference used to reach it
+    fn build_path(&self, steps: &[PathStep]) -> CrossChainPath {
+        let mut chains: Vec<ChainId> = Vec::new();
+        for (event_id, _) in steps {
+            if let Some(chain) = self.chain(event_id) {
+                if chains.last() != Some(chain) {
+                    chains.push(chain.clone());
+                }
+            }
+        }
+
+        CrossChainPath {
+            chains,
+            events: steps.iter().map(|(id, _)| id.clone()).collect(),
+            references: steps.iter()
+                .filter_map(|(_, reference)| reference.map(|i| self.causality.cross_chain_relations[i].clone()))
+                .collect(),
+            length: steps.len().saturating_sub(1),
+        }
     }
 }
 
@@ -610,6 +1175,210 @@ pub struct CrossChainPath {
     pub events: Vec<String>,
     /// Cross-chain references used
     pub references: Vec<CrossChainReference>,
-    /// Total path length
+    /// Number of hops in the path
     pub length: usize,
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::{ExportDirection, ExportEdgeKind};
+    use crate::types::{CausalityEventType, HandlerId, TypedDomain};
+
+    /// Effect moving a resource from the `from` account to the `to` account
+    fn transfer(id: &str, chain: &str, block_number: u64, from: &str, to: &str) -> CausalityEvent {
+        let flow = |from_event: String, to_event: String| ResourceFlow {
+            from_event,
+            to_event,
+            resource_type: "token:usdc".to_string(),
+            resource_data: b"100".to_vec(),
+            timestamp: SystemTime::UNIX_EPOCH,
+        };
+
+        CausalityEvent {
+            id: id.to_string(),
+            chain_id: ChainId(chain.to_string()),
+            block_number,
+            tx_hash: format!("0x{}", id),
+            event_type: CausalityEventType::EffectEvent,
+            timestamp: SystemTime::UNIX_EPOCH,
+            data: CausalityEventData::Effect(CausalityEffect {
+                id: EntityId::null(),
+                name: id.to_string(),
+                domain_id: DomainId::null(),
+                effect_type: "token_transfer".to_string(),
+                inputs: vec![flow(format!("{}:{}", chain, from), id.to_string())],
+                outputs: vec![flow(id.to_string(), format!("{}:{}", chain, to))],
+                expression: None,
+                timestamp: SystemTime::UNIX_EPOCH,
+                scoped_by: HandlerId::null(),
+                intent_id: None,
+                source_typed_domain: TypedDomain::default(),
+                target_typed_domain: TypedDomain::default(),
+                originating_dataflow_instance: None,
+            }),
+        }
+    }
+
+    fn bridge(source: &str, target: &str) -> CrossChainReference {
+        CrossChainReference {
+            source_chain: ChainId("ethereum".to_string()),
+            target_chain: ChainId("neutron-1".to_string()),
+            source_event: source.to_string(),
+            target_event: target.to_string(),
+            ref_type: "bridge".to_string(),
+            ref_data: Vec::new(),
+            timestamp: SystemTime::UNIX_EPOCH,
+        }
+    }
+
+    /// Ethereum deposit bridged to Neutron, where the minted funds are then executed
+    fn deposit_scenario() -> CrossChainCausality {
+        let mut causality = CrossChainCausality::new(Box::new(crate::smt::Sha256SmtHasher));
+        causality.add_event(transfer("eth-deposit", "ethereum", 10, "alice", "bridge")).unwrap();
+        causality.add_event(transfer("eth-other", "ethereum", 11, "carol", "dave")).unwrap();
+        causality.add_event(transfer("ntrn-mint", "neutron-1", 5, "bridge", "bob")).unwrap();
+        causality.add_event(transfer("ntrn-exec", "neutron-1", 6, "bob", "vault")).unwrap();
+        causality.add_cross_chain_reference(bridge("eth-deposit", "ntrn-mint"));
+        causality
+    }
+
+    #[test]
+    fn test_event_successors_follow_resource_flows() {
+        let causality = deposit_scenario();
+        let graph = causality.chain_trackers[&ChainId("neutron-1".to_string())].graph();
+
+        let successors = graph.event_successors();
+        assert_eq!(successors.get("ntrn-mint"), Some(&vec!["ntrn-exec".to_string()]));
+        assert!(!successors.contains_key("ntrn-exec"));
+    }
+
+    #[test]
+    fn test_successors_follow_spenders_by_block() {
+        let mut graph = CausalityGraph::new();
+        graph.add_event(transfer("mint", "neutron-1", 5, "bridge", "bob")).unwrap();
+        graph.add_event(transfer("exec", "neutron-1", 6, "bob", "vault")).unwrap();
+        // Spends from the account before the mint sent to it
+        graph.add_event(transfer("early", "neutron-1", 4, "bob", "carol")).unwrap();
+
+        assert_eq!(graph.successors("mint"), vec!["exec"]);
+        assert_eq!(graph.predecessors("exec"), vec!["mint"]);
+        assert!(graph.predecessors("early").is_empty());
+
+        // Replacing an event re-indexes its flows
+        graph.add_event(transfer("exec", "neutron-1", 3, "bob", "vault")).unwrap();
+        assert!(graph.successors("mint").is_empty());
+
+        graph.add_event(transfer("exec", "neutron-1", 6, "bob", "vault")).unwrap();
+        graph.remove_event("exec");
+        assert!(graph.successors("mint").is_empty());
+        assert!(graph.predecessors("exec").is_empty());
+    }
+
+    #[test]
+    fn test_shortest_path_crosses_chains() {
+        let causality = deposit_scenario();
+
+        let path = causality.find_shortest_path("eth-deposit", "ntrn-exec", DEFAULT_MAX_PATH_DEPTH).unwrap();
+        assert_eq!(path.events, vec!["eth-deposit", "ntrn-mint", "ntrn-exec"]);
+        assert_eq!(path.chains, vec![ChainId("ethereum".to_string()), ChainId("neutron-1".to_string())]);
+        assert_eq!(path.references.len(), 1);
+        assert_eq!(path.references[0].source_event, "eth-deposit");
+        assert_eq!(path.length, 2);
+
+        assert!(causality.find_shortest_path("eth-other", "ntrn-exec", DEFAULT_MAX_PATH_DEPTH).is_none());
+        assert!(causality.find_shortest_path("eth-deposit", "ntrn-exec", 1).is_none());
+    }
+
+    #[test]
+    fn test_all_paths_up_to_depth() {
+        let mut causality = deposit_scenario();
+        // A second, direct route from the deposit to the execution
+        causality.add_cross_chain_reference(bridge("eth-deposit", "ntrn-exec"));
+
+        let paths = causality.find_paths("eth-deposit", "ntrn-exec", DEFAULT_MAX_PATH_DEPTH, usize::MAX);
+        assert_eq!(paths.len(), 2);
+        assert_eq!(paths[0].events, vec!["eth-deposit", "ntrn-exec"]);
+        assert_eq!(paths[1].events, vec!["eth-deposit", "ntrn-mint", "ntrn-exec"]);
+
+        assert_eq!(causality.find_paths("eth-deposit", "ntrn-exec", 1, usize::MAX).len(), 1);
+
+        // The limit keeps the shortest paths
+        let first = causality.find_paths("eth-deposit", "ntrn-exec", DEFAULT_MAX_PATH_DEPTH, 1);
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].events, vec!["eth-deposit", "ntrn-exec"]);
+        assert!(causality.find_paths("eth-deposit", "ntrn-exec", DEFAULT_MAX_PATH_DEPTH, 0).is_empty());
+    }
+
+    #[test]
+    fn test_find_cross_chain_paths_between_chains() {
+        let mut causality = deposit_scenario();
+        let ethereum = ChainId("ethereum".to_string());
+        let neutron = ChainId("neutron-1".to_string());
+
+        let paths = causality.find_cross_chain_paths(&ethereum, &neutron);
+        let targets: Vec<&str> = paths.iter().map(|p| p.events.last().unwrap().as_str()).collect();
+        assert_eq!(targets, vec!["ntrn-exec", "ntrn-mint"]);
+        assert!(paths.iter().all(|p| p.events[0] == "eth-deposit"));
+        assert!(causality.find_cross_chain_paths(&neutron, &ethereum).is_empty());
+
+        // Removing the bridged event drops the reference with it
+        causality.remove_event("ntrn-mint");
+        assert!(causality.references().is_empty());
+        assert!(causality.find_cross_chain_paths(&ethereum, &neutron).is_empty());
+    }
+
+    #[test]
+    fn test_export_subgraph_across_chains() {
+        let causality = deposit_scenario();
+
+        let subgraph = causality.export_subgraph("ntrn-mint", &ExportOptions::default()).unwrap();
+        let ids: Vec<&str> = subgraph.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, vec!["eth-deposit", "ntrn-mint", "ntrn-exec"]);
+        assert_eq!(subgraph.edges.len(), 2);
+        assert_eq!(subgraph.edges[0].kind, ExportEdgeKind::CrossChain);
+        assert_eq!(subgraph.edges[0].label.as_deref(), Some("bridge"));
+        assert_eq!(subgraph.edges[1].kind, ExportEdgeKind::Causal);
+
+        let forward = ExportOptions { direction: ExportDirection::Forward, ..ExportOptions::default() };
+        let subgraph = causality.export_subgraph("ntrn-mint", &forward).unwrap();
+        assert_eq!(subgraph.nodes.len(), 2);
+
+        let shallow = ExportOptions { max_depth: 1, ..ExportOptions::default() };
+        let subgraph = causality.export_subgraph("eth-deposit", &shallow).unwrap();
+        let ids: Vec<&str> = subgraph.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, vec!["eth-deposit", "ntrn-mint"]);
+
+        assert!(causality.export_subgraph("missing", &ExportOptions::default()).is_none());
+    }
+
+    #[test]
+    fn test_export_subgraph_leaves_out_siblings() {
+        let mut causality = deposit_scenario();
+        // A second effect of the deposit, unrelated to the mint
+        causality.add_event(transfer("ntrn-refund", "neutron-1", 7, "carol", "erin")).unwrap();
+        causality.add_cross_chain_reference(bridge("eth-deposit", "ntrn-refund"));
+
+        let subgraph = causality.export_subgraph("ntrn-mint", &ExportOptions::default()).unwrap();
+        let ids: Vec<&str> = subgraph.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, vec!["eth-deposit", "ntrn-mint", "ntrn-exec"]);
+
+        let subgraph = causality.export_subgraph("eth-deposit", &ExportOptions::default()).unwrap();
+        let ids: Vec<&str> = subgraph.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, vec!["eth-deposit", "ntrn-mint", "ntrn-exec", "ntrn-refund"]);
+    }
+
+    #[test]
+    fn test_export_subgraph_respects_time_window() {
+        let causality = deposit_scenario();
+        let window = ExportOptions {
+            since: Some(SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1)),
+            ..ExportOptions::default()
+        };
+
+        // The root is kept even when outside the window
+        let subgraph = causality.export_subgraph("ntrn-mint", &window).unwrap();
+        assert_eq!(subgraph.nodes.len(), 1);
+        assert!(subgraph.edges.is_empty());
+    }
+}
@@ -47,6 +47,10 @@ pub enum CausalityError {
     #[error("Configuration error: {0}")]
     ConfigError(String),
 
+    /// Event could not be mapped to causality data
+    #[error("Event mapping failed: {0}")]
+    MappingError(String),
+
     /// Generic error
     #[error("Generic error: {0}")]
     Generic(String),
@@ -120,4 +124,9 @@ impl CausalityError {
     pub fn invalid_hash(msg: impl Into<String>) -> Self {
         Self::InvalidHash(msg.into())
     }
+
+    /// Create an event mapping error
+    pub fn mapping_error(msg: impl Into<String>) -> Self {
+        Self::MappingError(msg.into())
+    }
 } 
\ No newline at end of file
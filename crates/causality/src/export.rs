@@ -0,0 +1,467 @@
+/// Export of causality subgraphs to Graphviz DOT, GraphML and JSON
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::fmt::Write;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::{CausalityError, Result};
+use crate::types::{CausalityEvent, CrossChainReference};
+
+/// Output format of a subgraph export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Graphviz DOT
+    Dot,
+    /// GraphML XML
+    GraphMl,
+    /// JSON object with `nodes` and `edges` arrays
+    Json,
+}
+
+impl ExportFormat {
+    /// MIME type of the rendered output
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Dot => "text/vnd.graphviz",
+            Self::GraphMl => "application/graphml+xml",
+            Self::Json => "application/json",
+        }
+    }
+
+    /// Conventional file extension of the rendered output
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Dot => "dot",
+            Self::GraphMl => "graphml",
+            Self::Json => "json",
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = CausalityError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "dot" | "graphviz" => Ok(Self::Dot),
+            "graphml" => Ok(Self::GraphMl),
+            "json" => Ok(Self::Json),
+            other => Err(CausalityError::ConfigError(format!(
+                "Unknown export format '{}', expected dot, graphml or json", other
+            ))),
+        }
+    }
+}
+
+/// Which side of the root event to include in an export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportDirection {
+    /// Events caused by the root
+    Forward,
+    /// Events that led to the root
+    Backward,
+    /// Both causes and effects of the root
+    #[default]
+    Both,
+}
+
+impl FromStr for ExportDirection {
+    type Err = CausalityError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "forward" | "effects" => Ok(Self::Forward),
+            "backward" | "causes" => Ok(Self::Backward),
+            "both" => Ok(Self::Both),
+            other => Err(CausalityError::ConfigError(format!(
+                "Unknown export direction '{}', expected forward, backward or both", other
+            ))),
+        }
+    }
+}
+
+/// Bounds of a subgraph export around a root event
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    /// Maximum number of hops from the root
+    pub max_depth: usize,
+    /// Side of the root to follow
+    pub direction: ExportDirection,
+    /// Leave out events before this time
+    pub since: Option<SystemTime>,
+    /// Leave out events after this time
+    pub until: Option<SystemTime>,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: DEFAULT_EXPORT_DEPTH,
+            direction: ExportDirection::Both,
+            since: None,
+            until: None,
+        }
+    }
+}
+
+impl ExportOptions {
+    /// Whether an event falls inside the time window
+    fn in_window(&self, event: &CausalityEvent) -> bool {
+        self.since.map_or(true, |since| event.timestamp >= since)
+            && self.until.map_or(true, |until| event.timestamp <= until)
+    }
+}
+
+/// Default number of hops included around the root event
+pub const DEFAULT_EXPORT_DEPTH: usize = 3;
+
+/// Event in an exported subgraph
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExportNode {
+    /// Event ID
+    pub id: String,
+    /// Chain the event occurred on
+    pub chain_id: String,
+    /// Block the event was included in
+    pub block_number: u64,
+    /// Transaction hash
+    pub tx_hash: String,
+    /// Causality event type
+    pub event_type: String,
+    /// Event time in seconds since the Unix epoch
+    pub timestamp: u64,
+    /// Hops from the root event
+    pub distance: usize,
+}
+
+/// Kind of link between two exported events
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ExportEdgeKind {
+    /// Causal link within a chain
+    Causal,
+    /// Cross-chain reference
+    CrossChain,
+}
+
+impl ExportEdgeKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Causal => "causal",
+            Self::CrossChain => "cross_chain",
+        }
+    }
+}
+
+/// Causal link in an exported subgraph
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExportEdge {
+    /// Causing event ID
+    pub source: String,
+    /// Caused event ID
+    pub target: String,
+    /// Kind of link
+    pub kind: ExportEdgeKind,
+    /// Reference type for cross-chain links (e.g. "bridge")
+    pub label: Option<String>,
+}
+
+/// Subgraph around a root event, ready to be rendered
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CausalitySubgraph {
+    /// Event the export is rooted at
+    pub root: String,
+    /// Events ordered by chain, block and ID
+    pub nodes: Vec<ExportNode>,
+    /// Links between the events, ordered by source and target
+    pub edges: Vec<ExportEdge>,
+}
+
+/// Successor of an event, with the cross-chain reference used if any
+pub(crate) type ExportLink<'a> = (String, Option<&'a CrossChainReference>);
+
+/// Collect the events within `options` of `root` and the links between them
+///
+/// Returns `None` if the root event is unknown. Events outside the time
+/// window are left out and not traversed; the root is always included.
+/// Exporting both directions takes the union of the root's effects and its
+/// causes, each followed in its own direction, so other effects of the
+/// root's causes are not pulled in. Links are looked up with `successors`
+/// and `predecessors` for the events reached only.
+pub(crate) fn extract_subgraph<'a>(
+    root: &str,
+    options: &ExportOptions,
+    event: impl Fn(&str) -> Option<&'a CausalityEvent>,
+    successors: impl Fn(&str) -> Vec<ExportLink<'a>>,
+    predecessors: impl Fn(&str) -> Vec<String>,
+) -> Option<CausalitySubgraph> {
+    let root_event = event(root)?;
+
+    let root_id = root_event.id.as_str();
+    let mut distances: HashMap<String, usize> = HashMap::from([(root_id.to_string(), 0)]);
+    if options.direction != ExportDirection::Backward {
+        let effects = traverse(root_id, options, &event, |current| {
+            successors(current).into_iter().map(|(to, _)| to).collect()
+        });
+        merge_distances(&mut distances, effects);
+    }
+    if options.direction != ExportDirection::Forward {
+        let causes = traverse(root_id, options, &event, &predecessors);
+        merge_distances(&mut distances, causes);
+    }
+
+    let mut nodes: Vec<ExportNode> = distances.iter()
+        .filter_map(|(id, distance)| event(id).map(|e| export_node(e, *distance)))
+        .collect();
+    nodes.sort_by(|a, b| (&a.chain_id, a.block_number, &a.id).cmp(&(&b.chain_id, b.block_number, &b.id)));
+
+    let mut exported: BTreeSet<ExportEdge> = BTreeSet::new();
+    for from in distances.keys() {
+        for (to, reference) in successors(from) {
+            if distances.contains_key(&to) {
+                exported.insert(ExportEdge {
+                    source: from.clone(),
+                    target: to,
+                    kind: if reference.is_some() { ExportEdgeKind::CrossChain } else { ExportEdgeKind::Causal },
+                    label: reference.map(|r| r.ref_type.clone()),
+                });
+            }
+        }
+    }
+
+    Some(CausalitySubgraph {
+        root: root_event.id.clone(),
+        nodes,
+        edges: exported.into_iter().collect(),
+    })
+}
+
+/// Distance of the events within `options.max_depth` hops of `root`,
+/// following `neighbours` only
+fn traverse<'a>(
+    root: &str,
+    options: &ExportOptions,
+    event: &impl Fn(&str) -> Option<&'a CausalityEvent>,
+    neighbours: impl Fn(&str) -> Vec<String>,
+) -> HashMap<String, usize> {
+    let mut distances: HashMap<String, usize> = HashMap::from([(root.to_string(), 0)]);
+    let mut queue = VecDeque::from([(root.to_string(), 0)]);
+
+    while let Some((current, distance)) = queue.pop_front() {
+        if distance >= options.max_depth {
+            continue;
+        }
+
+        for next in neighbours(&current) {
+            if distances.contains_key(&next) || !event(&next).is_some_and(|e| options.in_window(e)) {
+                continue;
+            }
+            distances.insert(next.clone(), distance + 1);
+            queue.push_back((next, distance + 1));
+        }
+    }
+
+    distances
+}
+
+/// Add the events of `other` to `distances`, keeping the shorter distance
+fn merge_distances(distances: &mut HashMap<String, usize>, other: HashMap<String, usize>) {
+    for (id, distance) in other {
+        let entry = distances.entry(id).or_insert(distance);
+        *entry = (*entry).min(distance);
+    }
+}
+
+fn export_node(event: &CausalityEvent, distance: usize) -> ExportNode {
+    ExportNode {
+        id: event.id.clone(),
+        chain_id: event.chain_id.0.clone(),
+        block_number: event.block_number,
+        tx_hash: event.tx_hash.clone(),
+        event_type: format!("{:?}", event.event_type),
+        timestamp: event.timestamp.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+        distance,
+    }
+}
+
+impl CausalitySubgraph {
+    /// Render the subgraph in the given format
+    pub fn render(&self, format: ExportFormat) -> Result<String> {
+        match format {
+            ExportFormat::Dot => Ok(self.to_dot()),
+            ExportFormat::GraphMl => Ok(self.to_graphml()),
+            ExportFormat::Json => self.to_json(),
+        }
+    }
+
+    /// Render as Graphviz DOT, clustering events by chain
+    pub fn to_dot(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "digraph causality {{");
+        let _ = writeln!(out, "  rankdir=LR;");
+        let _ = writeln!(out, "  node [shape=box, fontname=\"monospace\"];");
+
+        let mut chains: Vec<&str> = self.nodes.iter().map(|node| node.chain_id.as_str()).collect();
+        chains.dedup();
+
+        for (index, chain) in chains.iter().enumerate() {
+            let _ = writeln!(out, "  subgraph cluster_{} {{", index);
+            let _ = writeln!(out, "    label=\"{}\";", dot_escape(chain));
+            for node in self.nodes.iter().filter(|node| node.chain_id == *chain) {
+                let label = format!("{}\n{} @ {}", node.id, node.event_type, node.block_number);
+                let style = if node.id == self.root { ", style=bold" } else { "" };
+                let _ = writeln!(out, "    \"{}\" [label=\"{}\"{}];", dot_escape(&node.id), dot_escape(&label), style);
+            }
+            let _ = writeln!(out, "  }}");
+        }
+
+        for edge in &self.edges {
+            let attributes = match (edge.kind, &edge.label) {
+                (ExportEdgeKind::CrossChain, Some(label)) => format!(" [style=dashed, label=\"{}\"]", dot_escape(label)),
+                (ExportEdgeKind::CrossChain, None) => " [style=dashed]".to_string(),
+                (ExportEdgeKind::Causal, _) => String::new(),
+            };
+            let _ = writeln!(out, "  \"{}\" -> \"{}\"{};", dot_escape(&edge.source), dot_escape(&edge.target), attributes);
+        }
+
+        out.push_str("}\n");
+        out
+    }
+
+    /// Render as GraphML with event and link attributes as data keys
+    pub fn to_graphml(&self) -> String {
+        let mut out = String::new();
+        out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+        for (id, domain, name, kind) in [
+            ("chain", "node", "chain_id", "string"),
+            ("block", "node", "block_number", "long"),
+            ("tx", "node", "tx_hash", "string"),
+            ("type", "node", "event_type", "string"),
+            ("time", "node", "timestamp", "long"),
+            ("distance", "node", "distance", "int"),
+            ("kind", "edge", "kind", "string"),
+            ("label", "edge", "label", "string"),
+        ] {
+            let _ = writeln!(out, "  <key id=\"{}\" for=\"{}\" attr.name=\"{}\" attr.type=\"{}\"/>", id, domain, name, kind);
+        }
+        let _ = writeln!(out, "  <graph id=\"{}\" edgedefault=\"directed\">", xml_escape(&self.root));
+
+        for node in &self.nodes {
+            let _ = writeln!(out, "    <node id=\"{}\">", xml_escape(&node.id));
+            let _ = writeln!(out, "      <data key=\"chain\">{}</data>", xml_escape(&node.chain_id));
+            let _ = writeln!(out, "      <data key=\"block\">{}</data>", node.block_number);
+            let _ = writeln!(out, "      <data key=\"tx\">{}</data>", xml_escape(&node.tx_hash));
+            let _ = writeln!(out, "      <data key=\"type\">{}</data>", xml_escape(&node.event_type));
+            let _ = writeln!(out, "      <data key=\"time\">{}</data>", node.timestamp);
+            let _ = writeln!(out, "      <data key=\"distance\">{}</data>", node.distance);
+            out.push_str("    </node>\n");
+        }
+
+        for edge in &self.edges {
+            let _ = writeln!(out, "    <edge source=\"{}\" target=\"{}\">", xml_escape(&edge.source), xml_escape(&edge.target));
+            let _ = writeln!(out, "      <data key=\"kind\">{}</data>", edge.kind.as_str());
+            if let Some(label) = &edge.label {
+                let _ = writeln!(out, "      <data key=\"label\">{}</data>", xml_escape(label));
+            }
+            out.push_str("    </edge>\n");
+        }
+
+        out.push_str("  </graph>\n</graphml>\n");
+        out
+    }
+
+    /// Render as pretty-printed JSON
+    pub fn to_json(&self) -> Result<String> {
+        #[cfg(feature = "serde")]
+        {
+            serde_json::to_string_pretty(self).map_err(CausalityError::from)
+        }
+        #[cfg(not(feature = "serde"))]
+        {
+            Err(CausalityError::serialization_error("JSON export requires the serde feature"))
+        }
+    }
+}
+
+/// Escape a string for a quoted DOT identifier or label
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// Escape a string for XML text or attribute content
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subgraph() -> CausalitySubgraph {
+        let node = |id: &str, chain: &str, distance| ExportNode {
+            id: id.to_string(),
+            chain_id: chain.to_string(),
+            block_number: 7,
+            tx_hash: "0xabc".to_string(),
+            event_type: "EffectEvent".to_string(),
+            timestamp: 1_700_000_000,
+            distance,
+        };
+
+        CausalitySubgraph {
+            root: "eth\"deposit".to_string(),
+            nodes: vec![node("eth\"deposit", "ethereum", 0), node("ntrn<mint>", "neutron-1", 1)],
+            edges: vec![ExportEdge {
+                source: "eth\"deposit".to_string(),
+                target: "ntrn<mint>".to_string(),
+                kind: ExportEdgeKind::CrossChain,
+                label: Some("bridge & relay".to_string()),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_dot_clusters_chains_and_escapes_ids() {
+        let dot = subgraph().to_dot();
+        assert!(dot.starts_with("digraph causality {"));
+        assert_eq!(dot.matches("subgraph cluster_").count(), 2);
+        assert!(dot.contains("\"eth\\\"deposit\" [label=\"eth\\\"deposit\\nEffectEvent @ 7\", style=bold];"));
+        assert!(dot.contains("\"eth\\\"deposit\" -> \"ntrn<mint>\" [style=dashed, label=\"bridge & relay\"];"));
+    }
+
+    #[test]
+    fn test_graphml_escapes_xml() {
+        let graphml = subgraph().to_graphml();
+        assert!(graphml.contains("<node id=\"eth&quot;deposit\">"));
+        assert!(graphml.contains("<edge source=\"eth&quot;deposit\" target=\"ntrn&lt;mint&gt;\">"));
+        assert!(graphml.contains("<data key=\"label\">bridge &amp; relay</data>"));
+        assert!(!graphml.contains("<mint>"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_json_round_trip() {
+        let subgraph = subgraph();
+        let json = subgraph.render(ExportFormat::Json).unwrap();
+        let parsed: CausalitySubgraph = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, subgraph);
+        assert!(json.contains("\"cross_chain\""));
+    }
+
+    #[test]
+    fn test_parse_format_and_direction() {
+        assert_eq!("GraphML".parse::<ExportFormat>().unwrap(), ExportFormat::GraphMl);
+        assert_eq!("backward".parse::<ExportDirection>().unwrap(), ExportDirection::Backward);
+        assert!("svg".parse::<ExportFormat>().is_err());
+    }
+}
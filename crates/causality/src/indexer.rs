@@ -1,19 +1,31 @@
 /// Main causality indexer that integrates SMT and causality tracking
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::SystemTime;
 
-use tokio::sync::RwLock;
-use tracing::{info, error};
+use async_trait::async_trait;
+use tokio::sync::{broadcast, RwLock};
+use tracing::{info, error, warn};
 
 use indexer_core::event::Event;
+use indexer_core::pipeline::EventHandler;
+use indexer_core::reorg::ReorgEvent;
 use indexer_core::types::ChainId;
+use indexer_storage::Storage;
 
 use crate::error::{CausalityError, Result};
-use crate::types::{CausalityEvent, CausalityIndex, SmtRoot, SmtHasher};
+use crate::types::{
+    CausalityEvent, CausalityIndex, CrossChainReference, DomainId, EntityId, SmtRoot, SmtRootVersion, SmtHasher
+};
 use crate::smt::{SparseMerkleTree, SmtBackend, Blake3SmtHasher};
 use crate::storage::{CausalityStorage, CausalityStorageBackend};
-use crate::causality::{CausalityTracker, CausalityGraph};
+use crate::causality::{CausalityTracker, CausalityGraph, CrossChainCausality, CrossChainPath};
+use crate::export::{CausalitySubgraph, ExportOptions};
+use crate::mapping::{EventMapper, EventMapperRegistry, MapperKey};
+use crate::violations::{SafetyViolation, ViolationDetector, ViolationFilter};
+
+/// Number of violations buffered for slow subscribers before they lag
+const VIOLATION_CHANNEL_CAPACITY: usize = 1024;
 
 /// Configuration for the causality indexer
 #[derive(Debug, Clone)]
@@ -33,6 +45,9 @@ pub struct CausalityIndexerConfig {
     pub indexed_chains: Vec<ChainId>,
     /// SMT hasher type
     pub hasher_type: HasherType,
+    /// Maximum number of root versions kept for historical queries and
+    /// rollback, or `None` to keep all of them
+    pub max_root_versions: Option<usize>,
 }
 
 /// Types of hashers available
@@ -55,6 +70,7 @@ impl Default for CausalityIndexerConfig {
             enable_cross_chain: true,
             indexed_chains: Vec::new(),
             hasher_type: HasherType::Sha256,
+            max_root_versions: None,
         }
     }
 }
@@ -67,14 +83,26 @@ pub struct CausalityIndexer<B: SmtBackend> {
     smt: SparseMerkleTree<B>,
     /// Causality tracker
     causality_tracker: Arc<RwLock<CausalityTracker>>,
+    /// Per-chain causality graphs linked by cross-chain references
+    cross_chain: Arc<RwLock<CrossChainCausality>>,
     /// Storage backend
     storage: CausalityStorage<B>,
     /// Current SMT root
     current_root: Arc<RwLock<SmtRoot>>,
+    /// SMT roots after each inserted event, in processing order
+    root_history: Arc<RwLock<Vec<SmtRootVersion>>>,
+    /// Last root version pruned from the history, which the history builds on
+    root_base: Arc<RwLock<Option<SmtRootVersion>>>,
     /// Causality index
     causality_index: Arc<RwLock<CausalityIndex>>,
     /// Per-chain event counters
     chain_counters: Arc<RwLock<HashMap<ChainId, u64>>>,
+    /// Mappers turning chain events into causality event data
+    mappers: EventMapperRegistry,
+    /// Double spend and conservation checks over all indexed events
+    violations: Arc<RwLock<ViolationDetector>>,
+    /// Newly detected violations, for alerting
+    violation_sender: broadcast::Sender<SafetyViolation>,
 }
 
 impl<B: SmtBackend> CausalityIndexer<B> {
@@ -96,19 +124,37 @@ impl<B: SmtBackend> CausalityIndexer<B> {
                 HasherType::Sha256 => Box::new(crate::smt::Sha256SmtHasher),
             }
         )));
+        let cross_chain = Arc::new(RwLock::new(CrossChainCausality::new(
+            match config.hasher_type {
+                HasherType::Blake3 => Box::new(Blake3SmtHasher),
+                HasherType::Sha256 => Box::new(crate::smt::Sha256SmtHasher),
+            }
+        )));
         let storage = CausalityStorage::new(causality_backend, smt_backend);
 
         Ok(Self {
             config,
             smt,
             causality_tracker,
+            cross_chain,
             storage,
             current_root: Arc::new(RwLock::new(SparseMerkleTree::<B>::empty_root())),
+            root_history: Arc::new(RwLock::new(Vec::new())),
+            root_base: Arc::new(RwLock::new(None)),
             causality_index: Arc::new(RwLock::new(CausalityIndex::new())),
             chain_counters: Arc::new(RwLock::new(HashMap::new())),
+            mappers: EventMapperRegistry::with_default_mappers(),
+            violations: Arc::new(RwLock::new(ViolationDetector::new())),
+            violation_sender: broadcast::channel(VIOLATION_CHANNEL_CAPACITY).0,
         })
     }
 
+    /// Replace the event mapper registry
+    pub fn with_mappers(mut self, mappers: EventMapperRegistry) -> Self {
+        self.mappers = mappers;
+        self
+    }
+
     /// Initialize the indexer
     pub async fn initialize(&self) -> Result<()> {
         info!("Initializing causality indexer");
@@ -124,11 +170,49 @@ impl<B: SmtBackend> CausalityIndexer<B> {
             info!("Loaded existing causality index with {} events", index.event_count);
         }
 
+        // Load the root history so earlier roots stay queryable after a restart
+        let versions = self.storage.causality_backend().get_root_versions().await?;
+        *self.root_history.write().await = versions;
+        *self.root_base.write().await = self.storage.causality_backend().get_root_base().await?;
+
+        // Rebuild the in-memory graph from the persisted events and relations,
+        // replaying the events in the order they were processed
+        let graph = self.storage.causality_backend().load_graph().await?;
+        let events = self.storage.causality_backend().get_events_in_processing_order().await?;
+
+        if self.config.enable_cross_chain {
+            let references = self.storage.causality_backend().get_cross_chain_references().await?;
+            let mut cross_chain = self.cross_chain.write().await;
+            for event in &events {
+                cross_chain.add_event(event.clone())?;
+            }
+            for reference in references {
+                cross_chain.add_cross_chain_reference(reference);
+            }
+        }
+
+        // Replay the checks so earlier violations stay queryable; they were
+        // already announced when first detected
+        if self.config.enable_causality_tracking {
+            let mut violations = self.violations.write().await;
+            for event in &events {
+                violations.check_event(event);
+            }
+        }
+
         // Initialize chain counters
         let mut counters = self.chain_counters.write().await;
         for chain_id in &self.config.indexed_chains {
             counters.insert(chain_id.clone(), 0);
         }
+        for event in &events {
+            *counters.entry(event.chain_id.clone()).or_insert(0) += 1;
+        }
+
+        if !events.is_empty() {
+            info!("Rebuilt causality graph with {} events", events.len());
+        }
+        *self.causality_tracker.write().await.graph_mut() = graph;
 
         info!("Causality indexer initialized successfully");
         Ok(())
@@ -143,7 +227,7 @@ impl<B: SmtBackend> CausalityIndexer<B> {
             return Ok(());
         }
 
-        let causality_event = CausalityEvent::from_event(event);
+        let causality_event = self.mappers.map_event(event);
         
         // Process with SMT if enabled
         if self.config.enable_smt {
@@ -164,42 +248,161 @@ impl<B: SmtBackend> CausalityIndexer<B> {
         Ok(())
     }
 
+    /// Process several events, inserting each block's events into the SMT at once
+    ///
+    /// Runs of consecutive events from the same chain and block go into the
+    /// tree with a single `insert_batch` and get one root version, taken after
+    /// the last event of the run. Causality tracking and storage are the same
+    /// as for `process_event`.
+    pub async fn process_events(&self, events: &[&dyn Event]) -> Result<()> {
+        let causality_events: Vec<CausalityEvent> = events.iter()
+            .filter(|event| {
+                let chain_id = ChainId(event.chain().to_string());
+                self.config.indexed_chains.is_empty() || self.config.indexed_chains.contains(&chain_id)
+            })
+            .map(|event| self.mappers.map_event(*event))
+            .collect();
+
+        if causality_events.is_empty() {
+            return Ok(());
+        }
+
+        if self.config.enable_smt {
+            let mut root_guard = self.current_root.write().await;
+            let mut history = self.root_history.write().await;
+
+            for run in block_runs(&causality_events) {
+                *root_guard = self.apply_events_smt(*root_guard, run, &mut history).await?;
+            }
+            self.prune_root_history(&mut history).await?;
+        }
+
+        for event in &causality_events {
+            if self.config.enable_causality_tracking {
+                self.process_event_causality(event.clone()).await?;
+            }
+            self.storage.causality_backend().store_event(event).await?;
+        }
+
+        self.update_index_batch(&causality_events).await
+    }
+
     /// Process event with SMT
     async fn process_event_smt(&self, event: &CausalityEvent) -> Result<()> {
-        let key = event.smt_key(self.smt.hasher());
-        let data = event.to_bytes()?;
-        
-        let current_root = *self.current_root.read().await;
-        let new_root = self.smt.insert(current_root, &key, &data).await?;
-        
+        // Hold the root for the whole insertion so the history stays in order
         let mut root_guard = self.current_root.write().await;
-        *root_guard = new_root;
-        
+        let mut history = self.root_history.write().await;
+
+        *root_guard = self.apply_event_smt(*root_guard, event, &mut history).await?;
+        self.prune_root_history(&mut history).await?;
+
         Ok(())
     }
 
+    /// Drop the oldest root versions beyond `max_root_versions`
+    ///
+    /// The last dropped version becomes the base that rollbacks rewind to
+    /// when they reach the start of the remaining history.
+    async fn prune_root_history(&self, history: &mut Vec<SmtRootVersion>) -> Result<()> {
+        let excess = match self.config.max_root_versions {
+            Some(max) if history.len() > max => history.len() - max,
+            _ => return Ok(()),
+        };
+
+        self.storage.causality_backend().prune_root_versions(excess).await?;
+        let base = history.drain(..excess).last();
+        *self.root_base.write().await = base;
+
+        Ok(())
+    }
+
+    /// Insert an event into the tree at `root` and record the resulting root version
+    async fn apply_event_smt(
+        &self,
+        root: SmtRoot,
+        event: &CausalityEvent,
+        history: &mut Vec<SmtRootVersion>,
+    ) -> Result<SmtRoot> {
+        self.apply_events_smt(root, std::slice::from_ref(event), history).await
+    }
+
+    /// Insert events into the tree at `root` in one batch and record the resulting
+    /// root version under the last of them
+    async fn apply_events_smt(
+        &self,
+        root: SmtRoot,
+        events: &[CausalityEvent],
+        history: &mut Vec<SmtRootVersion>,
+    ) -> Result<SmtRoot> {
+        let last = match events.last() {
+            Some(last) => last,
+            None => return Ok(root),
+        };
+
+        let mut entries = Vec::with_capacity(events.len());
+        for event in events {
+            entries.push((event.smt_key(self.smt.hasher()), event.to_bytes()?));
+        }
+
+        let new_root = self.smt.insert_batch(root, entries).await?;
+
+        let version = SmtRootVersion {
+            chain_id: last.chain_id.clone(),
+            block_number: last.block_number,
+            event_id: last.id.clone(),
+            root: new_root,
+        };
+        self.storage.causality_backend().store_root_version(&version).await?;
+        history.push(version);
+
+        Ok(new_root)
+    }
+
     /// Process event with causality tracking
     async fn process_event_causality(&self, event: CausalityEvent) -> Result<()> {
-        let mut tracker = self.causality_tracker.write().await;
-        tracker.add_event(event)?;
+        if self.config.enable_cross_chain {
+            self.cross_chain.write().await.add_event(event.clone())?;
+        }
+
+        let found = self.violations.write().await.check_event(&event);
+        for violation in found {
+            warn!(
+                "Causality violation by event {} on chain {} at block {}: {:?}",
+                violation.event_id, violation.chain_id.0, violation.block_number, violation.kind
+            );
+            // Nobody may be subscribed, in which case the violation is only recorded
+            let _ = self.violation_sender.send(violation);
+        }
+
+        let relations = self.causality_tracker.write().await.add_event(event)?;
+        for relation in &relations {
+            self.storage.causality_backend().store_relation(relation).await?;
+        }
         Ok(())
     }
 
     /// Update the causality index
     async fn update_index(&self, event: &CausalityEvent) -> Result<()> {
+        self.update_index_batch(std::slice::from_ref(event)).await
+    }
+
+    /// Update the causality index for several events, storing it once
+    async fn update_index_batch(&self, events: &[CausalityEvent]) -> Result<()> {
         let mut index = self.causality_index.write().await;
         let mut counters = self.chain_counters.write().await;
         
-        // Update event count
-        index.event_count += 1;
-        
-        // Update chain counter
-        let chain_counter = counters.entry(event.chain_id.clone()).or_insert(0);
-        *chain_counter += 1;
-        
-        // Update chain list if new
-        if !index.chains.contains(&event.chain_id) {
-            index.chains.push(event.chain_id.clone());
+        for event in events {
+            // Update event count
+            index.event_count += 1;
+
+            // Update chain counter
+            let chain_counter = counters.entry(event.chain_id.clone()).or_insert(0);
+            *chain_counter += 1;
+
+            // Update chain list if new
+            if !index.chains.contains(&event.chain_id) {
+                index.chains.push(event.chain_id.clone());
+            }
         }
         
         // Update root and timestamp
@@ -217,6 +420,25 @@ impl<B: SmtBackend> CausalityIndexer<B> {
         *self.current_root.read().await
     }
 
+    /// Get the SMT root as of a block on a chain
+    ///
+    /// This is the root after the last event of the chain at or before
+    /// `block_number`, or `None` if the chain had no events by then.
+    pub async fn get_root_at(&self, chain_id: &ChainId, block_number: u64) -> Option<SmtRoot> {
+        let history = self.root_history.read().await;
+        let base = self.root_base.read().await;
+        history.iter()
+            .rev()
+            .chain(base.iter())
+            .find(|version| version.chain_id == *chain_id && version.block_number <= block_number)
+            .map(|version| version.root)
+    }
+
+    /// Get the recorded SMT root history in processing order
+    pub async fn get_root_history(&self) -> Vec<SmtRootVersion> {
+        self.root_history.read().await.clone()
+    }
+
     /// Get causality index
     pub async fn get_causality_index(&self) -> CausalityIndex {
         self.causality_index.read().await.clone()
@@ -227,6 +449,54 @@ impl<B: SmtBackend> CausalityIndexer<B> {
         self.causality_tracker.read().await.graph().clone()
     }
 
+    /// Get the cross-chain causality tracker, e.g. to serve path queries from the API
+    pub fn cross_chain(&self) -> Arc<RwLock<CrossChainCausality>> {
+        self.cross_chain.clone()
+    }
+
+    /// Record that an event on one chain caused an event on another
+    ///
+    /// The reference is persisted so `initialize` restores it after a restart.
+    pub async fn add_cross_chain_reference(&self, reference: CrossChainReference) -> Result<()> {
+        self.storage.causality_backend().store_cross_chain_reference(&reference).await?;
+        self.cross_chain.write().await.add_cross_chain_reference(reference);
+        Ok(())
+    }
+
+    /// Find the shortest causal path between two events, possibly on different chains
+    pub async fn find_cross_chain_path(&self, from_event: &str, to_event: &str, max_depth: usize) -> Option<CrossChainPath> {
+        self.cross_chain.read().await.find_shortest_path(from_event, to_event, max_depth)
+    }
+
+    /// Get the violation detector, e.g. to serve violation queries from the API
+    pub fn violation_detector(&self) -> Arc<RwLock<ViolationDetector>> {
+        self.violations.clone()
+    }
+
+    /// Get the recorded double spends and conservation violations matching a filter
+    pub async fn get_violations(&self, filter: &ViolationFilter) -> Vec<SafetyViolation> {
+        self.violations.read().await.violations(filter)
+    }
+
+    /// Subscribe to violations as they are detected
+    ///
+    /// Only violations detected after subscribing are received; use
+    /// `get_violations` for earlier ones.
+    pub fn subscribe_violations(&self) -> broadcast::Receiver<SafetyViolation> {
+        self.violation_sender.subscribe()
+    }
+
+    /// Export the events around `root` for visualization
+    ///
+    /// Follows cross-chain references when cross-chain tracking is enabled.
+    pub async fn export_subgraph(&self, root: &str, options: &ExportOptions) -> Option<CausalitySubgraph> {
+        if self.config.enable_cross_chain {
+            self.cross_chain.read().await.export_subgraph(root, options)
+        } else {
+            self.causality_tracker.read().await.graph().export_subgraph(root, options)
+        }
+    }
+
     /// Generate SMT proof for an event
     pub async fn generate_event_proof(&self, event_id: &str) -> Result<Option<crate::types::SmtProof>> {
         // Get the event to find its SMT key
@@ -239,6 +509,139 @@ impl<B: SmtBackend> CausalityIndexer<B> {
         }
     }
 
+    /// Generate an SMT proof for an event against the root as of a block
+    ///
+    /// Returns `None` if the event is unknown or was included after
+    /// `block_number` on its chain.
+    pub async fn generate_event_proof_at(
+        &self,
+        event_id: &str,
+        block_number: u64,
+    ) -> Result<Option<crate::types::SmtProof>> {
+        let event = match self.storage.causality_backend().get_event(event_id).await? {
+            Some(event) if event.block_number <= block_number => event,
+            _ => return Ok(None),
+        };
+
+        match self.get_root_at(&event.chain_id, block_number).await {
+            Some(root) => {
+                let key = event.smt_key(self.smt.hasher());
+                self.smt.get_proof(root, &key).await
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Roll back the events of a chain at or above `from_block`
+    ///
+    /// The SMT root is rewound to the last version before the first orphaned
+    /// event, and events from other chains indexed after it are re-applied on
+    /// top. Orphaned events are removed from storage, the causality graph and
+    /// the index. Returns the new current root.
+    ///
+    /// Fails if the rollback reaches into root versions pruned by
+    /// `max_root_versions`, since their events can no longer be undone.
+    pub async fn rollback_chain(&self, chain_id: &ChainId, from_block: u64) -> Result<SmtRoot> {
+        let mut root_guard = self.current_root.write().await;
+        let mut history = self.root_history.write().await;
+        let base = self.root_base.read().await.clone();
+        let backend = self.storage.causality_backend();
+
+        // Every orphaned block must still have a root version to rewind past
+        if base.is_some() {
+            let retained: HashSet<u64> = history.iter()
+                .filter(|version| version.chain_id == *chain_id)
+                .map(|version| version.block_number)
+                .collect();
+            let orphaned = backend.get_events_in_range(chain_id, from_block, u64::MAX).await?;
+            if let Some(event) = orphaned.iter().find(|event| !retained.contains(&event.block_number)) {
+                return Err(CausalityError::storage_error(format!(
+                    "Cannot roll back chain {} from block {}: root history of block {} was pruned",
+                    chain_id.0, from_block, event.block_number
+                )));
+            }
+        }
+
+        if let Some(position) = history.iter()
+            .position(|version| version.chain_id == *chain_id && version.block_number >= from_block)
+        {
+            let rewound = history.split_off(position);
+            backend.truncate_root_versions(position).await?;
+
+            let mut root = history.last()
+                .or(base.as_ref())
+                .map(|version| version.root)
+                .unwrap_or_else(SparseMerkleTree::<B>::empty_root);
+
+            // Versions of batched blocks cover all events of the block, so
+            // surviving blocks are re-applied whole; re-inserting an event
+            // that was already in the tree leaves the root unchanged
+            let mut reapplied = HashSet::new();
+            for version in rewound {
+                if version.chain_id == *chain_id && version.block_number >= from_block {
+                    continue;
+                }
+                if !reapplied.insert((version.chain_id.clone(), version.block_number)) {
+                    continue;
+                }
+                let events = backend
+                    .get_events_in_range(&version.chain_id, version.block_number, version.block_number)
+                    .await?;
+                if events.is_empty() {
+                    return Err(CausalityError::storage_error(format!(
+                        "Events of block {} on chain {} in root history not found",
+                        version.block_number, version.chain_id.0
+                    )));
+                }
+                root = self.apply_events_smt(root, &events, &mut history).await?;
+            }
+
+            *root_guard = root;
+        }
+
+        // Release the root before taking the index lock, matching `update_index`
+        let new_root = *root_guard;
+        drop(history);
+        drop(root_guard);
+
+        let removed = backend.remove_events_from_block(chain_id, from_block).await?;
+        {
+            let mut tracker = self.causality_tracker.write().await;
+            let mut cross_chain = self.cross_chain.write().await;
+            let mut violations = self.violations.write().await;
+            for event in &removed {
+                tracker.graph_mut().remove_event(&event.id);
+                cross_chain.remove_event(&event.id);
+                violations.remove_event(&event.id);
+            }
+        }
+
+        let mut index = self.causality_index.write().await;
+        let mut counters = self.chain_counters.write().await;
+        index.event_count = index.event_count.saturating_sub(removed.len() as u64);
+        if let Some(counter) = counters.get_mut(chain_id) {
+            *counter = counter.saturating_sub(removed.len() as u64);
+        }
+        index.root = new_root;
+        index.last_updated = SystemTime::now();
+        backend.store_index(&index).await?;
+
+        info!(
+            "Rolled back {} causality events on chain {} from block {}",
+            removed.len(), chain_id.0, from_block
+        );
+
+        Ok(new_root)
+    }
+
+    /// Roll back the blocks reorganized out of the canonical chain
+    pub async fn handle_reorg(&self, reorg: &ReorgEvent) -> Result<SmtRoot> {
+        match reorg.reorganized_blocks.iter().map(|block| block.old_block.number).min() {
+            Some(fork_block) => self.rollback_chain(&reorg.chain_id, fork_block).await,
+            None => Ok(self.get_current_root().await),
+        }
+    }
+
     /// Verify an SMT proof for an event
     pub async fn verify_event_proof(
         &self,
@@ -272,6 +675,16 @@ impl<B: SmtBackend> CausalityIndexer<B> {
             .await
     }
 
+    /// Get the events carrying an entity
+    pub async fn get_entity_events(&self, entity_id: &EntityId) -> Result<Vec<CausalityEvent>> {
+        self.storage.causality_backend().get_entity_events(entity_id).await
+    }
+
+    /// Get the entities indexed for a domain
+    pub async fn get_domain_entities(&self, domain_id: &DomainId) -> Result<Vec<EntityId>> {
+        self.storage.causality_backend().get_domain_entities(domain_id).await
+    }
+
     /// Get statistics about the indexer
     pub async fn get_statistics(&self) -> CausalityIndexerStatistics {
         let index = self.causality_index.read().await;
@@ -288,6 +701,22 @@ impl<B: SmtBackend> CausalityIndexer<B> {
     }
 }
 
+/// Split events into runs of consecutive events from the same chain and block
+fn block_runs(events: &[CausalityEvent]) -> Vec<&[CausalityEvent]> {
+    let mut runs = Vec::new();
+    let mut start = 0;
+    for end in 1..=events.len() {
+        if end == events.len()
+            || events[end].chain_id != events[start].chain_id
+            || events[end].block_number != events[start].block_number
+        {
+            runs.push(&events[start..end]);
+            start = end;
+        }
+    }
+    runs
+}
+
 /// Statistics about the causality indexer
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -328,11 +757,32 @@ impl<B: SmtBackend> CausalityEventProcessor<B> {
         }
     }
 
-    /// Process multiple events
+    /// Process multiple events, batching SMT insertion per block
     pub async fn process_events(&self, events: &[&dyn Event]) -> indexer_core::Result<()> {
-        for event in events {
-            self.process_event(*event).await?;
+        match self.indexer.process_events(events).await {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                error!("Failed to process events in causality indexer: {}", e);
+                Err(indexer_core::Error::generic(format!("Causality indexer error: {}", e)))
+            }
         }
+    }
+
+    /// Reorganize a chain in storage and roll back its causality state
+    pub async fn reorg_chain(
+        &self,
+        storage: &dyn Storage,
+        chain: &str,
+        from_block: u64,
+    ) -> indexer_core::Result<()> {
+        storage.reorg_chain(chain, from_block).await?;
+
+        self.indexer.rollback_chain(&ChainId(chain.to_string()), from_block).await
+            .map_err(|e| {
+                error!("Failed to roll back causality indexer: {}", e);
+                indexer_core::Error::generic(format!("Causality indexer error: {}", e))
+            })?;
+
         Ok(())
     }
 
@@ -342,11 +792,35 @@ impl<B: SmtBackend> CausalityEventProcessor<B> {
     }
 }
 
+/// Indexes every event the ingestor stores and rolls the indexer back when
+/// the ingestor rolls a chain back after a reorg
+#[async_trait]
+impl<B: SmtBackend + 'static> EventHandler for CausalityEventProcessor<B> {
+    fn name(&self) -> &str {
+        "causality-indexer"
+    }
+
+    async fn handle_event(&self, event: &dyn Event) -> indexer_core::Result<bool> {
+        self.process_event(event).await?;
+        Ok(true)
+    }
+
+    async fn rollback(&self, chain: &str, from_block: u64) -> indexer_core::Result<()> {
+        self.indexer.rollback_chain(&ChainId(chain.to_string()), from_block).await
+            .map(|_| ())
+            .map_err(|e| {
+                error!("Failed to roll back causality indexer: {}", e);
+                indexer_core::Error::generic(format!("Causality indexer error: {}", e))
+            })
+    }
+}
+
 /// Builder for creating causality indexers
 pub struct CausalityIndexerBuilder<B: SmtBackend> {
     config: CausalityIndexerConfig,
     smt_backend: Option<B>,
     causality_backend: Option<Box<dyn CausalityStorageBackend>>,
+    mappers: EventMapperRegistry,
 }
 
 impl<B: SmtBackend> CausalityIndexerBuilder<B> {
@@ -356,6 +830,7 @@ impl<B: SmtBackend> CausalityIndexerBuilder<B> {
             config: CausalityIndexerConfig::default(),
             smt_backend: None,
             causality_backend: None,
+            mappers: EventMapperRegistry::with_default_mappers(),
         }
     }
 
@@ -377,6 +852,18 @@ impl<B: SmtBackend> CausalityIndexerBuilder<B> {
         self
     }
 
+    /// Register an event mapper, taking precedence over less specific ones
+    pub fn with_event_mapper(mut self, key: MapperKey, mapper: Arc<dyn EventMapper>) -> Self {
+        self.mappers.register(key, mapper);
+        self
+    }
+
+    /// Replace the event mapper registry, dropping the built-in mappers
+    pub fn with_mapper_registry(mut self, mappers: EventMapperRegistry) -> Self {
+        self.mappers = mappers;
+        self
+    }
+
     /// Add a chain to index
     pub fn add_chain(mut self, chain_id: ChainId) -> Self {
         self.config.indexed_chains.push(chain_id);
@@ -401,6 +888,12 @@ impl<B: SmtBackend> CausalityIndexerBuilder<B> {
         self
     }
 
+    /// Limit the number of root versions kept for historical queries and rollback
+    pub fn with_max_root_versions(mut self, max: usize) -> Self {
+        self.config.max_root_versions = Some(max);
+        self
+    }
+
     /// Build the causality indexer
     pub fn build(self) -> Result<CausalityIndexer<B>> {
         let smt_backend = self.smt_backend
@@ -409,7 +902,8 @@ impl<B: SmtBackend> CausalityIndexerBuilder<B> {
         let causality_backend = self.causality_backend
             .ok_or_else(|| CausalityError::ConfigError("Causality backend not provided".to_string()))?;
 
-        CausalityIndexer::new(self.config, smt_backend, causality_backend)
+        Ok(CausalityIndexer::new(self.config, smt_backend, causality_backend)?
+            .with_mappers(self.mappers))
     }
 }
 
@@ -486,4 +980,391 @@ mod tests {
         let root = indexer.get_current_root().await;
         assert_ne!(root, SparseMerkleTree::<MemorySmtBackend>::empty_root());
     }
-} 
\ No newline at end of file
+
+    fn test_event(id: &str, chain: &str, block_number: u64) -> CausalityEvent {
+        CausalityEvent {
+            id: id.to_string(),
+            chain_id: ChainId(chain.to_string()),
+            block_number,
+            tx_hash: format!("0x{}", id),
+            event_type: crate::types::CausalityEventType::CrossDomainMessage,
+            timestamp: SystemTime::UNIX_EPOCH,
+            data: crate::types::CausalityEventData::CrossDomainMessage {
+                source_domain: crate::types::empty_hash(),
+                target_domain: crate::types::empty_hash(),
+                message_type: "test".to_string(),
+                payload: id.as_bytes().to_vec(),
+            },
+        }
+    }
+
+    fn test_indexer() -> CausalityIndexer<MemorySmtBackend> {
+        CausalityIndexerBuilder::new()
+            .with_smt_backend(MemorySmtBackend::new())
+            .with_causality_backend(Box::new(MemoryCausalityStorage::new()))
+            .build()
+            .unwrap()
+    }
+
+    async fn index_event(indexer: &CausalityIndexer<MemorySmtBackend>, event: &CausalityEvent) {
+        indexer.process_event_smt(event).await.unwrap();
+        indexer.process_event_causality(event.clone()).await.unwrap();
+        indexer.storage.causality_backend().store_event(event).await.unwrap();
+        indexer.update_index(event).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_root_history_and_proofs_at_block() {
+        let indexer = test_indexer();
+        let chain = ChainId("chain-a".to_string());
+
+        index_event(&indexer, &test_event("a-100", "chain-a", 100)).await;
+        let root_100 = indexer.get_current_root().await;
+        index_event(&indexer, &test_event("b-50", "chain-b", 50)).await;
+        index_event(&indexer, &test_event("a-101", "chain-a", 101)).await;
+        let root_101 = indexer.get_current_root().await;
+
+        assert_eq!(indexer.get_root_at(&chain, 99).await, None);
+        assert_eq!(indexer.get_root_at(&chain, 100).await, Some(root_100));
+        assert_eq!(indexer.get_root_at(&chain, 500).await, Some(root_101));
+
+        let proof = indexer.generate_event_proof_at("a-100", 100).await.unwrap().unwrap();
+        assert!(indexer.verify_event_proof("a-100", &proof, &root_100).await.unwrap());
+        assert!(!indexer.verify_event_proof("a-100", &proof, &root_101).await.unwrap());
+
+        // Not yet included at block 100
+        assert!(indexer.generate_event_proof_at("a-101", 100).await.unwrap().is_none());
+    }
+
+    fn nullifier_event(id: &str, chain: &str, block_number: u64) -> CausalityEvent {
+        CausalityEvent {
+            data: crate::types::CausalityEventData::Nullifier(crate::types::CausalityNullifier {
+                resource_id: EntityId::new([1; 32]),
+                nullifier_hash: [9; 32],
+                timestamp: SystemTime::UNIX_EPOCH,
+            }),
+            event_type: crate::types::CausalityEventType::NullifierEvent,
+            ..test_event(id, chain, block_number)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_double_spend_is_recorded_and_announced() {
+        let indexer = test_indexer();
+        let mut alerts = indexer.subscribe_violations();
+
+        index_event(&indexer, &nullifier_event("eth-spend", "ethereum", 10)).await;
+        index_event(&indexer, &nullifier_event("ntrn-spend", "neutron-1", 4)).await;
+
+        let alert = alerts.try_recv().unwrap();
+        assert_eq!(alert.event_id, "ntrn-spend");
+        assert!(alerts.try_recv().is_err());
+
+        let filter = ViolationFilter { nullifier_hash: Some([9; 32]), ..ViolationFilter::default() };
+        assert_eq!(indexer.get_violations(&filter).await, vec![alert]);
+
+        // Rolling back the reusing chain clears the finding
+        indexer.rollback_chain(&ChainId("neutron-1".to_string()), 4).await.unwrap();
+        assert!(indexer.get_violations(&ViolationFilter::default()).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rollback_chain_rewinds_root_and_graph() {
+        let indexer = test_indexer();
+        let chain = ChainId("chain-a".to_string());
+
+        index_event(&indexer, &test_event("a-100", "chain-a", 100)).await;
+        index_event(&indexer, &test_event("a-101", "chain-a", 101)).await;
+        index_event(&indexer, &test_event("b-50", "chain-b", 50)).await;
+        index_event(&indexer, &test_event("a-102", "chain-a", 102)).await;
+
+        let root = indexer.rollback_chain(&chain, 101).await.unwrap();
+
+        // Same root as indexing only the surviving events
+        let expected = test_indexer();
+        index_event(&expected, &test_event("a-100", "chain-a", 100)).await;
+        index_event(&expected, &test_event("b-50", "chain-b", 50)).await;
+        assert_eq!(root, expected.get_current_root().await);
+        assert_eq!(indexer.get_current_root().await, root);
+
+        assert_eq!(indexer.get_root_at(&chain, 200).await, expected.get_root_at(&chain, 200).await);
+        assert!(indexer.generate_event_proof_at("a-101", 200).await.unwrap().is_none());
+        assert!(indexer.generate_event_proof("b-50").await.unwrap().is_some());
+
+        let graph = indexer.get_causality_graph().await;
+        assert_eq!(graph.get_chain_events(&chain).len(), 1);
+
+        let stats = indexer.get_statistics().await;
+        assert_eq!(stats.total_events, 2);
+        assert_eq!(stats.current_root, root);
+    }
+
+    #[tokio::test]
+    async fn test_pruned_root_history_bounds_rollback() {
+        let indexer = CausalityIndexerBuilder::new()
+            .with_smt_backend(MemorySmtBackend::new())
+            .with_causality_backend(Box::new(MemoryCausalityStorage::new()))
+            .with_max_root_versions(2)
+            .build()
+            .unwrap();
+        let chain = ChainId("chain-a".to_string());
+
+        index_event(&indexer, &test_event("a-100", "chain-a", 100)).await;
+        let root_100 = indexer.get_current_root().await;
+        index_event(&indexer, &test_event("a-101", "chain-a", 101)).await;
+        index_event(&indexer, &test_event("a-102", "chain-a", 102)).await;
+
+        // The oldest version is pruned but still answers queries as the base
+        assert_eq!(indexer.get_root_history().await.len(), 2);
+        assert_eq!(indexer.get_root_at(&chain, 100).await, Some(root_100));
+
+        assert!(indexer.rollback_chain(&chain, 100).await.is_err());
+        assert_eq!(indexer.rollback_chain(&chain, 101).await.unwrap(), root_100);
+        assert!(indexer.get_root_history().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_handle_reorg_rolls_back_to_fork_point() {
+        use indexer_core::reorg::{CanonicalBlock, ReorganizedBlock};
+
+        let indexer = test_indexer();
+        index_event(&indexer, &test_event("a-100", "chain-a", 100)).await;
+        let root_100 = indexer.get_current_root().await;
+        index_event(&indexer, &test_event("a-101", "chain-a", 101)).await;
+        index_event(&indexer, &test_event("a-102", "chain-a", 102)).await;
+
+        let block = |number: u64, hash: &str| CanonicalBlock {
+            number,
+            hash: hash.to_string(),
+            parent_hash: String::new(),
+            timestamp: 0,
+        };
+        let reorg = ReorgEvent {
+            chain_id: ChainId("chain-a".to_string()),
+            reorganized_blocks: vec![
+                ReorganizedBlock { old_block: block(102, "0xold102"), new_block: block(102, "0xnew102"), depth: 1, detected_at: 0 },
+                ReorganizedBlock { old_block: block(101, "0xold101"), new_block: block(101, "0xnew101"), depth: 2, detected_at: 0 },
+            ],
+            canonical_tip: block(102, "0xnew102"),
+        };
+
+        assert_eq!(indexer.handle_reorg(&reorg).await.unwrap(), root_100);
+        assert_eq!(indexer.get_root_history().await.len(), 1);
+    }
+
+    /// Effect event spending a resource produced by `from_event`
+    fn flow_event(id: &str, chain: &str, block_number: u64, from_event: &str) -> CausalityEvent {
+        let effect = crate::types::CausalityEffect {
+            id: crate::types::EntityId::new([block_number as u8; 32]),
+            name: "transfer".to_string(),
+            domain_id: crate::types::DomainId::new([7u8; 32]),
+            effect_type: "transfer".to_string(),
+            inputs: vec![crate::types::ResourceFlow {
+                from_event: from_event.to_string(),
+                to_event: id.to_string(),
+                resource_type: "token".to_string(),
+                resource_data: Vec::new(),
+                timestamp: SystemTime::UNIX_EPOCH,
+            }],
+            outputs: Vec::new(),
+            expression: None,
+            timestamp: SystemTime::UNIX_EPOCH,
+            scoped_by: crate::types::HandlerId::null(),
+            intent_id: None,
+            source_typed_domain: Default::default(),
+            target_typed_domain: Default::default(),
+            originating_dataflow_instance: None,
+        };
+
+        CausalityEvent {
+            event_type: crate::types::CausalityEventType::EffectEvent,
+            data: crate::types::CausalityEventData::Effect(effect),
+            ..test_event(id, chain, block_number)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_initialize_rebuilds_graph_from_storage() {
+        let storage = MemoryCausalityStorage::new();
+        let build = || {
+            CausalityIndexerBuilder::new()
+                .with_smt_backend(MemorySmtBackend::new())
+                .with_causality_backend(Box::new(storage.clone()))
+                .build()
+                .unwrap()
+        };
+        let chain = ChainId("chain-a".to_string());
+
+        let indexer = build();
+        index_event(&indexer, &test_event("a-1", "chain-a", 1)).await;
+        index_event(&indexer, &flow_event("a-2", "chain-a", 2, "a-1")).await;
+        assert_eq!(indexer.get_causality_graph().await.get_dependencies("a-2"), ["a-1"]);
+
+        let restarted = build();
+        restarted.initialize().await.unwrap();
+
+        let graph = restarted.get_causality_graph().await;
+        assert_eq!(graph.events().count(), 2);
+        assert_eq!(graph.get_dependencies("a-2"), ["a-1"]);
+        assert_eq!(restarted.get_statistics().await.chain_event_counts[&chain], 2);
+        assert!(restarted.find_cross_chain_path("a-1", "a-2", 4).await.is_some());
+        assert_eq!(
+            restarted.get_domain_entities(&crate::types::DomainId::new([7u8; 32])).await.unwrap(),
+            vec![crate::types::EntityId::new([2u8; 32])]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_process_event_uses_semantic_mapping() {
+        use indexer_core::event::{EventData, UnifiedEvent};
+
+        let indexer = test_indexer();
+        let event = UnifiedEvent {
+            id: "transfer-1".to_string(),
+            chain: "neutron-1".to_string(),
+            block_number: 7,
+            block_hash: "0xblock".to_string(),
+            tx_hash: "0xtx".to_string(),
+            timestamp: SystemTime::UNIX_EPOCH,
+            event_type: "transfer".to_string(),
+            event_data: EventData::Generic {
+                attributes: [("sender", "alice"), ("recipient", "bob"), ("amount", "5")]
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+            },
+            raw_data: Vec::new(),
+            decoded: None,
+        };
+
+        indexer.process_event(&event).await.unwrap();
+
+        let stored = indexer.get_chain_events(&ChainId("neutron-1".to_string())).await.unwrap();
+        assert_eq!(stored[0].event_type, crate::types::CausalityEventType::EffectEvent);
+    }
+
+    #[tokio::test]
+    async fn test_processor_handles_events_and_rollbacks() {
+        use indexer_core::event::{EventData, UnifiedEvent};
+
+        let indexer = Arc::new(test_indexer());
+        let processor: Arc<dyn EventHandler> = Arc::new(CausalityEventProcessor::new(indexer.clone()));
+        let event = UnifiedEvent {
+            id: "transfer-1".to_string(),
+            chain: "neutron-1".to_string(),
+            block_number: 7,
+            block_hash: "0xblock".to_string(),
+            tx_hash: "0xtx".to_string(),
+            timestamp: SystemTime::UNIX_EPOCH,
+            event_type: "transfer".to_string(),
+            event_data: EventData::Generic {
+                attributes: [("sender", "alice"), ("recipient", "bob"), ("amount", "5")]
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+            },
+            raw_data: Vec::new(),
+            decoded: None,
+        };
+
+        assert!(processor.handle_event(&event).await.unwrap());
+        assert_eq!(indexer.get_statistics().await.total_events, 1);
+        assert!(indexer.cross_chain().read().await.get_event("transfer-1").is_some());
+
+        processor.rollback("neutron-1", 7).await.unwrap();
+        assert_eq!(indexer.get_statistics().await.total_events, 0);
+        assert!(indexer.cross_chain().read().await.get_event("transfer-1").is_none());
+        assert_eq!(indexer.get_current_root().await, SparseMerkleTree::<MemorySmtBackend>::empty_root());
+    }
+
+    #[tokio::test]
+    async fn test_process_events_batches_per_block() {
+        use indexer_core::event::{EventData, UnifiedEvent};
+
+        let unified = |id: &str, chain: &str, block_number: u64| UnifiedEvent {
+            id: id.to_string(),
+            chain: chain.to_string(),
+            block_number,
+            block_hash: format!("0xblock{}", block_number),
+            tx_hash: format!("0x{}", id),
+            timestamp: SystemTime::UNIX_EPOCH,
+            event_type: "transfer".to_string(),
+            event_data: EventData::Generic {
+                attributes: [("sender", "alice"), ("recipient", id), ("amount", "5")]
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+            },
+            raw_data: Vec::new(),
+            decoded: None,
+        };
+        let events = [
+            unified("a-1", "chain-a", 1),
+            unified("a-2", "chain-a", 1),
+            unified("b-1", "chain-b", 7),
+            unified("a-3", "chain-a", 2),
+            unified("a-4", "chain-a", 2),
+        ];
+        let refs: Vec<&dyn Event> = events.iter().map(|event| event as &dyn Event).collect();
+
+        let batched = test_indexer();
+        batched.process_events(&refs).await.unwrap();
+
+        let sequential = test_indexer();
+        for event in &refs {
+            sequential.process_event(*event).await.unwrap();
+        }
+
+        assert_eq!(batched.get_current_root().await, sequential.get_current_root().await);
+        let history = batched.get_root_history().await;
+        let versions: Vec<&str> = history.iter().map(|version| version.event_id.as_str()).collect();
+        assert_eq!(versions, vec!["a-2", "b-1", "a-4"]);
+        assert_eq!(batched.get_statistics().await.total_events, 5);
+
+        // Rolling back re-applies the surviving batched blocks whole
+        let chain_b = ChainId("chain-b".to_string());
+        let root = batched.rollback_chain(&chain_b, 7).await.unwrap();
+        assert!(batched.generate_event_proof_at("a-4", 2).await.unwrap().is_some());
+        assert!(batched.generate_event_proof("a-1").await.unwrap().is_some());
+        assert_ne!(root, sequential.get_current_root().await);
+    }
+
+    #[tokio::test]
+    async fn test_cross_chain_path_through_indexer() {
+        let storage = MemoryCausalityStorage::new();
+        let build = || {
+            CausalityIndexerBuilder::new()
+                .with_smt_backend(MemorySmtBackend::new())
+                .with_causality_backend(Box::new(storage.clone()))
+                .build()
+                .unwrap()
+        };
+
+        let indexer = build();
+        index_event(&indexer, &test_event("eth-deposit", "ethereum", 10)).await;
+        index_event(&indexer, &test_event("ntrn-exec", "neutron-1", 5)).await;
+
+        indexer.add_cross_chain_reference(crate::types::CrossChainReference {
+            source_chain: ChainId("ethereum".to_string()),
+            target_chain: ChainId("neutron-1".to_string()),
+            source_event: "eth-deposit".to_string(),
+            target_event: "ntrn-exec".to_string(),
+            ref_type: "bridge".to_string(),
+            ref_data: Vec::new(),
+            timestamp: SystemTime::UNIX_EPOCH,
+        }).await.unwrap();
+
+        let path = indexer.find_cross_chain_path("eth-deposit", "ntrn-exec", 4).await.unwrap();
+        assert_eq!(path.events, vec!["eth-deposit", "ntrn-exec"]);
+
+        // The reference survives a restart
+        let restarted = build();
+        restarted.initialize().await.unwrap();
+        let path = restarted.find_cross_chain_path("eth-deposit", "ntrn-exec", 4).await.unwrap();
+        assert_eq!(path.references.len(), 1);
+
+        // Orphaning the deposit breaks the path
+        indexer.rollback_chain(&ChainId("ethereum".to_string()), 10).await.unwrap();
+        assert!(indexer.find_cross_chain_path("eth-deposit", "ntrn-exec", 4).await.is_none());
+    }
+}
@@ -26,28 +26,55 @@ pub mod indexer;
 pub mod types;
 /// Error types for causality operations
 pub mod error;
+/// Semantic mapping of chain events into causality event data
+pub mod mapping;
+/// Export of causality subgraphs to DOT, GraphML and JSON
+pub mod export;
+/// Double spend and resource conservation checks
+pub mod violations;
+/// Canonical SSZ encoding and content addressing of causality entities
+pub mod ssz;
 
 // Re-export core types and traits
 pub use error::{CausalityError, Result};
 pub use types::{
     CausalityEvent, CausalityResource, CausalityProof, 
-    SmtRoot, SmtKey, SmtProof, CausalityIndex, SmtHasher,
-    ResourceFlow, CrossChainReference
+    SmtRoot, SmtRootVersion, SmtKey, SmtProof, CausalityIndex, SmtHasher,
+    ResourceFlow, CrossChainReference, ContentAddressable, EntityId
 };
 pub use smt::{
-    SmtBackend, MemorySmtBackend, PostgresSmtBackend,
+    SmtBackend, SmtBatchEntry, MemorySmtBackend,
     SparseMerkleTree, Blake3SmtHasher, Sha256SmtHasher
 };
+#[cfg(feature = "postgres")]
+pub use smt::PostgresSmtBackend;
+#[cfg(feature = "rocks")]
+pub use smt::RocksSmtBackend;
 pub use causality::{
     CausalityTracker, CausalityRelation, CausalityGraph,
-    CrossChainCausality
+    CrossChainCausality, CrossChainPath
 };
 pub use indexer::{
     CausalityIndexer, CausalityIndexerConfig, CausalityEventProcessor
 };
+pub use export::{
+    CausalitySubgraph, ExportDirection, ExportEdge, ExportEdgeKind, ExportFormat,
+    ExportNode, ExportOptions, DEFAULT_EXPORT_DEPTH
+};
+pub use ssz::{Ssz, SszDecoder, SszDecoderBuilder, SszEncoder};
+pub use violations::{
+    SafetyViolation, ViolationDetector, ViolationFilter, ViolationKind, ViolationType
+};
+pub use mapping::{
+    EventMapper, EventMapperRegistry, EventContext, MapperKey, chain_domain_id
+};
 pub use storage::{
-    CausalityStorage, SmtStorage, CausalityStorageBackend
+    CausalityStorage, SmtStorage, CausalityStorageBackend, MemoryCausalityStorage
 };
+#[cfg(feature = "postgres")]
+pub use storage::PostgresCausalityStorage;
+#[cfg(feature = "rocks")]
+pub use storage::RocksCausalityStorage;
 
 /// Version of the causality indexer
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -0,0 +1,800 @@
+/// Semantic mapping of chain events into causality event data
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+use indexer_core::event::{Event, EventData, UnifiedEvent};
+use indexer_core::types::ChainId;
+
+use crate::error::{CausalityError, Result};
+use crate::types::{
+    CausalityEffect, CausalityEvent, CausalityEventData, CausalityHandler, CausalityNullifier,
+    DomainId, EntityId, ExprId, Hash, HandlerId, ResourceFlow, TypedDomain,
+};
+
+/// Event type of the attributes CosmWasm contracts add to their responses
+pub const WASM_EVENT: &str = "wasm";
+/// `action` of authorization events sending messages to a processor
+pub const SEND_MSGS_ACTION: &str = "send_msgs";
+/// `method` of processor events enqueuing a message batch
+pub const ENQUEUE_MESSAGES_METHOD: &str = "enqueue_messages";
+/// `action` of authorization events handling a processor callback
+pub const PROCESSOR_CALLBACK_ACTION: &str = "process_processor_callback";
+/// `method` of library events executing a library function
+pub const PROCESS_FUNCTION_METHOD: &str = "process_function";
+/// `action` of authorization events minting authorization tokens
+pub const MINT_AUTHORIZATIONS_ACTION: &str = "mint_authorizations";
+
+/// Derive the domain ID of a chain
+pub fn chain_domain_id(chain_id: &ChainId) -> DomainId {
+    DomainId::new(content_hash(&[b"domain:", chain_id.0.as_bytes()]))
+}
+
+/// SHA-256 over the concatenation of `parts`
+fn content_hash(parts: &[&[u8]]) -> Hash {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&hasher.finalize());
+    hash
+}
+
+/// Core event plus the attributes extracted from it
+pub struct EventContext<'a> {
+    /// The event being mapped
+    pub event: &'a dyn Event,
+    /// Chain the event occurred on
+    pub chain_id: ChainId,
+    /// Event attributes keyed by name
+    pub attributes: HashMap<String, String>,
+}
+
+impl<'a> EventContext<'a> {
+    /// Build the context for an event, extracting its attributes
+    ///
+    /// Attributes come from the structured data of a [`UnifiedEvent`], or
+    /// from a flat JSON object in the raw event data otherwise. EVM events
+    /// expose `contract_address`, `data` and `topic0`..`topicN`, plus the
+    /// parameters of the log when it was decoded with a contract ABI.
+    pub fn new(event: &'a dyn Event) -> Self {
+        let mut attributes = HashMap::new();
+
+        match event.as_any().downcast_ref::<UnifiedEvent>().map(|e| &e.event_data) {
+            Some(EventData::Evm { topics, data, address, .. }) => {
+                attributes.insert("contract_address".to_string(), address.clone());
+                attributes.insert("data".to_string(), data.clone());
+                for (i, topic) in topics.iter().enumerate() {
+                    attributes.insert(format!("topic{}", i), topic.clone());
+                }
+            }
+            Some(EventData::Cosmos { attributes: cosmos_attributes, .. }) => {
+                for attribute in cosmos_attributes {
+                    attributes.insert(attribute.key.clone(), attribute.value.clone());
+                }
+            }
+            Some(EventData::Generic { attributes: generic }) => {
+                attributes.extend(generic.clone());
+            }
+            None => {
+                #[cfg(feature = "serde")]
+                if let Ok(serde_json::Value::Object(fields)) = serde_json::from_slice(event.raw_data()) {
+                    for (key, value) in fields {
+                        let value = match value {
+                            serde_json::Value::String(s) => s,
+                            other => other.to_string(),
+                        };
+                        attributes.insert(key, value);
+                    }
+                }
+            }
+        }
+
+        if let Some(decoded) = event.as_any().downcast_ref::<UnifiedEvent>().and_then(|e| e.decoded.as_ref()) {
+            for (key, value) in decoded {
+                let value = value.as_str().map(str::to_string).unwrap_or_else(|| value.to_string());
+                attributes.insert(key.clone(), value);
+            }
+        }
+
+        // CosmWasm events carry the emitting contract as `_contract_address`
+        if let Some(address) = attributes.get("_contract_address").cloned() {
+            attributes.entry("contract_address".to_string()).or_insert(address);
+        }
+
+        Self {
+            event,
+            chain_id: ChainId(event.chain().to_string()),
+            attributes,
+        }
+    }
+
+    /// Get an attribute
+    pub fn attribute(&self, key: &str) -> Option<&str> {
+        self.attributes.get(key).map(String::as_str)
+    }
+
+    /// Get an attribute, failing if it is missing
+    pub fn require(&self, key: &str) -> Result<&str> {
+        self.attribute(key).ok_or_else(|| CausalityError::mapping_error(format!(
+            "Event {} of type '{}' has no '{}' attribute",
+            self.event.id(), self.event.event_type(), key
+        )))
+    }
+
+    /// Address of the contract that emitted the event, if known
+    pub fn contract_address(&self) -> Option<&str> {
+        self.attribute("contract_address")
+    }
+
+    /// Domain of the event's chain
+    pub fn domain_id(&self) -> DomainId {
+        chain_domain_id(&self.chain_id)
+    }
+
+    /// Content-addressed entity ID for the event under `kind`
+    pub fn entity_id(&self, kind: &str) -> EntityId {
+        EntityId::new(content_hash(&[
+            kind.as_bytes(), b":", self.chain_id.0.as_bytes(), b":", self.event.id().as_bytes(),
+        ]))
+    }
+
+    /// Reference to an account on the event's chain, used as a resource flow endpoint
+    pub fn account_ref(&self, address: &str) -> String {
+        format!("{}:{}", self.chain_id.0, address)
+    }
+}
+
+/// Maps a chain event to semantic causality data
+pub trait EventMapper: Send + Sync {
+    /// Name of the mapper, for logging
+    fn name(&self) -> &str;
+
+    /// Map an event, returning `None` if the mapper does not apply to it
+    fn map(&self, ctx: &EventContext<'_>) -> Result<Option<CausalityEventData>>;
+}
+
+/// Events a mapper is registered for
+///
+/// Unset fields match any event. When several registrations match, the one
+/// with the most fields set wins.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MapperKey {
+    /// Chain the event must come from
+    pub chain_id: Option<ChainId>,
+    /// Core event type, e.g. `transfer`
+    pub event_type: Option<String>,
+    /// Address of the emitting contract, compared case-insensitively
+    pub contract_address: Option<String>,
+    /// Attribute the event must carry with the given value, e.g. `action`
+    pub attribute: Option<(String, String)>,
+}
+
+impl MapperKey {
+    /// Match events of the given type
+    pub fn event_type(event_type: impl Into<String>) -> Self {
+        Self {
+            event_type: Some(event_type.into()),
+            ..Self::default()
+        }
+    }
+
+    /// Only match events from a chain
+    pub fn on_chain(mut self, chain_id: ChainId) -> Self {
+        self.chain_id = Some(chain_id);
+        self
+    }
+
+    /// Only match events emitted by a contract
+    pub fn from_contract(mut self, address: impl Into<String>) -> Self {
+        self.contract_address = Some(address.into());
+        self
+    }
+
+    /// Only match events carrying an attribute with the given value
+    pub fn with_attribute(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.attribute = Some((key.into(), value.into()));
+        self
+    }
+
+    fn matches(&self, ctx: &EventContext<'_>) -> bool {
+        self.chain_id.as_ref().map_or(true, |chain| *chain == ctx.chain_id)
+            && self.event_type.as_deref().map_or(true, |t| t == ctx.event.event_type())
+            && self.contract_address.as_deref().map_or(true, |address| {
+                ctx.contract_address().is_some_and(|c| c.eq_ignore_ascii_case(address))
+            })
+            && self.attribute.as_ref().map_or(true, |(key, value)| ctx.attribute(key) == Some(value.as_str()))
+    }
+
+    fn specificity(&self) -> usize {
+        usize::from(self.chain_id.is_some())
+            + usize::from(self.event_type.is_some())
+            + usize::from(self.contract_address.is_some())
+            + usize::from(self.attribute.is_some())
+    }
+}
+
+/// Registry of event mappers keyed by chain, event type and contract
+#[derive(Clone, Default)]
+pub struct EventMapperRegistry {
+    mappers: Vec<(MapperKey, Arc<dyn EventMapper>)>,
+}
+
+impl std::fmt::Debug for EventMapperRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.mappers.iter().map(|(key, mapper)| (key, mapper.name())))
+            .finish()
+    }
+}
+
+impl EventMapperRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a registry with the built-in Valence and token transfer mappers
+    ///
+    /// Valence contracts report through `wasm` events, told apart by the
+    /// `action` (authorization) or `method` (processor, libraries) attribute.
+    pub fn with_default_mappers() -> Self {
+        let wasm = |key: &str, value: &str| MapperKey::event_type(WASM_EVENT).with_attribute(key, value);
+
+        let mut registry = Self::new();
+        registry.register(wasm("action", SEND_MSGS_ACTION), Arc::new(ValenceProcessorMessageMapper));
+        registry.register(wasm("method", ENQUEUE_MESSAGES_METHOD), Arc::new(ValenceProcessorMessageMapper));
+        registry.register(wasm("action", PROCESSOR_CALLBACK_ACTION), Arc::new(ValenceProcessorMessageMapper));
+        registry.register(wasm("method", PROCESS_FUNCTION_METHOD), Arc::new(ValenceLibraryExecutionMapper));
+        registry.register(wasm("action", MINT_AUTHORIZATIONS_ACTION), Arc::new(ValenceAuthorizationGrantMapper));
+        registry.register(MapperKey::event_type("transfer"), Arc::new(TokenTransferMapper));
+        registry.register(MapperKey::event_type("Transfer"), Arc::new(TokenTransferMapper));
+        registry
+    }
+
+    /// Register a mapper for the events matching `key`
+    pub fn register(&mut self, key: MapperKey, mapper: Arc<dyn EventMapper>) {
+        self.mappers.push((key, mapper));
+    }
+
+    /// Builder-style variant of [`EventMapperRegistry::register`]
+    pub fn with_mapper(mut self, key: MapperKey, mapper: Arc<dyn EventMapper>) -> Self {
+        self.register(key, mapper);
+        self
+    }
+
+    /// Number of registered mappers
+    pub fn len(&self) -> usize {
+        self.mappers.len()
+    }
+
+    /// Whether no mappers are registered
+    pub fn is_empty(&self) -> bool {
+        self.mappers.is_empty()
+    }
+
+    /// Map an event using the most specific matching mapper
+    ///
+    /// Events no mapper recognises, or that a mapper fails on, fall back to
+    /// [`CausalityEvent::from_event`].
+    pub fn map_event(&self, event: &dyn Event) -> CausalityEvent {
+        let ctx = EventContext::new(event);
+
+        let mut candidates: Vec<&(MapperKey, Arc<dyn EventMapper>)> = self.mappers.iter()
+            .filter(|(key, _)| key.matches(&ctx))
+            .collect();
+        // Stable sort keeps registration order among equally specific mappers
+        candidates.sort_by_key(|(key, _)| std::cmp::Reverse(key.specificity()));
+
+        for (_, mapper) in candidates {
+            match mapper.map(&ctx) {
+                Ok(Some(data)) => return CausalityEvent::with_data(event, data),
+                Ok(None) => continue,
+                Err(e) => {
+                    warn!("Mapper '{}' failed on event {}: {}", mapper.name(), event.id(), e);
+                }
+            }
+        }
+
+        CausalityEvent::from_event(event)
+    }
+}
+
+/// Maps Valence processor messages
+///
+/// Messages sent to or enqueued on a processor become cross-domain messages
+/// between the source and target chain domains, the target defaulting to the
+/// event's own chain. Processor callbacks become nullifiers consuming the
+/// execution, so an execution cannot be completed twice.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ValenceProcessorMessageMapper;
+
+impl EventMapper for ValenceProcessorMessageMapper {
+    fn name(&self) -> &str {
+        "valence-processor-message"
+    }
+
+    fn map(&self, ctx: &EventContext<'_>) -> Result<Option<CausalityEventData>> {
+        if ctx.attribute("action") == Some(PROCESSOR_CALLBACK_ACTION) {
+            let execution_id = ctx.require("execution_id")?;
+            let resource_id = EntityId::new(content_hash(&[b"valence-execution:", execution_id.as_bytes()]));
+            return Ok(Some(CausalityEventData::Nullifier(CausalityNullifier {
+                resource_id,
+                nullifier_hash: content_hash(&[b"nullifier:", &resource_id.inner()]),
+                timestamp: ctx.event.timestamp(),
+            })));
+        }
+
+        let source_chain = ctx.attribute("source_chain_id").unwrap_or(&ctx.chain_id.0);
+        let target_chain = ctx.attribute("target_chain_id").unwrap_or(&ctx.chain_id.0);
+        let message_type = ctx.attribute("action").or_else(|| ctx.attribute("method")).unwrap_or(SEND_MSGS_ACTION);
+
+        Ok(Some(CausalityEventData::CrossDomainMessage {
+            source_domain: chain_domain_id(&ChainId(source_chain.to_string())).inner(),
+            target_domain: chain_domain_id(&ChainId(target_chain.to_string())).inner(),
+            message_type: format!("valence_{}", message_type),
+            payload: ctx.attribute("payload").unwrap_or_default().as_bytes().to_vec(),
+        }))
+    }
+}
+
+/// Maps Valence library executions to effects scoped by the library
+///
+/// The library is the `library_id` attribute or the emitting contract.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ValenceLibraryExecutionMapper;
+
+impl EventMapper for ValenceLibraryExecutionMapper {
+    fn name(&self) -> &str {
+        "valence-library-execution"
+    }
+
+    fn map(&self, ctx: &EventContext<'_>) -> Result<Option<CausalityEventData>> {
+        let library = match ctx.attribute("library_id").or_else(|| ctx.contract_address()) {
+            Some(library) => library,
+            None => ctx.require("library_id")?,
+        };
+        let account = ctx.attribute("account_id").or_else(|| ctx.attribute("user_address"));
+        let function = ctx.attribute("function_name").or_else(|| ctx.attribute("method")).unwrap_or("execute");
+        let timestamp = ctx.event.timestamp();
+
+        let inputs = account
+            .map(|account| vec![ResourceFlow {
+                from_event: ctx.account_ref(account),
+                to_event: ctx.event.id().to_string(),
+                resource_type: "valence_account".to_string(),
+                resource_data: account.as_bytes().to_vec(),
+                timestamp,
+            }])
+            .unwrap_or_default();
+
+        let domain = TypedDomain::VerifiableDomain {
+            domain_id: ctx.domain_id(),
+            capabilities: Vec::new(),
+        };
+
+        Ok(Some(CausalityEventData::Effect(CausalityEffect {
+            id: ctx.entity_id("effect"),
+            name: format!("{}::{}", library, function),
+            domain_id: ctx.domain_id(),
+            effect_type: "valence_library_execution".to_string(),
+            inputs,
+            outputs: Vec::new(),
+            expression: None,
+            timestamp,
+            scoped_by: HandlerId::new(content_hash(&[b"valence-library:", library.as_bytes()])),
+            intent_id: None,
+            source_typed_domain: domain.clone(),
+            target_typed_domain: domain,
+            originating_dataflow_instance: None,
+        })))
+    }
+}
+
+/// Maps Valence authorization grants to handlers for the granted permissions
+///
+/// The authorization is identified by its `label`, falling back to
+/// `auth_id` and then the authorization contract.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ValenceAuthorizationGrantMapper;
+
+impl EventMapper for ValenceAuthorizationGrantMapper {
+    fn name(&self) -> &str {
+        "valence-authorization-grant"
+    }
+
+    fn map(&self, ctx: &EventContext<'_>) -> Result<Option<CausalityEventData>> {
+        let grantee = match ctx.attribute("grantee").or_else(|| ctx.attribute("recipient")) {
+            Some(grantee) => grantee,
+            None => ctx.require("grantee")?,
+        };
+        let permissions: Vec<String> = ctx.attribute("permissions")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .map(str::to_string)
+            .collect();
+        let auth_id = ctx.attribute("label")
+            .or_else(|| ctx.attribute("auth_id"))
+            .or_else(|| ctx.contract_address())
+            .unwrap_or_default();
+
+        Ok(Some(CausalityEventData::Handler(CausalityHandler {
+            id: ctx.entity_id("handler"),
+            name: format!("{}:{}", auth_id, grantee),
+            domain_id: ctx.domain_id(),
+            expression: ExprId::new(content_hash(&[
+                auth_id.as_bytes(), b":", grantee.as_bytes(), b":", permissions.join(",").as_bytes(),
+            ])),
+            effect_types: permissions,
+            timestamp: ctx.event.timestamp(),
+        })))
+    }
+}
+
+/// Maps fungible token transfers to effects moving the token between accounts
+///
+/// Reads `from`, `to` and `amount` attributes (Cosmos `sender`/`recipient`
+/// and decoded ERC-20 `value` also work), or the indexed topics and data of
+/// an undecoded ERC-20 `Transfer` log. Logs whose data is not a single
+/// 32-byte amount, such as ERC-721 transfers, are skipped.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokenTransferMapper;
+
+impl TokenTransferMapper {
+    /// Address from a 32-byte topic, keeping the last 20 bytes
+    fn topic_address(topic: &str) -> String {
+        let hex = topic.trim_start_matches("0x");
+        format!("0x{}", &hex[hex.len().saturating_sub(40)..])
+    }
+
+    /// Decimal amount from the data of an ERC-20 `Transfer` log
+    ///
+    /// Returns `None` when the data is not a single 32-byte word.
+    fn data_amount(data: &str) -> Result<Option<String>> {
+        let hex = data.trim_start_matches("0x");
+        if hex.len() != 64 {
+            return Ok(None);
+        }
+
+        let digits = hex.trim_start_matches('0');
+        if digits.is_empty() {
+            return Ok(Some("0".to_string()));
+        }
+        if digits.len() > 32 {
+            return Err(CausalityError::mapping_error(format!("Transfer amount 0x{} exceeds 128 bits", digits)));
+        }
+
+        u128::from_str_radix(digits, 16)
+            .map(|amount| Some(amount.to_string()))
+            .map_err(|e| CausalityError::mapping_error(format!("Invalid transfer amount 0x{}: {}", digits, e)))
+    }
+}
+
+impl EventMapper for TokenTransferMapper {
+    fn name(&self) -> &str {
+        "token-transfer"
+    }
+
+    fn map(&self, ctx: &EventContext<'_>) -> Result<Option<CausalityEventData>> {
+        let from = ctx.attribute("from").or_else(|| ctx.attribute("sender")).map(str::to_string)
+            .or_else(|| ctx.attribute("topic1").map(Self::topic_address));
+        let to = ctx.attribute("to").or_else(|| ctx.attribute("recipient")).map(str::to_string)
+            .or_else(|| ctx.attribute("topic2").map(Self::topic_address));
+        let amount = match ctx.attribute("amount").or_else(|| ctx.attribute("value")) {
+            Some(amount) => Some(amount.to_string()),
+            None => match ctx.attribute("data") {
+                Some(data) => Self::data_amount(data)?,
+                None => None,
+            },
+        };
+
+        let (from, to, amount) = match (from, to, amount) {
+            (Some(from), Some(to), Some(amount)) => (from, to, amount),
+            // Not a fungible transfer we understand
+            _ => return Ok(None),
+        };
+
+        let token = ctx.attribute("denom")
+            .or_else(|| ctx.attribute("token"))
+            .or_else(|| ctx.contract_address())
+            .unwrap_or("native");
+        let resource_type = format!("token:{}", token);
+        let timestamp = ctx.event.timestamp();
+        let event_id = ctx.event.id().to_string();
+
+        let domain = TypedDomain::VerifiableDomain {
+            domain_id: ctx.domain_id(),
+            capabilities: Vec::new(),
+        };
+
+        Ok(Some(CausalityEventData::Effect(CausalityEffect {
+            id: ctx.entity_id("effect"),
+            name: format!("transfer {} {}", amount, token),
+            domain_id: ctx.domain_id(),
+            effect_type: "token_transfer".to_string(),
+            inputs: vec![ResourceFlow {
+                from_event: ctx.account_ref(&from),
+                to_event: event_id.clone(),
+                resource_type: resource_type.clone(),
+                resource_data: amount.clone().into_bytes(),
+                timestamp,
+            }],
+            outputs: vec![ResourceFlow {
+                from_event: event_id,
+                to_event: ctx.account_ref(&to),
+                resource_type,
+                resource_data: amount.into_bytes(),
+                timestamp,
+            }],
+            expression: None,
+            timestamp,
+            scoped_by: HandlerId::new(content_hash(&[b"token:", token.as_bytes()])),
+            intent_id: None,
+            source_typed_domain: domain.clone(),
+            target_typed_domain: domain,
+            originating_dataflow_instance: None,
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::SystemTime;
+    use crate::types::CausalityEventType;
+
+    fn generic_event(event_type: &str, attributes: &[(&str, &str)]) -> UnifiedEvent {
+        UnifiedEvent {
+            id: "event-1".to_string(),
+            chain: "neutron-1".to_string(),
+            block_number: 42,
+            block_hash: "0xblock".to_string(),
+            tx_hash: "0xtx".to_string(),
+            timestamp: SystemTime::UNIX_EPOCH,
+            event_type: event_type.to_string(),
+            event_data: EventData::Generic {
+                attributes: attributes.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+            },
+            raw_data: Vec::new(),
+            decoded: None,
+        }
+    }
+
+    #[test]
+    fn test_processor_message_maps_to_cross_domain_message() {
+        let registry = EventMapperRegistry::with_default_mappers();
+        let event = generic_event(WASM_EVENT, &[
+            ("_contract_address", "neutron1authorization"),
+            ("action", SEND_MSGS_ACTION),
+            ("target_chain_id", "ethereum"),
+            ("payload", "hello"),
+        ]);
+
+        let mapped = registry.map_event(&event);
+        assert_eq!(mapped.event_type, CausalityEventType::CrossDomainMessage);
+        match mapped.data {
+            CausalityEventData::CrossDomainMessage { source_domain, target_domain, message_type, payload } => {
+                assert_eq!(source_domain, chain_domain_id(&ChainId("neutron-1".to_string())).inner());
+                assert_eq!(target_domain, chain_domain_id(&ChainId("ethereum".to_string())).inner());
+                assert_eq!(message_type, "valence_send_msgs");
+                assert_eq!(payload, b"hello");
+            }
+            other => panic!("unexpected data: {:?}", other),
+        }
+
+        // Messages enqueued on a local processor stay within the chain's domain
+        let enqueued = generic_event(WASM_EVENT, &[("method", ENQUEUE_MESSAGES_METHOD)]);
+        match registry.map_event(&enqueued).data {
+            CausalityEventData::CrossDomainMessage { source_domain, target_domain, .. } => {
+                assert_eq!(source_domain, target_domain);
+            }
+            other => panic!("unexpected data: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_processor_callback_maps_to_nullifier() {
+        let registry = EventMapperRegistry::with_default_mappers();
+        let completed = |id: &str| {
+            let mut event = generic_event(WASM_EVENT, &[("action", PROCESSOR_CALLBACK_ACTION), ("execution_id", "7")]);
+            event.id = id.to_string();
+            registry.map_event(&event)
+        };
+
+        let first = completed("event-1");
+        let second = completed("event-2");
+        assert_eq!(first.event_type, CausalityEventType::NullifierEvent);
+
+        // Both executions of the same message produce the same nullifier
+        match (first.data, second.data) {
+            (CausalityEventData::Nullifier(a), CausalityEventData::Nullifier(b)) => {
+                assert_eq!(a.nullifier_hash, b.nullifier_hash);
+            }
+            other => panic!("unexpected data: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_transfer_maps_to_resource_flows() {
+        let registry = EventMapperRegistry::with_default_mappers();
+        let event = generic_event("transfer", &[
+            ("sender", "neutron1alice"),
+            ("recipient", "neutron1bob"),
+            ("amount", "100"),
+            ("denom", "untrn"),
+        ]);
+
+        let mapped = registry.map_event(&event);
+        match mapped.data {
+            CausalityEventData::Effect(effect) => {
+                assert_eq!(effect.domain_id, chain_domain_id(&ChainId("neutron-1".to_string())));
+                assert_eq!(effect.inputs[0].from_event, "neutron-1:neutron1alice");
+                assert_eq!(effect.outputs[0].to_event, "neutron-1:neutron1bob");
+                assert_eq!(effect.outputs[0].resource_type, "token:untrn");
+                assert_eq!(effect.outputs[0].resource_data, b"100");
+            }
+            other => panic!("unexpected data: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_erc20_transfer_topics() {
+        let registry = EventMapperRegistry::with_default_mappers();
+        let mut event = generic_event("Transfer", &[]);
+        event.chain = "ethereum".to_string();
+        event.event_data = EventData::Evm {
+            topics: vec![
+                "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef".to_string(),
+                format!("0x{}{}", "0".repeat(24), "a".repeat(40)),
+                format!("0x{}{}", "0".repeat(24), "b".repeat(40)),
+            ],
+            data: format!("0x{:064x}", 100),
+            address: "0xToken".to_string(),
+            log_index: 0,
+        };
+
+        match registry.map_event(&event).data {
+            CausalityEventData::Effect(effect) => {
+                assert_eq!(effect.inputs[0].from_event, format!("ethereum:0x{}", "a".repeat(40)));
+                assert_eq!(effect.outputs[0].to_event, format!("ethereum:0x{}", "b".repeat(40)));
+                assert_eq!(effect.outputs[0].resource_type, "token:0xToken");
+                assert_eq!(effect.outputs[0].resource_data, b"100");
+            }
+            other => panic!("unexpected data: {:?}", other),
+        }
+
+        // Decoded parameters take precedence over the raw log data
+        #[cfg(feature = "serde")]
+        {
+            let mut decoded = serde_json::Map::new();
+            decoded.insert("value".to_string(), serde_json::Value::String("250".to_string()));
+            event.decoded = Some(decoded);
+            match registry.map_event(&event).data {
+                CausalityEventData::Effect(effect) => assert_eq!(effect.outputs[0].resource_data, b"250"),
+                other => panic!("unexpected data: {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_undecodable_transfer_data_is_not_an_amount() {
+        let registry = EventMapperRegistry::with_default_mappers();
+        let transfer = |data: String| {
+            let mut event = generic_event("Transfer", &[]);
+            event.event_data = EventData::Evm {
+                topics: vec![
+                    "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef".to_string(),
+                    format!("0x{}{}", "0".repeat(24), "a".repeat(40)),
+                    format!("0x{}{}", "0".repeat(24), "b".repeat(40)),
+                ],
+                data,
+                address: "0xToken".to_string(),
+                log_index: 0,
+            };
+            registry.map_event(&event).event_type
+        };
+
+        // ERC-721 transfers carry no amount, oversized amounts are rejected;
+        // both fall back instead of recording the raw hex as an amount
+        assert_eq!(transfer("0x".to_string()), CausalityEventType::CrossDomainMessage);
+        assert_eq!(transfer(format!("0x{}", "f".repeat(64))), CausalityEventType::CrossDomainMessage);
+        assert_eq!(transfer(format!("0x{:064x}", 1)), CausalityEventType::EffectEvent);
+    }
+
+    #[test]
+    fn test_library_execution_and_authorization_grant() {
+        let registry = EventMapperRegistry::with_default_mappers();
+
+        let execution = generic_event(WASM_EVENT, &[
+            ("_contract_address", "neutron1splitter"),
+            ("method", PROCESS_FUNCTION_METHOD),
+            ("account_id", "acct-1"),
+        ]);
+        match registry.map_event(&execution).data {
+            CausalityEventData::Effect(effect) => {
+                assert_eq!(effect.name, "neutron1splitter::process_function");
+                assert_eq!(effect.scoped_by, HandlerId::new(content_hash(&[b"valence-library:", b"neutron1splitter"])));
+            }
+            other => panic!("unexpected data: {:?}", other),
+        }
+
+        let grant = generic_event(WASM_EVENT, &[
+            ("action", MINT_AUTHORIZATIONS_ACTION),
+            ("label", "swap-auth"),
+            ("grantee", "neutron1bob"),
+            ("permissions", "swap, bridge"),
+        ]);
+        match registry.map_event(&grant).data {
+            CausalityEventData::Handler(handler) => {
+                assert_eq!(handler.name, "swap-auth:neutron1bob");
+                assert_eq!(handler.effect_types, vec!["swap".to_string(), "bridge".to_string()]);
+            }
+            other => panic!("unexpected data: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_unknown_or_malformed_events_fall_back() {
+        let registry = EventMapperRegistry::with_default_mappers();
+
+        let unknown = generic_event("swap", &[]);
+        assert_eq!(registry.map_event(&unknown).event_type, CausalityEventType::CrossDomainMessage);
+
+        // Wasm events of other contract methods are not Valence messages
+        let other_method = generic_event(WASM_EVENT, &[("method", "update_config")]);
+        assert_eq!(registry.map_event(&other_method).event_type, CausalityEventType::CrossDomainMessage);
+
+        // A callback missing the required execution_id
+        let malformed = generic_event(WASM_EVENT, &[("action", PROCESSOR_CALLBACK_ACTION)]);
+        match registry.map_event(&malformed).data {
+            CausalityEventData::CrossDomainMessage { source_domain, .. } => {
+                assert_eq!(source_domain, crate::types::empty_hash());
+            }
+            other => panic!("unexpected data: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_most_specific_mapper_wins() {
+        struct Fixed(&'static str);
+
+        impl EventMapper for Fixed {
+            fn name(&self) -> &str {
+                self.0
+            }
+
+            fn map(&self, _ctx: &EventContext<'_>) -> Result<Option<CausalityEventData>> {
+                Ok(Some(CausalityEventData::CrossDomainMessage {
+                    source_domain: crate::types::empty_hash(),
+                    target_domain: crate::types::empty_hash(),
+                    message_type: self.0.to_string(),
+                    payload: Vec::new(),
+                }))
+            }
+        }
+
+        let registry = EventMapperRegistry::new()
+            .with_mapper(MapperKey::event_type("custom"), Arc::new(Fixed("generic")))
+            .with_mapper(
+                MapperKey::event_type("custom").on_chain(ChainId("neutron-1".to_string())).from_contract("NEUTRON1CONTRACT"),
+                Arc::new(Fixed("contract")),
+            );
+
+        let mut event = generic_event("custom", &[("_contract_address", "neutron1contract")]);
+        match registry.map_event(&event).data {
+            CausalityEventData::CrossDomainMessage { message_type, .. } => assert_eq!(message_type, "contract"),
+            other => panic!("unexpected data: {:?}", other),
+        }
+
+        event.event_data = EventData::Generic { attributes: HashMap::new() };
+        match registry.map_event(&event).data {
+            CausalityEventData::CrossDomainMessage { message_type, .. } => assert_eq!(message_type, "generic"),
+            other => panic!("unexpected data: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,875 @@
+/// Sparse Merkle Tree implementation for causality indexing
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use blake3;
+use sha2::{Digest, Sha256};
+
+use crate::error::{CausalityError, Result};
+use crate::types::{Hash, SmtChildren, SmtProof, SmtProofLeaf, empty_hash, key_bit};
+pub use crate::types::SmtHasher;
+
+#[cfg(feature = "postgres")]
+mod postgres;
+#[cfg(feature = "rocks")]
+mod rocks;
+
+#[cfg(feature = "postgres")]
+pub use postgres::PostgresSmtBackend;
+#[cfg(feature = "rocks")]
+pub use rocks::RocksSmtBackend;
+
+/// Hash length in bytes
+pub const HASH_LEN: usize = 32;
+
+/// Entry written to an SMT backend as part of a batch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SmtBatchEntry {
+    /// Prefix partitioning the entry
+    pub prefix: Vec<u8>,
+    /// Key of the entry
+    pub key: Hash,
+    /// Data stored under the key
+    pub data: Vec<u8>,
+}
+
+impl SmtBatchEntry {
+    /// Create a new batch entry
+    pub fn new(prefix: &[u8], key: Hash, data: impl Into<Vec<u8>>) -> Self {
+        Self {
+            prefix: prefix.to_vec(),
+            key,
+            data: data.into(),
+        }
+    }
+}
+
+/// Storage backend trait for SMT data
+#[async_trait]
+pub trait SmtBackend: Send + Sync + Clone {
+    /// Get data by prefix and key
+    async fn get(&self, prefix: &[u8], key: &Hash) -> Result<Option<Vec<u8>>>;
+    
+    /// Set data by prefix and key, returning previous value if any
+    async fn set(&self, prefix: &[u8], key: &Hash, data: &[u8]) -> Result<Option<Vec<u8>>>;
+    
+    /// Remove data by prefix and key, returning previous value if any
+    async fn remove(&self, prefix: &[u8], key: &Hash) -> Result<Option<Vec<u8>>>;
+    
+    /// Check if data exists
+    async fn has(&self, prefix: &[u8], key: &Hash) -> Result<bool>;
+    
+    /// Set several entries at once; later entries for the same prefix and key win
+    async fn set_batch(&self, entries: Vec<SmtBatchEntry>) -> Result<()> {
+        for entry in entries {
+            self.set(&entry.prefix, &entry.key, &entry.data).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Storage key type for memory backend
+type MemoryStorageKey = (Vec<u8>, Hash);
+
+/// Storage data type for memory backend
+type MemoryStorageData = HashMap<MemoryStorageKey, Vec<u8>>;
+
+/// In-memory SMT backend for testing and development
+#[derive(Debug, Clone, Default)]
+pub struct MemorySmtBackend {
+    data: Arc<Mutex<MemoryStorageData>>,
+}
+
+impl MemorySmtBackend {
+    /// Create a new memory backend
+    pub fn new() -> Self {
+        Self {
+            data: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+#[async_trait]
+impl SmtBackend for MemorySmtBackend {
+    async fn get(&self, prefix: &[u8], key: &Hash) -> Result<Option<Vec<u8>>> {
+        let storage_key: MemoryStorageKey = (prefix.to_vec(), *key);
+        let data = self.data.lock()
+            .map_err(|e| CausalityError::storage_error(format!("Failed to lock memory backend: {}", e)))?;
+        Ok(data.get(&storage_key).cloned())
+    }
+    
+    async fn set(&self, prefix: &[u8], key: &Hash, data: &[u8]) -> Result<Option<Vec<u8>>> {
+        let storage_key: MemoryStorageKey = (prefix.to_vec(), *key);
+        let mut storage = self.data.lock()
+            .map_err(|e| CausalityError::storage_error(format!("Failed to lock memory backend: {}", e)))?;
+        Ok(storage.insert(storage_key, data.to_vec()))
+    }
+    
+    async fn remove(&self, prefix: &[u8], key: &Hash) -> Result<Option<Vec<u8>>> {
+        let storage_key: MemoryStorageKey = (prefix.to_vec(), *key);
+        let mut data = self.data.lock()
+            .map_err(|e| CausalityError::storage_error(format!("Failed to lock memory backend: {}", e)))?;
+        Ok(data.remove(&storage_key))
+    }
+    
+    async fn has(&self, prefix: &[u8], key: &Hash) -> Result<bool> {
+        let storage_key: MemoryStorageKey = (prefix.to_vec(), *key);
+        let data = self.data.lock()
+            .map_err(|e| CausalityError::storage_error(format!("Failed to lock memory backend: {}", e)))?;
+        Ok(data.contains_key(&storage_key))
+    }
+    
+    async fn set_batch(&self, entries: Vec<SmtBatchEntry>) -> Result<()> {
+        let mut data = self.data.lock()
+            .map_err(|e| CausalityError::storage_error(format!("Failed to lock memory backend: {}", e)))?;
+        for entry in entries {
+            data.insert((entry.prefix, entry.key), entry.data);
+        }
+        Ok(())
+    }
+}
+
+/// Blake3 hasher implementation
+#[derive(Debug, Clone, Default)]
+pub struct Blake3SmtHasher;
+
+impl SmtHasher for Blake3SmtHasher {
+    fn hash(&self, data: &[u8]) -> Hash {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(&[0x00]); // Data prefix
+        hasher.update(data);
+        hasher.finalize().into()
+    }
+    
+    fn merge(&self, left: &Hash, right: &Hash) -> Hash {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(&[0x01]); // Merge prefix
+        hasher.update(left);
+        hasher.update(right);
+        hasher.finalize().into()
+    }
+    
+    fn key(&self, context: &str, data: &[u8]) -> Hash {
+        blake3::derive_key(context, data)
+    }
+    
+    fn digest(&self, data_list: &[&[u8]]) -> Hash {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(&[0x00]); // Data prefix
+        for data in data_list {
+            hasher.update(data);
+        }
+        hasher.finalize().into()
+    }
+}
+
+/// SHA256 hasher implementation
+#[derive(Debug, Clone, Default)]
+pub struct Sha256SmtHasher;
+
+impl SmtHasher for Sha256SmtHasher {
+    fn hash(&self, data: &[u8]) -> Hash {
+        let mut hasher = Sha256::new();
+        hasher.update([0x00]); // Data prefix
+        hasher.update(data);
+        hasher.finalize().into()
+    }
+    
+    fn merge(&self, left: &Hash, right: &Hash) -> Hash {
+        let mut hasher = Sha256::new();
+        hasher.update([0x01]); // Merge prefix
+        hasher.update(left);
+        hasher.update(right);
+        hasher.finalize().into()
+    }
+    
+    fn key(&self, context: &str, data: &[u8]) -> Hash {
+        let mut hasher = Sha256::new();
+        hasher.update(context.as_bytes());
+        hasher.update(data);
+        hasher.finalize().into()
+    }
+    
+    fn digest(&self, data_list: &[&[u8]]) -> Hash {
+        let mut hasher = Sha256::new();
+        hasher.update([0x00]); // Data prefix
+        for data in data_list {
+            hasher.update(data);
+        }
+        hasher.finalize().into()
+    }
+}
+
+/// Sparse Merkle Tree implementation
+pub struct SparseMerkleTree<B: SmtBackend> {
+    backend: B,
+    hasher: Box<dyn SmtHasher>,
+}
+
+impl<B: SmtBackend> SparseMerkleTree<B> {
+    /// Prefix for tree nodes
+    pub const PREFIX_NODE: &'static [u8] = b"smt-node";
+    
+    /// Prefix for data storage
+    pub const PREFIX_DATA: &'static [u8] = b"smt-data";
+    
+    /// Prefix for key mapping
+    pub const PREFIX_KEY: &'static [u8] = b"smt-key";
+
+    /// Create a new SMT with the given backend and hasher
+    pub fn new(backend: B, hasher: Box<dyn SmtHasher>) -> Self {
+        Self { backend, hasher }
+    }
+
+    /// Create a new SMT with Blake3 hasher
+    pub fn with_blake3(backend: B) -> Self {
+        Self::new(backend, Box::new(Blake3SmtHasher))
+    }
+
+    /// Create a new SMT with SHA256 hasher (default for reverse-causality compatibility)
+    pub fn with_sha256(backend: B) -> Self {
+        Self::new(backend, Box::new(Sha256SmtHasher))
+    }
+
+    /// Create a new SMT with default hasher (SHA256)
+    pub fn with_default_hasher(backend: B) -> Self {
+        Self::with_sha256(backend)
+    }
+
+    /// Get the empty tree root
+    pub fn empty_root() -> Hash {
+        empty_hash()
+    }
+
+    /// Insert data into the tree, returning the new root
+    ///
+    /// All nodes written by the insertion are committed to the backend in a
+    /// single batch once the new root has been computed.
+    pub async fn insert(&self, root: Hash, key: &Hash, data: &[u8]) -> Result<Hash> {
+        let value_hash = self.hasher.hash(data);
+        let leaf_hash = self.hasher.leaf(key, &value_hash);
+        let mut batch = vec![
+            SmtBatchEntry::new(Self::PREFIX_DATA, *key, data),
+            SmtBatchEntry::new(Self::PREFIX_KEY, leaf_hash, [key.as_ref(), value_hash.as_ref()].concat()),
+        ];
+
+        let new_root = self.insert_at(root, key, &leaf_hash, 0, &mut batch).await?;
+        self.backend.set_batch(batch).await?;
+
+        Ok(new_root)
+    }
+
+    /// Insert several entries into the tree, returning the new root
+    ///
+    /// Gives the same root as inserting the entries one by one, with later
+    /// entries for the same key winning. Each existing node on the touched
+    /// paths is read once, each new node is computed once, and all writes are
+    /// committed to the backend in a single batch.
+    pub async fn insert_batch(&self, root: Hash, entries: Vec<(Hash, Vec<u8>)>) -> Result<Hash> {
+        // Keep the last value for each key, in first-seen key order
+        let mut positions: HashMap<Hash, usize> = HashMap::with_capacity(entries.len());
+        let mut unique: Vec<(Hash, Vec<u8>)> = Vec::with_capacity(entries.len());
+        for (key, data) in entries {
+            match positions.get(&key) {
+                Some(&position) => unique[position].1 = data,
+                None => {
+                    positions.insert(key, unique.len());
+                    unique.push((key, data));
+                }
+            }
+        }
+
+        if unique.is_empty() {
+            return Ok(root);
+        }
+
+        let mut batch = Vec::with_capacity(unique.len() * 4);
+        let mut leaves = Vec::with_capacity(unique.len());
+        for (key, data) in &unique {
+            let value_hash = self.hasher.hash(data);
+            let leaf_hash = self.hasher.leaf(key, &value_hash);
+            batch.push(SmtBatchEntry::new(Self::PREFIX_DATA, *key, data.as_slice()));
+            batch.push(SmtBatchEntry::new(Self::PREFIX_KEY, leaf_hash, [key.as_ref(), value_hash.as_ref()].concat()));
+            leaves.push((*key, leaf_hash));
+        }
+
+        let new_root = self.insert_batch_at(root, 0, leaves, &mut batch).await?;
+        self.backend.set_batch(batch).await?;
+
+        Ok(new_root)
+    }
+
+    /// Get data from the tree
+    pub async fn get(&self, _root: Hash, key: &Hash) -> Result<Option<Vec<u8>>> {
+        self.backend.get(Self::PREFIX_DATA, key).await
+    }
+
+    /// Generate an inclusion proof for the given key
+    ///
+    /// Returns `None` if the key is not in the tree.
+    pub async fn get_proof(&self, root: Hash, key: &Hash) -> Result<Option<SmtProof>> {
+        let (terminal, siblings, directions) = self.walk_path(root, key).await?;
+
+        match terminal {
+            Some(leaf) if leaf.key == *key => Ok(Some(SmtProof::new(siblings, directions))),
+            _ => Ok(None),
+        }
+    }
+
+    /// Generate a non-inclusion proof for the given key
+    ///
+    /// Returns `None` if the key is in the tree.
+    pub async fn get_non_inclusion_proof(&self, root: Hash, key: &Hash) -> Result<Option<SmtProof>> {
+        let (terminal, siblings, directions) = self.walk_path(root, key).await?;
+
+        match terminal {
+            Some(leaf) if leaf.key == *key => Ok(None),
+            other_leaf => Ok(Some(SmtProof::non_inclusion(siblings, directions, other_leaf))),
+        }
+    }
+
+    /// Verify a proof
+    pub fn verify_proof(&self, root: &Hash, key: &Hash, data: &[u8], proof: &SmtProof) -> bool {
+        proof.verify(root, key, data, self.hasher.as_ref())
+    }
+
+    /// Verify a non-inclusion proof
+    pub fn verify_non_inclusion_proof(&self, root: &Hash, key: &Hash, proof: &SmtProof) -> bool {
+        proof.verify_non_inclusion(root, key, self.hasher.as_ref())
+    }
+
+    /// Get the hasher
+    pub fn hasher(&self) -> &dyn SmtHasher {
+        self.hasher.as_ref()
+    }
+
+    /// Check if a node is a leaf
+    async fn is_leaf(&self, node: &Hash) -> Result<bool> {
+        if *node == empty_hash() {
+            return Ok(true);
+        }
+        self.has_node_key(node).await
+    }
+
+    /// Get children of a node
+    async fn get_children(&self, node: &Hash) -> Result<Option<SmtChildren>> {
+        if let Some(data) = self.backend.get(Self::PREFIX_NODE, node).await? {
+            if data.len() != 64 {
+                return Err(CausalityError::smt_error("Invalid children data length"));
+            }
+            
+            let mut left = [0u8; 32];
+            let mut right = [0u8; 32];
+            left.copy_from_slice(&data[0..32]);
+            right.copy_from_slice(&data[32..64]);
+            
+            Ok(Some(SmtChildren { left, right }))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Queue the children of a node for writing
+    fn set_children(&self, node: &Hash, children: &SmtChildren, batch: &mut Vec<SmtBatchEntry>) {
+        let mut data = Vec::with_capacity(64);
+        data.extend_from_slice(&children.left);
+        data.extend_from_slice(&children.right);
+        batch.push(SmtBatchEntry::new(Self::PREFIX_NODE, *node, data));
+    }
+
+    /// Check if node has a key mapping
+    async fn has_node_key(&self, node: &Hash) -> Result<bool> {
+        self.backend.has(Self::PREFIX_KEY, node).await
+    }
+
+    /// Get bit at position from key
+    fn get_bit(&self, key: &Hash, position: usize) -> bool {
+        key_bit(key, position)
+    }
+
+    /// Get the key and value hash of a leaf
+    async fn leaf_entry(&self, leaf: &Hash) -> Result<SmtProofLeaf> {
+        let entry = self.backend.get(Self::PREFIX_KEY, leaf).await?
+            .ok_or_else(|| CausalityError::smt_error("Existing leaf has no key mapping"))?;
+
+        if entry.len() != 64 {
+            return Err(CausalityError::smt_error("Invalid leaf key mapping length"));
+        }
+
+        let mut key = [0u8; 32];
+        let mut value_hash = [0u8; 32];
+        key.copy_from_slice(&entry[0..32]);
+        value_hash.copy_from_slice(&entry[32..64]);
+
+        Ok(SmtProofLeaf { key, value_hash })
+    }
+
+    /// Follow the path of `key` from the root down to a leaf or empty subtree
+    ///
+    /// Returns the leaf the path ends at, if any, with the siblings and
+    /// directions along the path from leaf to root.
+    async fn walk_path(&self, root: Hash, key: &Hash) -> Result<(Option<SmtProofLeaf>, Vec<Hash>, Vec<bool>)> {
+        let mut current = root;
+        let mut siblings = Vec::new();
+        let mut directions = Vec::new();
+        let mut depth = 0;
+
+        // Traverse from root to leaf
+        while current != empty_hash() && !self.has_node_key(&current).await? {
+            if depth >= HASH_LEN * 8 {
+                return Err(CausalityError::smt_error("Maximum tree depth exceeded"));
+            }
+
+            let children = self.get_children(&current).await?
+                .ok_or_else(|| CausalityError::smt_error("Node has no children"))?;
+
+            let bit = self.get_bit(key, depth);
+            let (next, sibling) = if bit {
+                (children.right, children.left)
+            } else {
+                (children.left, children.right)
+            };
+
+            siblings.push(sibling);
+            directions.push(bit);
+            current = next;
+            depth += 1;
+        }
+
+        let terminal = if current == empty_hash() {
+            None
+        } else {
+            Some(self.leaf_entry(&current).await?)
+        };
+
+        // Proofs list siblings from leaf to root
+        siblings.reverse();
+        directions.reverse();
+
+        Ok((terminal, siblings, directions))
+    }
+
+    /// Insert a leaf into the subtree rooted at `node`, which sits at `depth`
+    ///
+    /// Only nodes that existed before the insertion are read; new nodes are
+    /// queued in `batch`.
+    fn insert_at<'a>(
+        &'a self,
+        node: Hash,
+        key: &'a Hash,
+        leaf: &'a Hash,
+        depth: usize,
+        batch: &'a mut Vec<SmtBatchEntry>,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Hash>> + Send + 'a>> {
+        Box::pin(async move {
+            if node == empty_hash() {
+                return Ok(*leaf);
+            }
+
+            if self.is_leaf(&node).await? {
+                let existing_key = self.leaf_entry(&node).await?.key;
+
+                // Same key replaces the value
+                if existing_key == *key {
+                    return Ok(*leaf);
+                }
+
+                return self.split_leaf(node, &existing_key, leaf, key, depth, batch);
+            }
+
+            if depth >= HASH_LEN * 8 {
+                return Err(CausalityError::smt_error("Maximum tree depth exceeded"));
+            }
+
+            let children = self.get_children(&node).await?
+                .ok_or_else(|| CausalityError::smt_error("Node has no children"))?;
+
+            let bit = self.get_bit(key, depth);
+            let (target, sibling) = if bit {
+                (children.right, children.left)
+            } else {
+                (children.left, children.right)
+            };
+
+            let new_target = self.insert_at(target, key, leaf, depth + 1, batch).await?;
+
+            let new_children = if bit {
+                SmtChildren { left: sibling, right: new_target }
+            } else {
+                SmtChildren { left: new_target, right: sibling }
+            };
+
+            let new_node = self.hasher.merge(&new_children.left, &new_children.right);
+            self.set_children(&new_node, &new_children, batch);
+
+            Ok(new_node)
+        })
+    }
+
+    /// Insert leaves with distinct keys into the subtree rooted at `node`, which sits at `depth`
+    ///
+    /// Leaves are split by their bit at each depth so every existing node on
+    /// the touched paths is read once and every new node is queued once.
+    fn insert_batch_at<'a>(
+        &'a self,
+        node: Hash,
+        depth: usize,
+        mut leaves: Vec<(Hash, Hash)>,
+        batch: &'a mut Vec<SmtBatchEntry>,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Hash>> + Send + 'a>> {
+        Box::pin(async move {
+            if leaves.is_empty() {
+                return Ok(node);
+            }
+
+            let children = if node == empty_hash() {
+                None
+            } else if self.has_node_key(&node).await? {
+                // An existing leaf moves down alongside the new ones unless its key is replaced
+                let existing_key = self.leaf_entry(&node).await?.key;
+                if !leaves.iter().any(|(key, _)| *key == existing_key) {
+                    leaves.push((existing_key, node));
+                }
+                None
+            } else {
+                Some(self.get_children(&node).await?
+                    .ok_or_else(|| CausalityError::smt_error("Node has no children"))?)
+            };
+
+            if children.is_none() && leaves.len() == 1 {
+                return Ok(leaves[0].1);
+            }
+
+            if depth >= HASH_LEN * 8 {
+                return Err(CausalityError::smt_error("Maximum tree depth exceeded"));
+            }
+
+            let (right, left): (Vec<_>, Vec<_>) = leaves.into_iter()
+                .partition(|(key, _)| self.get_bit(key, depth));
+            let SmtChildren { left: old_left, right: old_right } = children
+                .unwrap_or(SmtChildren { left: empty_hash(), right: empty_hash() });
+
+            let new_children = SmtChildren {
+                left: self.insert_batch_at(old_left, depth + 1, left, batch).await?,
+                right: self.insert_batch_at(old_right, depth + 1, right, batch).await?,
+            };
+
+            if new_children.left == old_left && new_children.right == old_right && node != empty_hash() {
+                return Ok(node);
+            }
+
+            let new_node = self.hasher.merge(&new_children.left, &new_children.right);
+            self.set_children(&new_node, &new_children, batch);
+
+            Ok(new_node)
+        })
+    }
+
+    /// Replace a leaf at `depth` with a subtree holding it and a new leaf
+    fn split_leaf(
+        &self,
+        existing_leaf: Hash,
+        existing_key: &Hash,
+        new_leaf: &Hash,
+        new_key: &Hash,
+        depth: usize,
+        batch: &mut Vec<SmtBatchEntry>,
+    ) -> Result<Hash> {
+        // Find the first differing bit below this depth
+        let mut split_depth = depth;
+        while split_depth < HASH_LEN * 8 && self.get_bit(existing_key, split_depth) == self.get_bit(new_key, split_depth) {
+            split_depth += 1;
+        }
+
+        if split_depth >= HASH_LEN * 8 {
+            return Err(CausalityError::smt_error("Maximum tree depth exceeded"));
+        }
+
+        let children = if self.get_bit(new_key, split_depth) {
+            SmtChildren { left: existing_leaf, right: *new_leaf }
+        } else {
+            SmtChildren { left: *new_leaf, right: existing_leaf }
+        };
+
+        let mut current_node = self.hasher.merge(&children.left, &children.right);
+        self.set_children(&current_node, &children, batch);
+
+        // Create parent nodes along the shared path back up to `depth`
+        while split_depth > depth {
+            split_depth -= 1;
+            let bit = self.get_bit(new_key, split_depth);
+            let sibling = empty_hash();
+
+            let parent_children = if bit {
+                SmtChildren { left: sibling, right: current_node }
+            } else {
+                SmtChildren { left: current_node, right: sibling }
+            };
+
+            current_node = self.hasher.merge(&parent_children.left, &parent_children.right);
+            self.set_children(&current_node, &parent_children, batch);
+        }
+
+        Ok(current_node)
+    }
+}
+
+impl<B: SmtBackend> Clone for SparseMerkleTree<B> {
+    fn clone(&self) -> Self {
+        Self {
+            backend: self.backend.clone(),
+            hasher: Box::new(Sha256SmtHasher), // Default to SHA256 for cloning
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Seed of the randomized suites unless `SMT_TEST_SEED` overrides it
+    const DEFAULT_SEED: u64 = 0x5eed_cafe_f00d_0001;
+
+    /// Deterministic xorshift generator so failures can be replayed from the seed
+    struct TestRng(u64);
+
+    impl TestRng {
+        /// Generator seeded from `SMT_TEST_SEED`, or a fixed seed so runs are reproducible
+        fn from_env() -> Self {
+            let seed = std::env::var("SMT_TEST_SEED")
+                .ok()
+                .and_then(|seed| seed.parse::<u64>().ok())
+                .unwrap_or(DEFAULT_SEED);
+            // Xorshift never leaves the all-zero state
+            Self(seed | 1)
+        }
+
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+    }
+
+    /// Check the basic get/set/remove/has contract of a backend
+    pub(super) async fn run_backend_suite<B: SmtBackend>(backend: B) {
+        let mut rng = TestRng::from_env();
+        let key = Sha256SmtHasher.key("backend-suite", &rng.next().to_be_bytes());
+
+        assert!(!backend.has(b"smt-data", &key).await.unwrap());
+        assert_eq!(backend.get(b"smt-data", &key).await.unwrap(), None);
+
+        assert_eq!(backend.set(b"smt-data", &key, b"first").await.unwrap(), None);
+        assert_eq!(backend.set(b"smt-data", &key, b"second").await.unwrap(), Some(b"first".to_vec()));
+        assert!(backend.has(b"smt-data", &key).await.unwrap());
+
+        // Prefixes are independent partitions
+        assert!(!backend.has(b"smt-node", &key).await.unwrap());
+
+        // Later batch entries win
+        backend.set_batch(vec![
+            SmtBatchEntry::new(b"smt-data", key, b"third".to_vec()),
+            SmtBatchEntry::new(b"smt-node", key, b"node".to_vec()),
+            SmtBatchEntry::new(b"smt-data", key, b"fourth".to_vec()),
+        ]).await.unwrap();
+        assert_eq!(backend.get(b"smt-data", &key).await.unwrap(), Some(b"fourth".to_vec()));
+        assert_eq!(backend.get(b"smt-node", &key).await.unwrap(), Some(b"node".to_vec()));
+
+        assert_eq!(backend.remove(b"smt-data", &key).await.unwrap(), Some(b"fourth".to_vec()));
+        assert_eq!(backend.remove(b"smt-data", &key).await.unwrap(), None);
+        assert!(!backend.has(b"smt-data", &key).await.unwrap());
+        backend.remove(b"smt-node", &key).await.unwrap();
+    }
+
+    /// Insert random keys and check every value and proof against the final root
+    pub(super) async fn run_randomized_insert_proof_suite<B: SmtBackend>(backend: B) {
+        let mut rng = TestRng::from_env();
+        let seed = rng.0;
+        let smt = SparseMerkleTree::with_sha256(backend);
+
+        let mut root = empty_hash();
+        let mut entries = Vec::new();
+        for i in 0..64 {
+            let key = smt.hasher().key("randomized-suite", &rng.next().to_be_bytes());
+            let value = format!("value-{}-{}", seed, i).into_bytes();
+
+            root = smt.insert(root, &key, &value).await.unwrap();
+            assert_eq!(smt.get(root, &key).await.unwrap(), Some(value.clone()), "seed {}", seed);
+            entries.push((key, value));
+        }
+
+        // Replace the value of an existing key
+        let replaced = format!("replaced-{}", seed).into_bytes();
+        root = smt.insert(root, &entries[0].0, &replaced).await.unwrap();
+        entries[0].1 = replaced;
+
+        for (key, value) in &entries {
+            let proof = smt.get_proof(root, key).await.unwrap().expect("proof for inserted key");
+            assert!(smt.verify_proof(&root, key, value, &proof), "seed {}", seed);
+            assert!(!smt.verify_proof(&root, key, b"wrong value", &proof), "seed {}", seed);
+            assert!(smt.get_non_inclusion_proof(root, key).await.unwrap().is_none(), "seed {}", seed);
+
+            let decoded = SmtProof::from_bytes(&proof.to_bytes()).unwrap();
+            assert_eq!(decoded, proof, "seed {}", seed);
+        }
+
+        for _ in 0..16 {
+            let absent = smt.hasher().key("randomized-suite-absent", &rng.next().to_be_bytes());
+            assert!(smt.get_proof(root, &absent).await.unwrap().is_none(), "seed {}", seed);
+
+            let proof = smt.get_non_inclusion_proof(root, &absent).await.unwrap().expect("proof for absent key");
+            assert!(smt.verify_non_inclusion_proof(&root, &absent, &proof), "seed {}", seed);
+            assert!(!smt.verify_non_inclusion_proof(&root, &entries[1].0, &proof), "seed {}", seed);
+
+            let decoded = SmtProof::from_bytes(&proof.to_bytes()).unwrap();
+            assert!(smt.verify_non_inclusion_proof(&root, &absent, &decoded), "seed {}", seed);
+        }
+    }
+
+    /// Insert blocks of random keys in batches and check them against one-by-one insertion
+    pub(super) async fn run_batch_insert_suite<B: SmtBackend>(backend: B) {
+        let mut rng = TestRng::from_env();
+        let seed = rng.0;
+        let smt = SparseMerkleTree::with_sha256(backend);
+
+        let mut sequential_root = empty_hash();
+        let mut batch_root = empty_hash();
+        let mut latest: HashMap<Hash, Vec<u8>> = HashMap::new();
+
+        for block in 0..8 {
+            let mut entries: Vec<(Hash, Vec<u8>)> = (0..16)
+                .map(|i| {
+                    let key = smt.hasher().key("batch-suite", &rng.next().to_be_bytes());
+                    (key, format!("value-{}-{}-{}", seed, block, i).into_bytes())
+                })
+                .collect();
+
+            // Replace a key from an earlier block and repeat one within the block
+            if let Some(earlier) = latest.keys().next().copied() {
+                entries.push((earlier, format!("replaced-{}-{}", seed, block).into_bytes()));
+            }
+            entries.push((entries[0].0, format!("repeated-{}-{}", seed, block).into_bytes()));
+
+            for (key, value) in &entries {
+                sequential_root = smt.insert(sequential_root, key, value).await.unwrap();
+                latest.insert(*key, value.clone());
+            }
+            batch_root = smt.insert_batch(batch_root, entries).await.unwrap();
+            assert_eq!(batch_root, sequential_root, "seed {} block {}", seed, block);
+        }
+
+        for (key, value) in &latest {
+            let proof = smt.get_proof(batch_root, key).await.unwrap().expect("proof for inserted key");
+            assert!(smt.verify_proof(&batch_root, key, value, &proof), "seed {}", seed);
+        }
+
+        assert_eq!(smt.insert_batch(batch_root, Vec::new()).await.unwrap(), batch_root);
+    }
+
+    #[tokio::test]
+    async fn test_memory_backend_batch_insert() {
+        run_batch_insert_suite(MemorySmtBackend::new()).await;
+    }
+
+    #[tokio::test]
+    async fn test_memory_backend_suite() {
+        run_backend_suite(MemorySmtBackend::new()).await;
+    }
+
+    #[tokio::test]
+    async fn test_memory_backend_randomized() {
+        run_randomized_insert_proof_suite(MemorySmtBackend::new()).await;
+    }
+
+    #[tokio::test]
+    async fn test_empty_tree() {
+        let backend = MemorySmtBackend::new();
+        let _smt = SparseMerkleTree::with_sha256(backend);
+        
+        let root = SparseMerkleTree::<MemorySmtBackend>::empty_root();
+        assert_eq!(root, empty_hash());
+    }
+
+    #[tokio::test]
+    async fn test_single_insertion() {
+        let backend = MemorySmtBackend::new();
+        let smt = SparseMerkleTree::with_sha256(backend);
+        
+        let key = Sha256SmtHasher.key("test", b"key1");
+        let data = b"value1";
+        
+        let root = smt.insert(empty_hash(), &key, data).await.unwrap();
+        assert_ne!(root, empty_hash());
+        
+        let retrieved = smt.get(root, &key).await.unwrap();
+        assert_eq!(retrieved, Some(data.to_vec()));
+    }
+
+    #[tokio::test]
+    async fn test_proof_generation_and_verification() {
+        let backend = MemorySmtBackend::new();
+        let smt = SparseMerkleTree::with_sha256(backend);
+        
+        let key = Sha256SmtHasher.key("test", b"key1");
+        let data = b"value1";
+        
+        let root = smt.insert(empty_hash(), &key, data).await.unwrap();
+        let proof = smt.get_proof(root, &key).await.unwrap();
+        
+        assert!(proof.is_some());
+        let proof = proof.unwrap();
+        assert!(smt.verify_proof(&root, &key, data, &proof));
+    }
+
+    #[tokio::test]
+    async fn test_proof_is_bound_to_key() {
+        let smt = SparseMerkleTree::with_sha256(MemorySmtBackend::new());
+        let key_a = Sha256SmtHasher.key("test", b"a");
+        let key_b = Sha256SmtHasher.key("test", b"b");
+
+        // Same value under both keys must still give key-specific proofs
+        let mut root = smt.insert(empty_hash(), &key_a, b"shared").await.unwrap();
+        root = smt.insert(root, &key_b, b"shared").await.unwrap();
+
+        let proof_a = smt.get_proof(root, &key_a).await.unwrap().unwrap();
+        let proof_b = smt.get_proof(root, &key_b).await.unwrap().unwrap();
+        assert!(smt.verify_proof(&root, &key_a, b"shared", &proof_a));
+        assert!(smt.verify_proof(&root, &key_b, b"shared", &proof_b));
+        assert!(!smt.verify_proof(&root, &key_b, b"shared", &proof_a));
+        assert!(!smt.verify_proof(&root, &key_a, b"shared", &proof_b));
+    }
+
+    #[tokio::test]
+    async fn test_non_inclusion_in_empty_and_single_leaf_tree() {
+        let smt = SparseMerkleTree::with_sha256(MemorySmtBackend::new());
+        let key = Sha256SmtHasher.key("test", b"present");
+        let absent = Sha256SmtHasher.key("test", b"absent");
+
+        let empty_proof = smt.get_non_inclusion_proof(empty_hash(), &absent).await.unwrap().unwrap();
+        assert!(smt.verify_non_inclusion_proof(&empty_hash(), &absent, &empty_proof));
+
+        let root = smt.insert(empty_hash(), &key, b"value").await.unwrap();
+        let proof = smt.get_non_inclusion_proof(root, &absent).await.unwrap().unwrap();
+        assert_eq!(proof.other_leaf.map(|leaf| leaf.key), Some(key));
+        assert!(smt.verify_non_inclusion_proof(&root, &absent, &proof));
+
+        // A non-inclusion proof cannot be turned into an inclusion proof
+        assert!(!smt.verify_proof(&root, &key, b"value", &proof));
+    }
+
+    #[test]
+    fn test_proof_decoding_rejects_malformed_input() {
+        let proof = SmtProof::new(vec![[1u8; 32], empty_hash()], vec![true, false]);
+        let bytes = proof.to_bytes();
+        assert_eq!(SmtProof::from_bytes(&bytes).unwrap(), proof);
+
+        assert!(SmtProof::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(SmtProof::from_bytes(&[bytes.as_slice(), &[0]].concat()).is_err());
+
+        let mut wrong_version = bytes.clone();
+        wrong_version[0] = 99;
+        assert!(SmtProof::from_bytes(&wrong_version).is_err());
+    }
+}
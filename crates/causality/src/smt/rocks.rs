@@ -0,0 +1,184 @@
+/// RocksDB SMT backend
+use std::path::Path;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use rocksdb::{BoundColumnFamily, DBWithThreadMode, MultiThreaded, Options, WriteBatch};
+
+use crate::error::{CausalityError, Result};
+use crate::types::Hash;
+
+use super::{SmtBackend, SmtBatchEntry, SparseMerkleTree, MemorySmtBackend};
+
+/// RocksDB handle shared between clones of the backend
+type RocksDb = DBWithThreadMode<MultiThreaded>;
+
+/// RocksDB SMT backend storing each prefix in its own column family
+///
+/// Column families are named after the prefix (e.g. `smt-node`) and are
+/// created on first use.
+#[derive(Clone)]
+pub struct RocksSmtBackend {
+    db: Arc<RocksDb>,
+    cf_lock: Arc<Mutex<()>>,
+}
+
+impl std::fmt::Debug for RocksSmtBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RocksSmtBackend")
+            .field("path", &self.db.path())
+            .finish()
+    }
+}
+
+impl RocksSmtBackend {
+    /// Open or create a RocksDB backend at the given path
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+
+        // Column families from previous runs plus the ones the tree always uses
+        let mut cf_names = RocksDb::list_cf(&opts, path.as_ref()).unwrap_or_default();
+        for prefix in Self::default_prefixes() {
+            let name = cf_name(prefix);
+            if !cf_names.contains(&name) {
+                cf_names.push(name);
+            }
+        }
+
+        let db = RocksDb::open_cf(&opts, path.as_ref(), &cf_names)
+            .map_err(|e| CausalityError::storage_error(format!("Failed to open RocksDB: {}", e)))?;
+
+        Ok(Self {
+            db: Arc::new(db),
+            cf_lock: Arc::new(Mutex::new(())),
+        })
+    }
+
+    /// Prefixes used by the sparse Merkle tree
+    fn default_prefixes() -> [&'static [u8]; 3] {
+        [
+            SparseMerkleTree::<MemorySmtBackend>::PREFIX_NODE,
+            SparseMerkleTree::<MemorySmtBackend>::PREFIX_DATA,
+            SparseMerkleTree::<MemorySmtBackend>::PREFIX_KEY,
+        ]
+    }
+
+    /// Get the column family for a prefix, creating it if needed
+    fn cf(&self, prefix: &[u8]) -> Result<Arc<BoundColumnFamily<'_>>> {
+        let name = cf_name(prefix);
+        if let Some(cf) = self.db.cf_handle(&name) {
+            return Ok(cf);
+        }
+
+        let _guard = self.cf_lock.lock();
+        if self.db.cf_handle(&name).is_none() {
+            self.db.create_cf(&name, &Options::default())
+                .map_err(|e| CausalityError::storage_error(format!("Failed to create column family '{}': {}", name, e)))?;
+        }
+
+        self.db.cf_handle(&name)
+            .ok_or_else(|| CausalityError::storage_error(format!("Column family '{}' not found", name)))
+    }
+}
+
+#[async_trait]
+impl SmtBackend for RocksSmtBackend {
+    async fn get(&self, prefix: &[u8], key: &Hash) -> Result<Option<Vec<u8>>> {
+        let cf = self.cf(prefix)?;
+        self.db.get_cf(&cf, key).map_err(db_error)
+    }
+
+    async fn set(&self, prefix: &[u8], key: &Hash, data: &[u8]) -> Result<Option<Vec<u8>>> {
+        let cf = self.cf(prefix)?;
+        let previous = self.db.get_cf(&cf, key).map_err(db_error)?;
+        self.db.put_cf(&cf, key, data).map_err(db_error)?;
+        Ok(previous)
+    }
+
+    async fn remove(&self, prefix: &[u8], key: &Hash) -> Result<Option<Vec<u8>>> {
+        let cf = self.cf(prefix)?;
+        let previous = self.db.get_cf(&cf, key).map_err(db_error)?;
+        if previous.is_some() {
+            self.db.delete_cf(&cf, key).map_err(db_error)?;
+        }
+        Ok(previous)
+    }
+
+    async fn has(&self, prefix: &[u8], key: &Hash) -> Result<bool> {
+        let cf = self.cf(prefix)?;
+        Ok(self.db.get_pinned_cf(&cf, key).map_err(db_error)?.is_some())
+    }
+
+    async fn set_batch(&self, entries: Vec<SmtBatchEntry>) -> Result<()> {
+        let mut batch = WriteBatch::default();
+        for entry in &entries {
+            let cf = self.cf(&entry.prefix)?;
+            batch.put_cf(&cf, entry.key, &entry.data);
+        }
+        self.db.write(batch).map_err(db_error)
+    }
+}
+
+/// Column family holding the entries of a prefix
+///
+/// The prefix is hex-encoded so distinct prefixes, including ones that are
+/// not valid UTF-8, always map to distinct column families.
+fn cf_name(prefix: &[u8]) -> String {
+    format!("smt_{}", hex::encode(prefix))
+}
+
+/// Map a RocksDB error into a storage error
+fn db_error(e: rocksdb::Error) -> CausalityError {
+    CausalityError::storage_error(format!("RocksDB error: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cf_name() {
+        assert_eq!(cf_name(b"smt-node"), "smt_736d742d6e6f6465");
+        // Invalid UTF-8 prefixes no longer collapse to the same replacement characters
+        assert_ne!(cf_name(&[0xff]), cf_name(&[0xfe]));
+        assert_ne!(cf_name(b"default"), "default");
+    }
+
+    #[tokio::test]
+    async fn test_rocks_backend_distinct_binary_prefixes() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = RocksSmtBackend::open(dir.path()).unwrap();
+        let key = [7u8; 32];
+
+        backend.set(&[0xff], &key, b"first").await.unwrap();
+        backend.set(&[0xfe], &key, b"second").await.unwrap();
+        assert_eq!(backend.get(&[0xff], &key).await.unwrap(), Some(b"first".to_vec()));
+    }
+
+    #[tokio::test]
+    async fn test_rocks_backend_randomized() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = RocksSmtBackend::open(dir.path()).unwrap();
+
+        super::super::tests::run_backend_suite(backend.clone()).await;
+        super::super::tests::run_randomized_insert_proof_suite(backend.clone()).await;
+        super::super::tests::run_batch_insert_suite(backend).await;
+    }
+
+    #[tokio::test]
+    async fn test_rocks_backend_persists_across_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = [7u8; 32];
+
+        {
+            let backend = RocksSmtBackend::open(dir.path()).unwrap();
+            backend.set(b"custom-prefix", &key, b"value").await.unwrap();
+        }
+
+        let backend = RocksSmtBackend::open(dir.path()).unwrap();
+        assert_eq!(backend.get(b"custom-prefix", &key).await.unwrap(), Some(b"value".to_vec()));
+    }
+}
@@ -0,0 +1,1235 @@
+//! Canonical SSZ encoding of causality entities
+//!
+//! Entities are encoded as SSZ containers so that the same entity always
+//! produces the same bytes, independent of build features, and therefore the
+//! same content-addressed [`EntityId`]. The mapping of Rust types to SSZ types
+//! is:
+//!
+//! - `u8`, `u32`, `u64`, `bool`: `uint8`, `uint32`, `uint64`, `boolean`
+//! - [`Hash`] and the ID newtypes: `Vector[uint8, 32]`
+//! - `String` and [`ChainId`]: UTF-8 bytes as `List[uint8, N]`
+//! - `Vec<T>`: `List[T, N]`
+//! - `Option<T>`: `Union[None, T]`
+//! - `SystemTime`: `Container { seconds: uint64, nanos: uint32 }` since the
+//!   Unix epoch, with times before the epoch encoded as the epoch
+//! - enums: `Union` with one selector per variant in declaration order,
+//!   except [`CausalityEventType`] which is a `uint8`
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use indexer_core::types::ChainId;
+
+use crate::error::{CausalityError, Result};
+use crate::types::{
+    CausalityDomain, CausalityEffect, CausalityEvent, CausalityEventData, CausalityEventType,
+    CausalityHandler, CausalityIntent, CausalityNullifier, CausalityResource, CausalityTransaction,
+    ContentAddressable, DomainId, EffectId, EntityId, ExprId, HandlerId, Hash, IntentId, ResourceFlow,
+    ResourceId, TransactionId, TypedDomain, ValueExprId,
+};
+
+/// Size of an offset to a variable-size field
+pub const BYTES_PER_LENGTH_OFFSET: usize = 4;
+
+/// Type with a canonical SSZ encoding
+pub trait Ssz: Sized {
+    /// Whether every value of the type encodes to the same number of bytes
+    fn is_ssz_fixed_len() -> bool;
+
+    /// Encoded size of fixed-size types, or the offset size for variable-size types
+    fn ssz_fixed_len() -> usize {
+        BYTES_PER_LENGTH_OFFSET
+    }
+
+    /// Append the encoding of `self` to `buf`
+    fn ssz_append(&self, buf: &mut Vec<u8>);
+
+    /// Decode a value from exactly `bytes`
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self>;
+
+    /// Encode `self` into a new buffer
+    fn as_ssz_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        self.ssz_append(&mut buf);
+        buf
+    }
+}
+
+/// Encoder for the fields of an SSZ container, in declaration order
+///
+/// Fixed-size fields are written in place. Variable-size fields are written
+/// after the fixed part and referenced by an offset in their place.
+#[derive(Debug, Default)]
+pub struct SszEncoder {
+    fixed: Vec<u8>,
+    variable: Vec<u8>,
+    offsets: Vec<(usize, usize)>,
+}
+
+impl SszEncoder {
+    /// Create an encoder for an empty container
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append the next field
+    pub fn append<T: Ssz>(&mut self, value: &T) {
+        if T::is_ssz_fixed_len() {
+            value.ssz_append(&mut self.fixed);
+        } else {
+            self.offsets.push((self.fixed.len(), self.variable.len()));
+            self.fixed.extend_from_slice(&[0; BYTES_PER_LENGTH_OFFSET]);
+            value.ssz_append(&mut self.variable);
+        }
+    }
+
+    /// Resolve the offsets and append the container to `buf`
+    pub fn finish(self, buf: &mut Vec<u8>) {
+        let mut fixed = self.fixed;
+        let fixed_len = fixed.len();
+        for (position, start) in self.offsets {
+            let offset = encode_offset(fixed_len + start);
+            fixed[position..position + BYTES_PER_LENGTH_OFFSET].copy_from_slice(&offset);
+        }
+        buf.extend_from_slice(&fixed);
+        buf.extend_from_slice(&self.variable);
+    }
+}
+
+/// Builder registering the field types of an SSZ container before decoding
+#[derive(Debug)]
+pub struct SszDecoderBuilder<'a> {
+    bytes: &'a [u8],
+    fixed_end: usize,
+    items: Vec<(usize, usize)>,
+    offsets: Vec<(usize, usize)>,
+}
+
+impl<'a> SszDecoderBuilder<'a> {
+    /// Start decoding a container from `bytes`
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, fixed_end: 0, items: Vec::new(), offsets: Vec::new() }
+    }
+
+    /// Register the type of the next field
+    pub fn register_type<T: Ssz>(&mut self) -> Result<()> {
+        let end = self.fixed_end + T::ssz_fixed_len();
+        if end > self.bytes.len() {
+            return Err(CausalityError::serialization_error("SSZ container is truncated"));
+        }
+
+        if T::is_ssz_fixed_len() {
+            self.items.push((self.fixed_end, end));
+        } else {
+            let offset = decode_offset(&self.bytes[self.fixed_end..end]);
+            self.offsets.push((self.items.len(), offset));
+            self.items.push((0, 0));
+        }
+        self.fixed_end = end;
+        Ok(())
+    }
+
+    /// Check the offsets and resolve the byte range of every field
+    pub fn build(mut self) -> Result<SszDecoder<'a>> {
+        match self.offsets.first() {
+            None if self.fixed_end != self.bytes.len() => {
+                return Err(CausalityError::serialization_error("Trailing bytes after SSZ container"));
+            }
+            Some(&(_, first)) if first != self.fixed_end => {
+                return Err(CausalityError::serialization_error(format!(
+                    "First SSZ offset {} does not match fixed part length {}",
+                    first, self.fixed_end
+                )));
+            }
+            _ => {}
+        }
+
+        for (i, &(item, start)) in self.offsets.iter().enumerate() {
+            let end = self.offsets.get(i + 1).map_or(self.bytes.len(), |&(_, next)| next);
+            if start > end || end > self.bytes.len() {
+                return Err(CausalityError::serialization_error(format!("Invalid SSZ offset {}", start)));
+            }
+            self.items[item] = (start, end);
+        }
+
+        Ok(SszDecoder { bytes: self.bytes, items: self.items.into_iter() })
+    }
+}
+
+/// Decoder yielding the fields of an SSZ container in declaration order
+#[derive(Debug)]
+pub struct SszDecoder<'a> {
+    bytes: &'a [u8],
+    items: std::vec::IntoIter<(usize, usize)>,
+}
+
+impl<'a> SszDecoder<'a> {
+    /// Decode the next field
+    pub fn decode_next<T: Ssz>(&mut self) -> Result<T> {
+        let (start, end) = self.items.next()
+            .ok_or_else(|| CausalityError::serialization_error("No more SSZ fields to decode"))?;
+        T::from_ssz_bytes(&self.bytes[start..end])
+    }
+}
+
+fn encode_offset(offset: usize) -> [u8; BYTES_PER_LENGTH_OFFSET] {
+    (offset as u32).to_le_bytes()
+}
+
+fn decode_offset(bytes: &[u8]) -> usize {
+    let mut offset = [0u8; BYTES_PER_LENGTH_OFFSET];
+    offset.copy_from_slice(bytes);
+    u32::from_le_bytes(offset) as usize
+}
+
+/// Copy `bytes` into an array, failing if the length does not match
+fn fixed_bytes<const N: usize>(bytes: &[u8]) -> Result<[u8; N]> {
+    bytes.try_into().map_err(|_| {
+        CausalityError::serialization_error(format!("Expected {} SSZ bytes, got {}", N, bytes.len()))
+    })
+}
+
+/// Split a union into its selector and value bytes
+fn split_union(bytes: &[u8]) -> Result<(u8, &[u8])> {
+    bytes.split_first()
+        .map(|(selector, value)| (*selector, value))
+        .ok_or_else(|| CausalityError::serialization_error("SSZ union is empty"))
+}
+
+fn invalid_selector(type_name: &str, selector: u8) -> CausalityError {
+    CausalityError::serialization_error(format!("Invalid {} union selector {}", type_name, selector))
+}
+
+macro_rules! impl_ssz_uint {
+    ($($ty:ty),*) => {
+        $(
+            impl Ssz for $ty {
+                fn is_ssz_fixed_len() -> bool {
+                    true
+                }
+
+                fn ssz_fixed_len() -> usize {
+                    std::mem::size_of::<$ty>()
+                }
+
+                fn ssz_append(&self, buf: &mut Vec<u8>) {
+                    buf.extend_from_slice(&self.to_le_bytes());
+                }
+
+                fn from_ssz_bytes(bytes: &[u8]) -> Result<Self> {
+                    Ok(<$ty>::from_le_bytes(fixed_bytes(bytes)?))
+                }
+            }
+        )*
+    };
+}
+
+impl_ssz_uint!(u8, u32, u64);
+
+impl Ssz for bool {
+    fn is_ssz_fixed_len() -> bool {
+        true
+    }
+
+    fn ssz_fixed_len() -> usize {
+        1
+    }
+
+    fn ssz_append(&self, buf: &mut Vec<u8>) {
+        buf.push(u8::from(*self));
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self> {
+        match fixed_bytes::<1>(bytes)? {
+            [0] => Ok(false),
+            [1] => Ok(true),
+            [other] => Err(CausalityError::serialization_error(format!("Invalid SSZ boolean {}", other))),
+        }
+    }
+}
+
+impl Ssz for Hash {
+    fn is_ssz_fixed_len() -> bool {
+        true
+    }
+
+    fn ssz_fixed_len() -> usize {
+        32
+    }
+
+    fn ssz_append(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(self);
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self> {
+        fixed_bytes(bytes)
+    }
+}
+
+macro_rules! impl_ssz_id {
+    ($($ty:ident),*) => {
+        $(
+            impl Ssz for $ty {
+                fn is_ssz_fixed_len() -> bool {
+                    true
+                }
+
+                fn ssz_fixed_len() -> usize {
+                    32
+                }
+
+                fn ssz_append(&self, buf: &mut Vec<u8>) {
+                    buf.extend_from_slice(&self.inner());
+                }
+
+                fn from_ssz_bytes(bytes: &[u8]) -> Result<Self> {
+                    Ok($ty::new(fixed_bytes(bytes)?))
+                }
+            }
+        )*
+    };
+}
+
+impl_ssz_id!(EntityId, DomainId, ExprId, ValueExprId, HandlerId, IntentId, ResourceId, TransactionId, EffectId);
+
+impl Ssz for String {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn ssz_append(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(self.as_bytes());
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self> {
+        String::from_utf8(bytes.to_vec())
+            .map_err(|e| CausalityError::serialization_error(format!("Invalid UTF-8 in SSZ string: {}", e)))
+    }
+}
+
+impl Ssz for ChainId {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn ssz_append(&self, buf: &mut Vec<u8>) {
+        self.0.ssz_append(buf);
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self> {
+        String::from_ssz_bytes(bytes).map(ChainId)
+    }
+}
+
+impl Ssz for SystemTime {
+    fn is_ssz_fixed_len() -> bool {
+        true
+    }
+
+    fn ssz_fixed_len() -> usize {
+        12
+    }
+
+    fn ssz_append(&self, buf: &mut Vec<u8>) {
+        let since_epoch = self.duration_since(UNIX_EPOCH).unwrap_or_default();
+        since_epoch.as_secs().ssz_append(buf);
+        since_epoch.subsec_nanos().ssz_append(buf);
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut builder = SszDecoderBuilder::new(bytes);
+        builder.register_type::<u64>()?;
+        builder.register_type::<u32>()?;
+        let mut decoder = builder.build()?;
+
+        let seconds: u64 = decoder.decode_next()?;
+        let nanos: u32 = decoder.decode_next()?;
+        if nanos >= 1_000_000_000 {
+            return Err(CausalityError::serialization_error(format!("Invalid SSZ timestamp nanos {}", nanos)));
+        }
+        UNIX_EPOCH.checked_add(Duration::new(seconds, nanos))
+            .ok_or_else(|| CausalityError::serialization_error("SSZ timestamp out of range"))
+    }
+}
+
+impl<T: Ssz> Ssz for Vec<T> {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn ssz_append(&self, buf: &mut Vec<u8>) {
+        let mut encoder = SszEncoder::new();
+        for item in self {
+            encoder.append(item);
+        }
+        encoder.finish(buf);
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        if T::is_ssz_fixed_len() {
+            let item_len = T::ssz_fixed_len();
+            if bytes.len() % item_len != 0 {
+                return Err(CausalityError::serialization_error(format!(
+                    "SSZ list length {} is not a multiple of item length {}",
+                    bytes.len(), item_len
+                )));
+            }
+            return bytes.chunks(item_len).map(T::from_ssz_bytes).collect();
+        }
+
+        let first = decode_offset(bytes.get(..BYTES_PER_LENGTH_OFFSET).ok_or_else(|| {
+            CausalityError::serialization_error("SSZ list is truncated")
+        })?);
+        if first == 0 || first % BYTES_PER_LENGTH_OFFSET != 0 {
+            return Err(CausalityError::serialization_error(format!("Invalid first SSZ list offset {}", first)));
+        }
+
+        let count = first / BYTES_PER_LENGTH_OFFSET;
+        let mut builder = SszDecoderBuilder::new(bytes);
+        for _ in 0..count {
+            builder.register_type::<T>()?;
+        }
+        let mut decoder = builder.build()?;
+        (0..count).map(|_| decoder.decode_next()).collect()
+    }
+}
+
+impl<T: Ssz> Ssz for Option<T> {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn ssz_append(&self, buf: &mut Vec<u8>) {
+        match self {
+            None => buf.push(0),
+            Some(value) => {
+                buf.push(1);
+                value.ssz_append(buf);
+            }
+        }
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self> {
+        match split_union(bytes)? {
+            (0, []) => Ok(None),
+            (0, _) => Err(CausalityError::serialization_error("Trailing bytes after SSZ None")),
+            (1, value) => T::from_ssz_bytes(value).map(Some),
+            (selector, _) => Err(invalid_selector("Option", selector)),
+        }
+    }
+}
+
+impl Ssz for ResourceFlow {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn ssz_append(&self, buf: &mut Vec<u8>) {
+        let mut encoder = SszEncoder::new();
+        encoder.append(&self.from_event);
+        encoder.append(&self.to_event);
+        encoder.append(&self.resource_type);
+        encoder.append(&self.resource_data);
+        encoder.append(&self.timestamp);
+        encoder.finish(buf);
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut builder = SszDecoderBuilder::new(bytes);
+        builder.register_type::<String>()?;
+        builder.register_type::<String>()?;
+        builder.register_type::<String>()?;
+        builder.register_type::<Vec<u8>>()?;
+        builder.register_type::<SystemTime>()?;
+        let mut decoder = builder.build()?;
+
+        Ok(Self {
+            from_event: decoder.decode_next()?,
+            to_event: decoder.decode_next()?,
+            resource_type: decoder.decode_next()?,
+            resource_data: decoder.decode_next()?,
+            timestamp: decoder.decode_next()?,
+        })
+    }
+}
+
+impl Ssz for TypedDomain {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn ssz_append(&self, buf: &mut Vec<u8>) {
+        let mut encoder = SszEncoder::new();
+        match self {
+            Self::VerifiableDomain { domain_id, capabilities } => {
+                buf.push(0);
+                encoder.append(domain_id);
+                encoder.append(capabilities);
+            }
+            Self::ServiceDomain { domain_id, service_type, endpoint } => {
+                buf.push(1);
+                encoder.append(domain_id);
+                encoder.append(service_type);
+                encoder.append(endpoint);
+            }
+        }
+        encoder.finish(buf);
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self> {
+        let (selector, value) = split_union(bytes)?;
+        let mut builder = SszDecoderBuilder::new(value);
+        match selector {
+            0 => {
+                builder.register_type::<DomainId>()?;
+                builder.register_type::<Vec<String>>()?;
+                let mut decoder = builder.build()?;
+                Ok(Self::VerifiableDomain {
+                    domain_id: decoder.decode_next()?,
+                    capabilities: decoder.decode_next()?,
+                })
+            }
+            1 => {
+                builder.register_type::<DomainId>()?;
+                builder.register_type::<String>()?;
+                builder.register_type::<Option<String>>()?;
+                let mut decoder = builder.build()?;
+                Ok(Self::ServiceDomain {
+                    domain_id: decoder.decode_next()?,
+                    service_type: decoder.decode_next()?,
+                    endpoint: decoder.decode_next()?,
+                })
+            }
+            selector => Err(invalid_selector("TypedDomain", selector)),
+        }
+    }
+}
+
+impl Ssz for CausalityResource {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn ssz_append(&self, buf: &mut Vec<u8>) {
+        let mut encoder = SszEncoder::new();
+        encoder.append(&self.id);
+        encoder.append(&self.name);
+        encoder.append(&self.domain_id);
+        encoder.append(&self.resource_type);
+        encoder.append(&self.quantity);
+        encoder.append(&self.timestamp);
+        encoder.finish(buf);
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut builder = SszDecoderBuilder::new(bytes);
+        builder.register_type::<EntityId>()?;
+        builder.register_type::<String>()?;
+        builder.register_type::<DomainId>()?;
+        builder.register_type::<String>()?;
+        builder.register_type::<u64>()?;
+        builder.register_type::<SystemTime>()?;
+        let mut decoder = builder.build()?;
+
+        Ok(Self {
+            id: decoder.decode_next()?,
+            name: decoder.decode_next()?,
+            domain_id: decoder.decode_next()?,
+            resource_type: decoder.decode_next()?,
+            quantity: decoder.decode_next()?,
+            timestamp: decoder.decode_next()?,
+        })
+    }
+}
+
+impl Ssz for CausalityEffect {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn ssz_append(&self, buf: &mut Vec<u8>) {
+        let mut encoder = SszEncoder::new();
+        encoder.append(&self.id);
+        encoder.append(&self.name);
+        encoder.append(&self.domain_id);
+        encoder.append(&self.effect_type);
+        encoder.append(&self.inputs);
+        encoder.append(&self.outputs);
+        encoder.append(&self.expression);
+        encoder.append(&self.timestamp);
+        encoder.append(&self.scoped_by);
+        encoder.append(&self.intent_id);
+        encoder.append(&self.source_typed_domain);
+        encoder.append(&self.target_typed_domain);
+        encoder.append(&self.originating_dataflow_instance);
+        encoder.finish(buf);
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut builder = SszDecoderBuilder::new(bytes);
+        builder.register_type::<EntityId>()?;
+        builder.register_type::<String>()?;
+        builder.register_type::<DomainId>()?;
+        builder.register_type::<String>()?;
+        builder.register_type::<Vec<ResourceFlow>>()?;
+        builder.register_type::<Vec<ResourceFlow>>()?;
+        builder.register_type::<Option<ExprId>>()?;
+        builder.register_type::<SystemTime>()?;
+        builder.register_type::<HandlerId>()?;
+        builder.register_type::<Option<IntentId>>()?;
+        builder.register_type::<TypedDomain>()?;
+        builder.register_type::<TypedDomain>()?;
+        builder.register_type::<Option<ResourceId>>()?;
+        let mut decoder = builder.build()?;
+
+        Ok(Self {
+            id: decoder.decode_next()?,
+            name: decoder.decode_next()?,
+            domain_id: decoder.decode_next()?,
+            effect_type: decoder.decode_next()?,
+            inputs: decoder.decode_next()?,
+            outputs: decoder.decode_next()?,
+            expression: decoder.decode_next()?,
+            timestamp: decoder.decode_next()?,
+            scoped_by: decoder.decode_next()?,
+            intent_id: decoder.decode_next()?,
+            source_typed_domain: decoder.decode_next()?,
+            target_typed_domain: decoder.decode_next()?,
+            originating_dataflow_instance: decoder.decode_next()?,
+        })
+    }
+}
+
+impl Ssz for CausalityTransaction {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn ssz_append(&self, buf: &mut Vec<u8>) {
+        let mut encoder = SszEncoder::new();
+        encoder.append(&self.id);
+        encoder.append(&self.name);
+        encoder.append(&self.domain_id);
+        encoder.append(&self.effects);
+        encoder.append(&self.intents);
+        encoder.append(&self.inputs);
+        encoder.append(&self.outputs);
+        encoder.append(&self.timestamp);
+        encoder.finish(buf);
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut builder = SszDecoderBuilder::new(bytes);
+        builder.register_type::<EntityId>()?;
+        builder.register_type::<String>()?;
+        builder.register_type::<DomainId>()?;
+        builder.register_type::<Vec<EffectId>>()?;
+        builder.register_type::<Vec<IntentId>>()?;
+        builder.register_type::<Vec<ResourceFlow>>()?;
+        builder.register_type::<Vec<ResourceFlow>>()?;
+        builder.register_type::<SystemTime>()?;
+        let mut decoder = builder.build()?;
+
+        Ok(Self {
+            id: decoder.decode_next()?,
+            name: decoder.decode_next()?,
+            domain_id: decoder.decode_next()?,
+            effects: decoder.decode_next()?,
+            intents: decoder.decode_next()?,
+            inputs: decoder.decode_next()?,
+            outputs: decoder.decode_next()?,
+            timestamp: decoder.decode_next()?,
+        })
+    }
+}
+
+impl Ssz for CausalityIntent {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn ssz_append(&self, buf: &mut Vec<u8>) {
+        let mut encoder = SszEncoder::new();
+        encoder.append(&self.id);
+        encoder.append(&self.name);
+        encoder.append(&self.domain_id);
+        encoder.append(&self.intent_type);
+        encoder.append(&self.required_inputs);
+        encoder.append(&self.expected_outputs);
+        encoder.append(&self.constraints);
+        encoder.append(&self.timestamp);
+        encoder.append(&self.is_satisfied);
+        encoder.append(&self.priority);
+        encoder.append(&self.target_typed_domain);
+        encoder.finish(buf);
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut builder = SszDecoderBuilder::new(bytes);
+        builder.register_type::<EntityId>()?;
+        builder.register_type::<String>()?;
+        builder.register_type::<DomainId>()?;
+        builder.register_type::<String>()?;
+        builder.register_type::<Vec<ResourceFlow>>()?;
+        builder.register_type::<Vec<ResourceFlow>>()?;
+        builder.register_type::<Vec<u8>>()?;
+        builder.register_type::<SystemTime>()?;
+        builder.register_type::<bool>()?;
+        builder.register_type::<u32>()?;
+        builder.register_type::<Option<TypedDomain>>()?;
+        let mut decoder = builder.build()?;
+
+        Ok(Self {
+            id: decoder.decode_next()?,
+            name: decoder.decode_next()?,
+            domain_id: decoder.decode_next()?,
+            intent_type: decoder.decode_next()?,
+            required_inputs: decoder.decode_next()?,
+            expected_outputs: decoder.decode_next()?,
+            constraints: decoder.decode_next()?,
+            timestamp: decoder.decode_next()?,
+            is_satisfied: decoder.decode_next()?,
+            priority: decoder.decode_next()?,
+            target_typed_domain: decoder.decode_next()?,
+        })
+    }
+}
+
+impl Ssz for CausalityHandler {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn ssz_append(&self, buf: &mut Vec<u8>) {
+        let mut encoder = SszEncoder::new();
+        encoder.append(&self.id);
+        encoder.append(&self.name);
+        encoder.append(&self.domain_id);
+        encoder.append(&self.effect_types);
+        encoder.append(&self.expression);
+        encoder.append(&self.timestamp);
+        encoder.finish(buf);
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut builder = SszDecoderBuilder::new(bytes);
+        builder.register_type::<EntityId>()?;
+        builder.register_type::<String>()?;
+        builder.register_type::<DomainId>()?;
+        builder.register_type::<Vec<String>>()?;
+        builder.register_type::<ExprId>()?;
+        builder.register_type::<SystemTime>()?;
+        let mut decoder = builder.build()?;
+
+        Ok(Self {
+            id: decoder.decode_next()?,
+            name: decoder.decode_next()?,
+            domain_id: decoder.decode_next()?,
+            effect_types: decoder.decode_next()?,
+            expression: decoder.decode_next()?,
+            timestamp: decoder.decode_next()?,
+        })
+    }
+}
+
+impl Ssz for CausalityNullifier {
+    fn is_ssz_fixed_len() -> bool {
+        true
+    }
+
+    fn ssz_fixed_len() -> usize {
+        32 + 32 + 12
+    }
+
+    fn ssz_append(&self, buf: &mut Vec<u8>) {
+        let mut encoder = SszEncoder::new();
+        encoder.append(&self.resource_id);
+        encoder.append(&self.nullifier_hash);
+        encoder.append(&self.timestamp);
+        encoder.finish(buf);
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut builder = SszDecoderBuilder::new(bytes);
+        builder.register_type::<EntityId>()?;
+        builder.register_type::<Hash>()?;
+        builder.register_type::<SystemTime>()?;
+        let mut decoder = builder.build()?;
+
+        Ok(Self {
+            resource_id: decoder.decode_next()?,
+            nullifier_hash: decoder.decode_next()?,
+            timestamp: decoder.decode_next()?,
+        })
+    }
+}
+
+impl Ssz for CausalityDomain {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn ssz_append(&self, buf: &mut Vec<u8>) {
+        let mut encoder = SszEncoder::new();
+        encoder.append(&self.id);
+        encoder.append(&self.name);
+        encoder.append(&self.domain_type);
+        encoder.append(&self.capabilities);
+        encoder.append(&self.config);
+        encoder.append(&self.timestamp);
+        encoder.finish(buf);
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut builder = SszDecoderBuilder::new(bytes);
+        builder.register_type::<EntityId>()?;
+        builder.register_type::<String>()?;
+        builder.register_type::<String>()?;
+        builder.register_type::<Vec<String>>()?;
+        builder.register_type::<Vec<u8>>()?;
+        builder.register_type::<SystemTime>()?;
+        let mut decoder = builder.build()?;
+
+        Ok(Self {
+            id: decoder.decode_next()?,
+            name: decoder.decode_next()?,
+            domain_type: decoder.decode_next()?,
+            capabilities: decoder.decode_next()?,
+            config: decoder.decode_next()?,
+            timestamp: decoder.decode_next()?,
+        })
+    }
+}
+
+impl Ssz for CausalityEventType {
+    fn is_ssz_fixed_len() -> bool {
+        true
+    }
+
+    fn ssz_fixed_len() -> usize {
+        1
+    }
+
+    fn ssz_append(&self, buf: &mut Vec<u8>) {
+        buf.push(match self {
+            Self::ResourceEvent => 0,
+            Self::EffectEvent => 1,
+            Self::TransactionEvent => 2,
+            Self::IntentEvent => 3,
+            Self::HandlerEvent => 4,
+            Self::DomainEvent => 5,
+            Self::NullifierEvent => 6,
+            Self::CrossDomainMessage => 7,
+            Self::TegStateChange => 8,
+        });
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self> {
+        match u8::from_ssz_bytes(bytes)? {
+            0 => Ok(Self::ResourceEvent),
+            1 => Ok(Self::EffectEvent),
+            2 => Ok(Self::TransactionEvent),
+            3 => Ok(Self::IntentEvent),
+            4 => Ok(Self::HandlerEvent),
+            5 => Ok(Self::DomainEvent),
+            6 => Ok(Self::NullifierEvent),
+            7 => Ok(Self::CrossDomainMessage),
+            8 => Ok(Self::TegStateChange),
+            other => Err(CausalityError::serialization_error(format!("Invalid SSZ event type {}", other))),
+        }
+    }
+}
+
+impl Ssz for CausalityEventData {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn ssz_append(&self, buf: &mut Vec<u8>) {
+        match self {
+            Self::Resource(resource) => {
+                buf.push(0);
+                resource.ssz_append(buf);
+            }
+            Self::Effect(effect) => {
+                buf.push(1);
+                effect.ssz_append(buf);
+            }
+            Self::Transaction(transaction) => {
+                buf.push(2);
+                transaction.ssz_append(buf);
+            }
+            Self::Intent(intent) => {
+                buf.push(3);
+                intent.ssz_append(buf);
+            }
+            Self::Handler(handler) => {
+                buf.push(4);
+                handler.ssz_append(buf);
+            }
+            Self::Domain(domain) => {
+                buf.push(5);
+                domain.ssz_append(buf);
+            }
+            Self::Nullifier(nullifier) => {
+                buf.push(6);
+                nullifier.ssz_append(buf);
+            }
+            Self::CrossDomainMessage { source_domain, target_domain, message_type, payload } => {
+                buf.push(7);
+                let mut encoder = SszEncoder::new();
+                encoder.append(source_domain);
+                encoder.append(target_domain);
+                encoder.append(message_type);
+                encoder.append(payload);
+                encoder.finish(buf);
+            }
+            Self::TegStateChange { previous_root, new_root, transition } => {
+                buf.push(8);
+                let mut encoder = SszEncoder::new();
+                encoder.append(previous_root);
+                encoder.append(new_root);
+                encoder.append(transition);
+                encoder.finish(buf);
+            }
+        }
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self> {
+        let (selector, value) = split_union(bytes)?;
+        match selector {
+            0 => CausalityResource::from_ssz_bytes(value).map(Self::Resource),
+            1 => CausalityEffect::from_ssz_bytes(value).map(Self::Effect),
+            2 => CausalityTransaction::from_ssz_bytes(value).map(Self::Transaction),
+            3 => CausalityIntent::from_ssz_bytes(value).map(Self::Intent),
+            4 => CausalityHandler::from_ssz_bytes(value).map(Self::Handler),
+            5 => CausalityDomain::from_ssz_bytes(value).map(Self::Domain),
+            6 => CausalityNullifier::from_ssz_bytes(value).map(Self::Nullifier),
+            7 => {
+                let mut builder = SszDecoderBuilder::new(value);
+                builder.register_type::<Hash>()?;
+                builder.register_type::<Hash>()?;
+                builder.register_type::<String>()?;
+                builder.register_type::<Vec<u8>>()?;
+                let mut decoder = builder.build()?;
+                Ok(Self::CrossDomainMessage {
+                    source_domain: decoder.decode_next()?,
+                    target_domain: decoder.decode_next()?,
+                    message_type: decoder.decode_next()?,
+                    payload: decoder.decode_next()?,
+                })
+            }
+            8 => {
+                let mut builder = SszDecoderBuilder::new(value);
+                builder.register_type::<Hash>()?;
+                builder.register_type::<Hash>()?;
+                builder.register_type::<Vec<u8>>()?;
+                let mut decoder = builder.build()?;
+                Ok(Self::TegStateChange {
+                    previous_root: decoder.decode_next()?,
+                    new_root: decoder.decode_next()?,
+                    transition: decoder.decode_next()?,
+                })
+            }
+            selector => Err(invalid_selector("CausalityEventData", selector)),
+        }
+    }
+}
+
+impl Ssz for CausalityEvent {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn ssz_append(&self, buf: &mut Vec<u8>) {
+        let mut encoder = SszEncoder::new();
+        encoder.append(&self.id);
+        encoder.append(&self.chain_id);
+        encoder.append(&self.block_number);
+        encoder.append(&self.tx_hash);
+        encoder.append(&self.event_type);
+        encoder.append(&self.timestamp);
+        encoder.append(&self.data);
+        encoder.finish(buf);
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut builder = SszDecoderBuilder::new(bytes);
+        builder.register_type::<String>()?;
+        builder.register_type::<ChainId>()?;
+        builder.register_type::<u64>()?;
+        builder.register_type::<String>()?;
+        builder.register_type::<CausalityEventType>()?;
+        builder.register_type::<SystemTime>()?;
+        builder.register_type::<CausalityEventData>()?;
+        let mut decoder = builder.build()?;
+
+        Ok(Self {
+            id: decoder.decode_next()?,
+            chain_id: decoder.decode_next()?,
+            block_number: decoder.decode_next()?,
+            tx_hash: decoder.decode_next()?,
+            event_type: decoder.decode_next()?,
+            timestamp: decoder.decode_next()?,
+            data: decoder.decode_next()?,
+        })
+    }
+}
+
+/// Content addressing hashes the SSZ encoding with the entity's own `id`
+/// replaced by [`EntityId::null`], so an entity's content ID does not depend
+/// on the ID it currently carries.
+macro_rules! impl_content_addressable {
+    ($($ty:ty),*) => {
+        $(
+            impl ContentAddressable for $ty {
+                fn content_id(&self) -> EntityId {
+                    let unaddressed = Self { id: EntityId::null(), ..self.clone() };
+                    Self::compute_content_address(&unaddressed.as_ssz_bytes())
+                }
+
+                fn to_ssz_bytes(&self) -> Result<Vec<u8>> {
+                    Ok(self.as_ssz_bytes())
+                }
+            }
+        )*
+    };
+}
+
+impl_content_addressable!(
+    CausalityResource, CausalityEffect, CausalityTransaction, CausalityIntent, CausalityHandler, CausalityDomain
+);
+
+impl ContentAddressable for CausalityEvent {
+    fn content_id(&self) -> EntityId {
+        Self::compute_content_address(&self.as_ssz_bytes())
+    }
+
+    fn to_ssz_bytes(&self) -> Result<Vec<u8>> {
+        Ok(self.as_ssz_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::hash_to_hex;
+
+    fn at(seconds: u64, nanos: u32) -> SystemTime {
+        UNIX_EPOCH + Duration::new(seconds, nanos)
+    }
+
+    fn resource() -> CausalityResource {
+        CausalityResource {
+            id: EntityId::new([1; 32]),
+            name: "gold".to_string(),
+            domain_id: DomainId::new([2; 32]),
+            resource_type: "token".to_string(),
+            quantity: 1000,
+            timestamp: at(1_700_000_000, 5),
+        }
+    }
+
+    fn flow(from: &str, to: &str) -> ResourceFlow {
+        ResourceFlow {
+            from_event: from.to_string(),
+            to_event: to.to_string(),
+            resource_type: "token".to_string(),
+            resource_data: b"250".to_vec(),
+            timestamp: at(1_700_000_001, 0),
+        }
+    }
+
+    fn effect() -> CausalityEffect {
+        CausalityEffect {
+            id: EntityId::new([3; 32]),
+            name: "swap".to_string(),
+            domain_id: DomainId::new([2; 32]),
+            effect_type: "transfer".to_string(),
+            inputs: vec![flow("a", "b"), flow("c", "b")],
+            outputs: vec![flow("b", "d")],
+            expression: Some(ExprId::new([4; 32])),
+            timestamp: at(1_700_000_002, 999_999_999),
+            scoped_by: HandlerId::new([5; 32]),
+            intent_id: None,
+            source_typed_domain: TypedDomain::default(),
+            target_typed_domain: TypedDomain::ServiceDomain {
+                domain_id: DomainId::new([6; 32]),
+                service_type: "http".to_string(),
+                endpoint: Some("https://example.com".to_string()),
+            },
+            originating_dataflow_instance: Some(ResourceId::new([7; 32])),
+        }
+    }
+
+    fn transaction() -> CausalityTransaction {
+        CausalityTransaction {
+            id: EntityId::new([8; 32]),
+            name: "batch".to_string(),
+            domain_id: DomainId::new([2; 32]),
+            effects: vec![EffectId::new([3; 32])],
+            intents: vec![IntentId::new([9; 32]), IntentId::new([10; 32])],
+            inputs: vec![flow("a", "b")],
+            outputs: Vec::new(),
+            timestamp: at(1_700_000_003, 0),
+        }
+    }
+
+    fn intent() -> CausalityIntent {
+        CausalityIntent {
+            id: EntityId::new([9; 32]),
+            name: "buy".to_string(),
+            domain_id: DomainId::new([2; 32]),
+            intent_type: "swap".to_string(),
+            required_inputs: vec![flow("a", "b")],
+            expected_outputs: vec![flow("b", "c")],
+            constraints: vec![0xde, 0xad],
+            timestamp: at(1_700_000_004, 0),
+            is_satisfied: true,
+            priority: 7,
+            target_typed_domain: Some(TypedDomain::VerifiableDomain {
+                domain_id: DomainId::new([6; 32]),
+                capabilities: vec!["zk".to_string(), "fast".to_string()],
+            }),
+        }
+    }
+
+    fn event(data: CausalityEventData) -> CausalityEvent {
+        CausalityEvent {
+            id: "evt-1".to_string(),
+            chain_id: ChainId("ethereum".to_string()),
+            block_number: 42,
+            tx_hash: "0xabc".to_string(),
+            event_type: data.event_type(),
+            timestamp: at(1_700_000_000, 0),
+            data,
+        }
+    }
+
+    fn assert_round_trip<T: Ssz>(value: &T) {
+        let bytes = value.as_ssz_bytes();
+        let decoded = T::from_ssz_bytes(&bytes).unwrap();
+        assert_eq!(decoded.as_ssz_bytes(), bytes);
+    }
+
+    #[test]
+    fn test_resource_golden_vector() {
+        let bytes = resource().as_ssz_bytes();
+        assert_eq!(hex::encode(&bytes), concat!(
+            "0101010101010101010101010101010101010101010101010101010101010101",
+            "5c000000",
+            "0202020202020202020202020202020202020202020202020202020202020202",
+            "60000000",
+            "e803000000000000",
+            "00f1536500000000", "05000000",
+            "676f6c64",
+            "746f6b656e",
+        ));
+
+        let decoded = CausalityResource::from_ssz_bytes(&bytes).unwrap();
+        assert_eq!(decoded.id, EntityId::new([1; 32]));
+        assert_eq!(decoded.name, "gold");
+        assert_eq!(decoded.resource_type, "token");
+        assert_eq!(decoded.quantity, 1000);
+        assert_eq!(decoded.timestamp, at(1_700_000_000, 5));
+    }
+
+    #[test]
+    fn test_event_golden_vector() {
+        let event = event(CausalityEventData::CrossDomainMessage {
+            source_domain: [0xaa; 32],
+            target_domain: [0xbb; 32],
+            message_type: "bridge".to_string(),
+            payload: vec![1, 2, 3],
+        });
+
+        assert_eq!(hex::encode(event.to_bytes().unwrap()), concat!(
+            "25000000", "2a000000", "2a00000000000000", "32000000", "07",
+            "00f1536500000000", "00000000", "37000000",
+            "6576742d31",
+            "657468657265756d",
+            "3078616263",
+            "07",
+            "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+            "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb",
+            "48000000", "4e000000",
+            "627269646765",
+            "010203",
+        ));
+    }
+
+    #[test]
+    fn test_content_id_golden_vector() {
+        let resource = resource();
+        assert_eq!(
+            hash_to_hex(&resource.content_id().inner()),
+            "4d1e30895b7e155f8fbb76b3570d9a8726a9ed171200c402fc5d6a9bb7c7ceef"
+        );
+
+        // The content ID ignores the ID the entity currently carries
+        let readdressed = CausalityResource { id: resource.content_id(), ..resource.clone() };
+        assert_eq!(readdressed.content_id(), resource.content_id());
+
+        let changed = CausalityResource { quantity: 1001, ..resource.clone() };
+        assert_ne!(changed.content_id(), resource.content_id());
+    }
+
+    #[test]
+    fn test_entities_round_trip() {
+        assert_round_trip(&resource());
+        assert_round_trip(&effect());
+        assert_round_trip(&transaction());
+        assert_round_trip(&intent());
+
+        assert_round_trip(&event(CausalityEventData::Resource(resource())));
+        assert_round_trip(&event(CausalityEventData::Effect(effect())));
+        assert_round_trip(&event(CausalityEventData::Transaction(transaction())));
+        assert_round_trip(&event(CausalityEventData::Intent(intent())));
+        assert_round_trip(&event(CausalityEventData::Nullifier(CausalityNullifier {
+            resource_id: EntityId::new([1; 32]),
+            nullifier_hash: [0xcc; 32],
+            timestamp: at(1_700_000_005, 0),
+        })));
+        assert_round_trip(&event(CausalityEventData::TegStateChange {
+            previous_root: [0; 32],
+            new_root: [1; 32],
+            transition: Vec::new(),
+        }));
+
+        let decoded = CausalityEvent::from_bytes(&event(CausalityEventData::Effect(effect())).to_bytes().unwrap()).unwrap();
+        match decoded.data {
+            CausalityEventData::Effect(decoded) => {
+                assert_eq!(decoded.inputs.len(), 2);
+                assert_eq!(decoded.inputs[1].from_event, "c");
+                assert_eq!(decoded.intent_id, None);
+                assert_eq!(decoded.timestamp, at(1_700_000_002, 999_999_999));
+                assert_eq!(decoded.target_typed_domain, effect().target_typed_domain);
+            }
+            other => panic!("Unexpected event data {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decode_rejects_malformed_input() {
+        let bytes = resource().as_ssz_bytes();
+        assert!(CausalityResource::from_ssz_bytes(&bytes[..40]).is_err());
+
+        let mut bad_offset = bytes.clone();
+        bad_offset[32] = 0x5d;
+        assert!(CausalityResource::from_ssz_bytes(&bad_offset).is_err());
+
+        let mut bad_utf8 = bytes.clone();
+        let last = bad_utf8.len() - 1;
+        bad_utf8[last] = 0xff;
+        assert!(CausalityResource::from_ssz_bytes(&bad_utf8).is_err());
+
+        assert!(Option::<u64>::from_ssz_bytes(&[2]).is_err());
+        assert!(Option::<u64>::from_ssz_bytes(&[0, 0]).is_err());
+        assert!(CausalityEventData::from_ssz_bytes(&[9]).is_err());
+        assert!(bool::from_ssz_bytes(&[2]).is_err());
+        assert!(SystemTime::from_ssz_bytes(&[0, 0, 0, 0, 0, 0, 0, 0, 0, 0xca, 0x9a, 0x3b]).is_err());
+    }
+}
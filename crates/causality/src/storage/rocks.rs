@@ -0,0 +1,548 @@
+/// RocksDB causality storage backend
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use rocksdb::{ColumnFamily, Direction, IteratorMode, Options, WriteBatch, DB};
+
+use indexer_core::types::ChainId;
+
+use crate::causality::{event_hash, CausalityGraph, CausalityRelation};
+use crate::error::{CausalityError, Result};
+use crate::types::{
+    hash_from_hex, CausalityEvent, CausalityIndex, CausalityResource, CrossChainReference, DomainId,
+    EntityId, Hash, SmtRootVersion,
+};
+
+use super::{build_graph, carried_entity, latest_resource, CausalityStorageBackend};
+
+/// Events by ID
+const CF_EVENTS: &str = "causality-events";
+/// `chain \0 block event_id` keys ordering the events of each chain by block
+const CF_CHAIN_EVENTS: &str = "causality-chain-events";
+/// `entity block event_id` keys ordering the events carrying each entity
+const CF_ENTITY_EVENTS: &str = "causality-entity-events";
+/// `domain entity event_id` keys listing the entities of each domain
+const CF_DOMAIN_ENTITIES: &str = "causality-domain-entities";
+/// Resources stored outside of events, by resource ID
+const CF_RESOURCES: &str = "causality-resources";
+/// `from to relation_type` keys holding relations
+const CF_RELATIONS: &str = "causality-relations";
+/// Causality index and root history
+const CF_META: &str = "causality-meta";
+/// `source \0 target \0 ref_type` keys holding cross-chain references
+const CF_REFERENCES: &str = "causality-cross-chain-references";
+/// Big-endian sequence number keys holding event IDs in processing order
+const CF_EVENT_ORDER: &str = "causality-event-order";
+/// Sequence number of each event in the processing order, by event ID
+const CF_EVENT_SEQUENCES: &str = "causality-event-sequences";
+
+/// Column families used by the causality storage
+const COLUMN_FAMILIES: [&str; 10] = [
+    CF_EVENTS,
+    CF_CHAIN_EVENTS,
+    CF_ENTITY_EVENTS,
+    CF_DOMAIN_ENTITIES,
+    CF_RESOURCES,
+    CF_RELATIONS,
+    CF_META,
+    CF_REFERENCES,
+    CF_EVENT_ORDER,
+    CF_EVENT_SEQUENCES,
+];
+
+/// Key and value of a column family entry
+type Entry = (Box<[u8]>, Box<[u8]>);
+
+/// Key of the causality index in the meta column family
+const INDEX_KEY: &[u8] = b"index";
+/// Prefix of root history entries in the meta column family
+const ROOT_VERSION_PREFIX: &[u8] = b"root:";
+/// Key of the last pruned root version in the meta column family
+const ROOT_BASE_KEY: &[u8] = b"root-base";
+
+/// RocksDB causality storage backend
+///
+/// Secondary indexes are kept in their own column families and updated in
+/// the same write batch as the events they point to.
+#[derive(Clone)]
+pub struct RocksCausalityStorage {
+    db: Arc<DB>,
+    /// Serializes read-modify-write updates of the indexes and root history
+    write_lock: Arc<Mutex<()>>,
+}
+
+impl std::fmt::Debug for RocksCausalityStorage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RocksCausalityStorage")
+            .field("path", &self.db.path())
+            .finish()
+    }
+}
+
+impl RocksCausalityStorage {
+    /// Open or create a RocksDB causality storage at the given path
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+
+        let db = DB::open_cf(&opts, path.as_ref(), COLUMN_FAMILIES)
+            .map_err(|e| CausalityError::storage_error(format!("Failed to open RocksDB: {}", e)))?;
+
+        Ok(Self {
+            db: Arc::new(db),
+            write_lock: Arc::new(Mutex::new(())),
+        })
+    }
+
+    fn cf(&self, name: &str) -> Result<&ColumnFamily> {
+        self.db.cf_handle(name)
+            .ok_or_else(|| CausalityError::storage_error(format!("Column family '{}' not found", name)))
+    }
+
+    /// Keys and values under a prefix, in key order
+    fn scan_prefix(&self, cf_name: &str, prefix: &[u8]) -> Result<Vec<Entry>> {
+        let cf = self.cf(cf_name)?;
+        let mut entries = Vec::new();
+
+        for item in self.db.iterator_cf(cf, IteratorMode::From(prefix, Direction::Forward)) {
+            let (key, value) = item.map_err(db_error)?;
+            if !key.starts_with(prefix) {
+                break;
+            }
+            entries.push((key, value));
+        }
+
+        Ok(entries)
+    }
+
+    /// Load events from index keys ending with the event ID
+    fn events_for_keys(&self, keys: &[Box<[u8]>], id_offset: usize) -> Result<Vec<CausalityEvent>> {
+        let mut events = Vec::with_capacity(keys.len());
+        for key in keys {
+            if let Some(event) = self.read_event(&key[id_offset..])? {
+                events.push(event);
+            }
+        }
+        Ok(events)
+    }
+
+    fn read_event(&self, event_id: &[u8]) -> Result<Option<CausalityEvent>> {
+        self.db.get_cf(self.cf(CF_EVENTS)?, event_id)
+            .map_err(db_error)?
+            .map(|bytes| decode(&bytes))
+            .transpose()
+    }
+
+    /// Queue the removal of an event and its index entries
+    fn remove_event_in(&self, batch: &mut WriteBatch, event: &CausalityEvent) -> Result<()> {
+        batch.delete_cf(self.cf(CF_EVENTS)?, event.id.as_bytes());
+        if let Some(sequence) = self.db.get_cf(self.cf(CF_EVENT_SEQUENCES)?, event.id.as_bytes()).map_err(db_error)? {
+            batch.delete_cf(self.cf(CF_EVENT_ORDER)?, sequence);
+            batch.delete_cf(self.cf(CF_EVENT_SEQUENCES)?, event.id.as_bytes());
+        }
+        batch.delete_cf(self.cf(CF_CHAIN_EVENTS)?, chain_event_key(&event.chain_id, event.block_number, &event.id));
+
+        if let Some(entity) = carried_entity(&event.data) {
+            batch.delete_cf(self.cf(CF_ENTITY_EVENTS)?, entity_event_key(&entity.id, event.block_number, &event.id));
+            if let Some(domain_id) = entity.domain_id {
+                batch.delete_cf(self.cf(CF_DOMAIN_ENTITIES)?, domain_entity_key(&domain_id, &entity.id, &event.id));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Queue the removal of the relations from or to any of the given entities
+    fn remove_relations_in(&self, batch: &mut WriteBatch, entities: &HashSet<Hash>) -> Result<()> {
+        let cf = self.cf(CF_RELATIONS)?;
+
+        // Relations are keyed by source, so incoming ones need a full scan
+        for item in self.db.iterator_cf(cf, IteratorMode::Start) {
+            let (key, _) = item.map_err(db_error)?;
+            let touches = |range: std::ops::Range<usize>| {
+                key.get(range)
+                    .and_then(|bytes| <Hash>::try_from(bytes).ok())
+                    .is_some_and(|entity| entities.contains(&entity))
+            };
+            if touches(0..32) || touches(32..64) {
+                batch.delete_cf(cf, &key);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Queue the removal of the cross-chain references from or to any of the given events
+    fn remove_references_in(&self, batch: &mut WriteBatch, event_ids: &HashSet<&str>) -> Result<()> {
+        let cf = self.cf(CF_REFERENCES)?;
+
+        for (key, value) in self.scan_prefix(CF_REFERENCES, &[])? {
+            let reference: CrossChainReference = decode(&value)?;
+            if event_ids.contains(reference.source_event.as_str()) || event_ids.contains(reference.target_event.as_str()) {
+                batch.delete_cf(cf, key);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Sequence number following the last one in the processing order
+    fn next_sequence(&self) -> Result<u64> {
+        match self.db.iterator_cf(self.cf(CF_EVENT_ORDER)?, IteratorMode::End).next() {
+            Some(item) => {
+                let (key, _) = item.map_err(db_error)?;
+                Ok(block_at(&key, 0) + 1)
+            }
+            None => Ok(0),
+        }
+    }
+
+    fn root_versions(&self) -> Result<Vec<(Box<[u8]>, SmtRootVersion)>> {
+        self.scan_prefix(CF_META, ROOT_VERSION_PREFIX)?
+            .into_iter()
+            .map(|(key, value)| Ok((key, decode(&value)?)))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl CausalityStorageBackend for RocksCausalityStorage {
+    async fn store_event(&self, event: &CausalityEvent) -> Result<()> {
+        let _guard = self.write_lock.lock();
+        let mut batch = WriteBatch::default();
+
+        // Re-storing an event replaces its index entries
+        if let Some(previous) = self.read_event(event.id.as_bytes())? {
+            self.remove_event_in(&mut batch, &previous)?;
+        }
+
+        let sequence = self.next_sequence()?.to_be_bytes();
+        batch.put_cf(self.cf(CF_EVENTS)?, event.id.as_bytes(), encode(event)?);
+        batch.put_cf(self.cf(CF_EVENT_ORDER)?, sequence, event.id.as_bytes());
+        batch.put_cf(self.cf(CF_EVENT_SEQUENCES)?, event.id.as_bytes(), sequence);
+        batch.put_cf(self.cf(CF_CHAIN_EVENTS)?, chain_event_key(&event.chain_id, event.block_number, &event.id), b"");
+
+        if let Some(entity) = carried_entity(&event.data) {
+            batch.put_cf(self.cf(CF_ENTITY_EVENTS)?, entity_event_key(&entity.id, event.block_number, &event.id), b"");
+            if let Some(domain_id) = entity.domain_id {
+                batch.put_cf(self.cf(CF_DOMAIN_ENTITIES)?, domain_entity_key(&domain_id, &entity.id, &event.id), b"");
+            }
+        }
+
+        self.db.write(batch).map_err(db_error)
+    }
+    
+    async fn get_event(&self, event_id: &str) -> Result<Option<CausalityEvent>> {
+        self.read_event(event_id.as_bytes())
+    }
+    
+    async fn store_resource(&self, resource: &CausalityResource) -> Result<()> {
+        let _guard = self.write_lock.lock();
+        let id = resource.id.inner();
+        let mut batch = WriteBatch::default();
+
+        // Resources stored directly use an empty event ID in the domain index
+        if let Some(bytes) = self.db.get_cf(self.cf(CF_RESOURCES)?, id).map_err(db_error)? {
+            let previous: CausalityResource = decode(&bytes)?;
+            batch.delete_cf(self.cf(CF_DOMAIN_ENTITIES)?, domain_entity_key(&previous.domain_id, &id, ""));
+        }
+
+        batch.put_cf(self.cf(CF_RESOURCES)?, id, encode(resource)?);
+        batch.put_cf(self.cf(CF_DOMAIN_ENTITIES)?, domain_entity_key(&resource.domain_id, &id, ""), b"");
+
+        self.db.write(batch).map_err(db_error)
+    }
+    
+    async fn get_resource(&self, resource_id: &str) -> Result<Option<CausalityResource>> {
+        let id = hash_from_hex(resource_id)?;
+
+        let stored = self.db.get_cf(self.cf(CF_RESOURCES)?, id)
+            .map_err(db_error)?
+            .map(|bytes| decode(&bytes))
+            .transpose()?;
+
+        let events = self.get_entity_events(&EntityId::new(id)).await?;
+        Ok(latest_resource(stored, events))
+    }
+    
+    async fn store_index(&self, index: &CausalityIndex) -> Result<()> {
+        self.db.put_cf(self.cf(CF_META)?, INDEX_KEY, encode(index)?).map_err(db_error)
+    }
+    
+    async fn get_index(&self) -> Result<Option<CausalityIndex>> {
+        self.db.get_cf(self.cf(CF_META)?, INDEX_KEY)
+            .map_err(db_error)?
+            .map(|bytes| decode(&bytes))
+            .transpose()
+    }
+    
+    async fn get_chain_events(&self, chain_id: &ChainId) -> Result<Vec<CausalityEvent>> {
+        let prefix = chain_prefix(chain_id);
+        let keys: Vec<Box<[u8]>> = self.scan_prefix(CF_CHAIN_EVENTS, &prefix)?
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect();
+
+        self.events_for_keys(&keys, prefix.len() + 8)
+    }
+    
+    async fn get_events_in_range(&self, chain_id: &ChainId, start_block: u64, end_block: u64) -> Result<Vec<CausalityEvent>> {
+        let prefix = chain_prefix(chain_id);
+        let start = [prefix.as_slice(), &start_block.to_be_bytes()].concat();
+        let cf = self.cf(CF_CHAIN_EVENTS)?;
+
+        let mut keys = Vec::new();
+        for item in self.db.iterator_cf(cf, IteratorMode::From(start.as_slice(), Direction::Forward)) {
+            let (key, _) = item.map_err(db_error)?;
+            if !key.starts_with(&prefix) || block_at(&key, prefix.len()) > end_block {
+                break;
+            }
+            keys.push(key);
+        }
+
+        self.events_for_keys(&keys, prefix.len() + 8)
+    }
+
+    async fn remove_events_from_block(&self, chain_id: &ChainId, from_block: u64) -> Result<Vec<CausalityEvent>> {
+        let _guard = self.write_lock.lock();
+        let prefix = chain_prefix(chain_id);
+
+        let keys: Vec<Box<[u8]>> = self.scan_prefix(CF_CHAIN_EVENTS, &prefix)?
+            .into_iter()
+            .map(|(key, _)| key)
+            .filter(|key| block_at(key, prefix.len()) >= from_block)
+            .collect();
+        let removed = self.events_for_keys(&keys, prefix.len() + 8)?;
+
+        let mut batch = WriteBatch::default();
+        for event in &removed {
+            self.remove_event_in(&mut batch, event)?;
+        }
+        let hashes: HashSet<Hash> = removed.iter().map(|event| event_hash(&event.id)).collect();
+        self.remove_relations_in(&mut batch, &hashes)?;
+        let ids: HashSet<&str> = removed.iter().map(|event| event.id.as_str()).collect();
+        self.remove_references_in(&mut batch, &ids)?;
+        self.db.write(batch).map_err(db_error)?;
+
+        Ok(removed)
+    }
+
+    async fn store_root_version(&self, version: &SmtRootVersion) -> Result<()> {
+        let _guard = self.write_lock.lock();
+        let cf = self.cf(CF_META)?;
+
+        // Append after the last version; pruning removes versions from the front
+        let last = root_version_key(u64::MAX);
+        let position = match self.db.iterator_cf(cf, IteratorMode::From(last.as_slice(), Direction::Reverse)).next() {
+            Some(item) => {
+                let (key, _) = item.map_err(db_error)?;
+                if key.starts_with(ROOT_VERSION_PREFIX) {
+                    block_at(&key, ROOT_VERSION_PREFIX.len()) + 1
+                } else {
+                    0
+                }
+            }
+            None => 0,
+        };
+        self.db.put_cf(cf, root_version_key(position), encode(version)?).map_err(db_error)
+    }
+
+    async fn get_root_versions(&self) -> Result<Vec<SmtRootVersion>> {
+        Ok(self.root_versions()?.into_iter().map(|(_, version)| version).collect())
+    }
+
+    async fn truncate_root_versions(&self, len: usize) -> Result<()> {
+        let _guard = self.write_lock.lock();
+        let cf = self.cf(CF_META)?;
+
+        let mut batch = WriteBatch::default();
+        for (key, _) in self.root_versions()?.into_iter().skip(len) {
+            batch.delete_cf(cf, key);
+        }
+        self.db.write(batch).map_err(db_error)
+    }
+
+    async fn prune_root_versions(&self, count: usize) -> Result<()> {
+        let _guard = self.write_lock.lock();
+        let cf = self.cf(CF_META)?;
+
+        let mut batch = WriteBatch::default();
+        let mut base = None;
+        for (key, version) in self.root_versions()?.into_iter().take(count) {
+            batch.delete_cf(cf, key);
+            base = Some(version);
+        }
+        if let Some(base) = base {
+            batch.put_cf(cf, ROOT_BASE_KEY, encode(&base)?);
+        }
+        self.db.write(batch).map_err(db_error)
+    }
+
+    async fn get_root_base(&self) -> Result<Option<SmtRootVersion>> {
+        self.db.get_cf(self.cf(CF_META)?, ROOT_BASE_KEY)
+            .map_err(db_error)?
+            .map(|bytes| decode(&bytes))
+            .transpose()
+    }
+
+    async fn get_entity_events(&self, entity_id: &EntityId) -> Result<Vec<CausalityEvent>> {
+        let keys: Vec<Box<[u8]>> = self.scan_prefix(CF_ENTITY_EVENTS, &entity_id.inner())?
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect();
+
+        self.events_for_keys(&keys, 32 + 8)
+    }
+
+    async fn get_domain_entities(&self, domain_id: &DomainId) -> Result<Vec<EntityId>> {
+        let mut entities: Vec<EntityId> = Vec::new();
+
+        // Keys are ordered by entity, so duplicates are adjacent
+        for (key, _) in self.scan_prefix(CF_DOMAIN_ENTITIES, &domain_id.inner())? {
+            let mut entity = [0u8; 32];
+            entity.copy_from_slice(&key[32..64]);
+            if entities.last().map_or(true, |last| last.inner() != entity) {
+                entities.push(EntityId::new(entity));
+            }
+        }
+
+        Ok(entities)
+    }
+
+    async fn store_relation(&self, relation: &CausalityRelation) -> Result<()> {
+        self.db.put_cf(self.cf(CF_RELATIONS)?, relation_key(relation)?, encode(relation)?).map_err(db_error)
+    }
+
+    async fn get_relations(&self, from_entity: &Hash) -> Result<Vec<CausalityRelation>> {
+        self.scan_prefix(CF_RELATIONS, from_entity)?
+            .into_iter()
+            .map(|(_, value)| decode(&value))
+            .collect()
+    }
+
+    async fn get_events_in_processing_order(&self) -> Result<Vec<CausalityEvent>> {
+        let mut events = Vec::new();
+        for (_, event_id) in self.scan_prefix(CF_EVENT_ORDER, &[])? {
+            if let Some(event) = self.read_event(&event_id)? {
+                events.push(event);
+            }
+        }
+        Ok(events)
+    }
+
+    async fn load_graph(&self) -> Result<CausalityGraph> {
+        let events = self.get_events_in_processing_order().await?;
+        let resources = self.scan_prefix(CF_RESOURCES, &[])?
+            .into_iter()
+            .map(|(_, value)| decode(&value))
+            .collect::<Result<Vec<CausalityResource>>>()?;
+        let relations = self.scan_prefix(CF_RELATIONS, &[])?
+            .into_iter()
+            .map(|(_, value)| decode(&value))
+            .collect::<Result<Vec<CausalityRelation>>>()?;
+
+        build_graph(events, resources, relations)
+    }
+
+    async fn store_cross_chain_reference(&self, reference: &CrossChainReference) -> Result<()> {
+        let _guard = self.write_lock.lock();
+        let cf = self.cf(CF_REFERENCES)?;
+        let key = reference_key(reference);
+
+        if self.db.get_cf(cf, &key).map_err(db_error)?.is_none() {
+            self.db.put_cf(cf, key, encode(reference)?).map_err(db_error)?;
+        }
+        Ok(())
+    }
+
+    async fn get_cross_chain_references(&self) -> Result<Vec<CrossChainReference>> {
+        self.scan_prefix(CF_REFERENCES, &[])?
+            .into_iter()
+            .map(|(_, value)| decode(&value))
+            .collect()
+    }
+}
+
+/// Prefix of the chain event keys of a chain
+fn chain_prefix(chain_id: &ChainId) -> Vec<u8> {
+    [chain_id.0.as_bytes(), &[0u8]].concat()
+}
+
+fn chain_event_key(chain_id: &ChainId, block_number: u64, event_id: &str) -> Vec<u8> {
+    [chain_prefix(chain_id).as_slice(), &block_number.to_be_bytes(), event_id.as_bytes()].concat()
+}
+
+fn entity_event_key(entity_id: &Hash, block_number: u64, event_id: &str) -> Vec<u8> {
+    [entity_id.as_slice(), &block_number.to_be_bytes(), event_id.as_bytes()].concat()
+}
+
+fn domain_entity_key(domain_id: &DomainId, entity_id: &Hash, event_id: &str) -> Vec<u8> {
+    [domain_id.inner().as_slice(), entity_id, event_id.as_bytes()].concat()
+}
+
+fn relation_key(relation: &CausalityRelation) -> Result<Vec<u8>> {
+    let relation_type = encode(&relation.relation_type)?;
+    Ok([relation.from_entity.as_slice(), &relation.to_entity, &relation_type].concat())
+}
+
+fn reference_key(reference: &CrossChainReference) -> Vec<u8> {
+    let (source, target, ref_type) = super::reference_key(reference);
+    [source.as_bytes(), &[0u8], target.as_bytes(), &[0u8], ref_type.as_bytes()].concat()
+}
+
+fn root_version_key(position: u64) -> Vec<u8> {
+    [ROOT_VERSION_PREFIX, &position.to_be_bytes()].concat()
+}
+
+/// Read the big-endian block number or sequence following a key prefix
+fn block_at(key: &[u8], offset: usize) -> u64 {
+    let mut block = [0u8; 8];
+    block.copy_from_slice(&key[offset..offset + 8]);
+    u64::from_be_bytes(block)
+}
+
+fn encode<T: serde::Serialize>(value: &T) -> Result<Vec<u8>> {
+    serde_json::to_vec(value).map_err(CausalityError::from)
+}
+
+fn decode<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> Result<T> {
+    serde_json::from_slice(bytes).map_err(CausalityError::from)
+}
+
+/// Map a RocksDB error into a storage error
+fn db_error(e: rocksdb::Error) -> CausalityError {
+    CausalityError::storage_error(format!("RocksDB error: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rocks_causality_storage() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = RocksCausalityStorage::open(dir.path()).unwrap();
+
+        super::super::tests::run_storage_suite(&storage).await;
+    }
+
+    #[tokio::test]
+    async fn test_rocks_causality_storage_persists_across_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let event = super::super::tests::resource_event("event-1", "chain-a", 10, [1u8; 32], [9u8; 32]);
+
+        {
+            let storage = RocksCausalityStorage::open(dir.path()).unwrap();
+            storage.store_event(&event).await.unwrap();
+        }
+
+        let storage = RocksCausalityStorage::open(dir.path()).unwrap();
+        let graph = storage.load_graph().await.unwrap();
+        assert!(graph.get_event("event-1").is_some());
+        assert_eq!(storage.get_entity_events(&EntityId::new([1u8; 32])).await.unwrap().len(), 1);
+    }
+}
@@ -6,6 +6,7 @@ use indexer_core::event::Event;
 use indexer_core::types::ChainId;
 
 use crate::error::{CausalityError, Result};
+use crate::ssz::Ssz;
 
 /// Hash type - 32 bytes for compatibility with both SHA256 and Blake3
 pub type Hash = [u8; 32];
@@ -213,6 +214,74 @@ impl ResourceId {
     }
 }
 
+impl ValueExprId {
+    /// Create a new ValueExprId from a hash
+    pub fn new(hash: Hash) -> Self {
+        Self(hash)
+    }
+    
+    /// Create a null ValueExprId (all zeros)
+    pub fn null() -> Self {
+        Self([0u8; 32])
+    }
+    
+    /// Get the inner hash value
+    pub fn inner(&self) -> Hash {
+        self.0
+    }
+}
+
+impl IntentId {
+    /// Create a new IntentId from a hash
+    pub fn new(hash: Hash) -> Self {
+        Self(hash)
+    }
+    
+    /// Create a null IntentId (all zeros)
+    pub fn null() -> Self {
+        Self([0u8; 32])
+    }
+    
+    /// Get the inner hash value
+    pub fn inner(&self) -> Hash {
+        self.0
+    }
+}
+
+impl TransactionId {
+    /// Create a new TransactionId from a hash
+    pub fn new(hash: Hash) -> Self {
+        Self(hash)
+    }
+    
+    /// Create a null TransactionId (all zeros)
+    pub fn null() -> Self {
+        Self([0u8; 32])
+    }
+    
+    /// Get the inner hash value
+    pub fn inner(&self) -> Hash {
+        self.0
+    }
+}
+
+impl EffectId {
+    /// Create a new EffectId from a hash
+    pub fn new(hash: Hash) -> Self {
+        Self(hash)
+    }
+    
+    /// Create a null EffectId (all zeros)
+    pub fn null() -> Self {
+        Self([0u8; 32])
+    }
+    
+    /// Get the inner hash value
+    pub fn inner(&self) -> Hash {
+        self.0
+    }
+}
+
 /// Helper to create empty hash
 pub fn empty_hash() -> Hash {
     [0u8; 32]
@@ -241,43 +310,224 @@ pub struct SmtChildren {
     pub right: Hash,
 }
 
+/// Domain separator for SMT leaf hashes
+pub const SMT_LEAF_DOMAIN: &[u8] = b"almanac-smt-leaf";
+
+/// Version of the compact SMT proof encoding
+pub const SMT_PROOF_VERSION: u8 = 1;
+
+/// Get the bit at `position` of a key, most significant bit first
+pub fn key_bit(key: &Hash, position: usize) -> bool {
+    let byte_index = position / 8;
+    let bit_index = position % 8;
+    if byte_index >= key.len() {
+        return false;
+    }
+    (key[byte_index] >> (7 - bit_index)) & 1 == 1
+}
+
+/// Leaf met at the end of a non-inclusion proof path
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SmtProofLeaf {
+    /// Key the leaf was inserted under
+    pub key: Hash,
+    /// Hash of the leaf value
+    pub value_hash: Hash,
+}
+
 /// SMT proof for verifying inclusion/exclusion
-#[derive(Debug, Clone)]
+///
+/// Leaves commit to both their key and value, and the path directions must
+/// match the bits of the proven key, so a proof is bound to a single key.
+///
+/// The compact encoding produced by [`SmtProof::to_bytes`] is:
+///
+/// | bytes | content |
+/// |-------|---------|
+/// | 1 | version (`SMT_PROOF_VERSION`) |
+/// | 1 | flags, bit 0 set when `other_leaf` is present |
+/// | 2 | path depth `n`, big endian |
+/// | ceil(n/8) | direction bits, leaf to root, most significant bit first |
+/// | ceil(n/8) | bits marking non-empty siblings, same order |
+/// | 32 each | the non-empty siblings, leaf to root |
+/// | 64 | `other_leaf` key and value hash, if present |
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SmtProof {
     /// Path of sibling hashes from leaf to root
     pub siblings: Vec<Hash>,
-    /// Whether each sibling is on the left (false) or right (true)
+    /// Whether each sibling is on the left (true) or right (false) of the path
     pub directions: Vec<bool>,
+    /// Leaf holding a different key where the path ends, for non-inclusion proofs
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub other_leaf: Option<SmtProofLeaf>,
 }
 
 impl SmtProof {
     /// Create a new SMT proof
     pub fn new(siblings: Vec<Hash>, directions: Vec<bool>) -> Self {
-        Self { siblings, directions }
+        Self { siblings, directions, other_leaf: None }
+    }
+
+    /// Create a non-inclusion proof whose path ends at `other_leaf`, or at an empty subtree
+    pub fn non_inclusion(siblings: Vec<Hash>, directions: Vec<bool>, other_leaf: Option<SmtProofLeaf>) -> Self {
+        Self { siblings, directions, other_leaf }
+    }
+
+    /// Verify this proof shows `key` maps to `value` under `root`
+    pub fn verify(&self, root: &Hash, key: &Hash, value: &[u8], hasher: &dyn SmtHasher) -> bool {
+        if self.other_leaf.is_some() || !self.path_matches_key(key) {
+            return false;
+        }
+
+        let leaf_hash = hasher.leaf(key, &hasher.hash(value));
+        self.compute_root(leaf_hash, hasher) == *root
     }
 
-    /// Verify this proof against a root, key, and value
-    pub fn verify(&self, root: &Hash, _key: &Hash, value: &[u8], hasher: &dyn SmtHasher) -> bool {
-        if self.siblings.len() != self.directions.len() {
+    /// Verify this proof shows `key` is absent under `root`
+    pub fn verify_non_inclusion(&self, root: &Hash, key: &Hash, hasher: &dyn SmtHasher) -> bool {
+        if !self.path_matches_key(key) {
             return false;
         }
 
-        let leaf_hash = hasher.hash(value);
+        let start = match self.other_leaf {
+            None => empty_hash(),
+            Some(ref leaf) => {
+                // The other leaf must sit on the same path and hold a different key
+                let depth = self.siblings.len();
+                if leaf.key == *key || (0..depth).any(|d| key_bit(&leaf.key, d) != key_bit(key, d)) {
+                    return false;
+                }
+                hasher.leaf(&leaf.key, &leaf.value_hash)
+            }
+        };
+
+        self.compute_root(start, hasher) == *root
+    }
+
+    /// Encode the proof in the compact binary format
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let depth = self.siblings.len();
+        let non_empty: Vec<bool> = self.siblings.iter().map(|s| *s != empty_hash()).collect();
+
+        let mut bytes = Vec::with_capacity(4 + 2 * ((depth + 7) / 8) + 32 * depth + 64);
+        bytes.push(SMT_PROOF_VERSION);
+        bytes.push(u8::from(self.other_leaf.is_some()));
+        bytes.extend_from_slice(&(depth as u16).to_be_bytes());
+        bytes.extend_from_slice(&pack_bits(&self.directions));
+        bytes.extend_from_slice(&pack_bits(&non_empty));
+
+        for sibling in self.siblings.iter().filter(|s| **s != empty_hash()) {
+            bytes.extend_from_slice(sibling);
+        }
+
+        if let Some(ref leaf) = self.other_leaf {
+            bytes.extend_from_slice(&leaf.key);
+            bytes.extend_from_slice(&leaf.value_hash);
+        }
+
+        bytes
+    }
+
+    /// Decode a proof from the compact binary format
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut reader = ProofReader { bytes, position: 0 };
+
+        let version = reader.take(1)?[0];
+        if version != SMT_PROOF_VERSION {
+            return Err(CausalityError::invalid_proof(format!("Unsupported proof version {}", version)));
+        }
+
+        let flags = reader.take(1)?[0];
+        let depth = u16::from_be_bytes([reader.take(1)?[0], reader.take(1)?[0]]) as usize;
+        if depth > 256 {
+            return Err(CausalityError::invalid_proof(format!("Proof depth {} exceeds 256", depth)));
+        }
+
+        let bitmap_len = (depth + 7) / 8;
+        let directions = unpack_bits(reader.take(bitmap_len)?, depth);
+        let non_empty = unpack_bits(reader.take(bitmap_len)?, depth);
+
+        let mut siblings = Vec::with_capacity(depth);
+        for present in non_empty {
+            siblings.push(if present { reader.take_hash()? } else { empty_hash() });
+        }
+
+        let other_leaf = if flags & 1 == 1 {
+            Some(SmtProofLeaf {
+                key: reader.take_hash()?,
+                value_hash: reader.take_hash()?,
+            })
+        } else {
+            None
+        };
+
+        if reader.position != bytes.len() {
+            return Err(CausalityError::invalid_proof("Trailing bytes after proof"));
+        }
+
+        Ok(Self { siblings, directions, other_leaf })
+    }
+
+    /// Check that the path directions follow the bits of `key`
+    fn path_matches_key(&self, key: &Hash) -> bool {
+        let depth = self.siblings.len();
+        depth == self.directions.len()
+            && depth <= 256
+            && self.directions.iter().enumerate().all(|(i, &is_right)| is_right == key_bit(key, depth - 1 - i))
+    }
+
+    /// Hash from a leaf up to the root along the proof path
+    fn compute_root(&self, leaf_hash: Hash, hasher: &dyn SmtHasher) -> Hash {
         let mut current_hash = leaf_hash;
 
         // Traverse from leaf to root
-        for (i, &is_right) in self.directions.iter().enumerate() {
-            let sibling = self.siblings[i];
-            
+        for (sibling, &is_right) in self.siblings.iter().zip(&self.directions) {
             current_hash = if is_right {
-                hasher.merge(&sibling, &current_hash)
+                hasher.merge(sibling, &current_hash)
             } else {
-                hasher.merge(&current_hash, &sibling)
+                hasher.merge(&current_hash, sibling)
             };
         }
 
-        current_hash == *root
+        current_hash
+    }
+}
+
+/// Pack bits most significant bit first
+fn pack_bits(bits: &[bool]) -> Vec<u8> {
+    let mut bytes = vec![0u8; (bits.len() + 7) / 8];
+    for (i, _) in bits.iter().enumerate().filter(|(_, bit)| **bit) {
+        bytes[i / 8] |= 0x80 >> (i % 8);
+    }
+    bytes
+}
+
+/// Unpack `count` bits packed by `pack_bits`
+fn unpack_bits(bytes: &[u8], count: usize) -> Vec<bool> {
+    (0..count).map(|i| bytes[i / 8] & (0x80 >> (i % 8)) != 0).collect()
+}
+
+/// Cursor over an encoded proof
+struct ProofReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ProofReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self.position + len;
+        let slice = self.bytes.get(self.position..end)
+            .ok_or_else(|| CausalityError::invalid_proof("Proof is truncated"))?;
+        self.position = end;
+        Ok(slice)
+    }
+
+    fn take_hash(&mut self) -> Result<Hash> {
+        let mut hash = [0u8; 32];
+        hash.copy_from_slice(self.take(32)?);
+        Ok(hash)
     }
 }
 
@@ -294,6 +544,11 @@ pub trait SmtHasher: Send + Sync {
     
     /// Digest multiple byte arrays
     fn digest(&self, data_list: &[&[u8]]) -> Hash;
+    
+    /// Hash a leaf, committing to both its key and the hash of its value
+    fn leaf(&self, key: &Hash, value_hash: &Hash) -> Hash {
+        self.digest(&[SMT_LEAF_DOMAIN, key, value_hash])
+    }
 }
 
 /// Content-addressable trait for entities
@@ -485,7 +740,7 @@ pub struct CausalityEvent {
 }
 
 /// Types of events in the causality system
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CausalityEventType {
     /// Resource creation, update, or consumption
@@ -548,8 +803,42 @@ pub enum CausalityEventData {
     },
 }
 
+impl CausalityEventData {
+    /// Event type matching this data variant
+    pub fn event_type(&self) -> CausalityEventType {
+        match self {
+            Self::Resource(_) => CausalityEventType::ResourceEvent,
+            Self::Effect(_) => CausalityEventType::EffectEvent,
+            Self::Transaction(_) => CausalityEventType::TransactionEvent,
+            Self::Intent(_) => CausalityEventType::IntentEvent,
+            Self::Handler(_) => CausalityEventType::HandlerEvent,
+            Self::Domain(_) => CausalityEventType::DomainEvent,
+            Self::Nullifier(_) => CausalityEventType::NullifierEvent,
+            Self::CrossDomainMessage { .. } => CausalityEventType::CrossDomainMessage,
+            Self::TegStateChange { .. } => CausalityEventType::TegStateChange,
+        }
+    }
+}
+
 impl CausalityEvent {
+    /// Create a causality event from a core event with the given data
+    pub fn with_data(event: &dyn Event, data: CausalityEventData) -> Self {
+        Self {
+            id: event.id().to_string(),
+            chain_id: ChainId(event.chain().to_string()),
+            block_number: event.block_number(),
+            tx_hash: event.tx_hash().to_string(),
+            event_type: data.event_type(),
+            timestamp: event.timestamp(),
+            data,
+        }
+    }
+
     /// Create a new causality event from a core event
+    ///
+    /// The event is recorded as an opaque cross-domain message. Use
+    /// [`crate::mapping::EventMapperRegistry`] to map known events to their
+    /// semantic data.
     pub fn from_event(event: &dyn Event) -> Self {
         Self {
             id: event.id().to_string(),
@@ -572,37 +861,14 @@ impl CausalityEvent {
         hasher.key(&format!("event:{}", self.chain_id.0), self.id.as_bytes())
     }
 
-    /// Serialize to bytes for SMT storage
+    /// Serialize to canonical SSZ bytes for SMT storage
     pub fn to_bytes(&self) -> Result<Vec<u8>> {
-        #[cfg(feature = "serde")]
-        {
-            serde_json::to_vec(self).map_err(CausalityError::from)
-        }
-        #[cfg(not(feature = "serde"))]
-        {
-            // Simple binary serialization without serde
-            let mut bytes = Vec::new();
-            bytes.extend_from_slice(self.id.as_bytes());
-            bytes.extend_from_slice(&[0]); // separator
-            bytes.extend_from_slice(self.chain_id.0.as_bytes());
-            bytes.extend_from_slice(&[0]); // separator
-            bytes.extend_from_slice(&self.block_number.to_le_bytes());
-            // Note: CausalityEventData is complex, so we'll just use a placeholder for now
-            bytes.extend_from_slice(b"event_data");
-            Ok(bytes)
-        }
+        Ok(self.as_ssz_bytes())
     }
 
-    /// Deserialize from bytes
+    /// Deserialize from SSZ bytes
     pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
-        #[cfg(feature = "serde")]
-        {
-            serde_json::from_slice(bytes).map_err(CausalityError::from)
-        }
-        #[cfg(not(feature = "serde"))]
-        {
-            Err(CausalityError::serialization_error("Deserialization not supported without serde feature"))
-        }
+        Self::from_ssz_bytes(bytes)
     }
 }
 
@@ -612,22 +878,9 @@ impl CausalityResource {
         hasher.key(&format!("resource:{}", hash_to_hex(&self.domain_id.inner())), &self.id.inner())
     }
 
-    /// Serialize to bytes for SMT storage
+    /// Serialize to canonical SSZ bytes for SMT storage
     pub fn to_bytes(&self) -> Result<Vec<u8>> {
-        #[cfg(feature = "serde")]
-        {
-            serde_json::to_vec(self).map_err(CausalityError::from)
-        }
-        #[cfg(not(feature = "serde"))]
-        {
-            let mut bytes = Vec::new();
-            bytes.extend_from_slice(&self.id.inner());
-            bytes.extend_from_slice(&self.name.as_bytes());
-            bytes.extend_from_slice(&self.domain_id.inner());
-            bytes.extend_from_slice(self.resource_type.as_bytes());
-            bytes.extend_from_slice(&self.quantity.to_le_bytes());
-            Ok(bytes)
-        }
+        Ok(self.as_ssz_bytes())
     }
 }
 
@@ -637,21 +890,9 @@ impl CausalityEffect {
         hasher.key(&format!("effect:{}", hash_to_hex(&self.domain_id.inner())), &self.id.inner())
     }
 
-    /// Serialize to bytes for SMT storage
+    /// Serialize to canonical SSZ bytes for SMT storage
     pub fn to_bytes(&self) -> Result<Vec<u8>> {
-        #[cfg(feature = "serde")]
-        {
-            serde_json::to_vec(self).map_err(CausalityError::from)
-        }
-        #[cfg(not(feature = "serde"))]
-        {
-            let mut bytes = Vec::new();
-            bytes.extend_from_slice(&self.id.inner());
-            bytes.extend_from_slice(&self.name.as_bytes());
-            bytes.extend_from_slice(&self.domain_id.inner());
-            bytes.extend_from_slice(self.effect_type.as_bytes());
-            Ok(bytes)
-        }
+        Ok(self.as_ssz_bytes())
     }
 }
 
@@ -661,20 +902,9 @@ impl CausalityTransaction {
         hasher.key(&format!("transaction:{}", hash_to_hex(&self.domain_id.inner())), &self.id.inner())
     }
 
-    /// Serialize to bytes for SMT storage
+    /// Serialize to canonical SSZ bytes for SMT storage
     pub fn to_bytes(&self) -> Result<Vec<u8>> {
-        #[cfg(feature = "serde")]
-        {
-            serde_json::to_vec(self).map_err(CausalityError::from)
-        }
-        #[cfg(not(feature = "serde"))]
-        {
-            let mut bytes = Vec::new();
-            bytes.extend_from_slice(&self.id.inner());
-            bytes.extend_from_slice(&self.name.as_bytes());
-            bytes.extend_from_slice(&self.domain_id.inner());
-            Ok(bytes)
-        }
+        Ok(self.as_ssz_bytes())
     }
 }
 
@@ -702,6 +932,10 @@ pub struct CrossChainReference {
     pub source_chain: ChainId,
     /// Target chain
     pub target_chain: ChainId,
+    /// Event on the source chain that caused the target event
+    pub source_event: String,
+    /// Event on the target chain caused by the source event
+    pub target_event: String,
     /// Reference type (e.g., "bridge", "oracle", "message")
     pub ref_type: String,
     /// Reference data
@@ -771,4 +1005,22 @@ impl Default for CausalityIndex {
     fn default() -> Self {
         Self::new()
     }
+}
+
+/// SMT root recorded after an event was inserted into the tree
+///
+/// Versions are kept in processing order, so the root as of a given block
+/// on a chain is the root of the last version for that chain at or before
+/// that block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SmtRootVersion {
+    /// Chain the event belongs to
+    pub chain_id: ChainId,
+    /// Block the event was included in
+    pub block_number: u64,
+    /// ID of the event that produced this root
+    pub event_id: String,
+    /// SMT root after inserting the event
+    pub root: SmtRoot,
 } 
\ No newline at end of file
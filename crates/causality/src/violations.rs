@@ -0,0 +1,386 @@
+/// Detection of nullifier double spends and resource conservation violations
+use std::collections::{BTreeMap, HashMap};
+use std::time::SystemTime;
+
+use indexer_core::types::ChainId;
+
+use crate::causality::event_flows;
+use crate::types::{CausalityEvent, CausalityEventData, EntityId, Hash, ResourceFlow};
+
+/// Safety property broken by an event
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ViolationKind {
+    /// A nullifier was consumed more than once
+    DoubleSpend {
+        /// Reused nullifier hash
+        nullifier_hash: Hash,
+        /// Resource the nullifier consumes
+        resource_id: EntityId,
+        /// Event that consumed the nullifier first
+        first_event: String,
+        /// Chain of the first consumption
+        first_chain: ChainId,
+    },
+    /// The amounts of a resource type flowing into an event differ from those flowing out
+    UnbalancedFlow {
+        /// Resource type whose amounts differ
+        resource_type: String,
+        /// Total amount flowing in
+        inputs: u128,
+        /// Total amount flowing out
+        outputs: u128,
+    },
+}
+
+/// Kind of violation without its details, for filtering
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ViolationType {
+    /// Nullifier consumed more than once
+    DoubleSpend,
+    /// Resource amounts not conserved
+    UnbalancedFlow,
+}
+
+impl ViolationKind {
+    /// Kind of violation without its details
+    pub fn violation_type(&self) -> ViolationType {
+        match self {
+            Self::DoubleSpend { .. } => ViolationType::DoubleSpend,
+            Self::UnbalancedFlow { .. } => ViolationType::UnbalancedFlow,
+        }
+    }
+}
+
+/// Safety violation found while indexing an event
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SafetyViolation {
+    /// Event that broke the property
+    pub event_id: String,
+    /// Chain of the offending event
+    pub chain_id: ChainId,
+    /// Block of the offending event
+    pub block_number: u64,
+    /// What was violated
+    pub kind: ViolationKind,
+    /// When the violation was detected
+    pub detected_at: SystemTime,
+}
+
+/// Criteria for querying recorded violations; unset fields match everything
+#[derive(Debug, Clone, Default)]
+pub struct ViolationFilter {
+    /// Only violations by events on this chain
+    pub chain_id: Option<ChainId>,
+    /// Only violations of this kind
+    pub violation_type: Option<ViolationType>,
+    /// Only violations involving this nullifier
+    pub nullifier_hash: Option<Hash>,
+    /// Only violations at or after this block
+    pub from_block: Option<u64>,
+}
+
+impl ViolationFilter {
+    fn matches(&self, violation: &SafetyViolation) -> bool {
+        self.chain_id.as_ref().map_or(true, |chain| *chain == violation.chain_id)
+            && self.violation_type.map_or(true, |kind| kind == violation.kind.violation_type())
+            && self.from_block.map_or(true, |block| violation.block_number >= block)
+            && self.nullifier_hash.map_or(true, |hash| matches!(
+                &violation.kind,
+                ViolationKind::DoubleSpend { nullifier_hash, .. } if *nullifier_hash == hash
+            ))
+    }
+}
+
+/// Consumption of a nullifier by an event
+#[derive(Debug, Clone)]
+struct NullifierUse {
+    event_id: String,
+    chain_id: ChainId,
+    block_number: u64,
+    resource_id: EntityId,
+}
+
+/// Checks events for double spends and conservation violations
+///
+/// Nullifier consumption is tracked across all chains, so a nullifier spent
+/// on one chain and again on another is reported just like a reuse within a
+/// chain. Conservation is checked per event and per resource type: when a
+/// type flows both in and out of an effect or transaction and all its
+/// amounts are decimal numbers, the totals must match. Types that only flow
+/// in or only flow out (mints, burns, swaps) are not checked.
+#[derive(Debug, Default)]
+pub struct ViolationDetector {
+    /// Consumers of each nullifier, in processing order
+    nullifier_uses: HashMap<Hash, Vec<NullifierUse>>,
+    /// Recorded violations, in detection order
+    violations: Vec<SafetyViolation>,
+}
+
+impl ViolationDetector {
+    /// Create a detector with no consumed nullifiers
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check an event, record the violations it causes and return them
+    pub fn check_event(&mut self, event: &CausalityEvent) -> Vec<SafetyViolation> {
+        let mut found = Vec::new();
+        let violation = |kind| SafetyViolation {
+            event_id: event.id.clone(),
+            chain_id: event.chain_id.clone(),
+            block_number: event.block_number,
+            kind,
+            detected_at: SystemTime::now(),
+        };
+
+        if let CausalityEventData::Nullifier(nullifier) = &event.data {
+            let uses = self.nullifier_uses.entry(nullifier.nullifier_hash).or_default();
+            if uses.iter().any(|used| used.event_id == event.id) {
+                // Already checked, e.g. when replaying stored events
+                return found;
+            }
+            if let Some(first) = uses.first() {
+                found.push(violation(ViolationKind::DoubleSpend {
+                    nullifier_hash: nullifier.nullifier_hash,
+                    resource_id: nullifier.resource_id,
+                    first_event: first.event_id.clone(),
+                    first_chain: first.chain_id.clone(),
+                }));
+            }
+            uses.push(NullifierUse {
+                event_id: event.id.clone(),
+                chain_id: event.chain_id.clone(),
+                block_number: event.block_number,
+                resource_id: nullifier.resource_id,
+            });
+        }
+
+        let (inputs, outputs) = event_flows(event);
+        let (inputs, outputs) = (flow_totals(inputs), flow_totals(outputs));
+        for (resource_type, input) in &inputs {
+            if let Some(output) = outputs.get(resource_type) {
+                if let (Some(input), Some(output)) = (input, output) {
+                    if input != output {
+                        found.push(violation(ViolationKind::UnbalancedFlow {
+                            resource_type: resource_type.to_string(),
+                            inputs: *input,
+                            outputs: *output,
+                        }));
+                    }
+                }
+            }
+        }
+
+        self.violations.extend(found.iter().cloned());
+        found
+    }
+
+    /// Forget an event, e.g. when it is rolled back by a reorg
+    ///
+    /// Violations caused by the event are dropped. When the event was the
+    /// first consumer of a nullifier, the next consumer becomes the first and
+    /// every later consumer is reported as double spending against it.
+    pub fn remove_event(&mut self, event_id: &str) {
+        let mut promoted = Vec::new();
+        self.nullifier_uses.retain(|nullifier_hash, uses| {
+            if uses.first().is_some_and(|first| first.event_id == event_id) {
+                promoted.push(*nullifier_hash);
+            }
+            uses.retain(|used| used.event_id != event_id);
+            !uses.is_empty()
+        });
+        self.violations.retain(|violation| {
+            violation.event_id != event_id
+                && !matches!(&violation.kind, ViolationKind::DoubleSpend { first_event, .. } if first_event == event_id)
+        });
+
+        for nullifier_hash in promoted {
+            let Some((first, later)) = self.nullifier_uses.get(&nullifier_hash).and_then(|uses| uses.split_first()) else {
+                continue;
+            };
+            for used in later {
+                self.violations.push(SafetyViolation {
+                    event_id: used.event_id.clone(),
+                    chain_id: used.chain_id.clone(),
+                    block_number: used.block_number,
+                    kind: ViolationKind::DoubleSpend {
+                        nullifier_hash,
+                        resource_id: used.resource_id,
+                        first_event: first.event_id.clone(),
+                        first_chain: first.chain_id.clone(),
+                    },
+                    detected_at: SystemTime::now(),
+                });
+            }
+        }
+    }
+
+    /// Recorded violations matching a filter, in detection order
+    pub fn violations(&self, filter: &ViolationFilter) -> Vec<SafetyViolation> {
+        self.violations.iter().filter(|violation| filter.matches(violation)).cloned().collect()
+    }
+
+    /// Events that consumed a nullifier, in processing order
+    pub fn nullifier_consumers(&self, nullifier_hash: &Hash) -> Vec<String> {
+        self.nullifier_uses
+            .get(nullifier_hash)
+            .map(|uses| uses.iter().map(|used| used.event_id.clone()).collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Total amount per resource type, or `None` for types with non-numeric amounts
+fn flow_totals(flows: &[ResourceFlow]) -> BTreeMap<&str, Option<u128>> {
+    let mut totals: BTreeMap<&str, Option<u128>> = BTreeMap::new();
+    for flow in flows {
+        let amount = std::str::from_utf8(&flow.resource_data).ok().and_then(|s| s.parse::<u128>().ok());
+        let total = totals.entry(flow.resource_type.as_str()).or_insert(Some(0));
+        *total = total.zip(amount).and_then(|(total, amount)| total.checked_add(amount));
+    }
+    totals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{CausalityEffect, CausalityEventType, CausalityNullifier, DomainId, HandlerId, TypedDomain};
+
+    fn nullifier_event(id: &str, chain: &str, block_number: u64, hash: u8) -> CausalityEvent {
+        CausalityEvent {
+            id: id.to_string(),
+            chain_id: ChainId(chain.to_string()),
+            block_number,
+            tx_hash: format!("0x{}", id),
+            event_type: CausalityEventType::NullifierEvent,
+            timestamp: SystemTime::UNIX_EPOCH,
+            data: CausalityEventData::Nullifier(CausalityNullifier {
+                resource_id: EntityId::new([hash; 32]),
+                nullifier_hash: [hash; 32],
+                timestamp: SystemTime::UNIX_EPOCH,
+            }),
+        }
+    }
+
+    fn transfer_event(id: &str, amount_in: &str, amount_out: &str) -> CausalityEvent {
+        let flow = |amount: &str| ResourceFlow {
+            from_event: "ethereum:alice".to_string(),
+            to_event: "ethereum:bob".to_string(),
+            resource_type: "token:usdc".to_string(),
+            resource_data: amount.as_bytes().to_vec(),
+            timestamp: SystemTime::UNIX_EPOCH,
+        };
+
+        CausalityEvent {
+            id: id.to_string(),
+            chain_id: ChainId("ethereum".to_string()),
+            block_number: 1,
+            tx_hash: format!("0x{}", id),
+            event_type: CausalityEventType::EffectEvent,
+            timestamp: SystemTime::UNIX_EPOCH,
+            data: CausalityEventData::Effect(CausalityEffect {
+                id: EntityId::null(),
+                name: id.to_string(),
+                domain_id: DomainId::null(),
+                effect_type: "token_transfer".to_string(),
+                inputs: vec![flow(amount_in)],
+                outputs: vec![flow(amount_out)],
+                expression: None,
+                timestamp: SystemTime::UNIX_EPOCH,
+                scoped_by: HandlerId::null(),
+                intent_id: None,
+                source_typed_domain: TypedDomain::default(),
+                target_typed_domain: TypedDomain::default(),
+                originating_dataflow_instance: None,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_double_spend_across_chains() {
+        let mut detector = ViolationDetector::new();
+        assert!(detector.check_event(&nullifier_event("eth-spend", "ethereum", 10, 7)).is_empty());
+
+        let found = detector.check_event(&nullifier_event("ntrn-spend", "neutron-1", 4, 7));
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].event_id, "ntrn-spend");
+        match &found[0].kind {
+            ViolationKind::DoubleSpend { first_event, first_chain, .. } => {
+                assert_eq!(first_event, "eth-spend");
+                assert_eq!(first_chain, &ChainId("ethereum".to_string()));
+            }
+            other => panic!("unexpected violation: {:?}", other),
+        }
+
+        // Replaying an already checked event reports nothing new
+        assert!(detector.check_event(&nullifier_event("ntrn-spend", "neutron-1", 4, 7)).is_empty());
+        assert!(detector.check_event(&nullifier_event("other", "ethereum", 11, 8)).is_empty());
+
+        let filter = ViolationFilter { nullifier_hash: Some([7; 32]), ..ViolationFilter::default() };
+        assert_eq!(detector.violations(&filter).len(), 1);
+        let filter = ViolationFilter { chain_id: Some(ChainId("ethereum".to_string())), ..ViolationFilter::default() };
+        assert!(detector.violations(&filter).is_empty());
+    }
+
+    #[test]
+    fn test_rollback_promotes_next_consumer() {
+        let mut detector = ViolationDetector::new();
+        detector.check_event(&nullifier_event("eth-spend", "ethereum", 10, 7));
+        detector.check_event(&nullifier_event("ntrn-spend", "neutron-1", 4, 7));
+
+        detector.remove_event("eth-spend");
+        assert!(detector.violations(&ViolationFilter::default()).is_empty());
+        assert_eq!(detector.nullifier_consumers(&[7; 32]), vec!["ntrn-spend"]);
+
+        // Re-including the rolled back spend is now the double spend
+        let found = detector.check_event(&nullifier_event("eth-spend", "ethereum", 10, 7));
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].event_id, "eth-spend");
+    }
+
+    #[test]
+    fn test_rollback_rechecks_remaining_consumers() {
+        let mut detector = ViolationDetector::new();
+        detector.check_event(&nullifier_event("a", "ethereum", 10, 7));
+        detector.check_event(&nullifier_event("b", "neutron-1", 4, 7));
+        detector.check_event(&nullifier_event("c", "osmosis-1", 6, 7));
+
+        detector.remove_event("a");
+        let violations = detector.violations(&ViolationFilter::default());
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].event_id, "c");
+        assert_eq!(violations[0].chain_id, ChainId("osmosis-1".to_string()));
+        assert_eq!(violations[0].block_number, 6);
+        match &violations[0].kind {
+            ViolationKind::DoubleSpend { first_event, first_chain, .. } => {
+                assert_eq!(first_event, "b");
+                assert_eq!(first_chain, &ChainId("neutron-1".to_string()));
+            }
+            other => panic!("unexpected violation: {:?}", other),
+        }
+
+        // Removing a later consumer leaves the first one's findings alone
+        detector.remove_event("c");
+        assert!(detector.violations(&ViolationFilter::default()).is_empty());
+    }
+
+    #[test]
+    fn test_unbalanced_flow() {
+        let mut detector = ViolationDetector::new();
+        assert!(detector.check_event(&transfer_event("balanced", "100", "100")).is_empty());
+        assert!(detector.check_event(&transfer_event("opaque", "nft-1", "nft-2")).is_empty());
+
+        let found = detector.check_event(&transfer_event("inflated", "100", "150"));
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].kind, ViolationKind::UnbalancedFlow {
+            resource_type: "token:usdc".to_string(),
+            inputs: 100,
+            outputs: 150,
+        });
+
+        let filter = ViolationFilter { violation_type: Some(ViolationType::DoubleSpend), ..ViolationFilter::default() };
+        assert!(detector.violations(&filter).is_empty());
+    }
+}
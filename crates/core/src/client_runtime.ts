@@ -0,0 +1,110 @@
+/** Where and how requests are sent */
+export interface ClientOptions {
+  /** Base URL of the API, without a trailing slash */
+  baseUrl: string;
+  /** Headers sent with every request, such as `Authorization` */
+  headers?: Record<string, string>;
+  /** Fetch implementation, the global `fetch` by default */
+  fetch?: typeof fetch;
+}
+
+/** Response of a request that failed */
+export class ApiError extends Error {
+  /** HTTP status of the response */
+  readonly status: number;
+  /** Parsed body of the response, if it was JSON */
+  readonly body: unknown;
+
+  constructor(status: number, body: unknown) {
+    super(`Request failed with status ${status}`);
+    this.status = status;
+    this.body = body;
+  }
+}
+
+/** Query string for `query`, leaving out unset values and repeating arrays */
+function queryString(query: object = {}): string {
+  const params = new URLSearchParams();
+  for (const [key, value] of Object.entries(query)) {
+    if (value === undefined || value === null) {
+      continue;
+    }
+    for (const item of Array.isArray(value) ? value : [value]) {
+      params.append(key, String(item));
+    }
+  }
+  const encoded = params.toString();
+  return encoded ? `?${encoded}` : '';
+}
+
+/** Send a request and parse its JSON response */
+async function request<T>(options: ClientOptions, method: 'GET' | 'POST', path: string, body?: unknown): Promise<T> {
+  const headers: Record<string, string> = body === undefined ? {} : { 'Content-Type': 'application/json' };
+  const response = await (options.fetch ?? fetch)(`${options.baseUrl}${path}`, {
+    method,
+    headers: { ...headers, ...options.headers },
+    body: body === undefined ? undefined : JSON.stringify(body),
+  });
+  const payload: unknown = await response.json().catch(() => undefined);
+  if (!response.ok) {
+    throw new ApiError(response.status, payload);
+  }
+  return payload as T;
+}
+
+/** Query parameters of Almanac's event endpoints */
+export interface EventsQuery {
+  limit?: number;
+  offset?: number;
+  ascending?: boolean;
+  from_height?: number;
+  to_height?: number;
+  event_type?: string;
+  /** Text to search the events for */
+  text_query?: string;
+  text_search_mode?: 'contains' | 'fuzzy' | 'regex' | 'phrase' | 'boolean';
+  case_sensitive?: boolean;
+}
+
+/** Event indexed by Almanac, with its decoded attributes */
+export interface IndexedEvent<A = Record<string, unknown>> {
+  chain_id: string;
+  height: number;
+  tx_hash: string;
+  index: number;
+  address: string;
+  event_type: string;
+  attributes: A;
+  /** Raw event data, base64 encoded */
+  raw_data: string;
+  /** Unix timestamp in seconds */
+  timestamp: number;
+}
+
+/** Pagination of a list of events */
+export interface PaginationInfo {
+  total: number | null;
+  limit: number;
+  offset: number;
+  has_more: boolean;
+}
+
+/** Page of events indexed by Almanac */
+export interface EventsResponse<A = Record<string, unknown>> {
+  events: IndexedEvent<A>[];
+  pagination: PaginationInfo;
+}
+
+/**
+ * Events Almanac indexed for the contract
+ *
+ * `GET /api/v1/events/address/{chain_id}/{address}`. Pass the attributes
+ * type of an event, with its `event_type`, to type the decoded attributes.
+ */
+export function getIndexedEvents<A = Record<string, unknown>>(
+  options: ClientOptions,
+  query: EventsQuery = {},
+): Promise<EventsResponse<A>> {
+  const path = `/api/v1/events/address/${encodeURIComponent(CHAIN_ID)}/${encodeURIComponent(CONTRACT_ADDRESS)}`;
+  return request(options, 'GET', `${path}${queryString(query)}`);
+}
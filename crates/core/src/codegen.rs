@@ -0,0 +1,884 @@
+/// Support shared by the contract code generators
+///
+/// Generated files start with a provenance header recording the generator
+/// version and a hash of the ABI or schema they were generated from, and can
+/// be regenerated in memory and compared with the files on disk to catch
+/// bindings that drifted from their inputs. Generated migrations share the
+/// PostgreSQL naming and versioning defined here, user templates the naming,
+/// case helpers and checks of template files, and the TypeScript generated for frontends the client
+/// runtime and GraphQL documents for Almanac's own event API.
+///
+/// Rendering templates needs the `codegen` feature, which the code generators
+/// enable, so that crates using only the rest don't depend on Handlebars.
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "codegen")]
+use convert_case::{Case, Casing};
+#[cfg(feature = "codegen")]
+use handlebars::template::{Parameter, Template, TemplateElement};
+#[cfg(feature = "codegen")]
+use handlebars::{Context, Handlebars, Helper, HelperResult, Output, RenderContext, RenderError};
+#[cfg(feature = "codegen")]
+use serde_json::Value;
+
+use crate::{Error, Result};
+
+/// Version of the code generators, recorded in generated files
+pub const GENERATOR_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Marker in the provenance header of generated files
+const GENERATED_MARKER: &str = "@generated by almanac codegen";
+
+/// Inputs a set of files is generated from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Provenance {
+    /// File names of the inputs
+    pub inputs: Vec<String>,
+    /// MD5 of the input contents, in order
+    pub hash: String,
+}
+
+impl Provenance {
+    /// Provenance of files generated from the named input contents
+    ///
+    /// A single input is hashed as is, so the hash matches the input file's
+    /// MD5. Several inputs are each prefixed with their length, so moving
+    /// bytes from one input to the next changes the hash.
+    pub fn new(inputs: &[(&str, &[u8])]) -> Self {
+        let content = match inputs {
+            [(_, input)] => input.to_vec(),
+            _ => {
+                let mut content = Vec::new();
+                for (_, input) in inputs {
+                    content.extend_from_slice(&(input.len() as u64).to_be_bytes());
+                    content.extend_from_slice(input);
+                }
+                content
+            }
+        };
+        Self {
+            inputs: inputs.iter().map(|(name, _)| name.to_string()).collect(),
+            hash: format!("{:x}", md5::compute(content)),
+        }
+    }
+
+    /// Provenance of files generated from the files at `paths`, recorded by
+    /// file name so headers don't depend on where the inputs are checked out
+    pub fn from_files(paths: &[&str]) -> Result<Self> {
+        let mut inputs = Vec::new();
+        for path in paths {
+            let name = Path::new(path)
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| path.to_string());
+            inputs.push((name, std::fs::read(path)?));
+        }
+        let inputs: Vec<(&str, &[u8])> = inputs.iter().map(|(name, content)| (name.as_str(), content.as_slice())).collect();
+        Ok(Self::new(&inputs))
+    }
+
+    /// Header line for a generated file, commented for its extension, or
+    /// `None` for files without comments
+    pub fn header(&self, path: &Path) -> Option<String> {
+        let comment = match path.extension()?.to_str()? {
+            "rs" | "ts" => "//",
+            "sql" => "--",
+            _ => return None,
+        };
+        Some(format!(
+            "{} {} {} from {} (md5 {}), do not edit\n",
+            comment,
+            GENERATED_MARKER,
+            GENERATOR_VERSION,
+            self.inputs.join(", "),
+            self.hash
+        ))
+    }
+}
+
+/// File produced by a code generator
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedFile {
+    /// Path the file is written to
+    pub path: PathBuf,
+    /// Content including the provenance header
+    pub content: String,
+}
+
+/// Difference between a generated file and the file on disk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Drift {
+    /// The file is not on disk
+    Missing(PathBuf),
+    /// The file on disk has other content
+    Changed(PathBuf),
+    /// The file on disk was generated but is no longer generated
+    Stale(PathBuf),
+}
+
+impl fmt::Display for Drift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Drift::Missing(path) => write!(f, "missing: {}", path.display()),
+            Drift::Changed(path) => write!(f, "changed: {}", path.display()),
+            Drift::Stale(path) => write!(f, "stale: {}", path.display()),
+        }
+    }
+}
+
+/// Compare generated files with the files on disk
+///
+/// Files named with a leading version, as migrations are, are compared with
+/// the latest file on disk having the same name after the version, since
+/// they may have been generated with another explicit version. Files below
+/// the generated directories that carry a provenance header but are no
+/// longer generated, such as the handler of a removed event, are stale.
+pub fn check_drift(files: &[GeneratedFile]) -> Result<Vec<Drift>> {
+    let mut drift = Vec::new();
+    for file in files {
+        let Some(path) = existing_path(&file.path)? else {
+            drift.push(Drift::Missing(file.path.clone()));
+            continue;
+        };
+        if std::fs::read_to_string(&path)? != file.content {
+            drift.push(Drift::Changed(path));
+        }
+    }
+
+    let generated: BTreeSet<(&Path, &str)> = files.iter()
+        .filter_map(|file| Some((file.path.parent()?, generated_name(file.path.file_name()?.to_str()?))))
+        .collect();
+    let mut on_disk = Vec::new();
+    for dir in generated_roots(files) {
+        collect_generated_files(dir, &mut on_disk)?;
+    }
+    on_disk.sort();
+    for path in on_disk {
+        let known = path.parent().zip(path.file_name().and_then(|name| name.to_str()))
+            .is_some_and(|(dir, name)| generated.contains(&(dir, generated_name(name))));
+        if !known {
+            drift.push(Drift::Stale(path));
+        }
+    }
+    Ok(drift)
+}
+
+/// Report of the drift found by a check, for CLIs to print
+pub fn report_drift(drift: &[Drift]) -> String {
+    if drift.is_empty() {
+        return "✅ Generated code is up to date".to_string();
+    }
+    drift.iter().map(|file| format!("  {}", file)).collect::<Vec<_>>().join("\n")
+}
+
+/// Fail when a check found drift, so that CLIs exit non-zero on stale
+/// generated code
+pub fn fail_on_drift(drift: &[Drift]) -> Result<()> {
+    if drift.is_empty() {
+        return Ok(());
+    }
+    Err(Error::config(format!(
+        "{} generated files are out of date, regenerate them without --check",
+        drift.len()
+    )))
+}
+
+/// Name a generated file is matched by, without the version of a
+/// versioned file
+fn generated_name(name: &str) -> &str {
+    unversioned_name(name).unwrap_or(name)
+}
+
+/// Directories of the generated files that are not below another one
+fn generated_roots(files: &[GeneratedFile]) -> Vec<&Path> {
+    let dirs: BTreeSet<&Path> = files.iter().filter_map(|file| file.path.parent()).collect();
+    dirs.iter()
+        .filter(|dir| !dirs.iter().any(|other| other != *dir && dir.starts_with(other)))
+        .copied()
+        .collect()
+}
+
+/// Files below `dir` that start with a provenance header
+fn collect_generated_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_generated_files(&path, files)?;
+        } else if std::fs::read_to_string(&path)
+            .is_ok_and(|content| content.lines().next().is_some_and(|line| line.contains(GENERATED_MARKER)))
+        {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// File on disk corresponding to a generated path, if any
+fn existing_path(path: &Path) -> Result<Option<PathBuf>> {
+    if path.exists() {
+        return Ok(Some(path.to_path_buf()));
+    }
+    let (Some(name), Some(dir)) = (path.file_name().and_then(|name| name.to_str()), path.parent()) else {
+        return Ok(None);
+    };
+    let Some(unversioned) = unversioned_name(name) else {
+        return Ok(None);
+    };
+    if !dir.is_dir() {
+        return Ok(None);
+    }
+
+    let mut candidates = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let candidate = entry?.path();
+        if candidate.file_name().and_then(|name| name.to_str()).and_then(unversioned_name) == Some(unversioned) {
+            candidates.push(candidate);
+        }
+    }
+    candidates.sort();
+    Ok(candidates.pop())
+}
+
+/// Name after a leading numeric version such as `202406011200_`
+fn unversioned_name(name: &str) -> Option<&str> {
+    let (version, rest) = name.split_once('_')?;
+    (!version.is_empty() && version.bytes().all(|b| b.is_ascii_digit())).then_some(rest)
+}
+
+/// `.hbs` template files below `dir`, sorted by path
+pub fn collect_template_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    collect_template_files_into(dir, &mut files)?;
+    files.sort();
+    Ok(files)
+}
+
+fn collect_template_files_into(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_template_files_into(&path, files)?;
+        } else if path.extension().is_some_and(|extension| extension == "hbs") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Name of a template file, its path below `dir` without the extension and
+/// with `/` separators
+pub fn template_name(dir: &Path, path: &Path) -> String {
+    slash_path(&path.strip_prefix(dir).unwrap_or(path).with_extension(""))
+}
+
+/// `path` with `/` separators, as templates and generated files are named
+pub fn slash_path(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Case conversion helpers registered by [`register_case_helpers`]
+#[cfg(feature = "codegen")]
+pub const CASE_HELPERS: &[&str] = &[
+    "snake_case",
+    "camel_case",
+    "pascal_case",
+    "screaming_snake_case",
+    "kebab_case",
+];
+
+/// Helpers Handlebars registers itself
+#[cfg(feature = "codegen")]
+const BUILTIN_HELPERS: &[&str] = &[
+    "if", "unless", "each", "with", "lookup", "raw", "log",
+    "eq", "ne", "gt", "gte", "lt", "lte", "and", "or", "not", "len",
+];
+
+/// Register the [`CASE_HELPERS`], converting the case of a string
+#[cfg(feature = "codegen")]
+pub fn register_case_helpers(handlebars: &mut Handlebars) {
+    for (name, case) in [
+        ("snake_case", Case::Snake),
+        ("camel_case", Case::Camel),
+        ("pascal_case", Case::Pascal),
+        ("screaming_snake_case", Case::UpperSnake),
+        ("kebab_case", Case::Kebab),
+    ] {
+        handlebars.register_helper(
+            name,
+            Box::new(move |h: &Helper, _: &Handlebars, _: &Context, _: &mut RenderContext, out: &mut dyn Output| -> HelperResult {
+                let value = h.param(0).and_then(|param| param.value().as_str())
+                    .ok_or_else(|| RenderError::new(format!("{} expects a string", h.name())))?;
+                out.write(&value.to_case(case))?;
+                Ok(())
+            }),
+        );
+    }
+}
+
+/// Register the `.hbs` templates below `dir`, named by [`template_name`],
+/// returning their names
+///
+/// Templates are checked once all of them are registered, for syntax and
+/// for partials that don't exist and helpers that are neither Handlebars
+/// built-ins nor in `helpers`.
+#[cfg(feature = "codegen")]
+pub fn register_templates_dir(handlebars: &mut Handlebars, dir: &Path, helpers: &[&str]) -> Result<Vec<String>> {
+    if !dir.is_dir() {
+        return Err(Error::Config(format!("Templates directory not found: {}", dir.display())));
+    }
+
+    let files = collect_template_files(dir)?;
+    let mut names = Vec::new();
+    for path in &files {
+        let name = template_name(dir, path);
+        let source = std::fs::read_to_string(path)?;
+        handlebars.register_template_string(&name, source)
+            .map_err(|e| Error::Config(format!("Invalid template {}: {}", path.display(), e)))?;
+        names.push(name);
+    }
+
+    let checker = TemplateChecker { handlebars, helpers };
+    for (name, path) in names.iter().zip(&files) {
+        let mut problems = Vec::new();
+        if let Some(template) = handlebars.get_template(name) {
+            checker.check_template(template, &mut Vec::new(), &mut problems);
+        }
+        if !problems.is_empty() {
+            return Err(Error::Config(format!(
+                "Invalid template {}: {}", path.display(), problems.join("; ")
+            )));
+        }
+    }
+
+    Ok(names)
+}
+
+/// Finds the helpers and partials templates use that don't exist
+#[cfg(feature = "codegen")]
+struct TemplateChecker<'a, 'reg> {
+    handlebars: &'a Handlebars<'reg>,
+    /// Helpers registered in addition to the Handlebars built-ins
+    helpers: &'a [&'a str],
+}
+
+#[cfg(feature = "codegen")]
+impl TemplateChecker<'_, '_> {
+    /// Collect the helpers and partials `template` uses that don't exist,
+    /// given the `inline` partials it defined so far
+    fn check_template(&self, template: &Template, inline: &mut Vec<String>, problems: &mut Vec<String>) {
+        for element in &template.elements {
+            match element {
+                TemplateElement::HelperBlock(helper) => {
+                    self.check_helper(&helper.name, problems);
+                    helper.params.iter().for_each(|param| self.check_parameter(param, problems));
+                    for inner in helper.template.iter().chain(&helper.inverse) {
+                        self.check_template(inner, inline, problems);
+                    }
+                }
+                TemplateElement::Expression(helper) | TemplateElement::HtmlExpression(helper) => {
+                    if !helper.params.is_empty() || !helper.hash.is_empty() {
+                        self.check_helper(&helper.name, problems);
+                    }
+                    helper.params.iter().for_each(|param| self.check_parameter(param, problems));
+                }
+                TemplateElement::PartialExpression(partial) | TemplateElement::PartialBlock(partial) => {
+                    match partial.name.as_name() {
+                        Some(name) if !self.handlebars.has_template(name)
+                            && !inline.iter().any(|inline| inline == name)
+                            && name != "@partial-block" =>
+                        {
+                            problems.push(format!("unknown partial {}", name));
+                        }
+                        _ => {}
+                    }
+                    if let Some(inner) = &partial.template {
+                        self.check_template(inner, inline, problems);
+                    }
+                }
+                TemplateElement::DecoratorBlock(decorator) if decorator.name.as_name() == Some("inline") => {
+                    if let Some(Parameter::Literal(Value::String(name))) = decorator.params.first() {
+                        inline.push(name.clone());
+                    }
+                    if let Some(inner) = &decorator.template {
+                        self.check_template(inner, inline, problems);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn check_helper(&self, name: &Parameter, problems: &mut Vec<String>) {
+        if let Some(name) = name.as_name() {
+            if !self.helpers.contains(&name) && !BUILTIN_HELPERS.contains(&name) {
+                problems.push(format!("unknown helper {}", name));
+            }
+        }
+    }
+
+    fn check_parameter(&self, param: &Parameter, problems: &mut Vec<String>) {
+        if let Parameter::Subexpression(subexpression) = param {
+            if subexpression.is_helper() {
+                self.check_helper(&Parameter::Name(subexpression.name().to_string()), problems);
+            }
+            for param in subexpression.params().into_iter().flatten() {
+                self.check_parameter(param, problems);
+            }
+        }
+    }
+}
+
+/// Whether a template renders a file, being named with an extension, rather
+/// than being a partial
+pub fn is_file_template(name: &str) -> bool {
+    Path::new(name).extension().is_some()
+}
+
+/// Template names that render the generated file `name`, most specific
+/// first
+///
+/// Files named with a leading version, as migrations are, can also be
+/// rendered by a template named without it, such as
+/// `migrations/token_events.up.sql`, which keeps applying when the version
+/// changes.
+pub fn file_template_names(name: &str) -> Vec<String> {
+    let mut names = vec![name.to_string()];
+    let (dir, file) = match name.rsplit_once('/') {
+        Some((dir, file)) => (Some(dir), file),
+        None => (None, name),
+    };
+    if let Some(unversioned) = unversioned_name(file) {
+        names.push(match dir {
+            Some(dir) => format!("{}/{}", dir, unversioned),
+            None => unversioned.to_string(),
+        });
+    }
+    names
+}
+
+/// Template rendering `content` unchanged, with its `{{` escaped, or `None`
+/// for content Handlebars can't reproduce, such as a backslash before `{{`
+#[cfg(feature = "codegen")]
+pub fn reference_template(content: &str) -> Option<String> {
+    let template = content.replace("{{", "\\{{");
+    let mut handlebars = Handlebars::new();
+    handlebars.register_escape_fn(handlebars::no_escape);
+    match handlebars.render_template(&template, &Value::Null) {
+        Ok(rendered) if rendered == content => Some(template),
+        _ => None,
+    }
+}
+
+/// Write generated files below `output_dir` as the templates rendering them
+/// unchanged below `templates_dir`, returning the paths of the templates
+///
+/// The templates are named as file templates are looked up, migrations
+/// without their version, and leave out the provenance header, which is
+/// added to rendered files. They are a starting point for templates
+/// overriding the built-in generators.
+#[cfg(feature = "codegen")]
+pub fn write_reference_templates(files: &[GeneratedFile], output_dir: &Path, templates_dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for file in files {
+        let relative = file.path.strip_prefix(output_dir).unwrap_or(&file.path);
+        let name = file_template_names(&slash_path(relative)).pop().unwrap_or_default();
+        let content = match file.content.split_once('\n') {
+            Some((first, rest)) if first.contains(GENERATED_MARKER) => rest,
+            _ => file.content.as_str(),
+        };
+        let template = reference_template(content).ok_or_else(|| Error::config(format!(
+            "{} can't be written as a Handlebars template", file.path.display()
+        )))?;
+
+        let path = templates_dir.join(format!("{}.hbs", name));
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, template)?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+/// Version prefix of a generated migration
+///
+/// An explicit version must be numeric. Without one, a migration `name`
+/// (without version) in `dir` whose file already ends with `sql` keeps its
+/// version, so regenerating unchanged inputs keeps the file names. Any other
+/// migration is versioned with the current UTC time as `YYYYMMDDHHMMSS`,
+/// raised past the highest version in `dir` so it applies after them.
+pub fn migration_version(explicit: Option<&str>, dir: &Path, name: &str, sql: &str) -> Result<String> {
+    if let Some(version) = explicit {
+        if version.is_empty() || version.len() > 18 || !version.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(Error::config(format!(
+                "Migration version {} must be between 1 and 18 digits",
+                version
+            )));
+        }
+        return Ok(version.to_string());
+    }
+
+    let mut highest = 0u64;
+    if dir.is_dir() {
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            let file = match path.file_name().and_then(|file| file.to_str()) {
+                Some(file) => file,
+                None => continue,
+            };
+            let (version, unversioned) = match (file.split_once('_'), unversioned_name(file)) {
+                (Some((version, _)), Some(unversioned)) => (version, unversioned),
+                _ => continue,
+            };
+            if unversioned == name
+                && std::fs::read_to_string(&path).is_ok_and(|content| content.trim_end().ends_with(sql.trim_end()))
+            {
+                return Ok(version.to_string());
+            }
+            highest = highest.max(version.parse().unwrap_or(0));
+        }
+    }
+
+    let now: u64 = chrono::Utc::now()
+        .format("%Y%m%d%H%M%S")
+        .to_string()
+        .parse()
+        .map_err(|e| Error::config(format!("Failed to format migration timestamp: {}", e)))?;
+    Ok(now.max(highest + 1).to_string())
+}
+
+/// Longest identifier PostgreSQL keeps without truncating
+pub const MAX_IDENTIFIER_LEN: usize = 63;
+
+/// `name` shortened to fit PostgreSQL identifiers
+///
+/// PostgreSQL truncates long identifiers, which would make names sharing a
+/// long prefix collide, so long names end in a hash of the full name instead.
+pub fn bounded_identifier(name: &str) -> String {
+    if name.len() <= MAX_IDENTIFIER_LEN {
+        return name.to_string();
+    }
+    // 32-bit FNV-1a, stable across builds unlike the std hasher
+    let hash = name.bytes().fold(0x811c_9dc5_u32, |hash, byte| (hash ^ byte as u32).wrapping_mul(0x0100_0193));
+    let mut end = MAX_IDENTIFIER_LEN - 9;
+    while !name.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}_{:08x}", &name[..end], hash)
+}
+
+/// Double-quote `name` when it is a reserved word in PostgreSQL or does
+/// not start like an identifier, as with `0x` contract prefixes
+pub fn quote_identifier(name: &str) -> String {
+    match name {
+        "all" | "analyse" | "analyze" | "and" | "any" | "array" | "as" | "asc" | "asymmetric" | "both"
+        | "case" | "cast" | "check" | "collate" | "column" | "constraint" | "create" | "current_date"
+        | "current_role" | "current_time" | "current_timestamp" | "current_user" | "default" | "deferrable"
+        | "desc" | "distinct" | "do" | "else" | "end" | "except" | "false" | "fetch" | "for" | "foreign"
+        | "from" | "grant" | "group" | "having" | "in" | "initially" | "intersect" | "into" | "lateral"
+        | "leading" | "limit" | "localtime" | "localtimestamp" | "not" | "null" | "offset" | "on" | "only"
+        | "or" | "order" | "placing" | "primary" | "references" | "returning" | "select" | "session_user"
+        | "some" | "symmetric" | "table" | "then" | "to" | "trailing" | "true" | "union" | "unique"
+        | "user" | "using" | "variadic" | "when" | "where" | "window" | "with" => format!("\"{}\"", name),
+        _ if name.starts_with(|c: char| c.is_ascii_digit()) => format!("\"{}\"", name),
+        _ => name.to_string(),
+    }
+}
+
+/// Table name for generated event tables, bounded and quoted as needed
+pub fn table_name(name: &str) -> String {
+    quote_identifier(&bounded_identifier(name))
+}
+
+/// Index name for `column` of `table`
+pub fn index_name(table: &str, column: &str) -> String {
+    bounded_identifier(&format!("idx_{}_{}", table.trim_matches('"'), column.trim_matches('"')))
+}
+
+/// Column name for an event parameter or attribute
+///
+/// Unnamed parameters become `arg{index}`, and names taken by one of the
+/// `metadata_columns` every event table carries get `prefix` prepended.
+pub fn column_name(name: &str, index: usize, metadata_columns: &[&str], prefix: &str) -> String {
+    let column = name.trim_start_matches('_').to_case(Case::Snake);
+    if column.is_empty() {
+        return format!("arg{}", index);
+    }
+    if metadata_columns.contains(&column.as_str()) {
+        return bounded_identifier(&format!("{}{}", prefix, column));
+    }
+    quote_identifier(&bounded_identifier(&column))
+}
+
+/// Client runtime of the generated `typescript/client.ts`
+///
+/// Defines the request options and helpers the generated functions use, and
+/// `getIndexedEvents` for the events Almanac's REST API serves for the
+/// contract. The file importing it must define `CHAIN_ID` and
+/// `CONTRACT_ADDRESS`.
+pub const TYPESCRIPT_CLIENT_RUNTIME: &str = include_str!("client_runtime.ts");
+
+/// GraphQL fragment and query of the generated `typescript/fragments.ts` for
+/// the events Almanac's GraphQL API serves, named after `contract_name` so
+/// documents of several contracts can be combined
+pub fn typescript_indexed_event_documents(contract_name: &str) -> String {
+    format!(
+        r#"/** Fields of an event indexed by Almanac */
+export const INDEXED_EVENT_FIELDS = `
+fragment {name}IndexedEventFields on GraphQLEvent {{
+  id
+  chain
+  blockNumber
+  blockHash
+  txHash
+  timestamp
+  eventType
+  attributes
+}}
+`;
+
+/**
+ * Page of the events indexed by Almanac matching `$filter`
+ *
+ * Pass `pageInfo.endCursor` as `$after` to read the next page.
+ */
+export const INDEXED_EVENTS_QUERY = `
+query {name}IndexedEvents($filter: EventFilterInput, $first: Int, $after: String) {{
+  eventsConnection(filter: $filter, first: $first, after: $after) {{
+    edges {{
+      cursor
+      node {{
+        ...{name}IndexedEventFields
+      }}
+    }}
+    pageInfo {{
+      hasNextPage
+      endCursor
+    }}
+  }}
+}}
+${{INDEXED_EVENT_FIELDS}}`;
+"#,
+        name = contract_name
+    )
+}
+
+/// TypeScript property key for a JSON key, quoted unless it is an identifier
+pub fn typescript_key(key: &str) -> String {
+    let is_identifier = !key.is_empty()
+        && !key.starts_with(|c: char| c.is_ascii_digit())
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if is_identifier {
+        key.to_string()
+    } else {
+        format!("'{}'", key.replace('\\', "\\\\").replace('\'', "\\'"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_provenance_header() {
+        let provenance = Provenance::new(&[("erc20.json", b"[]")]);
+        assert_eq!(provenance.hash, format!("{:x}", md5::compute(b"[]")));
+
+        let header = provenance.header(Path::new("client/mod.rs")).unwrap();
+        assert!(header.starts_with("// @generated by almanac codegen "));
+        assert!(header.contains(" from erc20.json (md5 "));
+        assert!(provenance.header(Path::new("up.sql")).unwrap().starts_with("-- @generated"));
+        assert_eq!(provenance.header(Path::new("schema.json")), None);
+
+        // The hash covers every input
+        let proxy = Provenance::new(&[("proxy.json", b"[]"), ("v1.json", b"[{}]")]);
+        assert_eq!(proxy.inputs, vec!["proxy.json", "v1.json"]);
+        assert_ne!(proxy.hash, provenance.hash);
+        // Inputs are delimited by their lengths
+        let shifted = Provenance::new(&[("proxy.json", b"[]["), ("v1.json", b"{}]")]);
+        assert_ne!(shifted.hash, proxy.hash);
+    }
+
+    #[test]
+    fn test_check_drift() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("mod.rs"), "current").unwrap();
+        std::fs::write(dir.path().join("types.rs"), "stale").unwrap();
+        std::fs::write(dir.path().join("202401010000_token_events.up.sql"), "old").unwrap();
+        std::fs::write(dir.path().join("202402010000_token_events.up.sql"), "create").unwrap();
+
+        let file = |name: &str, content: &str| GeneratedFile {
+            path: dir.path().join(name),
+            content: content.to_string(),
+        };
+        let files = vec![
+            file("mod.rs", "current"),
+            file("types.rs", "fresh"),
+            file("handlers.rs", "new"),
+            // Compared with the latest version on disk
+            file("202406010000_token_events.up.sql", "create"),
+        ];
+        assert_eq!(
+            check_drift(&files).unwrap(),
+            vec![
+                Drift::Changed(dir.path().join("types.rs")),
+                Drift::Missing(dir.path().join("handlers.rs")),
+            ]
+        );
+
+        // Generated files that are no longer generated are stale, other files are left alone
+        let header = Provenance::new(&[("erc20.json", b"[]")]).header(Path::new("mod.rs")).unwrap();
+        std::fs::create_dir_all(dir.path().join("vault")).unwrap();
+        std::fs::write(dir.path().join("vault/mod.rs"), format!("{}pub mod client;\n", header)).unwrap();
+        std::fs::write(dir.path().join("approval_handler.rs"), format!("{}// removed event\n", header)).unwrap();
+        std::fs::write(dir.path().join("lib.rs"), "pub mod contracts;\n").unwrap();
+        std::fs::write(dir.path().join("202401010000_token_events.up.sql"), format!("{}old", header)).unwrap();
+        assert_eq!(
+            check_drift(&files).unwrap(),
+            vec![
+                Drift::Changed(dir.path().join("types.rs")),
+                Drift::Missing(dir.path().join("handlers.rs")),
+                Drift::Stale(dir.path().join("approval_handler.rs")),
+                Drift::Stale(dir.path().join("vault/mod.rs")),
+            ]
+        );
+        assert_eq!(report_drift(&[]), "✅ Generated code is up to date");
+        assert_eq!(report_drift(&[Drift::Stale(PathBuf::from("a.rs"))]), "  stale: a.rs");
+        assert!(fail_on_drift(&[]).is_ok());
+        assert!(fail_on_drift(&[Drift::Stale(PathBuf::from("a.rs"))]).is_err());
+
+        assert_eq!(unversioned_name("202406010000_token_events.up.sql"), Some("token_events.up.sql"));
+        assert_eq!(unversioned_name("token_events.up.sql"), None);
+        assert_eq!(unversioned_name("_events.sql"), None);
+    }
+
+    #[test]
+    fn test_template_names() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("client")).unwrap();
+        std::fs::write(dir.path().join("client/mod.rs.hbs"), "").unwrap();
+        std::fs::write(dir.path().join("header.hbs"), "").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "").unwrap();
+
+        let files = collect_template_files(dir.path()).unwrap();
+        let names: Vec<_> = files.iter().map(|path| template_name(dir.path(), path)).collect();
+        assert_eq!(names, vec!["client/mod.rs", "header"]);
+        assert!(is_file_template(&names[0]));
+        assert!(!is_file_template(&names[1]));
+
+        assert_eq!(
+            file_template_names("migrations/900000000000001_token_events.up.sql"),
+            vec!["migrations/900000000000001_token_events.up.sql", "migrations/token_events.up.sql"]
+        );
+        assert_eq!(file_template_names("client/mod.rs"), vec!["client/mod.rs"]);
+    }
+
+    #[cfg(feature = "codegen")]
+    #[test]
+    fn test_reference_templates() {
+        let output = Path::new("generated");
+        let header = Provenance::new(&[("erc20.json", b"[]")]).header(Path::new("client/mod.rs")).unwrap();
+        let files = vec![
+            GeneratedFile {
+                path: output.join("client/mod.rs"),
+                content: format!("{}fn name() -> String {{ format!(\"{{{{}}}}\", 1) }}\n", header),
+            },
+            GeneratedFile {
+                path: output.join("migrations/900000000000001_token_events.up.sql"),
+                content: "CREATE TABLE token_events (id TEXT);\n".to_string(),
+            },
+        ];
+
+        let dir = TempDir::new().unwrap();
+        let paths = write_reference_templates(&files, output, dir.path()).unwrap();
+        assert_eq!(paths, vec![
+            dir.path().join("client/mod.rs.hbs"),
+            dir.path().join("migrations/token_events.up.sql.hbs"),
+        ]);
+
+        // The templates render the generated content without its header
+        let mut handlebars = Handlebars::new();
+        handlebars.register_escape_fn(handlebars::no_escape);
+        let names = register_templates_dir(&mut handlebars, dir.path(), &[]).unwrap();
+        assert_eq!(names, vec!["client/mod.rs", "migrations/token_events.up.sql"]);
+        assert_eq!(
+            handlebars.render("client/mod.rs", &Value::Null).unwrap(),
+            "fn name() -> String { format!(\"{{}}\", 1) }\n"
+        );
+
+        assert_eq!(reference_template("\\{{ x }}"), None);
+    }
+
+    #[test]
+    fn test_migration_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let sql = "CREATE TABLE token_events (id TEXT);";
+        let version = |explicit| migration_version(explicit, dir.path(), "token_events.up.sql", sql);
+
+        assert_eq!(version(Some("202405010000")).unwrap(), "202405010000");
+        assert!(version(Some("v1")).is_err());
+        assert!(version(Some("")).is_err());
+
+        // A new migration is versioned with the current time
+        let first = version(None).unwrap();
+        assert_eq!(first.len(), 14);
+        assert!(first.as_str() > "202404070210");
+
+        // An unchanged migration keeps its version
+        let path = dir.path().join(format!("{}_token_events.up.sql", first));
+        std::fs::write(&path, format!("-- Migration: Event tables\n{}\n", sql)).unwrap();
+        assert_eq!(version(None).unwrap(), first);
+
+        // A changed migration sorts after every existing one
+        std::fs::write(dir.path().join("999999999999999_other_events.up.sql"), "").unwrap();
+        let changed = migration_version(None, dir.path(), "token_events.up.sql", "DROP TABLE token_events;").unwrap();
+        assert_eq!(changed, "1000000000000000");
+    }
+
+    #[test]
+    fn test_identifiers() {
+        assert_eq!(quote_identifier("from"), "\"from\"");
+        assert_eq!(quote_identifier("0xab_events"), "\"0xab_events\"");
+        assert_eq!(quote_identifier("value"), "value");
+
+        assert_eq!(index_name("token_transfer_events", "\"from\""), "idx_token_transfer_events_from");
+        let table = format!("{}_transfer_events", "a".repeat(42));
+        let from = index_name(&table, "from");
+        assert_eq!(from.len(), MAX_IDENTIFIER_LEN);
+        assert_ne!(from, index_name(&table, "to"));
+
+        // Long table names sharing a prefix stay distinct
+        let approval = table_name(&format!("{}_approval_events", "a".repeat(60)));
+        let transfer = table_name(&format!("{}_transfer_events", "a".repeat(60)));
+        assert_eq!(approval.len(), MAX_IDENTIFIER_LEN);
+        assert_ne!(approval, transfer);
+        assert_eq!(table_name("0xab_transfer_events"), "\"0xab_transfer_events\"");
+    }
+
+    #[test]
+    fn test_column_name() {
+        assert_eq!(column_name("", 1, &["id"], "param_"), "arg1");
+        assert_eq!(column_name("_tokenId", 0, &["id"], "param_"), "token_id");
+        assert_eq!(column_name("from", 0, &["id"], "param_"), "\"from\"");
+        assert_eq!(column_name("id", 0, &["id"], "attr_"), "attr_id");
+        assert_eq!(column_name(&"x".repeat(70), 0, &[], "").len(), MAX_IDENTIFIER_LEN);
+    }
+
+    #[test]
+    fn test_typescript_key() {
+        assert_eq!(typescript_key("amount"), "amount");
+        assert_eq!(typescript_key("_owner"), "_owner");
+        assert_eq!(typescript_key("token-id"), "'token-id'");
+        assert_eq!(typescript_key("0x"), "'0x'");
+        assert_eq!(typescript_key(""), "''");
+    }
+}
@@ -29,6 +29,17 @@ pub trait Event: Send + Sync + std::fmt::Debug {
     /// Raw event data as bytes
     fn raw_data(&self) -> &[u8];
     
+    /// Position of this event within its block, if the source reports one
+    fn log_index(&self) -> Option<u32> {
+        None
+    }
+    
+    /// Fields decoded with the contract schema registered for the emitting
+    /// contract, if the event was decoded on ingestion
+    fn decoded(&self) -> Option<&serde_json::Map<String, serde_json::Value>> {
+        None
+    }
+    
     /// Get the event as Any for downcasting
     fn as_any(&self) -> &dyn Any;
 }
@@ -45,6 +56,9 @@ pub struct UnifiedEvent {
     pub event_type: String,
     pub event_data: EventData,
     pub raw_data: Vec<u8>,
+    /// Event data decoded with the contract schema registered for the
+    /// emitting contract, keyed by field name
+    pub decoded: Option<serde_json::Map<String, serde_json::Value>>,
 }
 
 /// Standardized event data across different chains
@@ -55,6 +69,8 @@ pub enum EventData {
         topics: Vec<String>,
         data: String,
         address: String,
+        /// Position of the log within its block
+        log_index: u32,
     },
     /// Cosmos-style events with attributes
     Cosmos {
@@ -147,11 +163,63 @@ impl Event for UnifiedEvent {
         &self.raw_data
     }
     
+    fn log_index(&self) -> Option<u32> {
+        match &self.event_data {
+            EventData::Evm { log_index, .. } => Some(*log_index),
+            _ => None,
+        }
+    }
+    
+    fn decoded(&self) -> Option<&serde_json::Map<String, serde_json::Value>> {
+        self.decoded.as_ref()
+    }
+    
     fn as_any(&self) -> &dyn Any {
         self
     }
 }
 
+impl UnifiedEvent {
+    /// Convert an event from any source into a unified event
+    ///
+    /// Unified events are cloned as they are. Other events keep their
+    /// metadata and decoded fields and expose their raw data, when it is a
+    /// JSON object, as generic attributes, along with their log index if they
+    /// report one.
+    pub fn from_event(event: &dyn Event) -> Self {
+        if let Some(unified) = event.as_any().downcast_ref::<UnifiedEvent>() {
+            return unified.clone();
+        }
+
+        let mut attributes = std::collections::HashMap::new();
+        if let Ok(serde_json::Value::Object(fields)) = serde_json::from_slice(event.raw_data()) {
+            for (key, value) in fields {
+                let value = match value {
+                    serde_json::Value::String(value) => value,
+                    value => value.to_string(),
+                };
+                attributes.insert(key, value);
+            }
+        }
+        if let Some(log_index) = event.log_index() {
+            attributes.insert("log_index".to_string(), log_index.to_string());
+        }
+
+        Self {
+            id: event.id().to_string(),
+            chain: event.chain().to_string(),
+            block_number: event.block_number(),
+            block_hash: event.block_hash().to_string(),
+            tx_hash: event.tx_hash().to_string(),
+            timestamp: event.timestamp(),
+            event_type: event.event_type().to_string(),
+            event_data: EventData::Generic { attributes },
+            raw_data: event.raw_data().to_vec(),
+            decoded: event.decoded().cloned(),
+        }
+    }
+}
+
 /// Event mapping utilities
 pub mod mapping {
     /// Normalize event type across chains
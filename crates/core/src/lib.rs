@@ -235,6 +235,7 @@ pub mod data_export;
 pub mod backup_restore;
 pub mod archival;
 pub mod compression;
+pub mod codegen;
 
 #[cfg(test)]
 mod tests {
@@ -2,8 +2,11 @@
 ///
 /// This module contains the pipeline abstractions for processing blockchain events,
 /// including service traits and registries.
+use crate::event::{Event, EventData, UnifiedEvent};
 use crate::{Error, Result};
 use async_trait::async_trait;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
 use std::sync::Arc;
 
 /// A service that processes events from a blockchain
@@ -74,4 +77,366 @@ impl EventServiceRegistry {
         }
         chains
     }
-} 
\ No newline at end of file
+} 
+
+/// Handles the events of a single contract
+///
+/// Contract code generation emits one implementation per contract, which
+/// decodes the contract's events into the generated types and writes them to
+/// the generated tables.
+#[async_trait]
+pub trait EventHandler: Send + Sync + 'static {
+    /// Name of the handler, for logging
+    fn name(&self) -> &str;
+
+    /// Handle an event emitted by the handler's contract, returning whether
+    /// the handler recognised it
+    async fn handle_event(&self, event: &dyn Event) -> Result<bool>;
+
+    /// Undo what the handler stored from the blocks of `chain` at or after
+    /// `from_block`, after the chain reorganized from that block
+    async fn rollback(&self, _chain: &str, _from_block: u64) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Decodes the fields of events as they are ingested, before they are
+/// stored
+#[async_trait]
+pub trait EventDecoder: Send + Sync + 'static {
+    /// Fields of an event keyed by name, or `None` when the decoder has no
+    /// schema for it
+    async fn decode(&self, event: &UnifiedEvent) -> Result<Option<Map<String, Value>>>;
+
+    /// Forget what the decoder learned from the blocks of `chain` at or
+    /// after `from_block`, after the chain reorganized from that block
+    async fn rollback(&self, _chain: &str, _from_block: u64) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A registry of event handlers keyed by chain and contract address
+#[derive(Clone, Default)]
+pub struct EventHandlerRegistry {
+    handlers: HashMap<(String, String), Vec<Arc<dyn EventHandler>>>,
+}
+
+impl std::fmt::Debug for EventHandlerRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map()
+            .entries(self.handlers.iter().map(|(key, handlers)| {
+                (key, handlers.iter().map(|handler| handler.name()).collect::<Vec<_>>())
+            }))
+            .finish()
+    }
+}
+
+impl EventHandlerRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a handler for the events `contract_address` emits on `chain_id`
+    ///
+    /// Addresses are compared case-insensitively, so checksummed EVM
+    /// addresses match their lowercase form.
+    pub fn register(&mut self, chain_id: impl Into<String>, contract_address: impl AsRef<str>, handler: Arc<dyn EventHandler>) {
+        let key = (chain_id.into(), contract_address.as_ref().to_lowercase());
+        self.handlers.entry(key).or_default().push(handler);
+    }
+
+    /// Handlers registered for a contract
+    pub fn handlers(&self, chain_id: &str, contract_address: &str) -> &[Arc<dyn EventHandler>] {
+        self.handlers
+            .get(&(chain_id.to_string(), contract_address.to_lowercase()))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Number of registered handlers
+    pub fn len(&self) -> usize {
+        self.handlers.values().map(Vec::len).sum()
+    }
+
+    /// Whether no handlers are registered
+    pub fn is_empty(&self) -> bool {
+        self.handlers.is_empty()
+    }
+
+    /// Roll back the handlers registered on `chain_id` from `from_block`
+    pub async fn rollback(&self, chain_id: &str, from_block: u64) -> Result<()> {
+        let handlers = self.handlers.iter().filter(|((chain, _), _)| chain == chain_id).flat_map(|(_, handlers)| handlers);
+        for handler in handlers {
+            handler.rollback(chain_id, from_block).await?;
+        }
+        Ok(())
+    }
+
+    /// Pass an event to the handlers of the contract that emitted it,
+    /// returning whether any of them recognised it
+    ///
+    /// Handlers always receive a [`UnifiedEvent`], events of other types are
+    /// converted with [`UnifiedEvent::from_event`] first.
+    pub async fn dispatch(&self, event: &dyn Event) -> Result<bool> {
+        match event.as_any().downcast_ref::<UnifiedEvent>() {
+            Some(unified) => self.dispatch_unified(unified).await,
+            None => self.dispatch_unified(&UnifiedEvent::from_event(event)).await,
+        }
+    }
+
+    /// Pass a unified event to the handlers of its contract
+    async fn dispatch_unified(&self, event: &UnifiedEvent) -> Result<bool> {
+        let Some(contract_address) = event_contract_address(event) else {
+            return Ok(false);
+        };
+
+        let mut handled = false;
+        for handler in self.handlers(event.chain(), &contract_address) {
+            handled |= handler.handle_event(event).await?;
+        }
+        Ok(handled)
+    }
+}
+
+/// Attribute of an event's structured data
+///
+/// EVM events expose `contract_address`, `data`, `log_index` and
+/// `topic0`..`topicN`, Cosmos and generic events their attributes.
+pub fn event_attribute(event: &dyn Event, key: &str) -> Option<String> {
+    match &event.as_any().downcast_ref::<UnifiedEvent>()?.event_data {
+        EventData::Evm { topics, data, address, log_index } => match key {
+            "contract_address" => Some(address.clone()),
+            "data" => Some(data.clone()),
+            "log_index" => Some(log_index.to_string()),
+            _ => key
+                .strip_prefix("topic")
+                .and_then(|index| index.parse::<usize>().ok())
+                .and_then(|index| topics.get(index).cloned()),
+        },
+        EventData::Cosmos { attributes, .. } => attributes
+            .iter()
+            .find(|attribute| attribute.key == key)
+            .map(|attribute| attribute.value.clone()),
+        EventData::Generic { attributes } => attributes.get(key).cloned(),
+    }
+}
+
+/// Address of the contract that emitted an event, if known
+///
+/// CosmWasm events carry the emitting contract as `_contract_address`.
+pub fn event_contract_address(event: &dyn Event) -> Option<String> {
+    event_attribute(event, "contract_address").or_else(|| event_attribute(event, "_contract_address"))
+}
+
+/// Position of an event within its block
+///
+/// EVM events carry the log index of the log they were built from, Cosmos
+/// and generic events a `log_index` or `event_index` attribute. Events
+/// without one are rejected rather than given a made-up index, which would
+/// collide with other events of the same transaction.
+pub fn event_index(event: &dyn Event) -> Result<u32> {
+    if let Some(index) = event.log_index() {
+        return Ok(index);
+    }
+
+    let attribute = event_attribute(event, "log_index")
+        .or_else(|| event_attribute(event, "event_index"))
+        .ok_or_else(|| Error::invalid_data(format!("Event {} on {} has no log index", event.id(), event.chain())))?;
+    attribute.parse().map_err(|_| {
+        Error::invalid_data(format!("Event {} on {} has an invalid log index: {}", event.id(), event.chain(), attribute))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::EventAttribute;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::SystemTime;
+
+    #[derive(Default)]
+    struct CountingHandler {
+        handled: AtomicUsize,
+        rolled_back: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl EventHandler for CountingHandler {
+        fn name(&self) -> &str {
+            "counting"
+        }
+
+        async fn handle_event(&self, event: &dyn Event) -> Result<bool> {
+            assert!(event.as_any().downcast_ref::<UnifiedEvent>().is_some());
+            self.handled.fetch_add(1, Ordering::SeqCst);
+            Ok(true)
+        }
+
+        async fn rollback(&self, _chain: &str, _from_block: u64) -> Result<()> {
+            self.rolled_back.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    /// Event as read back from storage, with only metadata and raw data
+    #[derive(Debug)]
+    struct StoredEvent {
+        raw_data: Vec<u8>,
+    }
+
+    impl Event for StoredEvent {
+        fn id(&self) -> &str {
+            "0xdef:5"
+        }
+
+        fn chain(&self) -> &str {
+            "1"
+        }
+
+        fn block_number(&self) -> u64 {
+            2
+        }
+
+        fn block_hash(&self) -> &str {
+            "0x02"
+        }
+
+        fn tx_hash(&self) -> &str {
+            "0xdef"
+        }
+
+        fn timestamp(&self) -> SystemTime {
+            SystemTime::UNIX_EPOCH
+        }
+
+        fn event_type(&self) -> &str {
+            "Transfer"
+        }
+
+        fn raw_data(&self) -> &[u8] {
+            &self.raw_data
+        }
+
+        fn log_index(&self) -> Option<u32> {
+            Some(5)
+        }
+
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    fn event(id: &str, chain: &str, event_data: EventData) -> UnifiedEvent {
+        UnifiedEvent {
+            id: id.to_string(),
+            chain: chain.to_string(),
+            block_number: 1,
+            block_hash: "0x01".to_string(),
+            tx_hash: "0xabc".to_string(),
+            timestamp: SystemTime::UNIX_EPOCH,
+            event_type: "Transfer".to_string(),
+            event_data,
+            raw_data: Vec::new(),
+            decoded: None,
+        }
+    }
+
+    fn evm(address: &str) -> EventData {
+        EventData::Evm {
+            topics: vec!["0xddf252ad".to_string()],
+            data: "0x".to_string(),
+            address: address.to_string(),
+            log_index: 3,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_by_chain_and_contract() {
+        let handler = Arc::new(CountingHandler::default());
+        let mut registry = EventHandlerRegistry::new();
+        registry.register("1", "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48", handler.clone());
+        assert_eq!(registry.len(), 1);
+
+        let matching = event("0xabc:3", "1", evm("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"));
+        assert!(registry.dispatch(&matching).await.unwrap());
+
+        let other_chain = event("0xabc:3", "137", evm("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"));
+        assert!(!registry.dispatch(&other_chain).await.unwrap());
+
+        let other_contract = event("0xabc:3", "1", evm("0x0000000000000000000000000000000000000001"));
+        assert!(!registry.dispatch(&other_contract).await.unwrap());
+
+        assert_eq!(handler.handled.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rollback_by_chain() {
+        let ethereum = Arc::new(CountingHandler::default());
+        let polygon = Arc::new(CountingHandler::default());
+        let mut registry = EventHandlerRegistry::new();
+        registry.register("1", "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48", ethereum.clone());
+        registry.register("137", "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48", polygon.clone());
+
+        registry.rollback("1", 10).await.unwrap();
+        assert_eq!(ethereum.rolled_back.load(Ordering::SeqCst), 1);
+        assert_eq!(polygon.rolled_back.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_converts_events() {
+        let handler = Arc::new(CountingHandler::default());
+        let mut registry = EventHandlerRegistry::new();
+        registry.register("1", "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48", handler.clone());
+
+        let stored = StoredEvent {
+            raw_data: br#"{"contract_address":"0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48","value":1000}"#.to_vec(),
+        };
+        let unified = UnifiedEvent::from_event(&stored);
+        assert_eq!(unified.id, "0xdef:5");
+        assert_eq!(event_attribute(&unified, "value").as_deref(), Some("1000"));
+        assert_eq!(event_index(&unified).unwrap(), 5);
+
+        assert!(registry.dispatch(&stored).await.unwrap());
+        assert_eq!(handler.handled.load(Ordering::SeqCst), 1);
+
+        // Raw data that is not a JSON object names no contract
+        let opaque = StoredEvent { raw_data: vec![0xde, 0xad] };
+        assert!(!registry.dispatch(&opaque).await.unwrap());
+        assert_eq!(handler.handled.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_event_contract_address_and_index() {
+        let cosmos = event(
+            "ABC-7",
+            "neutron-1",
+            EventData::Cosmos {
+                attributes: vec![EventAttribute {
+                    key: "_contract_address".to_string(),
+                    value: "neutron1contract".to_string(),
+                    index: false,
+                }],
+                module: "wasm".to_string(),
+            },
+        );
+        assert_eq!(event_contract_address(&cosmos).as_deref(), Some("neutron1contract"));
+        // The ID suffix is not a log index
+        assert!(event_index(&cosmos).is_err());
+
+        let log = event("0xabc:3", "1", evm("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"));
+        assert_eq!(event_index(&log).unwrap(), 3);
+        assert_eq!(event_attribute(&log, "log_index").as_deref(), Some("3"));
+
+        let mut attributes = HashMap::new();
+        attributes.insert("log_index".to_string(), "12".to_string());
+        let generic = event("no-index", "1", EventData::Generic { attributes });
+        assert_eq!(event_contract_address(&generic), None);
+        assert_eq!(event_index(&generic).unwrap(), 12);
+
+        let mut attributes = HashMap::new();
+        attributes.insert("event_index".to_string(), "first".to_string());
+        let invalid = event("invalid-index", "1", EventData::Generic { attributes });
+        assert!(event_index(&invalid).is_err());
+    }
+}
@@ -2,8 +2,9 @@
 
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use std::collections::VecDeque;
-use std::sync::Arc;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
 
 use crate::types::ChainId;
 use crate::Result;
@@ -244,4 +245,250 @@ impl ChainReorgTracker {
             canonical_tip: new_block.clone(),
         })
     }
-} 
\ No newline at end of file
+} 
+
+/// Source of the canonical block headers of a chain
+#[async_trait]
+pub trait BlockHeaderSource: Send + Sync + 'static {
+    /// Number of the latest block
+    async fn latest_block_number(&self) -> Result<u64>;
+
+    /// Header of the canonical block at `number`, with its parent hash
+    async fn block_header(&self, number: u64) -> Result<CanonicalBlock>;
+
+    /// Header of the latest finalized block, `None` for chains that don't
+    /// report finality
+    async fn finalized_block(&self) -> Result<Option<CanonicalBlock>> {
+        Ok(None)
+    }
+}
+
+/// Reorg detector fed with the blocks events are ingested from and the
+/// headers polled from the chain
+///
+/// A reorganization is detected when a block arrives with a different hash
+/// than the one recorded at its height. The recorded blocks from that height
+/// on are announced as reorganized to every subscriber. Events carry the
+/// number and hash of their block but not its parent, headers also carry the
+/// parent hash, which [`Self::extends_recorded`] checks so that the headers
+/// of replaced blocks without events can be fed in as well. Blocks are
+/// recorded up to `max_depth` below the highest one seen.
+pub struct BlockHashReorgDetector {
+    /// Chain identifier
+    chain_id: ChainId,
+
+    /// Configuration for reorganization handling
+    config: ReorgConfig,
+
+    /// Recorded blocks by number
+    blocks: Mutex<BTreeMap<u64, CanonicalBlock>>,
+
+    /// Most recently detected reorganization
+    last_reorg: Mutex<Option<ReorgEvent>>,
+
+    /// Sender announcing detected reorganizations to subscribers
+    sender: broadcast::Sender<ReorgEvent>,
+}
+
+impl BlockHashReorgDetector {
+    /// Create a detector for a chain
+    pub fn new(chain_id: ChainId, config: ReorgConfig) -> Self {
+        let (sender, _) = broadcast::channel(100);
+        Self {
+            chain_id,
+            config,
+            blocks: Mutex::new(BTreeMap::new()),
+            last_reorg: Mutex::new(None),
+            sender,
+        }
+    }
+
+    /// Record a block, announcing a reorganization when it replaces a
+    /// different block at the same height
+    ///
+    /// The parent hash of the block is not used, see [`Self::extends_recorded`].
+    pub fn observe_block(&self, block: CanonicalBlock) -> Option<ReorgEvent> {
+        let reorg = self.detect_block(block);
+        if let Some(ref reorg) = reorg {
+            self.announce(reorg);
+        }
+        reorg
+    }
+
+    /// Record a block like [`Self::observe_block`] without announcing the
+    /// reorganization, so it can be handled before [`Self::announce`]
+    pub fn detect_block(&self, block: CanonicalBlock) -> Option<ReorgEvent> {
+        let mut blocks = self.blocks.lock().unwrap_or_else(|e| e.into_inner());
+
+        let reorg = match blocks.get(&block.number) {
+            Some(recorded) if recorded.hash != block.hash => {
+                let tip = blocks.keys().next_back().copied().unwrap_or(block.number);
+                let detected_at = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs();
+                let reorganized_blocks = blocks
+                    .split_off(&block.number)
+                    .into_values()
+                    .map(|old_block| ReorganizedBlock {
+                        depth: tip - old_block.number + 1,
+                        old_block,
+                        new_block: block.clone(),
+                        detected_at,
+                    })
+                    .collect();
+                Some(ReorgEvent {
+                    chain_id: self.chain_id.clone(),
+                    reorganized_blocks,
+                    canonical_tip: block.clone(),
+                })
+            }
+            _ => None,
+        };
+
+        // Keep the blocks within the reorg depth of the highest one
+        let number = block.number;
+        blocks.insert(number, block);
+        if let Some(&tip) = blocks.keys().next_back() {
+            let cutoff = tip.saturating_sub(self.config.max_depth);
+            *blocks = blocks.split_off(&cutoff);
+        }
+        reorg
+    }
+
+    /// Announce a reorganization to every subscriber
+    pub fn announce(&self, reorg: &ReorgEvent) {
+        *self.last_reorg.lock().unwrap_or_else(|e| e.into_inner()) = Some(reorg.clone());
+        // Having no subscribers is not an error
+        let _ = self.sender.send(reorg.clone());
+    }
+
+    /// Whether the parent of a block header is the recorded block below it
+    ///
+    /// Headers without a parent hash, or whose parent is not recorded, are
+    /// taken to extend the recorded chain.
+    pub fn extends_recorded(&self, block: &CanonicalBlock) -> bool {
+        let blocks = self.blocks.lock().unwrap_or_else(|e| e.into_inner());
+        match block.number.checked_sub(1).and_then(|parent| blocks.get(&parent)) {
+            Some(parent) if !block.parent_hash.is_empty() => parent.hash == block.parent_hash,
+            _ => true,
+        }
+    }
+}
+
+#[async_trait]
+impl ReorgDetector for BlockHashReorgDetector {
+    async fn start(&self) -> Result<()> {
+        // Blocks are fed in by the ingestion, there is nothing to poll
+        Ok(())
+    }
+
+    async fn stop(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn chain_id(&self) -> &ChainId {
+        &self.chain_id
+    }
+
+    async fn check_reorg(&self, from_block: u64) -> Result<Option<ReorgEvent>> {
+        let last_reorg = self.last_reorg.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(last_reorg.clone().filter(|reorg| {
+            reorg.reorganized_blocks.iter().any(|b| b.old_block.number >= from_block)
+        }))
+    }
+
+    async fn subscribe(&self) -> Result<Box<dyn ReorgSubscription>> {
+        Ok(Box::new(BroadcastReorgSubscription {
+            receiver: self.sender.subscribe(),
+        }))
+    }
+
+    async fn set_config(&mut self, config: ReorgConfig) -> Result<()> {
+        self.config = config;
+        Ok(())
+    }
+
+    fn config(&self) -> &ReorgConfig {
+        &self.config
+    }
+}
+
+/// Subscription to the reorganizations announced by a [`BlockHashReorgDetector`]
+struct BroadcastReorgSubscription {
+    receiver: broadcast::Receiver<ReorgEvent>,
+}
+
+#[async_trait]
+impl ReorgSubscription for BroadcastReorgSubscription {
+    async fn next(&mut self) -> Option<ReorgEvent> {
+        loop {
+            match self.receiver.recv().await {
+                Ok(reorg) => return Some(reorg),
+                // Missed reorganizations are skipped rather than ending the subscription
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    async fn close(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(number: u64, hash: &str) -> CanonicalBlock {
+        CanonicalBlock {
+            number,
+            hash: hash.to_string(),
+            parent_hash: String::new(),
+            timestamp: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_block_hash_reorg_detector() {
+        let detector = BlockHashReorgDetector::new(ChainId::from("ethereum"), ReorgConfig::default());
+        let mut subscription = detector.subscribe().await.unwrap();
+
+        for number in 100..=102 {
+            assert!(detector.observe_block(block(number, &format!("0x{}", number))).is_none());
+        }
+        // Further events of a recorded block are not a reorganization
+        assert!(detector.observe_block(block(101, "0x101")).is_none());
+
+        let reorg = detector.observe_block(block(101, "0x101b")).unwrap();
+        let replaced: Vec<(u64, u64)> = reorg.reorganized_blocks.iter()
+            .map(|b| (b.old_block.number, b.depth))
+            .collect();
+        assert_eq!(replaced, vec![(101, 2), (102, 1)]);
+        assert_eq!(reorg.canonical_tip.hash, "0x101b");
+
+        let announced = subscription.next().await.unwrap();
+        assert_eq!(announced.reorganized_blocks.len(), 2);
+        assert!(detector.check_reorg(101).await.unwrap().is_some());
+        assert!(detector.check_reorg(103).await.unwrap().is_none());
+
+        // The replacement is now the recorded block
+        assert!(detector.observe_block(block(101, "0x101b")).is_none());
+        assert!(detector.observe_block(block(102, "0x102b")).is_none());
+    }
+
+    #[test]
+    fn test_extends_recorded() {
+        let detector = BlockHashReorgDetector::new(ChainId::from("ethereum"), ReorgConfig::default());
+        detector.observe_block(block(100, "0x100"));
+
+        let header = |parent_hash: &str| CanonicalBlock { parent_hash: parent_hash.to_string(), ..block(101, "0x101") };
+        assert!(detector.extends_recorded(&header("0x100")));
+        assert!(!detector.extends_recorded(&header("0x100b")));
+        // Nothing to compare against
+        assert!(detector.extends_recorded(&header("")));
+        assert!(detector.extends_recorded(&CanonicalBlock { parent_hash: "0x98".to_string(), ..block(99, "0x99") }));
+        assert!(detector.extends_recorded(&block(0, "0x0")));
+    }
+}
@@ -1,6 +1,6 @@
 //! Security and authentication utilities for multi-chain indexing
 
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 
@@ -11,7 +11,29 @@ pub struct RateLimiter {
     /// Time window for rate limiting
     window: Duration,
     /// Request counts per endpoint
-    request_counts: RwLock<HashMap<String, Vec<Instant>>>,
+    request_counts: RwLock<HashMap<String, RequestWindow>>,
+}
+
+/// Requests charged to an endpoint within the current window
+#[derive(Default)]
+struct RequestWindow {
+    /// Time and cost of each request, oldest first
+    requests: VecDeque<(Instant, usize)>,
+    /// Sum of the costs in `requests`
+    total_cost: usize,
+}
+
+impl RequestWindow {
+    /// Drop the requests that fell out of the window
+    fn expire(&mut self, now: Instant, window: Duration) {
+        while let Some(&(time, cost)) = self.requests.front() {
+            if now.duration_since(time) < window {
+                break;
+            }
+            self.requests.pop_front();
+            self.total_cost -= cost;
+        }
+    }
 }
 
 impl RateLimiter {
@@ -24,20 +46,31 @@ impl RateLimiter {
         }
     }
     
+    /// Requests allowed per window
+    pub fn max_requests(&self) -> usize {
+        self.max_requests
+    }
+    
     /// Check if a request is allowed for the given endpoint
     pub async fn is_allowed(&self, endpoint: &str) -> bool {
+        self.is_allowed_with_cost(endpoint, 1).await
+    }
+    
+    /// Check if a request costing `cost` requests is allowed for the given endpoint
+    pub async fn is_allowed_with_cost(&self, endpoint: &str, cost: usize) -> bool {
         let mut counts = self.request_counts.write().await;
         let now = Instant::now();
         
         // Get or create request history for this endpoint
-        let requests = counts.entry(endpoint.to_string()).or_insert_with(Vec::new);
+        let requests = counts.entry(endpoint.to_string()).or_default();
         
         // Remove old requests outside the window
-        requests.retain(|&time| now.duration_since(time) < self.window);
+        requests.expire(now, self.window);
         
-        // Check if we can make another request
-        if requests.len() < self.max_requests {
-            requests.push(now);
+        // Check if the whole cost fits in the remaining budget
+        if requests.total_cost.saturating_add(cost) <= self.max_requests {
+            requests.requests.push_back((now, cost));
+            requests.total_cost += cost;
             true
         } else {
             false
@@ -48,8 +81,8 @@ impl RateLimiter {
     pub async fn time_until_allowed(&self, endpoint: &str) -> Option<Duration> {
         let counts = self.request_counts.read().await;
         if let Some(requests) = counts.get(endpoint) {
-            if requests.len() >= self.max_requests {
-                if let Some(&oldest) = requests.first() {
+            if requests.total_cost >= self.max_requests {
+                if let Some(&(oldest, _)) = requests.requests.front() {
                     let elapsed = Instant::now().duration_since(oldest);
                     if elapsed < self.window {
                         return Some(self.window - elapsed);
@@ -254,4 +287,35 @@ impl Default for CredentialStore {
     fn default() -> Self {
         Self::new()
     }
-} 
\ No newline at end of file
+} 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[tokio::test]
+    async fn test_rate_limiter_charges_costs() {
+        let limiter = RateLimiter::new(100, Duration::from_secs(60));
+        
+        assert!(limiter.is_allowed_with_cost("client", 60).await);
+        assert!(!limiter.is_allowed_with_cost("client", 41).await);
+        assert!(limiter.is_allowed_with_cost("client", 40).await);
+        assert!(!limiter.is_allowed("client").await);
+        assert!(limiter.time_until_allowed("client").await.is_some());
+        
+        // Costs are tracked per endpoint and a huge cost never overflows
+        assert!(limiter.is_allowed("other").await);
+        assert!(!limiter.is_allowed_with_cost("other", usize::MAX).await);
+    }
+    
+    #[tokio::test]
+    async fn test_rate_limiter_window_expires() {
+        let limiter = RateLimiter::new(10, Duration::from_millis(20));
+        
+        assert!(limiter.is_allowed_with_cost("client", 10).await);
+        assert!(!limiter.is_allowed("client").await);
+        
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(limiter.is_allowed_with_cost("client", 10).await);
+    }
+}
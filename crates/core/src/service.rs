@@ -2,7 +2,7 @@ use async_trait::async_trait;
 use std::sync::Arc;
 
 use crate::event::Event;
-use crate::types::{ChainId, EventFilter};
+use crate::types::{ChainId, EventFilter, EventPage};
 use crate::{BlockStatus, Result};
 
 /// Define common interfaces for chain services
@@ -25,6 +25,11 @@ pub trait EventService: Send + Sync {
     /// Get the chain ID
     fn chain_id(&self) -> &ChainId;
 
+    /// Chains the service serves events of, starting with its own
+    fn chain_ids(&self) -> Vec<ChainId> {
+        vec![self.chain_id().clone()]
+    }
+
     /// Get the latest block with the specified status
     async fn get_latest_block_with_status(&self, _chain: &str, _status: BlockStatus) -> Result<u64> {
         self.get_latest_block().await
@@ -38,6 +43,21 @@ pub trait EventService: Send + Sync {
     /// Get events matching the given filters
     async fn get_events(&self, filters: Vec<EventFilter>) -> Result<Vec<Box<dyn Event>>>;
 
+    /// Get a page of the events matching a filter in (chain, block, log
+    /// index) order
+    ///
+    /// The default implementation orders the result of `get_events` in
+    /// memory; services backed by storage push the page down instead.
+    async fn get_events_page(&self, filter: EventFilter, page: EventPage) -> Result<Vec<Box<dyn Event>>> {
+        Ok(page.apply(self.get_events(vec![filter]).await?))
+    }
+
+    /// Number of events matching a filter, or `None` when the service cannot
+    /// count them without reading them all
+    async fn count_events(&self, _filter: EventFilter) -> Result<Option<u64>> {
+        Ok(None)
+    }
+
     /// Subscribe to new events
     async fn subscribe(&self) -> Result<Box<dyn EventSubscription>>;
 
@@ -51,6 +71,11 @@ pub trait BoxedEventServiceTrait: Send + Sync {
     /// Get the chain ID
     fn chain_id(&self) -> &ChainId;
 
+    /// Chains the service serves events of, starting with its own
+    fn chain_ids(&self) -> Vec<ChainId> {
+        vec![self.chain_id().clone()]
+    }
+
     /// Get the latest block with the specified status
     async fn get_latest_block_with_status(&self, _chain: &str, _status: BlockStatus) -> Result<u64> {
         self.get_latest_block().await
@@ -64,6 +89,21 @@ pub trait BoxedEventServiceTrait: Send + Sync {
     /// Get events matching the given filters
     async fn get_events(&self, filters: Vec<EventFilter>) -> Result<Vec<Box<dyn Event>>>;
 
+    /// Get a page of the events matching a filter in (chain, block, log
+    /// index) order
+    ///
+    /// The default implementation orders the result of `get_events` in
+    /// memory; services backed by storage push the page down instead.
+    async fn get_events_page(&self, filter: EventFilter, page: EventPage) -> Result<Vec<Box<dyn Event>>> {
+        Ok(page.apply(self.get_events(vec![filter]).await?))
+    }
+
+    /// Number of events matching a filter, or `None` when the service cannot
+    /// count them without reading them all
+    async fn count_events(&self, _filter: EventFilter) -> Result<Option<u64>> {
+        Ok(None)
+    }
+
     /// Subscribe to new events
     async fn subscribe(&self) -> Result<Box<dyn EventSubscription>>;
 
@@ -94,6 +134,10 @@ where
         self.service.chain_id()
     }
 
+    fn chain_ids(&self) -> Vec<ChainId> {
+        self.service.chain_ids()
+    }
+
     async fn get_latest_block_with_status(&self, chain: &str, status: BlockStatus) -> Result<u64> {
         self.service.get_latest_block_with_status(chain, status).await
     }
@@ -106,6 +150,14 @@ where
         self.service.get_events(filters).await
     }
 
+    async fn get_events_page(&self, filter: EventFilter, page: EventPage) -> Result<Vec<Box<dyn Event>>> {
+        self.service.get_events_page(filter, page).await
+    }
+
+    async fn count_events(&self, filter: EventFilter) -> Result<Option<u64>> {
+        self.service.count_events(filter).await
+    }
+
     async fn subscribe(&self) -> Result<Box<dyn EventSubscription>> {
         self.service.subscribe().await
     }
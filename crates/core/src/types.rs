@@ -578,6 +578,97 @@ impl Default for EventFilter {
     }
 }
 
+/// Position of an event in the (chain, block, log index) order that event
+/// pages follow
+///
+/// Events without a log index, such as Cosmos and generic events, come
+/// first in their block, ordered by transaction and ID.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct EventPosition {
+    /// Chain the event was emitted on
+    pub chain: String,
+    
+    /// Block the event was included in
+    pub block_number: u64,
+    
+    /// Position of the event within its block, if known
+    pub log_index: Option<u32>,
+    
+    /// Transaction the event was emitted in
+    pub tx_hash: String,
+    
+    /// ID of the event
+    pub id: String,
+}
+
+impl EventPosition {
+    /// Position of an event
+    pub fn of(event: &dyn crate::event::Event) -> Self {
+        Self {
+            chain: event.chain().to_string(),
+            block_number: event.block_number(),
+            log_index: crate::pipeline::event_index(event).ok(),
+            tx_hash: event.tx_hash().to_string(),
+            id: event.id().to_string(),
+        }
+    }
+    
+    /// Position before every event of a block
+    pub fn block_start(chain: &str, block_number: u64) -> Self {
+        Self {
+            chain: chain.to_string(),
+            block_number,
+            log_index: None,
+            tx_hash: String::new(),
+            id: String::new(),
+        }
+    }
+}
+
+/// Keyset page of events in [`EventPosition`] order
+///
+/// Pages are bounded by the positions of events the client has already
+/// seen rather than by an offset, so events indexed while a client pages
+/// through results never shift the pages it has yet to read.
+#[derive(Debug, Clone, Default)]
+pub struct EventPage {
+    /// Only include events after this position
+    pub after: Option<EventPosition>,
+    
+    /// Only include events before this position
+    pub before: Option<EventPosition>,
+    
+    /// Maximum number of events to return
+    pub limit: usize,
+    
+    /// Take the events closest to `before` rather than those closest to
+    /// `after`; the page itself is still returned in ascending order
+    pub from_end: bool,
+}
+
+impl EventPage {
+    /// Whether a position lies strictly between the page bounds
+    pub fn contains(&self, position: &EventPosition) -> bool {
+        self.after.as_ref().map_or(true, |after| position > after)
+            && self.before.as_ref().map_or(true, |before| position < before)
+    }
+    
+    /// Cut this page out of already filtered events, ordering them in memory
+    ///
+    /// Used by services that cannot push the page down to their storage.
+    pub fn apply(&self, events: Vec<Box<dyn crate::event::Event>>) -> Vec<Box<dyn crate::event::Event>> {
+        let mut positioned: Vec<(EventPosition, Box<dyn crate::event::Event>)> = events
+            .into_iter()
+            .map(|event| (EventPosition::of(event.as_ref()), event))
+            .filter(|(position, _)| self.contains(position))
+            .collect();
+        positioned.sort_by(|(a, _), (b, _)| a.cmp(b));
+        
+        let skip = if self.from_end { positioned.len().saturating_sub(self.limit) } else { 0 };
+        positioned.into_iter().skip(skip).take(self.limit).map(|(_, event)| event).collect()
+    }
+}
+
 /// Calculate Levenshtein distance between two strings
 fn levenshtein_distance(s1: &str, s2: &str) -> u32 {
     let len1 = s1.len();
@@ -680,6 +771,27 @@ pub struct ApiConfig {
     /// Enable WebSocket subscriptions
     pub enable_websocket: bool,
     
+    /// Requests each client may make per minute, REST requests and GraphQL
+    /// query cost alike, or `None` for the server default
+    #[serde(default)]
+    pub rate_limit: Option<u32>,
+    
+    /// Maximum nesting depth of GraphQL queries, or `None` for the default
+    #[serde(default)]
+    pub graphql_max_depth: Option<usize>,
+    
+    /// Maximum complexity of a GraphQL query, or `None` for the default
+    #[serde(default)]
+    pub graphql_max_complexity: Option<usize>,
+    
+    /// Directory of persisted GraphQL queries, one `.graphql` file per query
+    #[serde(default)]
+    pub graphql_persisted_queries: Option<String>,
+    
+    /// Only execute persisted GraphQL queries
+    #[serde(default)]
+    pub graphql_persisted_queries_only: bool,
+    
     /// Additional API configuration parameters
     pub params: HashMap<String, String>,
 }
@@ -1327,4 +1439,55 @@ mod tests {
         assert!(!exclude_filter.matches_event(&event_with_excluded));
         assert!(exclude_filter.matches_event(&event_with_neither));
     }
-} 
\ No newline at end of file
+    
+    #[test]
+    fn test_event_page_in_memory() {
+        use crate::event::{Event, EventData, UnifiedEvent};
+        
+        // Events without a log index are Cosmos events of transaction `tx`
+        let event = |chain: &str, block_number: u64, log_index: Option<u32>, tx: &str| Box::new(UnifiedEvent {
+            id: format!("{}-{}-{}", chain, block_number, log_index.map_or(tx.to_string(), |index| index.to_string())),
+            chain: chain.to_string(),
+            block_number,
+            block_hash: "hash".to_string(),
+            tx_hash: tx.to_string(),
+            timestamp: UNIX_EPOCH,
+            event_type: "transfer".to_string(),
+            event_data: match log_index {
+                Some(log_index) => EventData::Evm {
+                    topics: Vec::new(),
+                    data: "0x".to_string(),
+                    address: "0x01".to_string(),
+                    log_index,
+                },
+                None => EventData::Cosmos { attributes: Vec::new(), module: "bank".to_string() },
+            },
+            raw_data: Vec::new(),
+            decoded: None,
+        }) as Box<dyn Event>;
+        let events = || vec![
+            event("polygon", 1, Some(0), "tx"), event("ethereum", 2, Some(5), "tx"), event("ethereum", 2, None, "tx2"),
+            event("ethereum", 2, Some(1), "tx"), event("ethereum", 1, Some(9), "tx"), event("ethereum", 2, None, "tx1"),
+        ];
+        let ids = |events: Vec<Box<dyn Event>>| events.iter().map(|e| e.id().to_string()).collect::<Vec<_>>();
+        
+        let first = EventPage { limit: 2, ..Default::default() };
+        assert_eq!(ids(first.apply(events())), vec!["ethereum-1-9", "ethereum-2-tx1"]);
+        
+        // Events without a log index come first in their block
+        let after = EventPage {
+            after: Some(EventPosition::of(event("ethereum", 2, None, "tx2").as_ref())),
+            limit: 10,
+            ..Default::default()
+        };
+        assert_eq!(ids(after.apply(events())), vec!["ethereum-2-1", "ethereum-2-5", "polygon-1-0"]);
+        
+        let last = EventPage {
+            before: Some(EventPosition::block_start("polygon", 1)),
+            limit: 2,
+            from_end: true,
+            ..Default::default()
+        };
+        assert_eq!(ids(last.apply(events())), vec!["ethereum-2-1", "ethereum-2-5"]);
+    }
+}
@@ -1,6 +1,7 @@
 //! CLI interface for cosmos contract code generation
 
-use super::{CosmosCodegenConfig, generate_contract_code};
+use super::{CosmosCodegenConfig, check_contract_code, dump_contract_templates, generate_contract_code};
+use indexer_core::codegen::{fail_on_drift, report_drift};
 use indexer_core::Result;
 use clap::{Arg, ArgMatches, Command};
 
@@ -45,15 +46,39 @@ pub fn build_generate_contract_command() -> Command {
             Arg::new("features")
                 .long("features")
                 .help("Comma-separated list of features to generate")
-                .default_value("client,storage,api,migrations")
+                .default_value("client,storage,api,migrations,handlers")
                 .value_name("FEATURES")
         )
+        .arg(
+            Arg::new("templates-dir")
+                .long("templates-dir")
+                .help("Directory of Handlebars templates overriding or adding generated files")
+                .value_name("DIR")
+        )
+        .arg(
+            Arg::new("migration-version")
+                .long("migration-version")
+                .help("Version prefix of the generated migration, derived from the inputs by default")
+                .value_name("VERSION")
+        )
         .arg(
             Arg::new("dry-run")
                 .long("dry-run")
                 .help("Preview generated code without writing files")
                 .action(clap::ArgAction::SetTrue)
         )
+        .arg(
+            Arg::new("dump-templates")
+                .long("dump-templates")
+                .help("Write the generated files as reference Handlebars templates to this directory instead")
+                .value_name("DIR")
+        )
+        .arg(
+            Arg::new("check")
+                .long("check")
+                .help("Regenerate in memory and fail if the files in the output directory differ")
+                .action(clap::ArgAction::SetTrue)
+        )
         .arg(
             Arg::new("verbose")
                 .long("verbose")
@@ -82,14 +107,18 @@ pub async fn handle_generate_contract_command(matches: &ArgMatches) -> Result<()
 
     let features_str = matches.get_one::<String>("features")
         .map(|s| s.as_str())
-        .unwrap_or("client,storage,api,migrations");
+        .unwrap_or("client,storage,api,migrations,handlers");
     
     let features = features_str
         .split(',')
         .map(|s| s.trim().to_string())
         .collect();
 
+    let templates_dir = matches.get_one::<String>("templates-dir").cloned();
+    let migration_version = matches.get_one::<String>("migration-version").cloned();
     let dry_run = matches.get_flag("dry-run");
+    let check = matches.get_flag("check");
+    let dump_templates = matches.get_one::<String>("dump-templates");
     let verbose = matches.get_flag("verbose");
 
     // Validate contract address format
@@ -108,6 +137,8 @@ pub async fn handle_generate_contract_command(matches: &ArgMatches) -> Result<()
         namespace,
         features,
         dry_run,
+        templates_dir,
+        migration_version,
     };
 
     if verbose {
@@ -121,10 +152,30 @@ pub async fn handle_generate_contract_command(matches: &ArgMatches) -> Result<()
             println!("Namespace: {}", ns);
         }
         println!("Features: {}", config.features.join(", "));
+        if let Some(ref dir) = config.templates_dir {
+            println!("Templates directory: {}", dir);
+        }
+        if let Some(ref version) = config.migration_version {
+            println!("Migration version: {}", version);
+        }
         println!("Dry run: {}", dry_run);
         println!();
     }
 
+    if let Some(dir) = dump_templates {
+        println!("📝 Writing reference templates to {}", dir);
+        let templates = dump_contract_templates(msg_file, config, dir).await?;
+        println!("✅ Wrote {} reference templates", templates.len());
+        return Ok(());
+    }
+
+    if check {
+        println!("🔍 Checking generated code in {}", output_dir);
+        let drift = check_contract_code(msg_file, config).await?;
+        println!("{}", report_drift(&drift));
+        return fail_on_drift(&drift);
+    }
+
     if dry_run {
         println!("🔍 Performing dry run - no files will be written");
     } else {
@@ -198,6 +249,8 @@ pub fn print_features_help() {
     println!("  storage    - Generate storage models and database schemas");
     println!("  api        - Generate REST, GraphQL, and WebSocket APIs");
     println!("  migrations - Generate database migration files");
+    println!("  handlers   - Generate an event handler writing to the migration tables");
+    println!("  typescript - Generate TypeScript event types, API client and GraphQL fragments");
     println!();
     println!("Example: --features client,storage,api");
 } 
\ No newline at end of file
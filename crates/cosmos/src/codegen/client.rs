@@ -0,0 +1,733 @@
+//! Rust client generation for CosmWasm contracts
+//!
+//! Generates the message, response and definition types of a contract
+//! schema, and a client with one typed method per execute and query variant
+//! on top of `indexer_cosmos::wasm::CosmWasmClient`.
+
+use std::collections::BTreeSet;
+
+use convert_case::{Case, Casing};
+
+use super::parser::{
+    CosmWasmSchema, EventSchema, MessageSchema, MessageVariant, PropertySchema, TypeDefinition, TypeInfo, VariantKind,
+};
+
+/// cosmwasm-std types that schema definitions of the same name map to
+const COSMWASM_STD_TYPES: &[&str] = &[
+    "Addr",
+    "Binary",
+    "Coin",
+    "Decimal",
+    "Decimal256",
+    "Empty",
+    "Timestamp",
+    "Uint64",
+    "Uint128",
+    "Uint256",
+    "Uint512",
+];
+
+/// Client methods that message variants must not shadow
+const RESERVED_METHODS: &[&str] = &["new", "contract_address", "execute", "query"];
+
+/// Decoder of optional event attributes, emitted once per schema with events
+const ATTRIBUTE_DECODER: &str = r#"/// Attribute `key` of an event, decoded from JSON or else from the raw string
+fn attribute<T: DeserializeOwned>(attributes: &[EventAttribute], key: &str) -> indexer_core::Result<Option<T>> {
+    let Some(attribute) = attributes.iter().find(|attribute| attribute.key == key) else {
+        return Ok(None);
+    };
+    serde_json::from_str(&attribute.value)
+        .or_else(|_| serde_json::from_value(serde_json::Value::String(attribute.value.clone())))
+        .map(Some)
+        .map_err(|e| indexer_core::Error::invalid_data(format!("Invalid event attribute {}: {}", key, e)))
+}
+"#;
+
+/// Decoder of required event attributes, emitted when an event has one
+const REQUIRED_ATTRIBUTE_DECODER: &str = r#"/// Attribute `key` of an event, which must be present
+fn required_attribute<T: DeserializeOwned>(attributes: &[EventAttribute], key: &str) -> indexer_core::Result<T> {
+    attribute(attributes, key)?
+        .ok_or_else(|| indexer_core::Error::invalid_data(format!("Missing event attribute {}", key)))
+}
+"#;
+
+/// Generated client code for a CosmWasm schema
+pub struct ClientGenerator<'a> {
+    schema: &'a CosmWasmSchema,
+    /// Name of the generated client struct
+    client_name: String,
+    /// Address the client code is generated for, used in file headers
+    contract_address: String,
+}
+
+impl<'a> ClientGenerator<'a> {
+    /// Create a generator for `{client_name}` over `schema`
+    pub fn new(schema: &'a CosmWasmSchema, client_name: impl Into<String>, contract_address: impl Into<String>) -> Self {
+        Self {
+            schema,
+            client_name: client_name.into(),
+            contract_address: contract_address.into(),
+        }
+    }
+
+    /// Whether the schema has execute messages
+    pub fn has_execute(&self) -> bool {
+        self.schema.execute_msg.is_some()
+    }
+
+    /// Whether the schema has query messages
+    pub fn has_query(&self) -> bool {
+        self.schema.query_msg.is_some()
+    }
+
+    /// `client/mod.rs`: the client struct with generic execute and query
+    pub fn module(&self) -> String {
+        let client = &self.client_name;
+        let mut modules = String::from("pub mod types;\n");
+        if self.has_execute() {
+            modules.push_str("mod execute;\n");
+        }
+        if self.has_query() {
+            modules.push_str("mod query;\n");
+        }
+
+        let mut methods = String::new();
+        if self.has_execute() {
+            methods.push_str(
+                r#"
+    /// Execute `msg` with `funds` attached and return the transaction hash
+    pub async fn execute(&self, msg: &ExecuteMsg, funds: &[Coin]) -> Result<String> {
+        let msg = serde_json::to_vec(msg)?;
+        self.client.execute_wasm(&self.contract_address, msg, funds).await
+    }
+"#,
+            );
+        }
+        if self.has_query() {
+            methods.push_str(
+                r#"
+    /// Run the smart query `msg` and decode its response
+    pub async fn query<R: DeserializeOwned>(&self, msg: &QueryMsg) -> Result<R> {
+        let msg = serde_json::to_vec(msg)?;
+        let response = self.client.query_wasm_smart(&self.contract_address, msg).await?;
+        Ok(serde_json::from_slice(&response)?)
+    }
+"#,
+            );
+        }
+
+        let mut imports = Vec::new();
+        if self.has_execute() {
+            imports.push("use cosmwasm_std::Coin;");
+        }
+        imports.push("use indexer_core::Result;");
+        imports.push("use indexer_cosmos::wasm::CosmWasmClient;");
+        if self.has_query() {
+            imports.push("use serde::de::DeserializeOwned;");
+        }
+
+        format!(
+            r#"//! Generated client code for contract: {address}
+
+{modules}
+pub use types::*;
+
+{imports}
+
+/// Client for the {address} contract
+pub struct {client}<C: CosmWasmClient> {{
+    client: C,
+    contract_address: String,
+}}
+
+impl<C: CosmWasmClient> {client}<C> {{
+    /// Create a client for the contract at `contract_address`
+    pub fn new(client: C, contract_address: String) -> Self {{
+        Self {{
+            client,
+            contract_address,
+        }}
+    }}
+
+    /// Get the contract address
+    pub fn contract_address(&self) -> &str {{
+        &self.contract_address
+    }}
+
+    /// Get the underlying CosmWasm client
+    pub fn client(&self) -> &C {{
+        &self.client
+    }}
+{methods}}}
+"#,
+            address = self.contract_address,
+            modules = modules,
+            imports = imports.join("\n"),
+            client = client,
+            methods = methods,
+        )
+    }
+
+    /// `client/types.rs`: messages, query responses, events and their
+    /// definitions
+    pub fn types(&self) -> String {
+        let mut types = TypeWriter::default();
+        let messages = [
+            ("InstantiateMsg", &self.schema.instantiate_msg),
+            ("ExecuteMsg", &self.schema.execute_msg),
+            ("QueryMsg", &self.schema.query_msg),
+            ("MigrateMsg", &self.schema.migrate_msg),
+        ];
+
+        let mut definitions = Vec::new();
+        for (name, message) in messages {
+            if let Some(message) = message {
+                definitions.push(types.message(name, message));
+            }
+        }
+
+        let mut names: Vec<&String> = self.schema.definitions.keys().collect();
+        names.sort();
+        for name in names {
+            if let Some(definition) = types.definition(&self.schema.definitions[name]) {
+                definitions.push(definition);
+            }
+        }
+
+        for event in &self.schema.events {
+            definitions.push(types.event(event));
+        }
+        if !self.schema.events.is_empty() {
+            definitions.push(ATTRIBUTE_DECODER.to_string());
+        }
+        if self.schema.events.iter().flat_map(|event| &event.attributes).any(|attribute| attribute.required) {
+            definitions.push(REQUIRED_ATTRIBUTE_DECODER.to_string());
+        }
+
+        // Keep the generated code compiling for schemas with dangling references
+        for name in undefined_references(self.schema) {
+            definitions.push(format!(
+                "/// `{}` is referenced but not defined by the schema\npub type {} = serde_json::Value;\n",
+                name,
+                type_name(&name)
+            ));
+        }
+
+        let mut code = format!(
+            "//! Generated message and response types for contract: {}\n\nuse serde::{{Deserialize, Serialize}};\n",
+            self.contract_address
+        );
+        if !self.schema.events.is_empty() {
+            code.push_str("use serde::de::DeserializeOwned;\nuse indexer_core::event::EventAttribute;\n");
+        }
+        if !types.std_imports.is_empty() {
+            let imports: Vec<&str> = types.std_imports.iter().map(String::as_str).collect();
+            code.push_str(&format!("use cosmwasm_std::{{{}}};\n", imports.join(", ")));
+        }
+        for definition in definitions {
+            code.push('\n');
+            code.push_str(&definition);
+        }
+        code
+    }
+
+    /// `client/execute.rs`: one method per execute variant
+    pub fn execute_methods(&self) -> String {
+        let mut methods = String::new();
+        let mut types = TypeWriter::default();
+
+        for variant in self.schema.execute_msg.iter().flat_map(|msg| &msg.variants) {
+            let method = self.method_name(variant, "execute");
+            let (params, value) = message_value(&mut types, "ExecuteMsg", variant);
+            methods.push_str(&format!(
+                r#"
+{docs}    pub async fn {method}(&self{params}) -> Result<String> {{
+        self.execute(&{value}, &[]).await
+    }}
+"#,
+                docs = method_docs(variant, "Execute"),
+                method = method,
+                params = params,
+                value = value,
+            ));
+        }
+
+        format!(
+            r#"//! Generated execute methods for contract: {address}
+
+{imports}
+
+impl<C: CosmWasmClient> {client}<C> {{{methods}}}
+"#,
+            address = self.contract_address,
+            imports = self.method_imports(&types, false),
+            client = self.client_name,
+            methods = methods,
+        )
+    }
+
+    /// `client/query.rs`: one method per query variant, decoding the
+    /// variant's response type when the schema declares one
+    pub fn query_methods(&self) -> String {
+        let mut methods = String::new();
+        let mut types = TypeWriter::default();
+        let mut generic = false;
+
+        for variant in self.schema.query_msg.iter().flat_map(|msg| &msg.variants) {
+            let method = self.method_name(variant, "query");
+            let (params, value) = message_value(&mut types, "QueryMsg", variant);
+            let (type_params, response) = match self.schema.responses.get(&variant.name) {
+                Some(response) => (String::new(), types.rust_type(response)),
+                None => {
+                    generic = true;
+                    ("<R: DeserializeOwned>".to_string(), "R".to_string())
+                }
+            };
+            methods.push_str(&format!(
+                r#"
+{docs}    pub async fn {method}{type_params}(&self{params}) -> Result<{response}> {{
+        self.query(&{value}).await
+    }}
+"#,
+                docs = method_docs(variant, "Query"),
+                method = method,
+                type_params = type_params,
+                params = params,
+                response = response,
+                value = value,
+            ));
+        }
+
+        format!(
+            r#"//! Generated query methods for contract: {address}
+
+{imports}
+
+impl<C: CosmWasmClient> {client}<C> {{{methods}}}
+"#,
+            address = self.contract_address,
+            imports = self.method_imports(&types, generic),
+            client = self.client_name,
+            methods = methods,
+        )
+    }
+
+    /// Imports of a method file whose signatures use the types seen by `types`
+    fn method_imports(&self, types: &TypeWriter, generic: bool) -> String {
+        let mut imports = vec![
+            "use super::types::*;".to_string(),
+            format!("use super::{};", self.client_name),
+        ];
+        if !types.std_imports.is_empty() {
+            let std_imports: Vec<&str> = types.std_imports.iter().map(String::as_str).collect();
+            imports.push(format!("use cosmwasm_std::{{{}}};", std_imports.join(", ")));
+        }
+        imports.push("use indexer_core::Result;".to_string());
+        imports.push("use indexer_cosmos::wasm::CosmWasmClient;".to_string());
+        if generic {
+            imports.push("use serde::de::DeserializeOwned;".to_string());
+        }
+        imports.join("\n")
+    }
+
+    /// Method name for a variant, prefixed with `prefix` when it would
+    /// shadow a client method or an execute method of the same name
+    fn method_name(&self, variant: &MessageVariant, prefix: &str) -> String {
+        let name = variant.name.to_case(Case::Snake);
+        let shadows_execute = prefix == "query"
+            && self
+                .schema
+                .execute_msg
+                .iter()
+                .flat_map(|msg| &msg.variants)
+                .any(|execute| execute.name.to_case(Case::Snake) == name);
+
+        if RESERVED_METHODS.contains(&name.as_str()) || shadows_execute {
+            format!("{}_{}", prefix, name)
+        } else {
+            field_name(&name)
+        }
+    }
+}
+
+/// Rust type definitions, tracking the cosmwasm-std types they use
+#[derive(Default)]
+struct TypeWriter {
+    std_imports: BTreeSet<String>,
+}
+
+impl TypeWriter {
+    /// Struct or enum for a message schema
+    fn message(&mut self, name: &str, message: &MessageSchema) -> String {
+        let docs = docs(message.description.as_deref(), "");
+        if message.is_enum {
+            return format!("{}{}", docs, self.enum_definition(name, &message.variants));
+        }
+
+        let properties = message.variants.first().map(|variant| variant.properties.as_slice()).unwrap_or_default();
+        format!("{}{}", docs, self.struct_definition(name, properties))
+    }
+
+    /// Type for a schema definition, or `None` for cosmwasm-std types
+    fn definition(&mut self, definition: &TypeDefinition) -> Option<String> {
+        if COSMWASM_STD_TYPES.contains(&definition.name.as_str()) {
+            return None;
+        }
+
+        let name = type_name(&definition.name);
+        let docs = docs(definition.description.as_deref(), "");
+        let code = if let Some(alias) = &definition.alias {
+            format!("pub type {} = {};\n", name, self.rust_type(alias))
+        } else if !definition.one_of.is_empty() {
+            self.enum_definition(&name, &definition.one_of)
+        } else if definition.is_enum {
+            let variants: Vec<MessageVariant> = definition
+                .variants
+                .iter()
+                .map(|variant| MessageVariant {
+                    name: variant.clone(),
+                    description: None,
+                    properties: Vec::new(),
+                    required: Vec::new(),
+                    kind: VariantKind::Unit,
+                })
+                .collect();
+            self.enum_definition(&name, &variants)
+        } else {
+            self.struct_definition(&name, &definition.properties)
+        };
+        Some(format!("{}{}", docs, code))
+    }
+
+    /// Struct for an event schema, decoded from the event's attributes
+    fn event(&mut self, event: &EventSchema) -> String {
+        let name = event_type_name(&event.name);
+        let mut values = String::new();
+        for attribute in &event.attributes {
+            let decoder = if attribute.required { "required_attribute" } else { "attribute" };
+            values.push_str(&format!(
+                "            {}: {}(attributes, \"{}\")?,\n",
+                field_name(&attribute.name),
+                decoder,
+                attribute.name
+            ));
+        }
+        let (param, value) = if values.is_empty() {
+            ("_attributes", "Self {}".to_string())
+        } else {
+            ("attributes", format!("Self {{\n{}        }}", values))
+        };
+
+        format!(
+            r#"{docs}{definition}
+impl {name} {{
+    /// Event type, without the `wasm-` prefix CosmWasm adds
+    pub const NAME: &'static str = "{event}";
+
+    /// Decode the event from its attributes
+    pub fn from_attributes({param}: &[EventAttribute]) -> indexer_core::Result<Self> {{
+        Ok({value})
+    }}
+}}
+"#,
+            docs = docs(event.description.as_deref(), ""),
+            definition = self.struct_definition(&name, &event.attributes),
+            name = name,
+            event = event_name(&event.name),
+            param = param,
+            value = value,
+        )
+    }
+
+    fn struct_definition(&mut self, name: &str, properties: &[PropertySchema]) -> String {
+        let mut fields = String::new();
+        for property in properties {
+            fields.push_str(&self.field(property, "    ", "pub "));
+        }
+        if fields.is_empty() {
+            return format!("#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]\npub struct {} {{}}\n", name);
+        }
+        format!(
+            "#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]\npub struct {} {{\n{}}}\n",
+            name, fields
+        )
+    }
+
+    fn enum_definition(&mut self, name: &str, variants: &[MessageVariant]) -> String {
+        let mut body = String::new();
+        for variant in variants {
+            body.push_str(&docs(variant.description.as_deref(), "    "));
+            body.push_str(&format!("    #[serde(rename = \"{}\")]\n", variant.name));
+            let variant_name = type_name(&variant.name);
+            match &variant.kind {
+                VariantKind::Unit => body.push_str(&format!("    {},\n", variant_name)),
+                VariantKind::Newtype(type_info) => {
+                    body.push_str(&format!("    {}({}),\n", variant_name, self.rust_type(type_info)))
+                }
+                VariantKind::Struct if variant.properties.is_empty() => {
+                    body.push_str(&format!("    {} {{}},\n", variant_name))
+                }
+                VariantKind::Struct => {
+                    body.push_str(&format!("    {} {{\n", variant_name));
+                    for property in &variant.properties {
+                        body.push_str(&self.field(property, "        ", ""));
+                    }
+                    body.push_str("    },\n");
+                }
+            }
+        }
+        format!(
+            "#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]\npub enum {} {{\n{}}}\n",
+            name, body
+        )
+    }
+
+    /// Field declaration, renamed when the JSON name is not a Rust identifier
+    fn field(&mut self, property: &PropertySchema, indent: &str, visibility: &str) -> String {
+        let field = field_name(&property.name);
+        let mut code = docs(property.description.as_deref(), indent);
+        let mut attributes = Vec::new();
+        if field.trim_start_matches("r#") != property.name {
+            attributes.push(format!("rename = \"{}\"", property.name));
+        }
+        if !property.required {
+            attributes.push("default, skip_serializing_if = \"Option::is_none\"".to_string());
+        }
+        if !attributes.is_empty() {
+            code.push_str(&format!("{}#[serde({})]\n", indent, attributes.join(", ")));
+        }
+        code.push_str(&format!("{}{}{}: {},\n", indent, visibility, field, self.property_type(property)));
+        code
+    }
+
+    /// Rust type of a property, optional unless required
+    fn property_type(&mut self, property: &PropertySchema) -> String {
+        let rust_type = self.rust_type(&property.type_info);
+        if property.required {
+            rust_type
+        } else {
+            format!("Option<{}>", rust_type)
+        }
+    }
+
+    /// Rust type for a schema type
+    fn rust_type(&mut self, type_info: &TypeInfo) -> String {
+        if let Some(reference) = &type_info.reference {
+            if COSMWASM_STD_TYPES.contains(&reference.as_str()) {
+                self.std_imports.insert(reference.clone());
+                return reference.clone();
+            }
+            return type_name(reference);
+        }
+
+        match type_info.base_type.as_str() {
+            "string" => "String".to_string(),
+            "boolean" => "bool".to_string(),
+            "number" => "f64".to_string(),
+            "integer" => match type_info.format.as_deref() {
+                Some(format @ ("uint8" | "uint16" | "uint32" | "uint64" | "int8" | "int16" | "int32" | "int64")) => {
+                    format.replace("uint", "u").replace("int", "i")
+                }
+                _ => "i64".to_string(),
+            },
+            "array" => match &type_info.items {
+                Some(items) => format!("Vec<{}>", self.rust_type(items)),
+                None => "Vec<serde_json::Value>".to_string(),
+            },
+            "null" => "()".to_string(),
+            _ => "serde_json::Value".to_string(),
+        }
+    }
+}
+
+/// Method parameters and the message value built from them
+fn message_value(types: &mut TypeWriter, message: &str, variant: &MessageVariant) -> (String, String) {
+    let variant_name = type_name(&variant.name);
+    match &variant.kind {
+        VariantKind::Unit => (String::new(), format!("{}::{}", message, variant_name)),
+        VariantKind::Newtype(type_info) => (
+            format!(", value: {}", types.rust_type(type_info)),
+            format!("{}::{}(value)", message, variant_name),
+        ),
+        VariantKind::Struct => {
+            let mut params = String::new();
+            let mut fields = Vec::new();
+            for property in &variant.properties {
+                let field = field_name(&property.name);
+                params.push_str(&format!(", {}: {}", field, types.property_type(property)));
+                fields.push(field);
+            }
+            let value = if fields.is_empty() {
+                format!("{}::{} {{}}", message, variant_name)
+            } else {
+                format!("{}::{} {{ {} }}", message, variant_name, fields.join(", "))
+            };
+            (params, value)
+        }
+    }
+}
+
+/// Doc comment for a generated method
+fn method_docs(variant: &MessageVariant, action: &str) -> String {
+    match &variant.description {
+        Some(description) => docs(Some(description), "    "),
+        None => format!("    /// {} `{}`\n", action, variant.name),
+    }
+}
+
+/// Doc comment lines for an optional schema description
+fn docs(description: Option<&str>, indent: &str) -> String {
+    description
+        .map(|description| {
+            description
+                .lines()
+                .map(|line| format!("{}/// {}\n", indent, line).replace("/// \n", "///\n"))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Rust type generated for a property, optional unless required
+pub(crate) fn property_rust_type(property: &PropertySchema) -> String {
+    TypeWriter::default().property_type(property)
+}
+
+/// Rust type generated for a schema type
+pub(crate) fn schema_rust_type(type_info: &TypeInfo) -> String {
+    TypeWriter::default().rust_type(type_info)
+}
+
+/// Event type without the `wasm-` prefix CosmWasm adds to custom events
+pub(crate) fn event_name(name: &str) -> &str {
+    name.trim_start_matches("wasm-")
+}
+
+/// Name of the generated struct for an event
+pub(crate) fn event_type_name(name: &str) -> String {
+    format!("{}Event", type_name(event_name(name)))
+}
+
+/// Rust type name for a schema name such as `Ownership_for_String`
+fn type_name(name: &str) -> String {
+    let type_name: String = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect::<String>()
+        .to_case(Case::Pascal);
+    if type_name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("V{}", type_name)
+    } else {
+        type_name
+    }
+}
+
+/// Rust field or method name for a JSON property name
+pub(crate) fn field_name(name: &str) -> String {
+    let is_identifier = !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    let field = if is_identifier { name.to_string() } else { name.to_case(Case::Snake) };
+
+    match field.as_str() {
+        "as" | "break" | "const" | "continue" | "else" | "enum" | "extern" | "false" | "fn" | "for" | "if"
+        | "impl" | "in" | "let" | "loop" | "match" | "mod" | "move" | "mut" | "pub" | "ref" | "return"
+        | "static" | "struct" | "trait" | "true" | "type" | "unsafe" | "use" | "where" | "while" | "async"
+        | "await" | "dyn" | "abstract" | "become" | "box" | "do" | "final" | "macro" | "override" | "priv"
+        | "typeof" | "unsized" | "virtual" | "yield" | "try" => format!("r#{}", field),
+        "self" | "super" | "crate" | "Self" => format!("{}_", field),
+        "" => "value".to_string(),
+        _ => field,
+    }
+}
+
+/// Names of the types `schema` references but does not define
+fn undefined_references(schema: &CosmWasmSchema) -> Vec<String> {
+    fn collect<'t>(type_info: &'t TypeInfo, references: &mut Vec<&'t str>) {
+        if let Some(reference) = &type_info.reference {
+            references.push(reference);
+        }
+        if let Some(items) = &type_info.items {
+            collect(items, references);
+        }
+    }
+
+    let mut references = Vec::new();
+    let messages = [&schema.instantiate_msg, &schema.execute_msg, &schema.query_msg, &schema.migrate_msg];
+    let variants = messages.into_iter().flatten().flat_map(|msg| &msg.variants);
+    let definition_variants = schema.definitions.values().flat_map(|definition| &definition.one_of);
+    for variant in variants.chain(definition_variants) {
+        if let VariantKind::Newtype(type_info) = &variant.kind {
+            collect(type_info, &mut references);
+        }
+        for property in &variant.properties {
+            collect(&property.type_info, &mut references);
+        }
+    }
+    for definition in schema.definitions.values() {
+        for property in &definition.properties {
+            collect(&property.type_info, &mut references);
+        }
+        if let Some(alias) = &definition.alias {
+            collect(alias, &mut references);
+        }
+    }
+    for response in schema.responses.values() {
+        collect(response, &mut references);
+    }
+    for attribute in schema.events.iter().flat_map(|event| &event.attributes) {
+        collect(&attribute.type_info, &mut references);
+    }
+
+    let mut undefined: Vec<String> = references
+        .into_iter()
+        .filter(|reference| !schema.definitions.contains_key(*reference) && !COSMWASM_STD_TYPES.contains(reference))
+        .map(String::from)
+        .collect();
+    undefined.sort();
+    undefined.dedup();
+    undefined
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_type_name() {
+        assert_eq!(type_name("Ownership_for_String"), "OwnershipForString");
+        assert_eq!(type_name("Expiration"), "Expiration");
+        assert_eq!(type_name("list_approved_libraries"), "ListApprovedLibraries");
+    }
+
+    #[test]
+    fn test_field_name() {
+        assert_eq!(field_name("start_after"), "start_after");
+        assert_eq!(field_name("type"), "r#type");
+        assert_eq!(field_name("self"), "self_");
+        assert_eq!(field_name("tokenId"), "token_id");
+    }
+
+    #[test]
+    fn test_rust_type() {
+        let mut types = TypeWriter::default();
+        let type_info = |base_type: &str, reference: Option<&str>, format: Option<&str>| TypeInfo {
+            base_type: base_type.to_string(),
+            reference: reference.map(String::from),
+            items: None,
+            enum_values: None,
+            format: format.map(String::from),
+        };
+
+        assert_eq!(types.rust_type(&type_info("integer", None, Some("uint64"))), "u64");
+        assert_eq!(types.rust_type(&type_info("integer", None, None)), "i64");
+        assert_eq!(types.rust_type(&type_info("object", Some("Uint128"), None)), "Uint128");
+        assert_eq!(types.rust_type(&type_info("object", Some("Ownership_for_String"), None)), "OwnershipForString");
+
+        let array = TypeInfo {
+            items: Some(Box::new(type_info("object", Some("Addr"), None))),
+            ..type_info("array", None, None)
+        };
+        assert_eq!(types.rust_type(&array), "Vec<Addr>");
+        assert_eq!(types.std_imports.into_iter().collect::<Vec<_>>(), vec!["Addr", "Uint128"]);
+    }
+}
@@ -1,82 +1,195 @@
 //! Code generator for CosmWasm contracts
 //! 
-//! Generates Rust code for client interactions, storage models, APIs, and migrations
-//! from parsed CosmWasm contract schemas.
+//! Generates Rust code for client interactions, storage models, APIs, migrations
+//! and event handlers, and TypeScript for frontends, from parsed CosmWasm
+//! contract schemas.
 
-use super::{CosmosCodegenConfig, parser::CosmWasmSchema};
+use super::{CosmosCodegenConfig, client::ClientGenerator, handlers::EventHandlerGenerator, migrations::EventTableGenerator, parser::CosmWasmSchema, typescript::TypeScriptGenerator};
+use super::templates::CosmosTemplateManager;
+use indexer_core::codegen::{self, GeneratedFile, Provenance};
 use indexer_core::Result;
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 use convert_case::Casing;
 
 /// Code generator for cosmos contracts
 pub struct CosmosContractCodegen {
     config: CosmosCodegenConfig,
+    /// Inputs recorded in the header of generated files
+    provenance: Option<Provenance>,
+    /// Files kept in memory instead of written
+    captured: Option<Mutex<Vec<GeneratedFile>>>,
+    /// User templates overriding or adding generated files
+    templates: Option<CosmosTemplateManager>,
+    /// Model of the schema being generated for templates, and the files
+    /// generated for it so far
+    template_state: Mutex<Option<(serde_json::Value, HashSet<String>)>>,
 }
 
 impl CosmosContractCodegen {
     /// Create a new code generator with the given configuration
     pub fn new(config: CosmosCodegenConfig) -> Self {
-        Self { config }
+        Self { config, provenance: None, captured: None, templates: None, template_state: Mutex::new(None) }
+    }
+
+    /// Render generated files with the file templates of `templates`, see
+    /// [`CosmosTemplateManager::with_templates_dir`]
+    pub fn with_templates(mut self, templates: CosmosTemplateManager) -> Self {
+        self.templates = Some(templates);
+        self
+    }
+
+    /// Start generated files with a header recording their inputs and the
+    /// generator version
+    pub fn with_provenance(mut self, provenance: Provenance) -> Self {
+        self.provenance = Some(provenance);
+        self
+    }
+
+    /// Keep generated files in memory instead of writing them, to be taken
+    /// with [`take_files`](Self::take_files)
+    pub fn in_memory(mut self) -> Self {
+        self.captured = Some(Mutex::new(Vec::new()));
+        self
+    }
+
+    /// Files generated in memory so far
+    pub fn take_files(&self) -> Vec<GeneratedFile> {
+        self.captured
+            .as_ref()
+            .map(|files| std::mem::take(&mut *files.lock().unwrap()))
+            .unwrap_or_default()
+    }
+
+    /// Whether generated files are written to the output directory
+    fn writes_files(&self) -> bool {
+        !self.config.dry_run && self.captured.is_none()
+    }
+
+    /// Version prefix of the generated migration `name`
+    ///
+    /// The configured version, or else the version of the unchanged migration
+    /// already in `migrations_dir`, or else the current time past the
+    /// highest version there.
+    fn migration_version(&self, migrations_dir: &Path, name: &str, up_sql: &str) -> Result<String> {
+        codegen::migration_version(
+            self.config.migration_version.as_deref(),
+            migrations_dir,
+            &format!("{}.up.sql", name),
+            up_sql,
+        )
     }
 
     /// Generate all enabled code components
     pub async fn generate_all(&self, schema: &CosmWasmSchema) -> Result<()> {
         let output_dir = Path::new(&self.config.output_dir);
         
-        if !self.config.dry_run {
+        if self.writes_files() {
             tokio::fs::create_dir_all(output_dir).await
                 .map_err(|e| indexer_core::Error::Config(format!("Failed to create output directory: {}", e)))?;
         }
 
+        if self.templates.is_some() {
+            *self.template_state.lock().unwrap() = Some((self.template_model(schema)?, HashSet::new()));
+        }
+
         for feature in &self.config.features {
             match feature.as_str() {
                 "client" => self.generate_client_code(schema, output_dir).await?,
                 "storage" => self.generate_storage_code(schema, output_dir).await?,
                 "api" => self.generate_api_code(schema, output_dir).await?,
                 "migrations" => self.generate_migration_code(schema, output_dir).await?,
+                "handlers" => self.generate_handler_code(schema, output_dir).await?,
+                "typescript" => self.generate_typescript_code(schema, output_dir).await?,
                 _ => {
                     println!("Warning: Unknown feature '{}'", feature);
                 }
             }
         }
 
+        self.generate_template_files(output_dir).await
+    }
+
+    /// Write the files of templates that don't override a generated file
+    async fn generate_template_files(&self, output_dir: &Path) -> Result<()> {
+        let Some(templates) = &self.templates else {
+            return Ok(());
+        };
+        let generated = match &*self.template_state.lock().unwrap() {
+            Some((_, generated)) => generated.clone(),
+            None => return Ok(()),
+        };
+        for name in templates.custom_templates() {
+            if codegen::is_file_template(name) && !generated.contains(name) {
+                self.write_file(&output_dir.join(name), "").await?;
+            }
+        }
         Ok(())
     }
 
+    /// Data templates are rendered with: the configuration, the name used
+    /// for the contract in generated code and the parsed schema
+    fn template_model(&self, schema: &CosmWasmSchema) -> Result<serde_json::Value> {
+        Ok(serde_json::json!({
+            "contract_address": self.config.contract_address,
+            "chain_id": self.config.chain_id,
+            "namespace": self.config.namespace,
+            "features": self.config.features,
+            "contract_name": self.sanitize_contract_name(),
+            "schema": serde_json::to_value(schema)?,
+        }))
+    }
+
+    /// Content of a generated file, rendered with its user template if any
+    ///
+    /// The template sees the file's path relative to the output directory as
+    /// `path` and the built-in content as `generated`, empty for files only
+    /// generated from a template. Migrations are also rendered by templates
+    /// named without their version, see [`codegen::file_template_names`].
+    fn render_template(&self, path: &Path, content: &str) -> Result<String> {
+        let (Some(templates), Ok(relative)) = (&self.templates, path.strip_prefix(&self.config.output_dir)) else {
+            return Ok(content.to_string());
+        };
+        let name = codegen::slash_path(relative);
+
+        let mut state = self.template_state.lock().unwrap();
+        let Some((model, generated)) = state.as_mut() else {
+            return Ok(content.to_string());
+        };
+        let names = codegen::file_template_names(&name);
+        generated.extend(names.iter().cloned());
+        let Some(template) = names.iter().find(|name| templates.custom_templates().contains(name)) else {
+            return Ok(content.to_string());
+        };
+
+        let mut data = model.clone();
+        data["path"] = serde_json::Value::String(name);
+        data["generated"] = serde_json::Value::String(content.to_string());
+        templates.render(template, &data)
+    }
+
     /// Generate client interaction code
-    async fn generate_client_code(&self, _schema: &CosmWasmSchema, output_dir: &Path) -> Result<()> {
+    async fn generate_client_code(&self, schema: &CosmWasmSchema, output_dir: &Path) -> Result<()> {
         println!("Generating client code for contract: {}", self.config.contract_address);
         
         let client_dir = output_dir.join("client");
-        if !self.config.dry_run {
+        if self.writes_files() {
             tokio::fs::create_dir_all(&client_dir).await
                 .map_err(|e| indexer_core::Error::Config(format!("Failed to create client directory: {}", e)))?;
         }
 
-        // Generate basic client module
-        let client_code = format!(
-            r#"//! Generated client code for contract: {}
-
-pub struct {}Client {{
-    contract_address: String,
-}}
+        let client_name = format!("{}Client", self.sanitize_contract_name());
+        let generator = ClientGenerator::new(schema, client_name, self.config.contract_address.as_str());
 
-impl {}Client {{
-    pub fn new(contract_address: String) -> Self {{
-        Self {{ contract_address }}
-    }}
-    
-    pub fn contract_address(&self) -> &str {{
-        &self.contract_address
-    }}
-}}
-"#,
-            self.config.contract_address,
-            self.sanitize_contract_name(),
-            self.sanitize_contract_name()
-        );
-
-        self.write_file(&client_dir.join("mod.rs"), &client_code).await?;
+        self.write_file(&client_dir.join("mod.rs"), &generator.module()).await?;
+        self.write_file(&client_dir.join("types.rs"), &generator.types()).await?;
+        if generator.has_execute() {
+            self.write_file(&client_dir.join("execute.rs"), &generator.execute_methods()).await?;
+        }
+        if generator.has_query() {
+            self.write_file(&client_dir.join("query.rs"), &generator.query_methods()).await?;
+        }
         Ok(())
     }
 
@@ -85,7 +198,7 @@ impl {}Client {{
         println!("Generating storage code for contract: {}", self.config.contract_address);
         
         let storage_dir = output_dir.join("storage");
-        if !self.config.dry_run {
+        if self.writes_files() {
             tokio::fs::create_dir_all(&storage_dir).await
                 .map_err(|e| indexer_core::Error::Config(format!("Failed to create storage directory: {}", e)))?;
         }
@@ -110,7 +223,7 @@ pub struct {}Storage {{
         println!("Generating API code for contract: {}", self.config.contract_address);
         
         let api_dir = output_dir.join("api");
-        if !self.config.dry_run {
+        if self.writes_files() {
             tokio::fs::create_dir_all(&api_dir).await
                 .map_err(|e| indexer_core::Error::Config(format!("Failed to create api directory: {}", e)))?;
         }
@@ -131,34 +244,73 @@ pub struct {}Api {{
     }
 
     /// Generate database migrations
-    async fn generate_migration_code(&self, _schema: &CosmWasmSchema, output_dir: &Path) -> Result<()> {
+    async fn generate_migration_code(&self, schema: &CosmWasmSchema, output_dir: &Path) -> Result<()> {
         println!("Generating migration code for contract: {}", self.config.contract_address);
         
         let migrations_dir = output_dir.join("migrations");
-        if !self.config.dry_run {
+        if self.writes_files() {
             tokio::fs::create_dir_all(&migrations_dir).await
                 .map_err(|e| indexer_core::Error::Config(format!("Failed to create migrations directory: {}", e)))?;
         }
 
-        let migration_sql = format!(
-            r#"-- Migration for contract: {}
--- Generated at: {}
-
-CREATE TABLE IF NOT EXISTS contract_state (
-    id BIGSERIAL PRIMARY KEY,
-    contract_address TEXT NOT NULL,
-    block_height BIGINT NOT NULL,
-    state_data JSONB NOT NULL,
-    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
-);
-"#,
+        let contract_name = self.sanitize_contract_name().to_lowercase();
+        let migration = EventTableGenerator::new(contract_name.as_str()).generate(schema);
+        let header = format!(
+            "-- Migration: Event tables for contract {}\n-- Chain: {}\n",
             self.config.contract_address,
-            chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
+            self.config.chain_id
         );
 
-        let timestamp = chrono::Utc::now().format("%Y%m%d%H%M%S");
-        let filename = format!("{}_{}_contract.sql", timestamp, self.sanitize_contract_name().to_lowercase());
-        self.write_file(&migrations_dir.join(filename), &migration_sql).await?;
+        let version = self.migration_version(&migrations_dir, &format!("{}_events", contract_name), &migration.up)?;
+        let name = format!("{}_{}_events", version, contract_name);
+        self.write_file(&migrations_dir.join(format!("{}.up.sql", name)), &format!("{}\n{}", header, migration.up)).await?;
+        self.write_file(&migrations_dir.join(format!("{}.down.sql", name)), &format!("{}\n{}", header, migration.down)).await?;
+
+        Ok(())
+    }
+
+    /// Generate the event handler feeding the migration tables
+    async fn generate_handler_code(&self, schema: &CosmWasmSchema, output_dir: &Path) -> Result<()> {
+        println!("Generating event handler for contract: {}", self.config.contract_address);
+
+        let handlers_dir = output_dir.join("handlers");
+        if self.writes_files() {
+            tokio::fs::create_dir_all(&handlers_dir).await
+                .map_err(|e| indexer_core::Error::Config(format!("Failed to create handlers directory: {}", e)))?;
+        }
+
+        let contract_name = self.sanitize_contract_name();
+        let handler_code = EventHandlerGenerator::new(
+            &contract_name,
+            contract_name.to_lowercase(),
+            &self.config.contract_address,
+            &self.config.chain_id,
+        )
+        .generate(schema);
+        self.write_file(&handlers_dir.join("mod.rs"), &handler_code).await?;
+
+        Ok(())
+    }
+
+    /// Generate TypeScript for frontends of the contract's events
+    async fn generate_typescript_code(&self, schema: &CosmWasmSchema, output_dir: &Path) -> Result<()> {
+        println!("Generating TypeScript code for contract: {}", self.config.contract_address);
+
+        let typescript_dir = output_dir.join("typescript");
+        if self.writes_files() {
+            tokio::fs::create_dir_all(&typescript_dir).await
+                .map_err(|e| indexer_core::Error::Config(format!("Failed to create typescript directory: {}", e)))?;
+        }
+
+        let generator = TypeScriptGenerator::new(
+            &self.sanitize_contract_name(),
+            &self.config.contract_address,
+            &self.config.chain_id,
+        );
+        self.write_file(&typescript_dir.join("events.ts"), &generator.events(schema)).await?;
+        self.write_file(&typescript_dir.join("client.ts"), &generator.client()).await?;
+        self.write_file(&typescript_dir.join("fragments.ts"), &generator.fragments()).await?;
+        self.write_file(&typescript_dir.join("index.ts"), &generator.index()).await?;
 
         Ok(())
     }
@@ -174,7 +326,14 @@ CREATE TABLE IF NOT EXISTS contract_state (
 
     /// Write content to file (or just print if dry run)
     async fn write_file(&self, path: &PathBuf, content: &str) -> Result<()> {
-        if self.config.dry_run {
+        let content = self.render_template(path, content)?;
+        let content = match self.provenance.as_ref().and_then(|provenance| provenance.header(path)) {
+            Some(header) => format!("{}{}", header, content),
+            None => content,
+        };
+        if let Some(files) = &self.captured {
+            files.lock().unwrap().push(GeneratedFile { path: path.clone(), content });
+        } else if self.config.dry_run {
             println!("\n--- {} ---", path.display());
             println!("{}", content);
         } else {
@@ -182,9 +341,9 @@ CREATE TABLE IF NOT EXISTS contract_state (
                 tokio::fs::create_dir_all(parent).await
                     .map_err(|e| indexer_core::Error::Config(format!("Failed to create directory: {}", e)))?;
             }
-            tokio::fs::write(path, content).await
+            tokio::fs::write(path, &content).await
                 .map_err(|e| indexer_core::Error::Config(format!("Failed to write file {}: {}", path.display(), e)))?;
         }
         Ok(())
     }
-} 
\ No newline at end of file
+}
@@ -0,0 +1,257 @@
+//! Event handler generation for CosmWasm events
+//!
+//! Generates an `indexer_core::pipeline::EventHandler` per contract that
+//! matches wasm events on their type, decodes their attributes into the
+//! generated event structs and inserts them into the generated event tables.
+//! Rows of reorganized blocks are flagged `removed` until their events are
+//! ingested again.
+
+use super::client::{event_name, event_type_name, field_name};
+use super::migrations::{column_name, column_type, EventTableGenerator};
+use super::parser::{CosmWasmSchema, EventSchema};
+
+/// Binding of attributes to JSONB columns
+const SQL_JSON: &str = r#"
+/// JSON value of an attribute for a JSONB column, SQL NULL when absent
+fn sql_json<T: Serialize>(value: &T) -> Result<Option<serde_json::Value>> {
+    Ok(Some(serde_json::to_value(value)?).filter(|value| !value.is_null()))
+}
+"#;
+
+/// Binding of attributes to scalar columns
+const SQL_TEXT: &str = r#"
+/// Text of an attribute for a scalar column, SQL NULL when absent
+fn sql_text<T: Serialize>(value: &T) -> Result<Option<String>> {
+    Ok(match serde_json::to_value(value)? {
+        serde_json::Value::Null => None,
+        serde_json::Value::String(text) => Some(text),
+        value => Some(value.to_string()),
+    })
+}
+"#;
+
+/// Generated event handler for a contract
+pub struct EventHandlerGenerator {
+    /// Name of the generated handler struct
+    handler_name: String,
+    /// Prefix of the generated event tables
+    table_prefix: String,
+    contract_address: String,
+    chain_id: String,
+}
+
+impl EventHandlerGenerator {
+    /// Create a generator for `{contract_name}EventHandler` writing to the
+    /// tables of the `{table_prefix}` migrations
+    pub fn new(
+        contract_name: &str,
+        table_prefix: impl Into<String>,
+        contract_address: impl Into<String>,
+        chain_id: impl Into<String>,
+    ) -> Self {
+        Self {
+            handler_name: format!("{}EventHandler", contract_name),
+            table_prefix: table_prefix.into(),
+            contract_address: contract_address.into(),
+            chain_id: chain_id.into(),
+        }
+    }
+
+    /// `handlers/mod.rs`: the handler and its registration
+    pub fn generate(&self, schema: &CosmWasmSchema) -> String {
+        let tables = EventTableGenerator::new(self.table_prefix.as_str());
+        let mut arms = String::new();
+        let mut rollbacks = String::new();
+        let mut helpers = Vec::new();
+        for event in &schema.events {
+            arms.push_str(&self.match_arm(&tables, schema, event, &mut helpers));
+            rollbacks.push_str(&format!(
+                "\n    \"UPDATE {} SET removed = TRUE WHERE block_height >= $1\",",
+                tables.table_name(event).replace('"', "\\\"")
+            ));
+        }
+
+        let mut imports = vec![
+            "use indexer_core::event::{Event, EventData, UnifiedEvent};",
+            "use indexer_core::pipeline::{event_contract_address, event_index, EventHandler, EventHandlerRegistry};",
+            "use indexer_core::{Error, Result};",
+        ];
+        if !helpers.is_empty() {
+            imports.push("use serde::Serialize;");
+        }
+        imports.push("use sqlx::PgPool;");
+        if !schema.events.is_empty() {
+            imports.push("\nuse super::client::types::*;");
+        }
+
+        let body = if schema.events.is_empty() {
+            "        let _ = event;\n        Ok(false)\n".to_string()
+        } else {
+            format!(
+                r#"        let Some(EventData::Cosmos {{ attributes, .. }}) =
+            event.as_any().downcast_ref::<UnifiedEvent>().map(|event| &event.event_data)
+        else {{
+            return Ok(false);
+        }};
+        // Custom events are emitted as `wasm-{{name}}`, the default one as
+        // `wasm` with an `action` attribute
+        let name = match event.event_type().strip_prefix("wasm-") {{
+            Some(name) => name,
+            None if event.event_type() == "wasm" => match attributes.iter().find(|attribute| attribute.key == "action") {{
+                Some(action) => action.value.as_str(),
+                None => return Ok(false),
+            }},
+            None => return Ok(false),
+        }};
+
+        let contract_address = event_contract_address(event).unwrap_or_else(|| CONTRACT_ADDRESS.to_string());
+        let index = i32::try_from(event_index(event)?)
+            .map_err(|_| Error::invalid_data(format!("Event index of event {{}} out of range", event.id())))?;
+        let block_height = i64::try_from(event.block_number())
+            .map_err(|_| Error::invalid_data(format!("Block height of event {{}} out of range", event.id())))?;
+        let block_timestamp = chrono::DateTime::<chrono::Utc>::from(event.timestamp());
+
+        match name {{
+{arms}            _ => Ok(false),
+        }}
+"#,
+                arms = arms
+            )
+        };
+
+        let mut code = format!(
+            r#"//! Generated event handler for contract: {address}
+//! Chain: {chain}
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+{imports}
+
+/// Chain the contract is deployed on
+pub const CHAIN_ID: &str = "{chain}";
+
+/// Address of the contract
+pub const CONTRACT_ADDRESS: &str = "{address}";
+
+/// Statements flagging the rows of blocks from `$1` on as removed
+const ROLLBACK_STATEMENTS: &[&str] = &[{rollbacks}
+];
+
+/// Decodes the events of the {address} contract into its event tables
+pub struct {handler} {{
+    pool: PgPool,
+}}
+
+impl {handler} {{
+    /// Create a handler writing to the event tables in `pool`
+    pub fn new(pool: PgPool) -> Self {{
+        Self {{ pool }}
+    }}
+
+    /// Register a handler for the contract's events on its chain
+    pub fn register(registry: &mut EventHandlerRegistry, pool: PgPool) {{
+        registry.register(CHAIN_ID, CONTRACT_ADDRESS, Arc::new(Self::new(pool)));
+    }}
+}}
+
+#[async_trait]
+impl EventHandler for {handler} {{
+    fn name(&self) -> &str {{
+        "{handler}"
+    }}
+
+    async fn handle_event(&self, event: &dyn Event) -> Result<bool> {{
+{body}    }}
+
+    async fn rollback(&self, chain: &str, from_block: u64) -> Result<()> {{
+        if chain != CHAIN_ID {{
+            return Ok(());
+        }}
+        let from_block = i64::try_from(from_block)
+            .map_err(|_| Error::invalid_data(format!("Block height {{}} out of range", from_block)))?;
+        for statement in ROLLBACK_STATEMENTS {{
+            sqlx::query(statement)
+                .bind(from_block)
+                .execute(&self.pool)
+                .await
+                .map_err(|e| Error::database(format!("Failed to roll back {handler} from block {{}}: {{}}", from_block, e)))?;
+        }}
+        Ok(())
+    }}
+}}
+"#,
+            address = self.contract_address,
+            chain = self.chain_id,
+            imports = imports.join("\n"),
+            handler = self.handler_name,
+            rollbacks = rollbacks,
+            body = body,
+        );
+        for helper in helpers {
+            code.push_str(helper);
+        }
+        code
+    }
+
+    /// Decoding and insertion of `event` when the event type matches it
+    fn match_arm(
+        &self,
+        tables: &EventTableGenerator,
+        schema: &CosmWasmSchema,
+        event: &EventSchema,
+        helpers: &mut Vec<&'static str>,
+    ) -> String {
+        let struct_name = event_type_name(&event.name);
+        let mut columns: Vec<String> = ["tx_hash", "contract_address", "block_height", "block_timestamp", "event_index"]
+            .iter()
+            .map(|column| column.to_string())
+            .collect();
+        let mut binds: Vec<String> = ["event.tx_hash()", "contract_address.as_str()", "block_height", "block_timestamp", "index"]
+            .iter()
+            .map(|bind| bind.to_string())
+            .collect();
+        let mut placeholders: Vec<String> = (1..=columns.len()).map(|i| format!("${}", i)).collect();
+
+        for (i, attribute) in event.attributes.iter().enumerate() {
+            let column = column_name(&attribute.name, i);
+            let field = format!("&decoded.{}", field_name(&attribute.name));
+            // Scalars are bound as text and cast to the column type
+            let (bind, cast) = match column_type(&column, attribute, &schema.definitions).0 {
+                "JSONB" => (format!("sql_json({})?", field), String::new()),
+                "TEXT" => (format!("sql_text({})?", field), String::new()),
+                sql_type => (format!("sql_text({})?", field), format!("::{}", sql_type)),
+            };
+            let helper = if bind.starts_with("sql_json") { SQL_JSON } else { SQL_TEXT };
+            if !helpers.contains(&helper) {
+                helpers.push(helper);
+            }
+            placeholders.push(format!("${}{}", placeholders.len() + 1, cast));
+            columns.push(column);
+            binds.push(bind);
+        }
+
+        let binds: String = binds.iter().map(|bind| format!("\n                    .bind({})", bind)).collect();
+        let decoded = if event.attributes.is_empty() { "" } else { "let decoded = " };
+        format!(
+            r#"            {struct_name}::NAME => {{
+                {decoded}{struct_name}::from_attributes(attributes)?;
+                sqlx::query(
+                    "INSERT INTO {table} ({columns}) VALUES ({placeholders}) ON CONFLICT (tx_hash, event_index) DO UPDATE SET block_height = EXCLUDED.block_height, block_timestamp = EXCLUDED.block_timestamp, removed = FALSE",
+                ){binds}
+                    .execute(&self.pool)
+                    .await
+                    .map_err(|e| Error::database(format!("Failed to store {name} event {{}}: {{}}", event.id(), e)))?;
+                Ok(true)
+            }}
+"#,
+            struct_name = struct_name,
+            decoded = decoded,
+            table = tables.table_name(event).replace('"', "\\\""),
+            columns = columns.join(", ").replace('"', "\\\""),
+            placeholders = placeholders.join(", "),
+            binds = binds,
+            name = event_name(&event.name),
+        )
+    }
+}
@@ -0,0 +1,259 @@
+//! PostgreSQL table generation for CosmWasm events
+//!
+//! Generates one typed table per event schema, with a column per event
+//! attribute, as reversible migrations that sort after the storage crate's
+//! own migrations.
+
+use std::collections::HashMap;
+
+use convert_case::{Case, Casing};
+use indexer_core::codegen::{self, index_name, table_name};
+
+use super::parser::{CosmWasmSchema, EventSchema, PropertySchema, TypeDefinition};
+
+/// Bech32 address with a lowercase human-readable prefix
+const BECH32_PATTERN: &str = "^[a-z0-9]+1[02-9ac-hj-np-z]+$";
+
+/// Columns every event table carries besides the event attributes
+const METADATA_COLUMNS: &[&str] = &[
+    "id",
+    "tx_hash",
+    "contract_address",
+    "block_height",
+    "block_timestamp",
+    "event_index",
+    "removed",
+    "created_at",
+];
+
+/// Up and down SQL of a generated migration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventMigration {
+    /// SQL creating the event tables and their indexes
+    pub up: String,
+    /// SQL dropping the event tables again
+    pub down: String,
+}
+
+/// Generated event tables for a CosmWasm schema
+pub struct EventTableGenerator {
+    /// Prefix of every table name, usually the lowercased contract name
+    table_prefix: String,
+}
+
+impl EventTableGenerator {
+    /// Create a generator naming tables `{table_prefix}_{event}_events`
+    pub fn new(table_prefix: impl Into<String>) -> Self {
+        Self {
+            table_prefix: table_prefix.into(),
+        }
+    }
+
+    /// Generate the up and down migration for every event in `schema`
+    pub fn generate(&self, schema: &CosmWasmSchema) -> EventMigration {
+        let mut up = String::new();
+        let mut down = String::new();
+
+        for event in &schema.events {
+            up.push_str(&self.create_table(event, &schema.definitions));
+            up.push('\n');
+        }
+        for event in schema.events.iter().rev() {
+            down.push_str(&format!("DROP TABLE IF EXISTS {};\n", self.table_name(event)));
+        }
+
+        EventMigration { up, down }
+    }
+
+    /// Table name for `event`
+    pub fn table_name(&self, event: &EventSchema) -> String {
+        let event_name = event.name.trim_start_matches("wasm-").to_case(Case::Snake);
+        table_name(&format!("{}_{}_events", self.table_prefix, event_name))
+    }
+
+    /// `CREATE TABLE` and `CREATE INDEX` statements for `event`
+    ///
+    /// Event attributes carry no indexed flag, so besides the block and
+    /// contract columns the attributes holding addresses are indexed.
+    pub fn create_table(&self, event: &EventSchema, definitions: &HashMap<String, TypeDefinition>) -> String {
+        let table = self.table_name(event);
+        let columns: Vec<(String, &PropertySchema)> = event
+            .attributes
+            .iter()
+            .enumerate()
+            .map(|(i, attribute)| (column_name(&attribute.name, i), attribute))
+            .collect();
+
+        let mut sql = format!(
+            r#"-- {name}
+CREATE TABLE IF NOT EXISTS {table} (
+    id BIGSERIAL PRIMARY KEY,
+    tx_hash TEXT NOT NULL,
+    contract_address TEXT NOT NULL CHECK (contract_address ~ '{bech32}'),
+    block_height BIGINT NOT NULL,
+    block_timestamp TIMESTAMPTZ NOT NULL,
+    event_index INTEGER NOT NULL,
+"#,
+            name = event.name,
+            table = table,
+            bech32 = BECH32_PATTERN,
+        );
+        for (column, attribute) in &columns {
+            sql.push_str(&format!("    {},\n", column_definition(column, attribute, definitions)));
+        }
+        sql.push_str(
+            r#"    removed BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (tx_hash, event_index)
+);
+
+"#,
+        );
+
+        let addresses = columns
+            .iter()
+            .filter(|(_, attribute)| attribute.type_info.reference.as_deref() == Some("Addr"))
+            .map(|(column, _)| column.as_str());
+        for column in ["contract_address", "block_height"].into_iter().chain(addresses) {
+            sql.push_str(&format!(
+                "CREATE INDEX IF NOT EXISTS {} ON {} ({});\n",
+                index_name(&table, column),
+                table,
+                column
+            ));
+        }
+
+        sql
+    }
+}
+
+/// Column definition for an event attribute
+fn column_definition(
+    column: &str,
+    attribute: &PropertySchema,
+    definitions: &HashMap<String, TypeDefinition>,
+) -> String {
+    let (sql_type, check) = column_type(column, attribute, definitions);
+    let nullability = if attribute.required { " NOT NULL" } else { "" };
+    match check {
+        Some(check) => format!("{} {}{} CHECK ({})", column, sql_type, nullability, check),
+        None => format!("{} {}{}", column, sql_type, nullability),
+    }
+}
+
+/// SQL type of the column for an event attribute and its check, if any
+///
+/// Attributes referencing the cosmwasm-std number types are stored as
+/// numerics, and string enums are checked against their variants.
+pub(crate) fn column_type(
+    column: &str,
+    attribute: &PropertySchema,
+    definitions: &HashMap<String, TypeDefinition>,
+) -> (&'static str, Option<String>) {
+    let type_info = &attribute.type_info;
+    let enum_values = type_info.enum_values.as_ref().or_else(|| {
+        let definition = definitions.get(type_info.reference.as_deref()?)?;
+        Some(&definition.variants).filter(|_| definition.is_enum)
+    });
+
+    match (type_info.reference.as_deref(), enum_values) {
+        (_, Some(values)) => {
+            let values = values
+                .iter()
+                .map(|value| format!("'{}'", value.replace('\'', "''")))
+                .collect::<Vec<_>>()
+                .join(", ");
+            ("TEXT", Some(format!("{} IN ({})", column, values)))
+        }
+        (Some("Addr"), None) => ("TEXT", Some(format!("{} ~ '{}'", column, BECH32_PATTERN))),
+        (Some("Uint512"), None) => ("NUMERIC(155, 0)", Some(format!("{} >= 0", column))),
+        (Some("Uint64" | "Uint128" | "Uint256" | "Timestamp"), None) => {
+            ("NUMERIC(78, 0)", Some(format!("{} >= 0", column)))
+        }
+        (Some("Int512"), None) => ("NUMERIC(155, 0)", None),
+        (Some("Int64" | "Int128" | "Int256"), None) => ("NUMERIC(78, 0)", None),
+        (Some("Decimal" | "Decimal256" | "SignedDecimal" | "SignedDecimal256"), None) => ("NUMERIC", None),
+        (Some("Binary"), None) => ("TEXT", None),
+        (Some(_), None) => ("JSONB", None),
+        (None, None) => match type_info.base_type.as_str() {
+            "string" => ("TEXT", None),
+            "integer" => ("BIGINT", None),
+            "number" => ("NUMERIC", None),
+            "boolean" => ("BOOLEAN", None),
+            _ => ("JSONB", None),
+        },
+    }
+}
+
+/// Column name for an attribute, using `arg{index}` for unnamed attributes
+/// and an `attr_` prefix for names taken by the metadata columns
+pub(crate) fn column_name(name: &str, index: usize) -> String {
+    codegen::column_name(name, index, METADATA_COLUMNS, "attr_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegen::parser::TypeInfo;
+
+    fn attribute(name: &str, base_type: &str, reference: Option<&str>, required: bool) -> PropertySchema {
+        PropertySchema {
+            name: name.to_string(),
+            type_info: TypeInfo {
+                base_type: base_type.to_string(),
+                reference: reference.map(String::from),
+                items: None,
+                enum_values: None,
+                format: None,
+            },
+            description: None,
+            required,
+        }
+    }
+
+    #[test]
+    fn test_column_definition() {
+        let definitions = HashMap::new();
+        assert_eq!(
+            column_definition("amount", &attribute("amount", "object", Some("Uint128"), true), &definitions),
+            "amount NUMERIC(78, 0) NOT NULL CHECK (amount >= 0)"
+        );
+        assert_eq!(
+            column_definition("memo", &attribute("memo", "string", None, false), &definitions),
+            "memo TEXT"
+        );
+        assert_eq!(
+            column_definition("\"to\"", &attribute("to", "object", Some("Addr"), true), &definitions),
+            format!("\"to\" TEXT NOT NULL CHECK (\"to\" ~ '{}')", BECH32_PATTERN)
+        );
+    }
+
+    #[test]
+    fn test_enum_column_definition() {
+        let mut definitions = HashMap::new();
+        definitions.insert(
+            "Side".to_string(),
+            TypeDefinition {
+                name: "Side".to_string(),
+                description: None,
+                properties: vec![],
+                is_enum: true,
+                variants: vec!["buy".to_string(), "sell".to_string()],
+                one_of: vec![],
+                alias: None,
+            },
+        );
+        assert_eq!(
+            column_definition("side", &attribute("side", "object", Some("Side"), true), &definitions),
+            "side TEXT NOT NULL CHECK (side IN ('buy', 'sell'))"
+        );
+    }
+
+    #[test]
+    fn test_column_name() {
+        assert_eq!(column_name("", 3), "arg3");
+        assert_eq!(column_name("tokenId", 0), "token_id");
+        assert_eq!(column_name("from", 0), "\"from\"");
+        assert_eq!(column_name("block_height", 0), "attr_block_height");
+    }
+}
@@ -5,6 +5,10 @@
 
 pub mod parser;
 pub mod generator;
+pub mod client;
+pub mod migrations;
+pub mod handlers;
+pub mod typescript;
 pub mod templates;
 pub mod cli;
 
@@ -13,7 +17,11 @@ mod tests;
 
 pub use parser::CosmWasmMsgParser;
 pub use generator::CosmosContractCodegen;
+pub use templates::CosmosTemplateManager;
 
+use std::path::{Path, PathBuf};
+
+use indexer_core::codegen::{check_drift, write_reference_templates, Drift, GeneratedFile, Provenance};
 use indexer_core::Result;
 
 /// Configuration for cosmos contract code generation
@@ -31,6 +39,11 @@ pub struct CosmosCodegenConfig {
     pub features: Vec<String>,
     /// Whether this is a dry run
     pub dry_run: bool,
+    /// Directory of Handlebars templates overriding or adding generated files
+    pub templates_dir: Option<String>,
+    /// Version prefix of the generated migration, the current time past
+    /// the existing migrations when unset
+    pub migration_version: Option<String>,
 }
 
 impl Default for CosmosCodegenConfig {
@@ -45,8 +58,11 @@ impl Default for CosmosCodegenConfig {
                 "storage".to_string(),
                 "api".to_string(),
                 "migrations".to_string(),
+                "handlers".to_string(),
             ],
             dry_run: false,
+            templates_dir: None,
+            migration_version: None,
         }
     }
 }
@@ -56,11 +72,65 @@ pub async fn generate_contract_code(
     msg_file_path: &str,
     config: CosmosCodegenConfig,
 ) -> Result<()> {
+    generate_contract(msg_file_path, config, false).await?;
+    Ok(())
+}
+
+/// Regenerate code in memory and compare it with the files in the output
+/// directory
+///
+/// Returns the generated files that are missing or differ on disk and the
+/// generated files on disk that are no longer generated, so stale bindings
+/// can be caught without writing anything.
+pub async fn check_contract_code(
+    msg_file_path: &str,
+    config: CosmosCodegenConfig,
+) -> Result<Vec<Drift>> {
+    let files = generate_contract(msg_file_path, config, true).await?;
+    check_drift(&files)
+}
+
+/// Write the files generated for a contract as reference templates below
+/// `templates_dir`
+///
+/// Each template renders the built-in output of its file unchanged, so
+/// copies edited in a templates directory override the generated files, see
+/// [`CosmosTemplateManager::with_templates_dir`]. Templates configured in
+/// `config` are not applied. Returns the paths of the templates.
+pub async fn dump_contract_templates(
+    msg_file_path: &str,
+    mut config: CosmosCodegenConfig,
+    templates_dir: &str,
+) -> Result<Vec<PathBuf>> {
+    config.templates_dir = None;
+    let output_dir = PathBuf::from(&config.output_dir);
+    let files = generate_contract(msg_file_path, config, true).await?;
+    write_reference_templates(&files, &output_dir, Path::new(templates_dir))
+}
+
+/// Generate code for a contract, returning the files when generated
+/// `in_memory`
+async fn generate_contract(
+    msg_file_path: &str,
+    config: CosmosCodegenConfig,
+    in_memory: bool,
+) -> Result<Vec<GeneratedFile>> {
     let parser = CosmWasmMsgParser::new();
     let schema = parser.parse_file(msg_file_path)?;
-    
-    let codegen = CosmosContractCodegen::new(config);
+
+    let templates = match &config.templates_dir {
+        Some(dir) => Some(CosmosTemplateManager::new()?.with_templates_dir(dir)?),
+        None => None,
+    };
+    let mut codegen = CosmosContractCodegen::new(config)
+        .with_provenance(Provenance::from_files(&[msg_file_path])?);
+    if let Some(templates) = templates {
+        codegen = codegen.with_templates(templates);
+    }
+    if in_memory {
+        codegen = codegen.in_memory();
+    }
     codegen.generate_all(&schema).await?;
-    
-    Ok(())
+
+    Ok(codegen.take_files())
 } 
\ No newline at end of file
@@ -22,6 +22,8 @@ pub struct CosmWasmSchema {
     pub events: Vec<EventSchema>,
     /// Custom type definitions
     pub definitions: HashMap<String, TypeDefinition>,
+    /// Query response types keyed by query variant name
+    pub responses: HashMap<String, TypeInfo>,
 }
 
 /// Message schema definition
@@ -48,6 +50,19 @@ pub struct MessageVariant {
     pub properties: Vec<PropertySchema>,
     /// Required fields
     pub required: Vec<String>,
+    /// Shape of the variant in JSON
+    pub kind: VariantKind,
+}
+
+/// Shape of an enum variant in JSON
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum VariantKind {
+    /// `{"name": {...}}` with the variant properties as fields
+    Struct,
+    /// The bare string `"name"`
+    Unit,
+    /// `{"name": value}` with a single unnamed value of the given type
+    Newtype(TypeInfo),
 }
 
 /// Property schema definition
@@ -74,6 +89,8 @@ pub struct TypeInfo {
     pub items: Option<Box<TypeInfo>>,
     /// Enum values (for string enums)
     pub enum_values: Option<Vec<String>>,
+    /// Format of integer and number types (e.g. uint64)
+    pub format: Option<String>,
 }
 
 /// Custom type definition
@@ -89,6 +106,10 @@ pub struct TypeDefinition {
     pub is_enum: bool,
     /// Enum variants (if is_enum is true)
     pub variants: Vec<String>,
+    /// Variant definitions (for oneOf enums whose variants may carry data)
+    pub one_of: Vec<MessageVariant>,
+    /// Aliased type (for definitions that are neither objects nor enums)
+    pub alias: Option<TypeInfo>,
 }
 
 /// Event schema definition
@@ -132,6 +153,7 @@ impl CosmWasmMsgParser {
             migrate_msg: None,
             events: Vec::new(),
             definitions: HashMap::new(),
+            responses: HashMap::new(),
         };
 
         // Parse definitions first
@@ -177,21 +199,39 @@ impl CosmWasmMsgParser {
             }
         } else {
             // Combined schema file - look for specific message schemas
-            if let Some(inst_msg) = value.get("instantiate").or_else(|| value.get("InstantiateMsg")) {
+            // Messages a contract does not define are null
+            let message = |key: &str, alias: &str| value.get(key).or_else(|| value.get(alias)).filter(|v| !v.is_null());
+
+            if let Some(inst_msg) = message("instantiate", "InstantiateMsg") {
                 schema.instantiate_msg = Some(self.parse_message_schema(inst_msg)?);
             }
 
-            if let Some(exec_msg) = value.get("execute").or_else(|| value.get("ExecuteMsg")) {
+            if let Some(exec_msg) = message("execute", "ExecuteMsg") {
                 schema.execute_msg = Some(self.parse_message_schema(exec_msg)?);
             }
 
-            if let Some(query_msg) = value.get("query").or_else(|| value.get("QueryMsg")) {
+            if let Some(query_msg) = message("query", "QueryMsg") {
                 schema.query_msg = Some(self.parse_message_schema(query_msg)?);
             }
 
-            if let Some(migrate_msg) = value.get("migrate").or_else(|| value.get("MigrateMsg")) {
+            if let Some(migrate_msg) = message("migrate", "MigrateMsg") {
                 schema.migrate_msg = Some(self.parse_message_schema(migrate_msg)?);
             }
+
+            // Each message schema of a combined file carries its own definitions
+            for key in ["instantiate", "execute", "query", "migrate", "InstantiateMsg", "ExecuteMsg", "QueryMsg", "MigrateMsg"] {
+                if let Some(msg) = value.get(key) {
+                    self.collect_definitions(msg, &mut schema.definitions);
+                }
+            }
+
+            if let Some(responses) = value.get("responses").and_then(|v| v.as_object()) {
+                for (query, response) in responses {
+                    self.collect_definitions(response, &mut schema.definitions);
+                    let response_type = self.parse_response(response, &mut schema.definitions)?;
+                    schema.responses.insert(query.clone(), response_type);
+                }
+            }
         }
 
         // Parse events if present
@@ -217,9 +257,7 @@ impl CosmWasmMsgParser {
         // Check if this is an enum (oneOf) or struct (properties)
         if let Some(one_of) = value.get("oneOf").and_then(|v| v.as_array()) {
             is_enum = true;
-            for variant_value in one_of {
-                variants.push(self.parse_message_variant(variant_value)?);
-            }
+            variants = self.parse_variants(one_of)?;
         } else if let Some(properties) = value.get("properties").and_then(|v| v.as_object()) {
             // This is a struct-like message
             let required: Vec<&str> = value.get("required")
@@ -242,6 +280,7 @@ impl CosmWasmMsgParser {
                 description: description.clone(),
                 properties: props,
                 required: required.iter().map(|s| s.to_string()).collect(),
+                kind: VariantKind::Struct,
             });
         }
 
@@ -253,39 +292,135 @@ impl CosmWasmMsgParser {
         })
     }
 
+    /// Parse the `oneOf` variants of an enum
+    fn parse_variants(&self, one_of: &[Value]) -> Result<Vec<MessageVariant>> {
+        let mut variants = Vec::new();
+        for variant_value in one_of {
+            // Unit variants are grouped into a single string enum
+            if let Some(names) = variant_value.get("enum").and_then(|v| v.as_array()) {
+                let description = variant_value.get("description").and_then(|v| v.as_str()).map(String::from);
+                for name in names.iter().filter_map(|v| v.as_str()) {
+                    variants.push(MessageVariant {
+                        name: name.to_string(),
+                        description: description.clone(),
+                        properties: Vec::new(),
+                        required: Vec::new(),
+                        kind: VariantKind::Unit,
+                    });
+                }
+            } else {
+                variants.push(self.parse_message_variant(variant_value)?);
+            }
+        }
+        Ok(variants)
+    }
+
     /// Parse a message variant
     fn parse_message_variant(&self, value: &Value) -> Result<MessageVariant> {
         let title = value.get("title").and_then(|v| v.as_str()).map(String::from);
         let description = value.get("description").and_then(|v| v.as_str()).map(String::from);
-        
-        let name = title.unwrap_or_else(|| "Variant".to_string());
+        let required = Self::parse_required(value);
+
+        // cosmwasm-schema wraps each variant as `{"name": ...}`, with the name
+        // as the only (required) property
+        let wrapped = value.get("properties")
+            .and_then(|v| v.as_object())
+            .filter(|props| props.len() == 1 && required.len() == 1)
+            .and_then(|props| props.get(&required[0]).map(|inner| (required[0].clone(), inner)));
+
+        if let Some((name, inner)) = wrapped {
+            let description = description.or_else(|| inner.get("description").and_then(|v| v.as_str()).map(String::from));
+            let is_struct = inner.get("properties").is_some()
+                || (inner.get("type").and_then(|v| v.as_str()) == Some("object") && inner.get("$ref").is_none());
+
+            return Ok(if is_struct {
+                MessageVariant {
+                    name,
+                    description,
+                    properties: Self::parse_properties(inner)?,
+                    required: Self::parse_required(inner),
+                    kind: VariantKind::Struct,
+                }
+            } else {
+                MessageVariant {
+                    name,
+                    description,
+                    properties: Vec::new(),
+                    required: Vec::new(),
+                    kind: VariantKind::Newtype(Self::parse_type_info_static(inner)?),
+                }
+            });
+        }
+
+        Ok(MessageVariant {
+            name: title.unwrap_or_else(|| "Variant".to_string()),
+            description,
+            properties: Self::parse_properties(value)?,
+            required,
+            kind: VariantKind::Struct,
+        })
+    }
+
+    /// Parse the properties of an object schema
+    fn parse_properties(value: &Value) -> Result<Vec<PropertySchema>> {
+        let required = Self::parse_required(value);
         let mut properties = Vec::new();
 
         if let Some(props) = value.get("properties").and_then(|v| v.as_object()) {
-            let required: Vec<&str> = value.get("required")
-                .and_then(|v| v.as_array())
-                .map(|arr| arr.iter().filter_map(|v| v.as_str()).collect())
-                .unwrap_or_default();
-
             for (prop_name, prop_value) in props {
                 properties.push(PropertySchema {
                     name: prop_name.clone(),
-                    type_info: self.parse_type_info(prop_value)?,
+                    type_info: Self::parse_type_info_static(prop_value)?,
                     description: prop_value.get("description").and_then(|v| v.as_str()).map(String::from),
-                    required: required.contains(&prop_name.as_str()),
+                    required: required.contains(prop_name),
                 });
             }
         }
 
-        Ok(MessageVariant {
-            name,
-            description,
-            properties,
-            required: value.get("required")
-                .and_then(|v| v.as_array())
-                .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect::<Vec<String>>())
-                .unwrap_or_default(),
-        })
+        Ok(properties)
+    }
+
+    /// Names of the required properties of an object schema
+    fn parse_required(value: &Value) -> Vec<String> {
+        value.get("required")
+            .and_then(|v| v.as_array())
+            .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+            .unwrap_or_default()
+    }
+
+    /// Add the definitions of a schema that are not defined yet
+    fn collect_definitions(&self, value: &Value, definitions: &mut HashMap<String, TypeDefinition>) {
+        if let Some(defs) = value.get("definitions").and_then(|v| v.as_object()) {
+            for (name, def) in defs {
+                if !definitions.contains_key(name) {
+                    if let Ok(type_def) = self.parse_type_definition(name, def) {
+                        definitions.insert(name.clone(), type_def);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Parse a query response schema, defining named objects and enums under their title
+    fn parse_response(&self, value: &Value, definitions: &mut HashMap<String, TypeDefinition>) -> Result<TypeInfo> {
+        let title = value.get("title").and_then(|v| v.as_str());
+        let is_named_type = value.get("properties").is_some() || value.get("oneOf").is_some() || value.get("enum").is_some();
+
+        match title {
+            Some(title) if is_named_type => {
+                if !definitions.contains_key(title) {
+                    definitions.insert(title.to_string(), self.parse_type_definition(title, value)?);
+                }
+                Ok(TypeInfo {
+                    base_type: "object".to_string(),
+                    reference: Some(title.to_string()),
+                    items: None,
+                    enum_values: None,
+                    format: None,
+                })
+            }
+            _ => Self::parse_type_info_static(value),
+        }
     }
 
     /// Parse type information
@@ -295,10 +430,24 @@ impl CosmWasmMsgParser {
 
     /// Parse type information (static implementation)
     fn parse_type_info_static(value: &Value) -> Result<TypeInfo> {
-        let base_type = value.get("type")
-            .and_then(|v| v.as_str())
-            .unwrap_or("object")
-            .to_string();
+        // Optional references are `anyOf` the reference and null, and
+        // documented references are wrapped in `allOf`
+        if let Some(options) = value.get("anyOf").or_else(|| value.get("allOf")).and_then(|v| v.as_array()) {
+            if let Some(option) = options.iter().find(|option| option.get("type").and_then(|v| v.as_str()) != Some("null")) {
+                return Self::parse_type_info_static(option);
+            }
+        }
+
+        // Optional primitives list "null" next to their type
+        let base_type = match value.get("type") {
+            Some(Value::String(base_type)) => base_type.clone(),
+            Some(Value::Array(types)) => types.iter()
+                .filter_map(|v| v.as_str())
+                .find(|base_type| *base_type != "null")
+                .unwrap_or("null")
+                .to_string(),
+            _ => "object".to_string(),
+        };
 
         let reference = value.get("$ref")
             .and_then(|v| v.as_str())
@@ -317,11 +466,14 @@ impl CosmWasmMsgParser {
             .and_then(|v| v.as_array())
             .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect());
 
+        let format = value.get("format").and_then(|v| v.as_str()).map(String::from);
+
         Ok(TypeInfo {
             base_type,
             reference,
             items,
             enum_values,
+            format,
         })
     }
 
@@ -331,6 +483,8 @@ impl CosmWasmMsgParser {
         let mut properties = Vec::new();
         let mut is_enum = false;
         let mut variants = Vec::new();
+        let mut one_of = Vec::new();
+        let mut alias = None;
 
         if let Some(props) = value.get("properties").and_then(|v| v.as_object()) {
             let required: Vec<&str> = value.get("required")
@@ -349,6 +503,16 @@ impl CosmWasmMsgParser {
         } else if let Some(enum_vals) = value.get("enum").and_then(|v| v.as_array()) {
             is_enum = true;
             variants = enum_vals.iter().filter_map(|v| v.as_str().map(String::from)).collect();
+        } else if let Some(one_of_vals) = value.get("oneOf").and_then(|v| v.as_array()) {
+            is_enum = true;
+            one_of = self.parse_variants(one_of_vals)?;
+            variants = one_of.iter().map(|variant| variant.name.clone()).collect();
+        } else {
+            // Objects without properties stay empty structs
+            let type_info = Self::parse_type_info_static(value)?;
+            if type_info.base_type != "object" || type_info.reference.is_some() {
+                alias = Some(type_info);
+            }
         }
 
         Ok(TypeDefinition {
@@ -357,6 +521,8 @@ impl CosmWasmMsgParser {
             properties,
             is_enum,
             variants,
+            one_of,
+            alias,
         })
     }
 
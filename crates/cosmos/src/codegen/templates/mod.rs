@@ -1,63 +1,107 @@
 //! Template system for cosmos contract code generation
 //! 
-//! Contains Handlebars templates for generating different types of code.
+//! Renders user Handlebars templates that override or add generated files,
+//! named by the path of the file they render, see
+//! [`CosmosTemplateManager::with_templates_dir`]. The generated code itself is built by the
+//! generators; [`super::dump_contract_templates`] writes their output as
+//! reference templates to start overrides from.
 
-use handlebars::Handlebars;
+use std::collections::HashMap;
+use std::path::Path;
+
+use handlebars::{Context, Handlebars, Helper, HelperResult, Output, RenderContext, RenderError};
+use indexer_core::codegen;
 use indexer_core::Result;
+use serde_json::Value;
+
+use super::client::{property_rust_type, schema_rust_type};
+use super::migrations::column_type;
+use super::parser::{PropertySchema, TypeDefinition, TypeInfo};
+
+/// Helpers registered for templates in addition to the Handlebars built-ins
+///
+/// - `snake_case`, `camel_case`, `pascal_case`, `screaming_snake_case` and
+///   `kebab_case` convert the case of a string
+/// - `rust_type` maps a property, a type, or a bare JSON schema type such
+///   as `string`, to the Rust type generated for it
+/// - `sql_type` maps an event attribute to the PostgreSQL type of its column
+///   in the generated event tables
+pub const HELPERS: &[&str] = &[
+    "snake_case",
+    "camel_case",
+    "pascal_case",
+    "screaming_snake_case",
+    "kebab_case",
+    "rust_type",
+    "sql_type",
+];
 
 /// Template manager for cosmos contract code generation
 pub struct CosmosTemplateManager {
     handlebars: Handlebars<'static>,
+    /// Names of the templates loaded from a templates directory
+    custom: Vec<String>,
 }
 
 impl CosmosTemplateManager {
-    /// Create a new template manager and register all templates
+    /// Create a template manager with the [`HELPERS`] and no templates
     pub fn new() -> Result<Self> {
         let mut handlebars = Handlebars::new();
-        
-        // Register built-in templates
-        Self::register_templates(&mut handlebars)?;
-        
-        Ok(Self { handlebars })
-    }
+        // Generated code is not HTML
+        handlebars.register_escape_fn(handlebars::no_escape);
 
-    /// Register all built-in templates
-    fn register_templates(handlebars: &mut Handlebars) -> Result<()> {
-        // Client templates
-        handlebars.register_template_string("client_mod", include_str!("client_mod.hbs"))
-            .map_err(|e| indexer_core::Error::Config(format!("Failed to register client_mod template: {}", e)))?;
-        
-        handlebars.register_template_string("client_query", include_str!("client_query.hbs"))
-            .map_err(|e| indexer_core::Error::Config(format!("Failed to register client_query template: {}", e)))?;
+        Self::register_helpers(&mut handlebars);
         
-        handlebars.register_template_string("client_execute", include_str!("client_execute.hbs"))
-            .map_err(|e| indexer_core::Error::Config(format!("Failed to register client_execute template: {}", e)))?;
-        
-        handlebars.register_template_string("client_types", include_str!("client_types.hbs"))
-            .map_err(|e| indexer_core::Error::Config(format!("Failed to register client_types template: {}", e)))?;
-
-        // Storage templates
-        handlebars.register_template_string("storage_postgres", include_str!("storage_postgres.hbs"))
-            .map_err(|e| indexer_core::Error::Config(format!("Failed to register storage_postgres template: {}", e)))?;
-        
-        handlebars.register_template_string("storage_rocksdb", include_str!("storage_rocksdb.hbs"))
-            .map_err(|e| indexer_core::Error::Config(format!("Failed to register storage_rocksdb template: {}", e)))?;
-        
-        handlebars.register_template_string("storage_traits", include_str!("storage_traits.hbs"))
-            .map_err(|e| indexer_core::Error::Config(format!("Failed to register storage_traits template: {}", e)))?;
+        Ok(Self { handlebars, custom: Vec::new() })
+    }
 
-        // API templates
-        handlebars.register_template_string("api_rest", include_str!("api_rest.hbs"))
-            .map_err(|e| indexer_core::Error::Config(format!("Failed to register api_rest template: {}", e)))?;
-        
-        handlebars.register_template_string("api_graphql", include_str!("api_graphql.hbs"))
-            .map_err(|e| indexer_core::Error::Config(format!("Failed to register api_graphql template: {}", e)))?;
+    /// Load the `.hbs` templates below `dir`, named by their path relative to
+    /// it without the extension
+    ///
+    /// Templates named as a file, such as `client/mod.rs`, render that file
+    /// of the generated code in place of the built-in generator or in
+    /// addition to it; others can be included as partials. Migrations can be
+    /// rendered by a template named without their version, such as
+    /// `migrations/token_events.up.sql`. Templates are checked when loaded, for syntax and
+    /// for helpers and partials that don't exist.
+    pub fn with_templates_dir(mut self, dir: impl AsRef<Path>) -> Result<Self> {
+        let names = codegen::register_templates_dir(&mut self.handlebars, dir.as_ref(), HELPERS)?;
+        self.custom.extend(names);
+        Ok(self)
+    }
 
-        // Migration templates
-        handlebars.register_template_string("migration_sql", include_str!("migration.hbs"))
-            .map_err(|e| indexer_core::Error::Config(format!("Failed to register migration_sql template: {}", e)))?;
+    /// Register the [`HELPERS`]
+    fn register_helpers(handlebars: &mut Handlebars) {
+        codegen::register_case_helpers(handlebars);
 
-        Ok(())
+        handlebars.register_helper(
+            "rust_type",
+            Box::new(|h: &Helper, _: &Handlebars, _: &Context, _: &mut RenderContext, out: &mut dyn Output| -> HelperResult {
+                let value = helper_value(h)?;
+                let rust_type = match value {
+                    Value::String(base_type) => schema_rust_type(&bare_type(base_type)),
+                    Value::Object(object) if object.contains_key("type_info") => {
+                        property_rust_type(&from_helper_value(h, value)?)
+                    }
+                    _ => schema_rust_type(&from_helper_value(h, value)?),
+                };
+                out.write(&rust_type)?;
+                Ok(())
+            }),
+        );
+        handlebars.register_helper(
+            "sql_type",
+            Box::new(|h: &Helper, _: &Handlebars, ctx: &Context, _: &mut RenderContext, out: &mut dyn Output| -> HelperResult {
+                let attribute: PropertySchema = from_helper_value(h, helper_value(h)?)?;
+                // String enums defined in the schema are checked against their variants
+                let definitions: HashMap<String, TypeDefinition> = ctx.data()
+                    .pointer("/schema/definitions")
+                    .and_then(|definitions| serde_json::from_value(definitions.clone()).ok())
+                    .unwrap_or_default();
+                out.write(column_type("", &attribute, &definitions).0)?;
+                Ok(())
+            }),
+        );
     }
 
     /// Render a template with the given data
@@ -66,6 +110,16 @@ impl CosmosTemplateManager {
             .map_err(|e| indexer_core::Error::Config(format!("Failed to render template {}: {}", template_name, e)))
     }
 
+    /// Whether a template of the given name is registered
+    pub fn has_template(&self, template_name: &str) -> bool {
+        self.handlebars.has_template(template_name)
+    }
+
+    /// Names of the templates loaded from a templates directory
+    pub fn custom_templates(&self) -> &[String] {
+        &self.custom
+    }
+
     /// Get list of available templates
     pub fn available_templates(&self) -> Vec<String> {
         self.handlebars.get_templates().keys().cloned().collect()
@@ -76,4 +130,27 @@ impl Default for CosmosTemplateManager {
     fn default() -> Self {
         Self::new().expect("Failed to initialize template manager")
     }
-} 
\ No newline at end of file
+} 
+
+/// First parameter passed to a helper
+fn helper_value<'a>(h: &'a Helper) -> std::result::Result<&'a Value, RenderError> {
+    h.param(0).map(|param| param.value())
+        .ok_or_else(|| RenderError::new(format!("{} expects a parameter", h.name())))
+}
+
+/// Part of the schema model passed to a helper
+fn from_helper_value<T: serde::de::DeserializeOwned>(h: &Helper, value: &Value) -> std::result::Result<T, RenderError> {
+    serde_json::from_value(value.clone())
+        .map_err(|e| RenderError::new(format!("{} got an unexpected parameter: {}", h.name(), e)))
+}
+
+/// Type of a bare JSON schema type name
+fn bare_type(base_type: &str) -> TypeInfo {
+    TypeInfo {
+        base_type: base_type.to_string(),
+        reference: None,
+        items: None,
+        enum_values: None,
+        format: None,
+    }
+}
@@ -0,0 +1,131 @@
+//! Compiled fixture for the generated contract client
+//!
+//! `fixtures/cw20_client` is the client generated for
+//! `fixtures/cw20_schema.json`, laid out as the generated `client` module. It
+//! is compiled here against a mock [`CosmWasmClient`], so a change to the
+//! generator that no longer compiles against `indexer_core`, cosmwasm-std and
+//! the `wasm` module fails these tests, and the JSON messages the methods
+//! send are checked. Regenerate the fixture when the generator output changes.
+
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use cosmwasm_std::{Addr, Binary, Coin, Uint128};
+use indexer_core::Result;
+use serde_json::{json, Value};
+
+use crate::codegen::client::ClientGenerator;
+use crate::codegen::parser::CosmWasmMsgParser;
+use crate::wasm::CosmWasmClient;
+
+const CW20_SCHEMA: &str = include_str!("fixtures/cw20_schema.json");
+const CW20_CLIENT: [(&str, &str); 4] = [
+    ("mod.rs", include_str!("fixtures/cw20_client/mod.rs")),
+    ("types.rs", include_str!("fixtures/cw20_client/types.rs")),
+    ("execute.rs", include_str!("fixtures/cw20_client/execute.rs")),
+    ("query.rs", include_str!("fixtures/cw20_client/query.rs")),
+];
+
+const CONTRACT: &str = "cosmos1cw20example";
+const OWNER: &str = "cosmos1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5lzv7xu";
+
+#[allow(dead_code)]
+#[path = "fixtures/cw20_client/mod.rs"]
+mod generated;
+
+use generated::{BalanceResponse, Cw20Client, Expiration};
+
+/// Client recording the messages it is sent and answering queries with
+/// `response`
+#[derive(Default)]
+struct MockClient {
+    response: Vec<u8>,
+    sent: Mutex<Vec<(String, Value)>>,
+}
+
+impl MockClient {
+    fn answering(response: Value) -> Self {
+        Self { response: serde_json::to_vec(&response).unwrap(), ..Self::default() }
+    }
+
+    /// Contract address and JSON of the last message sent
+    fn last_sent(&self) -> (String, Value) {
+        self.sent.lock().unwrap().last().cloned().unwrap()
+    }
+
+    fn record(&self, contract_address: &str, msg: &[u8]) {
+        let msg = serde_json::from_slice(msg).unwrap();
+        self.sent.lock().unwrap().push((contract_address.to_string(), msg));
+    }
+}
+
+#[async_trait]
+impl CosmWasmClient for MockClient {
+    async fn query_wasm_smart(&self, contract_address: &str, msg: Vec<u8>) -> Result<Vec<u8>> {
+        self.record(contract_address, &msg);
+        Ok(self.response.clone())
+    }
+
+    async fn execute_wasm(&self, contract_address: &str, msg: Vec<u8>, funds: &[Coin]) -> Result<String> {
+        assert!(funds.is_empty());
+        self.record(contract_address, &msg);
+        Ok("TXHASH".to_string())
+    }
+}
+
+#[test]
+fn test_fixture_matches_generator() {
+    let schema = CosmWasmMsgParser::new().parse_content(CW20_SCHEMA).unwrap();
+    let generator = ClientGenerator::new(&schema, "Cw20Client", CONTRACT);
+    let generated = [
+        generator.module(),
+        generator.types(),
+        generator.execute_methods(),
+        generator.query_methods(),
+    ];
+    for ((file, fixture), code) in CW20_CLIENT.iter().zip(generated) {
+        assert!(
+            code == *fixture,
+            "fixtures/cw20_client/{} is out of date, regenerate it from fixtures/cw20_schema.json",
+            file
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_execute_messages() {
+    let client = Cw20Client::new(MockClient::default(), CONTRACT.to_string());
+
+    assert_eq!(client.pause().await.unwrap(), "TXHASH");
+    assert_eq!(client.client().last_sent(), (CONTRACT.to_string(), json!("pause")));
+
+    client
+        .send(Uint128::new(5), OWNER.to_string(), Some(Expiration::AtHeight(10)), Binary::from(b"hi".to_vec()))
+        .await
+        .unwrap();
+    assert_eq!(
+        client.client().last_sent().1,
+        json!({"send": {"amount": "5", "contract": OWNER, "expires": {"at_height": 10}, "msg": "aGk="}})
+    );
+
+    // Unset optional fields are left out
+    client.send(Uint128::new(5), OWNER.to_string(), None, Binary::default()).await.unwrap();
+    assert_eq!(client.client().last_sent().1, json!({"send": {"amount": "5", "contract": OWNER, "msg": ""}}));
+
+    client.set_owner(Addr::unchecked(OWNER)).await.unwrap();
+    assert_eq!(client.client().last_sent().1, json!({"set_owner": OWNER}));
+}
+
+#[tokio::test]
+async fn test_query_responses() {
+    let client = Cw20Client::new(MockClient::answering(json!({"balance": "42"})), CONTRACT.to_string());
+
+    let balance = client.balance(OWNER.to_string()).await.unwrap();
+    assert_eq!(balance, BalanceResponse { balance: Uint128::new(42) });
+    assert_eq!(client.client().last_sent(), (CONTRACT.to_string(), json!({"balance": {"address": OWNER}})));
+
+    // Queries without a declared response decode into the requested type
+    let minter: Value = client.minter().await.unwrap();
+    assert_eq!(minter, json!({"balance": "42"}));
+    assert_eq!(client.client().last_sent().1, json!({"minter": {}}));
+}
@@ -0,0 +1,24 @@
+//! Generated execute methods for contract: cosmos1cw20example
+
+use super::types::*;
+use super::Cw20Client;
+use cosmwasm_std::{Addr, Binary, Uint128};
+use indexer_core::Result;
+use indexer_cosmos::wasm::CosmWasmClient;
+
+impl<C: CosmWasmClient> Cw20Client<C> {
+    /// Execute `pause`
+    pub async fn pause(&self) -> Result<String> {
+        self.execute(&ExecuteMsg::Pause, &[]).await
+    }
+
+    /// Execute `send`
+    pub async fn send(&self, amount: Uint128, contract: String, expires: Option<Expiration>, msg: Binary) -> Result<String> {
+        self.execute(&ExecuteMsg::Send { amount, contract, expires, msg }, &[]).await
+    }
+
+    /// Execute `set_owner`
+    pub async fn set_owner(&self, value: Addr) -> Result<String> {
+        self.execute(&ExecuteMsg::SetOwner(value), &[]).await
+    }
+}
@@ -0,0 +1,51 @@
+//! Generated client code for contract: cosmos1cw20example
+
+pub mod types;
+mod execute;
+mod query;
+
+pub use types::*;
+
+use cosmwasm_std::Coin;
+use indexer_core::Result;
+use indexer_cosmos::wasm::CosmWasmClient;
+use serde::de::DeserializeOwned;
+
+/// Client for the cosmos1cw20example contract
+pub struct Cw20Client<C: CosmWasmClient> {
+    client: C,
+    contract_address: String,
+}
+
+impl<C: CosmWasmClient> Cw20Client<C> {
+    /// Create a client for the contract at `contract_address`
+    pub fn new(client: C, contract_address: String) -> Self {
+        Self {
+            client,
+            contract_address,
+        }
+    }
+
+    /// Get the contract address
+    pub fn contract_address(&self) -> &str {
+        &self.contract_address
+    }
+
+    /// Get the underlying CosmWasm client
+    pub fn client(&self) -> &C {
+        &self.client
+    }
+
+    /// Execute `msg` with `funds` attached and return the transaction hash
+    pub async fn execute(&self, msg: &ExecuteMsg, funds: &[Coin]) -> Result<String> {
+        let msg = serde_json::to_vec(msg)?;
+        self.client.execute_wasm(&self.contract_address, msg, funds).await
+    }
+
+    /// Run the smart query `msg` and decode its response
+    pub async fn query<R: DeserializeOwned>(&self, msg: &QueryMsg) -> Result<R> {
+        let msg = serde_json::to_vec(msg)?;
+        let response = self.client.query_wasm_smart(&self.contract_address, msg).await?;
+        Ok(serde_json::from_slice(&response)?)
+    }
+}
@@ -0,0 +1,19 @@
+//! Generated query methods for contract: cosmos1cw20example
+
+use super::types::*;
+use super::Cw20Client;
+use indexer_core::Result;
+use indexer_cosmos::wasm::CosmWasmClient;
+use serde::de::DeserializeOwned;
+
+impl<C: CosmWasmClient> Cw20Client<C> {
+    /// Query `balance`
+    pub async fn balance(&self, address: String) -> Result<BalanceResponse> {
+        self.query(&QueryMsg::Balance { address }).await
+    }
+
+    /// Query `minter`
+    pub async fn minter<R: DeserializeOwned>(&self) -> Result<R> {
+        self.query(&QueryMsg::Minter {}).await
+    }
+}
@@ -0,0 +1,43 @@
+//! Generated message and response types for contract: cosmos1cw20example
+
+use serde::{Deserialize, Serialize};
+use cosmwasm_std::{Addr, Binary, Uint128};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum ExecuteMsg {
+    #[serde(rename = "pause")]
+    Pause,
+    #[serde(rename = "send")]
+    Send {
+        amount: Uint128,
+        contract: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        expires: Option<Expiration>,
+        msg: Binary,
+    },
+    #[serde(rename = "set_owner")]
+    SetOwner(Addr),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum QueryMsg {
+    #[serde(rename = "balance")]
+    Balance {
+        address: String,
+    },
+    #[serde(rename = "minter")]
+    Minter {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BalanceResponse {
+    pub balance: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum Expiration {
+    #[serde(rename = "at_height")]
+    AtHeight(u64),
+    #[serde(rename = "never")]
+    Never {},
+}
@@ -0,0 +1,120 @@
+//! Generated event handler for contract: cosmos1cw20example
+//! Chain: cosmoshub-4
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use indexer_core::event::{Event, EventData, UnifiedEvent};
+use indexer_core::pipeline::{event_contract_address, event_index, EventHandler, EventHandlerRegistry};
+use indexer_core::{Error, Result};
+use serde::Serialize;
+use sqlx::PgPool;
+
+use super::client::types::*;
+
+/// Chain the contract is deployed on
+pub const CHAIN_ID: &str = "cosmoshub-4";
+
+/// Address of the contract
+pub const CONTRACT_ADDRESS: &str = "cosmos1cw20example";
+
+/// Statements flagging the rows of blocks from `$1` on as removed
+const ROLLBACK_STATEMENTS: &[&str] = &[
+    "UPDATE cw20_transfer_events SET removed = TRUE WHERE block_height >= $1",
+];
+
+/// Decodes the events of the cosmos1cw20example contract into its event tables
+pub struct Cw20EventHandler {
+    pool: PgPool,
+}
+
+impl Cw20EventHandler {
+    /// Create a handler writing to the event tables in `pool`
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Register a handler for the contract's events on its chain
+    pub fn register(registry: &mut EventHandlerRegistry, pool: PgPool) {
+        registry.register(CHAIN_ID, CONTRACT_ADDRESS, Arc::new(Self::new(pool)));
+    }
+}
+
+#[async_trait]
+impl EventHandler for Cw20EventHandler {
+    fn name(&self) -> &str {
+        "Cw20EventHandler"
+    }
+
+    async fn handle_event(&self, event: &dyn Event) -> Result<bool> {
+        let Some(EventData::Cosmos { attributes, .. }) =
+            event.as_any().downcast_ref::<UnifiedEvent>().map(|event| &event.event_data)
+        else {
+            return Ok(false);
+        };
+        // Custom events are emitted as `wasm-{name}`, the default one as
+        // `wasm` with an `action` attribute
+        let name = match event.event_type().strip_prefix("wasm-") {
+            Some(name) => name,
+            None if event.event_type() == "wasm" => match attributes.iter().find(|attribute| attribute.key == "action") {
+                Some(action) => action.value.as_str(),
+                None => return Ok(false),
+            },
+            None => return Ok(false),
+        };
+
+        let contract_address = event_contract_address(event).unwrap_or_else(|| CONTRACT_ADDRESS.to_string());
+        let index = i32::try_from(event_index(event)?)
+            .map_err(|_| Error::invalid_data(format!("Event index of event {} out of range", event.id())))?;
+        let block_height = i64::try_from(event.block_number())
+            .map_err(|_| Error::invalid_data(format!("Block height of event {} out of range", event.id())))?;
+        let block_timestamp = chrono::DateTime::<chrono::Utc>::from(event.timestamp());
+
+        match name {
+            TransferEvent::NAME => {
+                let decoded = TransferEvent::from_attributes(attributes)?;
+                sqlx::query(
+                    "INSERT INTO cw20_transfer_events (tx_hash, contract_address, block_height, block_timestamp, event_index, recipient, amount, memo) VALUES ($1, $2, $3, $4, $5, $6, $7::NUMERIC(78, 0), $8) ON CONFLICT (tx_hash, event_index) DO UPDATE SET block_height = EXCLUDED.block_height, block_timestamp = EXCLUDED.block_timestamp, removed = FALSE",
+                )
+                    .bind(event.tx_hash())
+                    .bind(contract_address.as_str())
+                    .bind(block_height)
+                    .bind(block_timestamp)
+                    .bind(index)
+                    .bind(sql_text(&decoded.recipient)?)
+                    .bind(sql_text(&decoded.amount)?)
+                    .bind(sql_text(&decoded.memo)?)
+                    .execute(&self.pool)
+                    .await
+                    .map_err(|e| Error::database(format!("Failed to store transfer event {}: {}", event.id(), e)))?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn rollback(&self, chain: &str, from_block: u64) -> Result<()> {
+        if chain != CHAIN_ID {
+            return Ok(());
+        }
+        let from_block = i64::try_from(from_block)
+            .map_err(|_| Error::invalid_data(format!("Block height {} out of range", from_block)))?;
+        for statement in ROLLBACK_STATEMENTS {
+            sqlx::query(statement)
+                .bind(from_block)
+                .execute(&self.pool)
+                .await
+                .map_err(|e| Error::database(format!("Failed to roll back Cw20EventHandler from block {}: {}", from_block, e)))?;
+        }
+        Ok(())
+    }
+}
+
+/// Text of an attribute for a scalar column, SQL NULL when absent
+fn sql_text<T: Serialize>(value: &T) -> Result<Option<String>> {
+    Ok(match serde_json::to_value(value)? {
+        serde_json::Value::Null => None,
+        serde_json::Value::String(text) => Some(text),
+        value => Some(value.to_string()),
+    })
+}
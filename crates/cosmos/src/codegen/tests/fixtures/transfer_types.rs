@@ -0,0 +1,45 @@
+//! Generated message and response types for contract: cosmos1cw20example
+
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+use indexer_core::event::EventAttribute;
+use cosmwasm_std::{Addr, Uint128};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TransferEvent {
+    pub recipient: Addr,
+    pub amount: Uint128,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+}
+
+impl TransferEvent {
+    /// Event type, without the `wasm-` prefix CosmWasm adds
+    pub const NAME: &'static str = "transfer";
+
+    /// Decode the event from its attributes
+    pub fn from_attributes(attributes: &[EventAttribute]) -> indexer_core::Result<Self> {
+        Ok(Self {
+            recipient: required_attribute(attributes, "recipient")?,
+            amount: required_attribute(attributes, "amount")?,
+            memo: attribute(attributes, "memo")?,
+        })
+    }
+}
+
+/// Attribute `key` of an event, decoded from JSON or else from the raw string
+fn attribute<T: DeserializeOwned>(attributes: &[EventAttribute], key: &str) -> indexer_core::Result<Option<T>> {
+    let Some(attribute) = attributes.iter().find(|attribute| attribute.key == key) else {
+        return Ok(None);
+    };
+    serde_json::from_str(&attribute.value)
+        .or_else(|_| serde_json::from_value(serde_json::Value::String(attribute.value.clone())))
+        .map(Some)
+        .map_err(|e| indexer_core::Error::invalid_data(format!("Invalid event attribute {}: {}", key, e)))
+}
+
+/// Attribute `key` of an event, which must be present
+fn required_attribute<T: DeserializeOwned>(attributes: &[EventAttribute], key: &str) -> indexer_core::Result<T> {
+    attribute(attributes, key)?
+        .ok_or_else(|| indexer_core::Error::invalid_data(format!("Missing event attribute {}", key)))
+}
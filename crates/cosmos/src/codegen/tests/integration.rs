@@ -1,8 +1,10 @@
 //! Integration tests for cosmos contract code generation
 
-use crate::codegen::{CosmosCodegenConfig, parser::*, CosmosContractCodegen};
+use crate::codegen::{check_contract_code, dump_contract_templates, generate_contract_code, CosmosCodegenConfig, parser::*, CosmosContractCodegen, CosmosTemplateManager};
+use indexer_core::codegen::{Drift, Provenance};
 use indexer_core::Result;
 use std::fs;
+use std::path::Path;
 use tempfile::TempDir;
 use std::collections::HashMap;
 
@@ -19,6 +21,8 @@ async fn test_end_to_end_cw20_generation() -> Result<()> {
         output_dir: output_path.to_string(),
         features: vec!["client".to_string(), "storage".to_string()],
         dry_run: false,
+        templates_dir: None,
+        migration_version: None,
         namespace: None,
     };
 
@@ -34,6 +38,7 @@ async fn test_end_to_end_cw20_generation() -> Result<()> {
                     reference: None,
                     items: None,
                     enum_values: None,
+                    format: None,
                 },
                 description: Some("Recipient address".to_string()),
                 required: true,
@@ -45,12 +50,14 @@ async fn test_end_to_end_cw20_generation() -> Result<()> {
                     reference: None,
                     items: None,
                     enum_values: None,
+                    format: None,
                 },
                 description: Some("Amount to transfer".to_string()),
                 required: true,
             },
         ],
         required: vec!["recipient".to_string(), "amount".to_string()],
+        kind: VariantKind::Struct,
     };
 
     let execute_msg = MessageSchema {
@@ -71,12 +78,14 @@ async fn test_end_to_end_cw20_generation() -> Result<()> {
                     reference: None,
                     items: None,
                     enum_values: None,
+                    format: None,
                 },
                 description: Some("Address to query".to_string()),
                 required: true,
             },
         ],
         required: vec!["address".to_string()],
+        kind: VariantKind::Struct,
     };
 
     let query_msg = MessageSchema {
@@ -93,6 +102,7 @@ async fn test_end_to_end_cw20_generation() -> Result<()> {
         migrate_msg: None,
         events: vec![],
         definitions: HashMap::new(),
+        responses: HashMap::new(),
     };
 
     // Generate code
@@ -140,6 +150,8 @@ async fn test_complex_message_schema_generation() -> Result<()> {
         output_dir: output_path.to_string(),
         features: vec!["client".to_string()],
         dry_run: false,
+        templates_dir: None,
+        migration_version: None,
         namespace: Some("test".to_string()),
     };
 
@@ -155,6 +167,7 @@ async fn test_complex_message_schema_generation() -> Result<()> {
                     reference: None,
                     items: None,
                     enum_values: None,
+                    format: None,
                 },
                 description: Some("Nested data".to_string()),
                 required: true,
@@ -169,14 +182,17 @@ async fn test_complex_message_schema_generation() -> Result<()> {
                         reference: None,
                         items: None,
                         enum_values: None,
+                        format: None,
                     })),
                     enum_values: None,
+                    format: None,
                 },
                 description: Some("Optional array".to_string()),
                 required: false,
             },
         ],
         required: vec!["nested_data".to_string()],
+        kind: VariantKind::Struct,
     };
 
     let execute_msg = MessageSchema {
@@ -193,6 +209,7 @@ async fn test_complex_message_schema_generation() -> Result<()> {
         migrate_msg: None,
         events: vec![],
         definitions: HashMap::new(),
+        responses: HashMap::new(),
     };
 
     let codegen = CosmosContractCodegen::new(config);
@@ -214,6 +231,8 @@ async fn test_dry_run_generation() -> Result<()> {
         output_dir: "/tmp/should-not-exist".to_string(),
         features: vec!["client".to_string()],
         dry_run: true,
+        templates_dir: None,
+        migration_version: None,
         namespace: None,
     };
 
@@ -223,6 +242,7 @@ async fn test_dry_run_generation() -> Result<()> {
         description: Some("Simple message".to_string()),
         properties: vec![],
         required: vec![],
+        kind: VariantKind::Struct,
     };
 
     let instantiate_msg = MessageSchema {
@@ -239,6 +259,7 @@ async fn test_dry_run_generation() -> Result<()> {
         migrate_msg: None,
         events: vec![],
         definitions: HashMap::new(),
+        responses: HashMap::new(),
     };
 
     let codegen = CosmosContractCodegen::new(config);
@@ -263,6 +284,8 @@ async fn test_nested_message_schema_generation() -> Result<()> {
         output_dir: output_path.to_string(),
         features: vec!["client".to_string(), "storage".to_string()],
         dry_run: false,
+        templates_dir: None,
+        migration_version: None,
         namespace: None,
     };
 
@@ -278,6 +301,7 @@ async fn test_nested_message_schema_generation() -> Result<()> {
                     reference: Some("UserInfo".to_string()),
                     items: None,
                     enum_values: None,
+                    format: None,
                 },
                 description: Some("User information".to_string()),
                 required: true,
@@ -289,6 +313,7 @@ async fn test_nested_message_schema_generation() -> Result<()> {
                     reference: Some("Config".to_string()),
                     items: None,
                     enum_values: None,
+                    format: None,
                 },
                 description: Some("Optional configuration".to_string()),
                 required: false,
@@ -303,14 +328,17 @@ async fn test_nested_message_schema_generation() -> Result<()> {
                         reference: None,
                         items: None,
                         enum_values: None,
+                        format: None,
                     })),
                     enum_values: None,
+                    format: None,
                 },
                 description: Some("Array of amounts".to_string()),
                 required: true,
             },
         ],
         required: vec!["user_info".to_string(), "amounts".to_string()],
+        kind: VariantKind::Struct,
     };
 
     let execute_msg = MessageSchema {
@@ -334,6 +362,7 @@ async fn test_nested_message_schema_generation() -> Result<()> {
                     reference: None,
                     items: None,
                     enum_values: None,
+                    format: None,
                 },
                 description: Some("User address".to_string()),
                 required: true,
@@ -348,8 +377,10 @@ async fn test_nested_message_schema_generation() -> Result<()> {
                         reference: None,
                         items: None,
                         enum_values: Some(vec!["read".to_string(), "write".to_string(), "admin".to_string()]),
+                        format: None,
                     })),
                     enum_values: None,
+                    format: None,
                 },
                 description: Some("User permissions".to_string()),
                 required: true,
@@ -357,6 +388,8 @@ async fn test_nested_message_schema_generation() -> Result<()> {
         ],
         is_enum: false,
         variants: vec![],
+        one_of: vec![],
+        alias: None,
     });
 
     let schema = CosmWasmSchema {
@@ -366,6 +399,7 @@ async fn test_nested_message_schema_generation() -> Result<()> {
         migrate_msg: None,
         events: vec![],
         definitions,
+        responses: HashMap::new(),
     };
 
     let codegen = CosmosContractCodegen::new(config);
@@ -393,6 +427,8 @@ async fn test_enum_and_union_schema_generation() -> Result<()> {
         output_dir: output_path.to_string(),
         features: vec!["client".to_string()],
         dry_run: false,
+        templates_dir: None,
+        migration_version: None,
         namespace: None,
     };
 
@@ -408,6 +444,7 @@ async fn test_enum_and_union_schema_generation() -> Result<()> {
                     reference: None,
                     items: None,
                     enum_values: Some(vec!["pending".to_string(), "approved".to_string(), "rejected".to_string()]),
+                    format: None,
                 },
                 description: Some("Operation status".to_string()),
                 required: true,
@@ -419,6 +456,7 @@ async fn test_enum_and_union_schema_generation() -> Result<()> {
                     reference: None,
                     items: None,
                     enum_values: Some(vec!["1".to_string(), "2".to_string(), "3".to_string()]),
+                    format: None,
                 },
                 description: Some("Priority level".to_string()),
                 required: true,
@@ -430,12 +468,14 @@ async fn test_enum_and_union_schema_generation() -> Result<()> {
                     reference: None,
                     items: None,
                     enum_values: None,
+                    format: None,
                 },
                 description: Some("Free-form metadata".to_string()),
                 required: false,
             },
         ],
         required: vec!["status".to_string(), "priority".to_string()],
+        kind: VariantKind::Struct,
     };
 
     let execute_msg = MessageSchema {
@@ -452,6 +492,7 @@ async fn test_enum_and_union_schema_generation() -> Result<()> {
         migrate_msg: None,
         events: vec![],
         definitions: HashMap::new(),
+        responses: HashMap::new(),
     };
 
     let codegen = CosmosContractCodegen::new(config);
@@ -464,3 +505,485 @@ async fn test_enum_and_union_schema_generation() -> Result<()> {
     println!("✅ Enum and union schema generation test passed");
     Ok(())
 }
+
+#[tokio::test]
+async fn test_event_migration_generation() -> Result<()> {
+    let temp_dir = TempDir::new().unwrap();
+    let config = CosmosCodegenConfig {
+        contract_address: "cosmos1cw20example".to_string(),
+        chain_id: "cosmoshub-4".to_string(),
+        output_dir: temp_dir.path().to_str().unwrap().to_string(),
+        features: vec!["migrations".to_string()],
+        dry_run: false,
+        templates_dir: None,
+        migration_version: None,
+        namespace: None,
+    };
+
+    let type_info = |base_type: &str, reference: Option<&str>| TypeInfo {
+        base_type: base_type.to_string(),
+        reference: reference.map(String::from),
+        items: None,
+        enum_values: None,
+        format: None,
+    };
+    let transfer_event = EventSchema {
+        name: "wasm-transfer".to_string(),
+        description: Some("Tokens moved between accounts".to_string()),
+        attributes: vec![
+            PropertySchema {
+                name: "recipient".to_string(),
+                type_info: type_info("string", Some("Addr")),
+                description: None,
+                required: true,
+            },
+            PropertySchema {
+                name: "amount".to_string(),
+                type_info: type_info("string", Some("Uint128")),
+                description: None,
+                required: true,
+            },
+            PropertySchema {
+                name: "memo".to_string(),
+                type_info: type_info("string", None),
+                description: None,
+                required: false,
+            },
+        ],
+    };
+
+    let schema = CosmWasmSchema {
+        instantiate_msg: None,
+        execute_msg: None,
+        query_msg: None,
+        migrate_msg: None,
+        events: vec![transfer_event],
+        definitions: HashMap::new(),
+        responses: HashMap::new(),
+    };
+
+    CosmosContractCodegen::new(config).generate_all(&schema).await?;
+
+    let migrations_dir = temp_dir.path().join("migrations");
+    let mut files: Vec<String> = fs::read_dir(&migrations_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    files.sort();
+    assert_eq!(files.len(), 2, "Expected an up and a down migration: {:?}", files);
+    assert!(files[0].ends_with("_cosmos1cw20example_events.down.sql"));
+    assert!(files[1].ends_with("_cosmos1cw20example_events.up.sql"));
+
+    let up = fs::read_to_string(migrations_dir.join(&files[1])).unwrap();
+    assert!(up.contains("CREATE TABLE IF NOT EXISTS cosmos1cw20example_transfer_events ("));
+    assert!(up.contains("amount NUMERIC(78, 0) NOT NULL CHECK (amount >= 0)"));
+    assert!(up.contains("    memo TEXT,\n"));
+    assert!(up.contains("idx_cosmos1cw20example_transfer_events_recipient ON cosmos1cw20example_transfer_events (recipient);"));
+
+    let down = fs::read_to_string(migrations_dir.join(&files[0])).unwrap();
+    assert!(down.contains("DROP TABLE IF EXISTS cosmos1cw20example_transfer_events;"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_typed_client_generation() -> Result<()> {
+    let temp_dir = TempDir::new().unwrap();
+    let config = CosmosCodegenConfig {
+        contract_address: "cosmos1cw20example".to_string(),
+        chain_id: "cosmoshub-4".to_string(),
+        output_dir: temp_dir.path().to_str().unwrap().to_string(),
+        features: vec!["client".to_string()],
+        dry_run: false,
+        templates_dir: None,
+        migration_version: None,
+        namespace: None,
+    };
+
+    let schema = CosmWasmMsgParser::new().parse_content(r##"{
+        "contract_name": "cw20",
+        "execute": {
+            "title": "ExecuteMsg",
+            "oneOf": [
+                {"type": "string", "enum": ["pause"]},
+                {
+                    "type": "object",
+                    "required": ["send"],
+                    "properties": {
+                        "send": {
+                            "type": "object",
+                            "required": ["contract", "amount", "msg"],
+                            "properties": {
+                                "contract": {"type": "string"},
+                                "amount": {"$ref": "#/definitions/Uint128"},
+                                "msg": {"$ref": "#/definitions/Binary"},
+                                "expires": {"anyOf": [{"$ref": "#/definitions/Expiration"}, {"type": "null"}]}
+                            }
+                        }
+                    }
+                },
+                {
+                    "type": "object",
+                    "required": ["set_owner"],
+                    "properties": {"set_owner": {"$ref": "#/definitions/Addr"}}
+                }
+            ],
+            "definitions": {
+                "Addr": {"type": "string"},
+                "Binary": {"type": "string"},
+                "Uint128": {"type": "string"},
+                "Expiration": {
+                    "oneOf": [
+                        {
+                            "type": "object",
+                            "required": ["at_height"],
+                            "properties": {"at_height": {"type": "integer", "format": "uint64"}}
+                        },
+                        {
+                            "type": "object",
+                            "required": ["never"],
+                            "properties": {"never": {"type": "object"}}
+                        }
+                    ]
+                }
+            }
+        },
+        "query": {
+            "title": "QueryMsg",
+            "oneOf": [
+                {
+                    "type": "object",
+                    "required": ["balance"],
+                    "properties": {
+                        "balance": {
+                            "type": "object",
+                            "required": ["address"],
+                            "properties": {"address": {"type": "string"}}
+                        }
+                    }
+                },
+                {
+                    "type": "object",
+                    "required": ["minter"],
+                    "properties": {"minter": {"type": "object"}}
+                }
+            ]
+        },
+        "migrate": null,
+        "responses": {
+            "balance": {
+                "title": "BalanceResponse",
+                "type": "object",
+                "required": ["balance"],
+                "properties": {"balance": {"$ref": "#/definitions/Uint128"}},
+                "definitions": {"Uint128": {"type": "string"}}
+            }
+        }
+    }"##)?;
+
+    CosmosContractCodegen::new(config).generate_all(&schema).await?;
+
+    let client_dir = temp_dir.path().join("client");
+    let types = fs::read_to_string(client_dir.join("types.rs")).unwrap();
+    assert!(types.contains("use cosmwasm_std::{Addr, Binary, Uint128};"));
+    assert!(types.contains("pub enum ExecuteMsg {"));
+    assert!(types.contains("    #[serde(rename = \"pause\")]\n    Pause,\n"));
+    assert!(types.contains("    #[serde(rename = \"set_owner\")]\n    SetOwner(Addr),\n"));
+    assert!(types.contains("        expires: Option<Expiration>,\n"));
+    assert!(types.contains("    AtHeight(u64),\n"));
+    assert!(types.contains("pub struct BalanceResponse {\n    pub balance: Uint128,\n}"));
+    assert!(!types.contains("MigrateMsg"), "Null messages should not be generated");
+    assert!(!types.contains("pub type Uint128"), "cosmwasm-std types should not be redefined");
+
+    let client = fs::read_to_string(client_dir.join("mod.rs")).unwrap();
+    assert!(client.contains("Client<C: CosmWasmClient> {\n    client: C,\n    contract_address: String,\n}"));
+    assert!(client.contains("pub async fn execute(&self, msg: &ExecuteMsg, funds: &[Coin]) -> Result<String> {"));
+
+    let execute = fs::read_to_string(client_dir.join("execute.rs")).unwrap();
+    assert!(execute.contains(
+        "pub async fn send(&self, amount: Uint128, contract: String, expires: Option<Expiration>, msg: Binary) -> Result<String> {"
+    ));
+    assert!(execute.contains("self.execute(&ExecuteMsg::Pause, &[]).await"));
+
+    let query = fs::read_to_string(client_dir.join("query.rs")).unwrap();
+    assert!(query.contains("pub async fn balance(&self, address: String) -> Result<BalanceResponse> {"));
+    assert!(query.contains("pub async fn minter<R: DeserializeOwned>(&self) -> Result<R> {"));
+    assert!(query.contains("self.query(&QueryMsg::Minter {}).await"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_event_handler_generation() -> Result<()> {
+    let temp_dir = TempDir::new().unwrap();
+    let config = CosmosCodegenConfig {
+        contract_address: "cosmos1cw20example".to_string(),
+        chain_id: "cosmoshub-4".to_string(),
+        output_dir: temp_dir.path().to_str().unwrap().to_string(),
+        features: vec!["client".to_string(), "handlers".to_string()],
+        dry_run: false,
+        templates_dir: None,
+        migration_version: None,
+        namespace: None,
+    };
+
+    let attribute = |name: &str, reference: Option<&str>, required: bool| PropertySchema {
+        name: name.to_string(),
+        type_info: TypeInfo {
+            base_type: "string".to_string(),
+            reference: reference.map(String::from),
+            items: None,
+            enum_values: None,
+            format: None,
+        },
+        description: None,
+        required,
+    };
+    let schema = CosmWasmSchema {
+        instantiate_msg: None,
+        execute_msg: None,
+        query_msg: None,
+        migrate_msg: None,
+        events: vec![EventSchema {
+            name: "wasm-transfer".to_string(),
+            description: None,
+            attributes: vec![
+                attribute("recipient", Some("Addr"), true),
+                attribute("amount", Some("Uint128"), true),
+                attribute("memo", None, false),
+            ],
+        }],
+        definitions: HashMap::new(),
+        responses: HashMap::new(),
+    };
+
+    CosmosContractCodegen::new(config).generate_all(&schema).await?;
+
+    // Events decode into typed structs next to the message types
+    let types = fs::read_to_string(temp_dir.path().join("client").join("types.rs")).unwrap();
+    assert!(types.contains("pub struct TransferEvent {"));
+    assert!(types.contains("pub amount: Uint128,"));
+    assert!(types.contains("pub const NAME: &'static str = \"transfer\";"));
+    assert!(types.contains("amount: required_attribute(attributes, \"amount\")?,"));
+    assert!(types.contains("memo: attribute(attributes, \"memo\")?,"));
+
+    let handler = fs::read_to_string(temp_dir.path().join("handlers").join("mod.rs")).unwrap();
+    assert!(handler.contains("pub const CHAIN_ID: &str = \"cosmoshub-4\";"));
+    assert!(handler.contains("pub fn register(registry: &mut EventHandlerRegistry, pool: PgPool)"));
+    assert!(handler.contains("registry.register(CHAIN_ID, CONTRACT_ADDRESS, Arc::new(Self::new(pool)));"));
+    assert!(handler.contains("TransferEvent::NAME => {"));
+    assert!(handler.contains("let decoded = TransferEvent::from_attributes(attributes)?;"));
+    assert!(handler.contains("INSERT INTO cosmos1cw20example_transfer_events (tx_hash, contract_address, block_height, block_timestamp, event_index, recipient, amount, memo) VALUES ($1, $2, $3, $4, $5, $6, $7::NUMERIC(78, 0), $8) ON CONFLICT (tx_hash, event_index) DO UPDATE SET block_height = EXCLUDED.block_height, block_timestamp = EXCLUDED.block_timestamp, removed = FALSE"));
+    assert!(handler.contains(".bind(sql_text(&decoded.amount)?)"));
+    assert!(handler.contains("fn sql_text<T: Serialize>"));
+    assert!(!handler.contains("fn sql_json"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_typescript_generation() -> Result<()> {
+    let temp_dir = TempDir::new().unwrap();
+    let config = CosmosCodegenConfig {
+        contract_address: "cosmos1cw20example".to_string(),
+        chain_id: "cosmoshub-4".to_string(),
+        output_dir: temp_dir.path().to_str().unwrap().to_string(),
+        features: vec!["typescript".to_string()],
+        dry_run: false,
+        templates_dir: None,
+        migration_version: None,
+        namespace: None,
+    };
+
+    let attribute = |name: &str, base_type: &str, reference: Option<&str>, required: bool| PropertySchema {
+        name: name.to_string(),
+        type_info: TypeInfo {
+            base_type: base_type.to_string(),
+            reference: reference.map(String::from),
+            items: None,
+            enum_values: None,
+            format: None,
+        },
+        description: None,
+        required,
+    };
+    let schema = CosmWasmSchema {
+        instantiate_msg: None,
+        execute_msg: None,
+        query_msg: None,
+        migrate_msg: None,
+        events: vec![EventSchema {
+            name: "wasm-transfer".to_string(),
+            description: Some("Tokens moved between accounts".to_string()),
+            attributes: vec![
+                attribute("recipient", "string", Some("Addr"), true),
+                attribute("amount", "string", Some("Uint128"), true),
+                attribute("batch-size", "integer", None, false),
+            ],
+        }],
+        definitions: HashMap::new(),
+        responses: HashMap::new(),
+    };
+
+    CosmosContractCodegen::new(config).generate_all(&schema).await?;
+    let typescript_dir = temp_dir.path().join("typescript");
+
+    let events = fs::read_to_string(typescript_dir.join("events.ts")).unwrap();
+    assert!(events.contains("export const CHAIN_ID = 'cosmoshub-4';"));
+    assert!(events.contains("/** Tokens moved between accounts */\nexport interface TransferEvent {\n  recipient: string;\n  amount: string;\n  'batch-size'?: number;\n}"));
+    assert!(events.contains("export interface Cosmos1Cw20ExampleEvents {\n  transfer: TransferEvent;\n}"));
+    assert!(events.contains("  transfer: 'wasm-transfer',"));
+
+    // Only Almanac's own endpoints, the api feature generates no routes
+    let client = fs::read_to_string(typescript_dir.join("client.ts")).unwrap();
+    assert!(client.contains("import { CHAIN_ID, CONTRACT_ADDRESS } from './events';"));
+    assert!(client.contains("export function getIndexedEvents<A = Record<string, unknown>>("));
+
+    let fragments = fs::read_to_string(typescript_dir.join("fragments.ts")).unwrap();
+    assert!(fragments.contains("fragment Cosmos1Cw20ExampleIndexedEventFields on GraphQLEvent {"));
+    assert!(fragments.contains("query Cosmos1Cw20ExampleIndexedEvents($filter: EventFilterInput, $first: Int, $after: String) {"));
+    assert!(fragments.contains("eventsConnection(filter: $filter, first: $first, after: $after) {"));
+
+    assert!(typescript_dir.join("index.ts").exists());
+
+    Ok(())
+}
+
+/// Execute message schema with a single transfer variant
+const EXECUTE_MSG_SCHEMA: &str = r#"{
+    "title": "ExecuteMsg",
+    "type": "object",
+    "oneOf": [{
+        "type": "object",
+        "required": ["transfer"],
+        "properties": {
+            "transfer": {
+                "type": "object",
+                "required": ["recipient", "amount"],
+                "properties": {
+                    "recipient": {"type": "string"},
+                    "amount": {"type": "string"}
+                }
+            }
+        }
+    }]
+}"#;
+
+#[tokio::test]
+async fn test_provenance_and_drift_check() -> Result<()> {
+    let temp_dir = TempDir::new().unwrap();
+    let msg_path = temp_dir.path().join("execute_msg.json");
+    fs::write(&msg_path, EXECUTE_MSG_SCHEMA).unwrap();
+    let msg_file = msg_path.to_str().unwrap();
+    let output_dir = temp_dir.path().join("generated");
+
+    let config = CosmosCodegenConfig {
+        contract_address: "cosmos1cw20example".to_string(),
+        chain_id: "cosmoshub-4".to_string(),
+        output_dir: output_dir.to_str().unwrap().to_string(),
+        features: vec!["client".to_string(), "storage".to_string()],
+        dry_run: false,
+        templates_dir: None,
+        migration_version: None,
+        namespace: Some("token".to_string()),
+    };
+    generate_contract_code(msg_file, config.clone()).await?;
+
+    let provenance = Provenance::new(&[("execute_msg.json", EXECUTE_MSG_SCHEMA.as_bytes())]);
+    let client_path = output_dir.join("client").join("mod.rs");
+    let client = fs::read_to_string(&client_path).unwrap();
+    assert!(client.starts_with(&provenance.header(Path::new("mod.rs")).unwrap()));
+    let storage = fs::read_to_string(output_dir.join("storage").join("mod.rs")).unwrap();
+    assert!(storage.starts_with(&provenance.header(Path::new("mod.rs")).unwrap()));
+
+    assert!(check_contract_code(msg_file, config.clone()).await?.is_empty());
+
+    fs::write(&client_path, format!("{}// edited by hand\n", client)).unwrap();
+    fs::remove_dir_all(output_dir.join("storage")).unwrap();
+    let drift = check_contract_code(msg_file, config.clone()).await?;
+    assert!(drift.contains(&Drift::Changed(client_path)));
+    assert!(drift.contains(&Drift::Missing(output_dir.join("storage").join("mod.rs"))));
+    assert!(!output_dir.join("storage").exists());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_custom_templates() -> Result<()> {
+    let temp_dir = TempDir::new().unwrap();
+    let templates_dir = temp_dir.path().join("templates");
+    fs::create_dir_all(templates_dir.join("client")).unwrap();
+    // Overrides the generated client module, keeping the built-in code
+    fs::write(templates_dir.join("client").join("mod.rs.hbs"), "//! Client for {{contract_name}}\n{{generated}}").unwrap();
+    // Adds a file rendered from the schema
+    fs::write(
+        templates_dir.join("messages.txt.hbs"),
+        "{{#each schema.execute_msg.variants}}{{pascal_case name}}:{{#each properties}} {{snake_case name}} {{rust_type this}};{{/each}}\n{{/each}}",
+    ).unwrap();
+
+    let msg_path = temp_dir.path().join("execute_msg.json");
+    fs::write(&msg_path, EXECUTE_MSG_SCHEMA).unwrap();
+    let output_dir = temp_dir.path().join("generated");
+    let config = CosmosCodegenConfig {
+        contract_address: "cosmos1cw20example".to_string(),
+        chain_id: "cosmoshub-4".to_string(),
+        output_dir: output_dir.to_str().unwrap().to_string(),
+        features: vec!["client".to_string()],
+        dry_run: false,
+        templates_dir: Some(templates_dir.to_str().unwrap().to_string()),
+        migration_version: None,
+        namespace: None,
+    };
+    generate_contract_code(msg_path.to_str().unwrap(), config.clone()).await?;
+
+    let client = fs::read_to_string(output_dir.join("client").join("mod.rs")).unwrap();
+    assert_eq!(client.lines().nth(1), Some("//! Client for Cosmos1Cw20Example"));
+    assert!(client.contains("pub struct Cosmos1Cw20ExampleClient"));
+    assert!(output_dir.join("client").join("execute.rs").exists());
+
+    let messages = fs::read_to_string(output_dir.join("messages.txt")).unwrap();
+    assert_eq!(messages, "Transfer: amount String; recipient String;\n");
+
+    assert!(check_contract_code(msg_path.to_str().unwrap(), config).await?.is_empty());
+
+    // Templates using helpers that don't exist are rejected when loaded
+    fs::write(templates_dir.join("broken.rs.hbs"), "{{#each schema.events}}{{to_upper name}}{{/each}}").unwrap();
+    let error = CosmosTemplateManager::new()?.with_templates_dir(&templates_dir).err().unwrap();
+    assert!(error.to_string().contains("unknown helper to_upper"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reference_templates() -> Result<()> {
+    let temp_dir = TempDir::new().unwrap();
+    let msg_path = temp_dir.path().join("execute_msg.json");
+    fs::write(&msg_path, EXECUTE_MSG_SCHEMA).unwrap();
+    let msg_file = msg_path.to_str().unwrap();
+    let templates_dir = temp_dir.path().join("templates");
+    let output_dir = temp_dir.path().join("generated");
+
+    let config = CosmosCodegenConfig {
+        contract_address: "cosmos1cw20example".to_string(),
+        chain_id: "cosmoshub-4".to_string(),
+        output_dir: output_dir.to_str().unwrap().to_string(),
+        features: vec!["client".to_string(), "storage".to_string(), "api".to_string()],
+        dry_run: false,
+        templates_dir: None,
+        migration_version: None,
+        namespace: None,
+    };
+    let templates = dump_contract_templates(msg_file, config.clone(), templates_dir.to_str().unwrap()).await?;
+    assert!(templates.contains(&templates_dir.join("client").join("mod.rs.hbs")));
+    assert!(!output_dir.exists());
+
+    // Generating with the unedited reference templates gives the built-in output
+    let templated = CosmosCodegenConfig {
+        templates_dir: Some(templates_dir.to_str().unwrap().to_string()),
+        ..config.clone()
+    };
+    generate_contract_code(msg_file, templated).await?;
+    assert!(check_contract_code(msg_file, config).await?.is_empty());
+
+    Ok(())
+}
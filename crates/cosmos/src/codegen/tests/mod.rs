@@ -2,6 +2,9 @@
 
 use super::*;
 
+#[cfg(feature = "wasm")]
+mod client;
+mod handlers;
 mod integration;
 
 #[cfg(test)]
@@ -14,11 +17,12 @@ mod cosmos_tests {
         let config = CosmosCodegenConfig::default();
         
         assert_eq!(config.output_dir, "./generated");
-        assert_eq!(config.features.len(), 4);
+        assert_eq!(config.features.len(), 5);
         assert!(config.features.contains(&"client".to_string()));
         assert!(config.features.contains(&"storage".to_string()));
         assert!(config.features.contains(&"api".to_string()));
         assert!(config.features.contains(&"migrations".to_string()));
+        assert!(config.features.contains(&"handlers".to_string()));
         assert!(!config.dry_run);
     }
 
@@ -31,6 +35,8 @@ mod cosmos_tests {
             namespace: Some("my_namespace".to_string()),
             features: vec!["client".to_string()],
             dry_run: true,
+            templates_dir: None,
+            migration_version: None,
         };
         
         assert_eq!(config.contract_address, "cosmos1contract123");
@@ -138,13 +144,97 @@ mod cosmos_tests {
     }
 
     #[test]
-    fn test_template_manager_creation() {
+    fn test_parse_variant_kinds_and_responses() {
+        let parser = CosmWasmMsgParser::new();
+        let json_content = r##"
+        {
+            "execute": {
+                "title": "ExecuteMsg",
+                "oneOf": [
+                    {"type": "string", "enum": ["pause", "unpause"]},
+                    {
+                        "type": "object",
+                        "required": ["set_owner"],
+                        "properties": {"set_owner": {"$ref": "#/definitions/Addr"}}
+                    },
+                    {
+                        "type": "object",
+                        "required": ["update"],
+                        "properties": {
+                            "update": {
+                                "type": "object",
+                                "properties": {
+                                    "limit": {"type": ["integer", "null"], "format": "uint32"},
+                                    "expiry": {"anyOf": [{"$ref": "#/definitions/Expiration"}, {"type": "null"}]}
+                                }
+                            }
+                        }
+                    }
+                ],
+                "definitions": {"Addr": {"type": "string"}}
+            },
+            "migrate": null,
+            "responses": {
+                "config": {
+                    "title": "ConfigResponse",
+                    "type": "object",
+                    "required": ["owner"],
+                    "properties": {"owner": {"$ref": "#/definitions/Addr"}}
+                },
+                "admins": {"title": "Array_of_String", "type": "array", "items": {"type": "string"}}
+            }
+        }
+        "##;
+
+        let schema = parser.parse_content(json_content).unwrap();
+        assert!(schema.migrate_msg.is_none());
+
+        let variants = &schema.execute_msg.unwrap().variants;
+        let names: Vec<&str> = variants.iter().map(|variant| variant.name.as_str()).collect();
+        assert_eq!(names, vec!["pause", "unpause", "set_owner", "update"]);
+        assert!(matches!(variants[0].kind, parser::VariantKind::Unit));
+        assert!(matches!(
+            &variants[2].kind,
+            parser::VariantKind::Newtype(type_info) if type_info.reference.as_deref() == Some("Addr")
+        ));
+        assert!(matches!(variants[3].kind, parser::VariantKind::Struct));
+
+        let limit = variants[3].properties.iter().find(|p| p.name == "limit").unwrap();
+        assert_eq!(limit.type_info.base_type, "integer");
+        assert_eq!(limit.type_info.format.as_deref(), Some("uint32"));
+        assert!(!limit.required);
+        let expiry = variants[3].properties.iter().find(|p| p.name == "expiry").unwrap();
+        assert_eq!(expiry.type_info.reference.as_deref(), Some("Expiration"));
+
+        assert!(schema.definitions["Addr"].alias.is_some());
+        assert!(schema.definitions.contains_key("ConfigResponse"));
+        assert_eq!(schema.responses["config"].reference.as_deref(), Some("ConfigResponse"));
+        assert_eq!(schema.responses["admins"].base_type, "array");
+    }
+
+    #[tokio::test]
+    async fn test_template_manager_creation() {
         let result = super::templates::CosmosTemplateManager::new();
         assert!(result.is_ok());
-        
-        let manager = result.unwrap();
+
+        // Reference templates of the generated files are loaded like user templates
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let msg_path = temp_dir.path().join("msg.json");
+        std::fs::write(&msg_path, r#"{"title": "TestMsg", "type": "object"}"#).unwrap();
+        let templates_dir = temp_dir.path().join("templates");
+        let config = CosmosCodegenConfig {
+            contract_address: "cosmos1test123".to_string(),
+            chain_id: "test-1".to_string(),
+            output_dir: temp_dir.path().join("generated").to_str().unwrap().to_string(),
+            features: vec!["client".to_string()],
+            ..CosmosCodegenConfig::default()
+        };
+        dump_contract_templates(msg_path.to_str().unwrap(), config, templates_dir.to_str().unwrap()).await.unwrap();
+
+        let manager = result.unwrap().with_templates_dir(&templates_dir).unwrap();
         let templates = manager.available_templates();
         assert!(!templates.is_empty());
+        assert!(templates.contains(&"client/mod.rs".to_string()));
     }
 
     #[tokio::test]
@@ -156,6 +246,8 @@ mod cosmos_tests {
             namespace: None,
             features: vec!["client".to_string()],
             dry_run: true, // This should prevent file writes
+            templates_dir: None,
+            migration_version: None,
         };
 
         let parser = CosmWasmMsgParser::new();
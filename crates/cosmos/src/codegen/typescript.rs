@@ -0,0 +1,299 @@
+//! TypeScript generation for frontends of a contract
+//!
+//! Generates typed event interfaces, a client for Almanac's event endpoint
+//! and GraphQL fragments for Almanac's event type. The `api` feature generates
+//! no routes for CosmWasm contracts yet, so there are no route functions.
+//! Attributes are typed as their schema types decode from JSON: numbers and
+//! booleans as such, cosmwasm-std integers, decimals and addresses as strings.
+
+use std::collections::HashMap;
+
+use indexer_core::codegen::{typescript_indexed_event_documents, typescript_key, TYPESCRIPT_CLIENT_RUNTIME};
+
+use super::client::{event_name, event_type_name};
+use super::parser::{CosmWasmSchema, EventSchema, PropertySchema, TypeDefinition, TypeInfo};
+
+/// Generated TypeScript for a contract
+pub struct TypeScriptGenerator {
+    /// Prefix of the generated type names
+    contract_name: String,
+    contract_address: String,
+    chain_id: String,
+}
+
+impl TypeScriptGenerator {
+    /// Create a generator for the types of `contract_name`
+    ///
+    /// Names starting with a digit get a `Contract` prefix to stay valid
+    /// identifiers.
+    pub fn new(contract_name: &str, contract_address: impl Into<String>, chain_id: impl Into<String>) -> Self {
+        let contract_name = if contract_name.starts_with(|c: char| c.is_ascii_digit()) {
+            format!("Contract{}", contract_name)
+        } else {
+            contract_name.to_string()
+        };
+        Self {
+            contract_name,
+            contract_address: contract_address.into(),
+            chain_id: chain_id.into(),
+        }
+    }
+
+    /// `typescript/events.ts`: an interface per event and the event names
+    pub fn events(&self, schema: &CosmWasmSchema) -> String {
+        let name = &self.contract_name;
+
+        let mut code = format!(
+            r#"/**
+ * Generated event types for contract: {address}
+ * Chain: {chain}
+ *
+ * Fields are keyed by attribute name and hold the attribute values Almanac
+ * decodes with the contract schema.
+ */
+
+/** Chain the contract is deployed on */
+export const CHAIN_ID = '{chain}';
+
+/** Address of the contract */
+export const CONTRACT_ADDRESS = '{address}';
+"#,
+            address = self.contract_address,
+            chain = self.chain_id,
+        );
+
+        for event in &schema.events {
+            code.push('\n');
+            code.push_str(&event_interface(schema, event));
+        }
+
+        let mut entries = String::new();
+        let mut types = String::new();
+        for event in &schema.events {
+            let key = typescript_key(event_name(&event.name));
+            entries.push_str(&format!("  {}: {};\n", key, event_type_name(&event.name)));
+            types.push_str(&format!("  {}: '{}',\n", key, event.name));
+        }
+        code.push_str(&format!(
+            r#"
+/** Decoded events of the contract by name, without the `wasm-` prefix */
+export interface {name}Events {{
+{entries}}}
+
+/** Name of an event of the contract */
+export type {name}EventName = keyof {name}Events;
+
+/** Event type of each event, as indexed by Almanac */
+export const EVENT_TYPES = {{
+{types}}} as const;
+"#,
+            name = name,
+            entries = entries,
+            types = types,
+        ));
+        code
+    }
+
+    /// `typescript/client.ts`: Almanac's event endpoint for the contract
+    pub fn client(&self) -> String {
+        format!(
+            r#"/**
+ * Generated API client for contract: {address}
+ * Chain: {chain}
+ *
+ * Functions for the events Almanac indexes for the contract.
+ */
+
+import {{ CHAIN_ID, CONTRACT_ADDRESS }} from './events';
+
+{runtime}"#,
+            address = self.contract_address,
+            chain = self.chain_id,
+            runtime = TYPESCRIPT_CLIENT_RUNTIME,
+        )
+    }
+
+    /// `typescript/fragments.ts`: GraphQL fragments for Almanac's event type
+    pub fn fragments(&self) -> String {
+        format!(
+            r#"/**
+ * Generated GraphQL fragments for contract: {address}
+ * Chain: {chain}
+ */
+
+{documents}"#,
+            address = self.contract_address,
+            chain = self.chain_id,
+            documents = typescript_indexed_event_documents(&self.contract_name),
+        )
+    }
+
+    /// `typescript/index.ts`: re-exports of the other files
+    pub fn index(&self) -> String {
+        format!(
+            "/** Generated TypeScript for contract: {} */\n\nexport * from './events';\nexport * from './client';\nexport * from './fragments';\n",
+            self.contract_address
+        )
+    }
+}
+
+/// Interface for an event, documented with its description
+fn event_interface(schema: &CosmWasmSchema, event: &EventSchema) -> String {
+    let docs = match &event.description {
+        Some(description) => jsdoc(description, ""),
+        None => format!("/** `{}` event */\n", event.name),
+    };
+    let fields: String = event.attributes.iter().map(|attribute| field(&schema.definitions, attribute)).collect();
+    let body = if fields.is_empty() { "{}".to_string() } else { format!("{{\n{}}}", fields) };
+    format!("{}export interface {} {}\n", docs, event_type_name(&event.name), body)
+}
+
+/// Interface field for an attribute, optional unless required
+fn field(definitions: &HashMap<String, TypeDefinition>, property: &PropertySchema) -> String {
+    let docs = property.description.as_deref().map(|description| jsdoc(description, "  ")).unwrap_or_default();
+    let optional = if property.required { "" } else { "?" };
+    format!(
+        "{}  {}{}: {};\n",
+        docs,
+        typescript_key(&property.name),
+        optional,
+        ts_type(definitions, &property.type_info, &mut Vec::new())
+    )
+}
+
+/// TypeScript type of a schema type, with definitions inlined
+///
+/// `resolving` holds the definitions being inlined, a recursive reference
+/// is typed `unknown`.
+pub(crate) fn ts_type(definitions: &HashMap<String, TypeDefinition>, type_info: &TypeInfo, resolving: &mut Vec<String>) -> String {
+    if let Some(reference) = &type_info.reference {
+        return reference_type(definitions, reference, resolving);
+    }
+
+    match type_info.base_type.as_str() {
+        "string" => match &type_info.enum_values {
+            Some(values) if !values.is_empty() => string_union(values),
+            _ => "string".to_string(),
+        },
+        "boolean" => "boolean".to_string(),
+        "number" | "integer" => "number".to_string(),
+        "array" => match &type_info.items {
+            Some(items) => array_type(&ts_type(definitions, items, resolving)),
+            None => "unknown[]".to_string(),
+        },
+        "null" => "null".to_string(),
+        _ => "unknown".to_string(),
+    }
+}
+
+/// TypeScript type of a referenced cosmwasm-std type or schema definition
+fn reference_type(definitions: &HashMap<String, TypeDefinition>, reference: &str, resolving: &mut Vec<String>) -> String {
+    match reference {
+        "Addr" | "Binary" | "Decimal" | "Decimal256" | "Timestamp" | "Uint64" | "Uint128" | "Uint256" | "Uint512" => {
+            return "string".to_string();
+        }
+        "Coin" => return "{ denom: string; amount: string }".to_string(),
+        "Empty" => return "Record<string, never>".to_string(),
+        _ => {}
+    }
+    let Some(definition) = definitions.get(reference) else {
+        return "unknown".to_string();
+    };
+    if resolving.iter().any(|name| name == reference) {
+        return "unknown".to_string();
+    }
+
+    resolving.push(reference.to_string());
+    let ts_type = if let Some(alias) = &definition.alias {
+        ts_type(definitions, alias, resolving)
+    } else if !definition.one_of.is_empty() {
+        "unknown".to_string()
+    } else if definition.is_enum {
+        string_union(&definition.variants)
+    } else if definition.properties.is_empty() {
+        "Record<string, never>".to_string()
+    } else {
+        let fields: Vec<String> = definition.properties.iter()
+            .map(|property| {
+                let optional = if property.required { "" } else { "?" };
+                format!("{}{}: {}", typescript_key(&property.name), optional, ts_type(definitions, &property.type_info, resolving))
+            })
+            .collect();
+        format!("{{ {} }}", fields.join("; "))
+    };
+    resolving.pop();
+    ts_type
+}
+
+/// Union of string literals
+fn string_union(values: &[String]) -> String {
+    values.iter()
+        .map(|value| format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'")))
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+/// Array of `element`, parenthesized when it is a union
+fn array_type(element: &str) -> String {
+    if element.contains(" | ") {
+        format!("({})[]", element)
+    } else {
+        format!("{}[]", element)
+    }
+}
+
+/// JSDoc comment for a schema description
+fn jsdoc(description: &str, indent: &str) -> String {
+    let lines: Vec<&str> = description.lines().collect();
+    if let [line] = lines.as_slice() {
+        return format!("{}/** {} */\n", indent, line);
+    }
+    let mut comment = format!("{}/**\n", indent);
+    for line in lines {
+        if line.is_empty() {
+            comment.push_str(&format!("{} *\n", indent));
+        } else {
+            comment.push_str(&format!("{} * {}\n", indent, line));
+        }
+    }
+    comment.push_str(&format!("{} */\n", indent));
+    comment
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn type_info(base_type: &str, reference: Option<&str>, items: Option<TypeInfo>) -> TypeInfo {
+        TypeInfo {
+            base_type: base_type.to_string(),
+            reference: reference.map(String::from),
+            items: items.map(Box::new),
+            enum_values: None,
+            format: None,
+        }
+    }
+
+    #[test]
+    fn test_ts_type() {
+        let definitions = HashMap::from([(
+            "Status".to_string(),
+            TypeDefinition {
+                name: "Status".to_string(),
+                description: None,
+                properties: Vec::new(),
+                is_enum: true,
+                variants: vec!["open".to_string(), "closed".to_string()],
+                one_of: Vec::new(),
+                alias: None,
+            },
+        )]);
+        let ts = |type_info: &TypeInfo| ts_type(&definitions, type_info, &mut Vec::new());
+
+        assert_eq!(ts(&type_info("string", Some("Uint128"), None)), "string");
+        assert_eq!(ts(&type_info("integer", None, None)), "number");
+        assert_eq!(ts(&type_info("object", Some("Coin"), None)), "{ denom: string; amount: string }");
+        assert_eq!(ts(&type_info("array", None, Some(type_info("string", Some("Status"), None)))), "('open' | 'closed')[]");
+        assert_eq!(ts(&type_info("object", Some("Unknown"), None)), "unknown");
+    }
+}
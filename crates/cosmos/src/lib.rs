@@ -13,14 +13,21 @@ use indexer_core::event::{Event, UnifiedEvent};
 use indexer_core::service::{EventService, EventSubscription};
 use indexer_core::types::{ChainId, EventFilter};
 
+// Compiled fixtures of generated code refer to this crate by name
+#[cfg(test)]
+extern crate self as indexer_cosmos;
+
 pub mod contracts;
 pub mod codegen;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 /// Cosmos chain configuration
 #[derive(Debug, Clone)]
 pub struct CosmosChainConfig {
     pub chain_id: String,
     pub name: String,
+    /// gRPC endpoint URI, such as `https://grpc.noble.xyz:443`
     pub grpc_url: String,
     pub rpc_url: Option<String>,
     pub prefix: String,
@@ -100,6 +107,20 @@ impl CosmosChainConfig {
     }
 }
 
+/// Split a gRPC endpoint URI such as `https://grpc.noble.xyz:443` into the
+/// URI without its port and the port, defaulting to the scheme's port
+fn split_grpc_url(url: &str) -> (&str, &str) {
+    let url = url.trim_end_matches('/');
+    let authority = url.find("://").map_or(0, |scheme_end| scheme_end + 3);
+    match url[authority..].rsplit_once(':') {
+        Some((_, port)) if !port.is_empty() && port.chars().all(|c| c.is_ascii_digit()) => {
+            (&url[..url.len() - port.len() - 1], port)
+        }
+        _ if url.starts_with("http://") => (url, "80"),
+        _ => (url, "443"),
+    }
+}
+
 /// Cosmos client using valence-domain-clients Cosmos integration
 /// This uses NobleClient as an example, but can be expanded to support multiple chains
 pub struct CosmosClientWrapper {
@@ -138,9 +159,8 @@ impl CosmosClientWrapper {
     pub async fn new_with_config(config: CosmosChainConfig, mnemonic: String) -> AnyhowResult<Self> {
         // Create valence Noble client as an example
         // TODO: This should be expanded to support different chain types based on config
-        // Note: NobleClient::new takes (rpc_url, rpc_port, mnemonic, chain_id, chain_denom)
-        // We'll split the grpc_url for now - in production this should be proper URL parsing
-        let (rpc_url, rpc_port) = config.grpc_url.split_once(':').unwrap_or((&config.grpc_url, "443"));
+        // NobleClient::new takes the URL and port separately
+        let (rpc_url, rpc_port) = split_grpc_url(&config.grpc_url);
         let valence_client = NobleClient::new(rpc_url, rpc_port, &mnemonic, &config.chain_id, &config.denom).await
             .map_err(|e| anyhow::anyhow!("Failed to create Noble client: {}", e))?;
         
@@ -449,5 +469,11 @@ mod tests {
         // Test URL without port
         let config2 = CosmosChainConfig::noble_mainnet("grpc://test.noble.com".to_string());
         assert_eq!(config2.grpc_url, "grpc://test.noble.com");
+
+        // The valence client takes the URI and port separately
+        assert_eq!(split_grpc_url("grpc://test.noble.com:9090"), ("grpc://test.noble.com", "9090"));
+        assert_eq!(split_grpc_url("https://test.noble.com/"), ("https://test.noble.com", "443"));
+        assert_eq!(split_grpc_url("http://localhost"), ("http://localhost", "80"));
+        assert_eq!(split_grpc_url("localhost:9090"), ("localhost", "9090"));
     }
 }
\ No newline at end of file
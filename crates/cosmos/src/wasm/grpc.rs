@@ -0,0 +1,90 @@
+//! [`CosmWasmClient`] for [`CosmosClientWrapper`] over the chain's gRPC
+//! endpoint
+//!
+//! Queries and executions go through the wrapper's valence client: its gRPC
+//! channel, and its signing client for the account of the mnemonic the
+//! wrapper was created with, which sizes the fee from a simulation and signs.
+
+use async_trait::async_trait;
+use cosmrs::cosmwasm::MsgExecuteContract;
+use cosmrs::proto::cosmos::tx::v1beta1::{service_client::ServiceClient, BroadcastMode, BroadcastTxRequest};
+use cosmrs::proto::cosmwasm::wasm::v1::{query_client::QueryClient as WasmQueryClient, QuerySmartContractStateRequest};
+use cosmrs::tx::Msg;
+use cosmrs::AccountId;
+use cosmwasm_std::Coin;
+use valence_domain_clients::cosmos::grpc_client::GrpcSigningClient;
+
+use indexer_core::{Error, Result};
+
+use super::CosmWasmClient;
+use crate::CosmosClientWrapper;
+
+#[async_trait]
+impl CosmWasmClient for CosmosClientWrapper {
+    async fn query_wasm_smart(&self, contract_address: &str, msg: Vec<u8>) -> Result<Vec<u8>> {
+        let channel = self.valence_client.get_grpc_channel().await
+            .map_err(|e| Error::connection(format!("Failed to connect to {}: {}", self.config.grpc_url, e)))?;
+
+        let response = WasmQueryClient::new(channel)
+            .smart_contract_state(QuerySmartContractStateRequest {
+                address: contract_address.to_string(),
+                query_data: msg,
+            })
+            .await
+            .map_err(|e| self.chain_error(format!("Query of {} failed: {}", contract_address, e.message())))?;
+
+        Ok(response.into_inner().data)
+    }
+
+    async fn execute_wasm(&self, contract_address: &str, msg: Vec<u8>, funds: &[Coin]) -> Result<String> {
+        let signing_client = self.valence_client.get_signing_client().await
+            .map_err(|e| Error::authentication(format!("Failed to load the signing account: {}", e)))?;
+        let contract = contract_address
+            .parse::<AccountId>()
+            .map_err(|e| Error::invalid_data(format!("Invalid contract address {}: {}", contract_address, e)))?;
+        let funds = funds
+            .iter()
+            .map(|coin| {
+                Ok(cosmrs::Coin {
+                    denom: coin.denom.parse().map_err(|e| Error::invalid_data(format!("Invalid denom {}: {}", coin.denom, e)))?,
+                    amount: coin.amount.u128(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let execute = MsgExecuteContract { sender: signing_client.address.clone(), contract, msg, funds }
+            .to_any()
+            .map_err(|e| Error::invalid_data(format!("Failed to encode execute message: {}", e)))?;
+
+        let simulation = self.valence_client.simulate_tx(execute.clone()).await
+            .map_err(|e| self.chain_error(format!("Simulation failed: {}", e)))?;
+        let fee = self.valence_client.get_tx_fee(simulation)
+            .map_err(|e| self.chain_error(format!("Failed to size the fee: {}", e)))?;
+        let tx_bytes = signing_client.create_tx(execute, fee, None).await
+            .map_err(|e| Error::invalid_data(format!("Failed to sign transaction: {}", e)))?
+            .to_bytes()
+            .map_err(|e| Error::invalid_data(format!("Failed to encode transaction: {}", e)))?;
+
+        let channel = self.valence_client.get_grpc_channel().await
+            .map_err(|e| Error::connection(format!("Failed to connect to {}: {}", self.config.grpc_url, e)))?;
+        let response = ServiceClient::new(channel)
+            .broadcast_tx(BroadcastTxRequest { tx_bytes, mode: BroadcastMode::Sync as i32 })
+            .await
+            .map_err(|e| self.chain_error(format!("Broadcast failed: {}", e.message())))?
+            .into_inner()
+            .tx_response
+            .ok_or_else(|| self.chain_error("Broadcast returned no transaction response"))?;
+
+        if response.code != 0 {
+            return Err(self.chain_error(format!("Transaction {} failed: {}", response.txhash, response.raw_log)));
+        }
+        Ok(response.txhash)
+    }
+}
+
+impl CosmosClientWrapper {
+    /// Chain error tagged with this client's chain ID
+    fn chain_error(&self, message: impl Into<String>) -> Error {
+        Error::chain(self.config.chain_id.clone(), message)
+    }
+}
@@ -0,0 +1,28 @@
+//! CosmWasm contract queries and executions
+//!
+//! Generated contract clients talk to chains through [`CosmWasmClient`].
+//! Messages and responses are passed as JSON bytes so the generated code owns
+//! their types. With the `grpc` feature [`CosmosClientWrapper`] implements it
+//! over the chain's gRPC endpoint.
+//!
+//! [`CosmosClientWrapper`]: crate::CosmosClientWrapper
+
+use async_trait::async_trait;
+use cosmwasm_std::Coin;
+
+use indexer_core::Result;
+
+#[cfg(feature = "grpc")]
+mod grpc;
+
+/// Client able to query and execute CosmWasm contracts
+#[async_trait]
+pub trait CosmWasmClient: Send + Sync {
+    /// Run the JSON smart query `msg` against `contract_address` and return
+    /// the JSON response
+    async fn query_wasm_smart(&self, contract_address: &str, msg: Vec<u8>) -> Result<Vec<u8>>;
+
+    /// Execute the JSON message `msg` on `contract_address` with `funds`
+    /// attached and return the transaction hash
+    async fn execute_wasm(&self, contract_address: &str, msg: Vec<u8>, funds: &[Coin]) -> Result<String>;
+}
@@ -1,6 +1,7 @@
 //! CLI interface for ethereum contract code generation
 
-use super::{EthereumCodegenConfig, generate_contract_code};
+use super::{EthereumCodegenConfig, check_contract_code, dump_contract_templates, generate_contract_code, generate_proxy_contract_code};
+use indexer_core::codegen::{fail_on_drift, report_drift};
 use indexer_core::Result;
 use clap::{Arg, ArgMatches, Command};
 
@@ -10,17 +11,24 @@ pub fn build_generate_contract_command() -> Command {
         .about("Generate code for interacting with an Ethereum contract from its ABI")
         .arg(
             Arg::new("abi-file")
-                .help("Path to the contract ABI JSON file")
+                .help("Path to the contract ABI JSON file, a Foundry or Hardhat artifact, or a directory of artifacts")
                 .required(true)
                 .index(1)
         )
         .arg(
             Arg::new("address")
                 .long("address")
-                .help("Contract address on the chain")
-                .required(true)
+                .help("Contract address on the chain, optional for a directory of artifacts")
+                .required(false)
                 .value_name("CONTRACT_ADDRESS")
         )
+        .arg(
+            Arg::new("implementation-abi")
+                .long("implementation-abi")
+                .help("ABI or artifact of an implementation behind the proxy, repeated oldest first")
+                .action(clap::ArgAction::Append)
+                .value_name("PATH")
+        )
         .arg(
             Arg::new("chain")
                 .long("chain")
@@ -45,15 +53,39 @@ pub fn build_generate_contract_command() -> Command {
             Arg::new("features")
                 .long("features")
                 .help("Comma-separated list of features to generate")
-                .default_value("client,storage,api,migrations")
+                .default_value("client,storage,api,migrations,handlers")
                 .value_name("FEATURES")
         )
+        .arg(
+            Arg::new("templates-dir")
+                .long("templates-dir")
+                .help("Directory of Handlebars templates overriding or adding generated files")
+                .value_name("DIR")
+        )
+        .arg(
+            Arg::new("migration-version")
+                .long("migration-version")
+                .help("Version prefix of the generated migration, derived from the inputs by default")
+                .value_name("VERSION")
+        )
         .arg(
             Arg::new("dry-run")
                 .long("dry-run")
                 .help("Preview generated code without writing files")
                 .action(clap::ArgAction::SetTrue)
         )
+        .arg(
+            Arg::new("dump-templates")
+                .long("dump-templates")
+                .help("Write the generated files as reference Handlebars templates to this directory instead")
+                .value_name("DIR")
+        )
+        .arg(
+            Arg::new("check")
+                .long("check")
+                .help("Regenerate in memory and fail if the files in the output directory differ")
+                .action(clap::ArgAction::SetTrue)
+        )
         .arg(
             Arg::new("verbose")
                 .long("verbose")
@@ -68,8 +100,16 @@ pub async fn handle_generate_contract_command(matches: &ArgMatches) -> Result<()
     let abi_file = matches.get_one::<String>("abi-file")
         .ok_or_else(|| indexer_core::Error::Config("ABI file path is required".to_string()))?;
 
-    let contract_address = matches.get_one::<String>("address")
-        .ok_or_else(|| indexer_core::Error::Config("Contract address is required".to_string()))?;
+    let is_artifacts_dir = std::path::Path::new(abi_file).is_dir();
+    let contract_address = match matches.get_one::<String>("address") {
+        Some(address) => address.clone(),
+        None if is_artifacts_dir => String::new(),
+        None => return Err(indexer_core::Error::Config("Contract address is required".to_string())),
+    };
+
+    let implementation_abis: Vec<String> = matches.get_many::<String>("implementation-abi")
+        .map(|paths| paths.cloned().collect())
+        .unwrap_or_default();
 
     let chain_id = matches.get_one::<String>("chain")
         .ok_or_else(|| indexer_core::Error::Config("Chain ID is required".to_string()))?;
@@ -82,24 +122,35 @@ pub async fn handle_generate_contract_command(matches: &ArgMatches) -> Result<()
 
     let features_str = matches.get_one::<String>("features")
         .map(|s| s.as_str())
-        .unwrap_or("client,storage,api,migrations");
+        .unwrap_or("client,storage,api,migrations,handlers");
     
     let features = features_str
         .split(',')
         .map(|s| s.trim().to_string())
         .collect();
 
+    let templates_dir = matches.get_one::<String>("templates-dir").cloned();
+    let migration_version = matches.get_one::<String>("migration-version").cloned();
     let dry_run = matches.get_flag("dry-run");
+    let check = matches.get_flag("check");
+    let dump_templates = matches.get_one::<String>("dump-templates");
     let verbose = matches.get_flag("verbose");
 
-    // Validate contract address format
-    validate_contract_address(contract_address)?;
+    // Validate contract address format, artifacts may be generated without one
+    if !is_artifacts_dir || !contract_address.is_empty() {
+        validate_contract_address(&contract_address)?;
+    }
 
     // Validate chain ID
     validate_chain_id(chain_id)?;
 
     // Validate ABI file exists
-    validate_abi_file(abi_file).await?;
+    if !is_artifacts_dir {
+        validate_abi_file(abi_file).await?;
+    }
+    for path in &implementation_abis {
+        validate_abi_file(path).await?;
+    }
 
     let config = EthereumCodegenConfig {
         contract_address: contract_address.clone(),
@@ -108,6 +159,8 @@ pub async fn handle_generate_contract_command(matches: &ArgMatches) -> Result<()
         namespace,
         features,
         dry_run,
+        templates_dir,
+        migration_version,
     };
 
     if verbose {
@@ -115,23 +168,50 @@ pub async fn handle_generate_contract_command(matches: &ArgMatches) -> Result<()
         println!("=================================");
         println!("ABI file: {}", abi_file);
         println!("Contract address: {}", contract_address);
+        if !implementation_abis.is_empty() {
+            println!("Implementation ABIs: {}", implementation_abis.join(", "));
+        }
         println!("Chain ID: {}", chain_id);
         println!("Output directory: {}", output_dir);
         if let Some(ref ns) = config.namespace {
             println!("Namespace: {}", ns);
         }
         println!("Features: {}", config.features.join(", "));
+        if let Some(ref dir) = config.templates_dir {
+            println!("Templates directory: {}", dir);
+        }
+        if let Some(ref version) = config.migration_version {
+            println!("Migration version: {}", version);
+        }
         println!("Dry run: {}", dry_run);
         println!();
     }
 
+    if let Some(dir) = dump_templates {
+        println!("📝 Writing reference templates to {}", dir);
+        let templates = dump_contract_templates(abi_file, &implementation_abis, config, dir).await?;
+        println!("✅ Wrote {} reference templates", templates.len());
+        return Ok(());
+    }
+
+    if check {
+        println!("🔍 Checking generated code in {}", output_dir);
+        let drift = check_contract_code(abi_file, &implementation_abis, config).await?;
+        println!("{}", report_drift(&drift));
+        return fail_on_drift(&drift);
+    }
+
     if dry_run {
         println!("🔍 Performing dry run - no files will be written");
     } else {
         println!("🚀 Generating ethereum contract code...");
     }
 
-    generate_contract_code(abi_file, config).await?;
+    if implementation_abis.is_empty() {
+        generate_contract_code(abi_file, config).await?;
+    } else {
+        generate_proxy_contract_code(abi_file, &implementation_abis, config).await?;
+    }
 
     if !dry_run {
         println!("✅ Code generation completed successfully!");
@@ -197,6 +277,8 @@ pub fn print_features_help() {
     println!("  storage    - Generate storage models and database schemas");
     println!("  api        - Generate REST, GraphQL, and WebSocket APIs");
     println!("  migrations - Generate database migration files");
+    println!("  handlers   - Generate an event handler writing to the migration tables");
+    println!("  typescript - Generate TypeScript event types, API client and GraphQL fragments");
     println!();
     println!("Example: --features client,storage,api");
 } 
\ No newline at end of file
@@ -1,54 +1,191 @@
 //! Code generator for Ethereum contracts
 //! 
-//! Generates Rust code for client interactions, storage models, APIs, and migrations
-//! from parsed Ethereum contract ABIs.
-
-use super::{EthereumCodegenConfig, parser::EthereumAbi};
+//! Generates Rust code for client interactions, storage models, APIs, migrations
+//! and event handlers, and TypeScript for frontends, from parsed Ethereum
+//! contract ABIs.
+
+use super::{EthereumCodegenConfig, handlers::EventHandlerGenerator, migrations::EventTableGenerator, structs::{self, AbiTypeGenerator}, typescript::TypeScriptGenerator};
+use super::parser::{AbiParameter, ContractDocs, DocEntry, EthereumAbi};
+use super::templates::EthereumTemplateManager;
+use indexer_core::codegen::{self, GeneratedFile, Provenance};
 use indexer_core::Result;
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 use convert_case::Casing;
 
 /// Code generator for ethereum contracts
 pub struct EthereumContractCodegen {
     config: EthereumCodegenConfig,
+    /// Inputs recorded in the header of generated files
+    provenance: Option<Provenance>,
+    /// Files kept in memory instead of written
+    captured: Option<Mutex<Vec<GeneratedFile>>>,
+    /// User templates overriding or adding generated files
+    templates: Option<EthereumTemplateManager>,
+    /// Model of the ABI being generated for templates, and the files
+    /// generated for it so far
+    template_state: Mutex<Option<(serde_json::Value, HashSet<String>)>>,
 }
 
 impl EthereumContractCodegen {
     /// Create a new code generator with the given configuration
     pub fn new(config: EthereumCodegenConfig) -> Self {
-        Self { config }
+        Self { config, provenance: None, captured: None, templates: None, template_state: Mutex::new(None) }
+    }
+
+    /// Render generated files with the file templates of `templates`, see
+    /// [`EthereumTemplateManager::with_templates_dir`]
+    pub fn with_templates(mut self, templates: EthereumTemplateManager) -> Self {
+        self.templates = Some(templates);
+        self
+    }
+
+    /// Start generated files with a header recording their inputs and the
+    /// generator version
+    pub fn with_provenance(mut self, provenance: Provenance) -> Self {
+        self.provenance = Some(provenance);
+        self
+    }
+
+    /// Keep generated files in memory instead of writing them, to be taken
+    /// with [`take_files`](Self::take_files)
+    pub fn in_memory(mut self) -> Self {
+        self.captured = Some(Mutex::new(Vec::new()));
+        self
+    }
+
+    /// Files generated in memory so far
+    pub fn take_files(&self) -> Vec<GeneratedFile> {
+        self.captured
+            .as_ref()
+            .map(|files| std::mem::take(&mut *files.lock().unwrap()))
+            .unwrap_or_default()
+    }
+
+    /// Whether generated files are written to the output directory
+    fn writes_files(&self) -> bool {
+        !self.config.dry_run && self.captured.is_none()
+    }
+
+    /// Version prefix of the generated migration `name`
+    ///
+    /// The configured version, or else the version of the unchanged migration
+    /// already in `migrations_dir`, or else the current time past the
+    /// highest version there.
+    fn migration_version(&self, migrations_dir: &Path, name: &str, up_sql: &str) -> Result<String> {
+        codegen::migration_version(
+            self.config.migration_version.as_deref(),
+            migrations_dir,
+            &format!("{}.up.sql", name),
+            up_sql,
+        )
     }
 
     /// Generate all enabled code components
     pub async fn generate_all(&self, abi: &EthereumAbi) -> Result<()> {
         let output_dir = Path::new(&self.config.output_dir);
         
-        if !self.config.dry_run {
+        if self.writes_files() {
             tokio::fs::create_dir_all(output_dir).await
                 .map_err(|e| indexer_core::Error::Config(format!("Failed to create output directory: {}", e)))?;
         }
 
+        if self.templates.is_some() {
+            *self.template_state.lock().unwrap() = Some((self.template_model(abi)?, HashSet::new()));
+        }
+
         for feature in &self.config.features {
             match feature.as_str() {
                 "client" => self.generate_client_code(abi, output_dir).await?,
                 "storage" => self.generate_storage_code(abi, output_dir).await?,
                 "api" => self.generate_api_code(abi, output_dir).await?,
                 "migrations" => self.generate_migration_code(abi, output_dir).await?,
+                "handlers" => self.generate_handler_code(abi, output_dir).await?,
+                "typescript" => self.generate_typescript_code(abi, output_dir).await?,
                 _ => {
                     println!("Warning: Unknown feature '{}'", feature);
                 }
             }
         }
 
+        self.generate_template_files(output_dir).await
+    }
+
+    /// Write the files of templates that don't override a generated file
+    async fn generate_template_files(&self, output_dir: &Path) -> Result<()> {
+        let Some(templates) = &self.templates else {
+            return Ok(());
+        };
+        let generated = match &*self.template_state.lock().unwrap() {
+            Some((_, generated)) => generated.clone(),
+            None => return Ok(()),
+        };
+        for name in templates.custom_templates() {
+            if codegen::is_file_template(name) && !generated.contains(name) {
+                self.write_file(&output_dir.join(name), "").await?;
+            }
+        }
         Ok(())
     }
 
+    /// Data templates are rendered with: the configuration, the name used
+    /// for the contract in generated code and the parsed ABI
+    fn template_model(&self, abi: &EthereumAbi) -> Result<serde_json::Value> {
+        Ok(serde_json::json!({
+            "contract_address": self.config.contract_address,
+            "chain_id": self.config.chain_id,
+            "namespace": self.config.namespace,
+            "features": self.config.features,
+            "contract_name": self.sanitize_contract_name(),
+            "abi": serde_json::to_value(abi)?,
+        }))
+    }
+
+    /// Content of a generated file, rendered with its user template if any
+    ///
+    /// The template sees the file's path relative to the output directory as
+    /// `path` and the built-in content as `generated`, empty for files only
+    /// generated from a template. Migrations are also rendered by templates
+    /// named without their version, see [`codegen::file_template_names`].
+    fn render_template(&self, path: &Path, content: &str) -> Result<String> {
+        let (Some(templates), Ok(relative)) = (&self.templates, path.strip_prefix(&self.config.output_dir)) else {
+            return Ok(content.to_string());
+        };
+        let name = codegen::slash_path(relative);
+
+        let mut state = self.template_state.lock().unwrap();
+        let Some((model, generated)) = state.as_mut() else {
+            return Ok(content.to_string());
+        };
+        let names = codegen::file_template_names(&name);
+        generated.extend(names.iter().cloned());
+        let Some(template) = names.iter().find(|name| templates.custom_templates().contains(name)) else {
+            return Ok(content.to_string());
+        };
+
+        let mut data = model.clone();
+        data["path"] = serde_json::Value::String(name);
+        data["generated"] = serde_json::Value::String(content.to_string());
+        templates.render(template, &data)
+    }
+
+    /// Write `mod.rs` to the output directory, declaring `modules` so the
+    /// directory can be included as a module
+    pub async fn generate_module_file(&self, description: &str, modules: &[String]) -> Result<()> {
+        let mut code = format!("//! {}\n\n", description);
+        for module in modules {
+            code.push_str(&format!("pub mod {};\n", module));
+        }
+        self.write_file(&Path::new(&self.config.output_dir).join("mod.rs"), &code).await
+    }
+
     /// Generate client interaction code
     async fn generate_client_code(&self, abi: &EthereumAbi, output_dir: &Path) -> Result<()> {
         println!("Generating client code for contract: {}", self.config.contract_address);
         
         let client_dir = output_dir.join("client");
-        if !self.config.dry_run {
+        if self.writes_files() {
             tokio::fs::create_dir_all(&client_dir).await
                 .map_err(|e| indexer_core::Error::Config(format!("Failed to create client directory: {}", e)))?;
         }
@@ -60,23 +197,23 @@ impl EthereumContractCodegen {
         // Generate view functions
         let view_functions = abi.functions.iter().filter(|f| f.state_mutability == "view" || f.state_mutability == "pure").collect::<Vec<_>>();
         if !view_functions.is_empty() {
-            let view_code = self.generate_view_methods(&view_functions)?;
+            let view_code = self.generate_view_methods(&view_functions, &abi.docs)?;
             self.write_file(&client_dir.join("view.rs"), &view_code).await?;
         }
 
         // Generate transaction functions
         let tx_functions = abi.functions.iter().filter(|f| f.state_mutability == "nonpayable" || f.state_mutability == "payable").collect::<Vec<_>>();
         if !tx_functions.is_empty() {
-            let tx_code = self.generate_transaction_methods(&tx_functions)?;
+            let tx_code = self.generate_transaction_methods(&tx_functions, &abi.docs)?;
             self.write_file(&client_dir.join("transactions.rs"), &tx_code).await?;
         }
 
         // Generate deployment method
         if let Some(constructor) = &abi.constructor {
-            let deploy_code = self.generate_deployment_method(Some(constructor))?;
+            let deploy_code = self.generate_deployment_method(Some(constructor), abi)?;
             self.write_file(&client_dir.join("deploy.rs"), &deploy_code).await?;
         } else {
-            let deploy_code = self.generate_deployment_method(None)?;
+            let deploy_code = self.generate_deployment_method(None, abi)?;
             self.write_file(&client_dir.join("deploy.rs"), &deploy_code).await?;
         }
 
@@ -102,7 +239,7 @@ impl EthereumContractCodegen {
         println!("Generating storage code for contract: {}", self.config.contract_address);
         
         let storage_dir = output_dir.join("storage");
-        if !self.config.dry_run {
+        if self.writes_files() {
             tokio::fs::create_dir_all(&storage_dir).await
                 .map_err(|e| indexer_core::Error::Config(format!("Failed to create storage directory: {}", e)))?;
         }
@@ -131,7 +268,7 @@ impl EthereumContractCodegen {
         println!("Generating API code for contract: {}", self.config.contract_address);
         
         let api_dir = output_dir.join("api");
-        if !self.config.dry_run {
+        if self.writes_files() {
             tokio::fs::create_dir_all(&api_dir).await
                 .map_err(|e| indexer_core::Error::Config(format!("Failed to create api directory: {}", e)))?;
         }
@@ -164,16 +301,63 @@ impl EthereumContractCodegen {
         println!("Generating migration code for contract: {}", self.config.contract_address);
         
         let migrations_dir = output_dir.join("migrations");
-        if !self.config.dry_run {
+        if self.writes_files() {
             tokio::fs::create_dir_all(&migrations_dir).await
                 .map_err(|e| indexer_core::Error::Config(format!("Failed to create migrations directory: {}", e)))?;
         }
 
-        // Generate migration file
-        let migration_sql = self.generate_migration_sql(abi)?;
-        let timestamp = chrono::Utc::now().format("%Y%m%d%H%M%S");
-        let filename = format!("{}_{}_contract.sql", timestamp, self.sanitize_contract_name());
-        self.write_file(&migrations_dir.join(filename), &migration_sql).await?;
+        let (up_sql, down_sql) = self.generate_migration_sql(abi)?;
+        let name = format!("{}_events", self.sanitize_contract_name().to_lowercase());
+        let version = self.migration_version(&migrations_dir, &name, &up_sql)?;
+        let name = format!("{}_{}", version, name);
+        self.write_file(&migrations_dir.join(format!("{}.up.sql", name)), &up_sql).await?;
+        self.write_file(&migrations_dir.join(format!("{}.down.sql", name)), &down_sql).await?;
+
+        Ok(())
+    }
+
+    /// Generate the event handler feeding the migration tables
+    async fn generate_handler_code(&self, abi: &EthereumAbi, output_dir: &Path) -> Result<()> {
+        println!("Generating event handler for contract: {}", self.config.contract_address);
+
+        let handlers_dir = output_dir.join("handlers");
+        if self.writes_files() {
+            tokio::fs::create_dir_all(&handlers_dir).await
+                .map_err(|e| indexer_core::Error::Config(format!("Failed to create handlers directory: {}", e)))?;
+        }
+
+        let contract_name = self.sanitize_contract_name();
+        let handler_code = EventHandlerGenerator::new(
+            &contract_name,
+            contract_name.to_lowercase(),
+            &self.config.contract_address,
+            &self.config.chain_id,
+        )
+        .generate(abi);
+        self.write_file(&handlers_dir.join("mod.rs"), &handler_code).await?;
+
+        Ok(())
+    }
+
+    /// Generate TypeScript for frontends of the contract's API
+    async fn generate_typescript_code(&self, abi: &EthereumAbi, output_dir: &Path) -> Result<()> {
+        println!("Generating TypeScript code for contract: {}", self.config.contract_address);
+
+        let typescript_dir = output_dir.join("typescript");
+        if self.writes_files() {
+            tokio::fs::create_dir_all(&typescript_dir).await
+                .map_err(|e| indexer_core::Error::Config(format!("Failed to create typescript directory: {}", e)))?;
+        }
+
+        let generator = TypeScriptGenerator::new(
+            &self.sanitize_contract_name(),
+            &self.config.contract_address,
+            &self.config.chain_id,
+        );
+        self.write_file(&typescript_dir.join("events.ts"), &generator.events(abi)).await?;
+        self.write_file(&typescript_dir.join("client.ts"), &generator.client(abi)).await?;
+        self.write_file(&typescript_dir.join("fragments.ts"), &generator.fragments(abi)).await?;
+        self.write_file(&typescript_dir.join("index.ts"), &generator.index()).await?;
 
         Ok(())
     }
@@ -213,7 +397,19 @@ impl {}Storage {{
         ))
     }
 
-    fn generate_client_module(&self, _abi: &EthereumAbi) -> Result<String> {
+    fn generate_client_module(&self, abi: &EthereumAbi) -> Result<String> {
+        // NatSpec of the contract, then the generic description
+        let mut docs = abi.docs.contract.doc_comment(&[], &[], "");
+        if let Some(title) = &abi.docs.title {
+            docs.insert_str(0, &format!("/// {}\n///\n", title));
+        }
+        if let Some(author) = &abi.docs.author {
+            docs.push_str(&format!("///\n/// Author: {}\n", author));
+        }
+        if !docs.is_empty() {
+            docs.push_str("///\n");
+        }
+
         Ok(format!(
             r#"//! Generated client code for contract: {}
 //! Chain: {}
@@ -228,7 +424,7 @@ pub mod events;
 use indexer_core::Result;
 use indexer_ethereum::EthereumClient;
 
-/// Client for interacting with the {} contract
+{}/// Client for interacting with the {} contract
 pub struct {}Client {{
     ethereum_client: EthereumClient,
     contract_address: String,
@@ -256,13 +452,14 @@ impl {}Client {{
 "#,
             self.config.contract_address,
             self.config.chain_id,
+            docs,
             self.config.contract_address,
             self.sanitize_contract_name(),
             self.sanitize_contract_name()
         ))
     }
 
-    fn generate_view_methods(&self, functions: &[&super::parser::AbiFunction]) -> Result<String> {
+    fn generate_view_methods(&self, functions: &[&super::parser::AbiFunction], docs: &ContractDocs) -> Result<String> {
         let mut methods = String::new();
         let client_name = self.sanitize_contract_name();
 
@@ -317,6 +514,7 @@ impl {}Client {{
             };
 
             // Generate method documentation
+            methods.push_str(&natspec_docs(docs.function(function), &function.inputs, &function.outputs));
             methods.push_str(&format!("    /// Call view function: {}\n", method_name));
             if let Some(ref sig) = function.signature {
                 methods.push_str(&format!("    /// Function selector: {}\n", sig));
@@ -416,7 +614,7 @@ impl {}Client {{
         }
     }
 
-    fn generate_transaction_methods(&self, functions: &[&super::parser::AbiFunction]) -> Result<String> {
+    fn generate_transaction_methods(&self, functions: &[&super::parser::AbiFunction], docs: &ContractDocs) -> Result<String> {
         let mut methods = String::new();
         let client_name = self.sanitize_contract_name();
 
@@ -464,6 +662,7 @@ impl {}Client {{
             };
 
             // Generate method documentation
+            methods.push_str(&natspec_docs(docs.function(function), &function.inputs, &function.outputs));
             methods.push_str(&format!("    /// Call transaction function: {}\n", method_name));
             if let Some(ref sig) = function.signature {
                 methods.push_str(&format!("    /// Function selector: {}\n", sig));
@@ -522,10 +721,16 @@ impl {}Client {{
         Ok(methods)
     }
 
-    fn generate_deployment_method(&self, constructor: Option<&super::parser::AbiFunction>) -> Result<String> {
+    fn generate_deployment_method(&self, constructor: Option<&super::parser::AbiFunction>, abi: &EthereumAbi) -> Result<String> {
         let client_name = self.sanitize_contract_name();
         let mut method = String::new();
 
+        // Bytecode referencing unlinked libraries has `__$...$__` placeholders
+        // and has to be linked and passed in by the caller
+        let bytecode = abi.bytecode.as_deref()
+            .map(|bytecode| bytecode.trim_start_matches("0x"))
+            .filter(|bytecode| bytecode.chars().all(|c| c.is_ascii_hexdigit()));
+
         // Generate header
         method.push_str(&format!(
             r#"//! Generated deployment method for contract: {}
@@ -534,114 +739,104 @@ use super::types::*;
 use super::{}Client;
 use indexer_core::Result;
 use alloy_primitives::{{Address, U256, Bytes}};
-
-impl {}Client {{
 "#,
             self.config.contract_address,
-            client_name,
             client_name
         ));
+        if let Some(bytecode) = bytecode {
+            method.push_str(&format!(
+                "\n/// Creation bytecode of the contract, from its build artifact\npub const BYTECODE: &[u8] = &alloy_primitives::hex!(\"{}\");\n",
+                bytecode
+            ));
+        }
+        method.push_str(&format!("\nimpl {}Client {{\n", client_name));
+
+        let bytecode_param = if bytecode.is_some() { None } else { Some("bytecode: Bytes".to_string()) };
+        let bytecode_value = if bytecode.is_some() { "BYTECODE" } else { "bytecode" };
+        let bytecode_ref = if bytecode.is_some() { "BYTECODE" } else { "&bytecode" };
+        let unlinked_docs = if abi.bytecode.is_some() && bytecode.is_none() {
+            "    ///\n    /// The artifact's bytecode references libraries, so `bytecode` must be linked first\n"
+        } else {
+            ""
+        };
 
         if let Some(constructor) = constructor {
-            // Generate parameters string
-            let mut params = Vec::new();
-            for (i, input) in constructor.inputs.iter().enumerate() {
-                let param_name = if input.name.is_empty() {
-                    format!("param_{}", i)
-                } else {
-                    input.name.clone()
-                };
-                let rust_type = self.convert_abi_type_to_rust(&input.param_type);
-                params.push(format!("{}: {}", param_name, rust_type));
-            }
+            // Constructor arguments take the types generated for them, so
+            // tuples are passed as their structs
+            let rust_types = AbiTypeGenerator::new().constructor_types(abi)?;
+            let param_names: Vec<String> = constructor.inputs.iter()
+                .enumerate()
+                .map(|(i, input)| structs::field_name(&input.name, i))
+                .collect();
+            let mut params: Vec<String> = param_names.iter()
+                .zip(&rust_types)
+                .map(|(name, rust_type)| format!("{}: {}", name, rust_type))
+                .collect();
             
             // Add standard deployment parameters
-            params.push("bytecode: Bytes".to_string());
+            params.extend(bytecode_param);
             if constructor.payable {
                 params.push("value: U256".to_string());
             }
             
-            let params_str = params.join(", ");
+            let params_str = params.iter().map(|param| format!(", {}", param)).collect::<String>();
 
             // Generate method documentation
+            method.push_str(&natspec_docs(abi.docs.function(constructor), &constructor.inputs, &[]));
             method.push_str("    /// Deploy a new contract instance\n");
             if constructor.payable {
                 method.push_str("    /// This constructor is payable and can receive ETH\n");
             }
+            method.push_str(unlinked_docs);
 
-            // Generate method signature and body
-            method.push_str(&format!(
-                r#"    pub async fn deploy(&self, {}) -> Result<Address> {{
-        // Encode constructor arguments
-        let constructor_args = self.encode_constructor_args(&[
-"#,
-                params_str
-            ));
-
-            // Add parameters to constructor call (excluding bytecode and value)
-            for (i, input) in constructor.inputs.iter().enumerate() {
-                let param_name = if input.name.is_empty() {
-                    format!("param_{}", i)
-                } else {
-                    input.name.clone()
-                };
-                method.push_str(&format!("            {},\n", param_name));
-            }
-
-            method.push_str("        ])?;\n\n");
-            method.push_str("        // Combine bytecode with constructor arguments\n");
-            method.push_str("        let mut deployment_data = bytecode.to_vec();\n");
-            method.push_str("        deployment_data.extend_from_slice(&constructor_args);\n\n");
-
-            if constructor.payable {
-                method.push_str(r#"        // Deploy contract with value using ethereum client
-        // TODO: Implement actual ethereum contract deployment
-        // This would use self.ethereum_client().deploy_contract(&deployment_data, value).await
-        todo!("Implement ethereum contract deployment (payable)")
-    }
-"#);
+            // The creation code is the bytecode followed by the ABI-encoded constructor arguments
+            let value = if constructor.payable { "value" } else { "U256::ZERO" };
+            method.push_str(&format!("    pub async fn deploy(&self{}) -> Result<Address> {{\n", params_str));
+            if param_names.is_empty() {
+                method.push_str(&format!("        self.ethereum_client().deploy_contract({}, {}).await\n", bytecode_ref, value));
             } else {
-                method.push_str(r#"        // Deploy contract using ethereum client
-        // TODO: Implement actual ethereum contract deployment
-        // This would use self.ethereum_client().deploy_contract(&deployment_data, U256::ZERO).await
-        todo!("Implement ethereum contract deployment")
-    }
-"#);
+                method.push_str(&format!(
+                    "        let mut creation_code = {}.to_vec();\n        creation_code.extend(\n            TupleEncoder::default()\n",
+                    bytecode_value
+                ));
+                for name in &param_names {
+                    method.push_str(&format!("                .field(&{})\n", name));
+                }
+                method.push_str("                .finish(),\n        );\n");
+                method.push_str(&format!("        self.ethereum_client().deploy_contract(&creation_code, {}).await\n", value));
             }
+            method.push_str("    }\n");
         } else {
-            // No constructor - simple deployment
-            method.push_str(r#"    /// Deploy a new contract instance (no constructor)
-    pub async fn deploy(&self, bytecode: Bytes) -> Result<Address> {
-        // Deploy contract using ethereum client
-        // TODO: Implement actual ethereum contract deployment
-        // This would use self.ethereum_client().deploy_contract(&bytecode, U256::ZERO).await
-        todo!("Implement ethereum contract deployment")
-    }
-"#);
+            // No constructor - the creation code is the bytecode alone
+            method.push_str("    /// Deploy a new contract instance (no constructor)\n");
+            method.push_str(unlinked_docs);
+            method.push_str(&format!(
+                r#"    pub async fn deploy(&self{}) -> Result<Address> {{
+        self.ethereum_client().deploy_contract({}, U256::ZERO).await
+    }}
+"#,
+                bytecode_param.map(|param| format!(", {}", param)).unwrap_or_default(),
+                bytecode_ref
+            ));
         }
 
         method.push_str("\n}\n");
         Ok(method)
     }
 
-    fn generate_types(&self, _abi: &EthereumAbi) -> Result<String> {
+    fn generate_types(&self, abi: &EthereumAbi) -> Result<String> {
+        let abi_types = AbiTypeGenerator::new().generate(abi)?;
+
         Ok(format!(
             r#"//! Generated types for contract: {}
 
 use serde::{{Deserialize, Serialize}};
-use alloy_primitives::{{Address, U256, Bytes}};
+use alloy_primitives::{{b256, Address, Bytes, FixedBytes, B256, I256, U256}};
 
 // Re-export alloy types that might be used
 pub use alloy_primitives::*;
 
-// Event types will be generated here based on ABI event definitions
-// TODO: Implement type generation from ABI
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PlaceholderType {{
-    // Placeholder - will be replaced with actual generated types
-}}
-
+{}
 /// Contract execution errors
 #[derive(Debug, thiserror::Error)]
 pub enum ContractError {{
@@ -736,7 +931,8 @@ pub mod gas_utils {{
     }}
 }}
 "#,
-            self.config.contract_address
+            self.config.contract_address,
+            abi_types
         ))
     }
 
@@ -747,7 +943,6 @@ pub mod gas_utils {{
         sql.push_str(&format!(
             r#"-- Generated PostgreSQL schema for contract: {}
 -- Chain: {}
--- Generated at: {}
 
 -- Extension for JSON operations
 CREATE EXTENSION IF NOT EXISTS "uuid-ossp";
@@ -814,7 +1009,6 @@ CREATE TABLE IF NOT EXISTS {}_deployments (
 "#,
             self.config.contract_address,
             self.config.chain_id,
-            chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC"),
             contract_name.to_lowercase(),
             contract_name.to_lowercase(),
             contract_name.to_lowercase(),
@@ -860,40 +1054,12 @@ CREATE TABLE IF NOT EXISTS {}_{}_calls (
 "#);
         }
 
-        // Generate event-specific tables
+        // Generate event-specific tables, shared with the generated migrations
+        let event_tables = EventTableGenerator::new(contract_name.to_lowercase());
         for event in &abi.events {
-            sql.push_str(&format!(
-                r#"-- Event-specific table for {} events
-CREATE TABLE IF NOT EXISTS {}_{}_events (
-    id BIGSERIAL PRIMARY KEY,
-    transaction_hash TEXT NOT NULL,
-    contract_address TEXT NOT NULL,
-    block_number BIGINT NOT NULL,
-    block_timestamp TIMESTAMPTZ NOT NULL,
-    log_index INTEGER NOT NULL,
-    transaction_index INTEGER NOT NULL,
-"#,
-                event.name,
-                contract_name.to_lowercase(),
-                event.name.to_lowercase()
-            ));
-
-            // Add columns for each event input
-            for input in &event.inputs {
-                let param_name = if input.name.is_empty() {
-                    format!("param_{}", input.name)
-                } else {
-                    input.name.clone()
-                };
-                let sql_type = self.convert_abi_type_to_postgres(&input.param_type);
-                sql.push_str(&format!("    {} {},\n", param_name, sql_type));
-            }
-
-            sql.push_str(r#"    removed BOOLEAN NOT NULL DEFAULT FALSE,
-    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
-);
-
-"#);
+            sql.push_str(&format!("-- Event-specific table for {} events\n", event.name));
+            sql.push_str(&event_tables.create_table(abi, event));
+            sql.push('\n');
         }
 
         // Add indexes for performance
@@ -932,14 +1098,6 @@ CREATE TABLE IF NOT EXISTS {}_{}_events (
             sql.push_str(&format!("CREATE INDEX IF NOT EXISTS idx_{}_{}_calls_tx ON {}_{}_calls(transaction_hash);\n", contract_lower, function_lower, contract_lower, function_lower));
         }
 
-        // Event-specific indexes
-        for event in &abi.events {
-            let event_lower = event.name.to_lowercase();
-            sql.push_str(&format!("CREATE INDEX IF NOT EXISTS idx_{}_{}_events_contract ON {}_{}_events(contract_address);\n", contract_lower, event_lower, contract_lower, event_lower));
-            sql.push_str(&format!("CREATE INDEX IF NOT EXISTS idx_{}_{}_events_block ON {}_{}_events(block_number);\n", contract_lower, event_lower, contract_lower, event_lower));
-            sql.push_str(&format!("CREATE INDEX IF NOT EXISTS idx_{}_{}_events_tx ON {}_{}_events(transaction_hash);\n", contract_lower, event_lower, contract_lower, event_lower));
-        }
-
         Ok(sql)
     }
 
@@ -1674,23 +1832,27 @@ impl From<indexer_core::Error> for StatusCode {
         Ok(endpoints_code)
     }
 
-    fn generate_migration_sql(&self, _abi: &EthereumAbi) -> Result<String> {
-        Ok(format!(
-            r#"-- Migration for contract: {}
--- Chain: {}
-
--- TODO: Generate actual migration SQL based on contract ABI
-
--- Add contract-specific tables and indexes
-"#,
+    /// Up and down migration creating a typed table for every event
+    fn generate_migration_sql(&self, abi: &EthereumAbi) -> Result<(String, String)> {
+        let contract_name = self.sanitize_contract_name();
+        let migration = EventTableGenerator::new(contract_name.to_lowercase()).generate(abi);
+        let header = format!(
+            "-- Migration: Event tables for contract {}\n-- Chain: {}\n",
             self.config.contract_address,
             self.config.chain_id
+        );
+
+        Ok((
+            format!("{}\n{}", header, migration.up),
+            format!("{}\n{}", header, migration.down),
         ))
     }
 
-    /// Sanitize contract name for use in code identifiers
+    /// Sanitize contract name for use in code identifiers, taken from the
+    /// namespace when set and from the contract address otherwise
     fn sanitize_contract_name(&self) -> String {
-        self.config.contract_address
+        self.config.namespace.as_deref()
+            .unwrap_or(&self.config.contract_address)
             .chars()
             .filter(|c| c.is_alphanumeric())
             .collect::<String>()
@@ -1699,7 +1861,14 @@ impl From<indexer_core::Error> for StatusCode {
 
     /// Write content to file (or just print if dry run)
     async fn write_file(&self, path: &PathBuf, content: &str) -> Result<()> {
-        if self.config.dry_run {
+        let content = self.render_template(path, content)?;
+        let content = match self.provenance.as_ref().and_then(|provenance| provenance.header(path)) {
+            Some(header) => format!("{}{}", header, content),
+            None => content,
+        };
+        if let Some(files) = &self.captured {
+            files.lock().unwrap().push(GeneratedFile { path: path.clone(), content });
+        } else if self.config.dry_run {
             println!("\n--- {} ---", path.display());
             println!("{}", content);
         } else {
@@ -1707,7 +1876,7 @@ impl From<indexer_core::Error> for StatusCode {
                 tokio::fs::create_dir_all(parent).await
                     .map_err(|e| indexer_core::Error::Config(format!("Failed to create directory: {}", e)))?;
             }
-            tokio::fs::write(path, content).await
+            tokio::fs::write(path, &content).await
                 .map_err(|e| indexer_core::Error::Config(format!("Failed to write file {}: {}", path.display(), e)))?;
         }
         Ok(())
@@ -1853,39 +2022,21 @@ impl {}Client {{
         for event in events {
             let event_name = &event.name;
             let method_name = format!("parse_{}_event", event_name.to_lowercase());
-            
-            // Generate event struct if it has parameters
-            if !event.inputs.is_empty() {
-                methods.push_str(&format!(
-                    r#"    /// Parse {} event from transaction log
-    pub fn {}(&self, topics: &[String], data: &[u8]) -> Result<{}Event> {{
-        // TODO: Implement actual event parsing from log data
-        // This would decode the event parameters from topics and data according to ABI
-        todo!("Implement event parsing for {}")
-    }}
+            let struct_name = format!("{}Event", event_name.to_case(convert_case::Case::Pascal));
 
-"#,
-                    event_name, method_name, event_name, event_name
-                ));
-            } else {
-                methods.push_str(&format!(
-                    r#"    /// Parse {} event from transaction log (no parameters)
-    pub fn {}(&self, topics: &[String], data: &[u8]) -> Result<()> {{
-        // Verify this is the correct event type
-        if topics.is_empty() {{
-            return Err(indexer_core::Error::generic("Missing event topic"));
-        }}
-        
-        // TODO: Verify event signature matches {}
-        // let expected_topic = self.get_event_signature("{}")?;
-        
-        Ok(())
+            methods.push_str(&format!(
+                r#"    /// Parse {} event from transaction log
+    pub fn {}(&self, topics: &[String], data: &[u8]) -> Result<{}> {{
+        let topics = topics.iter()
+            .map(|topic| topic.parse::<B256>()
+                .map_err(|e| ContractError::EventParsingFailed(format!("Invalid topic {{}}: {{}}", topic, e))))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok({}::decode_log(&topics, data)?)
     }}
 
 "#,
-                    event_name, method_name, event_name, event_name
-                ));
-            }
+                event_name, method_name, struct_name, struct_name
+            ));
         }
 
         methods.push_str("}\n");
@@ -2499,4 +2650,13 @@ pub async fn rate_limit_middleware(
             contract_name
         ))
     }
-} 
\ No newline at end of file
+}
+
+/// NatSpec doc comment of a generated method, separated from the generated
+/// description that follows it
+fn natspec_docs(docs: Option<&DocEntry>, inputs: &[AbiParameter], outputs: &[AbiParameter]) -> String {
+    match docs.filter(|docs| !docs.is_empty()) {
+        Some(docs) => format!("{}    ///\n", docs.doc_comment(inputs, outputs, "    ")),
+        None => String::new(),
+    }
+}
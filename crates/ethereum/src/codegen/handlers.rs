@@ -0,0 +1,239 @@
+//! Event handler generation for ABI events
+//!
+//! Generates an `indexer_core::pipeline::EventHandler` per contract that
+//! matches logs on their signature topic, decodes them into the generated
+//! event structs and inserts them into the generated event tables. Rows of
+//! reorganized blocks are flagged `removed` until their logs are ingested
+//! again.
+
+use convert_case::{Case, Casing};
+
+use super::migrations::{column_name, is_value_type, EventTableGenerator};
+use super::parser::{AbiEvent, AbiParameter, EthereumAbi};
+use super::structs::field_name;
+
+/// Generated event handler for a contract
+pub struct EventHandlerGenerator {
+    /// Name of the generated handler struct
+    handler_name: String,
+    /// Prefix of the generated event tables
+    table_prefix: String,
+    contract_address: String,
+    chain_id: String,
+}
+
+impl EventHandlerGenerator {
+    /// Create a generator for `{contract_name}EventHandler` writing to the
+    /// tables of the `{table_prefix}` migrations
+    ///
+    /// Names starting with a digit, as `0x` addresses do, get a `Contract`
+    /// prefix to stay valid identifiers.
+    pub fn new(
+        contract_name: &str,
+        table_prefix: impl Into<String>,
+        contract_address: impl Into<String>,
+        chain_id: impl Into<String>,
+    ) -> Self {
+        let contract_name = if contract_name.starts_with(|c: char| c.is_ascii_digit()) {
+            format!("Contract{}", contract_name)
+        } else {
+            contract_name.to_string()
+        };
+        Self {
+            handler_name: format!("{}EventHandler", contract_name),
+            table_prefix: table_prefix.into(),
+            contract_address: contract_address.into(),
+            chain_id: chain_id.into(),
+        }
+    }
+
+    /// `handlers/mod.rs`: the handler and its registration
+    pub fn generate(&self, abi: &EthereumAbi) -> String {
+        let tables = EventTableGenerator::new(self.table_prefix.as_str());
+        // Anonymous events have no signature topic to match on
+        let events: Vec<&AbiEvent> = abi.events.iter().filter(|event| !event.anonymous).collect();
+
+        let mut arms = String::new();
+        let mut rollbacks = String::new();
+        for event in &events {
+            arms.push_str(&self.match_arm(&tables, abi, event));
+            rollbacks.push_str(&format!(
+                "\n    \"UPDATE {} SET removed = TRUE WHERE block_number >= $1\",",
+                tables.table_name(abi, event).replace('"', "\\\"")
+            ));
+        }
+
+        format!(
+            r#"//! Generated event handler for contract: {address}
+//! Chain: {chain}
+
+use std::sync::Arc;
+
+use alloy_primitives::{{hex, B256}};
+use async_trait::async_trait;
+use indexer_core::event::{{Event, EventData, UnifiedEvent}};
+use indexer_core::pipeline::{{event_index, EventHandler, EventHandlerRegistry}};
+use indexer_core::{{Error, Result}};
+use sqlx::PgPool;
+
+use super::client::types::*;
+
+/// Chain the contract is deployed on
+pub const CHAIN_ID: &str = "{chain}";
+
+/// Address of the contract
+pub const CONTRACT_ADDRESS: &str = "{address}";
+
+/// Statements flagging the rows of blocks from `$1` on as removed
+const ROLLBACK_STATEMENTS: &[&str] = &[{rollbacks}
+];
+
+/// Decodes the events of the {address} contract into its event tables
+pub struct {handler} {{
+    pool: PgPool,
+}}
+
+impl {handler} {{
+    /// Create a handler writing to the event tables in `pool`
+    pub fn new(pool: PgPool) -> Self {{
+        Self {{ pool }}
+    }}
+
+    /// Register a handler for the contract's events on its chain
+    pub fn register(registry: &mut EventHandlerRegistry, pool: PgPool) {{
+        registry.register(CHAIN_ID, CONTRACT_ADDRESS, Arc::new(Self::new(pool)));
+    }}
+}}
+
+#[async_trait]
+impl EventHandler for {handler} {{
+    fn name(&self) -> &str {{
+        "{handler}"
+    }}
+
+    async fn handle_event(&self, event: &dyn Event) -> Result<bool> {{
+        let Some(EventData::Evm {{ topics, data, address, .. }}) =
+            event.as_any().downcast_ref::<UnifiedEvent>().map(|event| &event.event_data)
+        else {{
+            return Ok(false);
+        }};
+        let topics = topics
+            .iter()
+            .map(|topic| topic.parse::<B256>())
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| Error::invalid_data(format!("Invalid topic in event {{}}: {{}}", event.id(), e)))?;
+        let Some(topic0) = topics.first() else {{
+            return Ok(false);
+        }};
+
+        let log_index = i32::try_from(event_index(event)?)
+            .map_err(|_| Error::invalid_data(format!("Log index of event {{}} out of range", event.id())))?;
+        let block_number = i64::try_from(event.block_number())
+            .map_err(|_| Error::invalid_data(format!("Block number of event {{}} out of range", event.id())))?;
+        let block_timestamp = chrono::DateTime::<chrono::Utc>::from(event.timestamp());
+
+        {dispatch}
+    }}
+
+    async fn rollback(&self, chain: &str, from_block: u64) -> Result<()> {{
+        if chain != CHAIN_ID {{
+            return Ok(());
+        }}
+        let from_block = i64::try_from(from_block)
+            .map_err(|_| Error::invalid_data(format!("Block number {{}} out of range", from_block)))?;
+        for statement in ROLLBACK_STATEMENTS {{
+            sqlx::query(statement)
+                .bind(from_block)
+                .execute(&self.pool)
+                .await
+                .map_err(|e| Error::database(format!("Failed to roll back {handler} from block {{}}: {{}}", from_block, e)))?;
+        }}
+        Ok(())
+    }}
+}}
+"#,
+            address = self.contract_address,
+            chain = self.chain_id,
+            handler = self.handler_name,
+            rollbacks = rollbacks,
+            dispatch = if events.is_empty() {
+                "let _ = (data, address, topic0, log_index, block_number, block_timestamp);\n        Ok(false)".to_string()
+            } else {
+                format!(
+                    "let data = hex::decode(data)\n            .map_err(|e| Error::invalid_data(format!(\"Invalid data in event {{}}: {{}}\", event.id(), e)))?;\n\n{}        Ok(false)",
+                    arms
+                )
+            },
+        )
+    }
+
+    /// Decoding and insertion of `event` when topic 0 matches it
+    fn match_arm(&self, tables: &EventTableGenerator, abi: &EthereumAbi, event: &AbiEvent) -> String {
+        let struct_name = format!("{}Event", event.name.to_case(Case::Pascal));
+        let mut columns = vec![
+            "transaction_hash".to_string(),
+            "contract_address".to_string(),
+            "block_number".to_string(),
+            "block_timestamp".to_string(),
+            "log_index".to_string(),
+        ];
+        let mut binds = vec![
+            "event.tx_hash()".to_string(),
+            "address.as_str()".to_string(),
+            "block_number".to_string(),
+            "block_timestamp".to_string(),
+            "log_index".to_string(),
+        ];
+        let mut placeholders: Vec<String> = (1..=columns.len()).map(|i| format!("${}", i)).collect();
+
+        for (i, param) in event.inputs.iter().enumerate() {
+            columns.push(column_name(&param.name, i));
+            let (bind, cast) = bind_expression(param, &format!("decoded.{}", field_name(&param.name, i)));
+            binds.push(bind);
+            placeholders.push(format!("${}{}", placeholders.len() + 1, cast));
+        }
+
+        let binds: String = binds.iter().map(|bind| format!("\n                .bind({})", bind)).collect();
+        format!(
+            r#"        if *topic0 == {struct_name}::SIGNATURE_HASH {{
+            let decoded = {struct_name}::decode_log(&topics, &data)?;
+            sqlx::query(
+                "INSERT INTO {table} ({columns}) VALUES ({placeholders}) ON CONFLICT (transaction_hash, log_index) DO UPDATE SET block_number = EXCLUDED.block_number, block_timestamp = EXCLUDED.block_timestamp, removed = FALSE",
+            ){binds}
+                .execute(&self.pool)
+                .await
+                .map_err(|e| Error::database(format!("Failed to store {name} event {{}}: {{}}", event.id(), e)))?;
+            return Ok(true);
+        }}
+"#,
+            struct_name = struct_name,
+            table = tables.table_name(abi, event).replace('"', "\\\""),
+            columns = columns.join(", ").replace('"', "\\\""),
+            placeholders = placeholders.join(", "),
+            binds = binds,
+            name = event.name,
+        )
+    }
+}
+
+/// Value bound for a decoded parameter and the cast of its placeholder,
+/// following the column types of the event tables
+fn bind_expression(param: &AbiParameter, field: &str) -> (String, &'static str) {
+    let param_type = param.param_type.as_str();
+    if param.indexed && !is_value_type(param_type) {
+        // Topic hash of a reference type
+        return (format!("{}.to_vec()", field), "");
+    }
+    if param_type.contains('[') || param_type.starts_with("tuple") {
+        return (format!("serde_json::to_value(&{})?", field), "");
+    }
+    match param_type {
+        "bool" => (field.to_string(), ""),
+        "string" => (format!("&{}", field), ""),
+        "address" => (format!("{}.to_string()", field), ""),
+        _ if param_type.starts_with("uint") || param_type.starts_with("int") => {
+            (format!("{}.to_string()", field), "::NUMERIC")
+        }
+        _ => (format!("{}.to_vec()", field), ""),
+    }
+}
@@ -0,0 +1,234 @@
+//! PostgreSQL table generation for ABI events
+//!
+//! Generates one typed table per contract event, with a column per event
+//! parameter and an index per indexed parameter, as reversible migrations
+//! that sort after the storage crate's own migrations.
+
+use indexer_core::codegen::{self, index_name, table_name};
+
+use super::parser::{AbiEvent, AbiParameter, AbiParser, EthereumAbi};
+
+/// Columns every event table carries besides the event parameters
+const METADATA_COLUMNS: &[&str] = &[
+    "id",
+    "transaction_hash",
+    "contract_address",
+    "block_number",
+    "block_timestamp",
+    "log_index",
+    "transaction_index",
+    "removed",
+    "created_at",
+];
+
+/// Up and down SQL of a generated migration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventMigration {
+    /// SQL creating the event tables and their indexes
+    pub up: String,
+    /// SQL dropping the event tables again
+    pub down: String,
+}
+
+/// Generated event tables for an ABI
+pub struct EventTableGenerator {
+    /// Prefix of every table name, usually the lowercased contract name
+    table_prefix: String,
+}
+
+impl EventTableGenerator {
+    /// Create a generator naming tables `{table_prefix}_{event}_events`
+    pub fn new(table_prefix: impl Into<String>) -> Self {
+        Self {
+            table_prefix: table_prefix.into(),
+        }
+    }
+
+    /// Generate the up and down migration for every event in `abi`
+    pub fn generate(&self, abi: &EthereumAbi) -> EventMigration {
+        let mut up = String::new();
+        let mut down = String::new();
+
+        for event in &abi.events {
+            up.push_str(&self.create_table(abi, event));
+            up.push('\n');
+        }
+        for event in abi.events.iter().rev() {
+            down.push_str(&format!("DROP TABLE IF EXISTS {};\n", self.table_name(abi, event)));
+        }
+
+        EventMigration { up, down }
+    }
+
+    /// Table name for `event`, suffixed with the topic prefix when the
+    /// event name is overloaded
+    pub fn table_name(&self, abi: &EthereumAbi, event: &AbiEvent) -> String {
+        let overloaded = abi.events.iter().filter(|other| other.name == event.name).count() > 1;
+        let event_name = event.name.to_lowercase();
+        match event.signature.as_deref().filter(|_| overloaded) {
+            Some(topic) => {
+                let topic = topic.trim_start_matches("0x");
+                table_name(&format!("{}_{}_{}_events", self.table_prefix, event_name, &topic[..8.min(topic.len())]))
+            }
+            None => table_name(&format!("{}_{}_events", self.table_prefix, event_name)),
+        }
+    }
+
+    /// `CREATE TABLE` and `CREATE INDEX` statements for `event`
+    pub fn create_table(&self, abi: &EthereumAbi, event: &AbiEvent) -> String {
+        let table = self.table_name(abi, event);
+        let columns: Vec<(String, &AbiParameter)> = event
+            .inputs
+            .iter()
+            .enumerate()
+            .map(|(i, param)| (column_name(&param.name, i), param))
+            .collect();
+
+        let mut sql = format!(
+            r#"-- {signature}
+CREATE TABLE IF NOT EXISTS {table} (
+    id BIGSERIAL PRIMARY KEY,
+    transaction_hash TEXT NOT NULL,
+    contract_address TEXT NOT NULL CHECK (contract_address ~ '^0x[0-9a-fA-F]{{40}}$'),
+    block_number BIGINT NOT NULL,
+    block_timestamp TIMESTAMPTZ NOT NULL,
+    log_index INTEGER NOT NULL,
+    transaction_index INTEGER,
+"#,
+            signature = AbiParser::canonical_signature(&event.name, &event.inputs),
+            table = table,
+        );
+        for (column, param) in &columns {
+            sql.push_str(&format!("    {},\n", column_definition(column, param)));
+        }
+        sql.push_str(
+            r#"    removed BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (transaction_hash, log_index)
+);
+
+"#,
+        );
+
+        let indexed = columns.iter().filter(|(_, param)| param.indexed).map(|(column, _)| column.as_str());
+        for column in ["contract_address", "block_number"].into_iter().chain(indexed) {
+            sql.push_str(&format!(
+                "CREATE INDEX IF NOT EXISTS {} ON {} ({});\n",
+                index_name(&table, column),
+                table,
+                column
+            ));
+        }
+
+        sql
+    }
+}
+
+/// Column definition for an event parameter
+fn column_definition(column: &str, param: &AbiParameter) -> String {
+    let (sql_type, check) = column_type(column, param);
+    match check {
+        Some(check) => format!("{} {} NOT NULL CHECK ({})", column, sql_type, check),
+        None => format!("{} {} NOT NULL", column, sql_type),
+    }
+}
+
+/// SQL type of the column for an event parameter and its check, if any
+///
+/// Indexed parameters of reference type only keep the keccak256 hash of
+/// their value in the topic, so they are stored as 32 raw bytes.
+pub(crate) fn column_type(column: &str, param: &AbiParameter) -> (&'static str, Option<String>) {
+    let param_type = param.param_type.as_str();
+    if param.indexed && !is_value_type(param_type) {
+        ("BYTEA", Some(format!("octet_length({}) = 32", column)))
+    } else if param_type.contains('[') || param_type.starts_with("tuple") {
+        ("JSONB", None)
+    } else {
+        match param_type {
+            "bool" => ("BOOLEAN", None),
+            "string" => ("TEXT", None),
+            "bytes" => ("BYTEA", None),
+            "address" => ("TEXT", Some(format!("{} ~ '^0x[0-9a-fA-F]{{40}}$'", column))),
+            "function" => ("BYTEA", Some(format!("octet_length({}) = 24", column))),
+            _ if param_type.starts_with("uint") => ("NUMERIC(78, 0)", Some(format!("{} >= 0", column))),
+            _ if param_type.starts_with("int") => ("NUMERIC(78, 0)", None),
+            _ if param_type.starts_with("bytes") => {
+                ("BYTEA", Some(format!("octet_length({}) = {}", column, &param_type[5..])))
+            }
+            _ => ("JSONB", None),
+        }
+    }
+}
+
+/// Whether an indexed parameter of this type is stored in its topic as is
+/// rather than hashed
+pub(crate) fn is_value_type(param_type: &str) -> bool {
+    !param_type.contains('[') && !matches!(param_type, "string" | "bytes") && !param_type.starts_with("tuple")
+}
+
+/// Column name for a parameter, using `arg{index}` for unnamed parameters
+/// and a `param_` prefix for names taken by the metadata columns
+pub(crate) fn column_name(name: &str, index: usize) -> String {
+    codegen::column_name(name, index, METADATA_COLUMNS, "param_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn param(name: &str, param_type: &str, indexed: bool) -> AbiParameter {
+        AbiParameter {
+            name: name.to_string(),
+            param_type: param_type.to_string(),
+            internal_type: None,
+            components: None,
+            indexed,
+        }
+    }
+
+    #[test]
+    fn test_column_definition() {
+        assert_eq!(
+            column_definition("value", &param("value", "uint256", false)),
+            "value NUMERIC(78, 0) NOT NULL CHECK (value >= 0)"
+        );
+        assert_eq!(
+            column_definition("\"from\"", &param("from", "address", true)),
+            "\"from\" TEXT NOT NULL CHECK (\"from\" ~ '^0x[0-9a-fA-F]{40}$')"
+        );
+        assert_eq!(
+            column_definition("salt", &param("salt", "bytes32", false)),
+            "salt BYTEA NOT NULL CHECK (octet_length(salt) = 32)"
+        );
+        assert_eq!(
+            column_definition("name", &param("name", "string", true)),
+            "name BYTEA NOT NULL CHECK (octet_length(name) = 32)"
+        );
+        assert_eq!(column_definition("ids", &param("ids", "uint256[]", false)), "ids JSONB NOT NULL");
+    }
+
+    #[test]
+    fn test_column_name() {
+        assert_eq!(column_name("", 1), "arg1");
+        assert_eq!(column_name("_tokenId", 0), "token_id");
+        assert_eq!(column_name("from", 0), "\"from\"");
+        assert_eq!(column_name("blockNumber", 0), "param_block_number");
+    }
+
+    #[test]
+    fn test_long_table_name() {
+        let abi = AbiParser::new()
+            .parse_content(
+                r#"[
+                  {"type": "event", "name": "Transfer", "inputs": [], "anonymous": false},
+                  {"type": "event", "name": "TransferBatch", "inputs": [], "anonymous": false}
+                ]"#,
+            )
+            .unwrap();
+        let tables = EventTableGenerator::new("a".repeat(60));
+        let transfer = tables.table_name(&abi, &abi.events[0]);
+        let batch = tables.table_name(&abi, &abi.events[1]);
+        assert_eq!(transfer.len(), codegen::MAX_IDENTIFIER_LEN);
+        assert_ne!(transfer, batch);
+    }
+}
@@ -5,6 +5,10 @@
 
 pub mod parser;
 pub mod generator;
+pub mod structs;
+pub mod migrations;
+pub mod handlers;
+pub mod typescript;
 pub mod templates;
 pub mod cli;
 
@@ -13,8 +17,15 @@ mod tests;
 
 pub use parser::AbiParser;
 pub use generator::EthereumContractCodegen;
+pub use templates::EthereumTemplateManager;
 
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use convert_case::{Case, Casing};
+use indexer_core::codegen::{check_drift, write_reference_templates, Drift, GeneratedFile, Provenance};
 use indexer_core::Result;
+use parser::EthereumAbi;
 
 /// Configuration for ethereum contract code generation
 #[derive(Debug, Clone)]
@@ -31,6 +42,11 @@ pub struct EthereumCodegenConfig {
     pub features: Vec<String>,
     /// Whether this is a dry run
     pub dry_run: bool,
+    /// Directory of Handlebars templates overriding or adding generated files
+    pub templates_dir: Option<String>,
+    /// Version prefix of the generated migration, the current time past
+    /// the existing migrations when unset
+    pub migration_version: Option<String>,
 }
 
 impl Default for EthereumCodegenConfig {
@@ -45,22 +61,237 @@ impl Default for EthereumCodegenConfig {
                 "storage".to_string(),
                 "api".to_string(),
                 "migrations".to_string(),
+                "handlers".to_string(),
             ],
             dry_run: false,
+            templates_dir: None,
+            migration_version: None,
         }
     }
 }
 
 /// Main entry point for ethereum contract code generation
+///
+/// `abi_file_path` is a bare ABI, a Foundry or Hardhat artifact, or a
+/// directory of artifacts generated with [`generate_artifacts_code`].
+/// Generated names follow the artifact's contract name unless a namespace
+/// is configured.
 pub async fn generate_contract_code(
     abi_file_path: &str,
     config: EthereumCodegenConfig,
 ) -> Result<()> {
+    if Path::new(abi_file_path).is_dir() {
+        return generate_artifacts_code(abi_file_path, config).await;
+    }
+    generate_contract(abi_file_path, &[], config, false).await?;
+    Ok(())
+}
+
+/// Generate code for a proxy from its ABI and the ABIs of its
+/// implementations, oldest first
+///
+/// The implementations' functions, events and errors are merged into the
+/// proxy's, so the generated client calls them through the proxy and the
+/// generated handler decodes logs emitted under any of them. Where an
+/// upgrade changed the signature of an item, the newest implementation's
+/// is generated; logs of older signatures can be decoded at runtime with
+/// [`crate::proxy::ProxyResolver`].
+pub async fn generate_proxy_contract_code(
+    abi_file_path: &str,
+    implementation_abi_paths: &[String],
+    config: EthereumCodegenConfig,
+) -> Result<()> {
+    generate_contract(abi_file_path, implementation_abi_paths, config, false).await?;
+    Ok(())
+}
+
+/// Generate code for every contract in a Foundry `out` or Hardhat
+/// `artifacts` directory
+///
+/// Each contract is generated into a module named after it, with a `mod.rs`
+/// declaring its generated modules, and a `mod.rs` declaring the contract
+/// modules is written to the output directory. Build info, Hardhat debug
+/// files and artifacts with an empty ABI are skipped. All contracts share
+/// the configured address, which is usually left empty here.
+pub async fn generate_artifacts_code(
+    artifacts_dir: &str,
+    config: EthereumCodegenConfig,
+) -> Result<()> {
+    generate_artifacts(artifacts_dir, config, false).await?;
+    Ok(())
+}
+
+/// Regenerate code in memory and compare it with the files in the output
+/// directory, for any input [`generate_contract_code`] and
+/// [`generate_proxy_contract_code`] accept
+///
+/// Returns the generated files that are missing or differ on disk and the
+/// generated files on disk that are no longer generated, so stale bindings
+/// can be caught without writing anything.
+pub async fn check_contract_code(
+    abi_file_path: &str,
+    implementation_abi_paths: &[String],
+    config: EthereumCodegenConfig,
+) -> Result<Vec<Drift>> {
+    let files = if Path::new(abi_file_path).is_dir() {
+        generate_artifacts(abi_file_path, config, true).await?
+    } else {
+        generate_contract(abi_file_path, implementation_abi_paths, config, true).await?
+    };
+    check_drift(&files)
+}
+
+/// Write the files generated for a contract, and the implementations behind
+/// it for a proxy, as reference templates below `templates_dir`
+///
+/// Each template renders the built-in output of its file unchanged, so
+/// copies edited in a templates directory override the generated files, see
+/// [`EthereumTemplateManager::with_templates_dir`]. Templates configured in
+/// `config` are not applied. Returns the paths of the templates.
+pub async fn dump_contract_templates(
+    abi_file_path: &str,
+    implementation_abi_paths: &[String],
+    mut config: EthereumCodegenConfig,
+    templates_dir: &str,
+) -> Result<Vec<PathBuf>> {
+    if Path::new(abi_file_path).is_dir() {
+        return Err(indexer_core::Error::Config(
+            "Reference templates are written for a single contract, not a directory of artifacts".to_string()
+        ));
+    }
+    config.templates_dir = None;
+    let output_dir = PathBuf::from(&config.output_dir);
+    let files = generate_contract(abi_file_path, implementation_abi_paths, config, true).await?;
+    write_reference_templates(&files, &output_dir, Path::new(templates_dir))
+}
+
+/// Generate code for a contract, and the implementations behind it for a
+/// proxy, returning the files when generated `in_memory`
+async fn generate_contract(
+    abi_file_path: &str,
+    implementation_abi_paths: &[String],
+    mut config: EthereumCodegenConfig,
+    in_memory: bool,
+) -> Result<Vec<GeneratedFile>> {
     let parser = AbiParser::new();
-    let abi = parser.parse_file(abi_file_path)?;
-    
-    let codegen = EthereumContractCodegen::new(config);
+    let mut abi = parser.parse_file(abi_file_path)?;
+    for path in implementation_abi_paths.iter().rev() {
+        let implementation = parser.parse_file(path)?;
+        if config.namespace.is_none() {
+            config.namespace = implementation.contract_name.clone();
+        }
+        for signature in abi.merge_implementation(implementation) {
+            println!("Warning: Skipping {} from {}, a newer implementation changed its signature", signature, path);
+        }
+    }
+    if config.namespace.is_none() {
+        config.namespace = abi.contract_name.clone();
+    }
+
+    let mut inputs = vec![abi_file_path];
+    inputs.extend(implementation_abi_paths.iter().map(String::as_str));
+    let codegen = contract_codegen(config, Some(Provenance::from_files(&inputs)?), in_memory)?;
     codegen.generate_all(&abi).await?;
-    
-    Ok(())
-} 
\ No newline at end of file
+
+    Ok(codegen.take_files())
+}
+
+/// Generate code for every contract in a directory of artifacts, returning
+/// the files when generated `in_memory`
+async fn generate_artifacts(
+    artifacts_dir: &str,
+    config: EthereumCodegenConfig,
+    in_memory: bool,
+) -> Result<Vec<GeneratedFile>> {
+    let parser = AbiParser::new();
+    let mut contracts: BTreeMap<String, (PathBuf, EthereumAbi)> = BTreeMap::new();
+
+    for path in artifact_files(Path::new(artifacts_dir))? {
+        let content: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+        if !matches!(content.get("abi"), Some(serde_json::Value::Array(abi)) if !abi.is_empty()) {
+            continue;
+        }
+
+        let abi = parser.parse_file(&path.to_string_lossy())?;
+        let contract_name = abi.contract_name.clone()
+            .ok_or_else(|| indexer_core::Error::Config(format!("No contract name for artifact {}", path.display())))?;
+        let module = contract_name.to_case(Case::Snake);
+        if let Some((other, _)) = contracts.get(&module) {
+            return Err(indexer_core::Error::Config(format!(
+                "Artifacts {} and {} both generate module {}", other.display(), path.display(), module
+            )));
+        }
+        contracts.insert(module, (path, abi));
+    }
+
+    // Only these features generate a `mod.rs`, migrations are SQL files,
+    // TypeScript is not a module and the API files are included individually
+    let features: Vec<String> = config.features.iter()
+        .filter(|feature| matches!(feature.as_str(), "client" | "storage" | "handlers"))
+        .cloned()
+        .collect();
+    let mut files = Vec::new();
+    for (module, (path, abi)) in &contracts {
+        let contract_config = EthereumCodegenConfig {
+            output_dir: Path::new(&config.output_dir).join(module).to_string_lossy().into_owned(),
+            namespace: abi.contract_name.clone(),
+            ..config.clone()
+        };
+        let provenance = Provenance::from_files(&[path.to_string_lossy().as_ref()])?;
+        let codegen = contract_codegen(contract_config, Some(provenance), in_memory)?;
+        codegen.generate_all(abi).await?;
+        // Relative to the artifacts so the file doesn't depend on where they are
+        let artifact = path.strip_prefix(artifacts_dir).unwrap_or(path);
+        codegen.generate_module_file(&format!("Generated code for contract artifact: {}", artifact.display()), &features).await?;
+        files.extend(codegen.take_files());
+    }
+
+    let modules: Vec<String> = contracts.keys().cloned().collect();
+    let codegen = contract_codegen(config, None, in_memory)?;
+    codegen.generate_module_file(&format!("Generated contracts from artifacts: {}", artifacts_dir), &modules).await?;
+    files.extend(codegen.take_files());
+
+    Ok(files)
+}
+
+/// Code generator recording `provenance`, generating in memory or to disk
+/// with the configured templates
+fn contract_codegen(
+    config: EthereumCodegenConfig,
+    provenance: Option<Provenance>,
+    in_memory: bool,
+) -> Result<EthereumContractCodegen> {
+    let templates = match &config.templates_dir {
+        Some(dir) => Some(EthereumTemplateManager::new()?.with_templates_dir(dir)?),
+        None => None,
+    };
+    let mut codegen = EthereumContractCodegen::new(config);
+    if let Some(templates) = templates {
+        codegen = codegen.with_templates(templates);
+    }
+    if let Some(provenance) = provenance {
+        codegen = codegen.with_provenance(provenance);
+    }
+    if in_memory {
+        codegen = codegen.in_memory();
+    }
+    Ok(codegen)
+}
+
+/// JSON artifact files below `dir`, in path order
+fn artifact_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let file_name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+        if path.is_dir() {
+            if file_name != "build-info" {
+                files.extend(artifact_files(&path)?);
+            }
+        } else if file_name.ends_with(".json") && !file_name.ends_with(".dbg.json") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
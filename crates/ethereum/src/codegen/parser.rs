@@ -1,6 +1,11 @@
 //! Ethereum ABI parser
 //! 
 //! Parses Ethereum contract ABI JSON files to extract contract interface definitions.
+//! Besides bare ABI arrays, Foundry (`out/*.json`), Hardhat and solc artifacts
+//! are accepted, adding the contract name, creation bytecode and NatSpec.
+
+use std::collections::HashMap;
+use std::path::Path;
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -19,6 +24,47 @@ pub struct EthereumAbi {
     pub errors: Vec<AbiError>,
     /// Raw ABI for reference
     pub raw_abi: Value,
+    /// Contract name, when parsed from an artifact
+    #[serde(default)]
+    pub contract_name: Option<String>,
+    /// `0x`-prefixed creation bytecode, when parsed from an artifact of a
+    /// deployable contract
+    #[serde(default)]
+    pub bytecode: Option<String>,
+    /// NatSpec documentation, when parsed from an artifact
+    #[serde(default)]
+    pub docs: ContractDocs,
+}
+
+/// NatSpec documentation of a contract, merged from solc's `userdoc` and
+/// `devdoc` output
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContractDocs {
+    /// `@title` of the contract
+    pub title: Option<String>,
+    /// `@author` of the contract
+    pub author: Option<String>,
+    /// `@notice` and `@dev` of the contract
+    pub contract: DocEntry,
+    /// Function docs by canonical signature, and the constructor's under `constructor`
+    pub methods: HashMap<String, DocEntry>,
+    /// Event docs by canonical signature
+    pub events: HashMap<String, DocEntry>,
+    /// Error docs by canonical signature
+    pub errors: HashMap<String, DocEntry>,
+}
+
+/// NatSpec documentation of a contract item
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DocEntry {
+    /// `@notice`, for end users
+    pub notice: Option<String>,
+    /// `@dev`, for developers
+    pub details: Option<String>,
+    /// `@param` descriptions by parameter name
+    pub params: HashMap<String, String>,
+    /// `@return` descriptions by output name, or `_{index}` for unnamed outputs
+    pub returns: HashMap<String, String>,
 }
 
 /// ABI function definition
@@ -62,6 +108,8 @@ pub struct AbiError {
     pub name: String,
     /// Error inputs
     pub inputs: Vec<AbiParameter>,
+    /// Error signature (4-byte selector)
+    pub signature: Option<String>,
 }
 
 /// ABI parameter definition
@@ -79,6 +127,64 @@ pub struct AbiParameter {
     pub indexed: bool,
 }
 
+impl DocEntry {
+    /// Whether no NatSpec was given for the item
+    pub fn is_empty(&self) -> bool {
+        self.notice.is_none() && self.details.is_none() && self.params.is_empty() && self.returns.is_empty()
+    }
+
+    /// Rust doc comment for the item, listing `inputs` and `outputs` in ABI
+    /// order, or an empty string without NatSpec
+    pub fn doc_comment(&self, inputs: &[AbiParameter], outputs: &[AbiParameter], indent: &str) -> String {
+        let mut sections = Vec::new();
+        sections.extend(self.notice.clone());
+        sections.extend(self.details.clone());
+
+        let params: Vec<String> = inputs.iter()
+            .filter_map(|input| Some(format!("- `{}`: {}", input.name, self.params.get(&input.name)?)))
+            .collect();
+        if !params.is_empty() {
+            sections.push(format!("# Parameters\n\n{}", params.join("\n")));
+        }
+        let returns: Vec<String> = outputs.iter()
+            .enumerate()
+            .filter_map(|(i, output)| {
+                let name = if output.name.is_empty() { format!("_{}", i) } else { output.name.clone() };
+                let description = self.returns.get(&name)?;
+                Some(if output.name.is_empty() { format!("- {}", description) } else { format!("- `{}`: {}", name, description) })
+            })
+            .collect();
+        if !returns.is_empty() {
+            sections.push(format!("# Returns\n\n{}", returns.join("\n")));
+        }
+
+        sections.join("\n\n")
+            .lines()
+            .map(|line| format!("{}/// {}", indent, line.trim()).trim_end().to_string() + "\n")
+            .collect()
+    }
+}
+
+impl ContractDocs {
+    /// NatSpec of `function`, or of the constructor
+    pub fn function(&self, function: &AbiFunction) -> Option<&DocEntry> {
+        if function.function_type == "constructor" {
+            return self.methods.get("constructor");
+        }
+        self.methods.get(&AbiParser::canonical_signature(&function.name, &function.inputs))
+    }
+
+    /// NatSpec of `event`
+    pub fn event(&self, event: &AbiEvent) -> Option<&DocEntry> {
+        self.events.get(&AbiParser::canonical_signature(&event.name, &event.inputs))
+    }
+
+    /// NatSpec of `error`
+    pub fn error(&self, error: &AbiError) -> Option<&DocEntry> {
+        self.errors.get(&AbiParser::canonical_signature(&error.name, &error.inputs))
+    }
+}
+
 /// Ethereum ABI parser
 pub struct AbiParser;
 
@@ -88,16 +194,134 @@ impl AbiParser {
         Self
     }
 
-    /// Parse an Ethereum ABI file
+    /// Parse an Ethereum ABI or artifact file
+    ///
+    /// Artifacts without a contract name, such as Foundry's, are named after
+    /// the file, as in `out/Token.sol/Token.json`.
     pub fn parse_file(&self, file_path: &str) -> Result<EthereumAbi> {
         let content = std::fs::read_to_string(file_path)?;
-        self.parse_content(&content)
+        let mut abi = self.parse_content(&content)?;
+        if abi.contract_name.is_none() && content.trim_start().starts_with('{') {
+            abi.contract_name = Path::new(file_path)
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .map(String::from);
+        }
+        Ok(abi)
     }
 
-    /// Parse Ethereum ABI from JSON content
+    /// Parse Ethereum ABI from JSON content, either a bare ABI array or an
+    /// artifact object with an `abi` field
     pub fn parse_content(&self, content: &str) -> Result<EthereumAbi> {
         let value: Value = serde_json::from_str(content)?;
-        self.parse_abi(&value)
+        match value.get("abi") {
+            Some(abi) if value.is_object() => {
+                let mut parsed = self.parse_abi(abi)?;
+                parsed.contract_name = Self::artifact_contract_name(&value);
+                parsed.bytecode = Self::artifact_bytecode(&value);
+                parsed.docs = Self::artifact_docs(&value);
+                Ok(parsed)
+            }
+            _ => self.parse_abi(&value),
+        }
+    }
+
+    /// Contract name of a Hardhat artifact, or the compilation target of a
+    /// Foundry artifact's metadata
+    fn artifact_contract_name(artifact: &Value) -> Option<String> {
+        if let Some(name) = artifact.get("contractName").and_then(|v| v.as_str()) {
+            return Some(name.to_string());
+        }
+        Self::artifact_metadata(artifact)?
+            .pointer("/settings/compilationTarget")?
+            .as_object()?
+            .values()
+            .next()?
+            .as_str()
+            .map(String::from)
+    }
+
+    /// Creation bytecode of an artifact
+    ///
+    /// Hardhat stores it as a string, Foundry as `bytecode.object` and solc
+    /// as `evm.bytecode.object`. Interfaces and abstract contracts have none.
+    fn artifact_bytecode(artifact: &Value) -> Option<String> {
+        let bytecode = artifact.get("bytecode")
+            .or_else(|| artifact.pointer("/evm/bytecode"))?;
+        let bytecode = bytecode.as_str()
+            .or_else(|| bytecode.get("object")?.as_str())?
+            .trim();
+        let bytecode = bytecode.strip_prefix("0x").unwrap_or(bytecode);
+        if bytecode.is_empty() {
+            None
+        } else {
+            Some(format!("0x{}", bytecode))
+        }
+    }
+
+    /// Compiler metadata of an artifact, which Foundry stores as an object
+    /// and solc as a JSON string
+    fn artifact_metadata(artifact: &Value) -> Option<Value> {
+        ["metadata", "rawMetadata"].iter()
+            .filter_map(|key| artifact.get(*key))
+            .find_map(|metadata| match metadata {
+                Value::String(raw) => serde_json::from_str(raw).ok(),
+                Value::Object(_) => Some(metadata.clone()),
+                _ => None,
+            })
+    }
+
+    /// NatSpec of an artifact, from its top-level `userdoc` and `devdoc` or
+    /// else from its metadata output
+    fn artifact_docs(artifact: &Value) -> ContractDocs {
+        let metadata = Self::artifact_metadata(artifact);
+        let doc = |kind: &str| {
+            artifact.get(kind)
+                .or_else(|| metadata.as_ref()?.pointer(&format!("/output/{}", kind)))
+                .cloned()
+                .unwrap_or(Value::Null)
+        };
+        let (userdoc, devdoc) = (doc("userdoc"), doc("devdoc"));
+
+        let text = |value: &Value, key: &str| value.get(key).and_then(|v| v.as_str()).map(String::from);
+        let mut docs = ContractDocs {
+            title: text(&devdoc, "title"),
+            author: text(&devdoc, "author"),
+            ..Default::default()
+        };
+        Self::merge_doc_entry(&mut docs.contract, &userdoc);
+        Self::merge_doc_entry(&mut docs.contract, &devdoc);
+
+        for (key, entries) in [("methods", &mut docs.methods), ("events", &mut docs.events), ("errors", &mut docs.errors)] {
+            for doc in [&userdoc, &devdoc] {
+                for (signature, value) in doc.get(key).and_then(|v| v.as_object()).into_iter().flatten() {
+                    let entry = entries.entry(signature.clone()).or_default();
+                    // Errors are documented per definition, as an array
+                    match value.as_array() {
+                        Some(definitions) => definitions.iter().for_each(|value| Self::merge_doc_entry(entry, value)),
+                        None => Self::merge_doc_entry(entry, value),
+                    }
+                }
+            }
+        }
+        docs
+    }
+
+    /// Add the NatSpec fields present in `value` to `entry`
+    fn merge_doc_entry(entry: &mut DocEntry, value: &Value) {
+        let text = |key: &str| value.get(key).and_then(|v| v.as_str()).map(String::from);
+        entry.notice = entry.notice.take().or_else(|| text("notice"));
+        entry.details = entry.details.take().or_else(|| text("details"));
+        let descriptions = |key: &str| {
+            value.get(key)
+                .and_then(|v| v.as_object())
+                .into_iter()
+                .flatten()
+                .filter_map(|(name, description)| Some((name.clone(), description.as_str()?.to_string())))
+                .collect::<Vec<_>>()
+        };
+        entry.params.extend(descriptions("params"));
+        entry.returns.extend(descriptions("returns"));
     }
 
     /// Parse ABI from JSON value
@@ -143,6 +367,9 @@ impl AbiParser {
             events,
             errors,
             raw_abi: value.clone(),
+            contract_name: None,
+            bytecode: None,
+            docs: ContractDocs::default(),
         })
     }
 
@@ -241,7 +468,9 @@ impl AbiParser {
             .transpose()?
             .unwrap_or_default();
 
-        Ok(AbiError { name, inputs })
+        let signature = Some(self.generate_function_signature(&name, &inputs));
+
+        Ok(AbiError { name, inputs, signature })
     }
 
     /// Parse function/error parameters
@@ -335,11 +564,7 @@ impl AbiParser {
 
     /// Generate function signature (4-byte selector)
     fn generate_function_signature(&self, name: &str, inputs: &[AbiParameter]) -> String {
-        let types: Vec<String> = inputs.iter()
-            .map(|param| Self::canonical_type(&param.param_type))
-            .collect();
-        
-        let signature_string = format!("{}({})", name, types.join(","));
+        let signature_string = Self::canonical_signature(name, inputs);
         
         // Use keccak256 to generate the selector
         use sha3::{Digest, Keccak256};
@@ -351,11 +576,7 @@ impl AbiParser {
 
     /// Generate event signature hash
     fn generate_event_signature(&self, name: &str, inputs: &[AbiParameter]) -> String {
-        let types: Vec<String> = inputs.iter()
-            .map(|param| Self::canonical_type(&param.param_type))
-            .collect();
-        
-        let signature_string = format!("{}({})", name, types.join(","));
+        let signature_string = Self::canonical_signature(name, inputs);
         
         // Use keccak256 to generate the event topic
         use sha3::{Digest, Keccak256};
@@ -365,27 +586,28 @@ impl AbiParser {
         format!("0x{}", hex::encode(hash))
     }
 
-    /// Convert parameter type to canonical form
-    fn canonical_type(param_type: &str) -> String {
-        // Handle array types
-        if let Some(base_type) = param_type.strip_suffix("[]") {
-            return format!("{}[]", Self::canonical_type(base_type));
-        }
-
-        // Handle fixed-size arrays
-        if let Some(bracket_pos) = param_type.rfind('[') {
-            let base_type = &param_type[..bracket_pos];
-            let array_part = &param_type[bracket_pos..];
-            return format!("{}{}", Self::canonical_type(base_type), array_part);
-        }
+    /// Canonical signature used for selectors and topics, e.g. `Transfer(address,address,uint256)`
+    pub fn canonical_signature(name: &str, inputs: &[AbiParameter]) -> String {
+        let types: Vec<String> = inputs.iter().map(Self::canonical_type).collect();
+        format!("{}({})", name, types.join(","))
+    }
 
-        // Handle tuple types
-        if param_type.starts_with("tuple") {
-            return param_type.to_string(); // Tuples need special handling in real implementation
+    /// Convert parameter type to canonical form
+    ///
+    /// Tuples are expanded to their component types, keeping any array suffix
+    /// (`tuple[2][]` becomes `(address,uint256)[2][]`).
+    pub fn canonical_type(param: &AbiParameter) -> String {
+        match param.param_type.strip_prefix("tuple") {
+            Some(array_suffix) => {
+                let components: Vec<String> = param.components.iter()
+                    .flatten()
+                    .map(Self::canonical_type)
+                    .collect();
+                format!("({}){}", components.join(","), array_suffix)
+            }
+            // Basic types and arrays of them are already canonical (uint256, address[], bytes32[4], etc.)
+            None => param.param_type.clone(),
         }
-
-        // Basic types - return as-is (uint256, address, bool, etc.)
-        param_type.to_string()
     }
 
     /// Get human-readable function signature
@@ -474,6 +696,75 @@ impl EthereumAbi {
         types.into_iter().collect()
     }
 
+    /// Add the functions, events and errors of an implementation behind this
+    /// proxy ABI, so generated code covers calls and logs made through the
+    /// proxy
+    ///
+    /// Items already present by signature are kept as they are. Items named
+    /// like an existing item with another signature are skipped, since their
+    /// generated names would clash, and their signatures returned.
+    pub fn merge_implementation(&mut self, implementation: EthereumAbi) -> Vec<String> {
+        let mut skipped = Vec::new();
+
+        let functions: Vec<(String, String)> = self.functions.iter()
+            .map(|f| (f.name.clone(), AbiParser::canonical_signature(&f.name, &f.inputs)))
+            .collect();
+        for function in implementation.functions {
+            let signature = AbiParser::canonical_signature(&function.name, &function.inputs);
+            if functions.iter().any(|(_, existing)| *existing == signature) {
+                continue;
+            }
+            if functions.iter().any(|(name, _)| *name == function.name) {
+                skipped.push(signature);
+                continue;
+            }
+            self.functions.push(function);
+        }
+
+        let events: Vec<(String, String)> = self.events.iter()
+            .map(|e| (e.name.clone(), AbiParser::canonical_signature(&e.name, &e.inputs)))
+            .collect();
+        for event in implementation.events {
+            let signature = AbiParser::canonical_signature(&event.name, &event.inputs);
+            if events.iter().any(|(_, existing)| *existing == signature) {
+                continue;
+            }
+            if events.iter().any(|(name, _)| *name == event.name) {
+                skipped.push(signature);
+                continue;
+            }
+            self.events.push(event);
+        }
+
+        let errors: Vec<(String, String)> = self.errors.iter()
+            .map(|e| (e.name.clone(), AbiParser::canonical_signature(&e.name, &e.inputs)))
+            .collect();
+        for error in implementation.errors {
+            let signature = AbiParser::canonical_signature(&error.name, &error.inputs);
+            if errors.iter().any(|(_, existing)| *existing == signature) {
+                continue;
+            }
+            if errors.iter().any(|(name, _)| *name == error.name) {
+                skipped.push(signature);
+                continue;
+            }
+            self.errors.push(error);
+        }
+
+        // The proxy's constructor is its own
+        for (signature, entry) in implementation.docs.methods.into_iter().filter(|(signature, _)| signature != "constructor") {
+            self.docs.methods.entry(signature).or_insert(entry);
+        }
+        for (signature, entry) in implementation.docs.events {
+            self.docs.events.entry(signature).or_insert(entry);
+        }
+        for (signature, entry) in implementation.docs.errors {
+            self.docs.errors.entry(signature).or_insert(entry);
+        }
+
+        skipped
+    }
+
     /// Helper method to collect types from a parameter recursively
     fn collect_types_from_parameter(param: &AbiParameter, types: &mut std::collections::HashSet<String>) {
         // Add the parameter type
@@ -0,0 +1,759 @@
+//! Rust type generation for ABI events, errors and tuples
+//!
+//! Generates one struct per event, error and tuple/struct parameter of a
+//! contract ABI, together with the ABI decoding needed to build them from
+//! log topics, log data and revert data, and the ABI encoding needed to pass
+//! them as constructor arguments.
+
+use std::collections::HashMap;
+
+use convert_case::{Case, Casing};
+use indexer_core::Result;
+
+use super::parser::{AbiEvent, AbiError, AbiParameter, AbiParser, DocEntry, EthereumAbi};
+use super::migrations::is_value_type;
+
+/// ABI decoding support emitted at the top of the generated types module
+const ABI_DECODE_RUNTIME: &str = r#"/// Type that can be decoded from ABI-encoded data
+pub trait AbiDecode: Sized {
+    /// Whether the type is dynamically sized and encoded behind an offset
+    const DYNAMIC: bool;
+
+    /// Bytes the type occupies in the head of an enclosing tuple
+    const HEAD_SIZE: usize = 32;
+
+    /// Decode the value whose encoding starts at `offset` in `data`
+    fn decode_at(data: &[u8], offset: usize) -> std::result::Result<Self, ContractError>;
+}
+
+/// Read the 32-byte word at `offset`
+fn abi_word(data: &[u8], offset: usize) -> std::result::Result<&[u8], ContractError> {
+    offset.checked_add(32)
+        .and_then(|end| data.get(offset..end))
+        .ok_or_else(|| ContractError::AbiDecodingFailed(format!("Data too short for word at offset {}", offset)))
+}
+
+/// Read the word at `offset` as an offset or length bounded by the data size
+fn abi_usize(data: &[u8], offset: usize) -> std::result::Result<usize, ContractError> {
+    let word = abi_word(data, offset)?;
+    let value = U256::from_be_slice(word);
+    usize::try_from(value)
+        .ok()
+        .filter(|value| *value <= data.len())
+        .ok_or_else(|| ContractError::AbiDecodingFailed(format!("Offset or length {} out of range", value)))
+}
+
+/// Decode the next field of a tuple encoded at `base`, advancing `head`
+pub fn decode_tuple_field<T: AbiDecode>(data: &[u8], base: usize, head: &mut usize) -> std::result::Result<T, ContractError> {
+    let position = base + *head;
+    let value = if T::DYNAMIC {
+        *head += 32;
+        T::decode_at(data, base + abi_usize(data, position)?)?
+    } else {
+        *head += T::HEAD_SIZE;
+        T::decode_at(data, position)?
+    };
+    Ok(value)
+}
+
+/// Decode an indexed event parameter from its topic
+pub fn decode_topic<T: AbiDecode>(topic: Option<&B256>, name: &str) -> std::result::Result<T, ContractError> {
+    let topic = topic.ok_or_else(|| ContractError::EventParsingFailed(format!("Missing topic for {}", name)))?;
+    T::decode_at(topic.as_slice(), 0)
+}
+
+impl AbiDecode for bool {
+    const DYNAMIC: bool = false;
+
+    fn decode_at(data: &[u8], offset: usize) -> std::result::Result<Self, ContractError> {
+        match U256::from_be_slice(abi_word(data, offset)?) {
+            value if value == U256::ZERO => Ok(false),
+            value if value == U256::from(1) => Ok(true),
+            value => Err(ContractError::AbiDecodingFailed(format!("Invalid bool {}", value))),
+        }
+    }
+}
+
+impl AbiDecode for Address {
+    const DYNAMIC: bool = false;
+
+    fn decode_at(data: &[u8], offset: usize) -> std::result::Result<Self, ContractError> {
+        Ok(Address::from_slice(&abi_word(data, offset)?[12..]))
+    }
+}
+
+impl AbiDecode for U256 {
+    const DYNAMIC: bool = false;
+
+    fn decode_at(data: &[u8], offset: usize) -> std::result::Result<Self, ContractError> {
+        Ok(U256::from_be_slice(abi_word(data, offset)?))
+    }
+}
+
+impl AbiDecode for I256 {
+    const DYNAMIC: bool = false;
+
+    fn decode_at(data: &[u8], offset: usize) -> std::result::Result<Self, ContractError> {
+        Ok(I256::from_raw(U256::decode_at(data, offset)?))
+    }
+}
+
+impl<const N: usize> AbiDecode for FixedBytes<N> {
+    const DYNAMIC: bool = false;
+
+    fn decode_at(data: &[u8], offset: usize) -> std::result::Result<Self, ContractError> {
+        Ok(FixedBytes::from_slice(&abi_word(data, offset)?[..N]))
+    }
+}
+
+impl AbiDecode for Bytes {
+    const DYNAMIC: bool = true;
+
+    fn decode_at(data: &[u8], offset: usize) -> std::result::Result<Self, ContractError> {
+        let len = abi_usize(data, offset)?;
+        let start = offset + 32;
+        data.get(start..start + len)
+            .map(Bytes::copy_from_slice)
+            .ok_or_else(|| ContractError::AbiDecodingFailed(format!("Data too short for {} bytes at offset {}", len, start)))
+    }
+}
+
+impl AbiDecode for String {
+    const DYNAMIC: bool = true;
+
+    fn decode_at(data: &[u8], offset: usize) -> std::result::Result<Self, ContractError> {
+        String::from_utf8(Bytes::decode_at(data, offset)?.to_vec())
+            .map_err(|e| ContractError::AbiDecodingFailed(format!("Invalid UTF-8 string: {}", e)))
+    }
+}
+
+impl<T: AbiDecode> AbiDecode for Vec<T> {
+    const DYNAMIC: bool = true;
+
+    fn decode_at(data: &[u8], offset: usize) -> std::result::Result<Self, ContractError> {
+        let len = abi_usize(data, offset)?;
+        let base = offset + 32;
+        let mut head = 0;
+        (0..len).map(|_| decode_tuple_field(data, base, &mut head)).collect()
+    }
+}
+
+impl<T: AbiDecode, const N: usize> AbiDecode for [T; N] {
+    const DYNAMIC: bool = T::DYNAMIC;
+    const HEAD_SIZE: usize = if T::DYNAMIC { 32 } else { T::HEAD_SIZE * N };
+
+    fn decode_at(data: &[u8], offset: usize) -> std::result::Result<Self, ContractError> {
+        let mut head = 0;
+        let items = (0..N)
+            .map(|_| decode_tuple_field(data, offset, &mut head))
+            .collect::<std::result::Result<Vec<T>, _>>()?;
+        items.try_into()
+            .map_err(|_| ContractError::AbiDecodingFailed(format!("Expected {} array items", N)))
+    }
+}
+"#;
+
+/// ABI encoding support emitted after the decoding support
+const ABI_ENCODE_RUNTIME: &str = r#"
+/// Type that can be ABI-encoded
+pub trait AbiEncode: AbiDecode {
+    /// Encoding of the value: its head for static types, its tail for
+    /// dynamic types
+    fn encode(&self) -> Vec<u8>;
+}
+
+/// Fields of a tuple being ABI-encoded, in order
+#[derive(Debug, Default)]
+pub struct TupleEncoder {
+    /// Whether each field is dynamic, and its encoding
+    fields: Vec<(bool, Vec<u8>)>,
+}
+
+impl TupleEncoder {
+    /// Add the next field of the tuple
+    pub fn field<T: AbiEncode>(mut self, value: &T) -> Self {
+        self.fields.push((T::DYNAMIC, value.encode()));
+        self
+    }
+
+    /// Encoding of the tuple, with dynamic fields behind offsets into its tail
+    pub fn finish(self) -> Vec<u8> {
+        let head_size: usize = self.fields.iter()
+            .map(|(dynamic, encoded)| if *dynamic { 32 } else { encoded.len() })
+            .sum();
+        let mut head = Vec::with_capacity(head_size);
+        let mut tail = Vec::new();
+        for (dynamic, encoded) in self.fields {
+            if dynamic {
+                head.extend(U256::from(head_size + tail.len()).encode());
+                tail.extend(encoded);
+            } else {
+                head.extend(encoded);
+            }
+        }
+        head.extend(tail);
+        head
+    }
+}
+
+/// Left-aligned bytes padded with zeros to a whole number of words
+fn abi_padded(bytes: &[u8]) -> Vec<u8> {
+    let mut padded = bytes.to_vec();
+    padded.resize(bytes.len().div_ceil(32) * 32, 0);
+    padded
+}
+
+impl AbiEncode for bool {
+    fn encode(&self) -> Vec<u8> {
+        U256::from(u8::from(*self)).encode()
+    }
+}
+
+impl AbiEncode for Address {
+    fn encode(&self) -> Vec<u8> {
+        self.into_word().to_vec()
+    }
+}
+
+impl AbiEncode for U256 {
+    fn encode(&self) -> Vec<u8> {
+        self.to_be_bytes::<32>().to_vec()
+    }
+}
+
+impl AbiEncode for I256 {
+    fn encode(&self) -> Vec<u8> {
+        self.into_raw().encode()
+    }
+}
+
+impl<const N: usize> AbiEncode for FixedBytes<N> {
+    fn encode(&self) -> Vec<u8> {
+        abi_padded(self.as_slice())
+    }
+}
+
+impl AbiEncode for Bytes {
+    fn encode(&self) -> Vec<u8> {
+        let mut encoded = U256::from(self.len()).encode();
+        encoded.extend(abi_padded(self));
+        encoded
+    }
+}
+
+impl AbiEncode for String {
+    fn encode(&self) -> Vec<u8> {
+        Bytes::copy_from_slice(self.as_bytes()).encode()
+    }
+}
+
+impl<T: AbiEncode> AbiEncode for Vec<T> {
+    fn encode(&self) -> Vec<u8> {
+        let mut encoded = U256::from(self.len()).encode();
+        encoded.extend(self.iter().fold(TupleEncoder::default(), TupleEncoder::field).finish());
+        encoded
+    }
+}
+
+impl<T: AbiEncode, const N: usize> AbiEncode for [T; N] {
+    fn encode(&self) -> Vec<u8> {
+        self.iter().fold(TupleEncoder::default(), TupleEncoder::field).finish()
+    }
+}
+"#;
+
+/// Generated type definitions for an ABI
+pub struct AbiTypeGenerator {
+    /// Rust definition of every tuple struct, in generation order
+    structs: Vec<String>,
+    /// Canonical tuple type for every generated struct name
+    struct_types: HashMap<String, String>,
+}
+
+impl AbiTypeGenerator {
+    /// Create a generator with no structs
+    pub fn new() -> Self {
+        Self {
+            structs: Vec::new(),
+            struct_types: HashMap::new(),
+        }
+    }
+
+    /// Generate the ABI runtime and the structs for every event, error and tuple in `abi`
+    pub fn generate(mut self, abi: &EthereumAbi) -> Result<String> {
+        let definitions = self.collect(abi)?;
+
+        let mut code = String::from(ABI_DECODE_RUNTIME);
+        code.push_str(ABI_ENCODE_RUNTIME);
+        for definition in &self.structs {
+            code.push('\n');
+            code.push_str(definition);
+        }
+        for definition in definitions {
+            code.push('\n');
+            code.push_str(&definition);
+        }
+        Ok(code)
+    }
+
+    /// Rust types of the constructor inputs, with tuples named as
+    /// [`generate`](Self::generate) names their structs
+    pub fn constructor_types(mut self, abi: &EthereumAbi) -> Result<Vec<String>> {
+        self.collect(abi)?;
+        let Some(constructor) = &abi.constructor else {
+            return Ok(Vec::new());
+        };
+        let context = constructor.name.to_case(Case::Pascal);
+        constructor.inputs.iter().map(|param| self.rust_type(param, &context)).collect()
+    }
+
+    /// Generate the structs for every tuple in `abi`, returning the event
+    /// and error definitions
+    ///
+    /// Tuples are named in the order they are met, so every caller collects
+    /// the whole ABI before looking up a type.
+    fn collect(&mut self, abi: &EthereumAbi) -> Result<Vec<String>> {
+        let mut definitions = Vec::new();
+
+        for event in &abi.events {
+            definitions.push(self.generate_event(event, abi.docs.event(event))?);
+        }
+        for error in &abi.errors {
+            definitions.push(self.generate_error(error, abi.docs.error(error))?);
+        }
+
+        let functions = abi.functions.iter().chain(abi.constructor.as_ref());
+        for function in functions {
+            let context = function.name.to_case(Case::Pascal);
+            for param in function.inputs.iter().chain(&function.outputs) {
+                self.rust_type(param, &context)?;
+            }
+        }
+        Ok(definitions)
+    }
+
+    /// Generate the struct and `decode_log` for an event, documented with its NatSpec
+    fn generate_event(&mut self, event: &AbiEvent, docs: Option<&DocEntry>) -> Result<String> {
+        let struct_name = format!("{}Event", event.name.to_case(Case::Pascal));
+        let signature = AbiParser::canonical_signature(&event.name, &event.inputs);
+
+        let mut fields = String::new();
+        let mut decoders = String::new();
+        for (i, param) in event.inputs.iter().enumerate() {
+            let field = field_name(&param.name, i);
+            let (rust_type, decoder) = if param.indexed {
+                // Reference types are indexed by the keccak256 hash of their encoding
+                let rust_type = if is_value_type(&param.param_type) {
+                    self.rust_type(param, &struct_name)?
+                } else {
+                    "B256".to_string()
+                };
+                (rust_type, format!("decode_topic(topics.next(), \"{}\")?", field))
+            } else {
+                (self.rust_type(param, &struct_name)?, "decode_tuple_field(data, 0, &mut head)?".to_string())
+            };
+
+            let indexed = if param.indexed { " (indexed)" } else { "" };
+            fields.push_str(&format!(
+                "    /// `{} {}`{}{}\n    pub {}: {},\n",
+                param.param_type, param.name, indexed, param_docs(docs, param), field, rust_type
+            ));
+            decoders.push_str(&format!("            {}: {},\n", field, decoder));
+        }
+
+        let has_topics = !event.anonymous || event.inputs.iter().any(|param| param.indexed);
+        let has_data = event.inputs.iter().any(|param| !param.indexed);
+
+        let mut constants = format!(
+            "    /// Canonical event signature\n    pub const SIGNATURE: &str = \"{}\";\n",
+            signature
+        );
+        let mut checks = String::new();
+        if has_topics {
+            checks.push_str("        let mut topics = topics.iter();\n");
+        }
+        if !event.anonymous {
+            let topic = event.signature.as_deref().unwrap_or_default().trim_start_matches("0x");
+            constants.push_str(&format!(
+                "\n    /// Topic 0 of the event log (keccak256 of the signature)\n    pub const SIGNATURE_HASH: B256 = b256!(\"{}\");\n",
+                topic
+            ));
+            checks.push_str(&format!(
+                r#"        if topics.next() != Some(&Self::SIGNATURE_HASH) {{
+            return Err(ContractError::EventParsingFailed("Topic 0 does not match {}".to_string()));
+        }}
+"#,
+                event.name
+            ));
+        }
+        if has_data {
+            checks.push_str("        let mut head = 0;\n");
+        }
+
+        let topics_arg = if has_topics { "topics" } else { "_topics" };
+        let data_arg = if has_data { "data" } else { "_data" };
+        let anonymous_note = if event.anonymous {
+            "\n    ///\n    /// The event is anonymous, so every topic is an indexed parameter."
+        } else {
+            ""
+        };
+
+        Ok(format!(
+            r#"{docs}/// `{name}` event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct {struct_name} {fields}
+
+impl {struct_name} {{
+{constants}
+    /// Decode the event from the topics and data of a log{anonymous_note}
+    pub fn decode_log({topics_arg}: &[B256], {data_arg}: &[u8]) -> std::result::Result<Self, ContractError> {{
+{checks}        Ok(Self {decoders})
+    }}
+}}
+"#,
+            docs = item_docs(docs),
+            name = event.name,
+            struct_name = struct_name,
+            fields = braced(&fields, ""),
+            constants = constants,
+            anonymous_note = anonymous_note,
+            topics_arg = topics_arg,
+            data_arg = data_arg,
+            checks = checks,
+            decoders = braced(&decoders, "        "),
+        ))
+    }
+
+    /// Generate the struct and `decode` for a custom error, documented with its NatSpec
+    fn generate_error(&mut self, error: &AbiError, docs: Option<&DocEntry>) -> Result<String> {
+        let struct_name = format!("{}Error", error.name.to_case(Case::Pascal));
+        let signature = AbiParser::canonical_signature(&error.name, &error.inputs);
+        let selector = error.signature.as_deref().unwrap_or_default().trim_start_matches("0x");
+        let selector_bytes = (0..selector.len())
+            .step_by(2)
+            .map(|i| format!("0x{}", &selector[i..i + 2]))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let mut fields = String::new();
+        let mut decoders = String::new();
+        for (i, param) in error.inputs.iter().enumerate() {
+            let field = field_name(&param.name, i);
+            let rust_type = self.rust_type(param, &struct_name)?;
+            fields.push_str(&format!(
+                "    /// `{} {}`{}\n    pub {}: {},\n",
+                param.param_type, param.name, param_docs(docs, param), field, rust_type
+            ));
+            decoders.push_str(&format!("            {}: decode_tuple_field(data, 0, &mut head)?,\n", field));
+        }
+
+        let body = if error.inputs.is_empty() {
+            format!(
+                r#"        if !data.starts_with(&Self::SELECTOR) {{
+            return Err(ContractError::AbiDecodingFailed("Selector does not match {name}".to_string()));
+        }}
+        Ok(Self {{}})"#,
+                name = error.name
+            )
+        } else {
+            format!(
+                r#"        let data = data.strip_prefix(&Self::SELECTOR[..])
+            .ok_or_else(|| ContractError::AbiDecodingFailed("Selector does not match {name}".to_string()))?;
+        let mut head = 0;
+        Ok(Self {decoders})"#,
+                name = error.name,
+                decoders = braced(&decoders, "        ")
+            )
+        };
+
+        Ok(format!(
+            r#"{docs}/// `{name}` error
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct {struct_name} {fields}
+
+impl {struct_name} {{
+    /// Canonical error signature
+    pub const SIGNATURE: &str = "{signature}";
+
+    /// First four bytes of the revert data (keccak256 of the signature)
+    pub const SELECTOR: [u8; 4] = [{selector_bytes}];
+
+    /// Decode the error from revert data, including the selector
+    pub fn decode(data: &[u8]) -> std::result::Result<Self, ContractError> {{
+{body}
+    }}
+}}
+"#,
+            docs = item_docs(docs),
+            name = error.name,
+            struct_name = struct_name,
+            fields = braced(&fields, ""),
+            signature = signature,
+            selector_bytes = selector_bytes,
+            body = body,
+        ))
+    }
+
+    /// Rust type of a parameter, generating structs for its tuple types
+    ///
+    /// `context` names the enclosing event, error, function or struct and is
+    /// used to name tuples without a `struct` internal type.
+    fn rust_type(&mut self, param: &AbiParameter, context: &str) -> Result<String> {
+        let (base, dimensions) = split_array_type(&param.param_type);
+
+        let mut rust_type = if base == "tuple" {
+            self.generate_struct(param, context)?
+        } else {
+            elementary_rust_type(base).ok_or_else(|| {
+                indexer_core::Error::Config(format!("Unsupported ABI type {} for {}", param.param_type, param.name))
+            })?
+        };
+
+        for dimension in dimensions {
+            rust_type = match dimension {
+                Some(size) => format!("[{}; {}]", rust_type, size),
+                None => format!("Vec<{}>", rust_type),
+            };
+        }
+        Ok(rust_type)
+    }
+
+    /// Generate the struct for a tuple parameter and return its name
+    ///
+    /// Tuples with the same name and components share one struct. A tuple
+    /// whose name is taken by a different tuple is named after its context.
+    fn generate_struct(&mut self, param: &AbiParameter, context: &str) -> Result<String> {
+        let components = param.components.as_deref().unwrap_or_default();
+        let tuple_param = AbiParameter {
+            param_type: "tuple".to_string(),
+            ..param.clone()
+        };
+        let canonical = AbiParser::canonical_type(&tuple_param);
+
+        let contextual_name = format!("{}{}", context, param.name.to_case(Case::Pascal));
+        let mut name = struct_name_from_internal_type(param.internal_type.as_deref())
+            .unwrap_or_else(|| contextual_name.clone());
+        match self.struct_types.get(&name) {
+            Some(existing) if *existing == canonical => return Ok(name),
+            Some(_) => name = contextual_name,
+            None => {}
+        }
+        let base_name = name.clone();
+        let mut suffix = 2;
+        while self.struct_types.get(&name).is_some_and(|existing| *existing != canonical) {
+            name = format!("{}{}", base_name, suffix);
+            suffix += 1;
+        }
+        if self.struct_types.contains_key(&name) {
+            return Ok(name);
+        }
+        self.struct_types.insert(name.clone(), canonical.clone());
+
+        let mut fields = String::new();
+        let mut field_types = Vec::new();
+        let mut decoders = String::new();
+        let mut encoders = String::new();
+        for (i, component) in components.iter().enumerate() {
+            let field = field_name(&component.name, i);
+            let rust_type = self.rust_type(component, &name)?;
+            fields.push_str(&format!("    /// `{} {}`\n    pub {}: {},\n", component.param_type, component.name, field, rust_type));
+            decoders.push_str(&format!("            {}: decode_tuple_field(data, offset, &mut head)?,\n", field));
+            encoders.push_str(&format!("\n            .field(&self.{})", field));
+            field_types.push(rust_type);
+        }
+
+        let (dynamic, head_size) = if field_types.is_empty() {
+            ("false".to_string(), "0".to_string())
+        } else {
+            let dynamic = field_types.iter()
+                .map(|ty| format!("<{} as AbiDecode>::DYNAMIC", ty))
+                .collect::<Vec<_>>()
+                .join("\n        || ");
+            let head_size = field_types.iter()
+                .map(|ty| format!("<{} as AbiDecode>::HEAD_SIZE", ty))
+                .collect::<Vec<_>>()
+                .join("\n            + ");
+            (dynamic, format!("if Self::DYNAMIC {{\n        32\n    }} else {{\n        {}\n    }}", head_size))
+        };
+
+        let description = match &param.internal_type {
+            Some(internal_type) => internal_type.split('[').next().unwrap_or_default().to_string(),
+            None => canonical.clone(),
+        };
+        let (mut_head, data_arg, offset_arg) = if components.is_empty() {
+            ("", "_data", "_offset")
+        } else {
+            ("        let mut head = 0;\n", "data", "offset")
+        };
+
+        let definition = format!(
+            r#"/// ABI tuple `{description}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct {name} {fields}
+
+impl AbiDecode for {name} {{
+    const DYNAMIC: bool = {dynamic};
+    const HEAD_SIZE: usize = {head_size};
+
+    fn decode_at({data_arg}: &[u8], {offset_arg}: usize) -> std::result::Result<Self, ContractError> {{
+{mut_head}        Ok(Self {decoders})
+    }}
+}}
+
+impl AbiEncode for {name} {{
+    fn encode(&self) -> Vec<u8> {{
+        TupleEncoder::default(){encoders}
+            .finish()
+    }}
+}}
+"#,
+            description = description,
+            name = name,
+            fields = braced(&fields, ""),
+            dynamic = dynamic,
+            head_size = head_size,
+            data_arg = data_arg,
+            offset_arg = offset_arg,
+            mut_head = mut_head,
+            decoders = braced(&decoders, "        "),
+            encoders = encoders,
+        );
+        self.structs.push(definition);
+        Ok(name)
+    }
+}
+
+impl Default for AbiTypeGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// NatSpec notice and details of an event or error, separated from the
+/// generated description that follows them
+fn item_docs(docs: Option<&DocEntry>) -> String {
+    let docs = docs.map(|docs| docs.doc_comment(&[], &[], "")).unwrap_or_default();
+    if docs.is_empty() {
+        docs
+    } else {
+        format!("{}///\n", docs)
+    }
+}
+
+/// NatSpec description of a parameter, appended to its field docs
+fn param_docs(docs: Option<&DocEntry>, param: &AbiParameter) -> String {
+    docs.and_then(|docs| docs.params.get(&param.name))
+        .map(|description| format!(": {}", description.split_whitespace().collect::<Vec<_>>().join(" ")))
+        .unwrap_or_default()
+}
+
+/// Braced struct body, or `{}` when there are no fields
+fn braced(body: &str, indent: &str) -> String {
+    if body.is_empty() {
+        "{}".to_string()
+    } else {
+        format!("{{\n{}{}}}", body, indent)
+    }
+}
+
+/// Split `uint256[2][]` into `uint256` and its dimensions from innermost to
+/// outermost, with `None` for dynamic dimensions
+fn split_array_type(param_type: &str) -> (&str, Vec<Option<&str>>) {
+    let base_end = param_type.find('[').unwrap_or(param_type.len());
+    let dimensions = param_type[base_end..]
+        .split_terminator(']')
+        .map(|dimension| Some(dimension.trim_start_matches('[')).filter(|size| !size.is_empty()))
+        .collect();
+    (&param_type[..base_end], dimensions)
+}
+
+/// Rust type for an elementary ABI type
+fn elementary_rust_type(abi_type: &str) -> Option<String> {
+    let rust_type = match abi_type {
+        "bool" => "bool".to_string(),
+        "address" => "Address".to_string(),
+        "string" => "String".to_string(),
+        "bytes" => "Bytes".to_string(),
+        "function" => "FixedBytes<24>".to_string(),
+        _ if abi_type.starts_with("uint") => "U256".to_string(),
+        _ if abi_type.starts_with("int") => "I256".to_string(),
+        _ if abi_type.starts_with("bytes") => {
+            let size: usize = abi_type[5..].parse().ok().filter(|size| (1..=32).contains(size))?;
+            format!("FixedBytes<{}>", size)
+        }
+        _ => return None,
+    };
+    Some(rust_type)
+}
+
+/// Rust type of a parameter as generated for it, with tuples named after
+/// their `struct` internal type or left as JSON values without one
+pub(crate) fn param_rust_type(param: &AbiParameter) -> String {
+    let (base, dimensions) = split_array_type(&param.param_type);
+    let mut rust_type = if base == "tuple" {
+        struct_name_from_internal_type(param.internal_type.as_deref())
+    } else {
+        elementary_rust_type(base)
+    }
+    .unwrap_or_else(|| "serde_json::Value".to_string());
+
+    for dimension in dimensions {
+        rust_type = match dimension {
+            Some(size) => format!("[{}; {}]", rust_type, size),
+            None => format!("Vec<{}>", rust_type),
+        };
+    }
+    rust_type
+}
+
+/// Struct name from an internal type such as `struct IPool.Order[]`
+fn struct_name_from_internal_type(internal_type: Option<&str>) -> Option<String> {
+    let name = internal_type?.strip_prefix("struct ")?;
+    let name = name.split('[').next()?;
+    let name = name.rsplit('.').next()?;
+    Some(name.to_case(Case::Pascal))
+}
+
+/// Rust field name for a parameter, using `arg{index}` for unnamed parameters
+pub(crate) fn field_name(name: &str, index: usize) -> String {
+    let field = name.trim_start_matches('_').to_case(Case::Snake);
+    if field.is_empty() {
+        return format!("arg{}", index);
+    }
+    match field.as_str() {
+        "as" | "break" | "const" | "continue" | "crate" | "else" | "enum" | "extern" | "false" | "fn" | "for"
+        | "if" | "impl" | "in" | "let" | "loop" | "match" | "mod" | "move" | "mut" | "pub" | "ref" | "return"
+        | "static" | "struct" | "trait" | "true" | "type" | "unsafe" | "use" | "where" | "while" | "async"
+        | "await" | "dyn" | "abstract" | "become" | "box" | "do" | "final" | "macro" | "override" | "priv"
+        | "typeof" | "unsized" | "virtual" | "yield" | "try" => format!("r#{}", field),
+        "self" | "super" | "Self" => format!("{}_", field),
+        _ => field,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_array_type() {
+        assert_eq!(split_array_type("uint256"), ("uint256", vec![]));
+        assert_eq!(split_array_type("tuple[]"), ("tuple", vec![None]));
+        assert_eq!(split_array_type("bytes32[2][]"), ("bytes32", vec![Some("2"), None]));
+    }
+
+    #[test]
+    fn test_field_name() {
+        assert_eq!(field_name("", 2), "arg2");
+        assert_eq!(field_name("_initialSupply", 0), "initial_supply");
+        assert_eq!(field_name("type", 0), "r#type");
+    }
+
+    #[test]
+    fn test_struct_name_from_internal_type() {
+        assert_eq!(struct_name_from_internal_type(Some("struct IPool.Order[]")), Some("Order".to_string()));
+        assert_eq!(struct_name_from_internal_type(Some("struct Key")), Some("Key".to_string()));
+        assert_eq!(struct_name_from_internal_type(Some("address")), None);
+        assert_eq!(struct_name_from_internal_type(None), None);
+    }
+}
@@ -1,50 +1,92 @@
 //! Template system for ethereum contract code generation
 //! 
-//! Contains Handlebars templates for generating different types of code.
+//! Renders user Handlebars templates that override or add generated files,
+//! named by the path of the file they render, see
+//! [`EthereumTemplateManager::with_templates_dir`]. The generated code itself is built by the
+//! generators; [`super::dump_contract_templates`] writes their output as
+//! reference templates to start overrides from.
 
-use handlebars::Handlebars;
+use std::path::Path;
+
+use handlebars::{Context, Handlebars, Helper, HelperResult, Output, RenderContext, RenderError};
+use indexer_core::codegen;
 use indexer_core::Result;
+use serde_json::Value;
+
+use super::migrations::column_type;
+use super::parser::AbiParameter;
+use super::structs::param_rust_type;
+
+/// Helpers registered for templates in addition to the Handlebars built-ins
+///
+/// - `snake_case`, `camel_case`, `pascal_case`, `screaming_snake_case` and
+///   `kebab_case` convert the case of a string
+/// - `rust_type` maps an ABI parameter, or a bare ABI type such as
+///   `uint256[]`, to the Rust type generated for it
+/// - `sql_type` maps an event parameter to the PostgreSQL type of its column
+///   in the generated event tables
+pub const HELPERS: &[&str] = &[
+    "snake_case",
+    "camel_case",
+    "pascal_case",
+    "screaming_snake_case",
+    "kebab_case",
+    "rust_type",
+    "sql_type",
+];
 
 /// Template manager for ethereum contract code generation
 pub struct EthereumTemplateManager {
     handlebars: Handlebars<'static>,
+    /// Names of the templates loaded from a templates directory
+    custom: Vec<String>,
 }
 
 impl EthereumTemplateManager {
-    /// Create a new template manager and register all templates
+    /// Create a template manager with the [`HELPERS`] and no templates
     pub fn new() -> Result<Self> {
         let mut handlebars = Handlebars::new();
-        
-        // Register built-in templates
-        Self::register_templates(&mut handlebars)?;
-        
-        Ok(Self { handlebars })
-    }
+        // Generated code is not HTML
+        handlebars.register_escape_fn(handlebars::no_escape);
 
-    /// Register all built-in templates
-    fn register_templates(handlebars: &mut Handlebars) -> Result<()> {
-        // Client templates
-        handlebars.register_template_string("client_mod", include_str!("client_mod.hbs"))
-            .map_err(|e| indexer_core::Error::Config(format!("Failed to register client_mod template: {}", e)))?;
-        
-        handlebars.register_template_string("client_view", include_str!("client_view.hbs"))
-            .map_err(|e| indexer_core::Error::Config(format!("Failed to register client_view template: {}", e)))?;
+        Self::register_helpers(&mut handlebars);
         
-        handlebars.register_template_string("client_transaction", include_str!("client_transaction.hbs"))
-            .map_err(|e| indexer_core::Error::Config(format!("Failed to register client_transaction template: {}", e)))?;
-
-        handlebars.register_template_string("client_types", include_str!("client_types.hbs"))
-            .map_err(|e| indexer_core::Error::Config(format!("Failed to register client_types template: {}", e)))?;
+        Ok(Self { handlebars, custom: Vec::new() })
+    }
 
-        // Storage templates
-        handlebars.register_template_string("storage_postgres", include_str!("storage_postgres.hbs"))
-            .map_err(|e| indexer_core::Error::Config(format!("Failed to register storage_postgres template: {}", e)))?;
+    /// Load the `.hbs` templates below `dir`, named by their path relative to
+    /// it without the extension
+    ///
+    /// Templates named as a file, such as `client/mod.rs`, render that file
+    /// of the generated code in place of the built-in generator or in
+    /// addition to it; others can be included as partials. Migrations can be
+    /// rendered by a template named without their version, such as
+    /// `migrations/token_events.up.sql`. Templates are checked when loaded, for syntax and
+    /// for helpers and partials that don't exist.
+    pub fn with_templates_dir(mut self, dir: impl AsRef<Path>) -> Result<Self> {
+        let names = codegen::register_templates_dir(&mut self.handlebars, dir.as_ref(), HELPERS)?;
+        self.custom.extend(names);
+        Ok(self)
+    }
 
-        // API templates
-        handlebars.register_template_string("api_rest", include_str!("api_rest.hbs"))
-            .map_err(|e| indexer_core::Error::Config(format!("Failed to register api_rest template: {}", e)))?;
+    /// Register the [`HELPERS`]
+    fn register_helpers(handlebars: &mut Handlebars) {
+        codegen::register_case_helpers(handlebars);
 
-        Ok(())
+        handlebars.register_helper(
+            "rust_type",
+            Box::new(|h: &Helper, _: &Handlebars, _: &Context, _: &mut RenderContext, out: &mut dyn Output| -> HelperResult {
+                out.write(&param_rust_type(&helper_param(h)?))?;
+                Ok(())
+            }),
+        );
+        handlebars.register_helper(
+            "sql_type",
+            Box::new(|h: &Helper, _: &Handlebars, _: &Context, _: &mut RenderContext, out: &mut dyn Output| -> HelperResult {
+                out.write(column_type("", &helper_param(h)?).0)?;
+                Ok(())
+            }),
+        );
     }
 
     /// Render a template with the given data
@@ -53,6 +95,16 @@ impl EthereumTemplateManager {
             .map_err(|e| indexer_core::Error::Config(format!("Failed to render template {}: {}", template_name, e)))
     }
 
+    /// Whether a template of the given name is registered
+    pub fn has_template(&self, template_name: &str) -> bool {
+        self.handlebars.has_template(template_name)
+    }
+
+    /// Names of the templates loaded from a templates directory
+    pub fn custom_templates(&self) -> &[String] {
+        &self.custom
+    }
+
     /// Get list of available templates
     pub fn available_templates(&self) -> Vec<String> {
         self.handlebars.get_templates().keys().cloned().collect()
@@ -63,4 +115,22 @@ impl Default for EthereumTemplateManager {
     fn default() -> Self {
         Self::new().expect("Failed to initialize template manager")
     }
-} 
\ No newline at end of file
+} 
+
+/// ABI parameter passed to a helper, either as a parameter of the model or
+/// as a bare type
+fn helper_param(h: &Helper) -> std::result::Result<AbiParameter, RenderError> {
+    let value = h.param(0).map(|param| param.value())
+        .ok_or_else(|| RenderError::new(format!("{} expects an ABI parameter or type", h.name())))?;
+    match value {
+        Value::String(param_type) => Ok(AbiParameter {
+            name: String::new(),
+            param_type: param_type.clone(),
+            internal_type: None,
+            components: None,
+            indexed: false,
+        }),
+        value => serde_json::from_value(value.clone())
+            .map_err(|e| RenderError::new(format!("{} expects an ABI parameter or type: {}", h.name(), e))),
+    }
+}
@@ -0,0 +1,223 @@
+//! Compiled fixture for the generated ABI types
+//!
+//! `fixtures/decode_types.rs` is the `AbiTypeGenerator` output for
+//! `fixtures/decode_abi.json`. It is compiled here against the same imports
+//! as the generated `types.rs` and used to decode hand-encoded logs and to
+//! encode constructor arguments, so a change to the ABI runtime that no
+//! longer compiles, decodes or encodes fails these tests. Regenerate the
+//! fixture when the generator output changes.
+
+use alloy_primitives::{keccak256, Address, Bytes, FixedBytes, B256, I256, U256};
+
+use crate::codegen::parser::AbiParser;
+use crate::codegen::structs::AbiTypeGenerator;
+
+const DECODE_ABI: &str = include_str!("fixtures/decode_abi.json");
+const DECODE_TYPES: &str = include_str!("fixtures/decode_types.rs");
+
+/// Generated types, with the prelude of the generated `types.rs`
+///
+/// Generated code is a public API for the contract, so not every item is
+/// used by these tests.
+#[allow(dead_code)]
+pub(super) mod generated {
+    use alloy_primitives::{b256, Address, Bytes, FixedBytes, B256, I256, U256};
+    use serde::{Deserialize, Serialize};
+
+    /// Errors the generated decoders report
+    #[derive(Debug)]
+    pub enum ContractError {
+        AbiDecodingFailed(String),
+        EventParsingFailed(String),
+    }
+
+    impl From<ContractError> for indexer_core::Error {
+        fn from(err: ContractError) -> Self {
+            indexer_core::Error::generic(format!("{:?}", err))
+        }
+    }
+
+    include!("fixtures/decode_types.rs");
+}
+
+use generated::{
+    ConstructorFee, ContractError, InsufficientBalanceError, Leg, Order, OrderFilledEvent, SyncEvent, TransferEvent,
+    TupleEncoder,
+};
+
+fn word(value: u64) -> [u8; 32] {
+    U256::from(value).to_be_bytes()
+}
+
+fn address_word(address: Address) -> [u8; 32] {
+    address.into_word().0
+}
+
+fn encode(words: &[[u8; 32]]) -> Vec<u8> {
+    words.concat()
+}
+
+#[test]
+fn test_fixture_matches_generator() {
+    let abi = AbiParser::new().parse_content(DECODE_ABI).unwrap();
+    let generated = AbiTypeGenerator::new().generate(&abi).unwrap();
+    assert!(
+        generated == DECODE_TYPES,
+        "fixtures/decode_types.rs is out of date, regenerate it from fixtures/decode_abi.json"
+    );
+}
+
+#[test]
+fn test_signature_hashes() {
+    assert_eq!(TransferEvent::SIGNATURE_HASH, keccak256(TransferEvent::SIGNATURE));
+    assert_eq!(OrderFilledEvent::SIGNATURE_HASH, keccak256(OrderFilledEvent::SIGNATURE));
+    assert_eq!(InsufficientBalanceError::SELECTOR[..], keccak256(InsufficientBalanceError::SIGNATURE)[..4]);
+}
+
+#[test]
+fn test_decode_transfer_log() {
+    let from = Address::repeat_byte(0x11);
+    let to = Address::repeat_byte(0x22);
+    let topics = [TransferEvent::SIGNATURE_HASH, from.into_word(), to.into_word()];
+    let data = encode(&[word(1_000_000)]);
+
+    let event = TransferEvent::decode_log(&topics, &data).unwrap();
+    assert_eq!(event, TransferEvent { from, to, value: U256::from(1_000_000u64) });
+
+    let mut wrong_topic = topics;
+    wrong_topic[0] = OrderFilledEvent::SIGNATURE_HASH;
+    assert!(matches!(
+        TransferEvent::decode_log(&wrong_topic, &data),
+        Err(ContractError::EventParsingFailed(_))
+    ));
+    assert!(matches!(
+        TransferEvent::decode_log(&topics[..2], &data),
+        Err(ContractError::EventParsingFailed(_))
+    ));
+    assert!(matches!(
+        TransferEvent::decode_log(&topics, &data[..31]),
+        Err(ContractError::AbiDecodingFailed(_))
+    ));
+}
+
+#[test]
+fn test_decode_nested_tuple_log() {
+    let maker = Address::repeat_byte(0xaa);
+    let first_asset = Address::repeat_byte(0xbb);
+    let second_asset = Address::repeat_byte(0xcc);
+    let order_hash = B256::repeat_byte(0x42);
+    // Indexed strings are only available as the hash of their contents
+    let tag = keccak256("limit");
+
+    let mut selector = [0u8; 32];
+    selector[..4].copy_from_slice(&[0xa9, 0x05, 0x9c, 0xbb]);
+    let mut leg_data = [0u8; 32];
+    leg_data[..3].copy_from_slice(&[1, 2, 3]);
+    let tick = I256::try_from(-60i64).unwrap().into_raw().to_be_bytes::<32>();
+
+    let data = encode(&[
+        // (order, selector) head: the order is dynamic and behind an offset
+        word(0x40),
+        selector,
+        // Order head: maker, amounts[2], offset of legs, tick
+        address_word(maker),
+        word(5),
+        word(7),
+        word(0xa0),
+        tick,
+        // legs: length and the offsets of both dynamic legs
+        word(2),
+        word(0x40),
+        word(0xc0),
+        // legs[0]: asset, offset of data, data
+        address_word(first_asset),
+        word(0x40),
+        word(3),
+        leg_data,
+        // legs[1]: asset, offset of empty data
+        address_word(second_asset),
+        word(0x40),
+        word(0),
+    ]);
+    let topics = [OrderFilledEvent::SIGNATURE_HASH, order_hash, tag];
+
+    let event = OrderFilledEvent::decode_log(&topics, &data).unwrap();
+    assert_eq!(
+        event,
+        OrderFilledEvent {
+            order_hash,
+            tag,
+            order: Order {
+                maker,
+                amounts: [U256::from(5u64), U256::from(7u64)],
+                legs: vec![
+                    Leg { asset: first_asset, data: Bytes::from_static(&[1, 2, 3]) },
+                    Leg { asset: second_asset, data: Bytes::new() },
+                ],
+                tick: I256::try_from(-60i64).unwrap(),
+            },
+            selector: FixedBytes::from([0xa9, 0x05, 0x9c, 0xbb]),
+        }
+    );
+
+    // Encoding the decoded fields gives back the log data
+    assert_eq!(TupleEncoder::default().field(&event.order).field(&event.selector).finish(), data);
+
+    // Data that ends inside the last leg is rejected rather than read past
+    let mut truncated = data.clone();
+    truncated.truncate(data.len() - 64);
+    assert!(matches!(
+        OrderFilledEvent::decode_log(&topics, &truncated),
+        Err(ContractError::AbiDecodingFailed(_))
+    ));
+}
+
+#[test]
+fn test_decode_anonymous_log() {
+    // Anonymous events have no signature topic, the first topic is reserve0
+    let topics = [B256::from(U256::from(900u64))];
+    let data = encode(&[word(1_200)]);
+
+    let event = SyncEvent::decode_log(&topics, &data).unwrap();
+    assert_eq!(event, SyncEvent { reserve_0: U256::from(900u64), reserve_1: U256::from(1_200u64) });
+    assert!(matches!(SyncEvent::decode_log(&[], &data), Err(ContractError::EventParsingFailed(_))));
+}
+
+#[test]
+fn test_decode_error() {
+    let mut data = InsufficientBalanceError::SELECTOR.to_vec();
+    data.extend(encode(&[word(10), word(25)]));
+
+    let error = InsufficientBalanceError::decode(&data).unwrap();
+    assert_eq!(error, InsufficientBalanceError { available: U256::from(10u64), required: U256::from(25u64) });
+    assert!(InsufficientBalanceError::decode(&data[4..]).is_err());
+}
+
+#[test]
+fn test_encode_constructor_arguments() {
+    let recipient = Address::repeat_byte(0x33);
+    let fee = ConstructorFee { recipient, bps: U256::from(30u64) };
+    let mut label = [0u8; 32];
+    label[..8].copy_from_slice(b"exchange");
+
+    let encoded = TupleEncoder::default()
+        .field(&fee)
+        .field(&"exchange".to_string())
+        .field(&true)
+        .field(&I256::MINUS_ONE)
+        .finish();
+    assert_eq!(
+        encoded,
+        encode(&[
+            // Static tuples are encoded in place
+            address_word(recipient),
+            word(30),
+            // The string follows the head of five words
+            word(0xa0),
+            word(1),
+            [0xff; 32],
+            word(8),
+            label,
+        ])
+    );
+}
@@ -0,0 +1,63 @@
+//! Compiled fixture for the generated deployment method
+//!
+//! `fixtures/deploy.rs` is the deployment method generated for the payable
+//! constructor of `fixtures/decode_abi.json`. It is compiled here as the
+//! generated `deploy` module next to the fixture types of `decode.rs` and a
+//! client holding an [`EthereumClient`], so a change to the deployment
+//! method that no longer compiles against them fails these tests.
+//! Regenerate the fixture when the generator output changes.
+
+use std::path::Path;
+
+use crate::codegen::parser::AbiParser;
+use crate::codegen::{EthereumCodegenConfig, EthereumContractCodegen};
+use crate::EthereumClient;
+
+const DECODE_ABI: &str = include_str!("fixtures/decode_abi.json");
+const DEPLOY: &str = include_str!("fixtures/deploy.rs");
+
+/// The generated `types` module the deployment method takes its types from
+mod types {
+    pub use crate::codegen::tests::decode::generated::*;
+}
+
+/// The generated client the deployment method is implemented on
+pub struct ExchangeClient {
+    ethereum_client: EthereumClient,
+}
+
+impl ExchangeClient {
+    pub fn ethereum_client(&self) -> &EthereumClient {
+        &self.ethereum_client
+    }
+}
+
+#[allow(dead_code)]
+#[path = "fixtures/deploy.rs"]
+mod generated;
+
+#[tokio::test]
+async fn test_fixture_matches_generator() {
+    let abi = AbiParser::new().parse_content(DECODE_ABI).unwrap();
+    let codegen = EthereumContractCodegen::new(EthereumCodegenConfig {
+        contract_address: "0x1111111111111111111111111111111111111111".to_string(),
+        chain_id: "1".to_string(),
+        output_dir: "generated".to_string(),
+        features: vec!["client".to_string()],
+        dry_run: false,
+        templates_dir: None,
+        migration_version: None,
+        namespace: None,
+    })
+    .in_memory();
+    codegen.generate_all(&abi).await.unwrap();
+
+    let deploy = codegen.take_files()
+        .into_iter()
+        .find(|file| file.path == Path::new("generated/client/deploy.rs"))
+        .unwrap();
+    assert!(
+        deploy.content == DEPLOY,
+        "fixtures/deploy.rs is out of date, regenerate it from fixtures/decode_abi.json"
+    );
+}
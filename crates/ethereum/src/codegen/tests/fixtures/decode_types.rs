@@ -0,0 +1,489 @@
+/// Type that can be decoded from ABI-encoded data
+pub trait AbiDecode: Sized {
+    /// Whether the type is dynamically sized and encoded behind an offset
+    const DYNAMIC: bool;
+
+    /// Bytes the type occupies in the head of an enclosing tuple
+    const HEAD_SIZE: usize = 32;
+
+    /// Decode the value whose encoding starts at `offset` in `data`
+    fn decode_at(data: &[u8], offset: usize) -> std::result::Result<Self, ContractError>;
+}
+
+/// Read the 32-byte word at `offset`
+fn abi_word(data: &[u8], offset: usize) -> std::result::Result<&[u8], ContractError> {
+    offset.checked_add(32)
+        .and_then(|end| data.get(offset..end))
+        .ok_or_else(|| ContractError::AbiDecodingFailed(format!("Data too short for word at offset {}", offset)))
+}
+
+/// Read the word at `offset` as an offset or length bounded by the data size
+fn abi_usize(data: &[u8], offset: usize) -> std::result::Result<usize, ContractError> {
+    let word = abi_word(data, offset)?;
+    let value = U256::from_be_slice(word);
+    usize::try_from(value)
+        .ok()
+        .filter(|value| *value <= data.len())
+        .ok_or_else(|| ContractError::AbiDecodingFailed(format!("Offset or length {} out of range", value)))
+}
+
+/// Decode the next field of a tuple encoded at `base`, advancing `head`
+pub fn decode_tuple_field<T: AbiDecode>(data: &[u8], base: usize, head: &mut usize) -> std::result::Result<T, ContractError> {
+    let position = base + *head;
+    let value = if T::DYNAMIC {
+        *head += 32;
+        T::decode_at(data, base + abi_usize(data, position)?)?
+    } else {
+        *head += T::HEAD_SIZE;
+        T::decode_at(data, position)?
+    };
+    Ok(value)
+}
+
+/// Decode an indexed event parameter from its topic
+pub fn decode_topic<T: AbiDecode>(topic: Option<&B256>, name: &str) -> std::result::Result<T, ContractError> {
+    let topic = topic.ok_or_else(|| ContractError::EventParsingFailed(format!("Missing topic for {}", name)))?;
+    T::decode_at(topic.as_slice(), 0)
+}
+
+impl AbiDecode for bool {
+    const DYNAMIC: bool = false;
+
+    fn decode_at(data: &[u8], offset: usize) -> std::result::Result<Self, ContractError> {
+        match U256::from_be_slice(abi_word(data, offset)?) {
+            value if value == U256::ZERO => Ok(false),
+            value if value == U256::from(1) => Ok(true),
+            value => Err(ContractError::AbiDecodingFailed(format!("Invalid bool {}", value))),
+        }
+    }
+}
+
+impl AbiDecode for Address {
+    const DYNAMIC: bool = false;
+
+    fn decode_at(data: &[u8], offset: usize) -> std::result::Result<Self, ContractError> {
+        Ok(Address::from_slice(&abi_word(data, offset)?[12..]))
+    }
+}
+
+impl AbiDecode for U256 {
+    const DYNAMIC: bool = false;
+
+    fn decode_at(data: &[u8], offset: usize) -> std::result::Result<Self, ContractError> {
+        Ok(U256::from_be_slice(abi_word(data, offset)?))
+    }
+}
+
+impl AbiDecode for I256 {
+    const DYNAMIC: bool = false;
+
+    fn decode_at(data: &[u8], offset: usize) -> std::result::Result<Self, ContractError> {
+        Ok(I256::from_raw(U256::decode_at(data, offset)?))
+    }
+}
+
+impl<const N: usize> AbiDecode for FixedBytes<N> {
+    const DYNAMIC: bool = false;
+
+    fn decode_at(data: &[u8], offset: usize) -> std::result::Result<Self, ContractError> {
+        Ok(FixedBytes::from_slice(&abi_word(data, offset)?[..N]))
+    }
+}
+
+impl AbiDecode for Bytes {
+    const DYNAMIC: bool = true;
+
+    fn decode_at(data: &[u8], offset: usize) -> std::result::Result<Self, ContractError> {
+        let len = abi_usize(data, offset)?;
+        let start = offset + 32;
+        data.get(start..start + len)
+            .map(Bytes::copy_from_slice)
+            .ok_or_else(|| ContractError::AbiDecodingFailed(format!("Data too short for {} bytes at offset {}", len, start)))
+    }
+}
+
+impl AbiDecode for String {
+    const DYNAMIC: bool = true;
+
+    fn decode_at(data: &[u8], offset: usize) -> std::result::Result<Self, ContractError> {
+        String::from_utf8(Bytes::decode_at(data, offset)?.to_vec())
+            .map_err(|e| ContractError::AbiDecodingFailed(format!("Invalid UTF-8 string: {}", e)))
+    }
+}
+
+impl<T: AbiDecode> AbiDecode for Vec<T> {
+    const DYNAMIC: bool = true;
+
+    fn decode_at(data: &[u8], offset: usize) -> std::result::Result<Self, ContractError> {
+        let len = abi_usize(data, offset)?;
+        let base = offset + 32;
+        let mut head = 0;
+        (0..len).map(|_| decode_tuple_field(data, base, &mut head)).collect()
+    }
+}
+
+impl<T: AbiDecode, const N: usize> AbiDecode for [T; N] {
+    const DYNAMIC: bool = T::DYNAMIC;
+    const HEAD_SIZE: usize = if T::DYNAMIC { 32 } else { T::HEAD_SIZE * N };
+
+    fn decode_at(data: &[u8], offset: usize) -> std::result::Result<Self, ContractError> {
+        let mut head = 0;
+        let items = (0..N)
+            .map(|_| decode_tuple_field(data, offset, &mut head))
+            .collect::<std::result::Result<Vec<T>, _>>()?;
+        items.try_into()
+            .map_err(|_| ContractError::AbiDecodingFailed(format!("Expected {} array items", N)))
+    }
+}
+
+/// Type that can be ABI-encoded
+pub trait AbiEncode: AbiDecode {
+    /// Encoding of the value: its head for static types, its tail for
+    /// dynamic types
+    fn encode(&self) -> Vec<u8>;
+}
+
+/// Fields of a tuple being ABI-encoded, in order
+#[derive(Debug, Default)]
+pub struct TupleEncoder {
+    /// Whether each field is dynamic, and its encoding
+    fields: Vec<(bool, Vec<u8>)>,
+}
+
+impl TupleEncoder {
+    /// Add the next field of the tuple
+    pub fn field<T: AbiEncode>(mut self, value: &T) -> Self {
+        self.fields.push((T::DYNAMIC, value.encode()));
+        self
+    }
+
+    /// Encoding of the tuple, with dynamic fields behind offsets into its tail
+    pub fn finish(self) -> Vec<u8> {
+        let head_size: usize = self.fields.iter()
+            .map(|(dynamic, encoded)| if *dynamic { 32 } else { encoded.len() })
+            .sum();
+        let mut head = Vec::with_capacity(head_size);
+        let mut tail = Vec::new();
+        for (dynamic, encoded) in self.fields {
+            if dynamic {
+                head.extend(U256::from(head_size + tail.len()).encode());
+                tail.extend(encoded);
+            } else {
+                head.extend(encoded);
+            }
+        }
+        head.extend(tail);
+        head
+    }
+}
+
+/// Left-aligned bytes padded with zeros to a whole number of words
+fn abi_padded(bytes: &[u8]) -> Vec<u8> {
+    let mut padded = bytes.to_vec();
+    padded.resize(bytes.len().div_ceil(32) * 32, 0);
+    padded
+}
+
+impl AbiEncode for bool {
+    fn encode(&self) -> Vec<u8> {
+        U256::from(u8::from(*self)).encode()
+    }
+}
+
+impl AbiEncode for Address {
+    fn encode(&self) -> Vec<u8> {
+        self.into_word().to_vec()
+    }
+}
+
+impl AbiEncode for U256 {
+    fn encode(&self) -> Vec<u8> {
+        self.to_be_bytes::<32>().to_vec()
+    }
+}
+
+impl AbiEncode for I256 {
+    fn encode(&self) -> Vec<u8> {
+        self.into_raw().encode()
+    }
+}
+
+impl<const N: usize> AbiEncode for FixedBytes<N> {
+    fn encode(&self) -> Vec<u8> {
+        abi_padded(self.as_slice())
+    }
+}
+
+impl AbiEncode for Bytes {
+    fn encode(&self) -> Vec<u8> {
+        let mut encoded = U256::from(self.len()).encode();
+        encoded.extend(abi_padded(self));
+        encoded
+    }
+}
+
+impl AbiEncode for String {
+    fn encode(&self) -> Vec<u8> {
+        Bytes::copy_from_slice(self.as_bytes()).encode()
+    }
+}
+
+impl<T: AbiEncode> AbiEncode for Vec<T> {
+    fn encode(&self) -> Vec<u8> {
+        let mut encoded = U256::from(self.len()).encode();
+        encoded.extend(self.iter().fold(TupleEncoder::default(), TupleEncoder::field).finish());
+        encoded
+    }
+}
+
+impl<T: AbiEncode, const N: usize> AbiEncode for [T; N] {
+    fn encode(&self) -> Vec<u8> {
+        self.iter().fold(TupleEncoder::default(), TupleEncoder::field).finish()
+    }
+}
+
+/// ABI tuple `struct IExchange.Leg`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Leg {
+    /// `address asset`
+    pub asset: Address,
+    /// `bytes data`
+    pub data: Bytes,
+}
+
+impl AbiDecode for Leg {
+    const DYNAMIC: bool = <Address as AbiDecode>::DYNAMIC
+        || <Bytes as AbiDecode>::DYNAMIC;
+    const HEAD_SIZE: usize = if Self::DYNAMIC {
+        32
+    } else {
+        <Address as AbiDecode>::HEAD_SIZE
+            + <Bytes as AbiDecode>::HEAD_SIZE
+    };
+
+    fn decode_at(data: &[u8], offset: usize) -> std::result::Result<Self, ContractError> {
+        let mut head = 0;
+        Ok(Self {
+            asset: decode_tuple_field(data, offset, &mut head)?,
+            data: decode_tuple_field(data, offset, &mut head)?,
+        })
+    }
+}
+
+impl AbiEncode for Leg {
+    fn encode(&self) -> Vec<u8> {
+        TupleEncoder::default()
+            .field(&self.asset)
+            .field(&self.data)
+            .finish()
+    }
+}
+
+/// ABI tuple `struct IExchange.Order`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Order {
+    /// `address maker`
+    pub maker: Address,
+    /// `uint256[2] amounts`
+    pub amounts: [U256; 2],
+    /// `tuple[] legs`
+    pub legs: Vec<Leg>,
+    /// `int24 tick`
+    pub tick: I256,
+}
+
+impl AbiDecode for Order {
+    const DYNAMIC: bool = <Address as AbiDecode>::DYNAMIC
+        || <[U256; 2] as AbiDecode>::DYNAMIC
+        || <Vec<Leg> as AbiDecode>::DYNAMIC
+        || <I256 as AbiDecode>::DYNAMIC;
+    const HEAD_SIZE: usize = if Self::DYNAMIC {
+        32
+    } else {
+        <Address as AbiDecode>::HEAD_SIZE
+            + <[U256; 2] as AbiDecode>::HEAD_SIZE
+            + <Vec<Leg> as AbiDecode>::HEAD_SIZE
+            + <I256 as AbiDecode>::HEAD_SIZE
+    };
+
+    fn decode_at(data: &[u8], offset: usize) -> std::result::Result<Self, ContractError> {
+        let mut head = 0;
+        Ok(Self {
+            maker: decode_tuple_field(data, offset, &mut head)?,
+            amounts: decode_tuple_field(data, offset, &mut head)?,
+            legs: decode_tuple_field(data, offset, &mut head)?,
+            tick: decode_tuple_field(data, offset, &mut head)?,
+        })
+    }
+}
+
+impl AbiEncode for Order {
+    fn encode(&self) -> Vec<u8> {
+        TupleEncoder::default()
+            .field(&self.maker)
+            .field(&self.amounts)
+            .field(&self.legs)
+            .field(&self.tick)
+            .finish()
+    }
+}
+
+/// ABI tuple `(address,uint16)`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConstructorFee {
+    /// `address recipient`
+    pub recipient: Address,
+    /// `uint16 bps`
+    pub bps: U256,
+}
+
+impl AbiDecode for ConstructorFee {
+    const DYNAMIC: bool = <Address as AbiDecode>::DYNAMIC
+        || <U256 as AbiDecode>::DYNAMIC;
+    const HEAD_SIZE: usize = if Self::DYNAMIC {
+        32
+    } else {
+        <Address as AbiDecode>::HEAD_SIZE
+            + <U256 as AbiDecode>::HEAD_SIZE
+    };
+
+    fn decode_at(data: &[u8], offset: usize) -> std::result::Result<Self, ContractError> {
+        let mut head = 0;
+        Ok(Self {
+            recipient: decode_tuple_field(data, offset, &mut head)?,
+            bps: decode_tuple_field(data, offset, &mut head)?,
+        })
+    }
+}
+
+impl AbiEncode for ConstructorFee {
+    fn encode(&self) -> Vec<u8> {
+        TupleEncoder::default()
+            .field(&self.recipient)
+            .field(&self.bps)
+            .finish()
+    }
+}
+
+/// `Transfer` event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransferEvent {
+    /// `address from` (indexed)
+    pub from: Address,
+    /// `address to` (indexed)
+    pub to: Address,
+    /// `uint256 value`
+    pub value: U256,
+}
+
+impl TransferEvent {
+    /// Canonical event signature
+    pub const SIGNATURE: &str = "Transfer(address,address,uint256)";
+
+    /// Topic 0 of the event log (keccak256 of the signature)
+    pub const SIGNATURE_HASH: B256 = b256!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
+
+    /// Decode the event from the topics and data of a log
+    pub fn decode_log(topics: &[B256], data: &[u8]) -> std::result::Result<Self, ContractError> {
+        let mut topics = topics.iter();
+        if topics.next() != Some(&Self::SIGNATURE_HASH) {
+            return Err(ContractError::EventParsingFailed("Topic 0 does not match Transfer".to_string()));
+        }
+        let mut head = 0;
+        Ok(Self {
+            from: decode_topic(topics.next(), "from")?,
+            to: decode_topic(topics.next(), "to")?,
+            value: decode_tuple_field(data, 0, &mut head)?,
+        })
+    }
+}
+
+/// `OrderFilled` event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderFilledEvent {
+    /// `bytes32 orderHash` (indexed)
+    pub order_hash: FixedBytes<32>,
+    /// `string tag` (indexed)
+    pub tag: B256,
+    /// `tuple order`
+    pub order: Order,
+    /// `bytes4 selector`
+    pub selector: FixedBytes<4>,
+}
+
+impl OrderFilledEvent {
+    /// Canonical event signature
+    pub const SIGNATURE: &str = "OrderFilled(bytes32,string,(address,uint256[2],(address,bytes)[],int24),bytes4)";
+
+    /// Topic 0 of the event log (keccak256 of the signature)
+    pub const SIGNATURE_HASH: B256 = b256!("931a9c9285f4a41530ae84048815bc46efe4cac8e834ccdf74e55ee6f7977f73");
+
+    /// Decode the event from the topics and data of a log
+    pub fn decode_log(topics: &[B256], data: &[u8]) -> std::result::Result<Self, ContractError> {
+        let mut topics = topics.iter();
+        if topics.next() != Some(&Self::SIGNATURE_HASH) {
+            return Err(ContractError::EventParsingFailed("Topic 0 does not match OrderFilled".to_string()));
+        }
+        let mut head = 0;
+        Ok(Self {
+            order_hash: decode_topic(topics.next(), "order_hash")?,
+            tag: decode_topic(topics.next(), "tag")?,
+            order: decode_tuple_field(data, 0, &mut head)?,
+            selector: decode_tuple_field(data, 0, &mut head)?,
+        })
+    }
+}
+
+/// `Sync` event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncEvent {
+    /// `uint112 reserve0` (indexed)
+    pub reserve_0: U256,
+    /// `uint112 reserve1`
+    pub reserve_1: U256,
+}
+
+impl SyncEvent {
+    /// Canonical event signature
+    pub const SIGNATURE: &str = "Sync(uint112,uint112)";
+
+    /// Decode the event from the topics and data of a log
+    ///
+    /// The event is anonymous, so every topic is an indexed parameter.
+    pub fn decode_log(topics: &[B256], data: &[u8]) -> std::result::Result<Self, ContractError> {
+        let mut topics = topics.iter();
+        let mut head = 0;
+        Ok(Self {
+            reserve_0: decode_topic(topics.next(), "reserve_0")?,
+            reserve_1: decode_tuple_field(data, 0, &mut head)?,
+        })
+    }
+}
+
+/// `InsufficientBalance` error
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InsufficientBalanceError {
+    /// `uint256 available`
+    pub available: U256,
+    /// `uint256 required`
+    pub required: U256,
+}
+
+impl InsufficientBalanceError {
+    /// Canonical error signature
+    pub const SIGNATURE: &str = "InsufficientBalance(uint256,uint256)";
+
+    /// First four bytes of the revert data (keccak256 of the signature)
+    pub const SELECTOR: [u8; 4] = [0xcf, 0x47, 0x91, 0x81];
+
+    /// Decode the error from revert data, including the selector
+    pub fn decode(data: &[u8]) -> std::result::Result<Self, ContractError> {
+        let data = data.strip_prefix(&Self::SELECTOR[..])
+            .ok_or_else(|| ContractError::AbiDecodingFailed("Selector does not match InsufficientBalance".to_string()))?;
+        let mut head = 0;
+        Ok(Self {
+            available: decode_tuple_field(data, 0, &mut head)?,
+            required: decode_tuple_field(data, 0, &mut head)?,
+        })
+    }
+}
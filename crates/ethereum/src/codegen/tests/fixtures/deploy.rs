@@ -0,0 +1,24 @@
+//! Generated deployment method for contract: 0x1111111111111111111111111111111111111111
+
+use super::types::*;
+use super::ExchangeClient;
+use indexer_core::Result;
+use alloy_primitives::{Address, U256, Bytes};
+
+impl ExchangeClient {
+    /// Deploy a new contract instance
+    /// This constructor is payable and can receive ETH
+    pub async fn deploy(&self, owner: Address, initial_order: Order, fee: ConstructorFee, label: String, bytecode: Bytes, value: U256) -> Result<Address> {
+        let mut creation_code = bytecode.to_vec();
+        creation_code.extend(
+            TupleEncoder::default()
+                .field(&owner)
+                .field(&initial_order)
+                .field(&fee)
+                .field(&label)
+                .finish(),
+        );
+        self.ethereum_client().deploy_contract(&creation_code, value).await
+    }
+
+}
@@ -0,0 +1,129 @@
+//! Generated event handler for contract: 0x1111111111111111111111111111111111111111
+//! Chain: 1
+
+use std::sync::Arc;
+
+use alloy_primitives::{hex, B256};
+use async_trait::async_trait;
+use indexer_core::event::{Event, EventData, UnifiedEvent};
+use indexer_core::pipeline::{event_index, EventHandler, EventHandlerRegistry};
+use indexer_core::{Error, Result};
+use sqlx::PgPool;
+
+use super::client::types::*;
+
+/// Chain the contract is deployed on
+pub const CHAIN_ID: &str = "1";
+
+/// Address of the contract
+pub const CONTRACT_ADDRESS: &str = "0x1111111111111111111111111111111111111111";
+
+/// Statements flagging the rows of blocks from `$1` on as removed
+const ROLLBACK_STATEMENTS: &[&str] = &[
+    "UPDATE exchange_transfer_events SET removed = TRUE WHERE block_number >= $1",
+    "UPDATE exchange_orderfilled_events SET removed = TRUE WHERE block_number >= $1",
+];
+
+/// Decodes the events of the 0x1111111111111111111111111111111111111111 contract into its event tables
+pub struct ExchangeEventHandler {
+    pool: PgPool,
+}
+
+impl ExchangeEventHandler {
+    /// Create a handler writing to the event tables in `pool`
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Register a handler for the contract's events on its chain
+    pub fn register(registry: &mut EventHandlerRegistry, pool: PgPool) {
+        registry.register(CHAIN_ID, CONTRACT_ADDRESS, Arc::new(Self::new(pool)));
+    }
+}
+
+#[async_trait]
+impl EventHandler for ExchangeEventHandler {
+    fn name(&self) -> &str {
+        "ExchangeEventHandler"
+    }
+
+    async fn handle_event(&self, event: &dyn Event) -> Result<bool> {
+        let Some(EventData::Evm { topics, data, address, .. }) =
+            event.as_any().downcast_ref::<UnifiedEvent>().map(|event| &event.event_data)
+        else {
+            return Ok(false);
+        };
+        let topics = topics
+            .iter()
+            .map(|topic| topic.parse::<B256>())
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| Error::invalid_data(format!("Invalid topic in event {}: {}", event.id(), e)))?;
+        let Some(topic0) = topics.first() else {
+            return Ok(false);
+        };
+
+        let log_index = i32::try_from(event_index(event)?)
+            .map_err(|_| Error::invalid_data(format!("Log index of event {} out of range", event.id())))?;
+        let block_number = i64::try_from(event.block_number())
+            .map_err(|_| Error::invalid_data(format!("Block number of event {} out of range", event.id())))?;
+        let block_timestamp = chrono::DateTime::<chrono::Utc>::from(event.timestamp());
+
+        let data = hex::decode(data)
+            .map_err(|e| Error::invalid_data(format!("Invalid data in event {}: {}", event.id(), e)))?;
+
+        if *topic0 == TransferEvent::SIGNATURE_HASH {
+            let decoded = TransferEvent::decode_log(&topics, &data)?;
+            sqlx::query(
+                "INSERT INTO exchange_transfer_events (transaction_hash, contract_address, block_number, block_timestamp, log_index, \"from\", \"to\", value) VALUES ($1, $2, $3, $4, $5, $6, $7, $8::NUMERIC) ON CONFLICT (transaction_hash, log_index) DO UPDATE SET block_number = EXCLUDED.block_number, block_timestamp = EXCLUDED.block_timestamp, removed = FALSE",
+            )
+                .bind(event.tx_hash())
+                .bind(address.as_str())
+                .bind(block_number)
+                .bind(block_timestamp)
+                .bind(log_index)
+                .bind(decoded.from.to_string())
+                .bind(decoded.to.to_string())
+                .bind(decoded.value.to_string())
+                .execute(&self.pool)
+                .await
+                .map_err(|e| Error::database(format!("Failed to store Transfer event {}: {}", event.id(), e)))?;
+            return Ok(true);
+        }
+        if *topic0 == OrderFilledEvent::SIGNATURE_HASH {
+            let decoded = OrderFilledEvent::decode_log(&topics, &data)?;
+            sqlx::query(
+                "INSERT INTO exchange_orderfilled_events (transaction_hash, contract_address, block_number, block_timestamp, log_index, order_hash, tag, \"order\", selector) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) ON CONFLICT (transaction_hash, log_index) DO UPDATE SET block_number = EXCLUDED.block_number, block_timestamp = EXCLUDED.block_timestamp, removed = FALSE",
+            )
+                .bind(event.tx_hash())
+                .bind(address.as_str())
+                .bind(block_number)
+                .bind(block_timestamp)
+                .bind(log_index)
+                .bind(decoded.order_hash.to_vec())
+                .bind(decoded.tag.to_vec())
+                .bind(serde_json::to_value(&decoded.order)?)
+                .bind(decoded.selector.to_vec())
+                .execute(&self.pool)
+                .await
+                .map_err(|e| Error::database(format!("Failed to store OrderFilled event {}: {}", event.id(), e)))?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    async fn rollback(&self, chain: &str, from_block: u64) -> Result<()> {
+        if chain != CHAIN_ID {
+            return Ok(());
+        }
+        let from_block = i64::try_from(from_block)
+            .map_err(|_| Error::invalid_data(format!("Block number {} out of range", from_block)))?;
+        for statement in ROLLBACK_STATEMENTS {
+            sqlx::query(statement)
+                .bind(from_block)
+                .execute(&self.pool)
+                .await
+                .map_err(|e| Error::database(format!("Failed to roll back ExchangeEventHandler from block {}: {}", from_block, e)))?;
+        }
+        Ok(())
+    }
+}
@@ -1,9 +1,11 @@
 //! Integration tests for Ethereum contract code generation
 
-use crate::codegen::{EthereumCodegenConfig, EthereumContractCodegen};
+use crate::codegen::{check_contract_code, dump_contract_templates, generate_contract_code, generate_proxy_contract_code, EthereumCodegenConfig, EthereumContractCodegen, EthereumTemplateManager};
+use indexer_core::codegen::{Drift, Provenance};
 use crate::codegen::parser::AbiParser;
 use indexer_core::Result;
 use std::fs;
+use std::path::Path;
 use tempfile::TempDir;
 
 /// Real ERC20 contract ABI (USDC) for testing
@@ -128,6 +130,8 @@ async fn test_end_to_end_erc20_generation() -> Result<()> {
         output_dir: output_path.to_string(),
         features: vec!["client".to_string(), "storage".to_string()],
         dry_run: false,
+        templates_dir: None,
+        migration_version: None,
         namespace: None,
     };
 
@@ -253,6 +257,8 @@ async fn test_complex_ethereum_contract_generation() -> Result<()> {
         output_dir: output_path.to_string(),
         features: vec!["client".to_string(), "api".to_string()],
         dry_run: false,
+        templates_dir: None,
+        migration_version: None,
         namespace: None,
     };
 
@@ -298,6 +304,8 @@ async fn test_dry_run_ethereum_generation() -> Result<()> {
         output_dir: "./test_output".to_string(),
         features: vec!["client".to_string()],
         dry_run: true,
+        templates_dir: None,
+        migration_version: None,
         namespace: None,
     };
 
@@ -368,4 +376,649 @@ async fn test_ethereum_parser_edge_cases() -> Result<()> {
 
     println!("✅ Ethereum parser edge cases test passed");
     Ok(())
-} 
\ No newline at end of file
+} 
+#[tokio::test]
+async fn test_typed_struct_generation() -> Result<()> {
+    const TUPLE_ABI: &str = r#"[
+      {
+        "type": "event",
+        "name": "OrderFilled",
+        "inputs": [
+          {"name": "orderHash", "type": "bytes32", "indexed": true},
+          {"name": "tag", "type": "string", "indexed": true},
+          {
+            "name": "order", "type": "tuple", "indexed": false, "internalType": "struct IExchange.Order",
+            "components": [
+              {"name": "maker", "type": "address"},
+              {"name": "amounts", "type": "uint256[2]"},
+              {
+                "name": "legs", "type": "tuple[]", "internalType": "struct IExchange.Leg[]",
+                "components": [
+                  {"name": "asset", "type": "address"},
+                  {"name": "data", "type": "bytes"}
+                ]
+              }
+            ]
+          }
+        ],
+        "anonymous": false
+      },
+      {
+        "type": "event",
+        "name": "Sync",
+        "inputs": [{"name": "reserve", "type": "uint112", "indexed": true}],
+        "anonymous": true
+      },
+      {
+        "type": "error",
+        "name": "InsufficientBalance",
+        "inputs": [
+          {"name": "available", "type": "uint256"},
+          {"name": "required", "type": "uint256"}
+        ]
+      }
+    ]"#;
+
+    let parser = AbiParser::new();
+    let erc20 = parser.parse_content(ERC20_ABI)?;
+    let transfer = erc20.events.iter().find(|e| e.name == "Transfer").unwrap();
+    assert_eq!(
+        transfer.signature.as_deref(),
+        Some("0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef")
+    );
+
+    let abi = parser.parse_content(TUPLE_ABI)?;
+    assert_eq!(AbiParser::canonical_signature(&abi.events[0].name, &abi.events[0].inputs),
+        "OrderFilled(bytes32,string,(address,uint256[2],(address,bytes)[]))");
+    assert_eq!(abi.errors[0].signature.as_deref(), Some("0xcf479181"));
+
+    let temp_dir = TempDir::new().unwrap();
+    let config = EthereumCodegenConfig {
+        contract_address: "0xa0b86a33e6411fbae92b7b5e06e1d4320827cd8".to_string(),
+        chain_id: "1".to_string(),
+        output_dir: temp_dir.path().to_str().unwrap().to_string(),
+        features: vec!["client".to_string()],
+        dry_run: false,
+        templates_dir: None,
+        migration_version: None,
+        namespace: None,
+    };
+    EthereumContractCodegen::new(config).generate_all(&abi).await?;
+
+    let types = fs::read_to_string(temp_dir.path().join("client").join("types.rs")).unwrap();
+    assert!(!types.contains("PlaceholderType"));
+    assert!(types.contains("pub struct Order {"));
+    assert!(types.contains("pub legs: Vec<Leg>,"));
+    assert!(types.contains("pub amounts: [U256; 2],"));
+    assert!(types.contains("pub struct OrderFilledEvent {"));
+    assert!(types.contains("pub order_hash: FixedBytes<32>,"));
+    // Indexed dynamic types are only available as their topic hash
+    assert!(types.contains("pub tag: B256,"));
+    assert!(types.contains("pub fn decode_log(topics: &[B256], data: &[u8])"));
+    assert!(types.contains("pub struct InsufficientBalanceError {"));
+    assert!(types.contains("pub const SELECTOR: [u8; 4] = [0xcf, 0x47, 0x91, 0x81];"));
+
+    // Anonymous events have no signature topic to check
+    let sync = &types[types.find("impl SyncEvent").unwrap()..];
+    assert!(!sync[..sync.find("\n}\n").unwrap()].contains("SIGNATURE_HASH"));
+
+    let events = fs::read_to_string(temp_dir.path().join("client").join("events.rs")).unwrap();
+    assert!(events.contains("OrderFilledEvent::decode_log(&topics, data)"));
+    assert!(!events.contains("todo!"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_event_migration_generation() -> Result<()> {
+    let temp_dir = TempDir::new().unwrap();
+    let config = EthereumCodegenConfig {
+        contract_address: "0xa0b86a33e6411fbae92b7b5e06e1d4320827cd8".to_string(),
+        chain_id: "1".to_string(),
+        output_dir: temp_dir.path().to_str().unwrap().to_string(),
+        features: vec!["migrations".to_string()],
+        dry_run: false,
+        templates_dir: None,
+        migration_version: None,
+        namespace: None,
+    };
+
+    let migration_files = |dir: &Path| -> Vec<String> {
+        let mut files: Vec<String> = fs::read_dir(dir.join("migrations"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        files.sort();
+        files
+    };
+
+    let abi = AbiParser::new().parse_content(ERC20_ABI)?;
+    EthereumContractCodegen::new(config.clone()).generate_all(&abi).await?;
+
+    let files = migration_files(temp_dir.path());
+    assert_eq!(files.len(), 2, "Expected an up and a down migration: {:?}", files);
+    assert!(files[0].ends_with("_events.down.sql"));
+    assert!(files[1].ends_with("_events.up.sql"));
+
+    // Versions must sort after the storage crate's `YYYYMMDDHHMM` migrations
+    let version = files[1].split('_').next().unwrap();
+    assert!(version > "202404070210");
+
+    // Regenerating the same ABI keeps the version
+    EthereumContractCodegen::new(config.clone()).generate_all(&abi).await?;
+    assert_eq!(migration_files(temp_dir.path()), files);
+
+    // An explicit version is used as is
+    let versioned_dir = TempDir::new().unwrap();
+    let versioned = EthereumCodegenConfig {
+        output_dir: versioned_dir.path().to_str().unwrap().to_string(),
+        migration_version: Some("202405010000".to_string()),
+        ..config.clone()
+    };
+    EthereumContractCodegen::new(versioned).generate_all(&abi).await?;
+    assert!(migration_files(versioned_dir.path())[1].starts_with("202405010000_"));
+    let invalid = EthereumCodegenConfig {
+        migration_version: Some("v2".to_string()),
+        ..config
+    };
+    assert!(EthereumContractCodegen::new(invalid).generate_all(&abi).await.is_err());
+
+    let up = fs::read_to_string(temp_dir.path().join("migrations").join(&files[1])).unwrap();
+    assert!(up.contains("-- Transfer(address,address,uint256)"));
+    assert!(up.contains("_transfer_events\" ("));
+    assert!(up.contains("\"from\" TEXT NOT NULL CHECK (\"from\" ~ '^0x[0-9a-fA-F]{40}$')"));
+    assert!(up.contains("value NUMERIC(78, 0) NOT NULL CHECK (value >= 0)"));
+
+    // Contract address and block number, plus the indexed `from` and `to`
+    let transfer_indexes: Vec<&str> = up.lines()
+        .filter(|line| line.starts_with("CREATE INDEX") && line.contains("_transfer_events\" ("))
+        .collect();
+    assert_eq!(transfer_indexes.len(), 4, "{:?}", transfer_indexes);
+    assert!(transfer_indexes.iter().any(|line| line.ends_with("(\"from\");")));
+    assert!(!transfer_indexes.iter().any(|line| line.ends_with("(value);")));
+
+    let down = fs::read_to_string(temp_dir.path().join("migrations").join(&files[0])).unwrap();
+    assert!(down.contains("DROP TABLE IF EXISTS"));
+    assert!(down.contains("_transfer_events\";"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_event_handler_generation() -> Result<()> {
+    let temp_dir = TempDir::new().unwrap();
+    let config = EthereumCodegenConfig {
+        contract_address: "0xa0b86a33e6411fbae92b7b5e06e1d4320827cd8".to_string(),
+        chain_id: "1".to_string(),
+        output_dir: temp_dir.path().to_str().unwrap().to_string(),
+        features: vec!["handlers".to_string()],
+        dry_run: false,
+        templates_dir: None,
+        migration_version: None,
+        namespace: None,
+    };
+
+    let abi = AbiParser::new().parse_content(ERC20_ABI)?;
+    EthereumContractCodegen::new(config).generate_all(&abi).await?;
+
+    let handler = fs::read_to_string(temp_dir.path().join("handlers").join("mod.rs")).unwrap();
+    assert!(handler.contains("pub const CHAIN_ID: &str = \"1\";"));
+    assert!(handler.contains("pub const CONTRACT_ADDRESS: &str = \"0xa0b86a33e6411fbae92b7b5e06e1d4320827cd8\";"));
+    assert!(handler.contains("pub fn register(registry: &mut EventHandlerRegistry, pool: PgPool)"));
+    assert!(handler.contains("registry.register(CHAIN_ID, CONTRACT_ADDRESS, Arc::new(Self::new(pool)));"));
+
+    // Logs are matched on their signature topic and stored in the migration tables
+    assert!(handler.contains("if *topic0 == TransferEvent::SIGNATURE_HASH {"));
+    assert!(handler.contains("let decoded = TransferEvent::decode_log(&topics, &data)?;"));
+    assert!(handler.contains("_transfer_events\\\" (transaction_hash, contract_address, block_number, block_timestamp, log_index, \\\"from\\\", \\\"to\\\", value) VALUES ($1, $2, $3, $4, $5, $6, $7, $8::NUMERIC) ON CONFLICT (transaction_hash, log_index) DO UPDATE SET block_number = EXCLUDED.block_number, block_timestamp = EXCLUDED.block_timestamp, removed = FALSE"));
+    assert!(handler.contains(".bind(decoded.from.to_string())"));
+    assert!(handler.contains(".bind(decoded.value.to_string())"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_typescript_generation() -> Result<()> {
+    let temp_dir = TempDir::new().unwrap();
+    let config = EthereumCodegenConfig {
+        contract_address: "0xa0b86a33e6411fbae92b7b5e06e1d4320827cd8".to_string(),
+        chain_id: "1".to_string(),
+        output_dir: temp_dir.path().to_str().unwrap().to_string(),
+        features: vec!["typescript".to_string()],
+        dry_run: false,
+        templates_dir: None,
+        migration_version: None,
+        namespace: Some("token".to_string()),
+    };
+
+    let abi = AbiParser::new().parse_content(ERC20_ABI)?;
+    EthereumContractCodegen::new(config).generate_all(&abi).await?;
+    let typescript_dir = temp_dir.path().join("typescript");
+
+    // Event fields are keyed and typed like the runtime decoder's JSON
+    let events = fs::read_to_string(typescript_dir.join("events.ts")).unwrap();
+    assert!(events.contains("export interface TransferEvent {\n  /** `address from` (indexed) */\n  from: string;\n"));
+    assert!(events.contains("  value: string;\n"));
+    assert!(events.contains("export interface TokenEvents {\n  Transfer: TransferEvent;\n  Approval: ApprovalEvent;\n}"));
+    assert!(events.contains("export type TokenEventName = keyof TokenEvents;"));
+    assert!(events.contains("  Transfer: '0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef',"));
+
+    // A function per generated REST route
+    let client = fs::read_to_string(typescript_dir.join("client.ts")).unwrap();
+    assert!(client.contains("import type { TokenEventName, TokenEvents } from './events';"));
+    assert!(client.contains("request(options, 'GET', '/contract/0xa0b86a33e6411fbae92b7b5e06e1d4320827cd8/info')"));
+    assert!(client.contains("): Promise<ApiResponse<TokenEventData[]>> {"));
+    assert!(client.contains("export function callBalanceOf(options: ClientOptions, params: { account: string }): Promise<ApiResponse<unknown>> {"));
+    assert!(client.contains("`/contract/0xa0b86a33e6411fbae92b7b5e06e1d4320827cd8/call/balanceof${queryString(jsonParams(params))}`"));
+    assert!(client.contains("export function callTotalSupply(options: ClientOptions): Promise<ApiResponse<unknown>> {"));
+    assert!(client.contains("request(options, 'POST', '/contract/0xa0b86a33e6411fbae92b7b5e06e1d4320827cd8/execute/transferfrom', params)"));
+    assert!(client.contains("export function getIndexedEvents<A = Record<string, unknown>>("));
+
+    // Fragments for the generated schema and Almanac's event type
+    let fragments = fs::read_to_string(typescript_dir.join("fragments.ts")).unwrap();
+    assert!(fragments.contains("fragment TokenEventFields on EventData {"));
+    assert!(fragments.contains("fragment TokenViewFields on TokenQuery {\n  name\n  symbol\n  decimals\n  totalsupply\n  balanceof(account: $account)\n  allowance(owner: $owner, spender: $spender)\n}"));
+    assert!(fragments.contains("fragment TokenIndexedEventFields on GraphQLEvent {"));
+    assert!(fragments.contains("  eventsConnection(filter: $filter, first: $first, after: $after) {"));
+
+    let index = fs::read_to_string(typescript_dir.join("index.ts")).unwrap();
+    assert!(index.contains("export * from './fragments';"));
+
+    Ok(())
+}
+
+/// Foundry artifact of an ERC20 with NatSpec in its metadata
+const FOUNDRY_ARTIFACT: &str = r#"{
+    "abi": [
+        {"type": "constructor", "inputs": [{"name": "supply", "type": "uint256"}], "stateMutability": "nonpayable"},
+        {"type": "function", "name": "balanceOf", "inputs": [{"name": "owner", "type": "address"}], "outputs": [{"name": "", "type": "uint256"}], "stateMutability": "view"},
+        {"type": "event", "name": "Transfer", "inputs": [
+            {"name": "from", "type": "address", "indexed": true},
+            {"name": "to", "type": "address", "indexed": true},
+            {"name": "value", "type": "uint256", "indexed": false}
+        ], "anonymous": false}
+    ],
+    "bytecode": {"object": "0x6080604052", "linkReferences": {}},
+    "deployedBytecode": {"object": "0x6080604052"},
+    "metadata": {
+        "output": {
+            "userdoc": {
+                "kind": "user",
+                "methods": {"balanceOf(address)": {"notice": "Balance of an account"}},
+                "events": {"Transfer(address,address,uint256)": {"notice": "Tokens moved between accounts"}}
+            },
+            "devdoc": {
+                "kind": "dev",
+                "title": "Example token",
+                "author": "Almanac",
+                "methods": {"balanceOf(address)": {"params": {"owner": "Account to query"}, "returns": {"_0": "Token balance"}}},
+                "events": {"Transfer(address,address,uint256)": {"params": {"value": "Amount moved"}}}
+            }
+        },
+        "settings": {"compilationTarget": {"src/Token.sol": "Token"}}
+    }
+}"#;
+
+/// Hardhat artifact with unlinked library references in its bytecode
+const HARDHAT_ARTIFACT: &str = r#"{
+    "_format": "hh-sol-artifact-1",
+    "contractName": "Vault",
+    "sourceName": "contracts/Vault.sol",
+    "abi": [
+        {"type": "constructor", "inputs": [], "stateMutability": "nonpayable"},
+        {"type": "function", "name": "deposit", "inputs": [], "outputs": [], "stateMutability": "payable"}
+    ],
+    "bytecode": "0x6080__$1234567890abcdef1234567890abcdef12$__6040",
+    "deployedBytecode": "0x6080",
+    "linkReferences": {},
+    "deployedLinkReferences": {}
+}"#;
+
+#[tokio::test]
+async fn test_artifact_parsing() -> Result<()> {
+    let parser = AbiParser::new();
+
+    let foundry = parser.parse_content(FOUNDRY_ARTIFACT)?;
+    assert_eq!(foundry.contract_name.as_deref(), Some("Token"));
+    assert_eq!(foundry.bytecode.as_deref(), Some("0x6080604052"));
+    assert_eq!(foundry.functions.len(), 1);
+    assert!(foundry.constructor.is_some());
+    assert_eq!(foundry.docs.title.as_deref(), Some("Example token"));
+    let balance_of = foundry.docs.function(&foundry.functions[0]).unwrap();
+    assert_eq!(balance_of.notice.as_deref(), Some("Balance of an account"));
+    assert_eq!(balance_of.params.get("owner").map(String::as_str), Some("Account to query"));
+
+    let hardhat = parser.parse_content(HARDHAT_ARTIFACT)?;
+    assert_eq!(hardhat.contract_name.as_deref(), Some("Vault"));
+    assert!(hardhat.bytecode.as_deref().unwrap().contains("__$"));
+    assert!(hardhat.docs.methods.is_empty());
+
+    // Bare ABIs have none of the artifact information
+    let abi = parser.parse_content(ERC20_ABI)?;
+    assert!(abi.contract_name.is_none());
+    assert!(abi.bytecode.is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_artifact_generation() -> Result<()> {
+    let temp_dir = TempDir::new().unwrap();
+    let config = EthereumCodegenConfig {
+        contract_address: "0xa0b86a33e6411fbae92b7b5e06e1d4320827cd8".to_string(),
+        chain_id: "1".to_string(),
+        output_dir: temp_dir.path().to_str().unwrap().to_string(),
+        features: vec!["client".to_string()],
+        dry_run: false,
+        templates_dir: None,
+        migration_version: None,
+        namespace: None,
+    };
+
+    let artifact_path = temp_dir.path().join("Token.json");
+    fs::write(&artifact_path, FOUNDRY_ARTIFACT).unwrap();
+    generate_contract_code(artifact_path.to_str().unwrap(), config).await?;
+
+    let client_dir = temp_dir.path().join("client");
+    let client = fs::read_to_string(client_dir.join("mod.rs")).unwrap();
+    assert!(client.contains("/// Example token"));
+    assert!(client.contains("pub struct TokenClient"));
+
+    let view = fs::read_to_string(client_dir.join("view.rs")).unwrap();
+    assert!(view.contains("    /// Balance of an account\n"));
+    assert!(view.contains("    /// - `owner`: Account to query\n"));
+    assert!(view.contains("    /// - Token balance\n"));
+
+    let types = fs::read_to_string(client_dir.join("types.rs")).unwrap();
+    assert!(types.contains("/// Tokens moved between accounts"));
+    assert!(types.contains(": Amount moved"));
+
+    // Linked bytecode is embedded instead of taken as a parameter
+    let deploy = fs::read_to_string(client_dir.join("deploy.rs")).unwrap();
+    assert!(deploy.contains("pub const BYTECODE: &[u8] = &alloy_primitives::hex!(\"6080604052\");"));
+    assert!(!deploy.contains("bytecode: Bytes"));
+    // Constructor arguments are ABI-encoded after the creation bytecode
+    assert!(deploy.contains("pub async fn deploy(&self, supply: U256) -> Result<Address>"));
+    assert!(deploy.contains("let mut creation_code = BYTECODE.to_vec();"));
+    assert!(deploy.contains(".field(&supply)"));
+    assert!(deploy.contains("self.ethereum_client().deploy_contract(&creation_code, U256::ZERO).await"));
+    assert!(!deploy.contains("todo!"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_artifacts_directory_generation() -> Result<()> {
+    let temp_dir = TempDir::new().unwrap();
+    let out_dir = temp_dir.path().join("out");
+    fs::create_dir_all(out_dir.join("Token.sol")).unwrap();
+    fs::create_dir_all(out_dir.join("Vault.sol")).unwrap();
+    fs::create_dir_all(out_dir.join("build-info")).unwrap();
+    fs::write(out_dir.join("Token.sol").join("Token.json"), FOUNDRY_ARTIFACT).unwrap();
+    fs::write(out_dir.join("Vault.sol").join("Vault.json"), HARDHAT_ARTIFACT).unwrap();
+    fs::write(out_dir.join("Vault.sol").join("Vault.dbg.json"), r#"{"buildInfo": "../build-info/1.json"}"#).unwrap();
+    fs::write(out_dir.join("build-info").join("1.json"), r#"{"abi": [{"type": "function", "name": "ignored", "inputs": [], "outputs": []}]}"#).unwrap();
+    // Interfaces without functions or events produce no code
+    fs::write(out_dir.join("Token.sol").join("IEmpty.json"), r#"{"abi": []}"#).unwrap();
+
+    let generated_dir = temp_dir.path().join("generated");
+    let config = EthereumCodegenConfig {
+        contract_address: String::new(),
+        chain_id: "1".to_string(),
+        output_dir: generated_dir.to_str().unwrap().to_string(),
+        features: vec!["client".to_string(), "migrations".to_string()],
+        dry_run: false,
+        templates_dir: None,
+        migration_version: None,
+        namespace: None,
+    };
+    generate_contract_code(out_dir.to_str().unwrap(), config).await?;
+
+    let root = fs::read_to_string(generated_dir.join("mod.rs")).unwrap();
+    assert!(root.contains("pub mod token;\npub mod vault;\n"));
+    assert!(!root.contains("pub mod i_empty;"));
+    assert!(!root.contains("ignored"));
+
+    let token = fs::read_to_string(generated_dir.join("token").join("mod.rs")).unwrap();
+    assert!(token.contains("pub mod client;"));
+    assert!(!token.contains("pub mod migrations;"));
+    assert!(generated_dir.join("token").join("migrations").is_dir());
+
+    let vault = fs::read_to_string(generated_dir.join("vault").join("client").join("mod.rs")).unwrap();
+    assert!(vault.contains("pub struct VaultClient"));
+    let deploy = fs::read_to_string(generated_dir.join("vault").join("client").join("deploy.rs")).unwrap();
+    assert!(!deploy.contains("pub const BYTECODE"));
+    assert!(deploy.contains("bytecode: Bytes"));
+    assert!(deploy.contains("self.ethereum_client().deploy_contract(&bytecode, U256::ZERO).await"));
+
+    Ok(())
+}
+
+/// EIP-1967 proxy with its admin functions and upgrade event
+const PROXY_ABI: &str = r#"[
+    {"type": "function", "name": "upgradeTo", "inputs": [{"name": "implementation", "type": "address"}], "outputs": [], "stateMutability": "nonpayable"},
+    {"type": "event", "name": "Upgraded", "inputs": [{"name": "implementation", "type": "address", "indexed": true}], "anonymous": false}
+]"#;
+
+/// First implementation, whose `Deposit` event changes in the second
+const VAULT_V1_ABI: &str = r#"[
+    {"type": "function", "name": "deposit", "inputs": [], "outputs": [], "stateMutability": "payable"},
+    {"type": "event", "name": "Deposit", "inputs": [{"name": "amount", "type": "uint256", "indexed": false}], "anonymous": false},
+    {"type": "event", "name": "Paused", "inputs": [], "anonymous": false}
+]"#;
+
+const VAULT_V2_ABI: &str = r#"[
+    {"type": "function", "name": "deposit", "inputs": [], "outputs": [], "stateMutability": "payable"},
+    {"type": "function", "name": "withdraw", "inputs": [{"name": "amount", "type": "uint256"}], "outputs": [], "stateMutability": "nonpayable"},
+    {"type": "event", "name": "Deposit", "inputs": [{"name": "account", "type": "address", "indexed": true}, {"name": "amount", "type": "uint256", "indexed": false}], "anonymous": false}
+]"#;
+
+#[tokio::test]
+async fn test_proxy_implementation_merge() -> Result<()> {
+    let parser = AbiParser::new();
+    let mut abi = parser.parse_content(PROXY_ABI)?;
+
+    // Newest implementation first, as when generating
+    assert!(abi.merge_implementation(parser.parse_content(VAULT_V2_ABI)?).is_empty());
+    let skipped = abi.merge_implementation(parser.parse_content(VAULT_V1_ABI)?);
+    assert_eq!(skipped, vec!["Deposit(uint256)".to_string()]);
+
+    let functions: Vec<&str> = abi.functions.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(functions, vec!["upgradeTo", "deposit", "withdraw"]);
+    let events: Vec<&str> = abi.events.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(events, vec!["Upgraded", "Deposit", "Paused"]);
+    assert_eq!(abi.events[1].inputs.len(), 2);
+
+    let temp_dir = TempDir::new().unwrap();
+    let proxy_path = temp_dir.path().join("proxy.json");
+    let v1_path = temp_dir.path().join("vault_v1.json");
+    let v2_path = temp_dir.path().join("vault_v2.json");
+    fs::write(&proxy_path, PROXY_ABI).unwrap();
+    fs::write(&v1_path, VAULT_V1_ABI).unwrap();
+    fs::write(&v2_path, VAULT_V2_ABI).unwrap();
+
+    let config = EthereumCodegenConfig {
+        contract_address: "0xa0b86a33e6411fbae92b7b5e06e1d4320827cd88".to_string(),
+        chain_id: "1".to_string(),
+        output_dir: temp_dir.path().join("generated").to_str().unwrap().to_string(),
+        features: vec!["handlers".to_string()],
+        dry_run: false,
+        templates_dir: None,
+        migration_version: None,
+        namespace: Some("Vault".to_string()),
+    };
+    let implementations = vec![v1_path.to_str().unwrap().to_string(), v2_path.to_str().unwrap().to_string()];
+    generate_proxy_contract_code(proxy_path.to_str().unwrap(), &implementations, config).await?;
+
+    // The handler stores the proxy's own events and those of every implementation
+    let handler = fs::read_to_string(temp_dir.path().join("generated").join("handlers").join("mod.rs")).unwrap();
+    assert!(handler.contains("pub struct VaultEventHandler"));
+    assert!(handler.contains("if *topic0 == UpgradedEvent::SIGNATURE_HASH {"));
+    assert!(handler.contains("if *topic0 == DepositEvent::SIGNATURE_HASH {"));
+    assert!(handler.contains("if *topic0 == PausedEvent::SIGNATURE_HASH {"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_provenance_and_drift_check() -> Result<()> {
+    let temp_dir = TempDir::new().unwrap();
+    let abi_path = temp_dir.path().join("erc20.json");
+    fs::write(&abi_path, ERC20_ABI).unwrap();
+    let abi_file = abi_path.to_str().unwrap();
+    let output_dir = temp_dir.path().join("generated");
+
+    let config = EthereumCodegenConfig {
+        contract_address: "0xa0b86a33e6411fbae92b7b5e06e1d4320827cd88".to_string(),
+        chain_id: "1".to_string(),
+        output_dir: output_dir.to_str().unwrap().to_string(),
+        features: vec!["client".to_string(), "migrations".to_string()],
+        dry_run: false,
+        templates_dir: None,
+        migration_version: None,
+        namespace: Some("Usdc".to_string()),
+    };
+    generate_contract_code(abi_file, config.clone()).await?;
+
+    let client = fs::read_to_string(output_dir.join("client").join("mod.rs")).unwrap();
+    let provenance = Provenance::new(&[("erc20.json", ERC20_ABI.as_bytes())]);
+    assert!(client.starts_with(&provenance.header(Path::new("mod.rs")).unwrap()));
+    let migration = fs::read_dir(output_dir.join("migrations")).unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.to_string_lossy().ends_with(".up.sql"))
+        .unwrap();
+    assert!(fs::read_to_string(&migration).unwrap().starts_with("-- @generated by almanac codegen "));
+
+    // Freshly generated code has no drift, and checking writes nothing
+    assert!(check_contract_code(abi_file, &[], config.clone()).await?.is_empty());
+
+    fs::write(output_dir.join("client").join("mod.rs"), "// edited by hand\n").unwrap();
+    fs::remove_file(output_dir.join("client").join("view.rs")).unwrap();
+    let drift = check_contract_code(abi_file, &[], config.clone()).await?;
+    assert_eq!(drift, vec![
+        Drift::Changed(output_dir.join("client").join("mod.rs")),
+        Drift::Missing(output_dir.join("client").join("view.rs")),
+    ]);
+    assert!(!output_dir.join("client").join("view.rs").exists());
+
+    // Changing the ABI changes the hash in every header
+    generate_contract_code(abi_file, config.clone()).await?;
+    fs::write(&abi_path, ERC20_ABI.replace("\"symbol\"", "\"ticker\"")).unwrap();
+    let drift = check_contract_code(abi_file, &[], config).await?;
+    assert!(!drift.is_empty());
+    assert!(drift.iter().all(|drift| matches!(drift, Drift::Changed(_))));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_custom_templates() -> Result<()> {
+    let temp_dir = TempDir::new().unwrap();
+    let templates_dir = temp_dir.path().join("templates");
+    fs::create_dir_all(templates_dir.join("client")).unwrap();
+    fs::write(templates_dir.join("header.hbs"), "//! {{contract_name}} on chain {{chain_id}}\n").unwrap();
+    // Overrides the generated client module, keeping the built-in code
+    fs::write(templates_dir.join("client").join("mod.rs.hbs"), "{{> header}}{{generated}}").unwrap();
+    // Adds a file rendered from the ABI
+    fs::write(
+        templates_dir.join("EVENTS.md.hbs"),
+        "{{#each abi.events}}{{screaming_snake_case name}}:{{#each inputs}} {{snake_case name}} {{rust_type this}} {{sql_type this}};{{/each}}\n{{/each}}",
+    ).unwrap();
+    // Overrides the migration whatever its version
+    fs::create_dir_all(templates_dir.join("migrations")).unwrap();
+    fs::write(templates_dir.join("migrations").join("usdc_events.up.sql.hbs"), "{{generated}}-- reviewed\n").unwrap();
+
+    let abi_path = temp_dir.path().join("erc20.json");
+    fs::write(&abi_path, ERC20_ABI).unwrap();
+    let output_dir = temp_dir.path().join("generated");
+    let config = EthereumCodegenConfig {
+        contract_address: "0xa0b86a33e6411fbae92b7b5e06e1d4320827cd88".to_string(),
+        chain_id: "1".to_string(),
+        output_dir: output_dir.to_str().unwrap().to_string(),
+        features: vec!["client".to_string(), "migrations".to_string()],
+        dry_run: false,
+        templates_dir: Some(templates_dir.to_str().unwrap().to_string()),
+        migration_version: None,
+        namespace: Some("usdc".to_string()),
+    };
+    generate_contract_code(abi_path.to_str().unwrap(), config.clone()).await?;
+
+    let client = fs::read_to_string(output_dir.join("client").join("mod.rs")).unwrap();
+    assert!(client.lines().nth(1) == Some("//! Usdc on chain 1"));
+    assert!(client.contains("pub struct UsdcClient"));
+    // Files without an override are generated as usual
+    assert!(fs::read_to_string(output_dir.join("client").join("view.rs")).unwrap().contains("pub async fn balanceOf"));
+
+    let events = fs::read_to_string(output_dir.join("EVENTS.md")).unwrap();
+    assert!(events.contains("TRANSFER: from Address TEXT; to Address TEXT; value U256 NUMERIC(78, 0);\n"));
+
+    let migrations: Vec<String> = fs::read_dir(output_dir.join("migrations")).unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    assert_eq!(migrations.len(), 2);
+    let up = migrations.iter().find(|name| name.ends_with("_usdc_events.up.sql")).unwrap();
+    let up_sql = fs::read_to_string(output_dir.join("migrations").join(up)).unwrap();
+    assert!(up_sql.contains("CREATE TABLE") && up_sql.ends_with("-- reviewed\n"));
+
+    // Template output is checked for drift like generated code
+    assert!(check_contract_code(abi_path.to_str().unwrap(), &[], config).await?.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reference_templates() -> Result<()> {
+    let temp_dir = TempDir::new().unwrap();
+    let abi_path = temp_dir.path().join("erc20.json");
+    fs::write(&abi_path, ERC20_ABI).unwrap();
+    let abi_file = abi_path.to_str().unwrap();
+    let templates_dir = temp_dir.path().join("templates");
+    let output_dir = temp_dir.path().join("generated");
+
+    let config = EthereumCodegenConfig {
+        contract_address: "0xa0b86a33e6411fbae92b7b5e06e1d4320827cd88".to_string(),
+        chain_id: "1".to_string(),
+        output_dir: output_dir.to_str().unwrap().to_string(),
+        namespace: Some("usdc".to_string()),
+        ..EthereumCodegenConfig::default()
+    };
+    let templates = dump_contract_templates(abi_file, &[], config.clone(), templates_dir.to_str().unwrap()).await?;
+    assert!(templates.contains(&templates_dir.join("client").join("mod.rs.hbs")));
+    // Migrations are named without their version, so the templates keep applying
+    assert!(templates.contains(&templates_dir.join("migrations").join("usdc_events.up.sql.hbs")));
+    let client = fs::read_to_string(templates_dir.join("client").join("mod.rs.hbs")).unwrap();
+    assert!(!client.contains("@generated"));
+    assert!(!output_dir.exists());
+
+    // Generating with the unedited reference templates gives the built-in output
+    let templated = EthereumCodegenConfig {
+        templates_dir: Some(templates_dir.to_str().unwrap().to_string()),
+        ..config.clone()
+    };
+    generate_contract_code(abi_file, templated).await?;
+    assert!(check_contract_code(abi_file, &[], config.clone()).await?.is_empty());
+
+    // Directories of artifacts generate several contracts under one name
+    assert!(dump_contract_templates(temp_dir.path().to_str().unwrap(), &[], config, templates_dir.to_str().unwrap()).await.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_invalid_custom_templates() {
+    let temp_dir = TempDir::new().unwrap();
+    let load = |name: &str, source: &str| {
+        fs::write(temp_dir.path().join(name), source).unwrap();
+        let result = EthereumTemplateManager::new().unwrap().with_templates_dir(temp_dir.path());
+        fs::remove_file(temp_dir.path().join(name)).unwrap();
+        result.err().map(|e| e.to_string())
+    };
+
+    assert_eq!(load("valid.rs.hbs", "{{#each abi.functions}}{{pascal_case (snake_case name)}}{{/each}}"), None);
+    assert!(load("unclosed.rs.hbs", "{{#each abi.functions}}").unwrap().contains("unclosed.rs.hbs"));
+    assert!(load("helper.rs.hbs", "{{to_upper contract_name}}").unwrap().contains("unknown helper to_upper"));
+    assert!(load("block.rs.hbs", "{{#repeat 2}}x{{/repeat}}").unwrap().contains("unknown helper repeat"));
+    assert!(load("partial.rs.hbs", "{{> missing}}").unwrap().contains("unknown partial missing"));
+    assert_eq!(load("inline.rs.hbs", "{{#*inline \"row\"}}x{{/inline}}{{> row}}"), None);
+    assert!(EthereumTemplateManager::new().unwrap().with_templates_dir(temp_dir.path().join("missing")).is_err());
+}
@@ -1 +1,4 @@
-mod integration; 
\ No newline at end of file
+mod decode;
+mod deploy;
+mod handlers;
+mod integration;
@@ -0,0 +1,549 @@
+//! TypeScript generation for frontends of a contract
+//!
+//! Generates typed event interfaces, client functions for the REST routes of
+//! the `api` feature and for Almanac's event endpoint, and GraphQL fragments
+//! for the generated schema and Almanac's event type. Event fields are keyed
+//! and typed like the JSON [`crate::decode`] produces for logs.
+
+use std::collections::HashSet;
+
+use convert_case::{Case, Casing};
+use indexer_core::codegen::{typescript_indexed_event_documents, typescript_key, TYPESCRIPT_CLIENT_RUNTIME};
+
+use super::migrations::is_value_type;
+use super::parser::{AbiEvent, AbiFunction, AbiParameter, AbiParser, EthereumAbi};
+
+/// Encoding of view function parameters in query strings, emitted when a
+/// view function has parameters
+const JSON_PARAMS: &str = r#"/** Function parameters as query values, with non-strings encoded as JSON */
+function jsonParams(params: object): Record<string, string> {
+  const values: Record<string, string> = {};
+  for (const [key, value] of Object.entries(params)) {
+    values[key] = typeof value === 'string' ? value : JSON.stringify(value);
+  }
+  return values;
+}
+"#;
+
+/// Generated TypeScript for a contract
+pub struct TypeScriptGenerator {
+    /// Prefix of the generated type names
+    contract_name: String,
+    contract_address: String,
+    chain_id: String,
+}
+
+impl TypeScriptGenerator {
+    /// Create a generator for the types of `contract_name`
+    ///
+    /// Names starting with a digit, as `0x` addresses do, get a `Contract`
+    /// prefix to stay valid identifiers.
+    pub fn new(contract_name: &str, contract_address: impl Into<String>, chain_id: impl Into<String>) -> Self {
+        let contract_name = if contract_name.starts_with(|c: char| c.is_ascii_digit()) {
+            format!("Contract{}", contract_name)
+        } else {
+            contract_name.to_string()
+        };
+        Self {
+            contract_name,
+            contract_address: contract_address.into(),
+            chain_id: chain_id.into(),
+        }
+    }
+
+    /// `typescript/events.ts`: an interface per event and the event names
+    ///
+    /// Overloaded events share their name, only the first one is typed.
+    pub fn events(&self, abi: &EthereumAbi) -> String {
+        let name = &self.contract_name;
+        let events = unique_events(abi);
+
+        let mut code = format!(
+            r#"/**
+ * Generated event types for contract: {address}
+ * Chain: {chain}
+ *
+ * Fields are keyed by parameter name, or `arg{{index}}` when unnamed, and hold
+ * the values Almanac decodes: integers as decimal strings, checksummed
+ * addresses, bytes as 0x hex, arrays as arrays and tuples as objects.
+ * Indexed strings, bytes, arrays and tuples hold the hash in their topic.
+ */
+
+/** Chain the contract is deployed on */
+export const CHAIN_ID = '{chain}';
+
+/** Address of the contract */
+export const CONTRACT_ADDRESS = '{address}';
+"#,
+            address = self.contract_address,
+            chain = self.chain_id,
+        );
+
+        for event in &events {
+            code.push('\n');
+            code.push_str(&event_interface(abi, event));
+        }
+
+        let mut entries = String::new();
+        let mut topics = String::new();
+        for event in &events {
+            entries.push_str(&format!("  {}: {};\n", typescript_key(&event.name), event_interface_name(event)));
+            if let (false, Some(signature)) = (event.anonymous, &event.signature) {
+                topics.push_str(&format!("  {}: '{}',\n", typescript_key(&event.name), signature));
+            }
+        }
+        code.push_str(&format!(
+            r#"
+/** Decoded events of the contract by name */
+export interface {name}Events {{
+{entries}}}
+
+/** Name of an event of the contract */
+export type {name}EventName = keyof {name}Events;
+
+/** Topic 0 of the logs of each non-anonymous event */
+export const EVENT_TOPICS = {{
+{topics}}} as const;
+"#,
+            name = name,
+            entries = entries,
+            topics = topics,
+        ));
+        code
+    }
+
+    /// `typescript/client.ts`: a function per generated REST route, and
+    /// Almanac's event endpoint for the contract
+    pub fn client(&self, abi: &EthereumAbi) -> String {
+        let name = &self.contract_name;
+        let base = format!("/contract/{}", self.contract_address);
+
+        let mut code = format!(
+            r#"/**
+ * Generated API client for contract: {address}
+ * Chain: {chain}
+ *
+ * Functions for the REST routes of the generated `api` module, and for the
+ * events Almanac indexes for the contract.
+ */
+
+import {{ CHAIN_ID, CONTRACT_ADDRESS }} from './events';
+import type {{ {name}EventName, {name}Events }} from './events';
+
+{runtime}
+/** Response of the generated routes */
+export interface ApiResponse<T> {{
+  success: boolean;
+  data: T | null;
+  error: string | null;
+  metadata: ApiMetadata | null;
+}}
+
+/** Metadata of a response of the generated routes */
+export interface ApiMetadata {{
+  block_number: number | null;
+  timestamp: string | null;
+  gas_used: number | null;
+  transaction_hash: string | null;
+}}
+
+/** Query parameters for pagination */
+export interface PaginationQuery {{
+  page?: number;
+  limit?: number;
+  from_block?: number;
+  to_block?: number;
+}}
+
+/** Query parameters for event filtering */
+export interface EventFilterQuery {{
+  event_name?: string;
+  from_block?: number;
+  to_block?: number;
+  topics?: string[];
+  limit?: number;
+}}
+
+/** Contract information */
+export interface ContractInfo {{
+  address: string;
+  chain_id: string;
+  contract_type: string;
+}}
+
+/** Transaction sent to the contract */
+export interface TransactionData {{
+  hash: string;
+  block_number: number;
+  block_hash: string;
+  transaction_index: number;
+  from: string;
+  to: string;
+  value: string;
+  gas: number;
+  gas_price: string;
+  input: string;
+  status: number;
+}}
+
+/** Event emitted by the contract */
+export interface EventData<N extends string = string, D = unknown> {{
+  transaction_hash: string;
+  block_number: number;
+  log_index: number;
+  event_name: N;
+  event_data: D;
+}}
+
+/** Event of the contract, typed by its name */
+export type {name}EventData = {{
+  [K in {name}EventName]: EventData<K, {name}Events[K]>;
+}}[{name}EventName];
+
+/** Log emitted by the contract */
+export interface LogData {{
+  address: string;
+  topics: string[];
+  data: string;
+  block_number: number;
+  transaction_hash: string;
+  log_index: number;
+}}
+
+/** `GET {base}/info` */
+export function getContractInfo(options: ClientOptions): Promise<ApiResponse<ContractInfo>> {{
+  return request(options, 'GET', '{base}/info');
+}}
+
+/** `GET {base}/state`, at `blockNumber` or the latest block */
+export function getContractState(options: ClientOptions, blockNumber?: number): Promise<ApiResponse<unknown>> {{
+  return request(options, 'GET', `{base}/state${{queryString({{ block_number: blockNumber }})}}`);
+}}
+
+/** `GET {base}/transactions` */
+export function getContractTransactions(
+  options: ClientOptions,
+  query: PaginationQuery = {{}},
+): Promise<ApiResponse<TransactionData[]>> {{
+  return request(options, 'GET', `{base}/transactions${{queryString(query)}}`);
+}}
+
+/** `GET {base}/events` */
+export function getContractEvents(
+  options: ClientOptions,
+  query: EventFilterQuery = {{}},
+): Promise<ApiResponse<{name}EventData[]>> {{
+  return request(options, 'GET', `{base}/events${{queryString(query)}}`);
+}}
+
+/** `GET {base}/logs` */
+export function getContractLogs(options: ClientOptions, query: EventFilterQuery = {{}}): Promise<ApiResponse<LogData[]>> {{
+  return request(options, 'GET', `{base}/logs${{queryString(query)}}`);
+}}
+"#,
+            address = self.contract_address,
+            chain = self.chain_id,
+            name = name,
+            runtime = TYPESCRIPT_CLIENT_RUNTIME,
+            base = base,
+        );
+
+        let views = unique_functions(abi, &["view", "pure"]);
+        if views.iter().any(|function| !function.inputs.is_empty()) {
+            code.push('\n');
+            code.push_str(JSON_PARAMS);
+        }
+        for function in views {
+            let route = format!("{}/call/{}", base, function.name.to_lowercase());
+            let (params, path) = if function.inputs.is_empty() {
+                (String::new(), format!("'{}'", route))
+            } else {
+                (format!(", params: {}", inputs_type(&function.inputs)), format!("`{}${{queryString(jsonParams(params))}}`", route))
+            };
+            code.push_str(&format!(
+                r#"
+/** Call `{signature}`: `GET {route}` */
+export function call{function}(options: ClientOptions{params}): Promise<ApiResponse<unknown>> {{
+  return request(options, 'GET', {path});
+}}
+"#,
+                signature = AbiParser::canonical_signature(&function.name, &function.inputs),
+                route = route,
+                function = function.name.to_case(Case::Pascal),
+                params = params,
+                path = path,
+            ));
+        }
+
+        for function in unique_functions(abi, &["nonpayable", "payable"]) {
+            let route = format!("{}/execute/{}", base, function.name.to_lowercase());
+            let (params, body) = if function.inputs.is_empty() {
+                (String::new(), "{}")
+            } else {
+                (format!(", params: {}", inputs_type(&function.inputs)), "params")
+            };
+            code.push_str(&format!(
+                r#"
+/** Execute `{signature}`: `POST {route}` */
+export function execute{function}(options: ClientOptions{params}): Promise<ApiResponse<unknown>> {{
+  return request(options, 'POST', '{route}', {body});
+}}
+"#,
+                signature = AbiParser::canonical_signature(&function.name, &function.inputs),
+                route = route,
+                function = function.name.to_case(Case::Pascal),
+                params = params,
+                body = body,
+            ));
+        }
+        code
+    }
+
+    /// `typescript/fragments.ts`: GraphQL fragments for the types of the
+    /// generated schema and for Almanac's event type
+    ///
+    /// The view function fragment passes the arguments of views as
+    /// variables of the same name, which queries spreading it declare.
+    pub fn fragments(&self, abi: &EthereumAbi) -> String {
+        let name = &self.contract_name;
+
+        let mut views = String::new();
+        for function in unique_functions(abi, &["view", "pure"]) {
+            let field = function.name.to_lowercase().to_case(Case::Camel);
+            let arguments: Vec<String> = function.inputs.iter()
+                .filter(|input| !input.name.is_empty())
+                .map(|input| {
+                    let argument = input.name.trim_start_matches('_').to_case(Case::Camel);
+                    format!("{}: ${}", argument, argument)
+                })
+                .collect();
+            if arguments.is_empty() {
+                views.push_str(&format!("  {}\n", field));
+            } else {
+                views.push_str(&format!("  {}({})\n", field, arguments.join(", ")));
+            }
+        }
+
+        let mut code = format!(
+            r#"/**
+ * Generated GraphQL fragments for contract: {address}
+ * Chain: {chain}
+ */
+
+/** Fields of the contract information */
+export const CONTRACT_INFO_FIELDS = `
+fragment {name}ContractInfoFields on ContractInfo {{
+  address
+  chainId
+  contractType
+}}
+`;
+
+/** Fields of a transaction sent to the contract */
+export const TRANSACTION_FIELDS = `
+fragment {name}TransactionFields on TransactionData {{
+  hash
+  blockNumber
+  blockHash
+  transactionIndex
+  from
+  to
+  value
+  gas
+  gasPrice
+  input
+  status
+}}
+`;
+
+/** Fields of an event emitted by the contract */
+export const EVENT_FIELDS = `
+fragment {name}EventFields on EventData {{
+  transactionHash
+  blockNumber
+  logIndex
+  eventName
+  eventData
+}}
+`;
+
+/** Events of the contract, filtered by block range and name */
+export const EVENTS_QUERY = `
+query {name}Events($fromBlock: Int, $toBlock: Int, $eventName: String, $limit: Int, $offset: Int) {{
+  events(fromBlock: $fromBlock, toBlock: $toBlock, eventName: $eventName, limit: $limit, offset: $offset) {{
+    ...{name}EventFields
+  }}
+}}
+${{EVENT_FIELDS}}`;
+"#,
+            address = self.contract_address,
+            chain = self.chain_id,
+            name = name,
+        );
+
+        if !views.is_empty() {
+            code.push_str(&format!(
+                r#"
+/** Results of the view functions of the contract */
+export const VIEW_FIELDS = `
+fragment {name}ViewFields on {name}Query {{
+{views}}}
+`;
+"#,
+                name = name,
+                views = views,
+            ));
+        }
+
+        code.push('\n');
+        code.push_str(&typescript_indexed_event_documents(name));
+        code
+    }
+
+    /// `typescript/index.ts`: re-exports of the other files
+    pub fn index(&self) -> String {
+        format!(
+            "/** Generated TypeScript for contract: {} */\n\nexport * from './events';\nexport * from './client';\nexport * from './fragments';\n",
+            self.contract_address
+        )
+    }
+}
+
+/// TypeScript type of a parameter as decoded into JSON
+pub(crate) fn param_ts_type(param: &AbiParameter) -> String {
+    if let Some(element) = param.param_type.strip_suffix(']').and_then(|array| array.rsplit_once('[')).map(|(element, _)| element) {
+        let element = AbiParameter { param_type: element.to_string(), ..param.clone() };
+        return format!("{}[]", param_ts_type(&element));
+    }
+
+    match param.param_type.as_str() {
+        "tuple" => {
+            let components = param.components.as_deref().unwrap_or_default();
+            if components.is_empty() {
+                return "Record<string, never>".to_string();
+            }
+            let fields: Vec<String> = components.iter()
+                .enumerate()
+                .map(|(i, component)| format!("{}: {}", typescript_key(&param_key(component, i)), param_ts_type(component)))
+                .collect();
+            format!("{{ {} }}", fields.join("; "))
+        }
+        "bool" => "boolean".to_string(),
+        _ => "string".to_string(),
+    }
+}
+
+/// Interface for an event, documented with its signature and NatSpec
+fn event_interface(abi: &EthereumAbi, event: &AbiEvent) -> String {
+    let mut docs = vec![format!("`{}` event", AbiParser::canonical_signature(&event.name, &event.inputs))];
+    let natspec = abi.docs.event(event);
+    if let Some(notice) = natspec.and_then(|docs| docs.notice.as_deref()) {
+        docs.push(String::new());
+        docs.push(notice.split_whitespace().collect::<Vec<_>>().join(" "));
+    }
+
+    let mut fields = String::new();
+    for (i, param) in event.inputs.iter().enumerate() {
+        let indexed = if param.indexed { " (indexed)" } else { "" };
+        let description = natspec
+            .and_then(|docs| docs.params.get(&param.name))
+            .map(|description| format!(": {}", description.split_whitespace().collect::<Vec<_>>().join(" ")))
+            .unwrap_or_default();
+        let ts_type = if param.indexed && !is_value_type(&param.param_type) {
+            "string".to_string()
+        } else {
+            param_ts_type(param)
+        };
+        fields.push_str(&format!(
+            "  /** `{} {}`{}{} */\n  {}: {};\n",
+            param.param_type, param.name, indexed, description, typescript_key(&param_key(param, i)), ts_type
+        ));
+    }
+
+    let body = if fields.is_empty() { "{}".to_string() } else { format!("{{\n{}}}", fields) };
+    format!("{}export interface {} {}\n", jsdoc(&docs), event_interface_name(event), body)
+}
+
+/// Name of the interface for an event
+fn event_interface_name(event: &AbiEvent) -> String {
+    format!("{}Event", event.name.to_case(Case::Pascal))
+}
+
+/// Inline object type for function inputs
+fn inputs_type(inputs: &[AbiParameter]) -> String {
+    let fields: Vec<String> = inputs.iter()
+        .enumerate()
+        .map(|(i, input)| format!("{}: {}", typescript_key(&param_key(input, i)), param_ts_type(input)))
+        .collect();
+    format!("{{ {} }}", fields.join("; "))
+}
+
+/// Key of a parameter in decoded objects, `arg{index}` when unnamed
+fn param_key(param: &AbiParameter, index: usize) -> String {
+    if param.name.is_empty() {
+        format!("arg{}", index)
+    } else {
+        param.name.clone()
+    }
+}
+
+/// Events with distinct names, the first of overloaded ones
+fn unique_events(abi: &EthereumAbi) -> Vec<&AbiEvent> {
+    let mut names = HashSet::new();
+    abi.events.iter().filter(|event| names.insert(event.name.as_str())).collect()
+}
+
+/// Functions of the given state mutabilities with distinct routes, the
+/// first of overloaded ones
+fn unique_functions<'a>(abi: &'a EthereumAbi, mutabilities: &[&str]) -> Vec<&'a AbiFunction> {
+    let mut routes = HashSet::new();
+    abi.functions.iter()
+        .filter(|function| mutabilities.contains(&function.state_mutability.as_str()))
+        .filter(|function| routes.insert(function.name.to_lowercase()))
+        .collect()
+}
+
+/// JSDoc comment with the given lines
+fn jsdoc(lines: &[String]) -> String {
+    if let [line] = lines {
+        return format!("/** {} */\n", line);
+    }
+    let mut comment = String::from("/**\n");
+    for line in lines {
+        if line.is_empty() {
+            comment.push_str(" *\n");
+        } else {
+            comment.push_str(&format!(" * {}\n", line));
+        }
+    }
+    comment.push_str(" */\n");
+    comment
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn param(name: &str, param_type: &str, components: Option<Vec<AbiParameter>>) -> AbiParameter {
+        AbiParameter {
+            name: name.to_string(),
+            param_type: param_type.to_string(),
+            internal_type: None,
+            components,
+            indexed: false,
+        }
+    }
+
+    #[test]
+    fn test_param_ts_type() {
+        assert_eq!(param_ts_type(&param("value", "uint256", None)), "string");
+        assert_eq!(param_ts_type(&param("flag", "bool", None)), "boolean");
+        assert_eq!(param_ts_type(&param("flags", "bool[2][]", None)), "boolean[][]");
+
+        let order = param("orders", "tuple[]", Some(vec![
+            param("maker", "address", None),
+            param("", "bool", None),
+        ]));
+        assert_eq!(param_ts_type(&order), "{ maker: string; arg1: boolean }[]");
+    }
+}
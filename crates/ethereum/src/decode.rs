@@ -0,0 +1,356 @@
+//! Runtime decoding of contract logs
+//!
+//! Decodes logs with a parsed [`EthereumAbi`] rather than generated structs,
+//! so any contract whose ABI is known at runtime can be decoded. Values are
+//! produced as JSON: integers as decimal strings to keep their precision,
+//! addresses checksummed, bytes as `0x` hex, arrays as arrays and tuples as
+//! objects keyed by component name.
+
+use alloy_primitives::{Address, B256, I256, U256};
+use serde_json::{Map, Value};
+
+use indexer_core::event::{Event, EventData, UnifiedEvent};
+use indexer_core::{Error, Result};
+
+use crate::codegen::parser::{AbiEvent, AbiParameter, EthereumAbi};
+
+/// Size of an ABI word
+const WORD: usize = 32;
+
+/// Event of `abi` whose signature hash is `topic0`
+pub fn event_by_topic<'a>(abi: &'a EthereumAbi, topic0: &B256) -> Option<&'a AbiEvent> {
+    abi.events.iter().find(|event| {
+        !event.anonymous && event.signature.as_deref().and_then(|signature| signature.parse::<B256>().ok()) == Some(*topic0)
+    })
+}
+
+/// Decode the parameters of `event` from the topics and data of a log
+///
+/// Topic 0 is expected to be the signature hash unless the event is
+/// anonymous. Indexed reference types are only available as the hash stored
+/// in their topic. Unnamed parameters are keyed `arg{index}`.
+pub fn decode_log(event: &AbiEvent, topics: &[B256], data: &[u8]) -> Result<Map<String, Value>> {
+    let mut topics = topics.iter().skip(usize::from(!event.anonymous));
+    let data_params: Vec<AbiParameter> = event.inputs.iter().filter(|param| !param.indexed).cloned().collect();
+    let mut data_values = decode_sequence(&data_params, data)?.into_iter();
+
+    let mut values = Map::new();
+    for (i, param) in event.inputs.iter().enumerate() {
+        let value = if param.indexed {
+            let topic = topics.next()
+                .ok_or_else(|| Error::invalid_data(format!("Missing topic for {} parameter {}", event.name, i)))?;
+            if is_dynamic(param) || is_array(&param.param_type) || param.param_type.starts_with("tuple") {
+                Value::String(topic.to_string())
+            } else {
+                decode_word(&param.param_type, topic.as_slice())?
+            }
+        } else {
+            data_values.next()
+                .ok_or_else(|| Error::invalid_data(format!("Missing data for {} parameter {}", event.name, i)))?
+        };
+        values.insert(param_key(param, i), value);
+    }
+    Ok(values)
+}
+
+/// Decode an EVM [`UnifiedEvent`] with `abi`, returning the name and
+/// parameters of its event
+///
+/// Returns `None` for other events and for logs of events not in `abi`.
+pub fn decode_event(abi: &EthereumAbi, event: &dyn Event) -> Result<Option<(String, Map<String, Value>)>> {
+    let Some((_, topics, data)) = evm_log(event)? else {
+        return Ok(None);
+    };
+    let Some(abi_event) = topics.first().and_then(|topic0| event_by_topic(abi, topic0)) else {
+        return Ok(None);
+    };
+    Ok(Some((abi_event.name.clone(), decode_log(abi_event, &topics, &data)?)))
+}
+
+/// Address, topics and data of a log
+pub type EvmLog = (Address, Vec<B256>, Vec<u8>);
+
+/// Log of an EVM [`UnifiedEvent`], or `None` for other events
+pub fn evm_log(event: &dyn Event) -> Result<Option<EvmLog>> {
+    let Some(EventData::Evm { topics, data, address, .. }) =
+        event.as_any().downcast_ref::<UnifiedEvent>().map(|event| &event.event_data)
+    else {
+        return Ok(None);
+    };
+    let address: Address = address.parse()
+        .map_err(|e| Error::invalid_data(format!("Invalid address in event {}: {}", event.id(), e)))?;
+    let topics = topics.iter()
+        .map(|topic| topic.parse::<B256>())
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|e| Error::invalid_data(format!("Invalid topic in event {}: {}", event.id(), e)))?;
+    let data = hex::decode(data.trim_start_matches("0x"))
+        .map_err(|e| Error::invalid_data(format!("Invalid data in event {}: {}", event.id(), e)))?;
+    Ok(Some((address, topics, data)))
+}
+
+/// Key of a parameter in decoded objects
+fn param_key(param: &AbiParameter, index: usize) -> String {
+    if param.name.is_empty() {
+        format!("arg{}", index)
+    } else {
+        param.name.clone()
+    }
+}
+
+/// Decode consecutive values encoded as a tuple: static values inline in the
+/// head, dynamic ones at an offset from the start of `data`
+fn decode_sequence(params: &[AbiParameter], data: &[u8]) -> Result<Vec<Value>> {
+    let mut head = 0;
+    let mut values = Vec::with_capacity(params.len());
+    for param in params {
+        if is_dynamic(param) {
+            let offset = read_usize(data, head)?;
+            let tail = data.get(offset..)
+                .ok_or_else(|| Error::invalid_data(format!("Offset {} out of range for {}", offset, param.param_type)))?;
+            values.push(decode_value(param, tail)?);
+            head += WORD;
+        } else {
+            let slot = data.get(head..)
+                .ok_or_else(|| Error::invalid_data(format!("Missing data for {}", param.param_type)))?;
+            values.push(decode_value(param, slot)?);
+            head += static_size(param)?;
+        }
+    }
+    Ok(values)
+}
+
+/// Decode a single value starting at `data`
+fn decode_value(param: &AbiParameter, data: &[u8]) -> Result<Value> {
+    if let Some((element, length)) = split_array(param) {
+        let (length, items) = match length {
+            Some(length) => (length, data),
+            None => (read_usize(data, 0)?, &data[WORD..]),
+        };
+        // Every element takes at least a word, so longer arrays cannot fit in
+        // the data and are rejected before anything is allocated for them
+        if length > items.len() / WORD {
+            return Err(Error::invalid_data(format!("{} of length {} out of range", param.param_type, length)));
+        }
+        let elements = vec![element; length];
+        return Ok(Value::Array(decode_sequence(&elements, items)?));
+    }
+
+    if param.param_type == "tuple" {
+        let components = param.components.as_deref().unwrap_or_default();
+        let values = decode_sequence(components, data)?;
+        return Ok(Value::Object(
+            components.iter()
+                .enumerate()
+                .map(|(i, component)| param_key(component, i))
+                .zip(values)
+                .collect(),
+        ));
+    }
+
+    match param.param_type.as_str() {
+        "string" | "bytes" => {
+            let length = read_usize(data, 0)?;
+            let bytes = data.get(WORD..WORD + length)
+                .ok_or_else(|| Error::invalid_data(format!("{} of length {} out of range", param.param_type, length)))?;
+            Ok(if param.param_type == "string" {
+                Value::String(String::from_utf8_lossy(bytes).into_owned())
+            } else {
+                Value::String(format!("0x{}", hex::encode(bytes)))
+            })
+        }
+        param_type => decode_word(param_type, read_word(data, 0)?),
+    }
+}
+
+/// Decode a value type from its 32-byte word
+fn decode_word(param_type: &str, word: &[u8]) -> Result<Value> {
+    let value = match param_type {
+        "address" => Value::String(Address::from_slice(&word[12..]).to_string()),
+        "bool" => Value::Bool(word[31] != 0),
+        "function" => Value::String(format!("0x{}", hex::encode(&word[..24]))),
+        _ if param_type.starts_with("uint") => Value::String(U256::from_be_slice(word).to_string()),
+        _ if param_type.starts_with("int") => {
+            let word: [u8; WORD] = word.try_into()
+                .map_err(|_| Error::invalid_data(format!("Invalid {} word", param_type)))?;
+            Value::String(I256::from_be_bytes(word).to_string())
+        }
+        _ if param_type.starts_with("bytes") => {
+            let size: usize = param_type["bytes".len()..].parse()
+                .ok()
+                .filter(|size| (1..=WORD).contains(size))
+                .ok_or_else(|| Error::invalid_data(format!("Unsupported type {}", param_type)))?;
+            Value::String(format!("0x{}", hex::encode(&word[..size])))
+        }
+        _ => return Err(Error::invalid_data(format!("Unsupported type {}", param_type))),
+    };
+    Ok(value)
+}
+
+/// Element type and fixed length of an array type, `None` for other types
+fn split_array(param: &AbiParameter) -> Option<(AbiParameter, Option<usize>)> {
+    let (element_type, suffix) = param.param_type.strip_suffix(']')?.rsplit_once('[')?;
+    let element = AbiParameter {
+        param_type: element_type.to_string(),
+        ..param.clone()
+    };
+    Some((element, suffix.parse().ok()))
+}
+
+fn is_array(param_type: &str) -> bool {
+    param_type.ends_with(']')
+}
+
+/// Whether a value is encoded at an offset rather than inline
+fn is_dynamic(param: &AbiParameter) -> bool {
+    match split_array(param) {
+        Some((_, None)) => true,
+        Some((element, Some(_))) => is_dynamic(&element),
+        None => match param.param_type.as_str() {
+            "string" | "bytes" => true,
+            "tuple" => param.components.iter().flatten().any(is_dynamic),
+            _ => false,
+        },
+    }
+}
+
+/// Size of a static value in the head of its tuple
+fn static_size(param: &AbiParameter) -> Result<usize> {
+    let size = match split_array(param) {
+        Some((element, Some(length))) => length.checked_mul(static_size(&element)?),
+        _ if param.param_type == "tuple" => {
+            let mut size = Some(0usize);
+            for component in param.components.iter().flatten() {
+                let component_size = static_size(component)?;
+                size = size.and_then(|size| size.checked_add(component_size));
+            }
+            size
+        }
+        _ => Some(WORD),
+    };
+    size.ok_or_else(|| Error::invalid_data(format!("Size of {} overflows", param.param_type)))
+}
+
+fn read_word(data: &[u8], position: usize) -> Result<&[u8]> {
+    data.get(position..position + WORD)
+        .ok_or_else(|| Error::invalid_data(format!("Missing ABI word at {}", position)))
+}
+
+/// Offset or length word, bounded by the data it points into
+fn read_usize(data: &[u8], position: usize) -> Result<usize> {
+    let value = U256::from_be_slice(read_word(data, position)?);
+    usize::try_from(value)
+        .ok()
+        .filter(|value| *value <= data.len())
+        .ok_or_else(|| Error::invalid_data(format!("ABI offset or length {} out of range", value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegen::parser::AbiParser;
+    use alloy_primitives::{address, b256};
+
+    const ABI: &str = r#"[
+        {"type": "event", "name": "Transfer", "inputs": [
+            {"name": "from", "type": "address", "indexed": true},
+            {"name": "to", "type": "address", "indexed": true},
+            {"name": "value", "type": "uint256", "indexed": false}
+        ], "anonymous": false},
+        {"type": "event", "name": "Batch", "inputs": [
+            {"name": "tag", "type": "string", "indexed": true},
+            {"name": "delta", "type": "int64", "indexed": false},
+            {"name": "ids", "type": "uint32[]", "indexed": false},
+            {"name": "order", "type": "tuple", "indexed": false, "components": [
+                {"name": "maker", "type": "address"},
+                {"name": "note", "type": "string"}
+            ]},
+            {"name": "", "type": "bytes4", "indexed": false}
+        ], "anonymous": false}
+    ]"#;
+
+    fn word(value: u64) -> Vec<u8> {
+        U256::from(value).to_be_bytes::<32>().to_vec()
+    }
+
+    #[test]
+    fn test_decode_value_types() {
+        let abi = AbiParser::new().parse_content(ABI).unwrap();
+        let topic0 = b256!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
+        let event = event_by_topic(&abi, &topic0).unwrap();
+        assert_eq!(event.name, "Transfer");
+
+        let from = address!("a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48");
+        let to = address!("dac17f958d2ee523a2206206994597c13d831ec7");
+        let topics = [topic0, from.into_word(), to.into_word()];
+        let values = decode_log(event, &topics, &word(1_000_000)).unwrap();
+        assert_eq!(values["from"], from.to_string());
+        assert_eq!(values["to"], to.to_string());
+        assert_eq!(values["value"], "1000000");
+
+        assert!(decode_log(event, &topics[..2], &word(1)).is_err());
+        assert!(decode_log(event, &topics, &[]).is_err());
+    }
+
+    #[test]
+    fn test_decode_dynamic_types() {
+        let abi = AbiParser::new().parse_content(ABI).unwrap();
+        let event = abi.events.iter().find(|event| event.name == "Batch").unwrap();
+        let topic0: B256 = event.signature.as_deref().unwrap().parse().unwrap();
+        let tag_hash = b256!("1111111111111111111111111111111111111111111111111111111111111111");
+        let maker = address!("a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48");
+
+        // Head: delta, offset of ids, offset of order, selector
+        let mut data = I256::try_from(-5).unwrap().to_be_bytes::<32>().to_vec();
+        data.extend(word(128));
+        data.extend(word(224));
+        let mut selector = [0u8; 32];
+        selector[..4].copy_from_slice(&[0xde, 0xad, 0xbe, 0xef]);
+        data.extend(selector);
+        // ids = [7, 8]
+        data.extend(word(2));
+        data.extend(word(7));
+        data.extend(word(8));
+        // order = (maker, "hi")
+        data.extend(maker.into_word().as_slice());
+        data.extend(word(64));
+        data.extend(word(2));
+        let mut note = [0u8; 32];
+        note[..2].copy_from_slice(b"hi");
+        data.extend(note);
+
+        let values = decode_log(event, &[topic0, tag_hash], &data).unwrap();
+        assert_eq!(values["tag"], tag_hash.to_string());
+        assert_eq!(values["delta"], "-5");
+        assert_eq!(values["ids"], serde_json::json!(["7", "8"]));
+        assert_eq!(values["order"], serde_json::json!({"maker": maker.to_string(), "note": "hi"}));
+        assert_eq!(values["arg4"], "0xdeadbeef");
+    }
+
+    #[test]
+    fn test_decode_oversized_arrays() {
+        let param = |param_type: &str| AbiParameter {
+            name: "values".to_string(),
+            param_type: param_type.to_string(),
+            indexed: false,
+            components: None,
+            internal_type: None,
+        };
+
+        // A fixed length from the ABI longer than the data
+        assert!(decode_sequence(&[param("uint256[4294967296]")], &word(1)).is_err());
+        // A dynamic length that fits in the data as bytes but not as words
+        let mut data = word(32);
+        data.extend(word(64));
+        data.extend(word(1));
+        assert!(decode_sequence(&[param("uint256[]")], &data).is_err());
+        // Static sizes that overflow are rejected rather than wrapped
+        assert!(static_size(&param("uint256[18446744073709551615][2]")).is_err());
+
+        let mut data = word(32);
+        data.extend(word(2));
+        data.extend(word(7));
+        data.extend(word(8));
+        assert_eq!(decode_sequence(&[param("uint256[]")], &data).unwrap(), vec![serde_json::json!(["7", "8"])]);
+        assert_eq!(static_size(&param("uint256[3][2]")).unwrap(), 6 * WORD);
+    }
+}
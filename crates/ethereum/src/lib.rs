@@ -5,16 +5,31 @@
 use std::sync::Arc;
 use async_trait::async_trait;
 use std::any::Any;
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 use indexer_core::{Result, Error};
 use indexer_core::event::{Event, UnifiedEvent};
+use indexer_core::reorg::{BlockHeaderSource, CanonicalBlock};
 use indexer_core::service::{EventService, EventSubscription};
 use indexer_core::types::{ChainId, EventFilter};
+use serde_json::{json, Value};
 use valence_domain_clients::evm::base_client::EvmBaseClient;
 use valence_domain_clients::clients::ethereum::EthereumClient as ValenceEthereumClient;
 use valence_domain_clients::common::transaction::TransactionResponse;
 
 pub mod codegen;
+pub mod decode;
+pub mod logs;
+pub mod proxy;
+
+use alloy_primitives::U256;
+use proxy::{Address, ContractStateReader, B256};
+
+/// Interval at which the receipt of a contract deployment is polled
+const RECEIPT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Number of times the receipt of a contract deployment is polled before
+/// giving up
+const RECEIPT_POLL_ATTEMPTS: u32 = 120;
 
 /// EVM chain configuration
 #[derive(Debug, Clone)]
@@ -78,6 +93,7 @@ impl EvmChainConfig {
 }
 
 /// Ethereum client using valence-domain-clients EVM integration
+#[derive(Clone)]
 pub struct EthereumClient {
     /// Internal valence Ethereum client
     valence_client: Arc<ValenceEthereumClient>,
@@ -85,6 +101,8 @@ pub struct EthereumClient {
     config: EvmChainConfig,
     /// Legacy chain_id for compatibility
     chain_id: ChainId,
+    /// HTTP client for JSON-RPC requests to the chain's RPC endpoint
+    http: reqwest::Client,
 }
 
 impl EthereumClient {
@@ -114,6 +132,7 @@ impl EthereumClient {
             valence_client: Arc::new(valence_client),
             chain_id: ChainId(config.chain_id.clone()),
             config,
+            http: reqwest::Client::new(),
         })
     }
     
@@ -142,6 +161,7 @@ impl EthereumClient {
             valence_client: Arc::new(valence_client),
             chain_id: ChainId(chain_id),
             config,
+            http: reqwest::Client::new(),
         })
     }
     
@@ -159,6 +179,127 @@ impl EthereumClient {
     pub fn valence_client(&self) -> &ValenceEthereumClient {
         &self.valence_client
     }
+    
+    /// Deploy a contract from its creation code, the bytecode followed by
+    /// the ABI-encoded constructor arguments, and return its address
+    ///
+    /// The creation transaction is sent with `eth_sendTransaction` from the
+    /// first account of the node, which has to hold it unlocked, and its
+    /// receipt is polled until the transaction is mined.
+    pub async fn deploy_contract(&self, creation_code: &[u8], value: U256) -> Result<Address> {
+        let accounts = self.rpc_request("eth_accounts", json!([])).await?;
+        let from = accounts.get(0).cloned()
+            .ok_or_else(|| Error::chain(&self.config.chain_id, "No account to deploy the contract from"))?;
+        let transaction = json!({
+            "from": from,
+            "data": format!("0x{}", hex::encode(creation_code)),
+            "value": value,
+        });
+        let result = self.rpc_request("eth_sendTransaction", json!([transaction])).await?;
+        let tx_hash = hex_result("eth_sendTransaction", &result)?.to_string();
+
+        for _ in 0..RECEIPT_POLL_ATTEMPTS {
+            let receipt = self.rpc_request("eth_getTransactionReceipt", json!([tx_hash])).await?;
+            if receipt.is_null() {
+                tokio::time::sleep(RECEIPT_POLL_INTERVAL).await;
+                continue;
+            }
+            if receipt["status"] != "0x1" {
+                return Err(Error::chain(&self.config.chain_id, format!("Deployment {} reverted", tx_hash)));
+            }
+            return serde_json::from_value(receipt["contractAddress"].clone())
+                .map_err(|e| Error::invalid_data(format!("Invalid contract address in receipt of {}: {}", tx_hash, e)));
+        }
+        Err(Error::chain(&self.config.chain_id, format!("Deployment {} was not mined", tx_hash)))
+    }
+    
+    /// Header of the block at a number or tag, `None` when the node has no
+    /// such block
+    async fn block_by_number(&self, block: Value) -> Result<Option<CanonicalBlock>> {
+        let header = self.rpc_request("eth_getBlockByNumber", json!([block, false])).await?;
+        if header.is_null() {
+            return Ok(None);
+        }
+        let field = |name: &str| hex_result("eth_getBlockByNumber", &header[name]).map(str::to_string);
+        Ok(Some(CanonicalBlock {
+            number: quantity_result("eth_getBlockByNumber", &header["number"])?,
+            hash: field("hash")?,
+            parent_hash: field("parentHash")?,
+            timestamp: quantity_result("eth_getBlockByNumber", &header["timestamp"])?,
+        }))
+    }
+    
+    /// Send a JSON-RPC request to the chain's RPC endpoint and return its
+    /// result
+    async fn rpc_request(&self, method: &str, params: Value) -> Result<Value> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+        let mut response: Value = self.http.post(&self.config.rpc_url)
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| Error::connection(format!("{} request to {} failed: {}", method, self.config.rpc_url, e)))?
+            .json()
+            .await
+            .map_err(|e| Error::connection(format!("Invalid {} response from {}: {}", method, self.config.rpc_url, e)))?;
+        if let Some(error) = response.get("error") {
+            return Err(Error::chain(&self.config.chain_id, format!("{} failed: {}", method, error)));
+        }
+        Ok(response["result"].take())
+    }
+}
+
+/// Hex string of a JSON-RPC result
+fn hex_result<'a>(method: &str, result: &'a Value) -> Result<&'a str> {
+    result.as_str()
+        .ok_or_else(|| Error::invalid_data(format!("{} returned {} instead of a hex string", method, result)))
+}
+
+/// Hex quantity of a JSON-RPC result
+fn quantity_result(method: &str, result: &Value) -> Result<u64> {
+    let value = hex_result(method, result)?;
+    u64::from_str_radix(value.trim_start_matches("0x"), 16)
+        .map_err(|e| Error::invalid_data(format!("{} returned invalid quantity {}: {}", method, value, e)))
+}
+
+#[async_trait]
+impl ContractStateReader for EthereumClient {
+    async fn storage_at(&self, address: Address, slot: B256, block: u64) -> Result<B256> {
+        let result = self.rpc_request("eth_getStorageAt", json!([address, slot, format!("0x{:x}", block)])).await?;
+        hex_result("eth_getStorageAt", &result)?
+            .parse()
+            .map_err(|e| Error::invalid_data(format!("Invalid eth_getStorageAt result {}: {}", result, e)))
+    }
+    
+    async fn call(&self, to: Address, data: &[u8], block: u64) -> Result<Vec<u8>> {
+        let call = json!({
+            "to": to,
+            "data": format!("0x{}", hex::encode(data)),
+        });
+        let result = self.rpc_request("eth_call", json!([call, format!("0x{:x}", block)])).await?;
+        hex::decode(hex_result("eth_call", &result)?.trim_start_matches("0x"))
+            .map_err(|e| Error::invalid_data(format!("Invalid eth_call result {}: {}", result, e)))
+    }
+}
+
+#[async_trait]
+impl BlockHeaderSource for EthereumClient {
+    async fn latest_block_number(&self) -> Result<u64> {
+        self.get_latest_block().await
+    }
+
+    async fn block_header(&self, number: u64) -> Result<CanonicalBlock> {
+        self.block_by_number(json!(format!("0x{:x}", number))).await?
+            .ok_or_else(|| Error::chain(&self.config.chain_id, format!("Block {} not found", number)))
+    }
+
+    async fn finalized_block(&self) -> Result<Option<CanonicalBlock>> {
+        self.block_by_number(json!("finalized")).await
+    }
 }
 
 /// Event adapter to convert valence TransactionResponse to almanac Event
@@ -253,32 +394,21 @@ impl EventService for EthereumClient {
         Ok(block_number)
     }
     
-    async fn get_latest_block_with_status(&self, _chain: &str, _status: indexer_core::BlockStatus) -> indexer_core::Result<u64> {
-        // For EVM chains, we'll just return the latest block for now
-        // More sophisticated block status handling can be added later
-        self.get_latest_block().await
+    async fn get_latest_block_with_status(&self, _chain: &str, status: indexer_core::BlockStatus) -> indexer_core::Result<u64> {
+        // Statuses without a block tag of their own fall back to the head
+        let tag = match status {
+            indexer_core::BlockStatus::Finalized => "finalized",
+            indexer_core::BlockStatus::Safe => "safe",
+            _ => return self.get_latest_block().await,
+        };
+        self.block_by_number(json!(tag)).await?
+            .map(|block| block.number)
+            .ok_or_else(|| Error::chain(&self.config.chain_id, format!("Chain reports no {} block", tag)))
     }
     
+    /// Subscribe to the logs of new blocks, see [`EthereumClient::subscribe_logs`]
     async fn subscribe(&self) -> indexer_core::Result<Box<dyn EventSubscription>> {
-        // TODO: Implement event subscription using valence domain client
-        // This will be implemented in Phase 2.3 - Implement Ethereum event parsing and subscription
-        
-        // For now, return a dummy subscription
-        Ok(Box::new(DummySubscription))
-    }
-}
-
-/// Dummy subscription for compilation (to be replaced with real implementation)
-struct DummySubscription;
-
-#[async_trait]
-impl EventSubscription for DummySubscription {
-    async fn next(&mut self) -> Option<Box<dyn Event>> {
-        None
-    }
-    
-    async fn close(&mut self) -> indexer_core::Result<()> {
-        Ok(())
+        Ok(Box::new(self.subscribe_logs().await?))
     }
 }
 
@@ -488,17 +618,4 @@ mod tests {
             }
         }
     }
-    
-    #[tokio::test] 
-    async fn test_dummy_subscription() {
-        let mut subscription = DummySubscription;
-        
-        // Test that subscription returns None for next
-        let event = subscription.next().await;
-        assert!(event.is_none());
-        
-        // Test that close succeeds
-        let result = subscription.close().await;
-        assert!(result.is_ok());
-    }
 } 
\ No newline at end of file
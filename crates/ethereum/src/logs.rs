@@ -0,0 +1,245 @@
+//! Log ingestion over JSON-RPC
+//!
+//! Polls `eth_getLogs` for the logs of every contract in the blocks mined
+//! since the last poll and converts them to [`UnifiedEvent`]s, stamped with
+//! the time of their block. A subscription given the chain's reorg detector
+//! refetches the logs of the blocks replacing rolled back ones.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use futures::FutureExt;
+use serde_json::{json, Value};
+use tracing::warn;
+
+use indexer_core::event::{Event, EventData, UnifiedEvent};
+use indexer_core::reorg::{BlockHeaderSource, ReorgEvent, ReorgSubscription};
+use indexer_core::service::{EventService, EventSubscription};
+use indexer_core::{Error, Result};
+
+use crate::{hex_result, quantity_result, EthereumClient};
+
+/// Interval at which subscriptions poll for the logs of new blocks
+const LOG_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Blocks requested per `eth_getLogs` call, which nodes commonly cap
+const LOG_BLOCK_RANGE: u64 = 1000;
+
+/// Blocks below the next one to poll whose delivered logs are remembered,
+/// deeper than the reorgs the detector follows
+const REORG_WINDOW: u64 = 128;
+
+impl EthereumClient {
+    /// Logs emitted in a block range, both ends included
+    pub async fn get_logs(&self, from_block: u64, to_block: u64) -> Result<Vec<UnifiedEvent>> {
+        let filter = json!({
+            "fromBlock": format!("0x{:x}", from_block),
+            "toBlock": format!("0x{:x}", to_block),
+        });
+        let result = self.rpc_request("eth_getLogs", json!([filter])).await?;
+        let logs = result.as_array()
+            .ok_or_else(|| Error::invalid_data(format!("eth_getLogs returned {} instead of an array", result)))?;
+
+        let mut timestamps = HashMap::new();
+        let mut events = Vec::with_capacity(logs.len());
+        for log in logs {
+            if log["removed"] == true {
+                continue;
+            }
+            let block_number = quantity_result("eth_getLogs", &log["blockNumber"])?;
+            let timestamp = match timestamps.get(&block_number) {
+                Some(timestamp) => *timestamp,
+                None => {
+                    let timestamp = UNIX_EPOCH + Duration::from_secs(self.block_header(block_number).await?.timestamp);
+                    timestamps.insert(block_number, timestamp);
+                    timestamp
+                }
+            };
+            events.push(log_event(&self.chain_id().0, log, timestamp)?);
+        }
+        Ok(events)
+    }
+
+    /// Subscribe to the logs of the blocks mined from the chain's head on
+    pub async fn subscribe_logs(&self) -> Result<LogSubscription> {
+        Ok(LogSubscription {
+            client: self.clone(),
+            next_block: self.get_latest_block().await?,
+            delivered: BTreeMap::new(),
+            reorgs: Vec::new(),
+            pending: VecDeque::new(),
+            closed: false,
+        })
+    }
+}
+
+/// Event of a log returned by `eth_getLogs`
+fn log_event(chain: &str, log: &Value, timestamp: SystemTime) -> Result<UnifiedEvent> {
+    let field = |name: &str| hex_result("eth_getLogs", &log[name]).map(str::to_string);
+    let log_index = quantity_result("eth_getLogs", &log["logIndex"])?;
+    let log_index = u32::try_from(log_index)
+        .map_err(|_| Error::invalid_data(format!("Log index {} out of range", log_index)))?;
+    let topics = log["topics"].as_array()
+        .ok_or_else(|| Error::invalid_data(format!("Log without topics: {}", log)))?
+        .iter()
+        .map(|topic| hex_result("eth_getLogs", topic).map(str::to_string))
+        .collect::<Result<Vec<_>>>()?;
+    let data = field("data")?;
+    let raw_data = hex::decode(data.trim_start_matches("0x"))
+        .map_err(|e| Error::invalid_data(format!("Invalid log data {}: {}", data, e)))?;
+    let tx_hash = field("transactionHash")?;
+
+    Ok(UnifiedEvent {
+        id: format!("{}:{}", tx_hash, log_index),
+        chain: chain.to_string(),
+        block_number: quantity_result("eth_getLogs", &log["blockNumber"])?,
+        block_hash: field("blockHash")?,
+        tx_hash,
+        timestamp,
+        event_type: "log".to_string(),
+        event_data: EventData::Evm {
+            topics,
+            data,
+            address: field("address")?,
+            log_index,
+        },
+        raw_data,
+        decoded: None,
+    })
+}
+
+/// Subscription polling a chain for the logs of new blocks
+///
+/// Failed polls are logged and retried at the next interval.
+pub struct LogSubscription {
+    client: EthereumClient,
+    next_block: u64,
+    /// Hash of each recent block whose logs were delivered, so that
+    /// refetching a block after a rewind skips logs already delivered
+    delivered: BTreeMap<u64, String>,
+    reorgs: Vec<Box<dyn ReorgSubscription>>,
+    pending: VecDeque<Box<dyn Event>>,
+    closed: bool,
+}
+
+impl LogSubscription {
+    /// Refetch the logs from the fork block of the reorgs a detector of the
+    /// chain announces
+    ///
+    /// The detector has to announce reorgs once storage is rolled back, as
+    /// the ingestor of the storage crate does.
+    pub fn with_reorgs(mut self, reorgs: Box<dyn ReorgSubscription>) -> Self {
+        self.reorgs.push(reorgs);
+        self
+    }
+
+    /// Fetch the logs of the blocks mined since the last poll
+    async fn poll(&mut self) -> Result<()> {
+        let latest = self.client.get_latest_block().await?;
+        if latest < self.next_block {
+            return Ok(());
+        }
+        let to_block = latest.min(self.next_block + LOG_BLOCK_RANGE - 1);
+        let events: Vec<UnifiedEvent> = self.client.get_logs(self.next_block, to_block).await?
+            .into_iter()
+            .filter(|event| self.delivered.get(&event.block_number) != Some(&event.block_hash))
+            .collect();
+        for event in &events {
+            self.delivered.insert(event.block_number, event.block_hash.clone());
+        }
+        self.pending.extend(events.into_iter().map(|event| Box::new(event) as Box<dyn Event>));
+        self.next_block = to_block + 1;
+        self.delivered = self.delivered.split_off(&self.next_block.saturating_sub(REORG_WINDOW));
+        Ok(())
+    }
+
+    /// Rewind to the fork block of the reorgs announced since the last poll
+    fn rewind_reorged(&mut self) {
+        let mut reorgs = Vec::new();
+        for subscription in &mut self.reorgs {
+            while let Some(Some(reorg)) = subscription.next().now_or_never() {
+                reorgs.push(reorg);
+            }
+        }
+        for reorg in reorgs {
+            self.rewind(&reorg);
+        }
+    }
+
+    /// Drop the pending logs a reorg rolled back and fetch again from the
+    /// first block it replaced
+    fn rewind(&mut self, reorg: &ReorgEvent) {
+        if reorg.chain_id != *self.client.chain_id() {
+            return;
+        }
+        let from_block = match reorg.reorganized_blocks.iter().map(|b| b.old_block.number).min() {
+            Some(from_block) => from_block,
+            None => return,
+        };
+        for event in self.pending.iter().filter(|event| event.block_number() >= from_block) {
+            self.delivered.remove(&event.block_number());
+        }
+        self.pending.retain(|event| event.block_number() < from_block);
+        self.next_block = self.next_block.min(from_block);
+    }
+}
+
+#[async_trait]
+impl EventSubscription for LogSubscription {
+    async fn next(&mut self) -> Option<Box<dyn Event>> {
+        while !self.closed {
+            self.rewind_reorged();
+            if let Some(event) = self.pending.pop_front() {
+                return Some(event);
+            }
+            if let Err(e) = self.poll().await {
+                warn!("Failed to poll logs of {} from block {}: {}", self.client.chain_id().0, self.next_block, e);
+            }
+            if self.pending.is_empty() {
+                tokio::time::sleep(LOG_POLL_INTERVAL).await;
+            }
+        }
+
+        None
+    }
+
+    async fn close(&mut self) -> Result<()> {
+        self.closed = true;
+        self.pending.clear();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_event() {
+        let log = json!({
+            "address": "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+            "topics": ["0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"],
+            "data": "0x0a",
+            "blockNumber": "0x10",
+            "blockHash": "0xb10c",
+            "transactionHash": "0x7a",
+            "logIndex": "0x3",
+            "removed": false,
+        });
+        let event = log_event("1", &log, UNIX_EPOCH).unwrap();
+
+        assert_eq!(event.id, "0x7a:3");
+        assert_eq!(event.block_number, 16);
+        assert_eq!(event.block_hash, "0xb10c");
+        assert_eq!(event.raw_data, [10]);
+        match event.event_data {
+            EventData::Evm { topics, address, log_index, .. } => {
+                assert_eq!(topics.len(), 1);
+                assert_eq!(address, "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48");
+                assert_eq!(log_index, 3);
+            }
+            other => panic!("Unexpected event data {:?}", other),
+        }
+    }
+}
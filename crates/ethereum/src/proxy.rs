@@ -0,0 +1,471 @@
+//! Proxy-aware ABI resolution for upgradeable contracts
+//!
+//! Detects EIP-1967, beacon and EIP-1822 (UUPS) proxies from their storage
+//! slots and tracks their `Upgraded` and `BeaconUpgraded` events, so that a
+//! proxy's logs are decoded with the implementation ABI that was active when
+//! they were emitted. Logs from before the first tracked upgrade resolve the
+//! implementation from the proxy's storage at the previous block, which
+//! needs an archive node for old blocks.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, RwLock};
+
+use alloy_primitives::b256;
+/// Address and word types of the [`ContractStateReader`] API
+pub use alloy_primitives::{Address, B256};
+use async_trait::async_trait;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use indexer_core::event::Event;
+use indexer_core::pipeline::event_index;
+use indexer_core::{Error, Result};
+
+use crate::codegen::parser::{AbiParser, EthereumAbi};
+use crate::decode::{decode_log, event_by_topic, evm_log};
+
+/// EIP-1967 implementation slot, `keccak256("eip1967.proxy.implementation") - 1`
+pub const IMPLEMENTATION_SLOT: B256 = b256!("360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc");
+
+/// EIP-1967 beacon slot, `keccak256("eip1967.proxy.beacon") - 1`
+pub const BEACON_SLOT: B256 = b256!("a3f0ad74e5423aebfd80d3ef4346578335a9a72aeaee59ff6cb3582b35133d50");
+
+/// EIP-1822 implementation slot, `keccak256("PROXIABLE")`
+pub const PROXIABLE_SLOT: B256 = b256!("c5f16f0fcc639fa48a6947836d9850f504798523bf8c9a3a87d5876cf622bcf7");
+
+/// Topic of `Upgraded(address indexed implementation)`, emitted by proxies
+/// and by beacons
+pub const UPGRADED_TOPIC: B256 = b256!("bc7cd75a20ee27fd9adebab32041f755214dbc6bffa90cc0225b39da2e5c2d3b");
+
+/// Topic of `BeaconUpgraded(address indexed beacon)`
+pub const BEACON_UPGRADED_TOPIC: B256 = b256!("1cf3b03a6cf19fa2baba4df148e9dcabedea7f8a5c07840e207e5c089be95d3e");
+
+/// Selector of a beacon's `implementation()`
+const IMPLEMENTATION_SELECTOR: [u8; 4] = [0x5c, 0x60, 0xda, 0x1b];
+
+/// Proxy standard a contract implements
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ProxyKind {
+    /// Implementation in the EIP-1967 implementation slot
+    Eip1967,
+    /// Implementation returned by the beacon in the EIP-1967 beacon slot
+    Beacon(Address),
+    /// Implementation in the EIP-1822 `PROXIABLE` slot
+    Eip1822,
+}
+
+/// Read access to contract state at a block, as given by `eth_getStorageAt`
+/// and `eth_call`
+#[async_trait]
+pub trait ContractStateReader: Send + Sync {
+    /// Value of a storage slot after `block`
+    async fn storage_at(&self, address: Address, slot: B256, block: u64) -> Result<B256>;
+
+    /// Result of calling `to` with `data` on the state after `block`
+    async fn call(&self, to: Address, data: &[u8], block: u64) -> Result<Vec<u8>>;
+}
+
+/// Detect whether `address` is a proxy after `block`, and its implementation
+pub async fn detect_proxy(
+    reader: &dyn ContractStateReader,
+    address: Address,
+    block: u64,
+) -> Result<Option<(ProxyKind, Address)>> {
+    if let Some(implementation) = slot_address(reader.storage_at(address, IMPLEMENTATION_SLOT, block).await?) {
+        return Ok(Some((ProxyKind::Eip1967, implementation)));
+    }
+    if let Some(beacon) = slot_address(reader.storage_at(address, BEACON_SLOT, block).await?) {
+        let implementation = beacon_implementation(reader, beacon, block).await?;
+        return Ok(Some((ProxyKind::Beacon(beacon), implementation)));
+    }
+    if let Some(implementation) = slot_address(reader.storage_at(address, PROXIABLE_SLOT, block).await?) {
+        return Ok(Some((ProxyKind::Eip1822, implementation)));
+    }
+    Ok(None)
+}
+
+/// Implementation a beacon returns after `block`
+async fn beacon_implementation(reader: &dyn ContractStateReader, beacon: Address, block: u64) -> Result<Address> {
+    let output = reader.call(beacon, &IMPLEMENTATION_SELECTOR, block).await?;
+    if output.len() < 32 {
+        return Err(Error::invalid_data(format!("Invalid implementation() result from beacon {}", beacon)));
+    }
+    Ok(Address::from_slice(&output[12..32]))
+}
+
+/// Address stored right-aligned in a slot, `None` for an empty slot
+fn slot_address(slot: B256) -> Option<Address> {
+    Some(Address::from_word(slot)).filter(|address| !address.is_zero())
+}
+
+/// What a proxy delegates to from some point on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Target {
+    Implementation(Address),
+    Beacon(Address),
+    /// Not a proxy, or not initialized yet
+    Unset,
+}
+
+/// Targets of a proxy or beacon, keyed by the block and log index they apply
+/// from
+type History = BTreeMap<(u64, u64), Target>;
+
+/// Log decoded with the ABI of the contract, or of the implementation
+/// active at the log for a proxy
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DecodedLog {
+    /// Contract that emitted the log
+    pub address: Address,
+    /// Implementation whose ABI decoded the log, for proxies
+    pub implementation: Option<Address>,
+    /// Event name
+    pub event: String,
+    /// Canonical event signature
+    pub signature: String,
+    /// Decoded parameters by name
+    pub params: Map<String, Value>,
+}
+
+/// Resolves the implementation of proxies at any log and decodes logs with
+/// the ABI active at the time
+///
+/// Proxies are registered with [`add_proxy`](Self::add_proxy) and ABIs with
+/// [`register_abi`](Self::register_abi), for implementations as well as for
+/// events of the proxy itself. Logs must be passed in chain order so that
+/// upgrades are seen before the logs following them. Beacon upgrades are
+/// emitted by the beacon rather than the proxy, so the beacon's logs must be
+/// passed too.
+pub struct ProxyResolver {
+    reader: Arc<dyn ContractStateReader>,
+    /// Histories of tracked proxies and beacons
+    histories: RwLock<HashMap<Address, History>>,
+    abis: RwLock<HashMap<Address, Arc<EthereumAbi>>>,
+}
+
+impl ProxyResolver {
+    /// Create a resolver reading proxy state through `reader`
+    pub fn new(reader: Arc<dyn ContractStateReader>) -> Self {
+        Self {
+            reader,
+            histories: RwLock::new(HashMap::new()),
+            abis: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Resolve logs of `proxy` through its implementation
+    pub fn add_proxy(&self, proxy: Address) {
+        self.histories.write().unwrap().entry(proxy).or_default();
+    }
+
+    /// Whether `address` was added as a proxy or seen as a beacon
+    pub fn is_tracked(&self, address: &Address) -> bool {
+        self.histories.read().unwrap().contains_key(address)
+    }
+
+    /// Decode logs of the contract or implementation at `address` with `abi`
+    pub fn register_abi(&self, address: Address, abi: EthereumAbi) {
+        self.abis.write().unwrap().insert(address, Arc::new(abi));
+    }
+
+    /// Record an upgrade of a tracked proxy or beacon, taking effect after the
+    /// log at `block` and `log_index`
+    ///
+    /// Returns whether the log was an upgrade.
+    pub fn track_log(&self, address: Address, topics: &[B256], block: u64, log_index: u64) -> bool {
+        let target = match (topics.first(), topics.get(1)) {
+            (Some(&UPGRADED_TOPIC), Some(topic)) => Target::Implementation(Address::from_word(*topic)),
+            (Some(&BEACON_UPGRADED_TOPIC), Some(topic)) => Target::Beacon(Address::from_word(*topic)),
+            _ => return false,
+        };
+        let mut histories = self.histories.write().unwrap();
+        let Some(history) = histories.get_mut(&address) else {
+            return false;
+        };
+        history.insert((block, log_index + 1), target);
+        if let Target::Beacon(beacon) = target {
+            histories.entry(beacon).or_default();
+        }
+        true
+    }
+
+    /// Forget the upgrades and state read at or after `from_block`, after the
+    /// chain reorganized from that block
+    ///
+    /// Targets from before the fork are kept, so logs of the new blocks
+    /// resolve from them or from a fresh read of the proxy's storage.
+    pub fn rollback(&self, from_block: u64) {
+        for history in self.histories.write().unwrap().values_mut() {
+            history.retain(|&(block, _), _| block < from_block);
+        }
+    }
+
+    /// Implementation of `proxy` for its log at `block` and `log_index`, or
+    /// `None` when it is not a tracked proxy or has no implementation yet
+    pub async fn implementation_at(&self, proxy: Address, block: u64, log_index: u64) -> Result<Option<Address>> {
+        match self.target_at(proxy, block, log_index).await? {
+            Some(Target::Implementation(implementation)) => Ok(Some(implementation)),
+            Some(Target::Beacon(beacon)) => match self.target_at(beacon, block, log_index).await? {
+                Some(Target::Implementation(implementation)) => Ok(Some(implementation)),
+                _ => Ok(None),
+            },
+            _ => Ok(None),
+        }
+    }
+
+    /// Target of a tracked address at a log, read from the state before the
+    /// block when no tracked upgrade precedes the log
+    async fn target_at(&self, address: Address, block: u64, log_index: u64) -> Result<Option<Target>> {
+        {
+            let histories = self.histories.read().unwrap();
+            let Some(history) = histories.get(&address) else {
+                return Ok(None);
+            };
+            if let Some((_, target)) = history.range(..=(block, log_index)).next_back() {
+                return Ok(Some(*target));
+            }
+        }
+
+        let previous = block.saturating_sub(1);
+        let beacon = self.histories.read().unwrap()
+            .values()
+            .any(|history| history.values().any(|target| *target == Target::Beacon(address)));
+        let target = if beacon {
+            match beacon_implementation(self.reader.as_ref(), address, previous).await? {
+                implementation if implementation.is_zero() => Target::Unset,
+                implementation => Target::Implementation(implementation),
+            }
+        } else {
+            match detect_proxy(self.reader.as_ref(), address, previous).await? {
+                Some((ProxyKind::Beacon(beacon), _)) => {
+                    self.histories.write().unwrap().entry(beacon).or_default();
+                    Target::Beacon(beacon)
+                }
+                Some((_, implementation)) => Target::Implementation(implementation),
+                None => Target::Unset,
+            }
+        };
+        self.histories.write().unwrap().entry(address).or_default().insert((block, 0), target);
+        Ok(Some(target))
+    }
+
+    /// Implementation active at a log of a tracked proxy, recording the log
+    /// afterwards if it upgrades the proxy
+    ///
+    /// Returns `None` for untracked contracts and proxies without an
+    /// implementation yet.
+    pub async fn resolve_log(&self, address: Address, topics: &[B256], block: u64, log_index: u64) -> Result<Option<Address>> {
+        if !self.is_tracked(&address) {
+            return Ok(None);
+        }
+        let implementation = self.implementation_at(address, block, log_index).await?;
+        self.track_log(address, topics, block, log_index);
+        Ok(implementation)
+    }
+
+    /// Resolve the implementation at an EVM
+    /// [`UnifiedEvent`](indexer_core::event::UnifiedEvent) with
+    /// [`resolve_log`](Self::resolve_log), or `None` for other events
+    pub async fn resolve_event(&self, event: &dyn Event) -> Result<Option<Address>> {
+        let Some((address, topics, _)) = evm_log(event)? else {
+            return Ok(None);
+        };
+        self.resolve_log(address, &topics, event.block_number(), u64::from(event_index(event)?)).await
+    }
+
+    /// Decode a log, first recording it if it upgrades a tracked proxy
+    ///
+    /// Logs of a proxy are decoded with the implementation ABI active at the
+    /// log, falling back to the proxy's own ABI. Returns `None` when no
+    /// registered ABI has the event.
+    pub async fn decode_log(
+        &self,
+        address: Address,
+        topics: &[B256],
+        data: &[u8],
+        block: u64,
+        log_index: u64,
+    ) -> Result<Option<DecodedLog>> {
+        let Some(topic0) = topics.first() else {
+            return Ok(None);
+        };
+        let implementation = self.resolve_log(address, topics, block, log_index).await?;
+
+        let abis: Vec<Arc<EthereumAbi>> = {
+            let registered = self.abis.read().unwrap();
+            implementation.iter().chain([&address]).filter_map(|address| registered.get(address).cloned()).collect()
+        };
+        for abi in abis {
+            if let Some(event) = event_by_topic(&abi, topic0) {
+                return Ok(Some(DecodedLog {
+                    address,
+                    implementation,
+                    event: event.name.clone(),
+                    signature: AbiParser::canonical_signature(&event.name, &event.inputs),
+                    params: decode_log(event, topics, data)?,
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Decode an EVM [`UnifiedEvent`](indexer_core::event::UnifiedEvent), or
+    /// `None` for other events
+    pub async fn decode_event(&self, event: &dyn Event) -> Result<Option<DecodedLog>> {
+        let Some((address, topics, data)) = evm_log(event)? else {
+            return Ok(None);
+        };
+        self.decode_log(address, &topics, &data, event.block_number(), u64::from(event_index(event)?)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, keccak256, U256};
+    use std::sync::Mutex;
+
+    const PROXY: Address = address!("1111111111111111111111111111111111111111");
+    const BEACON: Address = address!("2222222222222222222222222222222222222222");
+    const V1: Address = address!("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa");
+    const V2: Address = address!("bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb");
+
+    /// Slot values and beacon implementations from some block on
+    #[derive(Default)]
+    struct MockReader {
+        slots: Mutex<Vec<(Address, B256, u64, Address)>>,
+        reads: Mutex<Vec<u64>>,
+    }
+
+    impl MockReader {
+        fn set(&self, address: Address, slot: B256, from_block: u64, value: Address) {
+            self.slots.lock().unwrap().push((address, slot, from_block, value));
+        }
+
+        fn value(&self, address: Address, slot: B256, block: u64) -> Address {
+            self.slots.lock().unwrap().iter()
+                .filter(|(a, s, from, _)| *a == address && *s == slot && *from <= block)
+                .max_by_key(|(_, _, from, _)| *from)
+                .map(|(_, _, _, value)| *value)
+                .unwrap_or_default()
+        }
+    }
+
+    #[async_trait]
+    impl ContractStateReader for MockReader {
+        async fn storage_at(&self, address: Address, slot: B256, block: u64) -> Result<B256> {
+            self.reads.lock().unwrap().push(block);
+            Ok(self.value(address, slot, block).into_word())
+        }
+
+        async fn call(&self, to: Address, data: &[u8], block: u64) -> Result<Vec<u8>> {
+            assert_eq!(data, IMPLEMENTATION_SELECTOR);
+            Ok(self.value(to, B256::ZERO, block).into_word().to_vec())
+        }
+    }
+
+    fn abi(events: &str) -> EthereumAbi {
+        AbiParser::new().parse_content(&format!("[{}]", events)).unwrap()
+    }
+
+    const DEPOSIT_V1: &str = r#"{"type": "event", "name": "Deposit", "inputs": [{"name": "amount", "type": "uint256", "indexed": false}], "anonymous": false}"#;
+    const DEPOSIT_V2: &str = r#"{"type": "event", "name": "Deposit", "inputs": [{"name": "account", "type": "address", "indexed": true}, {"name": "amount", "type": "uint256", "indexed": false}], "anonymous": false}"#;
+
+    #[test]
+    fn test_slot_constants() {
+        let eip1967 = |label: &str| B256::from(U256::from_be_bytes(keccak256(label).0) - U256::from(1));
+        assert_eq!(IMPLEMENTATION_SLOT, eip1967("eip1967.proxy.implementation"));
+        assert_eq!(BEACON_SLOT, eip1967("eip1967.proxy.beacon"));
+        assert_eq!(PROXIABLE_SLOT, keccak256("PROXIABLE"));
+        assert_eq!(UPGRADED_TOPIC, keccak256("Upgraded(address)"));
+        assert_eq!(BEACON_UPGRADED_TOPIC, keccak256("BeaconUpgraded(address)"));
+        assert_eq!(IMPLEMENTATION_SELECTOR, keccak256("implementation()")[..4]);
+    }
+
+    #[tokio::test]
+    async fn test_detect_proxy() {
+        let reader = MockReader::default();
+        reader.set(PROXY, PROXIABLE_SLOT, 0, V1);
+        assert_eq!(detect_proxy(&reader, PROXY, 10).await.unwrap(), Some((ProxyKind::Eip1822, V1)));
+
+        reader.set(PROXY, BEACON_SLOT, 5, BEACON);
+        reader.set(BEACON, B256::ZERO, 0, V2);
+        assert_eq!(detect_proxy(&reader, PROXY, 10).await.unwrap(), Some((ProxyKind::Beacon(BEACON), V2)));
+
+        reader.set(PROXY, IMPLEMENTATION_SLOT, 5, V1);
+        assert_eq!(detect_proxy(&reader, PROXY, 10).await.unwrap(), Some((ProxyKind::Eip1967, V1)));
+        assert_eq!(detect_proxy(&reader, V2, 10).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_decode_across_upgrade() {
+        let reader = Arc::new(MockReader::default());
+        reader.set(PROXY, IMPLEMENTATION_SLOT, 0, V1);
+        reader.set(PROXY, IMPLEMENTATION_SLOT, 100, V2);
+        let resolver = ProxyResolver::new(reader.clone());
+        resolver.add_proxy(PROXY);
+        resolver.register_abi(V1, abi(DEPOSIT_V1));
+        resolver.register_abi(V2, abi(DEPOSIT_V2));
+
+        let v1_topic = keccak256("Deposit(uint256)");
+        let v2_topic = keccak256("Deposit(address,uint256)");
+        let amount = U256::from(7).to_be_bytes::<32>();
+
+        // Historical log from before the upgrade, resolved from storage
+        let decoded = resolver.decode_log(PROXY, &[v1_topic], &amount, 50, 0).await.unwrap().unwrap();
+        assert_eq!(decoded.implementation, Some(V1));
+        assert_eq!(decoded.signature, "Deposit(uint256)");
+        assert_eq!(decoded.params["amount"], "7");
+        assert_eq!(*reader.reads.lock().unwrap(), vec![49]);
+
+        // Upgrade to V2 at block 100, applying from the next log on
+        let upgrade = [UPGRADED_TOPIC, V2.into_word()];
+        assert_eq!(resolver.decode_log(PROXY, &upgrade, &[], 100, 3).await.unwrap(), None);
+        assert_eq!(resolver.implementation_at(PROXY, 100, 3).await.unwrap(), Some(V1));
+        assert_eq!(resolver.implementation_at(PROXY, 100, 4).await.unwrap(), Some(V2));
+
+        let account = address!("cccccccccccccccccccccccccccccccccccccccc");
+        let decoded = resolver.decode_log(PROXY, &[v2_topic, account.into_word()], &amount, 120, 0).await.unwrap().unwrap();
+        assert_eq!(decoded.implementation, Some(V2));
+        assert_eq!(decoded.params["account"], account.to_string());
+
+        // The V1 event no longer decodes after the upgrade
+        assert_eq!(resolver.decode_log(PROXY, &[v1_topic], &amount, 120, 1).await.unwrap(), None);
+        assert_eq!(reader.reads.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_beacon_upgrade() {
+        let reader = Arc::new(MockReader::default());
+        reader.set(PROXY, BEACON_SLOT, 0, BEACON);
+        reader.set(BEACON, B256::ZERO, 0, V1);
+        let resolver = ProxyResolver::new(reader);
+        resolver.add_proxy(PROXY);
+
+        assert_eq!(resolver.implementation_at(PROXY, 10, 0).await.unwrap(), Some(V1));
+        assert!(resolver.is_tracked(&BEACON));
+
+        // The beacon, not the proxy, emits the upgrade
+        assert!(resolver.track_log(BEACON, &[UPGRADED_TOPIC, V2.into_word()], 20, 0));
+        assert_eq!(resolver.implementation_at(PROXY, 20, 1).await.unwrap(), Some(V2));
+        assert_eq!(resolver.implementation_at(PROXY, 15, 0).await.unwrap(), Some(V1));
+        assert!(!resolver.track_log(V1, &[UPGRADED_TOPIC, V2.into_word()], 20, 0));
+    }
+
+    #[tokio::test]
+    async fn test_rollback_forgets_reorged_upgrade() {
+        let reader = Arc::new(MockReader::default());
+        reader.set(PROXY, IMPLEMENTATION_SLOT, 0, V1);
+        let resolver = ProxyResolver::new(reader.clone());
+        resolver.add_proxy(PROXY);
+
+        assert_eq!(resolver.implementation_at(PROXY, 50, 0).await.unwrap(), Some(V1));
+        assert!(resolver.track_log(PROXY, &[UPGRADED_TOPIC, V2.into_word()], 100, 0));
+        assert_eq!(resolver.implementation_at(PROXY, 101, 0).await.unwrap(), Some(V2));
+
+        // The block with the upgrade was reorganized away
+        resolver.rollback(100);
+        assert_eq!(resolver.implementation_at(PROXY, 101, 0).await.unwrap(), Some(V1));
+        assert_eq!(*reader.reads.lock().unwrap(), vec![49]);
+    }
+}
@@ -0,0 +1,3 @@
+-- Migration: Index events in page order, including events without a log index
+
+CREATE INDEX IF NOT EXISTS idx_events_page_order ON events (chain, block_number, COALESCE(log_index, -1), tx_hash, id);
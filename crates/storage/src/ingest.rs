@@ -0,0 +1,443 @@
+/// Ingestion of chain events into storage and the registered handlers
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, UNIX_EPOCH};
+
+use indexer_core::event::{Event, UnifiedEvent};
+use indexer_core::pipeline::{EventDecoder, EventHandler, EventHandlerRegistry};
+use indexer_core::reorg::{BlockHashReorgDetector, BlockHeaderSource, CanonicalBlock, ReorgDetector};
+use indexer_core::service::EventSubscription;
+use indexer_core::{BlockStatus, Error, Result};
+use tracing::warn;
+
+use crate::BoxedStorage;
+
+/// Stores the events of chain sources and passes them to the handlers of
+/// the contracts that emitted them
+///
+/// Events are converted to [`UnifiedEvent`] on ingestion, so storage and
+/// handlers see the same event whatever type the source produced.
+pub struct EventIngestor {
+    /// Storage the events are written to
+    storage: BoxedStorage,
+
+    /// Handlers of the indexed contracts
+    handlers: EventHandlerRegistry,
+
+    /// Handlers of every ingested event, whatever contract emitted it
+    event_handlers: Vec<Arc<dyn EventHandler>>,
+
+    /// Decoder filling in the fields of events before they are stored
+    decoder: Option<Arc<dyn EventDecoder>>,
+
+    /// Reorg detectors fed with the blocks of ingested events, by chain
+    reorg_detectors: HashMap<String, Arc<BlockHashReorgDetector>>,
+}
+
+impl EventIngestor {
+    /// Create an ingestor writing to `storage` without any handlers
+    pub fn new(storage: BoxedStorage) -> Self {
+        Self {
+            storage,
+            handlers: EventHandlerRegistry::new(),
+            event_handlers: Vec::new(),
+            decoder: None,
+            reorg_detectors: HashMap::new(),
+        }
+    }
+
+    /// Set the handlers events are dispatched to once stored
+    pub fn with_handlers(mut self, handlers: EventHandlerRegistry) -> Self {
+        self.handlers = handlers;
+        self
+    }
+
+    /// Pass every event to `handler` once stored, such as an indexer
+    /// deriving state from all chains, and roll it back on reorgs like the
+    /// contract handlers
+    ///
+    /// Events it fails on are logged and skipped.
+    pub fn with_event_handler(mut self, handler: Arc<dyn EventHandler>) -> Self {
+        self.event_handlers.push(handler);
+        self
+    }
+
+    /// Set the decoder events are decoded with before they are stored
+    pub fn with_decoder(mut self, decoder: Arc<dyn EventDecoder>) -> Self {
+        self.decoder = Some(decoder);
+        self
+    }
+
+    /// Feed the blocks of the events of the detector's chain to `detector`
+    ///
+    /// When an event shows that its block replaced another one, the chain is
+    /// rolled back in storage from that block before the event is stored.
+    pub fn with_reorg_detector(mut self, detector: Arc<BlockHashReorgDetector>) -> Self {
+        self.reorg_detectors.insert(detector.chain_id().0.clone(), detector);
+        self
+    }
+
+    /// Store an event and dispatch it to its contract's handlers, returning
+    /// whether a handler recognised it
+    pub async fn ingest(&self, event: &dyn Event) -> Result<bool> {
+        let event = self.store(event).await?;
+        self.handle_all(&event).await;
+        self.handlers.dispatch(&event).await
+    }
+
+    /// Ingest every event of `subscription` until it ends, returning the
+    /// number of events a handler recognised
+    ///
+    /// Storage errors stop ingestion. Events a handler fails on are logged
+    /// and skipped so that one bad event does not stall indexing.
+    pub async fn run(&self, subscription: &mut dyn EventSubscription) -> Result<u64> {
+        let mut handled = 0;
+        while let Some(event) = subscription.next().await {
+            let event = self.store(event.as_ref()).await?;
+            self.handle_all(&event).await;
+            match self.handlers.dispatch(&event).await {
+                Ok(true) => handled += 1,
+                Ok(false) => {}
+                Err(e) => warn!("Failed to handle event {} on {}: {}", event.id, event.chain, e),
+            }
+        }
+        Ok(handled)
+    }
+
+    /// Pass a stored event to the handlers of every event, logging failures
+    async fn handle_all(&self, event: &UnifiedEvent) {
+        for handler in &self.event_handlers {
+            if let Err(e) = handler.handle_event(event).await {
+                warn!("{} failed to handle event {} on {}: {}", handler.name(), event.id, event.chain, e);
+            }
+        }
+    }
+
+    /// Poll `source` for the headers of new blocks of `chain` every
+    /// `interval`, see [`Self::poll_blocks`]
+    ///
+    /// Failed polls are logged and retried at the next interval.
+    pub async fn track_blocks(&self, chain: &str, source: &dyn BlockHeaderSource, interval: Duration) -> Result<()> {
+        let mut from = source.latest_block_number().await?;
+        loop {
+            match self.poll_blocks(chain, source, from).await {
+                Ok(latest) => from = latest,
+                Err(e) => warn!("Failed to poll blocks of {} from {}: {}", chain, from, e),
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    /// Feed the headers of the blocks of `chain` from `from` to the latest
+    /// one to the chain's reorg detector, returning the latest block number
+    ///
+    /// A header replacing a recorded block is rolled back like the block of
+    /// an event. When the parent of a header is not the recorded block below
+    /// it, the headers below are fetched down to the block the chain forked
+    /// from, so reorgs replacing blocks without events are caught too.
+    /// Polling from the previous latest block catches a replaced tip.
+    ///
+    /// The headers are stored as the chain's latest blocks, and the chain's
+    /// finalized block, if it reports one, as finalized.
+    pub async fn poll_blocks(&self, chain: &str, source: &dyn BlockHeaderSource, from: u64) -> Result<u64> {
+        let detector = self.reorg_detectors.get(chain)
+            .ok_or_else(|| Error::config(format!("No reorg detector for chain {}", chain)))?;
+        let latest = source.latest_block_number().await?;
+        for number in from..=latest {
+            let mut header = source.block_header(number).await?;
+            let mut descendants = Vec::new();
+            while !detector.extends_recorded(&header) && (descendants.len() as u64) < detector.config().max_depth {
+                let parent = source.block_header(header.number - 1).await?;
+                descendants.push(std::mem::replace(&mut header, parent));
+            }
+            for header in std::iter::once(header).chain(descendants.into_iter().rev()) {
+                self.observe_block(chain, detector, header.clone()).await?;
+                self.storage.store_block(chain, &header, BlockStatus::Latest).await?;
+            }
+        }
+        if let Some(finalized) = source.finalized_block().await? {
+            self.storage.store_block(chain, &finalized, BlockStatus::Finalized).await?;
+        }
+        Ok(latest)
+    }
+
+    /// Convert and decode an event and write it to storage
+    ///
+    /// Reorgs are handled before decoding, so the decoder no longer sees the
+    /// orphaned blocks. Events the decoder fails on are logged and stored
+    /// undecoded.
+    async fn store(&self, event: &dyn Event) -> Result<UnifiedEvent> {
+        let mut event = UnifiedEvent::from_event(event);
+        self.detect_reorg(&event).await?;
+        if let Some(decoder) = &self.decoder {
+            match decoder.decode(&event).await {
+                Ok(Some(decoded)) => event.decoded = Some(decoded),
+                Ok(None) => {}
+                Err(e) => warn!("Failed to decode event {} on {}: {}", event.id, event.chain, e),
+            }
+        }
+        self.storage.store_event(&event.chain, Box::new(event.clone())).await?;
+        Ok(event)
+    }
+
+    /// Roll back storage, the decoder and the handlers when the block of an
+    /// event replaced a recorded one
+    async fn detect_reorg(&self, event: &UnifiedEvent) -> Result<()> {
+        let detector = match self.reorg_detectors.get(&event.chain) {
+            Some(detector) => detector,
+            None => return Ok(()),
+        };
+        // Events don't carry the parent of their block, the polled headers do
+        let block = CanonicalBlock {
+            number: event.block_number,
+            hash: event.block_hash.clone(),
+            parent_hash: String::new(),
+            timestamp: event.timestamp.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+        };
+        self.observe_block(&event.chain, detector, block).await
+    }
+
+    /// Record a block with the chain's detector, rolling back storage, the
+    /// decoder and the handlers when it replaced a recorded one
+    ///
+    /// The reorg is announced once rolled back, so subscribers reading
+    /// storage from the fork block no longer see the replaced events.
+    async fn observe_block(&self, chain: &str, detector: &BlockHashReorgDetector, block: CanonicalBlock) -> Result<()> {
+        let number = block.number;
+        if let Some(reorg) = detector.detect_block(block) {
+            let from_block = reorg.reorganized_blocks.iter()
+                .map(|b| b.old_block.number)
+                .min()
+                .unwrap_or(number);
+            warn!("Reorg on {} from block {}, rolling back stored events", chain, from_block);
+            self.storage.reorg_chain(chain, from_block).await?;
+            if let Some(decoder) = &self.decoder {
+                decoder.rollback(chain, from_block).await?;
+            }
+            self.handlers.rollback(chain, from_block).await?;
+            for handler in &self.event_handlers {
+                handler.rollback(chain, from_block).await?;
+            }
+            detector.announce(&reorg);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "rocks"))]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use std::time::SystemTime;
+
+    use async_trait::async_trait;
+    use indexer_core::event::EventData;
+    use indexer_core::reorg::ReorgConfig;
+    use indexer_core::service::EventService;
+    use indexer_core::types::ChainId;
+
+    use crate::rocks::{RocksConfig, RocksStorage};
+    use crate::service::StorageEventService;
+
+    fn event(id: &str, block_number: u64, block_hash: &str) -> UnifiedEvent {
+        UnifiedEvent {
+            id: id.to_string(),
+            chain: "ethereum".to_string(),
+            block_number,
+            block_hash: block_hash.to_string(),
+            tx_hash: format!("0x{}", id),
+            timestamp: SystemTime::UNIX_EPOCH,
+            event_type: "Transfer".to_string(),
+            event_data: EventData::Evm {
+                topics: Vec::new(),
+                data: "0x".to_string(),
+                address: "0x01".to_string(),
+                log_index: 0,
+            },
+            raw_data: Vec::new(),
+            decoded: None,
+        }
+    }
+
+    /// Chain whose block at each height has the given hash and the block
+    /// below it as parent
+    struct MockChain {
+        hashes: Mutex<Vec<String>>,
+    }
+
+    impl MockChain {
+        fn new(hashes: &[&str]) -> Self {
+            Self { hashes: Mutex::new(hashes.iter().map(|hash| hash.to_string()).collect()) }
+        }
+    }
+
+    #[async_trait]
+    impl BlockHeaderSource for MockChain {
+        async fn latest_block_number(&self) -> Result<u64> {
+            Ok(self.hashes.lock().unwrap().len() as u64 - 1)
+        }
+
+        async fn block_header(&self, number: u64) -> Result<CanonicalBlock> {
+            let hashes = self.hashes.lock().unwrap();
+            Ok(CanonicalBlock {
+                number,
+                hash: hashes[number as usize].clone(),
+                parent_hash: number.checked_sub(1).map(|parent| hashes[parent as usize].clone()).unwrap_or_default(),
+                timestamp: 0,
+            })
+        }
+
+        /// Blocks two below the tip are final
+        async fn finalized_block(&self) -> Result<Option<CanonicalBlock>> {
+            match self.latest_block_number().await?.checked_sub(2) {
+                Some(number) => self.block_header(number).await.map(Some),
+                None => Ok(None),
+            }
+        }
+    }
+
+    fn rocks_storage(name: &str) -> (BoxedStorage, std::path::PathBuf) {
+        let path = std::env::temp_dir().join(name);
+        if path.exists() {
+            std::fs::remove_dir_all(&path).unwrap();
+        }
+        let storage: BoxedStorage = Arc::new(RocksStorage::new(RocksConfig {
+            path: path.to_str().unwrap().to_string(),
+            create_if_missing: true,
+            cache_size_mb: 0,
+        }).unwrap());
+        (storage, path)
+    }
+
+    #[tokio::test]
+    async fn test_reorged_blocks_are_rolled_back() {
+        let (storage, path) = rocks_storage("ingest_reorg_test");
+        let detector = Arc::new(BlockHashReorgDetector::new(ChainId::from("ethereum"), ReorgConfig::default()));
+        let mut reorgs = detector.subscribe().await.unwrap();
+        let ingestor = EventIngestor::new(storage.clone()).with_reorg_detector(detector);
+
+        ingestor.ingest(&event("a", 10, "0x10")).await.unwrap();
+        ingestor.ingest(&event("b", 11, "0x11")).await.unwrap();
+        ingestor.ingest(&event("c", 11, "0x11b")).await.unwrap();
+
+        let ids: Vec<String> = storage.get_events("ethereum", 0, 20).await.unwrap()
+            .iter()
+            .map(|e| e.id().to_string())
+            .collect();
+        assert_eq!(ids, ["a", "c"]);
+        assert_eq!(reorgs.next().await.unwrap().reorganized_blocks[0].old_block.hash, "0x11");
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    /// Handler recording the events it sees and the rollbacks
+    #[derive(Default)]
+    struct RecordingHandler {
+        seen: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl EventHandler for RecordingHandler {
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        async fn handle_event(&self, event: &dyn Event) -> Result<bool> {
+            self.seen.lock().unwrap().push(event.id().to_string());
+            Ok(true)
+        }
+
+        async fn rollback(&self, chain: &str, from_block: u64) -> Result<()> {
+            self.seen.lock().unwrap().push(format!("rollback {} {}", chain, from_block));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_event_handlers_see_every_event_and_reorg() {
+        let (storage, path) = rocks_storage("ingest_event_handler_test");
+        let detector = Arc::new(BlockHashReorgDetector::new(ChainId::from("ethereum"), ReorgConfig::default()));
+        let handler = Arc::new(RecordingHandler::default());
+        let ingestor = EventIngestor::new(storage)
+            .with_reorg_detector(detector)
+            .with_event_handler(handler.clone());
+
+        // No contract handler is registered, so none recognises the events
+        assert!(!ingestor.ingest(&event("a", 10, "0x10")).await.unwrap());
+        ingestor.ingest(&event("b", 11, "0x11")).await.unwrap();
+        ingestor.ingest(&event("c", 11, "0x11b")).await.unwrap();
+
+        assert_eq!(*handler.seen.lock().unwrap(), ["a", "b", "rollback ethereum 11", "c"]);
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_reorgs_without_events_are_caught_from_headers() {
+        let (storage, path) = rocks_storage("ingest_header_reorg_test");
+        let detector = Arc::new(BlockHashReorgDetector::new(ChainId::from("ethereum"), ReorgConfig::default()));
+        let mut reorgs = detector.subscribe().await.unwrap();
+        let ingestor = EventIngestor::new(storage.clone()).with_reorg_detector(detector);
+
+        let chain = MockChain::new(&["0x0", "0x1", "0x2", "0x3"]);
+        ingestor.ingest(&event("a", 1, "0x1")).await.unwrap();
+        ingestor.ingest(&event("b", 2, "0x2")).await.unwrap();
+        assert_eq!(ingestor.poll_blocks("ethereum", &chain, 0).await.unwrap(), 3);
+        assert_eq!(storage.get_latest_block("ethereum").await.unwrap(), 3);
+        assert_eq!(storage.get_latest_block_with_status("ethereum", BlockStatus::Finalized).await.unwrap(), 1);
+
+        // Blocks 2 and 3 are replaced by blocks without events
+        *chain.hashes.lock().unwrap() = ["0x0", "0x1", "0x2b", "0x3b", "0x4b"].map(String::from).to_vec();
+        assert_eq!(ingestor.poll_blocks("ethereum", &chain, 3).await.unwrap(), 4);
+
+        let ids: Vec<String> = storage.get_events("ethereum", 0, 20).await.unwrap()
+            .iter()
+            .map(|e| e.id().to_string())
+            .collect();
+        assert_eq!(ids, ["a"]);
+        let replaced: Vec<String> = reorgs.next().await.unwrap().reorganized_blocks.into_iter()
+            .map(|b| b.old_block.hash)
+            .collect();
+        assert_eq!(replaced, ["0x2", "0x3"]);
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_subscriptions_deliver_replacement_events() {
+        let (storage, path) = rocks_storage("ingest_subscription_reorg_test");
+        let detector = Arc::new(BlockHashReorgDetector::new(ChainId::from("ethereum"), ReorgConfig::default()));
+        let service = StorageEventService::new("ethereum", storage.clone())
+            .with_poll_interval(Duration::from_millis(10))
+            .with_reorg_detector(detector.clone());
+        let ingestor = EventIngestor::new(storage).with_reorg_detector(detector);
+        let mut subscription = service.subscribe().await.unwrap();
+
+        ingestor.ingest(&event("a", 10, "0x10")).await.unwrap();
+        ingestor.ingest(&event("b", 11, "0x11")).await.unwrap();
+        assert_eq!(subscription.next().await.unwrap().id(), "a");
+        assert_eq!(subscription.next().await.unwrap().id(), "b");
+
+        // The replacement sits below the last delivered event
+        ingestor.ingest(&event("a2", 10, "0x10b")).await.unwrap();
+        assert_eq!(subscription.next().await.unwrap().id(), "a2");
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_subscriptions_deliver_events_of_every_chain() {
+        let (storage, path) = rocks_storage("ingest_subscription_chains_test");
+        let service = StorageEventService::new("ethereum", storage.clone())
+            .with_chain("base")
+            .with_poll_interval(Duration::from_millis(10));
+        let ingestor = EventIngestor::new(storage);
+        let mut subscription = service.subscribe().await.unwrap();
+        assert_eq!(service.chain_ids(), [ChainId::from("ethereum"), ChainId::from("base")]);
+
+        // Base sorts before the chain already delivered up to block 100
+        ingestor.ingest(&event("a", 100, "0x100")).await.unwrap();
+        assert_eq!(subscription.next().await.unwrap().id(), "a");
+        let mut base_event = event("b", 5, "0x5");
+        base_event.chain = "base".to_string();
+        ingestor.ingest(&base_event).await.unwrap();
+        assert_eq!(subscription.next().await.unwrap().id(), "b");
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+}
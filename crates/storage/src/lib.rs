@@ -8,6 +8,8 @@ pub use indexer_core::{Error, Result, BlockStatus};
 
 // Common module imports
 use indexer_core::event::Event;
+use indexer_core::reorg::CanonicalBlock;
+use indexer_core::types::EventPage;
 use serde::{Serialize, Deserialize};
 use std::sync::Arc;
 
@@ -19,6 +21,8 @@ pub mod rocks;
 pub mod postgres;
 
 // Common modules
+pub mod ingest;
+pub mod service;
 pub mod sync;
 
 // For testing only
@@ -38,6 +42,18 @@ pub trait Storage: Send + Sync {
     /// Get events by chain and block range
     async fn get_events(&self, chain: &str, from_block: u64, to_block: u64) -> Result<Vec<Box<dyn Event>>>;
     
+    /// Get a page of the events matching a filter in [`EventPosition`]
+    /// order
+    ///
+    /// [`EventPosition`]: indexer_core::types::EventPosition
+    async fn get_events_page(&self, filter: &EventFilter, page: &EventPage) -> Result<Vec<Box<dyn Event>>>;
+    
+    /// Number of stored events matching a filter, or `None` when counting
+    /// them would need a full scan
+    async fn count_events(&self, _filter: &EventFilter) -> Result<Option<u64>> {
+        Ok(None)
+    }
+    
     /// Get the latest block height for a chain
     async fn get_latest_block(&self, chain: &str) -> Result<u64>;
     
@@ -50,6 +66,10 @@ pub trait Storage: Send + Sync {
     /// Update block status
     async fn update_block_status(&self, chain: &str, block_number: u64, status: BlockStatus) -> Result<()>;
     
+    /// Record a block header polled from a chain with its status, raising
+    /// the chain's latest block to it
+    async fn store_block(&self, chain: &str, block: &CanonicalBlock, status: BlockStatus) -> Result<()>;
+    
     /// Get events with specific block status
     async fn get_events_with_status(&self, chain: &str, from_block: u64, to_block: u64, status: BlockStatus) -> Result<Vec<Box<dyn Event>>>;
     
@@ -186,6 +206,18 @@ pub trait Storage: Send + Sync {
         error: Option<String>,
     ) -> Result<()>;
 
+    /// Retrieves a processor message by its ID.
+    async fn get_valence_processor_message(&self, message_id: &str) -> Result<Option<ValenceProcessorMessage>>;
+
+    /// Retrieves messages submitted to a processor, newest first, optionally filtered by status.
+    async fn get_valence_processor_messages(
+        &self,
+        processor_id: &str,
+        status: Option<ValenceMessageStatus>,
+        limit: Option<usize>,
+        offset: Option<usize>,
+    ) -> Result<Vec<ValenceProcessorMessage>>;
+
     /// Retrieves the current state of a Valence Processor.
     async fn get_valence_processor_state(&self, processor_id: &str) -> Result<Option<ValenceProcessorState>>;
 
@@ -263,6 +295,25 @@ pub trait Storage: Send + Sync {
         reason: Option<String>,
     ) -> Result<()>;
 
+    /// Retrieves the current state of a Valence authorization contract, including its active grants.
+    async fn get_valence_authorization_state(&self, auth_id: &str) -> Result<Option<ValenceAuthorizationState>>;
+
+    /// Retrieves the grants recorded for an authorization contract.
+    async fn get_valence_authorization_grants(
+        &self,
+        auth_id: &str,
+        active_only: bool,
+    ) -> Result<Vec<ValenceAuthorizationGrant>>;
+
+    /// Retrieves authorization requests, newest first, optionally filtered by decision.
+    async fn get_valence_authorization_requests(
+        &self,
+        auth_id: &str,
+        decision: Option<ValenceAuthorizationDecision>,
+        limit: Option<usize>,
+        offset: Option<usize>,
+    ) -> Result<Vec<ValenceAuthorizationRequest>>;
+
     // Valence Library data models
 
     /// Stores information about a new Valence Library contract instantiation.
@@ -338,6 +389,12 @@ pub trait Storage: Send + Sync {
     
     /// Get historical processor state for a specific block
     async fn get_historical_processor_state(&self, chain: &str, block_number: u64) -> Result<Option<String>>;
+    
+    /// Store the serialized schema of a contract, replacing the previous one
+    async fn store_contract_schema(&self, chain: &str, address: &str, schema_data: &[u8]) -> Result<()>;
+    
+    /// Get the serialized schema of a contract
+    async fn get_contract_schema(&self, chain: &str, address: &str) -> Result<Option<Vec<u8>>>;
 }
 
 // Storage factory function
@@ -713,6 +770,7 @@ pub mod storage_defaults {
 
 // Re-export the EventFilter from indexer-core to avoid duplication
 pub use indexer_core::types::EventFilter;
+pub use service::StorageEventService;
 
 // Other exports for convenience
 #[cfg(feature = "rocks")]
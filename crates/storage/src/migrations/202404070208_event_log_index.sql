@@ -0,0 +1,5 @@
+-- Migration: Add the log index events are paged by
+
+ALTER TABLE events ADD COLUMN IF NOT EXISTS log_index INTEGER;
+
+CREATE INDEX IF NOT EXISTS idx_events_chain_block_log_index ON events (chain, block_number, log_index);
@@ -0,0 +1,3 @@
+-- Migration: Add the fields events were decoded to with registered contract schemas
+
+ALTER TABLE events ADD COLUMN IF NOT EXISTS decoded JSONB;
@@ -0,0 +1,5 @@
+-- Migration: Add the address of the contract that emitted each event
+
+ALTER TABLE events ADD COLUMN IF NOT EXISTS address TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_events_address ON events (address);
@@ -4,7 +4,7 @@ use crate::{
     ValenceProcessorInfo, ValenceProcessorConfig, ValenceProcessorMessage, ValenceMessageStatus,
     ValenceProcessorState, ValenceAuthorizationInfo, ValenceAuthorizationPolicy, ValenceAuthorizationGrant,
     ValenceAuthorizationRequest, ValenceAuthorizationDecision, ValenceLibraryInfo, ValenceLibraryVersion,
-    ValenceLibraryUsage, ValenceLibraryState, ValenceLibraryApproval
+    ValenceLibraryUsage, ValenceLibraryState, ValenceLibraryApproval, ValenceAuthorizationState
 };
 #[cfg(feature = "postgres")]
 use tracing::{debug, info, warn, instrument};
@@ -17,7 +17,9 @@ use indexer_core::{Error, Result, BlockStatus};
 #[cfg(feature = "postgres")]
 use indexer_core::event::Event;
 #[cfg(feature = "postgres")]
-use indexer_core::types::{ChainId, EventFilter};
+use indexer_core::reorg::CanonicalBlock;
+#[cfg(feature = "postgres")]
+use indexer_core::types::{ChainId, EventFilter, EventPage};
 #[cfg(feature = "postgres")]
 use sqlx::{Pool, Postgres};
 #[cfg(feature = "postgres")]
@@ -96,12 +98,18 @@ impl Storage for PostgresStorage {
         filter.chain_ids = Some(vec![ChainId::from(chain)]);
         filter.chain = Some(chain.to_string());
         filter.block_range = Some((from_block, to_block));
-        filter.limit = Some(1);
-        filter.offset = Some(0);
         // Get events using the repository
         self.event_repository.get_events(vec![filter]).await
     }
     
+    async fn get_events_page(&self, filter: &EventFilter, page: &EventPage) -> Result<Vec<Box<dyn Event>>> {
+        self.event_repository.get_events_page(filter, page).await
+    }
+    
+    async fn count_events(&self, filter: &EventFilter) -> Result<Option<u64>> {
+        self.event_repository.count_events(filter).await.map(Some)
+    }
+    
     async fn get_latest_block(&self, chain: &str) -> Result<u64> {
         // Get the latest block using the repository
         self.event_repository.get_latest_block(chain).await
@@ -158,6 +166,30 @@ impl Storage for PostgresStorage {
         Ok(())
     }
     
+    async fn store_block(&self, chain: &str, block: &CanonicalBlock, status: BlockStatus) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO blocks (chain, number, hash, parent_hash, timestamp, status)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (chain, number) DO UPDATE SET
+                hash = EXCLUDED.hash,
+                parent_hash = EXCLUDED.parent_hash,
+                timestamp = EXCLUDED.timestamp,
+                status = EXCLUDED.status
+            "#
+        )
+        .bind(chain)
+        .bind(block.number as i64)
+        .bind(&block.hash)
+        .bind(&block.parent_hash)
+        .bind(block.timestamp as i64)
+        .bind(status.as_str())
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(())
+    }
+    
     async fn get_latest_block_with_status(&self, chain: &str, status: BlockStatus) -> Result<u64> {
         // Convert enum to string
         let status_str = status.as_str();
@@ -631,6 +663,58 @@ impl Storage for PostgresStorage {
         Ok(())
     }
     
+    async fn get_valence_processor_message(&self, message_id: &str) -> Result<Option<ValenceProcessorMessage>> {
+        let row = sqlx::query(
+            r#"
+            SELECT
+                id, processor_id, source_chain_id, target_chain_id, sender_address,
+                payload, status::TEXT AS status, created_at_block, created_at_tx, last_updated_block,
+                processed_at_block, processed_at_tx, retry_count, next_retry_block,
+                gas_used, error
+            FROM processor_messages
+            WHERE id = $1
+            "#
+        )
+        .bind(message_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        Ok(row.map(|row| processor_message_from_row(&row)))
+    }
+    
+    async fn get_valence_processor_messages(
+        &self,
+        processor_id: &str,
+        status: Option<ValenceMessageStatus>,
+        limit: Option<usize>,
+        offset: Option<usize>,
+    ) -> Result<Vec<ValenceProcessorMessage>> {
+        let limit = limit.unwrap_or(100);
+        let offset = offset.unwrap_or(0);
+        
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                id, processor_id, source_chain_id, target_chain_id, sender_address,
+                payload, status::TEXT AS status, created_at_block, created_at_tx, last_updated_block,
+                processed_at_block, processed_at_tx, retry_count, next_retry_block,
+                gas_used, error
+            FROM processor_messages
+            WHERE processor_id = $1 AND ($2::TEXT IS NULL OR status::TEXT = $2)
+            ORDER BY created_at_block DESC, id
+            LIMIT $3 OFFSET $4
+            "#
+        )
+        .bind(processor_id)
+        .bind(status.as_ref().map(message_status_str))
+        .bind(limit as i64)
+        .bind(offset as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(rows.iter().map(processor_message_from_row).collect())
+    }
+    
     async fn get_valence_processor_state(&self, processor_id: &str) -> Result<Option<ValenceProcessorState>> {
         let result: Option<(String, String, String, Option<String>, Option<i64>, Option<i64>, Option<i64>, Option<i32>, bool, i64, String)> = sqlx::query_as(
             r#"
@@ -1094,6 +1178,123 @@ impl Storage for PostgresStorage {
         Ok(())
     }
 
+    async fn get_valence_authorization_state(&self, auth_id: &str) -> Result<Option<ValenceAuthorizationState>> {
+        let contract: Option<(String, String, Option<String>, Option<String>, i64, String)> = sqlx::query_as(
+            r#"
+            SELECT chain_id, contract_address, current_owner, active_policy_id,
+                   last_updated_block, last_updated_tx
+            FROM valence_authorizations
+            WHERE id = $1
+            "#
+        )
+        .bind(auth_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        if let Some((chain_id, address, current_owner, active_policy_id, last_block, last_tx)) = contract {
+            let active_grants = self.get_valence_authorization_grants(auth_id, true).await?;
+            
+            Ok(Some(ValenceAuthorizationState {
+                auth_id: auth_id.to_string(),
+                chain_id,
+                address,
+                current_owner,
+                active_policy_id,
+                active_grants,
+                last_update_block: last_block as u64,
+                last_update_tx: last_tx,
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+    
+    async fn get_valence_authorization_grants(
+        &self,
+        auth_id: &str,
+        active_only: bool,
+    ) -> Result<Vec<ValenceAuthorizationGrant>> {
+        let grants: Vec<(String, String, String, String, String, i64, String, Option<i64>, bool, Option<i64>, Option<String>)> = sqlx::query_as(
+            r#"
+            SELECT id, auth_id, grantee, permissions, resources, granted_at_block,
+                   granted_at_tx, expiry, is_active, revoked_at_block, revoked_at_tx
+            FROM authorization_grants
+            WHERE auth_id = $1 AND (is_active = true OR $2 = false)
+            ORDER BY granted_at_block DESC
+            "#
+        )
+        .bind(auth_id)
+        .bind(active_only)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        let grant_objects: Vec<ValenceAuthorizationGrant> = grants.into_iter().map(|(gid, auth_id, grantee, permissions_json, resources_json, granted_block, granted_tx, expiry, is_active, revoked_block, revoked_tx)| {
+            ValenceAuthorizationGrant {
+                id: gid,
+                auth_id,
+                grantee,
+                permissions: serde_json::from_str(&permissions_json).unwrap_or_default(),
+                resources: serde_json::from_str(&resources_json).unwrap_or_default(),
+                granted_at_block: granted_block as u64,
+                granted_at_tx: granted_tx,
+                expiry: expiry.map(|e| e as u64),
+                is_active,
+                revoked_at_block: revoked_block.map(|b| b as u64),
+                revoked_at_tx: revoked_tx,
+            }
+        }).collect();
+        
+        Ok(grant_objects)
+    }
+    
+    async fn get_valence_authorization_requests(
+        &self,
+        auth_id: &str,
+        decision: Option<ValenceAuthorizationDecision>,
+        limit: Option<usize>,
+        offset: Option<usize>,
+    ) -> Result<Vec<ValenceAuthorizationRequest>> {
+        let limit = limit.unwrap_or(100);
+        let offset = offset.unwrap_or(0);
+        
+        let requests: Vec<(String, String, String, String, String, Option<String>, String, i64, String, Option<i64>, Option<String>, Option<String>)> = sqlx::query_as(
+            r#"
+            SELECT id, auth_id, requester, action, resource, request_data,
+                   decision::TEXT, requested_at_block, requested_at_tx, processed_at_block,
+                   processed_at_tx, reason
+            FROM authorization_requests
+            WHERE auth_id = $1 AND ($2::TEXT IS NULL OR decision::TEXT = $2)
+            ORDER BY requested_at_block DESC, id
+            LIMIT $3 OFFSET $4
+            "#
+        )
+        .bind(auth_id)
+        .bind(decision.as_ref().map(authorization_decision_str))
+        .bind(limit as i64)
+        .bind(offset as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        let request_objects: Vec<ValenceAuthorizationRequest> = requests.into_iter().map(|(rid, auth_id, requester, action, resource, request_data, decision, requested_block, requested_tx, processed_block, processed_tx, reason)| {
+            ValenceAuthorizationRequest {
+                id: rid,
+                auth_id,
+                requester,
+                action,
+                resource,
+                request_data,
+                decision: parse_authorization_decision(&decision),
+                requested_at_block: requested_block as u64,
+                requested_at_tx: requested_tx,
+                processed_at_block: processed_block.map(|b| b as u64),
+                processed_at_tx: processed_tx,
+                reason,
+            }
+        }).collect();
+        
+        Ok(request_objects)
+    }
+
     // --- Valence Library Methods ---
     
     async fn store_valence_library_instantiation(
@@ -1565,6 +1766,14 @@ impl Storage for PostgresStorage {
         debug!("PostgreSQL get_historical_processor_state not fully implemented");
         Ok(None)
     }
+    
+    async fn store_contract_schema(&self, chain: &str, address: &str, schema_data: &[u8]) -> Result<()> {
+        self.contract_schema_repository.store_schema(chain, address, schema_data).await
+    }
+    
+    async fn get_contract_schema(&self, chain: &str, address: &str) -> Result<Option<Vec<u8>>> {
+        self.contract_schema_repository.get_schema(chain, address).await
+    }
 }
 
 impl PostgresStorage {
@@ -1598,16 +1807,6 @@ impl PostgresStorage {
         })
     }
     
-    /// Store a contract schema
-    pub async fn store_contract_schema(&self, chain: &str, address: &str, schema_data: &[u8]) -> Result<()> {
-        self.contract_schema_repository.store_schema(chain, address, schema_data).await
-    }
-    
-    /// Get a contract schema
-    pub async fn get_contract_schema(&self, chain: &str, address: &str) -> Result<Option<Vec<u8>>> {
-        self.contract_schema_repository.get_schema(chain, address).await
-    }
-
     /// Reorgs the chain in response to a blockchain reorg
     #[instrument(skip(self), fields(chain = %chain, from_block = %from_block))]
     pub async fn handle_chain_reorg(&self, chain: &str, from_block: u64) -> Result<()> {
@@ -1651,3 +1850,74 @@ impl PostgresStorage {
         Ok(())
     }
 }
+
+/// Processor message status as stored in the `status` column
+#[cfg(feature = "postgres")]
+fn message_status_str(status: &ValenceMessageStatus) -> &'static str {
+    match status {
+        ValenceMessageStatus::Pending => "pending",
+        ValenceMessageStatus::Processing => "processing",
+        ValenceMessageStatus::Completed => "completed",
+        ValenceMessageStatus::Failed => "failed",
+        ValenceMessageStatus::TimedOut => "timed_out",
+    }
+}
+
+/// Parse a processor message status as stored in the `status` column
+#[cfg(feature = "postgres")]
+fn parse_message_status(status: &str) -> ValenceMessageStatus {
+    match status {
+        "processing" => ValenceMessageStatus::Processing,
+        "completed" => ValenceMessageStatus::Completed,
+        "failed" => ValenceMessageStatus::Failed,
+        "timed_out" => ValenceMessageStatus::TimedOut,
+        _ => ValenceMessageStatus::Pending,
+    }
+}
+
+/// Build a processor message from a `processor_messages` row
+#[cfg(feature = "postgres")]
+fn processor_message_from_row(row: &sqlx::postgres::PgRow) -> ValenceProcessorMessage {
+    use sqlx::Row;
+    
+    ValenceProcessorMessage {
+        id: row.get("id"),
+        processor_id: row.get("processor_id"),
+        source_chain_id: row.get("source_chain_id"),
+        target_chain_id: row.get("target_chain_id"),
+        sender_address: row.get("sender_address"),
+        payload: row.get("payload"),
+        status: parse_message_status(row.get::<&str, _>("status")),
+        created_at_block: row.get::<i64, _>("created_at_block") as u64,
+        created_at_tx: row.get("created_at_tx"),
+        last_updated_block: row.get::<i64, _>("last_updated_block") as u64,
+        processed_at_block: row.get::<Option<i64>, _>("processed_at_block").map(|v| v as u64),
+        processed_at_tx: row.get("processed_at_tx"),
+        retry_count: row.get::<i32, _>("retry_count") as u32,
+        next_retry_block: row.get::<Option<i64>, _>("next_retry_block").map(|v| v as u64),
+        gas_used: row.get::<Option<i64>, _>("gas_used").map(|v| v as u64),
+        error: row.get("error"),
+    }
+}
+
+/// Authorization decision as stored in the `decision` column
+#[cfg(feature = "postgres")]
+fn authorization_decision_str(decision: &ValenceAuthorizationDecision) -> &'static str {
+    match decision {
+        ValenceAuthorizationDecision::Pending => "pending",
+        ValenceAuthorizationDecision::Approved => "approved",
+        ValenceAuthorizationDecision::Denied => "denied",
+        ValenceAuthorizationDecision::Error => "error",
+    }
+}
+
+/// Parse an authorization decision as stored in the `decision` column
+#[cfg(feature = "postgres")]
+fn parse_authorization_decision(decision: &str) -> ValenceAuthorizationDecision {
+    match decision {
+        "approved" => ValenceAuthorizationDecision::Approved,
+        "denied" => ValenceAuthorizationDecision::Denied,
+        "error" => ValenceAuthorizationDecision::Error,
+        _ => ValenceAuthorizationDecision::Pending,
+    }
+}
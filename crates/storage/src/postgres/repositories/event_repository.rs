@@ -2,9 +2,13 @@ use std::time::{SystemTime, UNIX_EPOCH};
 use std::any::Any;
 
 use async_trait::async_trait;
-use sqlx::{Pool, Postgres};
+use serde_json::{Map, Value};
+use sqlx::types::Json;
+use sqlx::{Pool, Postgres, QueryBuilder, Row};
 
 use indexer_core::event::{Event, EventMetadata};
+use indexer_core::pipeline::{event_contract_address, event_index};
+use indexer_core::types::{AttributeFilter, EventPage, EventPosition, FilterOperator, FilterValue, TextSearchConfig, TextSearchMode};
 use indexer_core::Result;
 
 use crate::EventFilter;
@@ -36,6 +40,12 @@ pub struct EventRecord {
     /// Raw event data
     pub raw_data: Vec<u8>,
     
+    /// Position of the event within its block, if known
+    pub log_index: Option<i32>,
+    
+    /// Fields decoded with the contract schema on ingestion, if any
+    pub decoded: Option<Json<Map<String, Value>>>,
+    
     /// Created at timestamp
     pub created_at: Option<chrono::DateTime<chrono::Utc>>,
 }
@@ -49,6 +59,12 @@ pub trait EventRepository: Send + Sync + 'static {
     /// Get events by filters
     async fn get_events(&self, filters: Vec<EventFilter>) -> Result<Vec<Box<dyn Event>>>;
     
+    /// Get a keyset page of the events matching a filter
+    async fn get_events_page(&self, filter: &EventFilter, page: &EventPage) -> Result<Vec<Box<dyn Event>>>;
+    
+    /// Count the events matching a filter
+    async fn count_events(&self, filter: &EventFilter) -> Result<u64>;
+    
     /// Get the latest block height for a chain
     async fn get_latest_block(&self, chain: &str) -> Result<u64>;
 }
@@ -80,6 +96,25 @@ impl PostgresEventRepository {
         Box::new(EventWrapper {
             metadata,
             raw_data: record.raw_data,
+            log_index: record.log_index.and_then(|index| u32::try_from(index).ok()),
+            decoded: record.decoded.map(|decoded| decoded.0),
+        })
+    }
+    
+    /// Convert a result row to core event
+    fn row_to_event(&self, row: sqlx::postgres::PgRow) -> Box<dyn Event> {
+        self.record_to_event(EventRecord {
+            id: row.get("id"),
+            chain: row.get("chain"),
+            block_number: row.get("block_number"),
+            block_hash: row.get("block_hash"),
+            tx_hash: row.get("tx_hash"),
+            timestamp: row.get("timestamp"),
+            event_type: row.get("event_type"),
+            raw_data: row.get("raw_data"),
+            log_index: row.get("log_index"),
+            decoded: row.get("decoded"),
+            created_at: row.get("created_at"),
         })
     }
     
@@ -109,6 +144,8 @@ impl PostgresEventRepository {
 pub struct EventWrapper {
     metadata: EventMetadata,
     raw_data: Vec<u8>,
+    log_index: Option<u32>,
+    decoded: Option<Map<String, Value>>,
 }
 
 // Implementation for our own wrapper
@@ -144,6 +181,14 @@ impl Event for EventWrapper {
     fn raw_data(&self) -> &[u8] {
         &self.raw_data
     }
+    
+    fn log_index(&self) -> Option<u32> {
+        self.log_index
+    }
+
+    fn decoded(&self) -> Option<&Map<String, Value>> {
+        self.decoded.as_ref()
+    }
 
     // Added implementation for as_any
     fn as_any(&self) -> &dyn Any {
@@ -158,8 +203,8 @@ impl EventRepository for PostgresEventRepository {
         // Insert into events table using basic SQLx query (no compile-time validation for now)
         sqlx::query(
             r#"
-            INSERT INTO events (id, chain, block_number, block_hash, tx_hash, timestamp, event_type, raw_data)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            INSERT INTO events (id, chain, block_number, block_hash, tx_hash, timestamp, event_type, raw_data, log_index, decoded, address)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
             "#
         )
         .bind(event.id())
@@ -170,6 +215,9 @@ impl EventRepository for PostgresEventRepository {
         .bind(event.timestamp().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() as i64)
         .bind(event.event_type())
         .bind(event.raw_data())
+        .bind(event_index(event.as_ref()).ok().map(|index| index as i32))
+        .bind(event.decoded().map(Json))
+        .bind(event_contract_address(event.as_ref()).map(|address| address.to_lowercase()))
         .execute(&self.pool)
         .await?;
         
@@ -177,16 +225,12 @@ impl EventRepository for PostgresEventRepository {
     }
     
     async fn get_events(&self, filters: Vec<EventFilter>) -> Result<Vec<Box<dyn Event>>> {
-        // This is a simplified implementation for demonstration purposes
-        // A real implementation would build a dynamic query based on the filters
+        let mut events = Vec::new();
         
-        // For now, we'll just query all events if filters is empty, or return an empty vector
         if filters.is_empty() {
-            use sqlx::Row;
-            
             let rows = sqlx::query(
                 r#"
-                SELECT id, chain, block_number, block_hash, tx_hash, timestamp, event_type, raw_data, created_at
+                SELECT id, chain, block_number, block_hash, tx_hash, timestamp, event_type, raw_data, log_index, decoded, created_at
                 FROM events
                 ORDER BY timestamp DESC
                 LIMIT 100
@@ -195,29 +239,66 @@ impl EventRepository for PostgresEventRepository {
             .fetch_all(&self.pool)
             .await?;
             
-            let events = rows.into_iter()
-                .map(|row| {
-                    let record = EventRecord {
-                        id: row.get("id"),
-                        chain: row.get("chain"),
-                        block_number: row.get("block_number"),
-                        block_hash: row.get("block_hash"),
-                        tx_hash: row.get("tx_hash"),
-                        timestamp: row.get("timestamp"),
-                        event_type: row.get("event_type"),
-                        raw_data: row.get("raw_data"),
-                        created_at: row.get("created_at"),
-                    };
-                    self.record_to_event(record)
-                })
-                .collect();
-            
+            events.extend(rows.into_iter().map(|row| self.row_to_event(row)));
             return Ok(events);
         }
         
-        // If we got here, we have filters but our implementation is simplified
-        // In a real implementation, we would build a dynamic query
-        Ok(Vec::new())
+        // Filters are combined with OR, each one is a separate query
+        for filter in &filters {
+            let mut query = QueryBuilder::<Postgres>::new(SELECT_EVENTS);
+            query.push(" WHERE TRUE");
+            push_filter(&mut query, filter);
+            query.push(" ORDER BY chain, block_number, log_index");
+            if let Some(limit) = filter.limit {
+                query.push(" LIMIT ").push_bind(clamp(limit as u64));
+            }
+            if let Some(offset) = filter.offset {
+                query.push(" OFFSET ").push_bind(clamp(offset as u64));
+            }
+            
+            let rows = query.build().fetch_all(&self.pool).await?;
+            events.extend(rows.into_iter().map(|row| self.row_to_event(row)));
+        }
+        
+        Ok(events)
+    }
+    
+    async fn get_events_page(&self, filter: &EventFilter, page: &EventPage) -> Result<Vec<Box<dyn Event>>> {
+        let mut query = QueryBuilder::<Postgres>::new(SELECT_EVENTS);
+        query.push(" WHERE TRUE");
+        push_filter(&mut query, filter);
+        
+        if let Some(after) = &page.after {
+            query.push(format!(" AND {} > ", EVENT_ORDER));
+            push_position(&mut query, after);
+        }
+        if let Some(before) = &page.before {
+            query.push(format!(" AND {} < ", EVENT_ORDER));
+            push_position(&mut query, before);
+        }
+        
+        let direction = if page.from_end { "DESC" } else { "ASC" };
+        query.push(format!(
+            " ORDER BY chain {direction}, block_number {direction}, COALESCE(log_index, -1) {direction}, \
+             tx_hash {direction}, id {direction} LIMIT "
+        ));
+        query.push_bind(clamp(page.limit as u64));
+        
+        let rows = query.build().fetch_all(&self.pool).await?;
+        let mut events: Vec<Box<dyn Event>> = rows.into_iter().map(|row| self.row_to_event(row)).collect();
+        if page.from_end {
+            events.reverse();
+        }
+        
+        Ok(events)
+    }
+    
+    async fn count_events(&self, filter: &EventFilter) -> Result<u64> {
+        let mut query = QueryBuilder::<Postgres>::new("SELECT COUNT(*) FROM events WHERE TRUE");
+        push_filter(&mut query, filter);
+        
+        let count: i64 = query.build().fetch_one(&self.pool).await?.get(0);
+        Ok(count as u64)
     }
     
     async fn get_latest_block(&self, chain: &str) -> Result<u64> {
@@ -239,4 +320,262 @@ impl EventRepository for PostgresEventRepository {
         
         Ok(max_block)
     }
-} 
\ No newline at end of file
+} 
+/// Columns selected for every event query
+const SELECT_EVENTS: &str = "SELECT id, chain, block_number, block_hash, tx_hash, timestamp, event_type, raw_data, log_index, decoded, created_at FROM events";
+
+/// Row of the columns events are paged by, in [`EventPosition`] order
+///
+/// Events without a log index come first in their block, as log indexes are
+/// never negative.
+const EVENT_ORDER: &str = "(chain, block_number, COALESCE(log_index, -1), tx_hash, id)";
+
+/// Push the row of `position` to compare with [`EVENT_ORDER`]
+fn push_position(query: &mut QueryBuilder<'_, Postgres>, position: &EventPosition) {
+    query.push("(")
+        .push_bind(position.chain.clone())
+        .push(", ")
+        .push_bind(clamp(position.block_number))
+        .push(", ")
+        .push_bind(position.log_index.map_or(-1, i64::from))
+        .push(", ")
+        .push_bind(position.tx_hash.clone())
+        .push(", ")
+        .push_bind(position.id.clone())
+        .push(")");
+}
+
+/// Clamp an unsigned bound into the BIGINT range
+fn clamp(value: u64) -> i64 {
+    i64::try_from(value).unwrap_or(i64::MAX)
+}
+
+/// Append the predicates of a filter to a query that already has a WHERE clause
+fn push_filter(query: &mut QueryBuilder<'_, Postgres>, filter: &EventFilter) {
+    if let Some(chain) = &filter.chain {
+        query.push(" AND chain = ").push_bind(chain.clone());
+    }
+    let mut chains: Vec<String> = filter.chains.clone().unwrap_or_default();
+    if let Some(chain_ids) = &filter.chain_ids {
+        chains.extend(chain_ids.iter().map(|id| id.0.clone()));
+    }
+    if !chains.is_empty() {
+        query.push(" AND chain = ANY(").push_bind(chains).push(")");
+    }
+    
+    let mut push_ranges = |column: &str, single: Option<(u64, u64)>, multiple: &Option<Vec<(u64, u64)>>| {
+        let ranges: Vec<(u64, u64)> = single.into_iter().chain(multiple.iter().flatten().copied()).collect();
+        if ranges.is_empty() {
+            return;
+        }
+        query.push(" AND (");
+        for (i, (from, to)) in ranges.into_iter().enumerate() {
+            if i > 0 {
+                query.push(" OR ");
+            }
+            query.push(format!("{column} BETWEEN "))
+                .push_bind(clamp(from))
+                .push(" AND ")
+                .push_bind(clamp(to));
+        }
+        query.push(")");
+    };
+    push_ranges("block_number", filter.block_range, &filter.block_ranges);
+    push_ranges("timestamp", filter.time_range, &filter.time_ranges);
+    
+    if let Some(event_types) = &filter.event_types {
+        query.push(" AND event_type = ANY(").push_bind(event_types.clone()).push(")");
+    }
+    if let Some(exclude) = &filter.exclude_event_types {
+        query.push(" AND NOT (event_type = ANY(").push_bind(exclude.clone()).push("))");
+    }
+    if let Some(tx_hashes) = &filter.tx_hashes {
+        query.push(" AND tx_hash = ANY(").push_bind(tx_hashes.clone()).push(")");
+    }
+    if let Some(block_hashes) = &filter.block_hashes {
+        query.push(" AND block_hash = ANY(").push_bind(block_hashes.clone()).push(")");
+    }
+    
+    // Addresses are stored lowercase, so checksummed EVM addresses match
+    let mut addresses: Vec<String> = filter.addresses.clone().unwrap_or_default();
+    addresses.extend(filter.custom_filters.get("address").cloned());
+    if !addresses.is_empty() {
+        let addresses: Vec<String> = addresses.iter().map(|address| address.to_lowercase()).collect();
+        query.push(" AND address = ANY(").push_bind(addresses).push(")");
+    }
+    if let Some(exclude) = &filter.exclude_addresses {
+        let exclude: Vec<String> = exclude.iter().map(|address| address.to_lowercase()).collect();
+        query.push(" AND (address IS NULL OR NOT (address = ANY(").push_bind(exclude).push(")))");
+    }
+    
+    // Other custom filters match fields decoded on ingestion
+    for (key, value) in &filter.custom_filters {
+        if key != "address" {
+            query.push(" AND decoded ->> ").push_bind(key.clone())
+                .push(" = ").push_bind(value.clone());
+        }
+    }
+    for attribute in filter.attribute_filters.iter().flatten() {
+        push_attribute_filter(query, attribute);
+    }
+    
+    if let Some(text_query) = &filter.text_query {
+        push_text_query(query, text_query, &filter.text_search_config.clone().unwrap_or_default());
+    }
+}
+
+/// Append an attribute filter over the fields decoded on ingestion
+fn push_attribute_filter(query: &mut QueryBuilder<'_, Postgres>, filter: &AttributeFilter) {
+    let text = |value: &FilterValue| match value {
+        FilterValue::String(value) => value.clone(),
+        FilterValue::Number(value) => value.to_string(),
+        FilterValue::Boolean(value) => value.to_string(),
+        FilterValue::List(values) => values.join(","),
+    };
+    let list = |value: &FilterValue| match value {
+        FilterValue::List(values) => values.clone(),
+        value => vec![text(value)],
+    };
+    let number = |value: &FilterValue| match value {
+        FilterValue::Number(value) => Some(*value),
+        FilterValue::String(value) => value.parse::<f64>().ok(),
+        _ => None,
+    };
+    
+    let field = |query: &mut QueryBuilder<'_, Postgres>| {
+        query.push("decoded ->> ").push_bind(filter.key.clone());
+    };
+    query.push(" AND ");
+    match filter.operator {
+        FilterOperator::Exists => {
+            query.push("decoded ? ").push_bind(filter.key.clone());
+        }
+        FilterOperator::NotExists => {
+            query.push("(decoded IS NULL OR NOT decoded ? ").push_bind(filter.key.clone()).push(")");
+        }
+        FilterOperator::Equals => {
+            field(query);
+            query.push(" = ").push_bind(text(&filter.value));
+        }
+        FilterOperator::NotEquals => {
+            field(query);
+            query.push(" <> ").push_bind(text(&filter.value));
+        }
+        FilterOperator::Contains => {
+            query.push("strpos(");
+            field(query);
+            query.push(", ").push_bind(text(&filter.value)).push(") > 0");
+        }
+        FilterOperator::NotContains => {
+            query.push("strpos(");
+            field(query);
+            query.push(", ").push_bind(text(&filter.value)).push(") = 0");
+        }
+        FilterOperator::StartsWith => {
+            query.push("starts_with(");
+            field(query);
+            query.push(", ").push_bind(text(&filter.value)).push(")");
+        }
+        FilterOperator::EndsWith => {
+            query.push("right(");
+            field(query);
+            query.push(", length(").push_bind(text(&filter.value)).push(")) = ").push_bind(text(&filter.value));
+        }
+        FilterOperator::GreaterThan
+        | FilterOperator::GreaterThanOrEqual
+        | FilterOperator::LessThan
+        | FilterOperator::LessThanOrEqual => {
+            let Some(bound) = number(&filter.value) else {
+                // Nothing compares numerically with a non-numeric bound
+                query.push("FALSE");
+                return;
+            };
+            let operator = match filter.operator {
+                FilterOperator::GreaterThan => ">",
+                FilterOperator::GreaterThanOrEqual => ">=",
+                FilterOperator::LessThan => "<",
+                _ => "<=",
+            };
+            // Non-numeric fields are NULL and never match
+            query.push("(CASE WHEN ");
+            field(query);
+            query.push(" ~ '^-?[0-9]+(\\.[0-9]+)?$' THEN (");
+            field(query);
+            query.push(format!(")::numeric END) {operator} "))
+                .push_bind(bound)
+                .push("::numeric");
+        }
+        FilterOperator::In => {
+            field(query);
+            query.push(" = ANY(").push_bind(list(&filter.value)).push(")");
+        }
+        FilterOperator::NotIn => {
+            query.push("NOT (");
+            field(query);
+            query.push(" = ANY(").push_bind(list(&filter.value)).push("))");
+        }
+        FilterOperator::Regex => {
+            field(query);
+            query.push(" ~ ").push_bind(text(&filter.value));
+        }
+    }
+}
+
+/// Text of an event searched by text queries
+const SEARCHABLE_TEXT: &str = "concat_ws(' ', id, chain, event_type, tx_hash, block_hash, address, decoded::text)";
+
+/// Append a text search over the event's identifiers and decoded fields
+///
+/// Fuzzy queries have no edit distance function without extensions and
+/// match as substrings instead.
+fn push_text_query(query: &mut QueryBuilder<'_, Postgres>, text_query: &str, config: &TextSearchConfig) {
+    let (haystack, needle) = if config.case_sensitive {
+        (SEARCHABLE_TEXT.to_string(), text_query.to_string())
+    } else {
+        (format!("lower({SEARCHABLE_TEXT})"), text_query.to_lowercase())
+    };
+    let contains = |query: &mut QueryBuilder<'_, Postgres>, term: &str| {
+        query.push(format!("strpos({haystack}, ")).push_bind(term.to_string()).push(") > 0");
+    };
+    
+    query.push(" AND (");
+    match config.mode {
+        TextSearchMode::Contains | TextSearchMode::Phrase | TextSearchMode::Fuzzy { .. } => {
+            contains(query, &needle);
+        }
+        TextSearchMode::FullText => {
+            query.push("TRUE");
+            for word in needle.split_whitespace() {
+                query.push(" AND ");
+                contains(query, word);
+            }
+        }
+        TextSearchMode::Regex => {
+            let operator = if config.case_sensitive { "~" } else { "~*" };
+            query.push(format!("{SEARCHABLE_TEXT} {operator} ")).push_bind(text_query.to_string());
+        }
+        TextSearchMode::Boolean => {
+            if needle.contains(" and ") {
+                for (i, term) in needle.split(" and ").enumerate() {
+                    if i > 0 {
+                        query.push(" AND ");
+                    }
+                    contains(query, term.trim());
+                }
+            } else if needle.contains(" or ") {
+                for (i, term) in needle.split(" or ").enumerate() {
+                    if i > 0 {
+                        query.push(" OR ");
+                    }
+                    contains(query, term.trim());
+                }
+            } else if let Some(term) = needle.strip_prefix("not ") {
+                query.push("NOT ");
+                contains(query, term.trim());
+            } else {
+                contains(query, &needle);
+            }
+        }
+    }
+    query.push(")");
+}
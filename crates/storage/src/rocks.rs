@@ -10,8 +10,11 @@ use std::collections::HashSet;
 use async_trait::async_trait;
 use indexer_core::{BlockStatus, Error, Result};
 use indexer_core::event::Event;
+use indexer_core::pipeline::event_index;
+use indexer_core::reorg::CanonicalBlock;
+use indexer_core::types::{EventPage, EventPosition};
 #[cfg(feature = "rocks")]
-use rocksdb::{Options, DB, WriteBatch, BlockBasedOptions, ColumnFamily};
+use rocksdb::{Direction, IteratorMode, Options, DB, WriteBatch, BlockBasedOptions, ColumnFamily};
 use serde::{Deserialize, Serialize};
 use tracing::{debug, warn};
 use serde_json;
@@ -24,7 +27,7 @@ use crate::{
     ValenceProcessorInfo, ValenceProcessorConfig, ValenceProcessorMessage, ValenceMessageStatus,
     ValenceProcessorState, ValenceAuthorizationInfo, ValenceAuthorizationPolicy, ValenceAuthorizationGrant,
     ValenceAuthorizationRequest, ValenceAuthorizationDecision, ValenceLibraryInfo, ValenceLibraryVersion,
-    ValenceLibraryUsage, ValenceLibraryState, ValenceLibraryApproval
+    ValenceLibraryUsage, ValenceLibraryState, ValenceLibraryApproval, ValenceAuthorizationState
 };
 
 /// Configuration for RocksDB storage
@@ -120,6 +123,7 @@ impl Storage for RocksStorage {
                 .as_secs(),
             event_type: event.event_type().to_string(),
             raw_data: event.raw_data().to_vec(),
+            log_index: event_index(event.as_ref()).ok(),
         };
         
         let serialized = bincode::serialize(&event_data)
@@ -128,23 +132,17 @@ impl Storage for RocksStorage {
         // Store the event in the events column family
         self.db.put_cf(_events_cf, key.to_bytes(), serialized)?;
         
-        // Update latest block for chain in the latest_block column family
-        let latest_block_cf = self.cf_handle_ref("latest_block")?;
-        let latest_block_key = Key::new("latest_block", chain);
-        
-        let current_latest = match self.db.get_cf(latest_block_cf, latest_block_key.to_bytes())? {
-            Some(bytes) => String::from_utf8(bytes)
-                .map_err(|_| Error::generic("Invalid latest block format"))?
-                .parse::<u64>()
-                .map_err(|_| Error::generic("Invalid latest block format"))?,
-            None => 0,
-        };
+        // Index the event by its position so pages can be read in order
+        let position = EventPosition::of(event.as_ref());
+        self.db.put_cf(self.cf_event_order()?, event_order_key(&position), event.id().as_bytes())?;
         
-        if event.block_number() > current_latest {
-            self.db.put_cf(latest_block_cf, latest_block_key.to_bytes(), event.block_number().to_string().as_bytes())?;
+        // Keep the decoded fields apart, as JSON values don't round-trip through bincode
+        if let Some(decoded) = event.decoded() {
+            self.db.put_cf(self.cf_event_decoded()?, event.id().as_bytes(), serde_json::to_vec(decoded)?)?;
         }
         
-        Ok(())
+        // Update latest block for chain in the latest_block column family
+        self.raise_latest_block(chain, event.block_number())
     }
     
     async fn get_events(&self, chain: &str, from_block: u64, to_block: u64) -> Result<Vec<Box<dyn Event>>> {
@@ -165,6 +163,50 @@ impl Storage for RocksStorage {
             
         Ok(events)
     }
+    
+    async fn get_events_page(&self, filter: &EventFilter, page: &EventPage) -> Result<Vec<Box<dyn Event>>> {
+        let cf = self.cf_event_order()?;
+        let after_key = page.after.as_ref().map(event_order_key);
+        let before_key = page.before.as_ref().map(event_order_key);
+        
+        // Start at the bound on the side the page is read from
+        let mode = match (page.from_end, &after_key, &before_key) {
+            (false, Some(key), _) => IteratorMode::From(key, Direction::Forward),
+            (false, None, _) => IteratorMode::Start,
+            (true, _, Some(key)) => IteratorMode::From(key, Direction::Reverse),
+            (true, _, None) => IteratorMode::End,
+        };
+        
+        let mut events = Vec::new();
+        for item in self.db.iterator_cf(cf, mode) {
+            if events.len() >= page.limit {
+                break;
+            }
+            
+            let (key, id) = item.map_err(|e| Error::database(format!("RocksDB iterator error: {}", e)))?;
+            let position = event_order_position(&key)?;
+            if !page.contains(&position) {
+                // Positions are visited in order, so only the starting bound is skipped
+                let at_start = if page.from_end { before_key.as_deref() } else { after_key.as_deref() };
+                if at_start == Some(&key[..]) {
+                    continue;
+                }
+                break;
+            }
+            
+            if let Some(event) = self.get_event_by_id(&string_from_utf8(id.to_vec())?)? {
+                if filter.matches_event(event.as_ref()) {
+                    events.push(event);
+                }
+            }
+        }
+        
+        if page.from_end {
+            events.reverse();
+        }
+        
+        Ok(events)
+    }
 
     async fn get_latest_block(&self, chain: &str) -> Result<u64> {
         let key = Key::new("latest_block", chain);
@@ -182,6 +224,11 @@ impl Storage for RocksStorage {
         self.put(&key, status.as_str().as_bytes())
     }
 
+    async fn store_block(&self, chain: &str, block: &CanonicalBlock, status: BlockStatus) -> Result<()> {
+        self.update_block_status(chain, block.number, status).await?;
+        self.raise_latest_block(chain, block.number)
+    }
+
     async fn get_latest_block_with_status(&self, chain: &str, status: BlockStatus) -> Result<u64> {
         let prefix = Key::prefix(format!("block_status:{}", chain));
         let cf = self.cf_handle_ref("block_status")?;
@@ -468,27 +515,89 @@ impl Storage for RocksStorage {
     
     async fn store_valence_processor_message(
         &self,
-        _message: ValenceProcessorMessage,
+        message: ValenceProcessorMessage,
     ) -> Result<()> {
-        // Implementation would involve storing message state, perhaps in separate CF
+        let key = self.valence_processor_message_key(&message.id);
+        let cf = self.cf_valence_state()?;
+        let data = serde_json::to_vec(&message)?;
+        self.db.put_cf(cf, key, data)?;
         Ok(())
     }
     
     async fn update_valence_processor_message_status(
         &self,
-        _message_id: &str,
-        _new_status: ValenceMessageStatus,
-        _processed_block: Option<u64>,
-        _processed_tx: Option<&str>,
-        _retry_count: Option<u32>,
-        _next_retry_block: Option<u64>,
-        _gas_used: Option<u64>,
-        _error: Option<String>,
+        message_id: &str,
+        new_status: ValenceMessageStatus,
+        processed_block: Option<u64>,
+        processed_tx: Option<&str>,
+        retry_count: Option<u32>,
+        next_retry_block: Option<u64>,
+        gas_used: Option<u64>,
+        error: Option<String>,
     ) -> Result<()> {
-        // Update message state in its CF
+        if let Some(mut message) = self.get_valence_processor_message(message_id).await? {
+            message.status = new_status;
+            message.processed_at_block = processed_block;
+            message.processed_at_tx = processed_tx.map(|tx| tx.to_string());
+            if let Some(retry_count) = retry_count {
+                message.retry_count = retry_count;
+            }
+            message.next_retry_block = next_retry_block;
+            message.gas_used = gas_used;
+            message.error = error;
+            if let Some(block) = processed_block {
+                message.last_updated_block = block;
+            }
+            self.store_valence_processor_message(message).await?;
+        } else {
+            warn!(message_id, "Attempted status update for non-existent processor message");
+        }
         Ok(())
     }
     
+    async fn get_valence_processor_message(&self, message_id: &str) -> Result<Option<ValenceProcessorMessage>> {
+        let key = self.valence_processor_message_key(message_id);
+        let cf = self.cf_valence_state()?;
+        if let Some(data) = self.db.get_cf(cf, key)? {
+            let message: ValenceProcessorMessage = serde_json::from_slice(&data)?;
+            Ok(Some(message))
+        } else {
+            Ok(None)
+        }
+    }
+    
+    async fn get_valence_processor_messages(
+        &self,
+        processor_id: &str,
+        status: Option<ValenceMessageStatus>,
+        limit: Option<usize>,
+        offset: Option<usize>,
+    ) -> Result<Vec<ValenceProcessorMessage>> {
+        let prefix = b"processor_message:";
+        let cf = self.cf_valence_state()?;
+        let iter = self.db.prefix_iterator_cf(cf, prefix);
+        
+        let mut messages = Vec::new();
+        for item in iter {
+            let (key_bytes, value_bytes) = item.map_err(|e| Error::database(format!("RocksDB iterator error: {}", e)))?;
+            if !key_bytes.starts_with(prefix) {
+                break;
+            }
+            let message: ValenceProcessorMessage = serde_json::from_slice(&value_bytes)?;
+            if message.processor_id == processor_id && status.as_ref().map_or(true, |s| &message.status == s) {
+                messages.push(message);
+            }
+        }
+        
+        // Newest messages first, matching the Postgres ordering
+        messages.sort_by(|a, b| b.created_at_block.cmp(&a.created_at_block).then_with(|| a.id.cmp(&b.id)));
+        
+        Ok(messages.into_iter()
+            .skip(offset.unwrap_or(0))
+            .take(limit.unwrap_or(100))
+            .collect())
+    }
+    
     async fn get_valence_processor_state(&self, processor_id: &str) -> Result<Option<ValenceProcessorState>> {
         let key = self.valence_processor_state_key(processor_id);
         let cf = self.cf_block_status()?;
@@ -536,69 +645,160 @@ impl Storage for RocksStorage {
         }
     }
     
-    // --- Valence Authorization Methods (Placeholder) ---
+    // --- Valence Authorization Methods ---
     
     async fn store_valence_authorization_instantiation(
         &self,
-        _auth_info: ValenceAuthorizationInfo,
-        _initial_policy: Option<ValenceAuthorizationPolicy>,
+        auth_info: ValenceAuthorizationInfo,
+        initial_policy: Option<ValenceAuthorizationPolicy>,
     ) -> Result<()> {
+        self.put_valence_record(&self.valence_authorization_key(&auth_info.id), &auth_info)?;
+        if let Some(policy) = initial_policy {
+            self.store_valence_authorization_policy(policy).await?;
+        }
         Ok(())
     }
     
     async fn store_valence_authorization_policy(
         &self,
-        _policy: ValenceAuthorizationPolicy,
+        policy: ValenceAuthorizationPolicy,
     ) -> Result<()> {
-        Ok(())
+        self.put_valence_record(&self.valence_authorization_policy_key(&policy.id), &policy)
     }
     
     async fn update_active_authorization_policy(
         &self,
-        _auth_id: &str,
-        _policy_id: &str,
-        _update_block: u64,
-        _update_tx: &str,
+        auth_id: &str,
+        policy_id: &str,
+        update_block: u64,
+        update_tx: &str,
     ) -> Result<()> {
+        let policies: Vec<ValenceAuthorizationPolicy> = self.valence_records(b"authorization_policy:")?;
+        for mut policy in policies.into_iter().filter(|policy| policy.auth_id == auth_id) {
+            policy.is_active = policy.id == policy_id;
+            self.store_valence_authorization_policy(policy).await?;
+        }
+        
+        let key = self.valence_authorization_key(auth_id);
+        match self.get_valence_record::<ValenceAuthorizationInfo>(&key)? {
+            Some(mut auth_info) => {
+                auth_info.active_policy_id = Some(policy_id.to_string());
+                auth_info.last_updated_block = update_block;
+                auth_info.last_updated_tx = update_tx.to_string();
+                self.put_valence_record(&key, &auth_info)?;
+            }
+            None => warn!(auth_id, "Attempted policy update for non-existent authorization contract"),
+        }
         Ok(())
     }
     
     async fn store_valence_authorization_grant(
         &self,
-        _grant: ValenceAuthorizationGrant,
+        grant: ValenceAuthorizationGrant,
     ) -> Result<()> {
-        Ok(())
+        self.put_valence_record(&self.valence_authorization_grant_key(&grant.id), &grant)
     }
     
     async fn revoke_valence_authorization_grant(
         &self,
-        _auth_id: &str,
-        _grantee: &str,
-        _resource: &str,
-        _revoked_at_block: u64,
-        _revoked_at_tx: &str,
+        auth_id: &str,
+        grantee: &str,
+        resource: &str,
+        revoked_at_block: u64,
+        revoked_at_tx: &str,
     ) -> Result<()> {
+        let grants = self.get_valence_authorization_grants(auth_id, true).await?;
+        for mut grant in grants.into_iter().filter(|grant| grant.grantee == grantee && grant.resources.iter().any(|r| r == resource)) {
+            grant.is_active = false;
+            grant.revoked_at_block = Some(revoked_at_block);
+            grant.revoked_at_tx = Some(revoked_at_tx.to_string());
+            self.store_valence_authorization_grant(grant).await?;
+        }
         Ok(())
     }
     
     async fn store_valence_authorization_request(
         &self,
-        _request: ValenceAuthorizationRequest,
+        request: ValenceAuthorizationRequest,
     ) -> Result<()> {
-        Ok(())
+        self.put_valence_record(&self.valence_authorization_request_key(&request.id), &request)
     }
     
     async fn update_valence_authorization_request_decision(
         &self,
-        _request_id: &str,
-        _decision: ValenceAuthorizationDecision,
-        _processed_block: Option<u64>,
-        _processed_tx: Option<&str>,
-        _reason: Option<String>,
+        request_id: &str,
+        decision: ValenceAuthorizationDecision,
+        processed_block: Option<u64>,
+        processed_tx: Option<&str>,
+        reason: Option<String>,
     ) -> Result<()> {
+        let key = self.valence_authorization_request_key(request_id);
+        match self.get_valence_record::<ValenceAuthorizationRequest>(&key)? {
+            Some(mut request) => {
+                request.decision = decision;
+                request.processed_at_block = processed_block;
+                request.processed_at_tx = processed_tx.map(|tx| tx.to_string());
+                request.reason = reason;
+                self.put_valence_record(&key, &request)?;
+            }
+            None => warn!(request_id, "Attempted decision update for non-existent authorization request"),
+        }
         Ok(())
     }
 
+    async fn get_valence_authorization_state(&self, auth_id: &str) -> Result<Option<ValenceAuthorizationState>> {
+        let auth_info = match self.get_valence_record::<ValenceAuthorizationInfo>(&self.valence_authorization_key(auth_id))? {
+            Some(auth_info) => auth_info,
+            None => return Ok(None),
+        };
+        Ok(Some(ValenceAuthorizationState {
+            auth_id: auth_info.id,
+            chain_id: auth_info.chain_id,
+            address: auth_info.contract_address,
+            current_owner: auth_info.current_owner,
+            active_policy_id: auth_info.active_policy_id,
+            active_grants: self.get_valence_authorization_grants(auth_id, true).await?,
+            last_update_block: auth_info.last_updated_block,
+            last_update_tx: auth_info.last_updated_tx,
+        }))
+    }
+    
+    async fn get_valence_authorization_grants(
+        &self,
+        auth_id: &str,
+        active_only: bool,
+    ) -> Result<Vec<ValenceAuthorizationGrant>> {
+        let mut grants: Vec<ValenceAuthorizationGrant> = self.valence_records::<ValenceAuthorizationGrant>(b"authorization_grant:")?
+            .into_iter()
+            .filter(|grant| grant.auth_id == auth_id && (grant.is_active || !active_only))
+            .collect();
+        
+        // Newest grants first, matching the Postgres ordering
+        grants.sort_by(|a, b| b.granted_at_block.cmp(&a.granted_at_block));
+        Ok(grants)
+    }
+    
+    async fn get_valence_authorization_requests(
+        &self,
+        auth_id: &str,
+        decision: Option<ValenceAuthorizationDecision>,
+        limit: Option<usize>,
+        offset: Option<usize>,
+    ) -> Result<Vec<ValenceAuthorizationRequest>> {
+        let mut requests: Vec<ValenceAuthorizationRequest> = self.valence_records::<ValenceAuthorizationRequest>(b"authorization_request:")?
+            .into_iter()
+            .filter(|request| request.auth_id == auth_id && decision.as_ref().map_or(true, |d| &request.decision == d))
+            .collect();
+        
+        // Newest requests first, matching the Postgres ordering
+        requests.sort_by(|a, b| b.requested_at_block.cmp(&a.requested_at_block).then_with(|| a.id.cmp(&b.id)));
+        
+        Ok(requests.into_iter()
+            .skip(offset.unwrap_or(0))
+            .take(limit.unwrap_or(100))
+            .collect())
+    }
+
     // --- Valence Library Methods (Placeholder) ---
     
     async fn store_valence_library_instantiation(
@@ -729,6 +929,24 @@ impl Storage for RocksStorage {
             }
         }
         
+        // Delete events indexed by position from blocks >= from_block
+        let events_cf = self.cf_events()?;
+        let order_cf = self.cf_event_order()?;
+        let decoded_cf = self.cf_event_decoded()?;
+        let start = event_order_key(&EventPosition::block_start(chain, from_block));
+        let chain_prefix = event_order_chain_prefix(chain);
+        for item in self.db.iterator_cf(order_cf, IteratorMode::From(&start, Direction::Forward)) {
+            let (key, id) = item.map_err(|e| Error::database(format!("RocksDB iterator error: {}", e)))?;
+            if !key.starts_with(&chain_prefix) {
+                break;
+            }
+            
+            let event_key = Key::new("events", string_from_utf8(id.to_vec())?);
+            batch.delete_key_bytes(&event_key.to_bytes(), events_cf);
+            batch.delete_key_bytes(&id, decoded_cf);
+            batch.delete_key_bytes(&key, order_cf);
+        }
+        
         // 4. Update latest block
         // Find the highest block *before* from_block that exists.
         let new_latest_block = self.get_latest_block_before(chain, from_block).await?;
@@ -778,6 +996,16 @@ impl Storage for RocksStorage {
         }
     }
 
+    async fn store_contract_schema(&self, chain: &str, address: &str, schema_data: &[u8]) -> Result<()> {
+        let key = Key::new("contract_schemas", format!("{}:{}", chain, address));
+        self.put(&key, schema_data)
+    }
+
+    async fn get_contract_schema(&self, chain: &str, address: &str) -> Result<Option<Vec<u8>>> {
+        let key = Key::new("contract_schemas", format!("{}:{}", chain, address));
+        self.get(&key)
+    }
+
     async fn get_latest_block_before(&self, chain: &str, before_block: u64) -> Result<u64> {
         let block_status_cf = self.cf_block_status()?;
         let mut latest_block = 0;
@@ -819,6 +1047,9 @@ impl RocksStorage {
             "valence_state",
             "historical_valence_state",
             "latest_historical_valence_block",
+            "event_order",
+            "event_decoded",
+            "contract_schemas",
             // Add other CFs if needed
         ];
         let cf_opts: Vec<(&str, Options)> = cf_names.iter().skip(1) // Skip "default"
@@ -881,6 +1112,33 @@ impl RocksStorage {
         self.cf_handle_ref("latest_historical_valence_block")
     }
 
+    fn cf_event_order(&self) -> Result<&ColumnFamily> {
+        self.cf_handle_ref("event_order")
+    }
+
+    /// Raise the latest block of a chain to `block_number` if it is higher
+    fn raise_latest_block(&self, chain: &str, block_number: u64) -> Result<()> {
+        let latest_block_cf = self.cf_handle_ref("latest_block")?;
+        let latest_block_key = Key::new("latest_block", chain);
+        
+        let current_latest = match self.db.get_cf(latest_block_cf, latest_block_key.to_bytes())? {
+            Some(bytes) => String::from_utf8(bytes)
+                .map_err(|_| Error::generic("Invalid latest block format"))?
+                .parse::<u64>()
+                .map_err(|_| Error::generic("Invalid latest block format"))?,
+            None => 0,
+        };
+        
+        if block_number > current_latest {
+            self.db.put_cf(latest_block_cf, latest_block_key.to_bytes(), block_number.to_string().as_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn cf_event_decoded(&self) -> Result<&ColumnFamily> {
+        self.cf_handle_ref("event_decoded")
+    }
+
     // --- Add Crate-Public Key generation helpers --- 
     pub(crate) fn valence_account_state_key(&self, account_id: &str) -> Vec<u8> {
         Key::new("valence_state", account_id).to_bytes()
@@ -902,6 +1160,53 @@ impl RocksStorage {
         format!("historical_processor_state:{}:{}", processor_id, block_number).into_bytes()
     }
 
+    pub(crate) fn valence_processor_message_key(&self, message_id: &str) -> Vec<u8> {
+        format!("processor_message:{}", message_id).into_bytes()
+    }
+
+    pub(crate) fn valence_authorization_key(&self, auth_id: &str) -> Vec<u8> {
+        format!("authorization:{}", auth_id).into_bytes()
+    }
+
+    pub(crate) fn valence_authorization_policy_key(&self, policy_id: &str) -> Vec<u8> {
+        format!("authorization_policy:{}", policy_id).into_bytes()
+    }
+
+    pub(crate) fn valence_authorization_grant_key(&self, grant_id: &str) -> Vec<u8> {
+        format!("authorization_grant:{}", grant_id).into_bytes()
+    }
+
+    pub(crate) fn valence_authorization_request_key(&self, request_id: &str) -> Vec<u8> {
+        format!("authorization_request:{}", request_id).into_bytes()
+    }
+
+    /// Valence record stored as JSON under `key` in the Valence state column family
+    fn get_valence_record<T: serde::de::DeserializeOwned>(&self, key: &[u8]) -> Result<Option<T>> {
+        match self.db.get_cf(self.cf_valence_state()?, key)? {
+            Some(data) => Ok(Some(serde_json::from_slice(&data)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Store a Valence record as JSON under `key` in the Valence state column family
+    fn put_valence_record<T: Serialize>(&self, key: &[u8], record: &T) -> Result<()> {
+        self.db.put_cf(self.cf_valence_state()?, key, serde_json::to_vec(record)?)?;
+        Ok(())
+    }
+
+    /// Valence records whose keys start with `prefix`
+    fn valence_records<T: serde::de::DeserializeOwned>(&self, prefix: &[u8]) -> Result<Vec<T>> {
+        let mut records = Vec::new();
+        for item in self.db.prefix_iterator_cf(self.cf_valence_state()?, prefix) {
+            let (key_bytes, value_bytes) = item.map_err(|e| Error::database(format!("RocksDB iterator error: {}", e)))?;
+            if !key_bytes.starts_with(prefix) {
+                break;
+            }
+            records.push(serde_json::from_slice(&value_bytes)?);
+        }
+        Ok(records)
+    }
+
     // --- General DB Helpers --- 
     pub fn get(&self, key: &Key) -> Result<Option<Vec<u8>>> {
         let cf = self.cf_handle_ref(&key.namespace)?;
@@ -1050,9 +1355,11 @@ impl RocksStorage {
         let cf = self.cf_events()?;
         
         if let Some(bytes) = self.db.get_cf(cf, key.to_bytes())? {
-            let event_data: EventData = bincode::deserialize(&bytes)
-                .map_err(|e| Error::generic(format!("Failed to deserialize event data: {}", e)))?;
-            Ok(Some(Box::new(event_data.to_mock_event())))
+            let mut event = EventData::from_bytes(&bytes)?.to_mock_event();
+            if let Some(decoded) = self.db.get_cf(self.cf_event_decoded()?, id.as_bytes())? {
+                event.decoded = Some(serde_json::from_slice(&decoded)?);
+            }
+            Ok(Some(Box::new(event)))
         } else {
             Ok(None)
         }
@@ -1115,9 +1422,46 @@ pub struct EventData {
     
     /// Raw event data
     pub raw_data: Vec<u8>,
+    
+    /// Position of the event within its block, if known
+    pub log_index: Option<u32>,
+}
+
+/// Event data written before events carried a log index
+#[derive(Deserialize)]
+struct LegacyEventData {
+    id: String,
+    chain: String,
+    block_number: u64,
+    block_hash: String,
+    tx_hash: String,
+    timestamp: u64,
+    event_type: String,
+    raw_data: Vec<u8>,
 }
 
 impl EventData {
+    /// Deserialize stored event data, including records without a log index
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if let Ok(event_data) = bincode::deserialize::<EventData>(bytes) {
+            return Ok(event_data);
+        }
+        
+        let legacy: LegacyEventData = bincode::deserialize(bytes)
+            .map_err(|e| Error::generic(format!("Failed to deserialize event data: {}", e)))?;
+        Ok(Self {
+            id: legacy.id,
+            chain: legacy.chain,
+            block_number: legacy.block_number,
+            block_hash: legacy.block_hash,
+            tx_hash: legacy.tx_hash,
+            timestamp: legacy.timestamp,
+            event_type: legacy.event_type,
+            raw_data: legacy.raw_data,
+            log_index: None,
+        })
+    }
+    
     /// Convert to a mock event implementation
     pub fn to_mock_event(&self) -> MockEvent {
         MockEvent {
@@ -1129,6 +1473,8 @@ impl EventData {
             timestamp: SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(self.timestamp),
             event_type: self.event_type.clone(),
             raw_data: self.raw_data.clone(),
+            log_index: self.log_index,
+            decoded: None,
         }
     }
 }
@@ -1144,6 +1490,8 @@ pub struct MockEvent {
     timestamp: SystemTime,
     event_type: String,
     raw_data: Vec<u8>,
+    log_index: Option<u32>,
+    decoded: Option<serde_json::Map<String, serde_json::Value>>,
 }
 
 impl Event for MockEvent {
@@ -1178,12 +1526,63 @@ impl Event for MockEvent {
     fn raw_data(&self) -> &[u8] {
         &self.raw_data
     }
+    
+    fn log_index(&self) -> Option<u32> {
+        self.log_index
+    }
+
+    fn decoded(&self) -> Option<&serde_json::Map<String, serde_json::Value>> {
+        self.decoded.as_ref()
+    }
 
     fn as_any(&self) -> &dyn Any {
         self
     }
 }
 
+/// Prefix of the `event_order` keys of a chain
+///
+/// Chain names never contain a NUL byte, so the separator keeps chains that
+/// share a prefix ordered the same way as their names.
+fn event_order_chain_prefix(chain: &str) -> Vec<u8> {
+    let mut key = chain.as_bytes().to_vec();
+    key.push(0);
+    key
+}
+
+/// Key of an event in the `event_order` column family
+///
+/// The log index is stored one higher, with zero for events without one so
+/// that they come first in their block. Transaction hashes never contain a
+/// NUL byte either.
+fn event_order_key(position: &EventPosition) -> Vec<u8> {
+    let mut key = event_order_chain_prefix(&position.chain);
+    key.extend_from_slice(&position.block_number.to_be_bytes());
+    key.extend_from_slice(&position.log_index.map_or(0, |index| u64::from(index) + 1).to_be_bytes());
+    key.extend_from_slice(position.tx_hash.as_bytes());
+    key.push(0);
+    key.extend_from_slice(position.id.as_bytes());
+    key
+}
+
+/// Position encoded in an `event_order` key
+fn event_order_position(key: &[u8]) -> Result<EventPosition> {
+    let invalid = || Error::storage("Invalid event order key");
+    let chain_end = key.iter().position(|&byte| byte == 0).ok_or_else(invalid)?;
+    let numbers = key.get(chain_end + 1..chain_end + 17).ok_or_else(invalid)?;
+    let rest = &key[chain_end + 17..];
+    let tx_end = rest.iter().position(|&byte| byte == 0).ok_or_else(invalid)?;
+    let log_index = u64::from_be_bytes(numbers[8..].try_into().map_err(|_| invalid())?);
+    
+    Ok(EventPosition {
+        chain: string_from_utf8(key[..chain_end].to_vec())?,
+        block_number: u64::from_be_bytes(numbers[..8].try_into().map_err(|_| invalid())?),
+        log_index: log_index.checked_sub(1).map(|index| u32::try_from(index).map_err(|_| invalid())).transpose()?,
+        tx_hash: string_from_utf8(rest[..tx_end].to_vec())?,
+        id: string_from_utf8(rest[tx_end + 1..].to_vec())?,
+    })
+}
+
 /// A wrapper around WriteBatch that works with our Key type
 pub struct KeyBatch {
     batch: WriteBatch,
@@ -0,0 +1,250 @@
+/// Event service serving indexed events from storage
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures::FutureExt;
+use indexer_core::event::{Event, UnifiedEvent};
+use indexer_core::reorg::{BoxedReorgDetector, ReorgEvent, ReorgSubscription};
+use indexer_core::service::{EventService, EventSubscription};
+use indexer_core::types::{ChainId, EventFilter, EventPage, EventPosition};
+use indexer_core::{BlockStatus, Result};
+
+use crate::BoxedStorage;
+
+/// Interval at which subscriptions poll storage for new events
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Events read from storage per query of `get_events` and subscriptions
+const BATCH_SIZE: usize = 1000;
+
+/// Event service answering queries from the events indexed in storage
+///
+/// Filters and keyset pages are pushed down to the storage backend instead
+/// of being applied to every stored event in memory.
+pub struct StorageEventService {
+    /// Chains subscriptions deliver the events of; the first is reported as
+    /// the service's own
+    chain_ids: Vec<ChainId>,
+
+    /// Storage holding the indexed events
+    storage: BoxedStorage,
+
+    /// Interval at which subscriptions poll for new events
+    poll_interval: Duration,
+
+    /// Detectors of the reorgs subscriptions rewind on
+    reorg_detectors: Vec<BoxedReorgDetector>,
+}
+
+impl StorageEventService {
+    /// Create a service for the events of a chain in storage
+    pub fn new(chain_id: impl Into<ChainId>, storage: BoxedStorage) -> Self {
+        Self {
+            chain_ids: vec![chain_id.into()],
+            storage,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            reorg_detectors: Vec::new(),
+        }
+    }
+
+    /// Rewind subscriptions to the fork block of the reorgs `detector`
+    /// announces, so the events replacing rolled back ones are delivered
+    ///
+    /// The detector has to announce reorgs once storage is rolled back, as
+    /// the [`crate::ingest::EventIngestor`] does.
+    pub fn with_reorg_detector(mut self, detector: BoxedReorgDetector) -> Self {
+        self.reorg_detectors.push(detector);
+        self
+    }
+
+    /// Also deliver the events of another chain to subscriptions
+    pub fn with_chain(mut self, chain_id: impl Into<ChainId>) -> Self {
+        let chain_id = chain_id.into();
+        if !self.chain_ids.contains(&chain_id) {
+            self.chain_ids.push(chain_id);
+        }
+        self
+    }
+
+    /// Set the interval at which subscriptions poll for new events
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Cursor past the last event stored for a chain, so only events stored
+    /// after the subscription was made are delivered
+    async fn chain_cursor(&self, chain_id: &ChainId) -> Result<ChainCursor> {
+        let mut filter = EventFilter::new();
+        filter.chain = Some(chain_id.0.clone());
+
+        let tail = EventPage { limit: 1, from_end: true, ..Default::default() };
+        let after = self.storage.get_events_page(&filter, &tail).await?
+            .pop()
+            .map(|event| EventPosition::of(event.as_ref()));
+        Ok(ChainCursor { filter, after })
+    }
+}
+
+#[async_trait]
+impl EventService for StorageEventService {
+    type EventType = UnifiedEvent;
+
+    fn chain_id(&self) -> &ChainId {
+        &self.chain_ids[0]
+    }
+
+    fn chain_ids(&self) -> Vec<ChainId> {
+        self.chain_ids.clone()
+    }
+
+    async fn get_events(&self, filters: Vec<EventFilter>) -> Result<Vec<Box<dyn Event>>> {
+        let mut events = Vec::new();
+        for filter in filters {
+            let offset = filter.offset.unwrap_or(0);
+            let page = EventPage {
+                limit: filter.limit.unwrap_or(BATCH_SIZE).saturating_add(offset),
+                ..Default::default()
+            };
+            events.extend(self.storage.get_events_page(&filter, &page).await?.into_iter().skip(offset));
+        }
+
+        Ok(events)
+    }
+
+    async fn get_events_page(&self, filter: EventFilter, page: EventPage) -> Result<Vec<Box<dyn Event>>> {
+        self.storage.get_events_page(&filter, &page).await
+    }
+
+    async fn count_events(&self, filter: EventFilter) -> Result<Option<u64>> {
+        self.storage.count_events(&filter).await
+    }
+
+    /// Subscribe to the events of every chain of the service
+    ///
+    /// Each chain is paged with its own cursor, so events stored for one
+    /// chain are delivered however far the others are ahead.
+    async fn subscribe(&self) -> Result<Box<dyn EventSubscription>> {
+        let mut cursors = Vec::new();
+        for chain_id in &self.chain_ids {
+            cursors.push(self.chain_cursor(chain_id).await?);
+        }
+
+        let mut reorgs = Vec::new();
+        for detector in &self.reorg_detectors {
+            reorgs.push(detector.subscribe().await?);
+        }
+
+        Ok(Box::new(StorageSubscription {
+            storage: self.storage.clone(),
+            cursors,
+            reorgs,
+            pending: VecDeque::new(),
+            poll_interval: self.poll_interval,
+            closed: false,
+        }))
+    }
+
+    async fn get_latest_block(&self) -> Result<u64> {
+        self.storage.get_latest_block(&self.chain_id().0).await
+    }
+
+    /// The latest block of a chain is its head; other statuses are the
+    /// highest block storage recorded with that status
+    async fn get_latest_block_with_status(&self, chain: &str, status: BlockStatus) -> Result<u64> {
+        match status {
+            BlockStatus::Latest => self.storage.get_latest_block(chain).await,
+            status => self.storage.get_latest_block_with_status(chain, status).await,
+        }
+    }
+}
+
+/// Filter of the events of one chain and the position of the last one
+/// delivered
+struct ChainCursor {
+    filter: EventFilter,
+    after: Option<EventPosition>,
+}
+
+/// Subscription polling storage for events past the last one delivered on
+/// each chain
+struct StorageSubscription {
+    storage: BoxedStorage,
+    cursors: Vec<ChainCursor>,
+    reorgs: Vec<Box<dyn ReorgSubscription>>,
+    pending: VecDeque<Box<dyn Event>>,
+    poll_interval: Duration,
+    closed: bool,
+}
+
+#[async_trait]
+impl EventSubscription for StorageSubscription {
+    async fn next(&mut self) -> Option<Box<dyn Event>> {
+        while !self.closed {
+            self.rewind_reorged();
+            if let Some(event) = self.pending.pop_front() {
+                return Some(event);
+            }
+
+            for cursor in &mut self.cursors {
+                let page = EventPage {
+                    after: cursor.after.clone(),
+                    limit: BATCH_SIZE,
+                    ..Default::default()
+                };
+                match self.storage.get_events_page(&cursor.filter, &page).await {
+                    Ok(events) => {
+                        if let Some(last) = events.last() {
+                            cursor.after = Some(EventPosition::of(last.as_ref()));
+                        }
+                        self.pending.extend(events);
+                    }
+                    Err(e) => tracing::warn!("Failed to poll storage for events: {}", e),
+                }
+            }
+            if self.pending.is_empty() {
+                tokio::time::sleep(self.poll_interval).await;
+            }
+        }
+
+        None
+    }
+
+    async fn close(&mut self) -> Result<()> {
+        self.closed = true;
+        self.pending.clear();
+        Ok(())
+    }
+}
+
+impl StorageSubscription {
+    /// Rewind to the fork block of the reorgs announced since the last poll,
+    /// dropping the pending events rolled back
+    fn rewind_reorged(&mut self) {
+        let mut reorgs = Vec::new();
+        for subscription in &mut self.reorgs {
+            while let Some(Some(reorg)) = subscription.next().now_or_never() {
+                reorgs.push(reorg);
+            }
+        }
+        for reorg in reorgs {
+            self.rewind(&reorg);
+        }
+    }
+
+    /// Rewind to the start of the first block a reorg rolled back
+    fn rewind(&mut self, reorg: &ReorgEvent) {
+        let from_block = match reorg.reorganized_blocks.iter().map(|b| b.old_block.number).min() {
+            Some(from_block) => from_block,
+            None => return,
+        };
+        let fork = EventPosition::block_start(&reorg.chain_id.0, from_block);
+        self.pending.retain(|event| event.chain() != fork.chain || event.block_number() < from_block);
+        for cursor in &mut self.cursors {
+            if cursor.after.as_ref().is_some_and(|after| after.chain == fork.chain && *after >= fork) {
+                cursor.after = Some(fork.clone());
+            }
+        }
+    }
+}
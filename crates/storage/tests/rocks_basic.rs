@@ -151,4 +151,99 @@ async fn test_rocks_multiple_events() -> Result<()> {
     }
     
     Ok(())
-} 
\ No newline at end of file
+} 
+#[tokio::test]
+async fn test_rocks_valence_authorizations() -> Result<()> {
+    use indexer_storage::{
+        ValenceAuthorizationDecision, ValenceAuthorizationGrant, ValenceAuthorizationInfo,
+        ValenceAuthorizationRequest,
+    };
+
+    // Create a temporary directory for RocksDB
+    let temp_dir = env::temp_dir().join("rocks_valence_authorization_test");
+    if temp_dir.exists() {
+        fs::remove_dir_all(&temp_dir)?;
+    }
+    fs::create_dir_all(&temp_dir)?;
+
+    let rocks = RocksStorage::new(RocksConfig {
+        path: temp_dir.to_str().unwrap().to_string(),
+        create_if_missing: true,
+        cache_size_mb: 64,
+    })?;
+    let auth_id = "neutron-1:auth";
+
+    rocks.store_valence_authorization_instantiation(ValenceAuthorizationInfo {
+        id: auth_id.to_string(),
+        chain_id: "neutron-1".to_string(),
+        contract_address: "neutron1auth".to_string(),
+        created_at_block: 10,
+        created_at_tx: "0x10".to_string(),
+        current_owner: Some("owner".to_string()),
+        active_policy_id: None,
+        last_updated_block: 10,
+        last_updated_tx: "0x10".to_string(),
+    }, None).await?;
+    for (id, block) in [("g1", 11), ("g2", 12)] {
+        rocks.store_valence_authorization_grant(ValenceAuthorizationGrant {
+            id: id.to_string(),
+            auth_id: auth_id.to_string(),
+            grantee: "alice".to_string(),
+            permissions: vec!["execute".to_string()],
+            resources: vec![format!("vault-{}", id)],
+            granted_at_block: block,
+            granted_at_tx: format!("0x{}", block),
+            expiry: None,
+            is_active: true,
+            revoked_at_block: None,
+            revoked_at_tx: None,
+        }).await?;
+    }
+    rocks.update_active_authorization_policy(auth_id, "policy-2", 13, "0x13").await?;
+    rocks.revoke_valence_authorization_grant(auth_id, "alice", "vault-g1", 14, "0x14").await?;
+
+    let state = rocks.get_valence_authorization_state(auth_id).await?.expect("authorization state");
+    assert_eq!(state.active_policy_id.as_deref(), Some("policy-2"));
+    assert_eq!(state.last_update_block, 13);
+    let active: Vec<&str> = state.active_grants.iter().map(|grant| grant.id.as_str()).collect();
+    assert_eq!(active, ["g2"]);
+    let all = rocks.get_valence_authorization_grants(auth_id, false).await?;
+    assert_eq!(all.len(), 2);
+    assert_eq!(all[1].revoked_at_block, Some(14));
+    assert!(rocks.get_valence_authorization_state("other").await?.is_none());
+
+    for (id, block) in [("r1", 15), ("r2", 16)] {
+        rocks.store_valence_authorization_request(ValenceAuthorizationRequest {
+            id: id.to_string(),
+            auth_id: auth_id.to_string(),
+            requester: "alice".to_string(),
+            action: "withdraw".to_string(),
+            resource: "vault-g2".to_string(),
+            request_data: None,
+            decision: ValenceAuthorizationDecision::Pending,
+            requested_at_block: block,
+            requested_at_tx: format!("0x{}", block),
+            processed_at_block: None,
+            processed_at_tx: None,
+            reason: None,
+        }).await?;
+    }
+    rocks.update_valence_authorization_request_decision(
+        "r1", ValenceAuthorizationDecision::Approved, Some(17), Some("0x17"), None,
+    ).await?;
+
+    let requests = rocks.get_valence_authorization_requests(auth_id, None, None, None).await?;
+    let ids: Vec<&str> = requests.iter().map(|request| request.id.as_str()).collect();
+    assert_eq!(ids, ["r2", "r1"]);
+    let approved = rocks.get_valence_authorization_requests(auth_id, Some(ValenceAuthorizationDecision::Approved), None, None).await?;
+    assert_eq!(approved.len(), 1);
+    assert_eq!(approved[0].processed_at_block, Some(17));
+    assert_eq!(rocks.get_valence_authorization_requests(auth_id, None, Some(1), Some(1)).await?[0].id, "r1");
+
+    // Clean up
+    if temp_dir.exists() {
+        fs::remove_dir_all(&temp_dir)?;
+    }
+
+    Ok(())
+}
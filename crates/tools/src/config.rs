@@ -93,6 +93,22 @@ pub struct ApiConfig {
     /// Rate limiting: requests per minute
     pub rate_limit: Option<u32>,
     
+    /// Maximum nesting depth of GraphQL queries
+    #[serde(default)]
+    pub graphql_max_depth: Option<usize>,
+    
+    /// Maximum complexity of a GraphQL query
+    #[serde(default)]
+    pub graphql_max_complexity: Option<usize>,
+    
+    /// Directory of persisted GraphQL queries, one `.graphql` file per query
+    #[serde(default)]
+    pub graphql_persisted_queries: Option<String>,
+    
+    /// Only execute persisted GraphQL queries
+    #[serde(default)]
+    pub graphql_persisted_queries_only: bool,
+    
     /// Enable API key authentication
     pub auth_enabled: bool,
     
@@ -113,6 +129,10 @@ impl Default for ApiConfig {
             request_timeout: 30,
             max_request_size: 1024 * 1024, // 1MB
             rate_limit: Some(100),
+            graphql_max_depth: None,
+            graphql_max_complexity: None,
+            graphql_persisted_queries: None,
+            graphql_persisted_queries_only: false,
             auth_enabled: false,
             jwt_secret: None,
             api_key: None,
@@ -158,6 +178,11 @@ pub struct ChainConfig {
     
     /// Event signatures to filter
     pub event_signatures: Vec<String>,
+    
+    /// Upgradeable proxy contracts whose logs are decoded with the schema of
+    /// the implementation active when they were emitted
+    #[serde(default)]
+    pub proxy_addresses: Vec<String>,
 }
 
 impl Default for ChainConfig {
@@ -175,6 +200,7 @@ impl Default for ChainConfig {
             index_transactions: false,
             contract_addresses: HashMap::new(),
             event_signatures: Vec::new(),
+            proxy_addresses: Vec::new(),
         }
     }
 }